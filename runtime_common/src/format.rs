@@ -0,0 +1,141 @@
+// パス: runtime_common/src/format.rs
+// 役割: `format` の書式文字列を解釈し、引数を埋め込んだ文字列を組み立てる
+// 意図: 型検査・評価器・ネイティブランタイムで同じ書式規則を共有する
+// 関連ファイル: runtime_common/src/show.rs, runtime_native/src/format.rs, src/infer.rs, src/runtime.rs
+//! 書式文字列
+//!
+//! - `%d` は `Int`、`%f` は `Double`、`%s` は `String` を 1 つ埋め込み、`%%` は `%` そのものを表す。
+//! - `%.3f` のように精度を付けると小数点以下の桁数を固定する。精度がなければ `show` と同じ表示になる。
+//! - それ以外の `%` の使い方は書式エラーとする。
+
+use crate::show::format_double;
+
+/// 引数を 1 つ埋め込む指定子。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FormatSpec {
+    /// `%d`
+    Int,
+    /// `%f` / `%.Nf`。`precision` は小数点以下の桁数。
+    Double { precision: Option<usize> },
+    /// `%s`
+    Str,
+}
+
+impl FormatSpec {
+    /// 指定子が受け取る引数の型名。
+    pub fn type_name(self) -> &'static str {
+        match self {
+            FormatSpec::Int => "Int",
+            FormatSpec::Double { .. } => "Double",
+            FormatSpec::Str => "String",
+        }
+    }
+}
+
+/// 書式文字列を分解した断片。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FormatPiece {
+    Text(String),
+    Arg(FormatSpec),
+}
+
+/// `format` に渡す引数。
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FormatArg<'a> {
+    Int(i64),
+    Double(f64),
+    Str(&'a str),
+}
+
+/// 書式文字列を断片へ分解する。解釈できない `%` があればその説明を返す。
+pub fn parse_format(template: &str) -> Result<Vec<FormatPiece>, String> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            text.push(c);
+            continue;
+        }
+        let precision = if chars.peek() == Some(&'.') {
+            chars.next();
+            let mut digits = String::new();
+            while let Some(d) = chars.next_if(char::is_ascii_digit) {
+                digits.push(d);
+            }
+            Some(
+                digits
+                    .parse::<usize>()
+                    .map_err(|_| "`%.` の後には桁数が必要です".to_string())?,
+            )
+        } else {
+            None
+        };
+        let spec = match (chars.next(), precision) {
+            (Some('%'), None) => {
+                text.push('%');
+                continue;
+            }
+            (Some('d'), None) => FormatSpec::Int,
+            (Some('s'), None) => FormatSpec::Str,
+            (Some('f'), precision) => FormatSpec::Double { precision },
+            (Some(other), _) => return Err(format!("未対応の書式指定子です: %{other}")),
+            (None, _) => return Err("書式文字列が `%` で終わっています".into()),
+        };
+        if !text.is_empty() {
+            pieces.push(FormatPiece::Text(std::mem::take(&mut text)));
+        }
+        pieces.push(FormatPiece::Arg(spec));
+    }
+    if !text.is_empty() {
+        pieces.push(FormatPiece::Text(text));
+    }
+    Ok(pieces)
+}
+
+/// 書式が受け取る引数の指定子を順に返す。
+pub fn format_specs(pieces: &[FormatPiece]) -> Vec<FormatSpec> {
+    pieces
+        .iter()
+        .filter_map(|piece| match piece {
+            FormatPiece::Arg(spec) => Some(*spec),
+            FormatPiece::Text(_) => None,
+        })
+        .collect()
+}
+
+/// 断片へ引数を埋め込む。引数の数や種類が指定子と合わなければ説明を返す。
+pub fn render_format(pieces: &[FormatPiece], args: &[FormatArg<'_>]) -> Result<String, String> {
+    let mut out = String::new();
+    let mut args = args.iter();
+    for piece in pieces {
+        let spec = match piece {
+            FormatPiece::Text(text) => {
+                out.push_str(text);
+                continue;
+            }
+            FormatPiece::Arg(spec) => spec,
+        };
+        match (spec, args.next()) {
+            (FormatSpec::Int, Some(FormatArg::Int(n))) => out.push_str(&n.to_string()),
+            (FormatSpec::Double { precision: None }, Some(FormatArg::Double(d))) => {
+                out.push_str(&format_double(*d))
+            }
+            (FormatSpec::Double { precision: Some(p) }, Some(FormatArg::Double(d))) => {
+                out.push_str(&format!("{d:.p$}"))
+            }
+            (FormatSpec::Str, Some(FormatArg::Str(s))) => out.push_str(s),
+            (spec, Some(_)) => {
+                return Err(format!(
+                    "{} を受け取る指定子に別の型の値が渡されました",
+                    spec.type_name()
+                ))
+            }
+            (_, None) => return Err("書式の指定子に対して引数が足りません".into()),
+        }
+    }
+    if args.next().is_some() {
+        return Err("書式の指定子より引数が多すぎます".into());
+    }
+    Ok(out)
+}
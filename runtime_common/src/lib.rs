@@ -0,0 +1,13 @@
+//! TypeLang runtime common crate
+//!
+//! 評価器（コンパイラ crate）とネイティブランタイムの両方が使う、表示・書式・数値読み取りの
+//! 規則をまとめた小さな crate。ネイティブランタイムの静的ライブラリをコンパイラへリンクせずに
+//! 同じ結果を得るため、FFI を含まない純粋な関数だけを置く。
+
+mod format;
+mod number;
+mod show;
+
+pub use format::*;
+pub use number::*;
+pub use show::*;
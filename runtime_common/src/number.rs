@@ -0,0 +1,34 @@
+// パス: runtime_common/src/number.rs
+// 役割: 数値の丸めと文字列からの読み取りの規則を提供する
+// 意図: 評価器の `round` / `readInt` / `readDouble` とネイティブランタイムで結果を一致させる
+// 関連ファイル: runtime_native/src/math.rs, runtime_native/src/read.rs, src/runtime.rs
+//! 数値の丸めと読み取り
+//!
+//! - `round` はちょうど中間の値を偶数側へ丸める。
+//! - 読み取りは前後の空白を無視し、残りを Rust の `parse` で読む（`" 42 "` は 42、`"+7"` は 7）。
+//!   空文字列や数値として読めない形式は失敗とする。
+
+use std::str::FromStr;
+
+/// Haskell の `round` と同じく、ちょうど中間の値は偶数側へ丸める。
+pub fn round_half_even(value: f64) -> f64 {
+    if (value - value.trunc()).abs() == 0.5 {
+        2.0 * (value / 2.0).round()
+    } else {
+        value.round()
+    }
+}
+
+/// 前後の空白を除いて `Int` として読む。
+pub fn read_int(text: &str) -> Option<i64> {
+    read_trimmed(text)
+}
+
+/// 前後の空白を除いて `Double` として読む（`1.5e3`・`inf`・`NaN` なども受け付ける）。
+pub fn read_double(text: &str) -> Option<f64> {
+    read_trimmed(text)
+}
+
+fn read_trimmed<T: FromStr>(text: &str) -> Option<T> {
+    text.trim().parse().ok()
+}
@@ -0,0 +1,65 @@
+// パス: runtime_common/src/show.rs
+// 役割: Double を Haskell の `show` に揃えた文字列へ整形する共通ルーチンを提供する
+// 意図: 評価器とネイティブランタイムで浮動小数点の表示結果を一致させる
+// 関連ファイル: runtime_native/src/value.rs, src/runtime.rs, src/repl/printer.rs, runtime_common/src/format.rs
+
+/// Double の表示で科学表記へ切り替える絶対値の境界。
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DoubleFormat {
+    /// 絶対値がこの値未満なら科学表記にする。
+    pub sci_below: f64,
+    /// 絶対値がこの値以上なら科学表記にする。
+    pub sci_from: f64,
+}
+
+impl Default for DoubleFormat {
+    /// Haskell の `show` と同じく `0.1 <= |x| < 10^7` を固定小数で表示する。
+    fn default() -> Self {
+        Self {
+            sci_below: 0.1,
+            sci_from: 1e7,
+        }
+    }
+}
+
+/// 既定の書式で Double を文字列化する。
+pub fn format_double(value: f64) -> String {
+    format_double_with(value, &DoubleFormat::default())
+}
+
+/// 指定した境界で Double を文字列化する。
+///
+/// 整数値にも必ず小数点を付け (`1.0`)、`Infinity` / `-Infinity` / `NaN` / `-0.0` を区別する。
+pub fn format_double_with(value: f64, format: &DoubleFormat) -> String {
+    if value.is_nan() {
+        return "NaN".into();
+    }
+    if value.is_infinite() {
+        return if value > 0.0 { "Infinity" } else { "-Infinity" }.into();
+    }
+    if value == 0.0 {
+        return if value.is_sign_negative() {
+            "-0.0"
+        } else {
+            "0.0"
+        }
+        .into();
+    }
+    let magnitude = value.abs();
+    if magnitude < format.sci_below || magnitude >= format.sci_from {
+        let rendered = format!("{:e}", value);
+        let (mantissa, exponent) = rendered.split_once('e').unwrap_or((&rendered, "0"));
+        if mantissa.contains('.') {
+            format!("{mantissa}e{exponent}")
+        } else {
+            format!("{mantissa}.0e{exponent}")
+        }
+    } else {
+        let rendered = value.to_string();
+        if rendered.contains('.') {
+            rendered
+        } else {
+            format!("{rendered}.0")
+        }
+    }
+}
@@ -0,0 +1,75 @@
+// パス: runtime_common/tests/common.rs
+// 役割: runtime_common の表示・書式・数値読み取りの規則を検証する
+// 意図: 評価器とネイティブランタイムが共有する結果を 1 か所で固定する
+// 関連ファイル: runtime_common/src/show.rs, runtime_common/src/format.rs, runtime_common/src/number.rs
+
+use runtime_common::{
+    format_double, format_double_with, format_specs, parse_format, read_double, read_int,
+    render_format, round_half_even, DoubleFormat, FormatArg, FormatSpec,
+};
+
+#[test]
+fn double_formatting_matches_haskell_show() {
+    let cases = [
+        (1.0, "1.0"),
+        (-2.0, "-2.0"),
+        (0.5, "0.5"),
+        (0.1, "0.1"),
+        (1234567.5, "1234567.5"),
+        (1e7, "1.0e7"),
+        (12345678.9, "1.23456789e7"),
+        (0.05, "5.0e-2"),
+        (-0.0125, "-1.25e-2"),
+        (0.0, "0.0"),
+        (-0.0, "-0.0"),
+        (f64::INFINITY, "Infinity"),
+        (f64::NEG_INFINITY, "-Infinity"),
+        (f64::NAN, "NaN"),
+    ];
+    for (value, expected) in cases {
+        assert_eq!(format_double(value), expected, "format of {value:?}");
+    }
+
+    let wide = DoubleFormat {
+        sci_below: 1e-3,
+        sci_from: 1e9,
+    };
+    assert_eq!(format_double_with(0.05, &wide), "0.05");
+    assert_eq!(format_double_with(1e7, &wide), "10000000.0");
+}
+
+#[test]
+fn format_fills_specifiers_in_order() {
+    let pieces = parse_format("%d%% of %s is %.2f").expect("valid template");
+    assert_eq!(
+        format_specs(&pieces),
+        [
+            FormatSpec::Int,
+            FormatSpec::Str,
+            FormatSpec::Double { precision: Some(2) }
+        ]
+    );
+    let args = [
+        FormatArg::Int(50),
+        FormatArg::Str("3"),
+        FormatArg::Double(1.5),
+    ];
+    assert_eq!(
+        render_format(&pieces, &args).as_deref(),
+        Ok("50% of 3 is 1.50")
+    );
+    assert!(render_format(&pieces, &args[..2]).is_err());
+    assert!(parse_format("%x").is_err());
+    assert!(parse_format("100%").is_err());
+}
+
+#[test]
+fn numbers_round_half_to_even_and_read_trimmed_text() {
+    assert_eq!(round_half_even(2.5), 2.0);
+    assert_eq!(round_half_even(-3.5), -4.0);
+    assert_eq!(read_int("\t-12\n"), Some(-12));
+    assert_eq!(read_int("1 2"), None);
+    assert_eq!(read_int(""), None);
+    assert_eq!(read_double(" 2.5 "), Some(2.5));
+    assert_eq!(read_double("x"), None);
+}
@@ -0,0 +1,98 @@
+// パス: runtime_native/src/arena.rs
+// 役割: 短命なデータ値・リスト節点を確保するスレッドローカルなバンプアロケータを提供する
+// 意図: `arena` フィーチャ有効時に個別の確保・解放を避け、区切りでまとめて破棄できるようにする
+// 関連ファイル: runtime_native/src/data.rs, runtime_native/src/list.rs, src/codegen/cranelift.rs
+//! バンプアリーナ
+//!
+//! - 確保はチャンク内のオフセットを進めるだけで行い、個別の解放はしない。
+//! - `tl_arena_reset` で先頭チャンクだけを残して全体を巻き戻す。以後、それまでに確保した値は無効になる。
+//! - `arena` フィーチャが無効なビルドでは確保に使われず、`tl_arena_reset` は何もしない。
+
+#![cfg_attr(not(feature = "arena"), allow(dead_code))]
+
+use std::alloc::Layout;
+use std::cell::RefCell;
+use std::mem::MaybeUninit;
+
+/// 1 チャンクの既定サイズ。これより大きい確保は専用のチャンクを用意する。
+const CHUNK_SIZE: usize = 64 * 1024;
+
+struct Arena {
+    chunks: Vec<Box<[MaybeUninit<u8>]>>,
+    /// 最後のチャンク内で次に確保を始める位置。
+    offset: usize,
+}
+
+impl Arena {
+    const fn new() -> Self {
+        Self {
+            chunks: Vec::new(),
+            offset: 0,
+        }
+    }
+
+    fn alloc_layout(&mut self, layout: Layout) -> *mut u8 {
+        if let Some(ptr) = self.bump(layout) {
+            return ptr;
+        }
+        let size = CHUNK_SIZE.max(layout.size() + layout.align());
+        self.chunks
+            .push(vec![MaybeUninit::uninit(); size].into_boxed_slice());
+        self.offset = 0;
+        self.bump(layout)
+            .expect("a fresh chunk always fits the requested layout")
+    }
+
+    fn bump(&mut self, layout: Layout) -> Option<*mut u8> {
+        let chunk = self.chunks.last_mut()?;
+        let base = chunk.as_mut_ptr() as usize;
+        let align = layout.align();
+        let start = ((base + self.offset + align - 1) & !(align - 1)) - base;
+        let end = start.checked_add(layout.size())?;
+        if end > chunk.len() {
+            return None;
+        }
+        self.offset = end;
+        Some(chunk[start..].as_mut_ptr().cast())
+    }
+
+    fn reset(&mut self) {
+        self.chunks.truncate(1);
+        self.offset = 0;
+    }
+}
+
+thread_local! {
+    static ARENA: RefCell<Arena> = const { RefCell::new(Arena::new()) };
+}
+
+/// `value` をアリーナへ移して、そのアドレスを返す。`Drop` は呼ばれない。
+pub(crate) fn alloc<T>(value: T) -> *mut T {
+    let ptr = ARENA
+        .with(|arena| arena.borrow_mut().alloc_layout(Layout::new::<T>()))
+        .cast::<T>();
+    // SAFETY: `T` のレイアウトに合わせて確保した未使用領域へ書き込む。
+    unsafe { ptr.write(value) };
+    ptr
+}
+
+/// `items` をアリーナへ複製し、先頭アドレスを返す。
+pub(crate) fn alloc_slice<T: Copy>(items: &[T]) -> *mut T {
+    let layout = Layout::array::<T>(items.len()).expect("slice layout overflow");
+    let ptr = ARENA
+        .with(|arena| arena.borrow_mut().alloc_layout(layout))
+        .cast::<T>();
+    // SAFETY: `items.len()` 個分を確保済みで、元のスライスとは重ならない。
+    unsafe { std::ptr::copy_nonoverlapping(items.as_ptr(), ptr, items.len()) };
+    ptr
+}
+
+/// 現在のスレッドのアリーナを巻き戻し、確保済みの値をまとめて破棄する。
+///
+/// # Safety
+/// 呼び出し以前に `tl_data_pack` / `tl_list_cons` などで得た値は以後参照してはならない。
+#[no_mangle]
+pub unsafe extern "C" fn tl_arena_reset() {
+    #[cfg(feature = "arena")]
+    ARENA.with(|arena| arena.borrow_mut().reset());
+}
@@ -0,0 +1,146 @@
+// パス: runtime_native/src/array.rs
+// 役割: 要素を連続領域に並べた配列 (TlArray) の構築と添字アクセスを提供する
+// 意図: リストでは O(n) になる添字アクセスを O(1) で行えるようにし、数値計算のコードを速くする
+// 関連ファイル: runtime_native/src/list.rs, runtime_native/src/error.rs, src/codegen/cranelift.rs
+
+use std::ffi::c_int;
+
+use crate::error::{set_last_error, tl_abort_with_message, TlRuntimeError, TlStatus};
+use crate::list::{list_items, TlListNode};
+use crate::value::TlValue;
+
+const TL_ARRAY_MAGIC: u64 = 0x544C5F4152524131; // "TL_ARRA1"
+
+/// `tl_array_get` に範囲外の添字が渡されたときの中断コード。
+pub const TL_ABORT_ARRAY_INDEX_OUT_OF_RANGE: c_int = 2005;
+
+#[repr(C)]
+pub struct TlArray {
+    magic: u64,
+    len: usize,
+    items: *mut TlValue,
+}
+
+impl TlArray {
+    fn new(items: &[TlValue]) -> *mut TlArray {
+        let ptr_items = if items.is_empty() {
+            std::ptr::null_mut()
+        } else if cfg!(feature = "arena") {
+            crate::arena::alloc_slice(items)
+        } else {
+            let mut boxed_items = items.to_vec();
+            boxed_items.shrink_to_fit();
+            let ptr = boxed_items.as_mut_ptr();
+            std::mem::forget(boxed_items);
+            ptr
+        };
+
+        let array = TlArray {
+            magic: TL_ARRAY_MAGIC,
+            len: items.len(),
+            items: ptr_items,
+        };
+
+        if cfg!(feature = "arena") {
+            return crate::arena::alloc(array);
+        }
+        Box::into_raw(Box::new(array))
+    }
+
+    unsafe fn ensure<'a>(ptr: *const TlArray) -> Result<&'a TlArray, TlRuntimeError> {
+        match ptr.as_ref() {
+            None => Err(TlRuntimeError::NullPointer),
+            Some(array) if array.magic != TL_ARRAY_MAGIC => {
+                Err(TlRuntimeError::InvalidArgument("invalid TlArray handle"))
+            }
+            Some(array) => Ok(array),
+        }
+    }
+}
+
+/// `items` から `len` 個の要素を複製して配列を作る。
+#[no_mangle]
+pub unsafe extern "C" fn tl_array_new(items: *const TlValue, len: usize) -> *mut TlArray {
+    if len == 0 {
+        return TlArray::new(&[]);
+    }
+    if items.is_null() {
+        set_last_error(TlStatus::NullPointer);
+        return std::ptr::null_mut();
+    }
+    set_last_error(TlStatus::Ok);
+    TlArray::new(std::slice::from_raw_parts(items, len))
+}
+
+/// リストの要素を先頭から順に並べた配列を作る。`fromList` の実体。
+#[no_mangle]
+pub unsafe extern "C" fn tl_array_from_list(list: *const TlListNode) -> *mut TlArray {
+    match list_items(list) {
+        Some(items) => {
+            set_last_error(TlStatus::Ok);
+            TlArray::new(&items)
+        }
+        None => {
+            set_last_error(TlStatus::InvalidArgument);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tl_array_len(array: *const TlArray) -> usize {
+    match TlArray::ensure(array) {
+        Ok(array) => {
+            set_last_error(TlStatus::Ok);
+            array.len
+        }
+        Err(err) => {
+            set_last_error(err.status());
+            0
+        }
+    }
+}
+
+/// `index` 番目の要素を取り出す。
+///
+/// `index` が負または要素数以上なら範囲外を読まずに
+/// `tl_abort_with_message` (code=2005) で中断する。
+#[no_mangle]
+pub unsafe extern "C" fn tl_array_get(array: *const TlArray, index: i64) -> TlValue {
+    match TlArray::ensure(array) {
+        Ok(array) => {
+            let Some(offset) = usize::try_from(index).ok().filter(|&i| i < array.len) else {
+                set_last_error(TlStatus::InvalidArgument);
+                eprintln!(
+                    "TypeLang native runtime: array index {index} is out of range (length {})",
+                    array.len
+                );
+                tl_abort_with_message(TL_ABORT_ARRAY_INDEX_OUT_OF_RANGE);
+            };
+            set_last_error(TlStatus::Ok);
+            *array.items.add(offset)
+        }
+        Err(err) => {
+            set_last_error(err.status());
+            TlValue::null()
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tl_array_free(array: *mut TlArray) {
+    // アリーナ上の値は `tl_arena_reset` でまとめて破棄する。
+    if cfg!(feature = "arena") {
+        return;
+    }
+    if let Ok(array_ref) = TlArray::ensure(array) {
+        if !array_ref.items.is_null() && array_ref.len > 0 {
+            drop(Vec::from_raw_parts(
+                array_ref.items,
+                array_ref.len,
+                array_ref.len,
+            ));
+        }
+        drop(Box::from_raw(array));
+    }
+}
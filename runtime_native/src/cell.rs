@@ -0,0 +1,64 @@
+// パス: runtime_native/src/cell.rs
+// 役割: 書き換え可能な参照セル (TlRef) の生成・読み出し・書き込みを提供する
+// 意図: TypeLang の `newRef`/`readRef`/`writeRef` をネイティブに実行し、状態を持つアルゴリズムを書けるようにする
+// 関連ファイル: runtime_native/src/value.rs, src/codegen/cranelift.rs
+
+use crate::error::{set_last_error, TlRuntimeError, TlStatus};
+use crate::value::TlValue;
+
+const TL_REF_MAGIC: u64 = 0x544C5F5245465F31; // "TL_REF_1"
+
+/// 箱詰めした値を 1 つだけ保持する可変スロット。
+///
+/// 書き込みはその場で行うため、同じセルを共有するすべての参照から変更が見える。
+/// セルはアリーナ機能の有無にかかわらずヒープに確保し、プログラム終了まで解放しない。
+#[repr(C)]
+pub struct TlRef {
+    magic: u64,
+    value: TlValue,
+}
+
+impl TlRef {
+    unsafe fn ensure<'a>(ptr: *mut TlRef) -> Result<&'a mut TlRef, TlRuntimeError> {
+        match ptr.as_mut() {
+            None => Err(TlRuntimeError::NullPointer),
+            Some(cell) if cell.magic != TL_REF_MAGIC => {
+                Err(TlRuntimeError::InvalidArgument("invalid TlRef handle"))
+            }
+            Some(cell) => Ok(cell),
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn tl_ref_new(value: TlValue) -> *mut TlRef {
+    Box::into_raw(Box::new(TlRef {
+        magic: TL_REF_MAGIC,
+        value,
+    }))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tl_ref_read(cell: *mut TlRef) -> TlValue {
+    match TlRef::ensure(cell) {
+        Ok(cell) => {
+            set_last_error(TlStatus::Ok);
+            cell.value
+        }
+        Err(err) => {
+            set_last_error(err.status());
+            TlValue::null()
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tl_ref_write(cell: *mut TlRef, value: TlValue) {
+    match TlRef::ensure(cell) {
+        Ok(cell) => {
+            set_last_error(TlStatus::Ok);
+            cell.value = value;
+        }
+        Err(err) => set_last_error(err.status()),
+    }
+}
@@ -0,0 +1,209 @@
+// パス: runtime_native/src/compare.rs
+// 役割: リスト・タプルなど構造を持つ値の等価比較と順序比較を提供する
+// 意図: `Eq`/`Ord` 辞書を Int/Double/Bool 以外の型でもネイティブに構築できるようにする
+// 関連ファイル: runtime_native/src/dict_fallback.rs, runtime_native/src/list.rs, runtime_native/src/data.rs, runtime_native/src/map.rs
+
+use std::cmp::Ordering;
+use std::ffi::c_void;
+use std::hash::{Hash, Hasher};
+
+use crate::data::{data_parts, is_data_handle, TlData};
+use crate::error::{set_last_error, TlStatus};
+use crate::list::{is_list_handle, list_items, TlListNode};
+use crate::value::{
+    tl_value_kind, tl_value_to_bool, tl_value_to_double, tl_value_to_int, tl_value_to_ptr,
+    tl_value_to_str, TlValue, TlValueKind,
+};
+
+/// ボックス化された 2 値を、値の種別タグに従って再帰的に比較する。
+///
+/// 種別が食い違う場合や NaN を含む場合は比較できないため `None` を返す。
+pub(crate) fn compare_values(lhs: TlValue, rhs: TlValue) -> Option<Ordering> {
+    let kinds = (tl_value_kind(lhs)?, tl_value_kind(rhs)?);
+    unsafe {
+        match kinds {
+            (TlValueKind::Int, TlValueKind::Int) => {
+                Some(tl_value_to_int(lhs).cmp(&tl_value_to_int(rhs)))
+            }
+            (TlValueKind::Double, TlValueKind::Double) => {
+                tl_value_to_double(lhs).partial_cmp(&tl_value_to_double(rhs))
+            }
+            (TlValueKind::Bool, TlValueKind::Bool) => {
+                Some((tl_value_to_bool(lhs) != 0).cmp(&(tl_value_to_bool(rhs) != 0)))
+            }
+            (TlValueKind::Str, TlValueKind::Str) => Some(str_bytes(lhs).cmp(str_bytes(rhs))),
+            (TlValueKind::Pointer, TlValueKind::Pointer) => {
+                compare_handles(tl_value_to_ptr(lhs), tl_value_to_ptr(rhs))
+            }
+            _ => None,
+        }
+    }
+}
+
+unsafe fn str_bytes<'a>(value: TlValue) -> &'a [u8] {
+    let mut len = 0usize;
+    let ptr = tl_value_to_str(value, &mut len);
+    if ptr.is_null() || len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(ptr, len)
+    }
+}
+
+/// ポインタとして格納された値を、リスト同士・データ同士として比較する。
+unsafe fn compare_handles(lhs: *mut c_void, rhs: *mut c_void) -> Option<Ordering> {
+    if is_list_handle(lhs) && is_list_handle(rhs) {
+        compare_lists(lhs.cast(), rhs.cast())
+    } else if is_data_handle(lhs) && is_data_handle(rhs) {
+        compare_data(lhs.cast(), rhs.cast())
+    } else {
+        None
+    }
+}
+
+/// 要素を先頭から辞書式に比較する。短い方が先に尽きればそちらが小さい。
+unsafe fn compare_lists(lhs: *const TlListNode, rhs: *const TlListNode) -> Option<Ordering> {
+    compare_sequences(&list_items(lhs)?, &list_items(rhs)?)
+}
+
+/// コンストラクタのタグを先に比べ、同じならフィールドを辞書式に比較する。
+unsafe fn compare_data(lhs: *const TlData, rhs: *const TlData) -> Option<Ordering> {
+    let (lhs_tag, lhs_fields) = data_parts(lhs)?;
+    let (rhs_tag, rhs_fields) = data_parts(rhs)?;
+    match lhs_tag.cmp(&rhs_tag) {
+        Ordering::Equal => compare_sequences(lhs_fields, rhs_fields),
+        other => Some(other),
+    }
+}
+
+fn compare_sequences(lhs: &[TlValue], rhs: &[TlValue]) -> Option<Ordering> {
+    for (l, r) in lhs.iter().zip(rhs) {
+        match compare_values(*l, *r)? {
+            Ordering::Equal => continue,
+            other => return Some(other),
+        }
+    }
+    Some(lhs.len().cmp(&rhs.len()))
+}
+
+/// ボックス化された値を、`compare_values` で等しいと判定される値が同じハッシュになるよう再帰的に畳み込む。
+///
+/// 種別タグを先に混ぜるため、同じビット列でも種別が違えば別の値として扱われる。
+/// 比較できない値(不正なハンドルや NaN)を含む場合は `None` を返す。
+pub(crate) fn hash_value<H: Hasher>(value: TlValue, state: &mut H) -> Option<()> {
+    let kind = tl_value_kind(value)?;
+    (kind as u8).hash(state);
+    unsafe {
+        match kind {
+            TlValueKind::Int => tl_value_to_int(value).hash(state),
+            TlValueKind::Double => {
+                let double = tl_value_to_double(value);
+                if double.is_nan() {
+                    return None;
+                }
+                // `0.0` と `-0.0` は比較上等しいため、同じビット列へ揃える。
+                let normalized = if double == 0.0 { 0.0f64 } else { double };
+                normalized.to_bits().hash(state);
+            }
+            TlValueKind::Bool => (tl_value_to_bool(value) != 0).hash(state),
+            TlValueKind::Str => str_bytes(value).hash(state),
+            TlValueKind::Pointer => hash_handle(tl_value_to_ptr(value), state)?,
+        }
+    }
+    Some(())
+}
+
+unsafe fn hash_handle<H: Hasher>(ptr: *mut c_void, state: &mut H) -> Option<()> {
+    if is_list_handle(ptr) {
+        hash_sequence(&list_items(ptr.cast())?, state)
+    } else if is_data_handle(ptr) {
+        let (tag, fields) = data_parts(ptr.cast())?;
+        tag.hash(state);
+        hash_sequence(fields, state)
+    } else {
+        None
+    }
+}
+
+fn hash_sequence<H: Hasher>(items: &[TlValue], state: &mut H) -> Option<()> {
+    items.len().hash(state);
+    for item in items {
+        hash_value(*item, state)?;
+    }
+    Some(())
+}
+
+/// 比較結果を述語で真偽値へ落とす。比較できなければ `InvalidArgument` を記録して偽を返す。
+fn check(ordering: Option<Ordering>, pred: fn(Ordering) -> bool) -> i8 {
+    match ordering {
+        Some(ordering) => {
+            set_last_error(TlStatus::Ok);
+            pred(ordering) as i8
+        }
+        None => {
+            set_last_error(TlStatus::InvalidArgument);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tl_eq_list(lhs: *const TlListNode, rhs: *const TlListNode) -> i8 {
+    check(compare_lists(lhs, rhs), Ordering::is_eq)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tl_neq_list(lhs: *const TlListNode, rhs: *const TlListNode) -> i8 {
+    check(compare_lists(lhs, rhs), Ordering::is_ne)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tl_ord_list_lt(lhs: *const TlListNode, rhs: *const TlListNode) -> i8 {
+    check(compare_lists(lhs, rhs), Ordering::is_lt)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tl_ord_list_le(lhs: *const TlListNode, rhs: *const TlListNode) -> i8 {
+    check(compare_lists(lhs, rhs), Ordering::is_le)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tl_ord_list_gt(lhs: *const TlListNode, rhs: *const TlListNode) -> i8 {
+    check(compare_lists(lhs, rhs), Ordering::is_gt)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tl_ord_list_ge(lhs: *const TlListNode, rhs: *const TlListNode) -> i8 {
+    check(compare_lists(lhs, rhs), Ordering::is_ge)
+}
+
+/// タプルはタグ 0 の `TlData` として渡される前提で、フィールドを辞書式に比較する。
+#[no_mangle]
+pub unsafe extern "C" fn tl_eq_tuple(lhs: *const TlData, rhs: *const TlData) -> i8 {
+    check(compare_data(lhs, rhs), Ordering::is_eq)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tl_neq_tuple(lhs: *const TlData, rhs: *const TlData) -> i8 {
+    check(compare_data(lhs, rhs), Ordering::is_ne)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tl_ord_tuple_lt(lhs: *const TlData, rhs: *const TlData) -> i8 {
+    check(compare_data(lhs, rhs), Ordering::is_lt)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tl_ord_tuple_le(lhs: *const TlData, rhs: *const TlData) -> i8 {
+    check(compare_data(lhs, rhs), Ordering::is_le)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tl_ord_tuple_gt(lhs: *const TlData, rhs: *const TlData) -> i8 {
+    check(compare_data(lhs, rhs), Ordering::is_gt)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tl_ord_tuple_ge(lhs: *const TlData, rhs: *const TlData) -> i8 {
+    check(compare_data(lhs, rhs), Ordering::is_ge)
+}
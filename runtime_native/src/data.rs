@@ -3,11 +3,16 @@
 // 意図: ネイティブバックエンドがランタイム ABI を通じてデータコンストラクタを扱えるようにする
 // 関連ファイル: runtime_native/src/value.rs, runtime_native/src/list.rs
 
-use crate::error::{set_last_error, TlRuntimeError, TlStatus};
+use std::ffi::c_int;
+
+use crate::error::{set_last_error, tl_abort_with_message, TlRuntimeError, TlStatus};
 use crate::value::TlValue;
 
 const TL_DATA_MAGIC: u64 = 0x544C5F4441544131; // "TL_DATA1"
 
+/// `tl_data_field` にアリティ以上のフィールド番号が渡されたときの中断コード。
+pub const TL_ABORT_DATA_FIELD_OUT_OF_RANGE: c_int = 2003;
+
 #[repr(C)]
 pub struct TlData {
     magic: u64,
@@ -20,6 +25,8 @@ impl TlData {
     fn new(tag: u32, fields: &[TlValue]) -> Result<*mut TlData, TlRuntimeError> {
         let ptr_fields = if fields.is_empty() {
             std::ptr::null_mut()
+        } else if cfg!(feature = "arena") {
+            crate::arena::alloc_slice(fields)
         } else {
             let mut boxed_fields = Vec::with_capacity(fields.len());
             boxed_fields.extend_from_slice(fields);
@@ -35,6 +42,9 @@ impl TlData {
             fields: ptr_fields,
         };
 
+        if cfg!(feature = "arena") {
+            return Ok(crate::arena::alloc(data));
+        }
         Ok(Box::into_raw(Box::new(data)))
     }
 
@@ -61,6 +71,22 @@ impl TlData {
     }
 }
 
+/// `ptr` が代数的データを指しているか。ボックス化されたポインタの中身を見分けるのに使う。
+pub(crate) unsafe fn is_data_handle(ptr: *const std::ffi::c_void) -> bool {
+    TlData::ensure(ptr.cast()).is_ok()
+}
+
+/// タグとフィールド列を借用して返す。不正なハンドルなら `None`。
+pub(crate) unsafe fn data_parts<'a>(data: *const TlData) -> Option<(u32, &'a [TlValue])> {
+    let data = &*TlData::ensure(data).ok()?;
+    let fields = if data.len == 0 {
+        &[][..]
+    } else {
+        std::slice::from_raw_parts(data.fields, data.len)
+    };
+    Some((data.tag, fields))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn tl_data_pack(tag: u32, fields: *const TlValue, len: usize) -> *mut TlData {
     if len == 0 {
@@ -122,6 +148,10 @@ pub unsafe extern "C" fn tl_data_arity(data: *const TlData) -> usize {
     }
 }
 
+/// `index` 番目のフィールドを取り出す。
+///
+/// `index` が格納済みのアリティ以上なら範囲外を読まずに
+/// `tl_abort_with_message` (code=2003) で中断する。
 #[no_mangle]
 pub unsafe extern "C" fn tl_data_field(data: *const TlData, index: usize) -> TlValue {
     match TlData::ensure(data) {
@@ -129,11 +159,14 @@ pub unsafe extern "C" fn tl_data_field(data: *const TlData, index: usize) -> TlV
             let data = &*value;
             if index >= data.len {
                 set_last_error(TlStatus::InvalidArgument);
-                TlValue::null()
-            } else {
-                set_last_error(TlStatus::Ok);
-                *data.fields.add(index)
+                eprintln!(
+                    "TypeLang native runtime: field index {index} is out of range for constructor tag {} (arity {})",
+                    data.tag, data.len
+                );
+                tl_abort_with_message(TL_ABORT_DATA_FIELD_OUT_OF_RANGE);
             }
+            set_last_error(TlStatus::Ok);
+            *data.fields.add(index)
         }
         Err(err) => {
             set_last_error(err.status());
@@ -144,6 +177,10 @@ pub unsafe extern "C" fn tl_data_field(data: *const TlData, index: usize) -> TlV
 
 #[no_mangle]
 pub unsafe extern "C" fn tl_data_free(data: *mut TlData) {
+    // アリーナ上の値は `tl_arena_reset` でまとめて破棄する。
+    if cfg!(feature = "arena") {
+        return;
+    }
     if let Ok(ptr) = TlData::ensure_mut(data) {
         let data_ref = &*ptr;
         if !data_ref.fields.is_null() && data_ref.len > 0 {
@@ -22,11 +22,21 @@ struct TlDictEntryOwned {
     value: TlValue,
 }
 
+/// メソッド番号をそのまま添字にする表の上限。これを超える番号を持つ辞書は二分探索へ切り替える。
+const MAX_DENSE_SLOTS: u64 = 256;
+
+/// 直接索引表で「該当メソッドなし」を表す番兵。
+const EMPTY_SLOT: usize = usize::MAX;
+
+/// 型クラス辞書。`entries` は `method_id` 昇順に並び、`slots` は `method_id` から
+/// `entries` の添字を直接引く表（番号が疎な辞書では null）。
 #[repr(C)]
 pub struct TlDictionary {
     classname: *mut c_char,
     entries: *mut TlDictEntry,
     len: usize,
+    slots: *mut usize,
+    slots_len: usize,
 }
 
 pub struct TlDictBuilder {
@@ -92,6 +102,10 @@ pub unsafe extern "C" fn tl_dict_builder_finish(builder: *mut TlDictBuilder) ->
         return std::ptr::null_mut();
     };
 
+    // 安定ソートなので、同じ番号が重複した場合も従来どおり先に登録したものが引かれる。
+    builder.entries.sort_by_key(|entry| entry.method_id);
+    let slots = build_dense_slots(&builder.entries);
+
     let len = builder.entries.len();
     let mut raw_entries: Vec<TlDictEntry> = Vec::with_capacity(len);
     for owned in builder.entries.drain(..) {
@@ -110,16 +124,43 @@ pub unsafe extern "C" fn tl_dict_builder_finish(builder: *mut TlDictBuilder) ->
         ptr
     };
 
+    let slots_len = slots.len();
+    let slots_ptr = if slots.is_empty() {
+        std::ptr::null_mut()
+    } else {
+        Box::into_raw(slots.into_boxed_slice()) as *mut usize
+    };
+
     let dict = TlDictionary {
         classname: builder.classname.clone().into_raw(),
         entries: entries_ptr,
         len,
+        slots: slots_ptr,
+        slots_len,
     };
 
     set_last_error(TlStatus::Ok);
     Box::into_raw(Box::new(dict))
 }
 
+/// `method_id` 昇順の `entries` から直接索引表を作る。番号が上限を超える場合は空を返す。
+fn build_dense_slots(entries: &[TlDictEntryOwned]) -> Vec<usize> {
+    let Some(max_id) = entries.last().map(|entry| entry.method_id) else {
+        return Vec::new();
+    };
+    if max_id >= MAX_DENSE_SLOTS {
+        return Vec::new();
+    }
+    let mut slots = vec![EMPTY_SLOT; max_id as usize + 1];
+    for (index, entry) in entries.iter().enumerate() {
+        let slot = &mut slots[entry.method_id as usize];
+        if *slot == EMPTY_SLOT {
+            *slot = index;
+        }
+    }
+    slots
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn tl_dict_builder_dispose(builder: *mut TlDictBuilder) {
     if builder.is_null() {
@@ -136,15 +177,34 @@ pub unsafe extern "C" fn tl_dict_lookup(dict: *const TlDictionary, method_id: u6
         return TlValue::null();
     };
 
+    if dict.len == 0 {
+        set_last_error(TlStatus::InvalidArgument);
+        return TlValue::null();
+    }
     let entries = std::slice::from_raw_parts(dict.entries, dict.len);
-    for entry in entries {
-        if entry.method_id == method_id {
+    let found = if dict.slots.is_null() {
+        let start = entries.partition_point(|entry| entry.method_id < method_id);
+        entries
+            .get(start)
+            .filter(|entry| entry.method_id == method_id)
+    } else {
+        let slots = std::slice::from_raw_parts(dict.slots, dict.slots_len);
+        usize::try_from(method_id)
+            .ok()
+            .and_then(|id| slots.get(id))
+            .filter(|&&index| index != EMPTY_SLOT)
+            .map(|&index| &entries[index])
+    };
+    match found {
+        Some(entry) => {
             set_last_error(TlStatus::Ok);
-            return entry.value;
+            entry.value
+        }
+        None => {
+            set_last_error(TlStatus::InvalidArgument);
+            TlValue::null()
         }
     }
-    set_last_error(TlStatus::InvalidArgument);
-    TlValue::null()
 }
 
 #[no_mangle]
@@ -156,8 +216,15 @@ pub unsafe extern "C" fn tl_dict_free(dict: *mut TlDictionary) {
     let classname_ptr = dict_box.classname;
     let entries_ptr = dict_box.entries;
     let len = dict_box.len;
+    let slots_ptr = dict_box.slots;
+    let slots_len = dict_box.slots_len;
     drop(dict_box);
 
+    if !slots_ptr.is_null() {
+        let slots = std::ptr::slice_from_raw_parts_mut(slots_ptr, slots_len);
+        drop(Box::from_raw(slots));
+    }
+
     if !classname_ptr.is_null() {
         let _ = CString::from_raw(classname_ptr);
     }
@@ -107,6 +107,30 @@ const ORD_DOUBLE_METHODS: &[FallbackMethod] = fallback_methods![
     3 => ("ge", "Double -> Double -> Bool", tl_ord_double_ge),
 ];
 
+const EQ_LIST_METHODS: &[FallbackMethod] = fallback_methods![
+    0 => ("eq", "[a] -> [a] -> Bool", tl_eq_list),
+    1 => ("neq", "[a] -> [a] -> Bool", tl_neq_list),
+];
+
+const EQ_TUPLE_METHODS: &[FallbackMethod] = fallback_methods![
+    0 => ("eq", "(a, b) -> (a, b) -> Bool", tl_eq_tuple),
+    1 => ("neq", "(a, b) -> (a, b) -> Bool", tl_neq_tuple),
+];
+
+const ORD_LIST_METHODS: &[FallbackMethod] = fallback_methods![
+    0 => ("lt", "[a] -> [a] -> Bool", tl_ord_list_lt),
+    1 => ("le", "[a] -> [a] -> Bool", tl_ord_list_le),
+    2 => ("gt", "[a] -> [a] -> Bool", tl_ord_list_gt),
+    3 => ("ge", "[a] -> [a] -> Bool", tl_ord_list_ge),
+];
+
+const ORD_TUPLE_METHODS: &[FallbackMethod] = fallback_methods![
+    0 => ("lt", "(a, b) -> (a, b) -> Bool", tl_ord_tuple_lt),
+    1 => ("le", "(a, b) -> (a, b) -> Bool", tl_ord_tuple_le),
+    2 => ("gt", "(a, b) -> (a, b) -> Bool", tl_ord_tuple_gt),
+    3 => ("ge", "(a, b) -> (a, b) -> Bool", tl_ord_tuple_ge),
+];
+
 const BOOL_LOGIC_METHODS: &[FallbackMethod] = fallback_methods![
     0 => ("and", "Bool -> Bool -> Bool", tl_bool_logic_and),
     1 => ("or", "Bool -> Bool -> Bool", tl_bool_logic_or),
@@ -158,6 +182,26 @@ pub extern "C" fn tl_dict_build_Ord_Double() -> *mut crate::TlDictionary {
     unsafe { build_dictionary("Ord[Double]", ORD_DOUBLE_METHODS) }
 }
 
+#[no_mangle]
+pub extern "C" fn tl_dict_build_Eq_List() -> *mut crate::TlDictionary {
+    unsafe { build_dictionary("Eq[List]", EQ_LIST_METHODS) }
+}
+
+#[no_mangle]
+pub extern "C" fn tl_dict_build_Eq_Tuple() -> *mut crate::TlDictionary {
+    unsafe { build_dictionary("Eq[Tuple]", EQ_TUPLE_METHODS) }
+}
+
+#[no_mangle]
+pub extern "C" fn tl_dict_build_Ord_List() -> *mut crate::TlDictionary {
+    unsafe { build_dictionary("Ord[List]", ORD_LIST_METHODS) }
+}
+
+#[no_mangle]
+pub extern "C" fn tl_dict_build_Ord_Tuple() -> *mut crate::TlDictionary {
+    unsafe { build_dictionary("Ord[Tuple]", ORD_TUPLE_METHODS) }
+}
+
 #[no_mangle]
 pub extern "C" fn tl_dict_build_BoolLogic_Bool() -> *mut crate::TlDictionary {
     unsafe { build_dictionary("BoolLogic[Bool]", BOOL_LOGIC_METHODS) }
@@ -0,0 +1,51 @@
+// パス: runtime_native/src/format.rs
+// 役割: `format` の C ABI 入口を提供する
+// 意図: 書式の解釈と組み立ては runtime_common に任せ、ここでは値とリストの受け渡しだけを行う
+// 関連ファイル: runtime_common/src/format.rs, runtime_native/src/list.rs, runtime_native/src/value.rs
+
+use runtime_common::{format_specs, parse_format, render_format, FormatArg, FormatSpec};
+
+use crate::list::{tl_list_head, tl_list_is_empty, tl_list_tail, TlListNode};
+use crate::value::{
+    tl_value_from_static_str, tl_value_to_double, tl_value_to_int, tl_value_to_str, TlValue,
+};
+
+/// `format` のランタイム実装。`args` は指定子と同じ順に並べた値のリスト。
+///
+/// 結果の文字列はプログラム終了まで解放しない。書式や引数が不正な場合は空文字列を返す。
+///
+/// # Safety
+/// `template` と `args` の要素はランタイムが生成した有効な値、`args` は有効なリストでなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn tl_format(template: TlValue, mut args: *const TlListNode) -> TlValue {
+    let rendered = str_of(template)
+        .and_then(|text| parse_format(text).ok())
+        .and_then(|pieces| {
+            let mut values = Vec::new();
+            for spec in format_specs(&pieces) {
+                if tl_list_is_empty(args) {
+                    break;
+                }
+                let value = tl_list_head(args);
+                values.push(match spec {
+                    FormatSpec::Int => FormatArg::Int(tl_value_to_int(value)),
+                    FormatSpec::Double { .. } => FormatArg::Double(tl_value_to_double(value)),
+                    FormatSpec::Str => FormatArg::Str(str_of(value)?),
+                });
+                args = tl_list_tail(args);
+            }
+            render_format(&pieces, &values).ok()
+        })
+        .unwrap_or_default();
+    let bytes: &'static [u8] = Box::leak(rendered.into_boxed_str()).as_bytes();
+    tl_value_from_static_str(bytes.as_ptr(), bytes.len())
+}
+
+unsafe fn str_of<'a>(value: TlValue) -> Option<&'a str> {
+    let mut len = 0usize;
+    let ptr = tl_value_to_str(value, &mut len);
+    if ptr.is_null() {
+        return None;
+    }
+    std::str::from_utf8(std::slice::from_raw_parts(ptr, len)).ok()
+}
@@ -6,16 +6,34 @@
 
 #![allow(clippy::missing_safety_doc)]
 
+mod arena;
+mod array;
+mod cell;
+mod compare;
 mod data;
 mod dict;
 mod error;
+mod format;
 mod list;
+mod map;
+mod math;
+mod read;
+mod stack;
 mod value;
 
+pub use arena::tl_arena_reset;
+pub use array::*;
+pub use cell::*;
+pub use compare::*;
 pub use data::*;
 pub use dict::*;
 pub use error::*;
+pub use format::*;
 pub use list::*;
+pub use map::*;
+pub use math::*;
+pub use read::*;
+pub use stack::*;
 pub use value::*;
 
 include!(concat!(env!("OUT_DIR"), "/dict_autogen.rs"));
@@ -3,11 +3,16 @@
 // 意図: TypeLang のリスト値をネイティブランタイムで生成・走査できるようにする
 // 関連ファイル: runtime_native/src/value.rs, runtime_native/src/data.rs
 
+use std::ffi::{c_char, CStr};
+
 use crate::error::{set_last_error, TlRuntimeError};
-use crate::value::TlValue;
+use crate::value::{tl_value_from_static_str, TlValue};
+
+const TL_LIST_MAGIC: u64 = 0x544C5F4C49535431; // "TL_LIST1"
 
 #[repr(C)]
 pub struct TlListNode {
+    magic: u64,
     tag: u8,
     head: TlValue,
     tail: *mut TlListNode,
@@ -19,6 +24,7 @@ impl TlListNode {
 
     fn empty() -> Self {
         Self {
+            magic: TL_LIST_MAGIC,
             tag: Self::EMPTY_TAG,
             head: TlValue::null(),
             tail: std::ptr::null_mut(),
@@ -27,6 +33,7 @@ impl TlListNode {
 
     fn new_cons(head: TlValue, tail: *mut TlListNode) -> Self {
         Self {
+            magic: TL_LIST_MAGIC,
             tag: Self::CONS_TAG,
             head,
             tail,
@@ -36,16 +43,47 @@ impl TlListNode {
     fn is_empty(&self) -> bool {
         self.tag == Self::EMPTY_TAG
     }
+
+    fn into_raw(self) -> *mut TlListNode {
+        if cfg!(feature = "arena") {
+            crate::arena::alloc(self)
+        } else {
+            Box::into_raw(Box::new(self))
+        }
+    }
+}
+
+/// `ptr` がリスト節点を指しているか。ボックス化されたポインタの中身を見分けるのに使う。
+pub(crate) unsafe fn is_list_handle(ptr: *const std::ffi::c_void) -> bool {
+    ptr.cast::<TlListNode>()
+        .as_ref()
+        .is_some_and(|node| node.magic == TL_LIST_MAGIC)
+}
+
+/// リストの要素を先頭から順に集める。リスト節点でないものを含む場合は `None`。
+pub(crate) unsafe fn list_items(mut list: *const TlListNode) -> Option<Vec<TlValue>> {
+    let mut items = Vec::new();
+    loop {
+        let node = list.as_ref()?;
+        if node.magic != TL_LIST_MAGIC {
+            return None;
+        }
+        if node.is_empty() {
+            return Some(items);
+        }
+        items.push(node.head);
+        list = node.tail;
+    }
 }
 
 #[no_mangle]
 pub extern "C" fn tl_list_empty() -> *mut TlListNode {
-    Box::into_raw(Box::new(TlListNode::empty()))
+    TlListNode::empty().into_raw()
 }
 
 #[no_mangle]
 pub extern "C" fn tl_list_cons(head: TlValue, tail: *mut TlListNode) -> *mut TlListNode {
-    Box::into_raw(Box::new(TlListNode::new_cons(head, tail)))
+    TlListNode::new_cons(head, tail).into_raw()
 }
 
 #[no_mangle]
@@ -75,8 +113,123 @@ pub unsafe extern "C" fn tl_list_tail(list: *const TlListNode) -> *mut TlListNod
     }
 }
 
+/// リストの要素数を返す。
+#[no_mangle]
+pub unsafe extern "C" fn tl_list_length(mut list: *const TlListNode) -> i64 {
+    let mut len = 0;
+    while let Some(node) = list.as_ref() {
+        if node.is_empty() {
+            break;
+        }
+        len += 1;
+        list = node.tail;
+    }
+    len
+}
+
+/// `value` を `count` 個並べたリストを作る。`count` が 0 以下なら空リスト。
+#[no_mangle]
+pub extern "C" fn tl_list_replicate(count: i64, value: TlValue) -> *mut TlListNode {
+    (0..count.max(0)).fold(tl_list_empty(), |tail, _| tl_list_cons(value, tail))
+}
+
+/// 先頭から `count` 個の要素を複製した新しいリストを返す。
+/// `count` が 0 以下なら空リスト、要素数より大きければリスト全体の複製になる。
+///
+/// # Safety
+/// `list` は有効なリスト節点を指していなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn tl_list_take(count: i64, mut list: *const TlListNode) -> *mut TlListNode {
+    let mut items = Vec::new();
+    while let Some(node) = list.as_ref() {
+        if node.is_empty() || items.len() as i64 >= count {
+            break;
+        }
+        items.push(node.head);
+        list = node.tail;
+    }
+    items
+        .into_iter()
+        .rev()
+        .fold(tl_list_empty(), |tail, head| tl_list_cons(head, tail))
+}
+
+/// `lhs` の要素を複製した後ろへ `rhs` をつないだリストを返す（`<>` の実装）。
+/// `rhs` の節点は複製せず、元のリストと共有する。
+///
+/// # Safety
+/// `lhs` と `rhs` は有効なリスト節点を指していなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn tl_list_append(
+    mut lhs: *const TlListNode,
+    rhs: *mut TlListNode,
+) -> *mut TlListNode {
+    let mut items = Vec::new();
+    while let Some(node) = lhs.as_ref() {
+        if node.is_empty() {
+            break;
+        }
+        items.push(node.head);
+        lhs = node.tail;
+    }
+    items
+        .into_iter()
+        .rev()
+        .fold(rhs, |tail, head| tl_list_cons(head, tail))
+}
+
+/// 先頭の `count` 個を読み飛ばした残りを返す。残りの節点は元のリストと共有する。
+/// `count` が 0 以下なら `list` そのもの、要素数以上なら空リストになる。
+///
+/// # Safety
+/// `list` は有効なリスト節点を指していなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn tl_list_drop(count: i64, mut list: *mut TlListNode) -> *mut TlListNode {
+    let mut remaining = count;
+    while remaining > 0 {
+        match list.as_ref() {
+            Some(node) if !node.is_empty() => list = node.tail,
+            _ => break,
+        }
+        remaining -= 1;
+    }
+    list
+}
+
+/// コマンドライン引数（先頭のプログラム名を除く）を文字列値のリストにする。
+///
+/// `main :: [String] -> Int` のエントリポイントが C の `main(argc, argv)` から呼ぶ。
+/// UTF-8 でない引数は不正なバイト列を U+FFFD に置き換えた複製を使う。
+///
+/// # Safety
+/// `argv` は `argc` 個の NUL 終端文字列へのポインタを並べた配列で、プログラム終了まで有効でなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn tl_args_list(argc: i32, argv: *const *const c_char) -> *mut TlListNode {
+    let mut list = tl_list_empty();
+    if argv.is_null() {
+        return list;
+    }
+    for index in (1..usize::try_from(argc).unwrap_or(0)).rev() {
+        let arg = *argv.add(index);
+        if arg.is_null() {
+            continue;
+        }
+        let raw: &'static CStr = CStr::from_ptr(arg);
+        let bytes: &'static [u8] = match raw.to_str() {
+            Ok(text) => text.as_bytes(),
+            Err(_) => Box::leak(raw.to_string_lossy().into_owned().into_boxed_str()).as_bytes(),
+        };
+        list = tl_list_cons(tl_value_from_static_str(bytes.as_ptr(), bytes.len()), list);
+    }
+    list
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn tl_list_free(mut list: *mut TlListNode) {
+    // アリーナ上の節点は `tl_arena_reset` でまとめて破棄する。
+    if cfg!(feature = "arena") {
+        return;
+    }
     while let Some(node) = list.as_mut() {
         let tail = node.tail;
         drop(Box::from_raw(list));
@@ -0,0 +1,149 @@
+// パス: runtime_native/src/map.rs
+// 役割: キーを構造的ハッシュで引く連想配列 (TlMap) を提供する
+// 意図: TypeLang の `Map` 組み込み (`emptyMap`/`insert`/`lookup`/`findWithDefault`) をネイティブに実行できるようにする
+// 関連ファイル: runtime_native/src/compare.rs, runtime_native/src/data.rs, runtime_native/src/list.rs, src/codegen/cranelift.rs
+//! 連想配列
+//!
+//! - キーは `compare::hash_value` でハッシュし、同じバケット内は `compare::compare_values` で突き合わせる。
+//! - `tl_map_insert` は元の表を変更せず、挿入後の新しい表を返す(永続的な意味論)。
+//!   そのため挿入ごとに表全体を複製する。
+//! - 表は `HashMap` を抱えるため、アリーナ機能の有無にかかわらずヒープに確保する。
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::Hasher;
+
+use crate::compare::{compare_values, hash_value};
+use crate::data::{is_data_handle, tl_data_pack, TlData};
+use crate::error::{set_last_error, TlStatus};
+use crate::list::is_list_handle;
+use crate::value::{tl_value_from_ptr, tl_value_kind, TlValue};
+
+const TL_MAP_MAGIC: u64 = 0x544C5F4D41505F31; // "TL_MAP_1"
+
+#[repr(C)]
+pub struct TlMap {
+    magic: u64,
+    buckets: HashMap<u64, Vec<(TlValue, TlValue)>>,
+}
+
+impl TlMap {
+    fn into_raw(self) -> *mut TlMap {
+        Box::into_raw(Box::new(self))
+    }
+
+    unsafe fn get<'a>(ptr: *const TlMap) -> Option<&'a TlMap> {
+        ptr.as_ref().filter(|map| map.magic == TL_MAP_MAGIC)
+    }
+
+    fn find(&self, hash: u64, key: TlValue) -> Option<TlValue> {
+        self.buckets
+            .get(&hash)?
+            .iter()
+            .find(|(stored, _)| compare_values(*stored, key).is_some_and(|ord| ord.is_eq()))
+            .map(|(_, value)| *value)
+    }
+}
+
+/// 生成コードはリストやデータのハンドルを箱詰めせずに渡すため、比較・ハッシュの前に箱へ入れる。
+unsafe fn boxed_key(key: TlValue) -> TlValue {
+    let raw = key.as_raw();
+    if tl_value_kind(key).is_none() && (is_list_handle(raw) || is_data_handle(raw)) {
+        tl_value_from_ptr(raw)
+    } else {
+        key
+    }
+}
+
+fn key_hash(key: TlValue) -> Option<u64> {
+    let mut hasher = DefaultHasher::new();
+    hash_value(key, &mut hasher)?;
+    Some(hasher.finish())
+}
+
+#[no_mangle]
+pub extern "C" fn tl_map_empty() -> *mut TlMap {
+    TlMap {
+        magic: TL_MAP_MAGIC,
+        buckets: HashMap::new(),
+    }
+    .into_raw()
+}
+
+/// `key` に `value` を対応付けた新しい表を返す。既存の対応は置き換える。
+///
+/// 表が不正、またはキーがハッシュできない場合は `InvalidArgument` を記録し、元の表をそのまま返す。
+#[no_mangle]
+pub unsafe extern "C" fn tl_map_insert(
+    map: *mut TlMap,
+    key: TlValue,
+    value: TlValue,
+) -> *mut TlMap {
+    let key = boxed_key(key);
+    let (Some(current), Some(hash)) = (TlMap::get(map), key_hash(key)) else {
+        set_last_error(TlStatus::InvalidArgument);
+        return map;
+    };
+    let mut buckets = current.buckets.clone();
+    let bucket = buckets.entry(hash).or_default();
+    bucket.retain(|(stored, _)| !compare_values(*stored, key).is_some_and(|ord| ord.is_eq()));
+    bucket.push((key, value));
+    set_last_error(TlStatus::Ok);
+    TlMap {
+        magic: TL_MAP_MAGIC,
+        buckets,
+    }
+    .into_raw()
+}
+
+/// `key` に対応する値を `Maybe` として返す。見つかれば `just_tag` の 1 フィールドのデータ、
+/// なければ `nothing_tag` のフィールドなしのデータになる。
+///
+/// タグは `Maybe` の宣言での構築子の位置で、生成コードが Core IR のデータ配置から渡す。
+#[no_mangle]
+pub unsafe extern "C" fn tl_map_lookup(
+    map: *const TlMap,
+    key: TlValue,
+    nothing_tag: u32,
+    just_tag: u32,
+) -> *mut TlData {
+    let key = boxed_key(key);
+    let (Some(current), Some(hash)) = (TlMap::get(map), key_hash(key)) else {
+        let nothing = tl_data_pack(nothing_tag, std::ptr::null(), 0);
+        set_last_error(TlStatus::InvalidArgument);
+        return nothing;
+    };
+    match current.find(hash, key) {
+        Some(value) => tl_data_pack(just_tag, &value, 1),
+        None => tl_data_pack(nothing_tag, std::ptr::null(), 0),
+    }
+}
+
+/// `key` に対応する値を返す。見つからなければ `default` を返す。
+#[no_mangle]
+pub unsafe extern "C" fn tl_map_find_with_default(
+    map: *const TlMap,
+    key: TlValue,
+    default: TlValue,
+) -> TlValue {
+    let key = boxed_key(key);
+    let (Some(current), Some(hash)) = (TlMap::get(map), key_hash(key)) else {
+        set_last_error(TlStatus::InvalidArgument);
+        return default;
+    };
+    set_last_error(TlStatus::Ok);
+    current.find(hash, key).unwrap_or(default)
+}
+
+/// 表に含まれる対応の数を返す。不正な表なら 0。
+#[no_mangle]
+pub unsafe extern "C" fn tl_map_len(map: *const TlMap) -> usize {
+    TlMap::get(map).map_or(0, |map| map.buckets.values().map(Vec::len).sum())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tl_map_free(map: *mut TlMap) {
+    if TlMap::get(map).is_some() {
+        drop(Box::from_raw(map));
+    }
+}
@@ -0,0 +1,59 @@
+// パス: runtime_native/src/math.rs
+// 役割: `sqrt` / `sin` / `cos` / `exp` / `log` と丸め・整数変換の C ABI ラッパーを提供する
+// 意図: ネイティブコードから libm 相当の数学関数を一様なシンボル名で呼び出せるようにする
+// 関連ファイル: src/codegen/cranelift.rs, src/intrinsics.rs, src/runtime.rs, runtime_common/src/number.rs
+
+use runtime_common::round_half_even;
+
+#[no_mangle]
+pub extern "C" fn tl_sqrt(value: f64) -> f64 {
+    value.sqrt()
+}
+
+#[no_mangle]
+pub extern "C" fn tl_sin(value: f64) -> f64 {
+    value.sin()
+}
+
+#[no_mangle]
+pub extern "C" fn tl_cos(value: f64) -> f64 {
+    value.cos()
+}
+
+#[no_mangle]
+pub extern "C" fn tl_exp(value: f64) -> f64 {
+    value.exp()
+}
+
+/// 自然対数。Haskell の `log` と同じく底は e。
+#[no_mangle]
+pub extern "C" fn tl_log(value: f64) -> f64 {
+    value.ln()
+}
+
+/// `Double -> Int` の丸め。範囲外は `as` と同じく飽和する。
+#[no_mangle]
+pub extern "C" fn tl_floor(value: f64) -> i64 {
+    value.floor() as i64
+}
+
+#[no_mangle]
+pub extern "C" fn tl_ceiling(value: f64) -> i64 {
+    value.ceil() as i64
+}
+
+/// Haskell の `round` と同じく、ちょうど中間の値は偶数側へ丸める。
+#[no_mangle]
+pub extern "C" fn tl_round(value: f64) -> i64 {
+    round_half_even(value) as i64
+}
+
+#[no_mangle]
+pub extern "C" fn tl_truncate(value: f64) -> i64 {
+    value.trunc() as i64
+}
+
+#[no_mangle]
+pub extern "C" fn tl_from_integral(value: i64) -> f64 {
+    value as f64
+}
@@ -0,0 +1,68 @@
+// パス: runtime_native/src/read.rs
+// 役割: `readInt` / `readDouble` の C ABI 入口を提供する
+// 意図: 読み取りの規則は runtime_common と共有し、評価器と同じ形式だけを受け付ける
+// 関連ファイル: runtime_common/src/number.rs, runtime_native/src/value.rs, runtime_native/src/data.rs
+//! 結果は `lookup` と同じく `Maybe` のデータで返す。
+
+use runtime_common::{read_double, read_int};
+
+use crate::data::{tl_data_pack, TlData};
+use crate::value::{tl_value_from_double, tl_value_from_int, tl_value_to_str, TlValue};
+
+/// 文字列値を読み、読めた値を箱詰めした `just_tag` のデータか `nothing_tag` のデータを返す。
+unsafe fn read_into_maybe<T>(
+    value: TlValue,
+    read: fn(&str) -> Option<T>,
+    boxed: extern "C" fn(T) -> TlValue,
+    nothing_tag: u32,
+    just_tag: u32,
+) -> *mut TlData {
+    let mut len = 0usize;
+    let ptr = tl_value_to_str(value, &mut len);
+    let text = if ptr.is_null() {
+        None
+    } else {
+        std::str::from_utf8(std::slice::from_raw_parts(ptr, len)).ok()
+    };
+    match text.and_then(read) {
+        Some(parsed) => {
+            let field = boxed(parsed);
+            tl_data_pack(just_tag, &field, 1)
+        }
+        None => tl_data_pack(nothing_tag, std::ptr::null(), 0),
+    }
+}
+
+/// `readInt` のランタイム実装。文字列値でない引数も読めなかったものとして `Nothing` を返す。
+///
+/// タグは `Maybe` の宣言での構築子の位置で、生成コードが Core IR のデータ配置から渡す。
+///
+/// # Safety
+/// `value` はランタイムが生成した有効な値でなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn tl_read_int(
+    value: TlValue,
+    nothing_tag: u32,
+    just_tag: u32,
+) -> *mut TlData {
+    read_into_maybe(value, read_int, tl_value_from_int, nothing_tag, just_tag)
+}
+
+/// `readDouble` のランタイム実装。
+///
+/// # Safety
+/// `value` はランタイムが生成した有効な値でなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn tl_read_double(
+    value: TlValue,
+    nothing_tag: u32,
+    just_tag: u32,
+) -> *mut TlData {
+    read_into_maybe(
+        value,
+        read_double,
+        tl_value_from_double,
+        nothing_tag,
+        just_tag,
+    )
+}
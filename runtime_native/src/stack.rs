@@ -0,0 +1,55 @@
+// パス: runtime_native/src/stack.rs
+// 役割: 生成コードの関数呼び出しの深さを数え、上限を超えたら中断する
+// 意図: 深い再帰で C スタックを溢れさせてクラッシュする代わりに、診断付きで終了させる
+// 関連ファイル: runtime_native/src/error.rs, src/codegen/cranelift.rs, documents/native.md
+//! 呼び出し深さの上限
+//!
+//! - コード生成で上限を指定したときだけ、各関数の入口で `tl_stack_enter`、出口で `tl_stack_leave` を呼ぶ。
+//! - 上限は `main` の冒頭で `tl_stack_set_limit` により設定する。設定しなければ無制限。
+//! - 深さはスレッドローカルに数える。
+
+use std::cell::Cell;
+use std::ffi::c_int;
+
+use crate::error::tl_abort_with_message;
+
+/// 呼び出し深さが上限を超えたときの中断コード。
+pub const TL_ABORT_STACK_OVERFLOW: c_int = 2004;
+
+thread_local! {
+    static DEPTH: Cell<u32> = const { Cell::new(0) };
+    static LIMIT: Cell<u32> = const { Cell::new(u32::MAX) };
+}
+
+/// 呼び出し深さの上限を設定する。
+#[no_mangle]
+pub extern "C" fn tl_stack_set_limit(limit: u32) {
+    LIMIT.with(|cell| cell.set(limit));
+}
+
+/// 関数の入口で深さを 1 増やし、上限を超えたら `tl_abort_with_message` (code=2004) で中断する。
+#[no_mangle]
+pub extern "C" fn tl_stack_enter() {
+    let depth = DEPTH.with(|cell| {
+        let depth = cell.get().saturating_add(1);
+        cell.set(depth);
+        depth
+    });
+    let limit = LIMIT.with(Cell::get);
+    if depth > limit {
+        eprintln!("TypeLang native runtime: stack overflow (call depth exceeded {limit})");
+        tl_abort_with_message(TL_ABORT_STACK_OVERFLOW);
+    }
+}
+
+/// 関数の出口で深さを 1 減らす。
+#[no_mangle]
+pub extern "C" fn tl_stack_leave() {
+    DEPTH.with(|cell| cell.set(cell.get().saturating_sub(1)));
+}
+
+/// 現在の呼び出し深さを返す。
+#[no_mangle]
+pub extern "C" fn tl_stack_depth() -> u32 {
+    DEPTH.with(Cell::get)
+}
@@ -4,7 +4,10 @@
 // 関連ファイル: runtime_native/src/error.rs, runtime_native/src/list.rs, runtime_native/src/data.rs
 
 use crate::error::{clear_last_error, set_last_error, TlRuntimeError, TlStatus};
+use runtime_common::format_double;
+use std::collections::HashMap;
 use std::ffi::c_void;
+use std::sync::{Mutex, OnceLock};
 
 const TL_BOX_MAGIC: u64 = 0x544C5F424F585F31; // "TL_BOX_1"
 
@@ -22,6 +25,15 @@ pub enum TlValueKind {
     Double = 1,
     Bool = 2,
     Pointer = 3,
+    Str = 4,
+}
+
+/// 読み取り専用領域に置かれた文字列リテラルへの参照。バイト列は複製しない。
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct TlStrRef {
+    ptr: *const u8,
+    len: usize,
 }
 
 #[repr(C)]
@@ -31,6 +43,7 @@ union TlBoxPayload {
     double_value: f64,
     bool_value: i8,
     ptr_value: *mut c_void,
+    str_value: TlStrRef,
 }
 
 impl TlBox {
@@ -69,6 +82,19 @@ impl TlBox {
             payload: TlBoxPayload { ptr_value: ptr },
         }
     }
+
+    fn new_static_str(bytes: &'static [u8]) -> Self {
+        Self {
+            magic: TL_BOX_MAGIC,
+            kind: TlValueKind::Str,
+            payload: TlBoxPayload {
+                str_value: TlStrRef {
+                    ptr: bytes.as_ptr(),
+                    len: bytes.len(),
+                },
+            },
+        }
+    }
 }
 
 #[repr(transparent)]
@@ -124,6 +150,26 @@ fn box_ptr(ptr: *mut c_void) -> Result<TlValue, TlRuntimeError> {
     Ok(TlValue(Box::into_raw(boxed)))
 }
 
+/// 文字列リテラルの内容から、共有する `TlBox` のアドレスへの表。
+/// 登録された値はプログラム終了まで解放しない。
+static INTERNED_STRS: OnceLock<Mutex<HashMap<&'static [u8], usize>>> = OnceLock::new();
+
+fn intern_static_str(bytes: &'static [u8]) -> Result<TlValue, TlRuntimeError> {
+    if std::str::from_utf8(bytes).is_err() {
+        return Err(TlRuntimeError::InvalidArgument(
+            "string literal is not valid UTF-8",
+        ));
+    }
+    let table = INTERNED_STRS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut table = table
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let addr = *table
+        .entry(bytes)
+        .or_insert_with(|| Box::into_raw(Box::new(TlBox::new_static_str(bytes))) as usize);
+    Ok(TlValue(addr as *mut TlBox))
+}
+
 fn handle_result(result: Result<TlValue, TlRuntimeError>) -> TlValue {
     match result {
         Ok(value) => {
@@ -157,6 +203,77 @@ pub extern "C" fn tl_value_from_ptr(ptr: *mut c_void) -> TlValue {
     handle_result(box_ptr(ptr))
 }
 
+/// 文字列リテラルを内容ごとに 1 つの値へまとめて返す。
+///
+/// 同じ内容のリテラルは配置先が異なっても同一の `TlValue` を共有し、バイト列は複製しない。
+/// 返した値は `tl_value_release` しても解放されない。
+///
+/// # Safety
+/// `bytes` は `len` バイトの UTF-8 列を指し、プログラム終了まで有効かつ不変でなければならない
+/// （コード生成が読み取り専用セクションへ置いたリテラルを想定）。
+#[no_mangle]
+pub unsafe extern "C" fn tl_value_from_static_str(bytes: *const u8, len: usize) -> TlValue {
+    let slice: &'static [u8] = if len == 0 {
+        &[]
+    } else if bytes.is_null() {
+        set_last_error(TlStatus::NullPointer);
+        return TlValue::null();
+    } else {
+        std::slice::from_raw_parts(bytes, len)
+    };
+    handle_result(intern_static_str(slice))
+}
+
+/// 文字列値の先頭アドレスを返し、`out_len` へバイト長を書き込む。
+#[no_mangle]
+pub unsafe extern "C" fn tl_value_to_str(value: TlValue, out_len: *mut usize) -> *const u8 {
+    match value.validate() {
+        Ok(ptr) => {
+            let boxed = &*ptr;
+            match boxed.kind {
+                TlValueKind::Str => {
+                    let str_ref = boxed.payload.str_value;
+                    if let Some(out_len) = out_len.as_mut() {
+                        *out_len = str_ref.len;
+                    }
+                    set_last_error(TlStatus::Ok);
+                    str_ref.ptr
+                }
+                _ => {
+                    set_last_error(TlStatus::InvalidArgument);
+                    std::ptr::null()
+                }
+            }
+        }
+        Err(err) => {
+            set_last_error(err.status());
+            std::ptr::null()
+        }
+    }
+}
+
+/// 2 つの文字列値を連結した文字列値を返す（`String` どうしの `<>` の実装）。
+///
+/// 結果の文字列はプログラム終了まで解放しない。どちらかが文字列値でなければ null を返す。
+///
+/// # Safety
+/// `lhs` と `rhs` はランタイムが生成した有効な値でなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn tl_string_append(lhs: TlValue, rhs: TlValue) -> TlValue {
+    let mut lhs_len = 0usize;
+    let lhs_ptr = tl_value_to_str(lhs, &mut lhs_len);
+    let mut rhs_len = 0usize;
+    let rhs_ptr = tl_value_to_str(rhs, &mut rhs_len);
+    if lhs_ptr.is_null() || rhs_ptr.is_null() {
+        return TlValue::null();
+    }
+    let mut joined = Vec::with_capacity(lhs_len + rhs_len);
+    joined.extend_from_slice(std::slice::from_raw_parts(lhs_ptr, lhs_len));
+    joined.extend_from_slice(std::slice::from_raw_parts(rhs_ptr, rhs_len));
+    let bytes: &'static [u8] = Box::leak(joined.into_boxed_slice());
+    handle_result(intern_static_str(bytes))
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
 pub struct TlValueResult {
@@ -208,7 +325,7 @@ pub unsafe extern "C" fn tl_value_to_int(value: TlValue) -> i64 {
                 TlValueKind::Int => boxed.payload.int_value,
                 TlValueKind::Bool => boxed.payload.bool_value as i64,
                 TlValueKind::Double => boxed.payload.double_value as i64,
-                TlValueKind::Pointer => {
+                TlValueKind::Pointer | TlValueKind::Str => {
                     set_last_error(TlStatus::InvalidArgument);
                     0
                 }
@@ -230,7 +347,7 @@ pub unsafe extern "C" fn tl_value_to_double(value: TlValue) -> f64 {
                 TlValueKind::Int => boxed.payload.int_value as f64,
                 TlValueKind::Bool => boxed.payload.bool_value as f64,
                 TlValueKind::Double => boxed.payload.double_value,
-                TlValueKind::Pointer => {
+                TlValueKind::Pointer | TlValueKind::Str => {
                     set_last_error(TlStatus::InvalidArgument);
                     0.0
                 }
@@ -252,7 +369,7 @@ pub unsafe extern "C" fn tl_value_to_bool(value: TlValue) -> i8 {
                 TlValueKind::Int => (boxed.payload.int_value != 0) as i8,
                 TlValueKind::Double => (boxed.payload.double_value != 0.0) as i8,
                 TlValueKind::Bool => boxed.payload.bool_value,
-                TlValueKind::Pointer => {
+                TlValueKind::Pointer | TlValueKind::Str => {
                     set_last_error(TlStatus::InvalidArgument);
                     0
                 }
@@ -291,6 +408,10 @@ pub unsafe extern "C" fn tl_value_release(value: TlValue) {
         return;
     }
     let ptr = value.0;
+    // 文字列リテラルはインターン表が所有し続ける。
+    if tl_value_kind(value) == Some(TlValueKind::Str) {
+        return;
+    }
     drop(Box::from_raw(ptr));
 }
 
@@ -301,7 +422,7 @@ pub extern "C" fn tl_print_int(value: i64) {
 
 #[no_mangle]
 pub extern "C" fn tl_print_double(value: f64) {
-    println!("{value}");
+    println!("{}", format_double(value));
 }
 
 #[no_mangle]
@@ -315,27 +436,41 @@ pub extern "C" fn tl_print_bool(value: i8) {
 
 #[no_mangle]
 pub extern "C" fn tl_println(value: TlValue) -> TlValue {
+    println!("{}", render_value(value));
+    value
+}
+
+/// `trace` 用: メッセージを標準エラーへ出力し、第 2 引数をそのまま返す。
+#[no_mangle]
+pub extern "C" fn tl_trace(message: TlValue, value: TlValue) -> TlValue {
+    eprintln!("{}", render_value(message));
+    value
+}
+
+fn render_value(value: TlValue) -> String {
     match tl_value_kind(value) {
-        Some(TlValueKind::Int) => unsafe {
-            let v = tl_value_to_int(value);
-            println!("{v}");
-        },
-        Some(TlValueKind::Double) => unsafe {
-            let v = tl_value_to_double(value);
-            println!("{v}");
-        },
+        Some(TlValueKind::Int) => unsafe { tl_value_to_int(value).to_string() },
+        Some(TlValueKind::Double) => unsafe { format_double(tl_value_to_double(value)) },
         Some(TlValueKind::Bool) => unsafe {
-            let v = tl_value_to_bool(value);
-            if v != 0 {
-                println!("True");
+            if tl_value_to_bool(value) != 0 {
+                "True".to_string()
             } else {
-                println!("False");
+                "False".to_string()
             }
         },
-        Some(TlValueKind::Pointer) => println!("<pointer {:?}>", unsafe { tl_value_to_ptr(value) }),
-        None => println!("<invalid value>"),
+        Some(TlValueKind::Str) => unsafe {
+            let mut len = 0usize;
+            let ptr = tl_value_to_str(value, &mut len);
+            let bytes = if len == 0 {
+                &[][..]
+            } else {
+                std::slice::from_raw_parts(ptr, len)
+            };
+            String::from_utf8_lossy(bytes).into_owned()
+        },
+        Some(TlValueKind::Pointer) => format!("<pointer {:?}>", unsafe { tl_value_to_ptr(value) }),
+        None => "<invalid value>".to_string(),
     }
-    value
 }
 
 pub fn tl_value_kind(value: TlValue) -> Option<TlValueKind> {
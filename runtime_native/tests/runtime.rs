@@ -3,10 +3,15 @@
 // 意図: 辞書構築や TlValue 変換が期待通りに機能することを保証する
 // 関連ファイル: runtime_native/src/value.rs, runtime_native/src/dict.rs, tests/native_build.rs
 
+use std::process::Command;
+
 use runtime_native::{
-    tl_dict_build_BoolLogic_Bool, tl_dict_build_Eq_Int, tl_dict_build_Num_Int, tl_dict_free,
-    tl_dict_lookup, tl_last_error, tl_value_from_int_result, tl_value_release, tl_value_to_int,
-    tl_value_to_ptr, TlStatus,
+    tl_data_arity, tl_data_field, tl_data_free, tl_data_pack, tl_dict_build_BoolLogic_Bool,
+    tl_dict_build_Eq_Int, tl_dict_build_Num_Int, tl_dict_builder_dispose, tl_dict_builder_finish,
+    tl_dict_builder_new, tl_dict_builder_push_ext, tl_dict_free, tl_dict_lookup, tl_exp, tl_floor,
+    tl_last_error, tl_log, tl_round, tl_sqrt, tl_trace, tl_value_from_int,
+    tl_value_from_int_result, tl_value_from_static_str, tl_value_release, tl_value_to_int,
+    tl_value_to_ptr, tl_value_to_str, TlStatus,
 };
 
 #[test]
@@ -19,6 +24,117 @@ fn value_result_retains_status() {
     unsafe { tl_value_release(value) };
 }
 
+#[test]
+fn identical_string_literals_share_one_value() {
+    // 別々の領域に置かれた同じ内容のリテラルを想定する。
+    static FIRST: [u8; 3] = *b"foo";
+    static SECOND: [u8; 3] = *b"foo";
+    unsafe {
+        let a = tl_value_from_static_str(FIRST.as_ptr(), FIRST.len());
+        let b = tl_value_from_static_str(SECOND.as_ptr(), SECOND.len());
+        assert_eq!(tl_last_error(), TlStatus::Ok);
+        assert_eq!(a.as_raw(), b.as_raw());
+        let other = tl_value_from_static_str(b"bar".as_ptr(), 3);
+        assert_ne!(a.as_raw(), other.as_raw());
+
+        // バイト列は複製されず、解放しても共有値は残る。
+        let mut len = 0usize;
+        let ptr = tl_value_to_str(a, &mut len);
+        assert_eq!(ptr, FIRST.as_ptr());
+        assert_eq!(len, 3);
+        tl_value_release(a);
+        assert_eq!(tl_value_to_str(b, &mut len), FIRST.as_ptr());
+        assert_eq!(tl_last_error(), TlStatus::Ok);
+
+        assert!(tl_value_from_static_str(b"\xff".as_ptr(), 1)
+            .as_raw()
+            .is_null());
+        assert_eq!(tl_last_error(), TlStatus::InvalidArgument);
+    }
+}
+
+#[test]
+fn trace_returns_second_argument() {
+    let message = tl_value_from_int(1);
+    let value = tl_value_from_int(7);
+    let traced = tl_trace(message, value);
+    assert_eq!(traced.as_raw(), value.as_raw());
+    unsafe {
+        assert_eq!(tl_value_to_int(traced), 7);
+        tl_value_release(message);
+        tl_value_release(value);
+    }
+}
+
+#[test]
+fn data_field_reads_within_arity() {
+    let fields = [tl_value_from_int(3), tl_value_from_int(4)];
+    unsafe {
+        let data = tl_data_pack(1, fields.as_ptr(), fields.len());
+        assert_eq!(tl_data_arity(data), 2);
+        assert_eq!(tl_value_to_int(tl_data_field(data, 1)), 4);
+        assert_eq!(tl_last_error(), TlStatus::Ok);
+        tl_data_free(data);
+    }
+}
+
+#[cfg(feature = "arena")]
+#[test]
+fn arena_values_survive_until_reset() {
+    use runtime_native::{tl_arena_reset, tl_list_cons, tl_list_empty, tl_list_free, tl_list_head};
+    unsafe {
+        let mut list = tl_list_empty();
+        for value in 0..10_000 {
+            list = tl_list_cons(tl_value_from_int(value), list);
+        }
+        let fields = [tl_value_from_int(1), tl_value_from_int(2)];
+        let data = tl_data_pack(0, fields.as_ptr(), fields.len());
+        // アリーナ上の値は個別に解放しても残る。
+        tl_data_free(data);
+        tl_list_free(list);
+        assert_eq!(tl_value_to_int(tl_list_head(list)), 9_999);
+        assert_eq!(tl_value_to_int(tl_data_field(data, 1)), 2);
+        tl_arena_reset();
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn data_field_out_of_range_aborts() {
+    // 中断はプロセスごと終了させるため、このテスト自身を子プロセスとして再実行して確認する。
+    if std::env::var_os("TL_RUNTIME_DATA_FIELD_CHILD").is_some() {
+        let fields = [tl_value_from_int(3)];
+        unsafe {
+            let data = tl_data_pack(0, fields.as_ptr(), fields.len());
+            tl_data_field(data, 1);
+        }
+        unreachable!("tl_data_field must abort on out-of-range index");
+    }
+    let output = Command::new(std::env::current_exe().expect("test binary path"))
+        .args(["--exact", "data_field_out_of_range_aborts", "--nocapture"])
+        .env("TL_RUNTIME_DATA_FIELD_CHILD", "1")
+        .output()
+        .expect("spawn child test");
+    assert!(!output.status.success(), "out-of-range access must abort");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("out of range"), "stderr: {stderr}");
+    assert!(stderr.contains("code=2003"), "stderr: {stderr}");
+}
+
+#[test]
+fn stack_depth_tracks_enter_and_leave() {
+    use runtime_native::{tl_stack_depth, tl_stack_enter, tl_stack_leave, tl_stack_set_limit};
+    tl_stack_set_limit(3);
+    tl_stack_enter();
+    tl_stack_enter();
+    assert_eq!(tl_stack_depth(), 2);
+    tl_stack_leave();
+    tl_stack_leave();
+    assert_eq!(tl_stack_depth(), 0);
+    tl_stack_leave();
+    assert_eq!(tl_stack_depth(), 0);
+}
+
 #[test]
 fn dictionary_builder_supports_metadata() {
     unsafe {
@@ -36,6 +152,56 @@ fn dictionary_builder_supports_metadata() {
     }
 }
 
+#[test]
+fn dictionary_lookup_handles_gaps_and_sparse_method_ids() {
+    unsafe {
+        // 番号順でない登録と欠番を含む、直接索引で引ける辞書。
+        let builder = tl_dict_builder_new(c"Dense".as_ptr());
+        for id in [3u64, 0, 1] {
+            tl_dict_builder_push_ext(
+                builder,
+                c"m".as_ptr(),
+                id,
+                std::ptr::null(),
+                tl_value_from_int(id as i64 * 10),
+            );
+        }
+        let dense = tl_dict_builder_finish(builder);
+        tl_dict_builder_dispose(builder);
+        for id in [0u64, 1, 3] {
+            assert_eq!(tl_value_to_int(tl_dict_lookup(dense, id)), id as i64 * 10);
+            assert_eq!(tl_last_error(), TlStatus::Ok);
+        }
+        assert!(tl_dict_lookup(dense, 2).as_raw().is_null());
+        assert_eq!(tl_last_error(), TlStatus::InvalidArgument);
+        assert!(tl_dict_lookup(dense, 4).as_raw().is_null());
+        assert_eq!(tl_last_error(), TlStatus::InvalidArgument);
+        tl_dict_free(dense);
+
+        // 番号が大きく疎な辞書は二分探索で引く。
+        let builder = tl_dict_builder_new(c"Sparse".as_ptr());
+        for id in [1_000_000u64, 7] {
+            tl_dict_builder_push_ext(
+                builder,
+                c"m".as_ptr(),
+                id,
+                std::ptr::null(),
+                tl_value_from_int(id as i64),
+            );
+        }
+        let sparse = tl_dict_builder_finish(builder);
+        tl_dict_builder_dispose(builder);
+        assert_eq!(
+            tl_value_to_int(tl_dict_lookup(sparse, 1_000_000)),
+            1_000_000
+        );
+        assert_eq!(tl_value_to_int(tl_dict_lookup(sparse, 7)), 7);
+        assert!(tl_dict_lookup(sparse, 8).as_raw().is_null());
+        assert_eq!(tl_last_error(), TlStatus::InvalidArgument);
+        tl_dict_free(sparse);
+    }
+}
+
 #[test]
 fn eq_dictionary_exposes_methods() {
     unsafe {
@@ -50,6 +216,49 @@ fn eq_dictionary_exposes_methods() {
     }
 }
 
+#[test]
+fn list_and_tuple_comparisons_are_structural() {
+    use runtime_native::{
+        tl_eq_list, tl_eq_tuple, tl_list_cons, tl_list_empty, tl_neq_list, tl_ord_list_ge,
+        tl_ord_list_lt, tl_ord_tuple_lt, tl_value_from_ptr,
+    };
+    unsafe {
+        let ints = |items: &[i64]| {
+            items.iter().rev().fold(tl_list_empty(), |tail, item| {
+                tl_list_cons(tl_value_from_int(*item), tail)
+            })
+        };
+        let a = ints(&[1, 2, 3]);
+        let b = ints(&[1, 2, 3]);
+        let c = ints(&[1, 2]);
+        assert_eq!(tl_eq_list(a, b), 1);
+        assert_eq!(tl_neq_list(a, c), 1);
+        assert_eq!(tl_ord_list_lt(c, a), 1);
+        assert_eq!(tl_ord_list_ge(a, b), 1);
+        assert_eq!(tl_last_error(), TlStatus::Ok);
+
+        // 入れ子のリストはポインタ値として格納され、再帰的に比較される。
+        let nested = |inner: *mut runtime_native::TlListNode| {
+            tl_list_cons(tl_value_from_ptr(inner.cast()), tl_list_empty())
+        };
+        assert_eq!(tl_eq_list(nested(a), nested(b)), 1);
+        assert_eq!(tl_ord_list_lt(nested(c), nested(a)), 1);
+
+        let pair = |x: i64, y: i64| {
+            let fields = [tl_value_from_int(x), tl_value_from_int(y)];
+            tl_data_pack(0, fields.as_ptr(), fields.len())
+        };
+        assert_eq!(tl_eq_tuple(pair(1, 2), pair(1, 2)), 1);
+        assert_eq!(tl_ord_tuple_lt(pair(1, 2), pair(1, 3)), 1);
+        assert_eq!(tl_ord_tuple_lt(pair(2, 0), pair(1, 3)), 0);
+
+        // 種別の異なる要素は比較できない。
+        let mixed = tl_list_cons(tl_value_from_ptr(a.cast()), tl_list_empty());
+        assert_eq!(tl_eq_list(mixed, ints(&[1])), 0);
+        assert_eq!(tl_last_error(), TlStatus::InvalidArgument);
+    }
+}
+
 #[test]
 fn bool_logic_dictionary_supports_unary_method() {
     unsafe {
@@ -61,3 +270,250 @@ fn bool_logic_dictionary_supports_unary_method() {
         tl_dict_free(dict);
     }
 }
+
+#[test]
+fn math_wrappers_follow_f64_semantics() {
+    assert_eq!(tl_sqrt(16.0), 4.0);
+    assert!(tl_sqrt(-1.0).is_nan());
+    assert_eq!(tl_log(tl_exp(2.0)), 2.0);
+    assert_eq!(tl_log(0.0), f64::NEG_INFINITY);
+}
+
+#[test]
+fn rounding_wrappers_round_half_to_even() {
+    assert_eq!(tl_floor(-2.5), -3);
+    assert_eq!(tl_round(2.5), 2);
+    assert_eq!(tl_round(3.5), 4);
+}
+
+#[test]
+fn map_insert_is_persistent_and_keys_are_structural() {
+    use runtime_native::{
+        tl_data_tag, tl_list_cons, tl_list_empty, tl_map_empty, tl_map_find_with_default,
+        tl_map_insert, tl_map_len, tl_map_lookup, tl_value_from_double, tl_value_from_ptr,
+    };
+    // `Maybe` の構築子のタグ（`data Maybe a = Nothing | Just a`）。
+    const NOTHING: u32 = 0;
+    const JUST: u32 = 1;
+    unsafe {
+        let empty = tl_map_empty();
+        let one = tl_map_insert(empty, tl_value_from_int(1), tl_value_from_int(10));
+        let two = tl_map_insert(one, tl_value_from_int(2), tl_value_from_int(20));
+        let replaced = tl_map_insert(two, tl_value_from_int(1), tl_value_from_int(11));
+        assert_eq!(tl_map_len(empty), 0);
+        assert_eq!(tl_map_len(two), 2);
+        assert_eq!(tl_map_len(replaced), 2);
+
+        let found = tl_map_lookup(replaced, tl_value_from_int(1), NOTHING, JUST);
+        assert_eq!(tl_data_tag(found), JUST);
+        assert_eq!(tl_value_to_int(tl_data_field(found, 0)), 11);
+        let earlier = tl_map_lookup(two, tl_value_from_int(1), NOTHING, JUST);
+        assert_eq!(tl_value_to_int(tl_data_field(earlier, 0)), 10);
+        let missing = tl_map_lookup(one, tl_value_from_int(2), NOTHING, JUST);
+        assert_eq!(tl_data_tag(missing), NOTHING);
+        assert_eq!(tl_data_arity(missing), 0);
+        let fallback = tl_map_find_with_default(one, tl_value_from_int(2), tl_value_from_int(-1));
+        assert_eq!(tl_value_to_int(fallback), -1);
+
+        // 別々に構築した同じ内容のリストは同じキーとして扱われる。
+        let key = || {
+            let list = tl_list_cons(tl_value_from_int(7), tl_list_empty());
+            tl_value_from_ptr(list.cast())
+        };
+        let listed = tl_map_insert(empty, key(), tl_value_from_int(70));
+        let found = tl_map_lookup(listed, key(), NOTHING, JUST);
+        assert_eq!(tl_value_to_int(tl_data_field(found, 0)), 70);
+
+        // NaN はハッシュできないため挿入されない。
+        let unchanged = tl_map_insert(empty, tl_value_from_double(f64::NAN), key());
+        assert_eq!(tl_last_error(), TlStatus::InvalidArgument);
+        assert_eq!(tl_map_len(unchanged), 0);
+    }
+}
+
+#[test]
+fn array_indexing_reads_contiguous_items() {
+    use runtime_native::{
+        tl_array_from_list, tl_array_get, tl_array_len, tl_array_new, tl_list_cons, tl_list_empty,
+    };
+    unsafe {
+        let items = [
+            tl_value_from_int(4),
+            tl_value_from_int(5),
+            tl_value_from_int(6),
+        ];
+        let array = tl_array_new(items.as_ptr(), items.len());
+        assert_eq!(tl_array_len(array), 3);
+        assert_eq!(tl_value_to_int(tl_array_get(array, 0)), 4);
+        assert_eq!(tl_value_to_int(tl_array_get(array, 2)), 6);
+
+        let list = [7, 8].iter().rev().fold(tl_list_empty(), |tail, item| {
+            tl_list_cons(tl_value_from_int(*item), tail)
+        });
+        let from_list = tl_array_from_list(list);
+        assert_eq!(tl_array_len(from_list), 2);
+        assert_eq!(tl_value_to_int(tl_array_get(from_list, 1)), 8);
+        assert_eq!(tl_array_len(tl_array_from_list(tl_list_empty())), 0);
+        assert_eq!(tl_last_error(), TlStatus::Ok);
+    }
+}
+
+#[test]
+fn args_list_skips_program_name_and_keeps_order() {
+    use runtime_native::{tl_args_list, tl_list_head, tl_list_is_empty, tl_list_tail};
+    use std::ffi::c_char;
+    static ARGS: [&[u8]; 4] = [b"prog\0", b"one\0", b"\xe3\x81\x82\0", b"\xff\0"];
+    let argv: Vec<*const c_char> = ARGS.iter().map(|a| a.as_ptr().cast()).collect();
+    unsafe {
+        let mut list = tl_args_list(argv.len() as i32, argv.as_ptr());
+        let mut seen = Vec::new();
+        while !tl_list_is_empty(list) {
+            let mut len = 0usize;
+            let ptr = tl_value_to_str(tl_list_head(list), &mut len);
+            seen.push(String::from_utf8_lossy(std::slice::from_raw_parts(ptr, len)).into_owned());
+            list = tl_list_tail(list);
+        }
+        assert_eq!(seen, ["one", "あ", "\u{fffd}"]);
+        assert!(tl_list_is_empty(tl_args_list(1, argv.as_ptr())));
+        assert!(tl_list_is_empty(tl_args_list(0, std::ptr::null())));
+    }
+}
+
+#[test]
+fn replicate_take_and_drop_clamp_counts() {
+    use runtime_native::{
+        tl_list_cons, tl_list_drop, tl_list_empty, tl_list_length, tl_list_replicate, tl_list_take,
+    };
+    unsafe {
+        assert_eq!(
+            tl_list_length(tl_list_replicate(4, tl_value_from_int(1))),
+            4
+        );
+        assert_eq!(
+            tl_list_length(tl_list_replicate(-1, tl_value_from_int(1))),
+            0
+        );
+        let list = tl_list_cons(
+            tl_value_from_int(1),
+            tl_list_cons(tl_value_from_int(2), tl_list_empty()),
+        );
+        assert_eq!(tl_list_length(tl_list_take(1, list)), 1);
+        assert_eq!(tl_list_length(tl_list_take(5, list)), 2);
+        assert_eq!(tl_list_length(tl_list_take(-3, list)), 0);
+        assert_eq!(
+            tl_value_to_int(runtime_native::tl_list_head(tl_list_drop(1, list))),
+            2
+        );
+        assert_eq!(tl_list_drop(0, list), list);
+        assert_eq!(tl_list_length(tl_list_drop(7, list)), 0);
+    }
+}
+
+#[test]
+fn append_copies_left_list_and_shares_right_list() {
+    use runtime_native::{
+        tl_list_append, tl_list_cons, tl_list_drop, tl_list_empty, tl_list_head, tl_list_length,
+        tl_string_append,
+    };
+    unsafe {
+        let left = tl_list_cons(tl_value_from_int(1), tl_list_empty());
+        let right = tl_list_cons(
+            tl_value_from_int(2),
+            tl_list_cons(tl_value_from_int(3), tl_list_empty()),
+        );
+        let joined = tl_list_append(left, right);
+        assert_eq!(tl_list_length(joined), 3);
+        assert_eq!(tl_value_to_int(tl_list_head(joined)), 1);
+        assert_eq!(tl_list_drop(1, joined), right);
+        assert_eq!(tl_list_length(left), 1);
+        assert_eq!(tl_list_append(tl_list_empty(), right), right);
+
+        let text = tl_string_append(
+            tl_value_from_static_str(b"ab".as_ptr(), 2),
+            tl_value_from_static_str(b"cd".as_ptr(), 2),
+        );
+        let mut len = 0usize;
+        let ptr = tl_value_to_str(text, &mut len);
+        assert_eq!(std::slice::from_raw_parts(ptr, len), b"abcd");
+        assert!(tl_string_append(text, tl_value_from_int(1))
+            .as_raw()
+            .is_null());
+    }
+}
+
+#[test]
+fn format_fills_specifiers_in_order() {
+    use runtime_native::{tl_format, tl_list_cons, tl_list_empty, tl_value_from_double};
+    unsafe {
+        let template = tl_value_from_static_str(b"%d + %f".as_ptr(), 7);
+        let args = tl_list_cons(
+            tl_value_from_int(1),
+            tl_list_cons(tl_value_from_double(2.0), tl_list_empty()),
+        );
+        let mut len = 0usize;
+        let ptr = tl_value_to_str(tl_format(template, args), &mut len);
+        assert_eq!(std::slice::from_raw_parts(ptr, len), b"1 + 2.0");
+    }
+}
+
+#[test]
+fn read_parses_trimmed_numbers_into_maybe() {
+    use runtime_native::{tl_data_tag, tl_read_double, tl_read_int, tl_value_to_double};
+    // `Maybe` の構築子のタグ（`data Maybe a = Nothing | Just a`）。
+    const NOTHING: u32 = 0;
+    const JUST: u32 = 1;
+    unsafe {
+        let parsed = tl_read_int(tl_value_from_static_str(b" 7 ".as_ptr(), 3), NOTHING, JUST);
+        assert_eq!(tl_data_tag(parsed), JUST);
+        assert_eq!(tl_value_to_int(tl_data_field(parsed, 0)), 7);
+        let parsed = tl_read_double(tl_value_from_static_str(b"0.25".as_ptr(), 4), NOTHING, JUST);
+        assert_eq!(tl_value_to_double(tl_data_field(parsed, 0)), 0.25);
+        let unread = tl_read_int(tl_value_from_static_str(b"7a".as_ptr(), 2), NOTHING, JUST);
+        assert_eq!(tl_data_tag(unread), NOTHING);
+        assert_eq!(tl_data_arity(unread), 0);
+        let not_string = tl_read_double(tl_value_from_int(3), NOTHING, JUST);
+        assert_eq!(tl_data_tag(not_string), NOTHING);
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn array_index_out_of_range_aborts() {
+    use runtime_native::{tl_array_get, tl_array_new};
+    // data_field_out_of_range_aborts と同じく、子プロセスとして再実行して中断を確認する。
+    if std::env::var_os("TL_RUNTIME_ARRAY_INDEX_CHILD").is_some() {
+        let items = [tl_value_from_int(3)];
+        unsafe {
+            let array = tl_array_new(items.as_ptr(), items.len());
+            tl_array_get(array, -1);
+        }
+        unreachable!("tl_array_get must abort on out-of-range index");
+    }
+    let output = Command::new(std::env::current_exe().expect("test binary path"))
+        .args(["--exact", "array_index_out_of_range_aborts", "--nocapture"])
+        .env("TL_RUNTIME_ARRAY_INDEX_CHILD", "1")
+        .output()
+        .expect("spawn child test");
+    assert!(!output.status.success(), "out-of-range access must abort");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("array index -1 is out of range"),
+        "stderr: {stderr}"
+    );
+    assert!(stderr.contains("code=2005"), "stderr: {stderr}");
+}
+
+#[test]
+fn ref_cells_are_updated_in_place() {
+    use runtime_native::{tl_ref_new, tl_ref_read, tl_ref_write};
+    unsafe {
+        let cell = tl_ref_new(tl_value_from_int(1));
+        assert_eq!(tl_value_to_int(tl_ref_read(cell)), 1);
+        tl_ref_write(cell, tl_value_from_int(2));
+        assert_eq!(tl_value_to_int(tl_ref_read(cell)), 2);
+        assert_eq!(tl_last_error(), TlStatus::Ok);
+
+        tl_ref_write(std::ptr::null_mut(), tl_value_from_int(3));
+        assert_eq!(tl_last_error(), TlStatus::NullPointer);
+    }
+}
@@ -1,7 +1,7 @@
 // パス: src/ast.rs
 // 役割: 抽象構文木(AST)の型定義と表示ユーティリティを管理する
 // 意図: パーサ・型推論・評価器が同じデータ構造を共有できるように整える
-// 関連ファイル: src/parser.rs, src/infer.rs, src/evaluator.rs
+// 関連ファイル: src/parser.rs, src/infer.rs, src/evaluator.rs, src/ast/pretty.rs
 //! AST モジュール
 //!
 //! 概要:
@@ -11,6 +11,8 @@
 
 use std::fmt;
 
+pub mod pretty;
+
 /// ソース上の位置情報を保持する軽量な構造体。
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Span {
@@ -193,10 +195,20 @@ pub struct CaseArm {
 
 // 型クラス制約とシグマ型
 #[derive(Clone, Debug, PartialEq, Eq)]
-/// 型クラス名と対象の型変数を結び付ける制約。
+/// 型クラス名と対象の型式を結び付ける制約。
 pub struct Constraint {
     pub classname: String,
-    pub typevar: String,
+    pub r#type: TypeExpr,
+}
+
+impl Constraint {
+    /// 対象が単一の型変数 (`Class a`) であればその名前を返す。
+    pub fn typevar(&self) -> Option<&str> {
+        match &self.r#type {
+            TypeExpr::TEVar(name) => Some(name),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -214,6 +226,8 @@ pub struct TopLevel {
     pub params: Vec<String>,
     pub expr: Expr,
     pub signature: Option<SigmaType>,
+    /// 直前の `-- |` ドキュメントコメント（複数行は改行で連結）。
+    pub doc: Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -230,6 +244,17 @@ pub struct DataDecl {
     pub name: String,
     pub params: Vec<String>,
     pub constructors: Vec<DataConstructor>,
+    /// `newtype` で宣言された場合は true（単一コンストラクタ・単一フィールド）。
+    pub newtype: bool,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// `type Name a = ...` 形式の型シノニム宣言。
+pub struct TypeSynonymDecl {
+    pub name: String,
+    pub params: Vec<String>,
+    pub r#type: TypeExpr,
     pub span: Span,
 }
 
@@ -250,20 +275,75 @@ pub struct InstanceDecl {
     pub span: Span,
 }
 
+/// 中置演算子の結合性。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Associativity {
+    Left,
+    Right,
+    None,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// `infixl 6 <+>` のような結合性宣言を表現する。
+pub struct FixityDecl {
+    pub assoc: Associativity,
+    pub precedence: u8,
+    pub operators: Vec<String>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// `import "other.tl"` によるファイル取り込み宣言。パスは取り込み元ファイルからの相対パス。
+pub struct ImportDecl {
+    pub path: String,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// `module Foo (bar, baz)` によるモジュール宣言。`exports` が `None` なら全トップレベル定義を公開する。
+pub struct ModuleDecl {
+    pub name: String,
+    pub exports: Option<Vec<String>>,
+    pub span: Span,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 /// トップレベル定義の集まりとしてのプログラム。
 pub struct Program {
+    /// ファイル単位では高々 1 件。複数ファイルを連結したプログラムではファイルごとに 1 件ずつ持つ。
+    pub modules: Vec<ModuleDecl>,
+    pub imports: Vec<ImportDecl>,
     pub class_decls: Vec<ClassDecl>,
     pub instance_decls: Vec<InstanceDecl>,
     pub data_decls: Vec<DataDecl>,
+    pub type_synonyms: Vec<TypeSynonymDecl>,
+    pub fixities: Vec<FixityDecl>,
     pub decls: Vec<TopLevel>,
 }
 
+impl Program {
+    /// モジュール宣言に基づき、`(修飾名, 参照先の定義名)` の組を列挙する。
+    pub fn qualified_aliases(&self) -> Vec<(String, String)> {
+        let mut aliases = Vec::new();
+        for module in &self.modules {
+            let exported: Vec<&str> = match &module.exports {
+                Some(names) => names.iter().map(String::as_str).collect(),
+                None => self.decls.iter().map(|d| d.name.as_str()).collect(),
+            };
+            for name in exported {
+                aliases.push((format!("{}.{}", module.name, name), name.to_string()));
+            }
+        }
+        aliases
+    }
+}
+
 /// 式ノードを文字列表現へ整形する。
 impl fmt::Display for Expr {
     /// デバッグしやすい括弧付きの表記に変換する。
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            Expr::Var { name, .. } if is_operator_name(name) => write!(f, "({name})"),
             Expr::Var { name, .. } => write!(f, "{name}"),
             Expr::IntLit { value, .. } => write!(f, "{value}"),
             Expr::FloatLit { value, .. } => write!(f, "{value}"),
@@ -282,6 +362,11 @@ impl fmt::Display for Expr {
             Expr::LetIn { bindings, body, .. } => {
                 let mut parts = Vec::new();
                 for (n, ps, e) in bindings {
+                    let n = if is_operator_name(n) {
+                        format!("({n})")
+                    } else {
+                        n.clone()
+                    };
                     if ps.is_empty() {
                         parts.push(format!("{n} = {e}"));
                     } else {
@@ -324,6 +409,11 @@ impl fmt::Display for Expr {
     }
 }
 
+/// 名前が記号だけからなる演算子名（`|>` など）かどうか。
+pub fn is_operator_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(crate::lexer::is_symbol_char)
+}
+
 impl Expr {
     /// 現在の式に紐づく開始位置を返す。
     pub fn span(&self) -> Span {
@@ -0,0 +1,649 @@
+// パス: src/ast/pretty.rs
+// 役割: AST を再びパース可能な TypeLang ソースへ書き戻すプリティプリンタ
+// 意図: `parse(to_source(ast))` が元の AST と一致することを保証し、整形や保存などのツールの土台にする
+// 関連ファイル: src/ast.rs, src/parser/expr.rs, src/parser/fixity.rs, tests/ast_pretty.rs
+//! AST プリティプリンタ
+//!
+//! - 中置演算子は結合性テーブルの優先順位・結合性から括弧の要否を判断し、必要最小限の括弧だけを補う。
+//! - 単項マイナスはパーサが `negate x` の適用へ糖衣展開しているため `-x` に戻す。
+//!   ただし数値リテラルへの `-` はパース時に負のリテラルへ畳み込まれるので、その場合は `negate` のまま出力する。
+//! - `case` は後続の `; pat -> ...` を自分のアームとして取り込むため、`;` が続く位置では括弧で囲む。
+//! - レイアウトは保持しない。各トップレベル宣言を 1 行ずつ出力する。
+//! - `format_source` はこの出力を整形結果として使う。型シノニムは展開せず書かれたまま残すが、
+//!   AST に載らない通常のコメントは失われる（`-- |` のドキュメントコメントは保持する）。
+
+use super::{
+    is_operator_name, Associativity, CaseArm, ClassDecl, DataDecl, Expr, FixityDecl, IntBase,
+    Pattern, Program, SigmaType, TopLevel, TypeExpr,
+};
+use crate::errors::ParseErrors;
+use crate::parser::{parse_program_unexpanded, Fixity, FixityTable};
+
+/// プログラム全体をソース文字列へ変換する。結合性はプログラム内の宣言を既定値へ重ねて用いる。
+pub fn to_source(prog: &Program) -> String {
+    let mut fixities = FixityTable::default();
+    for decl in &prog.fixities {
+        fixities.declare(decl);
+    }
+    let printer = Printer {
+        fixities: &fixities,
+    };
+    let mut out = String::new();
+    for module in &prog.modules {
+        out.push_str("module ");
+        out.push_str(&module.name);
+        if let Some(exports) = &module.exports {
+            let names: Vec<String> = exports.iter().map(|name| binder_name(name)).collect();
+            out.push_str(&format!(" ({})", names.join(", ")));
+        }
+        out.push_str(" where;\n");
+    }
+    for import in &prog.imports {
+        out.push_str(&format!("import {};\n", quote_string(&import.path)));
+    }
+    for fixity in &prog.fixities {
+        printer.fixity_decl(fixity, &mut out);
+    }
+    for synonym in &prog.type_synonyms {
+        out.push_str("type ");
+        out.push_str(&synonym.name);
+        for param in &synonym.params {
+            out.push(' ');
+            out.push_str(param);
+        }
+        out.push_str(" = ");
+        printer.ty(&synonym.r#type, &mut out);
+        out.push_str(";\n");
+    }
+    for data in &prog.data_decls {
+        printer.data_decl(data, &mut out);
+    }
+    for class in &prog.class_decls {
+        printer.class_decl(class, &mut out);
+    }
+    for instance in &prog.instance_decls {
+        out.push_str(&format!(
+            "instance {} {};\n",
+            instance.classname, instance.tycon
+        ));
+    }
+    for decl in &prog.decls {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        printer.top_level(decl, &mut out);
+    }
+    out
+}
+
+/// 単一の式をソース文字列へ変換する（組み込み演算子の既定の結合性を用いる）。
+pub fn expr_to_source(expr: &Expr) -> String {
+    expr_to_source_with_fixities(expr, &FixityTable::default())
+}
+
+/// 与えた結合性テーブルに従って単一の式をソース文字列へ変換する。
+pub fn expr_to_source_with_fixities(expr: &Expr, fixities: &FixityTable) -> String {
+    let mut out = String::new();
+    Printer { fixities }.expr(expr, Ctx::Tail, &mut out);
+    out
+}
+
+/// ソースを解析し、正規化した書式で出力し直す。
+///
+/// 宣言は種類ごとにまとめ、シグネチャは対応する定義の直前に置き、各定義は `let name params = body;` の
+/// 1 行にそろえる。出力は再び整形しても変わらない。
+///
+/// # Examples
+/// ```
+/// use typelang::ast::pretty::format_source;
+///
+/// let formatted = format_source("let   main=(1+2)*3 ;").unwrap();
+/// assert_eq!(formatted, "let main = (1 + 2) * 3;\n");
+/// ```
+pub fn format_source(src: &str) -> Result<String, ParseErrors> {
+    Ok(to_source(&parse_program_unexpanded(src)?))
+}
+
+/// 式を書き出す位置。直後に続くトークンによって括弧が必要な式が変わる。
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Ctx {
+    /// `)`・`,`・`then`・`in` などで確実に閉じる位置。
+    Tail,
+    /// 直後に `;` が続く位置（let 束縛・途中の case アーム・トップレベル定義）。
+    BeforeSemi,
+    /// case のガード。直後の `->` が型注釈や式に取り込まれないよう括弧で閉じる。
+    Guard,
+}
+
+#[derive(Clone, Copy)]
+enum Side {
+    Left,
+    Right,
+}
+
+struct Printer<'a> {
+    fixities: &'a FixityTable,
+}
+
+impl Printer<'_> {
+    fn top_level(&self, decl: &TopLevel, out: &mut String) {
+        if let Some(doc) = &decl.doc {
+            for (idx, line) in doc.split('\n').enumerate() {
+                out.push_str(if idx == 0 { "-- |" } else { "--" });
+                if !line.is_empty() {
+                    out.push(' ');
+                    out.push_str(line);
+                }
+                out.push('\n');
+            }
+        }
+        let name = binder_name(&decl.name);
+        if let Some(sig) = &decl.signature {
+            out.push_str(&name);
+            out.push_str(" :: ");
+            self.sigma(sig, out);
+            out.push_str(";\n");
+        }
+        out.push_str("let ");
+        out.push_str(&name);
+        for param in &decl.params {
+            out.push(' ');
+            out.push_str(param);
+        }
+        out.push_str(" = ");
+        self.expr(&decl.expr, Ctx::BeforeSemi, out);
+        out.push_str(";\n");
+    }
+
+    fn fixity_decl(&self, decl: &FixityDecl, out: &mut String) {
+        let keyword = match decl.assoc {
+            Associativity::Left => "infixl",
+            Associativity::Right => "infixr",
+            Associativity::None => "infix",
+        };
+        let ops: Vec<String> = decl
+            .operators
+            .iter()
+            .map(|op| {
+                if is_operator_name(op) {
+                    op.clone()
+                } else {
+                    format!("`{op}`")
+                }
+            })
+            .collect();
+        out.push_str(&format!(
+            "{keyword} {} {};\n",
+            decl.precedence,
+            ops.join(", ")
+        ));
+    }
+
+    fn data_decl(&self, data: &DataDecl, out: &mut String) {
+        out.push_str(if data.newtype { "newtype " } else { "data " });
+        out.push_str(&data.name);
+        for param in &data.params {
+            out.push(' ');
+            out.push_str(param);
+        }
+        out.push_str(" =");
+        for (idx, ctor) in data.constructors.iter().enumerate() {
+            if idx > 0 {
+                out.push_str(" |");
+            }
+            out.push(' ');
+            out.push_str(&ctor.name);
+            for arg in &ctor.args {
+                out.push(' ');
+                self.ty_app(arg, out);
+            }
+        }
+        out.push_str(";\n");
+    }
+
+    fn class_decl(&self, class: &ClassDecl, out: &mut String) {
+        out.push_str("class ");
+        let typevar = class.typevar.as_deref().unwrap_or("a");
+        match class.superclasses.as_slice() {
+            [] => {}
+            [single] => out.push_str(&format!("{single} {typevar} => ")),
+            many => {
+                let parts: Vec<String> = many.iter().map(|c| format!("{c} {typevar}")).collect();
+                out.push_str(&format!("({}) => ", parts.join(", ")));
+            }
+        }
+        out.push_str(&class.name);
+        if let Some(var) = &class.typevar {
+            out.push(' ');
+            out.push_str(var);
+        }
+        out.push_str(";\n");
+    }
+
+    fn sigma(&self, sig: &SigmaType, out: &mut String) {
+        match sig.constraints.as_slice() {
+            [] => {}
+            [single] => {
+                out.push_str(&single.classname);
+                out.push(' ');
+                self.ty_atom(&single.r#type, out);
+                out.push_str(" => ");
+            }
+            many => {
+                out.push('(');
+                for (idx, constraint) in many.iter().enumerate() {
+                    if idx > 0 {
+                        out.push_str(", ");
+                    }
+                    out.push_str(&constraint.classname);
+                    out.push(' ');
+                    self.ty_atom(&constraint.r#type, out);
+                }
+                out.push_str(") => ");
+            }
+        }
+        self.ty(&sig.r#type, out);
+    }
+
+    fn ty(&self, ty: &TypeExpr, out: &mut String) {
+        match ty {
+            TypeExpr::TEFun(arg, ret) => {
+                self.ty_app(arg, out);
+                out.push_str(" -> ");
+                self.ty(ret, out);
+            }
+            _ => self.ty_app(ty, out),
+        }
+    }
+
+    fn ty_app(&self, ty: &TypeExpr, out: &mut String) {
+        match ty {
+            TypeExpr::TEApp(func, arg) => {
+                self.ty_app(func, out);
+                out.push(' ');
+                self.ty_atom(arg, out);
+            }
+            _ => self.ty_atom(ty, out),
+        }
+    }
+
+    fn ty_atom(&self, ty: &TypeExpr, out: &mut String) {
+        match ty {
+            TypeExpr::TEVar(name) | TypeExpr::TECon(name) => out.push_str(name),
+            TypeExpr::TEList(inner) => {
+                out.push('[');
+                self.ty(inner, out);
+                out.push(']');
+            }
+            TypeExpr::TETuple(items) => {
+                out.push('(');
+                for (idx, item) in items.iter().enumerate() {
+                    if idx > 0 {
+                        out.push_str(", ");
+                    }
+                    self.ty(item, out);
+                }
+                out.push(')');
+            }
+            TypeExpr::TEApp(..) | TypeExpr::TEFun(..) => {
+                out.push('(');
+                self.ty(ty, out);
+                out.push(')');
+            }
+        }
+    }
+
+    /// 式全体を書き出す。`ctx` に応じて開いた式（ラムダ・let・if・case・注釈）を括弧で閉じる。
+    fn expr(&self, expr: &Expr, ctx: Ctx, out: &mut String) {
+        let needs_parens = match ctx {
+            Ctx::Tail => false,
+            Ctx::BeforeSemi => ends_with_case(expr),
+            Ctx::Guard => is_open(expr),
+        };
+        if needs_parens {
+            self.parens(expr, out);
+            return;
+        }
+        match expr {
+            Expr::Lambda { params, body, .. } => {
+                out.push('\\');
+                out.push_str(&params.join(" "));
+                out.push_str(" -> ");
+                self.expr(body, ctx, out);
+            }
+            Expr::LetIn { bindings, body, .. } => {
+                out.push_str("let ");
+                for (idx, (name, params, rhs)) in bindings.iter().enumerate() {
+                    if idx > 0 {
+                        out.push_str("; ");
+                    }
+                    out.push_str(&binder_name(name));
+                    for param in params {
+                        out.push(' ');
+                        out.push_str(param);
+                    }
+                    out.push_str(" = ");
+                    let rhs_ctx = if idx + 1 < bindings.len() {
+                        Ctx::BeforeSemi
+                    } else {
+                        Ctx::Tail
+                    };
+                    self.expr(rhs, rhs_ctx, out);
+                }
+                out.push_str(" in ");
+                self.expr(body, ctx, out);
+            }
+            Expr::If {
+                cond,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                out.push_str("if ");
+                self.expr(cond, Ctx::Tail, out);
+                out.push_str(" then ");
+                self.expr(then_branch, Ctx::Tail, out);
+                out.push_str(" else ");
+                self.expr(else_branch, ctx, out);
+            }
+            Expr::Case {
+                scrutinee, arms, ..
+            } => {
+                out.push_str("case ");
+                self.expr(scrutinee, Ctx::Tail, out);
+                out.push_str(" of ");
+                for (idx, arm) in arms.iter().enumerate() {
+                    if idx > 0 {
+                        out.push_str("; ");
+                    }
+                    let body_ctx = if idx + 1 < arms.len() {
+                        Ctx::BeforeSemi
+                    } else {
+                        ctx
+                    };
+                    self.case_arm(arm, body_ctx, out);
+                }
+            }
+            Expr::Annot {
+                expr, type_expr, ..
+            } => {
+                self.infix(expr, out);
+                out.push_str(" :: ");
+                self.ty(type_expr, out);
+            }
+            _ => self.infix(expr, out),
+        }
+    }
+
+    fn case_arm(&self, arm: &CaseArm, body_ctx: Ctx, out: &mut String) {
+        self.pattern(&arm.pattern, out);
+        if let Some(guard) = &arm.guard {
+            out.push_str(" | ");
+            self.expr(guard, Ctx::Guard, out);
+        }
+        out.push_str(" -> ");
+        self.expr(&arm.body, body_ctx, out);
+    }
+
+    fn parens(&self, expr: &Expr, out: &mut String) {
+        out.push('(');
+        self.expr(expr, Ctx::Tail, out);
+        out.push(')');
+    }
+
+    /// 中置式の水準で書き出す。開いた式は括弧で閉じる。
+    fn infix(&self, expr: &Expr, out: &mut String) {
+        match expr {
+            Expr::BinOp {
+                op, left, right, ..
+            } => {
+                let fixity = self.fixities.lookup(op);
+                self.operand(left, fixity, Side::Left, out);
+                out.push(' ');
+                out.push_str(op);
+                out.push(' ');
+                self.operand(right, fixity, Side::Right, out);
+            }
+            _ if is_open(expr) => self.parens(expr, out),
+            _ => self.prefix(expr, out),
+        }
+    }
+
+    /// 親演算子 `parent` の左右の被演算子を書き出す。優先順位上昇法で同じ木へ戻る場合だけ括弧を省く。
+    fn operand(&self, expr: &Expr, parent: Fixity, side: Side, out: &mut String) {
+        let bare = match expr {
+            Expr::BinOp { op, .. } => {
+                let child = self.fixities.lookup(op);
+                child.precedence > parent.precedence
+                    || (child.precedence == parent.precedence
+                        && match side {
+                            Side::Left => child.assoc == Associativity::Left,
+                            Side::Right => parent.assoc == Associativity::Right,
+                        })
+            }
+            _ => !is_open(expr),
+        };
+        if bare {
+            self.infix(expr, out);
+        } else {
+            self.parens(expr, out);
+        }
+    }
+
+    /// 関数適用・単項マイナス・負のリテラルの水準で書き出す。
+    fn prefix(&self, expr: &Expr, out: &mut String) {
+        match expr {
+            Expr::App { arg, .. } if is_negation(expr) => {
+                out.push('-');
+                let mut operand = String::new();
+                self.atom(arg, &mut operand);
+                // `-?hole` のように記号が続くと 1 つの演算子として字句解析されるため空白を挟む。
+                if operand.starts_with(|c: char| c.is_ascii_punctuation() && !"([\"'_".contains(c))
+                {
+                    out.push(' ');
+                }
+                out.push_str(&operand);
+            }
+            Expr::App { func, arg, .. } => {
+                match func.as_ref() {
+                    Expr::App { .. } if !is_negation(func) => self.prefix(func, out),
+                    _ => self.atom(func, out),
+                }
+                out.push(' ');
+                self.atom(arg, out);
+            }
+            Expr::IntLit { value, base, .. } => out.push_str(&int_literal(*value, *base)),
+            Expr::FloatLit { value, .. } => out.push_str(&float_literal(*value)),
+            _ => self.atom(expr, out),
+        }
+    }
+
+    /// 原子式の水準で書き出す。それ以外は括弧で囲む。
+    fn atom(&self, expr: &Expr, out: &mut String) {
+        match expr {
+            Expr::Var { name, .. } => out.push_str(&binder_name(name)),
+            Expr::IntLit { value, base, .. } if *value >= 0 => {
+                out.push_str(&int_literal(*value, *base))
+            }
+            Expr::FloatLit { value, .. } if !value.is_sign_negative() => {
+                out.push_str(&float_literal(*value))
+            }
+            Expr::CharLit { value, .. } => out.push_str(&quote_char(*value)),
+            Expr::StringLit { value, .. } => out.push_str(&quote_string(value)),
+            Expr::BoolLit { value, .. } => out.push_str(if *value { "True" } else { "False" }),
+            Expr::ListLit { items, .. } => {
+                out.push('[');
+                self.comma_separated(items, out);
+                out.push(']');
+            }
+            Expr::TupleLit { items, .. } => {
+                out.push('(');
+                self.comma_separated(items, out);
+                out.push(')');
+            }
+            _ => self.parens(expr, out),
+        }
+    }
+
+    fn comma_separated(&self, items: &[Expr], out: &mut String) {
+        for (idx, item) in items.iter().enumerate() {
+            if idx > 0 {
+                out.push_str(", ");
+            }
+            self.expr(item, Ctx::Tail, out);
+        }
+    }
+
+    fn pattern(&self, pattern: &Pattern, out: &mut String) {
+        match pattern {
+            Pattern::As {
+                binder, pattern, ..
+            } => {
+                out.push_str(binder);
+                out.push('@');
+                // `@-` は 1 つの演算子として字句解析されるため、負のリテラルも括弧で区切る。
+                let needs_parens = match pattern.as_ref() {
+                    Pattern::Constructor { args, .. } => !args.is_empty(),
+                    Pattern::Int { value, .. } => *value < 0,
+                    Pattern::Float { value, .. } => value.is_sign_negative(),
+                    _ => false,
+                };
+                match pattern.as_ref() {
+                    _ if needs_parens => {
+                        out.push('(');
+                        self.pattern(pattern, out);
+                        out.push(')');
+                    }
+                    _ => self.pattern(pattern, out),
+                }
+            }
+            Pattern::Constructor { name, args, .. } => {
+                out.push_str(name);
+                let arity = args.len();
+                for (idx, arg) in args.iter().enumerate() {
+                    out.push(' ');
+                    // コンストラクタパターンは後続のパターンを引数として貪欲に取り込むため、
+                    // 入れ子のコンストラクタと as パターンは括弧で区切る。
+                    let needs_parens = match arg {
+                        Pattern::Constructor { args, .. } => !args.is_empty() || idx + 1 < arity,
+                        Pattern::As { .. } => true,
+                        _ => false,
+                    };
+                    if needs_parens {
+                        out.push('(');
+                        self.pattern(arg, out);
+                        out.push(')');
+                    } else {
+                        self.pattern(arg, out);
+                    }
+                }
+            }
+            Pattern::Wildcard { .. } => out.push('_'),
+            Pattern::Var { name, .. } => out.push_str(name),
+            Pattern::Int { value, base, .. } => out.push_str(&int_literal(*value, *base)),
+            Pattern::Float { value, .. } => out.push_str(&float_literal(*value)),
+            Pattern::Char { value, .. } => out.push_str(&quote_char(*value)),
+            Pattern::String { value, .. } => out.push_str(&quote_string(value)),
+            Pattern::Bool { value, .. } => out.push_str(if *value { "True" } else { "False" }),
+            Pattern::List { items, .. } => {
+                out.push('[');
+                self.comma_separated_patterns(items, out);
+                out.push(']');
+            }
+            Pattern::Tuple { items, .. } => {
+                out.push('(');
+                self.comma_separated_patterns(items, out);
+                out.push(')');
+            }
+        }
+    }
+
+    fn comma_separated_patterns(&self, items: &[Pattern], out: &mut String) {
+        for (idx, item) in items.iter().enumerate() {
+            if idx > 0 {
+                out.push_str(", ");
+            }
+            self.pattern(item, out);
+        }
+    }
+}
+
+/// 右端が式で終わり、後続のトークンを取り込みうる式か。
+fn is_open(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Lambda { .. }
+            | Expr::LetIn { .. }
+            | Expr::If { .. }
+            | Expr::Case { .. }
+            | Expr::Annot { .. }
+    )
+}
+
+/// 右端が `case` で終わる式か。直後の `; pat -> ...` をアームとして取り込んでしまう。
+fn ends_with_case(expr: &Expr) -> bool {
+    match expr {
+        Expr::Case { .. } => true,
+        Expr::Lambda { body, .. } | Expr::LetIn { body, .. } => ends_with_case(body),
+        Expr::If { else_branch, .. } => ends_with_case(else_branch),
+        _ => false,
+    }
+}
+
+/// `-x` から糖衣展開された `negate x` か。数値リテラルへの `-` は畳み込まれるため対象外。
+fn is_negation(expr: &Expr) -> bool {
+    match expr {
+        Expr::App { func, arg, .. } => {
+            matches!(func.as_ref(), Expr::Var { name, .. } if name == "negate")
+                && !matches!(arg.as_ref(), Expr::IntLit { .. } | Expr::FloatLit { .. })
+        }
+        _ => false,
+    }
+}
+
+/// 演算子名は `(op)` として括弧で囲む。
+fn binder_name(name: &str) -> String {
+    if is_operator_name(name) {
+        format!("({name})")
+    } else {
+        name.to_string()
+    }
+}
+
+fn int_literal(value: i64, base: IntBase) -> String {
+    let sign = if value < 0 { "-" } else { "" };
+    let magnitude = value.unsigned_abs();
+    match base {
+        IntBase::Dec => format!("{sign}{magnitude}"),
+        IntBase::Hex => format!("{sign}0x{magnitude:x}"),
+        IntBase::Oct => format!("{sign}0o{magnitude:o}"),
+        IntBase::Bin => format!("{sign}0b{magnitude:b}"),
+    }
+}
+
+/// `Debug` 表記は常に小数点か指数部を含むため、字句解析で浮動小数リテラルとして読み戻せる。
+fn float_literal(value: f64) -> String {
+    format!("{value:?}")
+}
+
+fn quote_char(value: char) -> String {
+    format!("'{}'", escape(value, '\''))
+}
+
+fn quote_string(value: &str) -> String {
+    let body: String = value.chars().map(|ch| escape(ch, '"')).collect();
+    format!("\"{body}\"")
+}
+
+fn escape(ch: char, quote: char) -> String {
+    match ch {
+        '\\' => "\\\\".into(),
+        '\n' => "\\n".into(),
+        '\r' => "\\r".into(),
+        '\t' => "\\t".into(),
+        '\0' => "\\0".into(),
+        c if c == quote => format!("\\{c}"),
+        c if c.is_control() => format!("\\u{{{:x}}}", c as u32),
+        c => c.to_string(),
+    }
+}
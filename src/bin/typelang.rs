@@ -12,8 +12,19 @@ use serde_json::to_string;
 
 /// TypeLang CLI
 #[derive(Parser)]
-#[command(author, version, about = "TypeLang HM CLI")]
+#[command(
+    author,
+    version,
+    about = "TypeLang HM CLI",
+    args_conflicts_with_subcommands = true
+)]
 struct Cli {
+    /// .tl ファイルの `main` をインタプリタで評価して結果を表示する（ビルド不要）
+    #[arg(long, value_name = "FILE")]
+    run: Option<PathBuf>,
+    /// .tl ファイルを整形して標準出力へ書き出す（ファイルは書き換えない）
+    #[arg(long, value_name = "FILE", conflicts_with = "run")]
+    fmt: Option<PathBuf>,
     #[arg(
         long,
         help = "デフォルトのコード生成バックエンドを指定します",
@@ -54,12 +65,30 @@ enum Command {
         /// 最適化レベルを上書き
         #[arg(long, value_enum)]
         optim_level: Option<OptimLevel>,
+        /// 整数の加減乗算でオーバーフローを検査する
+        #[arg(long, default_value_t = false)]
+        checked_arith: bool,
+        /// データ値とリストをアリーナから確保し、main の終了時にまとめて破棄する
+        #[arg(long, default_value_t = false)]
+        arena: bool,
+        /// 関数呼び出しの深さの上限。超えるとスタック溢れとして中断する
+        #[arg(long, value_name = "DEPTH")]
+        max_call_depth: Option<u32>,
+        /// コード生成の各段階と失敗時の診断コードを標準エラーへ表示
+        #[arg(long, default_value_t = false)]
+        verbose: bool,
+        /// エントリポイントとして呼び出すトップレベル関数名
+        #[arg(long, value_name = "NAME", default_value = typelang::DEFAULT_ENTRY)]
+        entry: String,
         /// 生成された辞書一覧を表示
         #[arg(long, default_value_t = false)]
         print_dictionaries: bool,
         /// 成功時に出力情報を JSON で表示
         #[arg(long, default_value_t = false)]
         json: bool,
+        /// 入力ファイルの更新を監視し、変更のたびにビルドし直す（Ctrl-C で終了）
+        #[arg(long, default_value_t = false)]
+        watch: bool,
     },
 }
 
@@ -68,31 +97,21 @@ enum EmitFormat {
     Native,
 }
 
-#[derive(Clone, ValueEnum, Copy, Debug)]
+#[derive(Clone, ValueEnum, Copy, Debug, Default)]
 enum Backend {
+    #[default]
     Cranelift,
     Llvm,
 }
 
-impl Default for Backend {
-    fn default() -> Self {
-        Self::Cranelift
-    }
-}
-
-#[derive(Clone, ValueEnum, Copy, Debug)]
+#[derive(Clone, ValueEnum, Copy, Debug, Default)]
 enum OptimLevel {
+    #[default]
     Debug,
     Fast,
     Best,
 }
 
-impl Default for OptimLevel {
-    fn default() -> Self {
-        Self::Debug
-    }
-}
-
 impl Backend {
     fn as_str(self) -> &'static str {
         match self {
@@ -142,6 +161,12 @@ fn main() {
 fn dispatch(cli: Cli) -> Result<(), String> {
     let default_backend = cli.backend;
     let default_optim = cli.optim_level;
+    if let Some(input) = cli.run {
+        return run_script(&input);
+    }
+    if let Some(input) = cli.fmt {
+        return format_file(&input);
+    }
     match cli.command.unwrap_or(Command::Repl {
         keep_outputs: false,
     }) {
@@ -159,8 +184,14 @@ fn dispatch(cli: Cli) -> Result<(), String> {
             output,
             backend,
             optim_level,
+            checked_arith,
+            arena,
+            max_call_depth,
+            verbose,
+            entry,
             print_dictionaries,
             json,
+            watch,
         } => {
             if !matches!(emit, EmitFormat::Native) {
                 return Err("現在サポートされる emit 形式は native のみです".into());
@@ -168,9 +199,17 @@ fn dispatch(cli: Cli) -> Result<(), String> {
             let options = BuildOptions {
                 backend: backend.unwrap_or(default_backend),
                 optim_level: optim_level.unwrap_or(default_optim),
+                checked_arith,
+                arena,
+                max_call_depth,
+                verbose,
+                entry,
                 print_dictionaries,
                 json,
             };
+            if watch {
+                watch_native(&input, output.as_deref(), &options);
+            }
             build_native(&input, output.as_deref(), &options)
         }
     }
@@ -179,27 +218,111 @@ fn dispatch(cli: Cli) -> Result<(), String> {
 struct BuildOptions {
     backend: Backend,
     optim_level: OptimLevel,
+    checked_arith: bool,
+    arena: bool,
+    max_call_depth: Option<u32>,
+    verbose: bool,
+    entry: String,
     print_dictionaries: bool,
     json: bool,
 }
 
+impl From<&BuildOptions> for typelang::NativeBuildOptions<'_> {
+    fn from(opts: &BuildOptions) -> Self {
+        Self {
+            backend: opts.backend.into(),
+            optim_level: opts.optim_level.into(),
+            arithmetic: if opts.checked_arith {
+                typelang::NativeArithmetic::Checked
+            } else {
+                typelang::NativeArithmetic::Wrapping
+            },
+            allocation: if opts.arena {
+                typelang::NativeAllocation::Arena
+            } else {
+                typelang::NativeAllocation::Individual
+            },
+            call_depth: opts.max_call_depth.map_or(
+                typelang::NativeCallDepth::Unlimited,
+                typelang::NativeCallDepth::Limit,
+            ),
+            verbosity: if opts.verbose {
+                typelang::NativeVerbosity::Verbose
+            } else {
+                typelang::NativeVerbosity::Quiet
+            },
+            entry: opts.entry.clone(),
+            prelude: None,
+        }
+    }
+}
+
+/// `main` を評価し、その値を REPL と同じ形式で表示する。
+/// 出力だけを行う `main :: ()` の結果 `()` は表示しない。
+fn run_script(input: &Path) -> Result<(), String> {
+    let value = typelang::repl::run_file(&input.display().to_string())?;
+    if !matches!(&value, typelang::evaluator::Value::Tuple(items) if items.is_empty()) {
+        typelang::repl::print_value(&value);
+    }
+    Ok(())
+}
+
+/// ファイルを整形した結果を標準出力へ書き出す。`import` 先は辿らない。
+fn format_file(input: &Path) -> Result<(), String> {
+    let source = fs::read_to_string(input).map_err(|e| {
+        format!(
+            "入力ファイルの読み込みに失敗しました ({}): {e}",
+            input.display()
+        )
+    })?;
+    let formatted = typelang::ast::pretty::format_source(&source)
+        .map_err(|e| format!("パースに失敗しました ({}): {e}", input.display()))?;
+    print!("{formatted}");
+    Ok(())
+}
+
+/// 入力ファイルと `import` されたファイル群を読み込み、依存順に連結した 1 つのプログラムにする。
+fn read_program_with_imports(input: &Path) -> Result<typelang::ast::Program, String> {
+    let modules = typelang::repl::resolve_imports(&input.display().to_string(), |path| {
+        let source = fs::read_to_string(path)
+            .map_err(|e| format!("入力ファイルの読み込みに失敗しました ({path}): {e}"))?;
+        typelang::parser::parse_program(&source)
+            .map_err(|e| format!("パースに失敗しました ({path}): {e}"))
+    })?;
+    // 連結後も各ファイルの公開範囲が分かるよう、公開リストを省略した module 宣言を具体化しておく。
+    let mut modules = modules.into_iter().map(|module| {
+        let mut program = module.program;
+        let names: Vec<String> = program.decls.iter().map(|d| d.name.clone()).collect();
+        for decl in &mut program.modules {
+            decl.exports.get_or_insert_with(|| names.clone());
+        }
+        program
+    });
+    let mut program = modules
+        .next()
+        .expect("resolve_imports always returns the entry program");
+    for module in modules {
+        program.modules.extend(module.modules);
+        program.class_decls.extend(module.class_decls);
+        program.instance_decls.extend(module.instance_decls);
+        program.data_decls.extend(module.data_decls);
+        program.type_synonyms.extend(module.type_synonyms);
+        program.fixities.extend(module.fixities);
+        program.decls.extend(module.decls);
+    }
+    program.imports.clear();
+    Ok(program)
+}
+
 fn build_native(input: &Path, out: Option<&Path>, opts: &BuildOptions) -> Result<(), String> {
-    let source = fs::read_to_string(input)
-        .map_err(|e| format!("入力ファイルの読み込みに失敗しました: {e}"))?;
-    let program = typelang::parser::parse_program(&source)
-        .map_err(|e| format!("パースに失敗しました: {e}"))?;
+    let program = read_program_with_imports(input)?;
 
     let output_path = out
         .map(PathBuf::from)
         .unwrap_or_else(|| default_output_path(input));
 
-    let artifacts = typelang::emit_native_with_options(
-        &program,
-        &output_path,
-        opts.backend.into(),
-        opts.optim_level.into(),
-    )
-    .map_err(|e| format!("ネイティブコード生成に失敗しました: {e}"))?;
+    let artifacts = typelang::emit_native_with_options(&program, &output_path, &opts.into())
+        .map_err(|e| format!("ネイティブコード生成に失敗しました: {e}"))?;
 
     let dict_views = dictionary_views(&artifacts);
     if opts.json {
@@ -210,6 +333,7 @@ fn build_native(input: &Path, out: Option<&Path>, opts: &BuildOptions) -> Result
             output: String,
             backend: &'static str,
             optim: &'static str,
+            object_hash: String,
             dictionaries: &'a [DictionaryView<'a>],
         }
         let payload = JsonOutput {
@@ -218,6 +342,7 @@ fn build_native(input: &Path, out: Option<&Path>, opts: &BuildOptions) -> Result
             output: output_path.display().to_string(),
             backend: opts.backend.as_str(),
             optim: opts.optim_level.as_str(),
+            object_hash: format!("{:016x}", artifacts.object_hash),
             dictionaries: &dict_views,
         };
         match to_string(&payload) {
@@ -241,6 +366,21 @@ fn build_native(input: &Path, out: Option<&Path>, opts: &BuildOptions) -> Result
     Ok(())
 }
 
+/// 入力ファイルの更新時刻を監視し、変わるたびに `build_native` をやり直す。
+/// ビルドに失敗してもエラーを表示して監視を続けるため、戻らない。
+fn watch_native(input: &Path, out: Option<&Path>, opts: &BuildOptions) -> ! {
+    let mut watcher = typelang::watch::FileWatcher::new(input);
+    eprintln!("{} を監視しています（Ctrl-C で終了）", input.display());
+    loop {
+        if watcher.poll_changed() {
+            if let Err(err) = build_native(input, out, opts) {
+                eprintln!("エラー: {err}");
+            }
+        }
+        std::thread::sleep(typelang::watch::WATCH_POLL_INTERVAL);
+    }
+}
+
 fn default_output_path(input: &Path) -> PathBuf {
     let stem = input
         .file_stem()
@@ -4,36 +4,74 @@
 // 関連ファイル: src/codegen/dictionary_codegen.rs, runtime_native/src/lib.rs, documents/native.md
 #![allow(clippy::result_large_err)]
 
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::rc::Rc;
 use std::sync::Arc;
 
 use cranelift_codegen::ir::condcodes::{FloatCC, IntCC};
 use cranelift_codegen::ir::{
-    types, AbiParam, Function as ClifFunction, InstBuilder, Signature, StackSlotData,
-    StackSlotKind, TrapCode, Type, UserFuncName, Value,
+    types, AbiParam, Block, Function as ClifFunction, InstBuilder, MemFlags, Signature,
+    StackSlotData, StackSlotKind, TrapCode, Type, UserFuncName, Value,
 };
 use cranelift_codegen::isa::{self, CallConv};
 use cranelift_codegen::settings::{self, Configurable};
 use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Variable};
-use cranelift_module::{FuncId, Linkage, Module};
+use cranelift_module::{DataDescription, DataId, FuncId, Linkage, Module};
 use cranelift_native;
 use cranelift_object::{ObjectBuilder, ObjectModule};
 use tempfile::tempdir;
 
+use crate::ast as A;
 use crate::codegen::{dictionary_codegen, NativeError, NativeResult};
 use crate::core_ir::{
-    self, Binding, ConstructorLayout, Expr, Function, Literal, MatchArm, MatchBinding, PrimOp,
-    ValueTy, VarKind,
+    self, type_repr, Binding, ConstructorLayout, Expr, Function, Literal, MatchArm, MatchBinding,
+    PrimOp, ValueTy, VarKind,
 };
 use crate::intrinsics::{self, IntrinsicKind};
+use crate::primitives::{JUST, MAYBE_TYPE, NOTHING};
+
+/// 利用者定義関数のシンボル接頭辞。ランタイムの `tl_*` シンボルと名前空間を分ける。
+const SYMBOL_PREFIX: &str = "tl_user_";
+
+/// ネイティブコード生成時の挙動を切り替えるオプション。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CodegenOptions {
+    /// 整数の加減乗算でオーバーフローを検査し、検出時に実行を中断する。
+    pub checked_arithmetic: bool,
+    /// データ値とリスト節点をランタイムのアリーナから確保し、`main` の終了時にまとめて破棄する。
+    pub arena_allocation: bool,
+    /// 関数呼び出しの深さの上限。指定すると各関数の入口と出口でランタイムの深さカウンタを更新し、
+    /// 超えた時点で実行を中断する。
+    pub max_call_depth: Option<u32>,
+    /// 各段階(関数宣言・辞書解決・エントリポイント型など)の進行状況を標準エラーへ出力する。
+    pub verbose: bool,
+}
 
-const SYMBOL_PREFIX: &str = "tl_";
+/// `verbose` が有効なときだけ、コード生成の進行状況を標準エラーへ 1 行出力する。
+pub(crate) fn trace(options: &CodegenOptions, message: impl std::fmt::Display) {
+    if options.verbose {
+        eprintln!("[typelang native] {message}");
+    }
+}
 
 /// Core IR モジュールをネイティブ実行可能ファイルとして出力する。
 pub fn emit_native(ir: &core_ir::Module, output: &Path) -> NativeResult<()> {
+    emit_native_with(ir, output, &CodegenOptions::default()).map(|_| ())
+}
+
+/// オプションを指定して Core IR モジュールをネイティブ実行可能ファイルとして出力する。
+///
+/// 戻り値はリンク前のオブジェクトのバイト列から求めた FNV-1a ハッシュ。
+/// 同じ入力から同じ値が得られれば、コード生成は決定的だったとみなせる。
+pub fn emit_native_with(
+    ir: &core_ir::Module,
+    output: &Path,
+    options: &CodegenOptions,
+) -> NativeResult<u64> {
     let entry_name = ir.entry().ok_or_else(|| {
         NativeError::unsupported("CODEGEN001", "エントリポイント関数 (main) が見つかりません")
     })?;
@@ -41,6 +79,16 @@ pub fn emit_native(ir: &core_ir::Module, output: &Path) -> NativeResult<()> {
         .functions
         .get(entry_name)
         .ok_or_else(|| NativeError::unsupported("CODEGEN002", "エントリポイントが不正です"))?;
+    let takes_args = is_args_entrypoint(entry_fn);
+    if !entry_fn.params.is_empty() && !takes_args {
+        return Err(NativeError::unsupported(
+            "CODEGEN008",
+            format!(
+                "main は引数を取れません (main must take no arguments; コマンドライン引数を受け取る場合は main :: [String] -> Int): {} 個の引数が宣言されています",
+                entry_fn.params.len()
+            ),
+        ));
+    }
     if !matches!(
         entry_fn.result,
         ValueTy::Int | ValueTy::Double | ValueTy::Bool | ValueTy::Unit
@@ -53,6 +101,10 @@ pub fn emit_native(ir: &core_ir::Module, output: &Path) -> NativeResult<()> {
             ),
         ));
     }
+    trace(
+        options,
+        format_args!("entrypoint: {entry_name} :: {:?}", entry_fn.result),
+    );
 
     let isa = build_isa()?;
     let obj_builder = ObjectBuilder::new(
@@ -66,7 +118,26 @@ pub fn emit_native(ir: &core_ir::Module, output: &Path) -> NativeResult<()> {
     let dict_source = dictionary_codegen::generate(&ir.dictionaries)?;
     let runtime = declare_runtime_symbols(&mut module, call_conv)?;
     let func_ids = declare_functions(ir, &mut module, call_conv)?;
+    trace(
+        options,
+        format_args!(
+            "declared {} functions: {}",
+            ir.functions.len(),
+            ir.functions.keys().cloned().collect::<Vec<_>>().join(", ")
+        ),
+    );
     let dict_symbols = declare_dictionary_symbols(ir, &mut module, call_conv)?;
+    for init in &ir.dictionaries {
+        trace(
+            options,
+            format_args!(
+                "dictionary {}<{}> -> {}",
+                init.classname,
+                init.type_repr,
+                init.builder.as_str().unwrap_or("(unresolved)")
+            ),
+        );
+    }
     define_functions(
         ir,
         &func_ids,
@@ -74,6 +145,7 @@ pub fn emit_native(ir: &core_ir::Module, output: &Path) -> NativeResult<()> {
         &runtime,
         &mut module,
         call_conv,
+        options,
     )?;
     define_entrypoint(
         entry_name,
@@ -82,11 +154,17 @@ pub fn emit_native(ir: &core_ir::Module, output: &Path) -> NativeResult<()> {
         &runtime,
         &mut module,
         call_conv,
+        options,
     )?;
     let product = module.finish();
     let obj_bytes = product.emit().map_err(|e| {
         NativeError::unsupported("CODEGEN105", format!("オブジェクト生成に失敗しました: {e}"))
     })?;
+    let object_hash = fnv1a_64(&obj_bytes);
+    trace(
+        options,
+        format_args!("object: {} bytes, hash {object_hash:016x}", obj_bytes.len()),
+    );
 
     let tmp_dir = tempdir()?;
     let obj_path = tmp_dir.path().join("program.o");
@@ -98,18 +176,31 @@ pub fn emit_native(ir: &core_ir::Module, output: &Path) -> NativeResult<()> {
         }
     }
 
-    build_runtime_library(dict_source.as_ref().map(|tmp| tmp.path()))?;
-    let runtime_lib_path = locate_runtime_library(&isa)?;
+    build_runtime_library(
+        dict_source.as_ref().map(|tmp| tmp.path()),
+        options.arena_allocation,
+    )?;
+    let runtime_lib_path = locate_runtime_library(&isa, options.arena_allocation)?;
+    trace(
+        options,
+        format_args!(
+            "linking {} with {}",
+            output.display(),
+            runtime_lib_path.display()
+        ),
+    );
 
     let mut cmd = Command::new("cc");
     cmd.arg(&obj_path)
         .arg(&runtime_lib_path)
+        // ランタイムの数学関数 (tl_sin など) は libm に依存する。
+        .arg("-lm")
         .arg("-O0")
         .arg("-o")
         .arg(output);
     let output_status = cmd.output();
     match output_status {
-        Ok(out) if out.status.success() => Ok(()),
+        Ok(out) if out.status.success() => Ok(object_hash),
         Ok(out) => Err(NativeError::command_failure(
             format!("cc {} {}", obj_path.display(), output.display()),
             Some(out.status),
@@ -123,6 +214,15 @@ pub fn emit_native(ir: &core_ir::Module, output: &Path) -> NativeResult<()> {
     }
 }
 
+/// 64 ビット FNV-1a。ビルドの再現性を確かめるための指紋で、暗号学的な強度は求めない。
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
 fn build_isa() -> NativeResult<Arc<dyn isa::TargetIsa>> {
     let isa_builder = cranelift_native::builder().map_err(|e| {
         NativeError::unsupported(
@@ -148,12 +248,36 @@ struct RuntimeSymbols {
     print_double: FuncId,
     print_bool: FuncId,
     println_value: FuncId,
+    trace_value: FuncId,
+    math_sin: FuncId,
+    math_cos: FuncId,
+    math_exp: FuncId,
+    math_log: FuncId,
     list_empty: FuncId,
     list_cons: FuncId,
     list_is_empty: FuncId,
+    list_length: FuncId,
+    list_replicate: FuncId,
+    list_take: FuncId,
+    list_drop: FuncId,
+    list_append: FuncId,
+    string_append: FuncId,
     list_head: FuncId,
     list_tail: FuncId,
     list_free: FuncId,
+    args_list: FuncId,
+    array_from_list: FuncId,
+    array_get: FuncId,
+    ref_new: FuncId,
+    ref_read: FuncId,
+    ref_write: FuncId,
+    map_empty: FuncId,
+    map_insert: FuncId,
+    map_lookup: FuncId,
+    read_int: FuncId,
+    read_double: FuncId,
+    format: FuncId,
+    map_find_with_default: FuncId,
     data_pack: FuncId,
     data_tag: FuncId,
     data_arity: FuncId,
@@ -162,12 +286,17 @@ struct RuntimeSymbols {
     value_from_int: FuncId,
     value_from_double: FuncId,
     value_from_bool: FuncId,
+    value_from_static_str: FuncId,
     value_to_int: FuncId,
     value_to_double: FuncId,
     value_to_bool: FuncId,
     dict_lookup: FuncId,
     value_to_ptr: FuncId,
     abort: FuncId,
+    arena_reset: FuncId,
+    stack_set_limit: FuncId,
+    stack_enter: FuncId,
+    stack_leave: FuncId,
 }
 
 fn declare_runtime_symbols(
@@ -193,6 +322,20 @@ fn declare_runtime_symbols(
     sig_println.returns.push(AbiParam::new(ptr_ty));
     let println_value = module.declare_function("tl_println", Linkage::Import, &sig_println)?;
 
+    let mut sig_trace = Signature::new(call_conv);
+    sig_trace.params.push(AbiParam::new(ptr_ty));
+    sig_trace.params.push(AbiParam::new(ptr_ty));
+    sig_trace.returns.push(AbiParam::new(ptr_ty));
+    let trace_value = module.declare_function("tl_trace", Linkage::Import, &sig_trace)?;
+
+    let mut sig_math = Signature::new(call_conv);
+    sig_math.params.push(AbiParam::new(types::F64));
+    sig_math.returns.push(AbiParam::new(types::F64));
+    let math_sin = module.declare_function("tl_sin", Linkage::Import, &sig_math)?;
+    let math_cos = module.declare_function("tl_cos", Linkage::Import, &sig_math)?;
+    let math_exp = module.declare_function("tl_exp", Linkage::Import, &sig_math)?;
+    let math_log = module.declare_function("tl_log", Linkage::Import, &sig_math)?;
+
     let mut sig_list_empty = Signature::new(call_conv);
     sig_list_empty.returns.push(AbiParam::new(ptr_ty));
     let list_empty = module.declare_function("tl_list_empty", Linkage::Import, &sig_list_empty)?;
@@ -209,6 +352,31 @@ fn declare_runtime_symbols(
     let list_is_empty =
         module.declare_function("tl_list_is_empty", Linkage::Import, &sig_list_is_empty)?;
 
+    let mut sig_list_length = Signature::new(call_conv);
+    sig_list_length.params.push(AbiParam::new(ptr_ty));
+    sig_list_length.returns.push(AbiParam::new(types::I64));
+    let list_length =
+        module.declare_function("tl_list_length", Linkage::Import, &sig_list_length)?;
+
+    // replicate / take / drop はいずれも (Int, ポインタ) を受け取ってリストを返す。
+    let mut sig_list_build = Signature::new(call_conv);
+    sig_list_build.params.push(AbiParam::new(types::I64));
+    sig_list_build.params.push(AbiParam::new(ptr_ty));
+    sig_list_build.returns.push(AbiParam::new(ptr_ty));
+    let list_replicate =
+        module.declare_function("tl_list_replicate", Linkage::Import, &sig_list_build)?;
+    let list_take = module.declare_function("tl_list_take", Linkage::Import, &sig_list_build)?;
+    let list_drop = module.declare_function("tl_list_drop", Linkage::Import, &sig_list_build)?;
+
+    // `<>` はリストどうし・文字列どうしとも 2 つのポインタを受け取って同じ表現の値を返す。
+    let mut sig_append = Signature::new(call_conv);
+    sig_append.params.push(AbiParam::new(ptr_ty));
+    sig_append.params.push(AbiParam::new(ptr_ty));
+    sig_append.returns.push(AbiParam::new(ptr_ty));
+    let list_append = module.declare_function("tl_list_append", Linkage::Import, &sig_append)?;
+    let string_append =
+        module.declare_function("tl_string_append", Linkage::Import, &sig_append)?;
+
     let mut sig_list_head = Signature::new(call_conv);
     sig_list_head.params.push(AbiParam::new(ptr_ty));
     sig_list_head.returns.push(AbiParam::new(ptr_ty));
@@ -223,6 +391,68 @@ fn declare_runtime_symbols(
     sig_list_free.params.push(AbiParam::new(ptr_ty));
     let list_free = module.declare_function("tl_list_free", Linkage::Import, &sig_list_free)?;
 
+    let mut sig_array_from_list = Signature::new(call_conv);
+    sig_array_from_list.params.push(AbiParam::new(ptr_ty));
+    sig_array_from_list.returns.push(AbiParam::new(ptr_ty));
+    let array_from_list =
+        module.declare_function("tl_array_from_list", Linkage::Import, &sig_array_from_list)?;
+
+    let mut sig_array_get = Signature::new(call_conv);
+    sig_array_get.params.push(AbiParam::new(ptr_ty)); // array
+    sig_array_get.params.push(AbiParam::new(types::I64)); // index
+    sig_array_get.returns.push(AbiParam::new(ptr_ty));
+    let array_get = module.declare_function("tl_array_get", Linkage::Import, &sig_array_get)?;
+
+    let mut sig_ref_new = Signature::new(call_conv);
+    sig_ref_new.params.push(AbiParam::new(ptr_ty)); // value
+    sig_ref_new.returns.push(AbiParam::new(ptr_ty));
+    let ref_new = module.declare_function("tl_ref_new", Linkage::Import, &sig_ref_new)?;
+
+    let mut sig_ref_read = Signature::new(call_conv);
+    sig_ref_read.params.push(AbiParam::new(ptr_ty)); // cell
+    sig_ref_read.returns.push(AbiParam::new(ptr_ty));
+    let ref_read = module.declare_function("tl_ref_read", Linkage::Import, &sig_ref_read)?;
+
+    let mut sig_ref_write = Signature::new(call_conv);
+    sig_ref_write.params.push(AbiParam::new(ptr_ty)); // cell
+    sig_ref_write.params.push(AbiParam::new(ptr_ty)); // value
+    let ref_write = module.declare_function("tl_ref_write", Linkage::Import, &sig_ref_write)?;
+
+    let mut sig_map_empty = Signature::new(call_conv);
+    sig_map_empty.returns.push(AbiParam::new(ptr_ty));
+    let map_empty = module.declare_function("tl_map_empty", Linkage::Import, &sig_map_empty)?;
+
+    let mut sig_map_insert = Signature::new(call_conv);
+    sig_map_insert.params.push(AbiParam::new(ptr_ty)); // map
+    sig_map_insert.params.push(AbiParam::new(ptr_ty)); // key
+    sig_map_insert.params.push(AbiParam::new(ptr_ty)); // value
+    sig_map_insert.returns.push(AbiParam::new(ptr_ty));
+    let map_insert = module.declare_function("tl_map_insert", Linkage::Import, &sig_map_insert)?;
+
+    let mut sig_map_lookup = Signature::new(call_conv);
+    sig_map_lookup.params.push(AbiParam::new(ptr_ty)); // map
+    sig_map_lookup.params.push(AbiParam::new(ptr_ty)); // key
+    sig_map_lookup.params.push(AbiParam::new(types::I32)); // Nothing のタグ
+    sig_map_lookup.params.push(AbiParam::new(types::I32)); // Just のタグ
+    sig_map_lookup.returns.push(AbiParam::new(ptr_ty));
+    let map_lookup = module.declare_function("tl_map_lookup", Linkage::Import, &sig_map_lookup)?;
+
+    let mut sig_read = Signature::new(call_conv);
+    sig_read.params.push(AbiParam::new(ptr_ty)); // 文字列値
+    sig_read.params.push(AbiParam::new(types::I32)); // Nothing のタグ
+    sig_read.params.push(AbiParam::new(types::I32)); // Just のタグ
+    sig_read.returns.push(AbiParam::new(ptr_ty));
+    let read_int = module.declare_function("tl_read_int", Linkage::Import, &sig_read)?;
+    let read_double = module.declare_function("tl_read_double", Linkage::Import, &sig_read)?;
+
+    let mut sig_format = Signature::new(call_conv);
+    sig_format.params.push(AbiParam::new(ptr_ty)); // 書式の文字列値
+    sig_format.params.push(AbiParam::new(ptr_ty)); // 引数のリスト
+    sig_format.returns.push(AbiParam::new(ptr_ty));
+    let format = module.declare_function("tl_format", Linkage::Import, &sig_format)?;
+    let map_find_with_default =
+        module.declare_function("tl_map_find_with_default", Linkage::Import, &sig_map_insert)?;
+
     let mut sig_data_pack = Signature::new(call_conv);
     sig_data_pack.params.push(AbiParam::new(types::I32)); // tag
     sig_data_pack.params.push(AbiParam::new(ptr_ty)); // fields
@@ -271,6 +501,18 @@ fn declare_runtime_symbols(
     let value_from_bool =
         module.declare_function("tl_value_from_bool", Linkage::Import, &sig_value_from_bool)?;
 
+    let mut sig_value_from_static_str = Signature::new(call_conv);
+    sig_value_from_static_str.params.push(AbiParam::new(ptr_ty)); // bytes
+    sig_value_from_static_str.params.push(AbiParam::new(ptr_ty)); // len
+    sig_value_from_static_str
+        .returns
+        .push(AbiParam::new(ptr_ty));
+    let value_from_static_str = module.declare_function(
+        "tl_value_from_static_str",
+        Linkage::Import,
+        &sig_value_from_static_str,
+    )?;
+
     let mut sig_value_to_int = Signature::new(call_conv);
     sig_value_to_int.params.push(AbiParam::new(ptr_ty));
     sig_value_to_int.returns.push(AbiParam::new(types::I64));
@@ -306,17 +548,62 @@ fn declare_runtime_symbols(
     sig_abort.params.push(AbiParam::new(types::I32));
     let abort = module.declare_function("tl_abort_with_message", Linkage::Import, &sig_abort)?;
 
+    let sig_arena_reset = Signature::new(call_conv);
+    let arena_reset =
+        module.declare_function("tl_arena_reset", Linkage::Import, &sig_arena_reset)?;
+
+    let mut sig_args_list = Signature::new(call_conv);
+    sig_args_list.params.push(AbiParam::new(types::I32));
+    sig_args_list.params.push(AbiParam::new(ptr_ty));
+    sig_args_list.returns.push(AbiParam::new(ptr_ty));
+    let args_list = module.declare_function("tl_args_list", Linkage::Import, &sig_args_list)?;
+
+    let mut sig_stack_set_limit = Signature::new(call_conv);
+    sig_stack_set_limit.params.push(AbiParam::new(types::I32));
+    let stack_set_limit =
+        module.declare_function("tl_stack_set_limit", Linkage::Import, &sig_stack_set_limit)?;
+
+    let sig_stack_enter = Signature::new(call_conv);
+    let stack_enter =
+        module.declare_function("tl_stack_enter", Linkage::Import, &sig_stack_enter)?;
+    let stack_leave =
+        module.declare_function("tl_stack_leave", Linkage::Import, &sig_stack_enter)?;
+
     Ok(RuntimeSymbols {
         print_int,
         print_double,
         print_bool,
         println_value,
+        trace_value,
+        math_sin,
+        math_cos,
+        math_exp,
+        math_log,
         list_empty,
         list_cons,
         list_is_empty,
+        list_length,
+        list_replicate,
+        list_take,
+        list_drop,
+        list_append,
+        string_append,
         list_head,
         list_tail,
         list_free,
+        args_list,
+        array_from_list,
+        array_get,
+        ref_new,
+        ref_read,
+        ref_write,
+        map_empty,
+        map_insert,
+        map_lookup,
+        read_int,
+        read_double,
+        format,
+        map_find_with_default,
         data_pack,
         data_tag,
         data_arity,
@@ -325,12 +612,17 @@ fn declare_runtime_symbols(
         value_from_int,
         value_from_double,
         value_from_bool,
+        value_from_static_str,
         value_to_int,
         value_to_double,
         value_to_bool,
         dict_lookup,
         value_to_ptr,
         abort,
+        arena_reset,
+        stack_set_limit,
+        stack_enter,
+        stack_leave,
     })
 }
 
@@ -350,7 +642,18 @@ fn declare_functions(
     Ok(ids)
 }
 
-type DictionarySymbols = HashMap<(String, String), FuncId>;
+/// 辞書 1 つ分のシンボル。
+///
+/// `cache` はモジュール全体で共有するポインタ幅の大域変数で、ゼロ初期化しておき、
+/// 最初に辞書を参照した時点で `builder` の結果を保存する。以降の参照は呼び出し元の関数によらず
+/// 保存済みの辞書を読むだけで済む。
+#[derive(Clone, Copy, Debug)]
+struct DictionarySymbol {
+    builder: FuncId,
+    cache: DataId,
+}
+
+type DictionarySymbols = HashMap<(String, String), DictionarySymbol>;
 
 fn declare_dictionary_symbols(
     ir: &core_ir::Module,
@@ -358,6 +661,8 @@ fn declare_dictionary_symbols(
     call_conv: CallConv,
 ) -> NativeResult<DictionarySymbols> {
     let mut symbols = DictionarySymbols::new();
+    // 同じビルダーを共有する辞書は、保存先の大域変数も共有する。
+    let mut caches: HashMap<String, DataId> = HashMap::new();
     for dict in &ir.dictionaries {
         let symbol = match &dict.builder {
             core_ir::DictionaryBuilder::Resolved(sym) => sym,
@@ -381,11 +686,43 @@ fn declare_dictionary_symbols(
                     format!("辞書ビルダー {symbol} の宣言に失敗しました: {err}"),
                 )
             })?;
-        symbols.insert((dict.classname.clone(), dict.type_repr.clone()), func_id);
+        let cache = match caches.get(symbol) {
+            Some(cache) => *cache,
+            None => {
+                let cache = declare_dictionary_cache(module, symbol)?;
+                caches.insert(symbol.clone(), cache);
+                cache
+            }
+        };
+        symbols.insert(
+            (dict.classname.clone(), dict.type_repr.clone()),
+            DictionarySymbol {
+                builder: func_id,
+                cache,
+            },
+        );
     }
     Ok(symbols)
 }
 
+/// ビルダー `symbol` の結果を保存する、ゼロ初期化した書き込み可能な大域変数を定義する。
+fn declare_dictionary_cache(module: &mut ObjectModule, symbol: &str) -> NativeResult<DataId> {
+    let name = format!("{symbol}__cache");
+    let declare_err = |err| {
+        NativeError::unsupported(
+            "CODEGEN303",
+            format!("辞書キャッシュ {name} の宣言に失敗しました: {err}"),
+        )
+    };
+    let cache = module
+        .declare_data(&name, Linkage::Local, true, false)
+        .map_err(declare_err)?;
+    let mut desc = DataDescription::new();
+    desc.define_zeroinit(module.isa().pointer_bytes() as usize);
+    module.define_data(cache, &desc).map_err(declare_err)?;
+    Ok(cache)
+}
+
 fn define_functions(
     ir: &core_ir::Module,
     func_ids: &HashMap<String, FuncId>,
@@ -393,10 +730,11 @@ fn define_functions(
     runtime: &RuntimeSymbols,
     module: &mut ObjectModule,
     call_conv: CallConv,
+    options: &CodegenOptions,
 ) -> NativeResult<()> {
     let mut builder_ctx = FunctionBuilderContext::new();
     let mut ctx = module.make_context();
-    let ptr_ty = module.isa().pointer_type();
+    let string_literals = StringLiterals::default();
 
     for (name, func) in &ir.functions {
         let func_id = *func_ids.get(name).ok_or_else(|| {
@@ -405,65 +743,217 @@ fn define_functions(
                 format!("関数 {name} の識別子が見つかりません"),
             )
         })?;
-        ctx.func = ClifFunction::with_name_signature(
-            UserFuncName::testcase(symbol_name(name)),
-            make_signature(func, call_conv, ptr_ty)?,
-        );
-
-        {
-            let mut builder = FunctionBuilder::new(&mut ctx.func, &mut builder_ctx);
-            let entry_block = builder.create_block();
-            builder.append_block_params_for_function_params(entry_block);
-            builder.switch_to_block(entry_block);
-            builder.seal_block(entry_block);
-
-            let mut env = CodegenEnv::new(ptr_ty, dict_symbols.clone());
-            env.next_index = func.params.len() as u32;
-            for (idx, param) in func.params.iter().enumerate() {
-                let var = Variable::from_u32(idx as u32);
-                let cl_ty = clif_type(ptr_ty, &param.ty)?;
-                builder.declare_var(var, cl_ty);
-                let value = builder.block_params(entry_block)[idx];
-                builder.def_var(var, value);
-                env.insert_existing(
-                    param.name.clone(),
-                    var,
-                    param.ty.clone(),
-                    param.dict_type_repr.clone(),
-                    param.dict_value_ty.clone(),
+        trace(options, format_args!("lowering function {name}"));
+        let mut cx = LowerCtx {
+            module: &mut *module,
+            ir,
+            runtime,
+            func_ids,
+            dict_symbols,
+            string_literals: &string_literals,
+        };
+        ctx.func = build_function(&mut cx, name, func, call_conv, options, &mut builder_ctx)
+            .map_err(|err| {
+                trace(
+                    options,
+                    format_args!("failed while lowering function {name}"),
                 );
-            }
+                err
+            })?;
+        module.define_function(func_id, &mut ctx)?;
+        module.clear_context(&mut ctx);
+    }
 
-            let lowered = lower_expr(
-                module,
-                ir,
-                runtime,
-                func_ids,
-                &mut builder,
-                &mut env,
-                &func.body,
-            )?;
-
-            match func.result {
-                ValueTy::Unit => {
-                    builder.ins().return_(&[]);
+    Ok(())
+}
+
+/// 関数本体を生成する間、式ごとの生成関数へまとめて渡すモジュール全体の情報。
+struct LowerCtx<'a> {
+    module: &'a mut ObjectModule,
+    ir: &'a core_ir::Module,
+    runtime: &'a RuntimeSymbols,
+    func_ids: &'a HashMap<String, FuncId>,
+    dict_symbols: &'a DictionarySymbols,
+    string_literals: &'a StringLiterals,
+}
+
+/// Core IR の 1 関数を Cranelift IR の関数本体へ変換する。
+fn build_function(
+    cx: &mut LowerCtx,
+    name: &str,
+    func: &Function,
+    call_conv: CallConv,
+    options: &CodegenOptions,
+    builder_ctx: &mut FunctionBuilderContext,
+) -> NativeResult<ClifFunction> {
+    let ptr_ty = cx.module.isa().pointer_type();
+    let mut clif_func = ClifFunction::with_name_signature(
+        UserFuncName::testcase(symbol_name(name)),
+        make_signature(func, call_conv, ptr_ty)?,
+    );
+
+    {
+        let mut builder = FunctionBuilder::new(&mut clif_func, builder_ctx);
+        let entry_block = builder.create_block();
+        builder.append_block_params_for_function_params(entry_block);
+        builder.switch_to_block(entry_block);
+        builder.seal_block(entry_block);
+
+        let mut env = CodegenEnv::new(ptr_ty, cx.dict_symbols.clone(), cx.string_literals.clone());
+        env.next_index.set(func.params.len() as u32);
+        env.checked_arithmetic = options.checked_arithmetic;
+        if options.max_call_depth.is_some() {
+            let enter_ref = cx
+                .module
+                .declare_func_in_func(cx.runtime.stack_enter, builder.func);
+            builder.ins().call(enter_ref, &[]);
+        }
+        for (idx, param) in func.params.iter().enumerate() {
+            let var = Variable::from_u32(idx as u32);
+            let cl_ty = clif_type(ptr_ty, &param.ty)?;
+            builder.declare_var(var, cl_ty);
+            let value = builder.block_params(entry_block)[idx];
+            builder.def_var(var, value);
+            env.insert_existing(
+                param.name.clone(),
+                var,
+                param.ty.clone(),
+                param.dict_type_repr.clone(),
+                param.dict_value_ty.clone(),
+            );
+        }
+
+        // 末尾位置に自己呼び出しがあれば、引数を更新してこのブロックへ戻るループにする。
+        let loop_block = has_self_tail_call(name, &func.body).then(|| {
+            let block = builder.create_block();
+            builder.ins().jump(block, &[]);
+            builder.switch_to_block(block);
+            block
+        });
+        let tail = TailContext {
+            name,
+            func,
+            loop_block,
+            max_call_depth: options.max_call_depth.is_some(),
+        };
+        lower_tail(cx, &mut builder, &mut env, &tail, &func.body)?;
+        if let Some(block) = loop_block {
+            builder.seal_block(block);
+        }
+        builder.finalize();
+    }
+
+    Ok(clif_func)
+}
+
+/// 末尾位置の式を生成するときに参照する、生成中の関数の情報。
+struct TailContext<'a> {
+    name: &'a str,
+    func: &'a Function,
+    /// 自己末尾呼び出しの飛び先。末尾位置に自己呼び出しがない関数では `None`。
+    loop_block: Option<Block>,
+    max_call_depth: bool,
+}
+
+/// `if` の分岐と `let` の本体を辿り、末尾位置に `name` 自身の呼び出しがあるか。
+fn has_self_tail_call(name: &str, expr: &Expr) -> bool {
+    match expr {
+        Expr::Apply { func, .. } => matches!(
+            func.as_ref(),
+            Expr::Var { name: callee, kind: VarKind::Function, .. } if callee == name
+        ),
+        Expr::If {
+            then_branch,
+            else_branch,
+            ..
+        } => has_self_tail_call(name, then_branch) || has_self_tail_call(name, else_branch),
+        Expr::Let { body, .. } => has_self_tail_call(name, body),
+        _ => false,
+    }
+}
+
+/// 関数本体の末尾位置にある式を生成し、戻り値を返すか自己呼び出しならループの先頭へ戻る。
+///
+/// `if` の各分岐と `let` の本体は末尾位置を引き継ぐため、合流ブロックを作らず分岐ごとに `return` する。
+fn lower_tail(
+    cx: &mut LowerCtx,
+    builder: &mut FunctionBuilder,
+    env: &mut CodegenEnv,
+    tail: &TailContext,
+    expr: &Expr,
+) -> NativeResult<()> {
+    if let Some(loop_block) = tail.loop_block {
+        match expr {
+            Expr::Apply { args, .. }
+                if has_self_tail_call(tail.name, expr) && args.len() == tail.func.params.len() =>
+            {
+                // 新しい引数をすべて計算してから仮引数を書き換える（引数が元の仮引数を参照するため）。
+                let mut lowered_args = Vec::with_capacity(args.len());
+                for (arg_expr, param) in args.iter().zip(&tail.func.params) {
+                    let lowered = lower_expr(cx, builder, env, arg_expr)?;
+                    lowered_args.push(
+                        coerce_value(cx.module, builder, cx.runtime, lowered, &param.ty)?.value,
+                    );
+                }
+                for (idx, value) in lowered_args.into_iter().enumerate() {
+                    builder.def_var(Variable::from_u32(idx as u32), value);
                 }
-                _ => {
-                    let lowered =
-                        coerce_value(module, &mut builder, runtime, lowered, &func.result)?;
-                    builder.ins().return_(&[lowered.value]);
+                builder.ins().jump(loop_block, &[]);
+                return Ok(());
+            }
+            Expr::If {
+                cond,
+                then_branch,
+                else_branch,
+                ..
+            } if has_self_tail_call(tail.name, expr) => {
+                let cond_b1 = lower_if_condition(cx, builder, env, cond)?;
+                let then_block = builder.create_block();
+                let else_block = builder.create_block();
+                builder
+                    .ins()
+                    .brif(cond_b1, then_block, &[], else_block, &[]);
+                builder.seal_block(then_block);
+                builder.seal_block(else_block);
+                for (block, branch) in [(then_block, then_branch), (else_block, else_branch)] {
+                    builder.switch_to_block(block);
+                    let mut branch_env = env.clone();
+                    lower_tail(cx, builder, &mut branch_env, tail, branch)?;
                 }
+                return Ok(());
+            }
+            Expr::Let { bindings, body, .. } if has_self_tail_call(tail.name, body) => {
+                let mut scope = bind_let_bindings(cx, builder, env, bindings)?;
+                return lower_tail(cx, builder, &mut scope, tail, body);
             }
-            builder.finalize();
+            _ => {}
         }
-
-        module.define_function(func_id, &mut ctx)?;
-        module.clear_context(&mut ctx);
     }
 
+    let lowered = lower_expr(cx, builder, env, expr)?;
+    let result = match tail.func.result {
+        ValueTy::Unit => None,
+        _ => Some(coerce_value(cx.module, builder, cx.runtime, lowered, &tail.func.result)?.value),
+    };
+    if tail.max_call_depth {
+        let leave_ref = cx
+            .module
+            .declare_func_in_func(cx.runtime.stack_leave, builder.func);
+        builder.ins().call(leave_ref, &[]);
+    }
+    match result {
+        Some(value) => builder.ins().return_(&[value]),
+        None => builder.ins().return_(&[]),
+    };
     Ok(())
 }
 
+/// `main :: [String] -> Int` の形か。引数にはコマンドライン引数を渡し、戻り値を終了コードにする。
+fn is_args_entrypoint(func: &Function) -> bool {
+    matches!(func.params.as_slice(), [param] if param.ty == ValueTy::List(Box::new(ValueTy::String)))
+        && func.result == ValueTy::Int
+}
+
 fn define_entrypoint(
     entry_name: &str,
     entry_func: &Function,
@@ -471,8 +961,15 @@ fn define_entrypoint(
     runtime: &RuntimeSymbols,
     module: &mut ObjectModule,
     call_conv: CallConv,
+    options: &CodegenOptions,
 ) -> NativeResult<()> {
+    let ptr_ty = module.isa().pointer_type();
+    let takes_args = is_args_entrypoint(entry_func);
     let mut sig = Signature::new(call_conv);
+    if takes_args {
+        sig.params.push(AbiParam::new(types::I32));
+        sig.params.push(AbiParam::new(ptr_ty));
+    }
     sig.returns.push(AbiParam::new(types::I32));
     let main_id = module.declare_function("main", Linkage::Export, &sig)?;
 
@@ -483,9 +980,18 @@ fn define_entrypoint(
     {
         let mut builder = FunctionBuilder::new(&mut ctx.func, &mut builder_ctx);
         let block = builder.create_block();
+        if takes_args {
+            builder.append_block_params_for_function_params(block);
+        }
         builder.switch_to_block(block);
         builder.seal_block(block);
 
+        if let Some(limit) = options.max_call_depth {
+            let limit = builder.ins().iconst(types::I32, i64::from(limit));
+            let set_limit_ref = module.declare_func_in_func(runtime.stack_set_limit, builder.func);
+            builder.ins().call(set_limit_ref, &[limit]);
+        }
+
         let entry_id = *func_ids.get(entry_name).ok_or_else(|| {
             NativeError::unsupported(
                 "CODEGEN116",
@@ -493,10 +999,32 @@ fn define_entrypoint(
             )
         })?;
         let func_ref = module.declare_func_in_func(entry_id, builder.func);
-        let call = builder.ins().call(func_ref, &[]);
-        let results = builder.inst_results(call);
+        let entry_args = if takes_args {
+            let (argc, argv) = {
+                let params = builder.block_params(block);
+                (params[0], params[1])
+            };
+            let args_ref = module.declare_func_in_func(runtime.args_list, builder.func);
+            let call = builder.ins().call(args_ref, &[argc, argv]);
+            vec![builder.inst_results(call)[0]]
+        } else {
+            Vec::new()
+        };
+        let call = builder.ins().call(func_ref, &entry_args);
+        let results = builder.inst_results(call).to_vec();
 
+        // `main :: [String] -> Int` の戻り値は表示せず、終了コードとして返す。
+        let mut exit_code = None;
         match &entry_func.result {
+            ValueTy::Int if takes_args => {
+                let value = *results.first().ok_or_else(|| {
+                    NativeError::unsupported(
+                        "CODEGEN117",
+                        "Int 戻り値を期待しましたが値が存在しません",
+                    )
+                })?;
+                exit_code = Some(builder.ins().ireduce(types::I32, value));
+            }
             ValueTy::Unit => {}
             ValueTy::Int => {
                 let value = *results.first().ok_or_else(|| {
@@ -536,7 +1064,13 @@ fn define_entrypoint(
             }
         }
 
-        let exit_code = builder.ins().iconst(types::I32, 0);
+        if options.arena_allocation {
+            // 結果の表示が済んだ時点で、アリーナ上の値はもう参照されない。
+            let reset_ref = module.declare_func_in_func(runtime.arena_reset, builder.func);
+            builder.ins().call(reset_ref, &[]);
+        }
+
+        let exit_code = exit_code.unwrap_or_else(|| builder.ins().iconst(types::I32, 0));
         builder.ins().return_(&[exit_code]);
         builder.finalize();
     }
@@ -546,7 +1080,22 @@ fn define_entrypoint(
     Ok(())
 }
 
-fn build_runtime_library(dict_source: Option<&Path>) -> NativeResult<()> {
+/// `cargo build` の既定の出力先（`CARGO_TARGET_DIR` があればそれ）を返す。
+fn default_target_dir() -> PathBuf {
+    std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("target"))
+}
+
+/// アリーナ版ランタイムの出力先。
+///
+/// 既定のビルドと同じ `libruntime_native.a` を上書きすると、並行して行われる
+/// 通常のビルドがアリーナ版をリンクしてしまうため、専用のディレクトリへ分ける。
+fn arena_target_dir() -> PathBuf {
+    default_target_dir().join("runtime-arena")
+}
+
+fn build_runtime_library(dict_source: Option<&Path>, arena: bool) -> NativeResult<()> {
     let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
     let mut cmd = Command::new("cargo");
     cmd.arg("build")
@@ -554,6 +1103,12 @@ fn build_runtime_library(dict_source: Option<&Path>) -> NativeResult<()> {
         .arg("runtime_native")
         .arg("--release")
         .current_dir(manifest_dir);
+    if arena {
+        cmd.arg("--features")
+            .arg("arena")
+            .arg("--target-dir")
+            .arg(arena_target_dir());
+    }
     if let Some(path) = dict_source {
         cmd.env("TYPELANG_DICT_AUTOGEN", path);
     }
@@ -575,23 +1130,24 @@ fn build_runtime_library(dict_source: Option<&Path>) -> NativeResult<()> {
     }
 }
 
-fn locate_runtime_library(isa: &Arc<dyn isa::TargetIsa>) -> NativeResult<PathBuf> {
-    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
-    let mut candidates = Vec::new();
-
-    if let Ok(custom_target) = std::env::var("CARGO_TARGET_DIR") {
-        let custom = PathBuf::from(custom_target);
-        candidates.push(custom.join("release/libruntime_native.a"));
-        candidates.push(custom.join(format!("{}/release/libruntime_native.a", isa.triple())));
+fn locate_runtime_library(isa: &Arc<dyn isa::TargetIsa>, arena: bool) -> NativeResult<PathBuf> {
+    let mut target_dirs = Vec::new();
+    if arena {
+        target_dirs.push(arena_target_dir());
+    } else {
+        if let Ok(custom_target) = std::env::var("CARGO_TARGET_DIR") {
+            target_dirs.push(PathBuf::from(custom_target));
+        }
+        target_dirs.push(Path::new(env!("CARGO_MANIFEST_DIR")).join("target"));
     }
 
-    candidates.push(manifest_dir.join("target/release/libruntime_native.a"));
-    candidates.push(manifest_dir.join(format!(
-        "target/{}/release/libruntime_native.a",
-        isa.triple()
-    )));
-
-    if let Some(found) = candidates.into_iter().find(|path| path.exists()) {
+    let mut candidates = target_dirs.iter().flat_map(|dir| {
+        [
+            dir.join("release/libruntime_native.a"),
+            dir.join(format!("{}/release/libruntime_native.a", isa.triple())),
+        ]
+    });
+    if let Some(found) = candidates.find(|path| path.exists()) {
         Ok(found)
     } else {
         Err(NativeError::unsupported(
@@ -602,80 +1158,60 @@ fn locate_runtime_library(isa: &Arc<dyn isa::TargetIsa>) -> NativeResult<PathBuf
 }
 
 fn lower_expr(
-    module: &mut ObjectModule,
-    ir: &core_ir::Module,
-    runtime: &RuntimeSymbols,
-    func_ids: &HashMap<String, FuncId>,
+    cx: &mut LowerCtx,
     builder: &mut FunctionBuilder,
     env: &mut CodegenEnv,
     expr: &Expr,
 ) -> NativeResult<LoweredValue> {
     match expr {
-        Expr::Literal { value, ty } => lower_literal(builder, value, ty),
-        Expr::Var { name, kind, ty } => lower_var(builder, env, name, kind, ty),
-        Expr::Let { bindings, body, .. } => {
-            lower_let(module, ir, runtime, func_ids, builder, env, bindings, body)
+        Expr::Literal { value, ty } => {
+            lower_literal(cx.module, cx.runtime, builder, env, value, ty)
         }
+        Expr::Var {
+            name,
+            kind: VarKind::Intrinsic,
+            ..
+        } if intrinsics::lookup(name).is_some_and(|intr| intr.kind.is_constant()) => {
+            lower_intrinsic_call(cx, builder, env, name, &[])
+        }
+        Expr::Var { name, kind, ty } => lower_var(builder, env, name, kind, ty),
+        Expr::Let { bindings, body, ty } => lower_let(cx, builder, env, bindings, body, ty),
         Expr::PrimOp {
             op,
             args,
             ty,
             dict_fallback,
-        } => lower_primop(
-            module,
-            ir,
-            runtime,
-            func_ids,
-            builder,
-            env,
-            *op,
-            args,
-            ty,
-            *dict_fallback,
-        ),
-        Expr::Apply { func, args, .. } => {
-            lower_apply(module, ir, runtime, func_ids, builder, env, func, args)
-        }
+        } => lower_primop(cx, builder, env, *op, args, ty, *dict_fallback),
+        Expr::Apply { func, args, .. } => lower_apply(cx, builder, env, func, args),
         Expr::If {
             cond,
             then_branch,
             else_branch,
             ..
-        } => lower_if(
-            module,
-            ir,
-            runtime,
-            func_ids,
-            builder,
-            env,
-            cond,
-            then_branch,
-            else_branch,
-        ),
+        } => lower_if(cx, builder, env, cond, then_branch, else_branch),
         Expr::DictionaryPlaceholder {
             classname,
             type_repr,
             ty,
-        } => lower_dictionary_placeholder(module, builder, env, classname, type_repr, ty),
+        } => lower_dictionary_placeholder(cx.module, builder, env, classname, type_repr, ty),
         Expr::Tuple { .. } | Expr::Lambda { .. } => Err(NativeError::unsupported(
             "CODEGEN030",
             "タプル・ラムダ式はまだサポートされていません",
         )),
-        Expr::List { items, ty } => {
-            lower_list_literal(module, ir, runtime, func_ids, builder, env, items, ty)
-        }
+        Expr::List { items, ty } => lower_list_literal(cx, builder, env, items, ty),
         Expr::Match {
             scrutinee,
             arms,
             ty,
-        } => lower_match(
-            module, ir, runtime, func_ids, builder, env, scrutinee, arms, ty,
-        ),
+        } => lower_match(cx, builder, env, scrutinee, arms, ty),
     }
 }
 
 fn lower_literal(
+    module: &mut ObjectModule,
+    runtime: &RuntimeSymbols,
     builder: &mut FunctionBuilder,
+    env: &CodegenEnv,
     lit: &Literal,
     ty: &ValueTy,
 ) -> NativeResult<LoweredValue> {
@@ -692,6 +1228,25 @@ fn lower_literal(
             builder.ins().f64const(*v),
             ValueTy::Double,
         )),
+        // 文字はボックス化せず、コードポイントを Int と同じ幅の整数で持つ。
+        (Literal::Char(c), ValueTy::Char) => Ok(LoweredValue::new(
+            builder.ins().iconst(types::I64, i64::from(u32::from(*c))),
+            ValueTy::Char,
+        )),
+        // 文字列は読み取り専用データへ置いたバイト列から、ランタイムが内容ごとに共有する値を得る。
+        (Literal::String(text), ValueTy::String) => {
+            let data = env.string_literal_data(module, text)?;
+            let data_gv = module.declare_data_in_func(data, builder.func);
+            let bytes = builder.ins().global_value(env.ptr_ty, data_gv);
+            let len = builder.ins().iconst(env.ptr_ty, text.len() as i64);
+            let value = call_runtime(
+                builder,
+                module,
+                runtime.value_from_static_str,
+                &[bytes, len],
+            );
+            Ok(LoweredValue::new(value, ValueTy::String))
+        }
         (Literal::Char(_), _) | (Literal::String(_), _) => Err(NativeError::unsupported(
             "CODEGEN032",
             "Char/String リテラルは現在未対応です",
@@ -700,9 +1255,13 @@ fn lower_literal(
             builder.ins().iconst(types::I8, 0),
             ValueTy::Unit,
         )),
+        (Literal::EmptyList, ValueTy::List(_)) => {
+            let empty = call_runtime(builder, module, runtime.list_empty, &[]);
+            Ok(LoweredValue::new(empty, ty.clone()))
+        }
         (Literal::EmptyList, _) => Err(NativeError::unsupported(
             "CODEGEN033",
-            "空リストリテラルは現在未対応です",
+            format!("空リストリテラルの型 {:?} は未対応です", ty),
         )),
         _ => Err(NativeError::unsupported(
             "CODEGEN034",
@@ -719,13 +1278,13 @@ fn lower_dictionary_placeholder(
     type_repr: &str,
     ty: &ValueTy,
 ) -> NativeResult<LoweredValue> {
-    let func_id = env.lookup_dictionary(classname, type_repr).ok_or_else(|| {
+    let symbol = env.lookup_dictionary(classname, type_repr).ok_or_else(|| {
         NativeError::unsupported(
             "CODEGEN301",
             format!("辞書 {classname}<{type_repr}> のビルダーが見つかりません"),
         )
     })?;
-    let value = env.ensure_dictionary(module, builder, classname, type_repr, func_id)?;
+    let value = env.ensure_dictionary(module, builder, classname, type_repr, symbol)?;
     Ok(LoweredValue::new(value, ty.clone()))
 }
 
@@ -758,17 +1317,30 @@ fn lower_var(
     }
 }
 
-#[allow(clippy::too_many_arguments)]
 fn lower_let(
-    module: &mut ObjectModule,
-    ir: &core_ir::Module,
-    runtime: &RuntimeSymbols,
-    func_ids: &HashMap<String, FuncId>,
+    cx: &mut LowerCtx,
     builder: &mut FunctionBuilder,
     env: &mut CodegenEnv,
     bindings: &[Binding],
     body: &Expr,
+    ty: &ValueTy,
 ) -> NativeResult<LoweredValue> {
+    let mut scope = bind_let_bindings(cx, builder, env, bindings)?;
+    let value = lower_expr(cx, builder, &mut scope, body)?;
+    // 多相関数の戻り値はボックス化されたまま返るため、型が確定していれば `let` の型へ戻す。
+    if value.ty == ValueTy::Unknown && *ty != ValueTy::Unknown {
+        return coerce_value(cx.module, builder, cx.runtime, value, ty);
+    }
+    Ok(value)
+}
+
+/// `let` の束縛を順に評価し、束縛を加えたスコープを返す。
+fn bind_let_bindings(
+    cx: &mut LowerCtx,
+    builder: &mut FunctionBuilder,
+    env: &CodegenEnv,
+    bindings: &[Binding],
+) -> NativeResult<CodegenEnv> {
     let mut scope = env.clone();
     for binding in bindings {
         if matches!(binding.ty, ValueTy::Function { .. }) {
@@ -777,29 +1349,18 @@ fn lower_let(
                 "ローカル関数束縛は現在サポートされていません",
             ));
         }
-        let lowered = lower_expr(
-            module,
-            ir,
-            runtime,
-            func_ids,
-            builder,
-            &mut scope,
-            &binding.value,
-        )?;
+        let lowered = lower_expr(cx, builder, &mut scope, &binding.value)?;
+        let lowered = coerce_value(cx.module, builder, cx.runtime, lowered, &binding.ty)?;
         let var = scope.insert(binding.name.clone(), binding.ty.clone());
         let cl_ty = clif_type(env.ptr_ty(), &binding.ty)?;
         builder.declare_var(var, cl_ty);
         builder.def_var(var, lowered.value);
     }
-    lower_expr(module, ir, runtime, func_ids, builder, &mut scope, body)
+    Ok(scope)
 }
 
-#[allow(clippy::too_many_arguments)]
 fn lower_primop(
-    module: &mut ObjectModule,
-    ir: &core_ir::Module,
-    runtime: &RuntimeSymbols,
-    func_ids: &HashMap<String, FuncId>,
+    cx: &mut LowerCtx,
     builder: &mut FunctionBuilder,
     env: &mut CodegenEnv,
     op: PrimOp,
@@ -821,19 +1382,44 @@ fn lower_primop(
             ),
         ));
     }
-    let lhs = lower_expr(module, ir, runtime, func_ids, builder, env, &args[0])?;
+    if matches!(op, PrimOp::AndBool | PrimOp::OrBool) {
+        return lower_short_circuit(cx, builder, env, op, args);
+    }
+    let lhs = lower_expr(cx, builder, env, &args[0])?;
     let rhs = if expected_args == 2 {
-        Some(lower_expr(
-            module, ir, runtime, func_ids, builder, env, &args[1],
-        )?)
+        Some(lower_expr(cx, builder, env, &args[1])?)
     } else {
         None
     };
 
     if dict_fallback {
-        return lower_dictionary_primop(module, ir, runtime, builder, env, op, lhs, rhs, result_ty);
+        return lower_dictionary_primop(cx, builder, env, op, lhs, rhs, result_ty);
     }
     match op {
+        PrimOp::AddInt if env.checked_arithmetic => checked_int_op(
+            cx.module,
+            cx.runtime,
+            builder,
+            lhs,
+            rhs.unwrap(),
+            |b, l, r| b.ins().sadd_overflow(l, r),
+        ),
+        PrimOp::SubInt if env.checked_arithmetic => checked_int_op(
+            cx.module,
+            cx.runtime,
+            builder,
+            lhs,
+            rhs.unwrap(),
+            |b, l, r| b.ins().ssub_overflow(l, r),
+        ),
+        PrimOp::MulInt if env.checked_arithmetic => checked_int_op(
+            cx.module,
+            cx.runtime,
+            builder,
+            lhs,
+            rhs.unwrap(),
+            |b, l, r| b.ins().smul_overflow(l, r),
+        ),
         PrimOp::AddInt => binary_int_op(builder, lhs, rhs.unwrap(), |b, l, r| b.ins().iadd(l, r)),
         PrimOp::SubInt => binary_int_op(builder, lhs, rhs.unwrap(), |b, l, r| b.ins().isub(l, r)),
         PrimOp::MulInt => binary_int_op(builder, lhs, rhs.unwrap(), |b, l, r| b.ins().imul(l, r)),
@@ -863,17 +1449,72 @@ fn lower_primop(
         PrimOp::LeDouble => compare_double(builder, lhs, rhs.unwrap(), FloatCC::LessThanOrEqual),
         PrimOp::GtDouble => compare_double(builder, lhs, rhs.unwrap(), FloatCC::GreaterThan),
         PrimOp::GeDouble => compare_double(builder, lhs, rhs.unwrap(), FloatCC::GreaterThanOrEqual),
-        PrimOp::AndBool => binary_bool_op(builder, lhs, rhs.unwrap(), |b, l, r| b.ins().band(l, r)),
-        PrimOp::OrBool => binary_bool_op(builder, lhs, rhs.unwrap(), |b, l, r| b.ins().bor(l, r)),
+        PrimOp::AndBool | PrimOp::OrBool => unreachable!("短絡評価する論理演算は先に処理済み"),
         PrimOp::NotBool => unary_bool_op(builder, lhs, |b, v| b.ins().bnot(v)),
+        PrimOp::AndInt => binary_int_op(builder, lhs, rhs.unwrap(), |b, l, r| b.ins().band(l, r)),
+        PrimOp::OrInt => binary_int_op(builder, lhs, rhs.unwrap(), |b, l, r| b.ins().bor(l, r)),
+        PrimOp::XorInt => binary_int_op(builder, lhs, rhs.unwrap(), |b, l, r| b.ins().bxor(l, r)),
+        PrimOp::ShlInt => binary_int_op(builder, lhs, rhs.unwrap(), |b, l, r| b.ins().ishl(l, r)),
+        PrimOp::ShrInt => binary_int_op(builder, lhs, rhs.unwrap(), |b, l, r| b.ins().sshr(l, r)),
     }
 }
 
-#[allow(clippy::too_many_arguments)]
+/// `&&` / `||` を左辺で分岐する制御フローとして生成する。
+///
+/// 左辺だけで結果が決まる場合(`&&` の偽、`||` の真)は右辺を評価せずに合流点へ進む。
+fn lower_short_circuit(
+    cx: &mut LowerCtx,
+    builder: &mut FunctionBuilder,
+    env: &mut CodegenEnv,
+    op: PrimOp,
+    args: &[Expr],
+) -> NativeResult<LoweredValue> {
+    let lhs = lower_bool_operand(cx, builder, env, &args[0])?;
+    let lhs_b1 = bool_to_b1(builder, lhs);
+
+    let rhs_block = builder.create_block();
+    let merge_block = builder.create_block();
+    let result = builder.append_block_param(merge_block, types::I8);
+    if op == PrimOp::AndBool {
+        builder
+            .ins()
+            .brif(lhs_b1, rhs_block, &[], merge_block, &[lhs]);
+    } else {
+        builder
+            .ins()
+            .brif(lhs_b1, merge_block, &[lhs], rhs_block, &[]);
+    }
+    builder.seal_block(rhs_block);
+
+    builder.switch_to_block(rhs_block);
+    let mut rhs_env = env.clone();
+    let rhs = lower_bool_operand(cx, builder, &mut rhs_env, &args[1])?;
+    builder.ins().jump(merge_block, &[rhs]);
+
+    builder.switch_to_block(merge_block);
+    builder.seal_block(merge_block);
+    Ok(LoweredValue::new(result, ValueTy::Bool))
+}
+
+/// 論理演算の被演算子を `I8` の Bool として生成する。多相関数の戻り値のような箱詰めの値は取り出す。
+fn lower_bool_operand(
+    cx: &mut LowerCtx,
+    builder: &mut FunctionBuilder,
+    env: &mut CodegenEnv,
+    expr: &Expr,
+) -> NativeResult<Value> {
+    let lowered = lower_expr(cx, builder, env, expr)?;
+    if !matches!(lowered.ty, ValueTy::Bool | ValueTy::Unknown) {
+        return Err(NativeError::unsupported(
+            "CODEGEN086",
+            "Bool 演算の引数が Bool ではありません",
+        ));
+    }
+    Ok(coerce_value(cx.module, builder, cx.runtime, lowered, &ValueTy::Bool)?.value)
+}
+
 fn lower_dictionary_primop(
-    module: &mut ObjectModule,
-    ir: &core_ir::Module,
-    runtime: &RuntimeSymbols,
+    cx: &mut LowerCtx,
     builder: &mut FunctionBuilder,
     env: &mut CodegenEnv,
     op: PrimOp,
@@ -888,56 +1529,89 @@ fn lower_dictionary_primop(
         )
     })?;
     let type_repr_hint = preferred_dictionary_type_repr(&lhs, rhs.as_ref());
-    let binding = env
-        .dictionary_param(info.classname, type_repr_hint.as_deref())?
-        .clone();
-    let dict_value_ty = ensure_dictionary_method_available(
-        ir,
-        &binding.classname,
-        &binding.type_repr,
-        info.method_id,
-    )?;
-    let operand_ty = binding
-        .value_ty
-        .clone()
+    let (dict_value, dict_repr, bound_value_ty) =
+        match env.dictionary_param(info.classname, type_repr_hint.as_deref()) {
+            Ok(binding) => (
+                builder.use_var(binding.var),
+                binding.type_repr.clone(),
+                binding.value_ty.clone(),
+            ),
+            Err(err) => {
+                // 引数で受け取っていなくても、リスト同士の比較のようにモジュールが辞書を記録していれば直接構築する。
+                let recorded = type_repr_hint
+                    .as_deref()
+                    .and_then(|repr| Some((repr, env.lookup_dictionary(info.classname, repr)?)));
+                if let Some((repr, symbol)) = recorded {
+                    let repr = repr.to_string();
+                    let value =
+                        env.ensure_dictionary(cx.module, builder, info.classname, &repr, symbol)?;
+                    (value, repr, None)
+                } else if !matches!(
+                    type_repr_hint.as_deref(),
+                    None | Some("Int" | "Double" | "Bool")
+                ) {
+                    // 構造を持つ型の表記はスコープ内の辞書と一致しないことがあるため、クラス名だけで引き直す。
+                    let binding = env.dictionary_param(info.classname, None)?;
+                    (
+                        builder.use_var(binding.var),
+                        binding.type_repr.clone(),
+                        binding.value_ty.clone(),
+                    )
+                } else {
+                    return Err(err);
+                }
+            }
+        };
+    let dict_value_ty =
+        ensure_dictionary_method_available(cx.ir, info.classname, &dict_repr, info.method_id)?;
+    let operand_ty = bound_value_ty
         .filter(|ty| !matches!(ty, ValueTy::Unknown))
         .unwrap_or(dict_value_ty);
 
-    let lhs = coerce_value(module, builder, runtime, lhs, &operand_ty)?;
-    let rhs = match rhs {
-        Some(value) => Some(coerce_value(module, builder, runtime, value, &operand_ty)?),
-        None => None,
-    };
+    // 引数と戻り値の型はメソッドのシグネチャから決め、型変数 `a` を辞書の対象型で置き換える。
+    let (param_tys, method_result_ty) = instantiate_method_signature(info.signature, &operand_ty)?;
+    let operands: Vec<LoweredValue> = std::iter::once(lhs).chain(rhs).collect();
+    if operands.len() != param_tys.len() {
+        return Err(NativeError::unsupported(
+            "CODEGEN216",
+            format!(
+                "辞書メソッド {}.{} の引数数 ({}) と演算子 {:?} の被演算子数 ({}) が一致しません",
+                info.classname,
+                info.method,
+                param_tys.len(),
+                op,
+                operands.len()
+            ),
+        ));
+    }
+    let mut call_sig = Signature::new(builder.func.signature.call_conv);
+    let mut arg_values = Vec::with_capacity(operands.len());
+    for (operand, param_ty) in operands.into_iter().zip(&param_tys) {
+        let coerced = coerce_value(cx.module, builder, cx.runtime, operand, param_ty)?;
+        call_sig
+            .params
+            .push(AbiParam::new(clif_type(env.ptr_ty(), param_ty)?));
+        arg_values.push(coerced.value);
+    }
+    let result_clif_ty = clif_type(env.ptr_ty(), &method_result_ty)?;
+    call_sig.returns.push(AbiParam::new(result_clif_ty));
 
-    let dict_value = builder.use_var(binding.var);
     let method_id_value = builder.ins().iconst(types::I64, info.method_id as i64);
-    let lookup_ref = module.declare_func_in_func(runtime.dict_lookup, builder.func);
+    let lookup_ref = cx
+        .module
+        .declare_func_in_func(cx.runtime.dict_lookup, builder.func);
     let lookup_call = builder
         .ins()
         .call(lookup_ref, &[dict_value, method_id_value]);
     let lookup_result = builder.inst_results(lookup_call)[0];
-    let fn_ptr = call_runtime(builder, module, runtime.value_to_ptr, &[lookup_result]);
+    let fn_ptr = call_runtime(
+        builder,
+        cx.module,
+        cx.runtime.value_to_ptr,
+        &[lookup_result],
+    );
 
-    let mut call_sig = Signature::new(builder.func.signature.call_conv);
-    let operand_clif_ty = clif_type(env.ptr_ty(), &operand_ty)?;
-    call_sig.params.push(AbiParam::new(operand_clif_ty));
-    if rhs.is_some() {
-        call_sig.params.push(AbiParam::new(operand_clif_ty));
-    }
-    let method_result_ty = if matches!(target_ty, ValueTy::Bool) {
-        ValueTy::Bool
-    } else {
-        operand_ty.clone()
-    };
-    let result_clif_ty = clif_type(env.ptr_ty(), &method_result_ty)?;
-    call_sig.returns.push(AbiParam::new(result_clif_ty));
     let sig_ref = builder.import_signature(call_sig);
-
-    let mut arg_values = Vec::with_capacity(if rhs.is_some() { 2 } else { 1 });
-    arg_values.push(lhs.value);
-    if let Some(rhs) = rhs {
-        arg_values.push(rhs.value);
-    }
     let call_inst = builder.ins().call_indirect(sig_ref, fn_ptr, &arg_values);
     let call_results = builder.inst_results(call_inst);
     let result_value = *call_results.first().ok_or_else(|| {
@@ -947,24 +1621,43 @@ fn lower_dictionary_primop(
         )
     })?;
     let lowered = LoweredValue::new(result_value, method_result_ty);
-    coerce_value(module, builder, runtime, lowered, target_ty)
+    coerce_value(cx.module, builder, cx.runtime, lowered, target_ty)
+}
+
+/// `a -> a -> Bool` 形式のメソッドシグネチャを、型変数 `a` を `operand_ty` に置き換えて
+/// 引数の型列と戻り値の型へ分解する。
+fn instantiate_method_signature(
+    signature: &str,
+    operand_ty: &ValueTy,
+) -> NativeResult<(Vec<ValueTy>, ValueTy)> {
+    let mut parts = signature
+        .split("->")
+        .map(|part| match part.trim() {
+            "a" => Ok(operand_ty.clone()),
+            "Int" => Ok(ValueTy::Int),
+            "Double" => Ok(ValueTy::Double),
+            "Bool" => Ok(ValueTy::Bool),
+            other => Err(NativeError::unsupported(
+                "CODEGEN215",
+                format!("辞書メソッドのシグネチャ {signature} の型 {other} は未対応です"),
+            )),
+        })
+        .collect::<NativeResult<Vec<_>>>()?;
+    let result = parts.pop().ok_or_else(|| {
+        NativeError::unsupported(
+            "CODEGEN215",
+            format!("辞書メソッドのシグネチャ {signature} が空です"),
+        )
+    })?;
+    Ok((parts, result))
 }
 
 fn preferred_dictionary_type_repr(
     lhs: &LoweredValue,
     rhs: Option<&LoweredValue>,
 ) -> Option<String> {
-    dictionary_type_repr_from_value(&lhs.ty)
-        .or_else(|| rhs.and_then(|value| dictionary_type_repr_from_value(&value.ty)))
-}
-
-fn dictionary_type_repr_from_value(ty: &ValueTy) -> Option<String> {
-    match ty {
-        ValueTy::Int => Some("Int".to_string()),
-        ValueTy::Double => Some("Double".to_string()),
-        ValueTy::Bool => Some("Bool".to_string()),
-        _ => None,
-    }
+    type_repr::from_value_ty(&lhs.ty)
+        .or_else(|| rhs.and_then(|value| type_repr::from_value_ty(&value.ty)))
 }
 
 fn ensure_dictionary_method_available(
@@ -996,12 +1689,8 @@ fn ensure_dictionary_method_available(
     Ok(dict.value_ty.clone())
 }
 
-#[allow(clippy::too_many_arguments)]
 fn lower_apply(
-    module: &mut ObjectModule,
-    ir: &core_ir::Module,
-    runtime: &RuntimeSymbols,
-    func_ids: &HashMap<String, FuncId>,
+    cx: &mut LowerCtx,
     builder: &mut FunctionBuilder,
     env: &mut CodegenEnv,
     func: &Expr,
@@ -1012,17 +1701,17 @@ fn lower_apply(
             name,
             kind: VarKind::Function,
             ..
-        } => lower_function_call(module, ir, runtime, func_ids, builder, env, name, args),
+        } => lower_function_call(cx, builder, env, name, args),
         Expr::Var {
             name,
             kind: VarKind::Primitive,
             ..
-        } => lower_constructor_call(module, ir, runtime, func_ids, builder, env, name, args),
+        } => lower_constructor_call(cx, builder, env, name, args),
         Expr::Var {
             name,
             kind: VarKind::Intrinsic,
             ..
-        } => lower_intrinsic_call(module, ir, runtime, func_ids, builder, env, name, args),
+        } => lower_intrinsic_call(cx, builder, env, name, args),
         _ => Err(NativeError::unsupported(
             "CODEGEN070",
             "高階関数や部分適用はまだサポートされていません",
@@ -1030,18 +1719,14 @@ fn lower_apply(
     }
 }
 
-#[allow(clippy::too_many_arguments)]
 fn lower_function_call(
-    module: &mut ObjectModule,
-    ir: &core_ir::Module,
-    runtime: &RuntimeSymbols,
-    func_ids: &HashMap<String, FuncId>,
+    cx: &mut LowerCtx,
     builder: &mut FunctionBuilder,
     env: &mut CodegenEnv,
     name: &str,
     args: &[Expr],
 ) -> NativeResult<LoweredValue> {
-    let callee_ir = ir.functions.get(name).ok_or_else(|| {
+    let callee_ir = cx.ir.functions.get(name).ok_or_else(|| {
         NativeError::unsupported("CODEGEN071", format!("関数 {name} が存在しません"))
     })?;
     if callee_ir.params.len() != args.len() {
@@ -1055,16 +1740,16 @@ fn lower_function_call(
             ),
         ));
     }
-    let func_id = *func_ids.get(name).ok_or_else(|| {
+    let func_id = *cx.func_ids.get(name).ok_or_else(|| {
         NativeError::unsupported("CODEGEN073", format!("関数 {name} の ID が見つかりません"))
     })?;
-    let callee_ref = module.declare_func_in_func(func_id, builder.func);
+    let callee_ref = cx.module.declare_func_in_func(func_id, builder.func);
 
     let mut lowered_args = Vec::with_capacity(args.len());
     for (idx, arg_expr) in args.iter().enumerate() {
-        let lowered = lower_expr(module, ir, runtime, func_ids, builder, env, arg_expr)?;
+        let lowered = lower_expr(cx, builder, env, arg_expr)?;
         let expected = &callee_ir.params[idx].ty;
-        let coerced = coerce_value(module, builder, runtime, lowered, expected)?;
+        let coerced = coerce_value(cx.module, builder, cx.runtime, lowered, expected)?;
         lowered_args.push(coerced.value);
     }
     let call = builder.ins().call(callee_ref, &lowered_args);
@@ -1075,18 +1760,14 @@ fn lower_function_call(
     Ok(LoweredValue::new(ret_val, callee_ir.result.clone()))
 }
 
-#[allow(clippy::too_many_arguments)]
 fn lower_constructor_call(
-    module: &mut ObjectModule,
-    ir: &core_ir::Module,
-    runtime: &RuntimeSymbols,
-    _func_ids: &HashMap<String, FuncId>,
+    cx: &mut LowerCtx,
     builder: &mut FunctionBuilder,
     env: &mut CodegenEnv,
     name: &str,
     args: &[Expr],
 ) -> NativeResult<LoweredValue> {
-    let layout = find_constructor_layout(ir, name).ok_or_else(|| {
+    let layout = find_constructor_layout(cx.ir, name).ok_or_else(|| {
         NativeError::unsupported(
             "CODEGEN130",
             format!("コンストラクタ {} が Core IR 上に見つかりません", name),
@@ -1104,14 +1785,19 @@ fn lower_constructor_call(
         ));
     }
 
+    if is_newtype_constructor(cx.ir, layout) {
+        // newtype は包んだ値そのものを表現として使うため、パックを省略する。
+        return lower_expr(cx, builder, env, &args[0]);
+    }
+
     let ptr_ty = env.ptr_ty();
 
     let mut lowered_args = Vec::with_capacity(args.len());
     let mut stored_fields = Vec::with_capacity(args.len());
     for (idx, arg_expr) in args.iter().enumerate() {
-        let lowered = lower_expr(module, ir, runtime, _func_ids, builder, env, arg_expr)?;
+        let lowered = lower_expr(cx, builder, env, arg_expr)?;
         let field_value =
-            prepare_constructor_field(module, runtime, builder, ptr_ty, name, idx, &lowered)?;
+            prepare_constructor_field(cx.module, cx.runtime, builder, ptr_ty, name, idx, &lowered)?;
         stored_fields.push(field_value);
         lowered_args.push(lowered);
     }
@@ -1174,7 +1860,9 @@ fn lower_constructor_call(
         (addr, len_value)
     };
 
-    let data_pack_ref = module.declare_func_in_func(runtime.data_pack, builder.func);
+    let data_pack_ref = cx
+        .module
+        .declare_func_in_func(cx.runtime.data_pack, builder.func);
     let call = builder
         .ins()
         .call(data_pack_ref, &[tag_value, fields_ptr, len_value]);
@@ -1183,59 +1871,690 @@ fn lower_constructor_call(
         NativeError::unsupported("CODEGEN132", "tl_data_pack の戻り値が取得できませんでした")
     })?;
 
+    // 値の型はコンストラクタ名ではなく、それが属するデータ型で表す。
+    let type_arity = cx
+        .ir
+        .data_layouts
+        .get(&layout.parent)
+        .map_or(0, |parent| parent.type_params.len());
+    Ok(LoweredValue::new(
+        data_ptr,
+        ValueTy::Data {
+            constructor: layout.parent.clone(),
+            args: vec![ValueTy::Unknown; type_arity],
+        },
+    ))
+}
+
+fn lower_intrinsic_call(
+    cx: &mut LowerCtx,
+    builder: &mut FunctionBuilder,
+    env: &mut CodegenEnv,
+    name: &str,
+    args: &[Expr],
+) -> NativeResult<LoweredValue> {
+    let intrinsic = intrinsics::lookup(name).ok_or_else(|| {
+        NativeError::unsupported(
+            "CODEGEN180",
+            format!("{name} は intrinsic として登録されていません"),
+        )
+    })?;
+    match intrinsic.kind {
+        IntrinsicKind::Println => lower_intrinsic_println(cx, builder, env, args),
+        IntrinsicKind::Trace => lower_intrinsic_trace(cx, builder, env, args),
+        IntrinsicKind::Sqrt
+        | IntrinsicKind::Sin
+        | IntrinsicKind::Cos
+        | IntrinsicKind::Exp
+        | IntrinsicKind::Log
+        | IntrinsicKind::Floor
+        | IntrinsicKind::Ceiling
+        | IntrinsicKind::Round
+        | IntrinsicKind::Truncate
+        | IntrinsicKind::FromIntegral => lower_intrinsic_math(cx, builder, env, intrinsic, args),
+        IntrinsicKind::Null | IntrinsicKind::Length => {
+            lower_intrinsic_list_query(cx, builder, env, intrinsic, args)
+        }
+        IntrinsicKind::Replicate | IntrinsicKind::Take | IntrinsicKind::Drop => {
+            lower_intrinsic_list_build(cx, builder, env, intrinsic, args)
+        }
+        IntrinsicKind::Append => lower_intrinsic_append(cx, builder, env, args),
+        IntrinsicKind::FoldLeft => lower_intrinsic_fold_left(cx, builder, env, args),
+        IntrinsicKind::ArrayFromList | IntrinsicKind::ArrayIndex => {
+            lower_intrinsic_array(cx, builder, env, intrinsic, args)
+        }
+        IntrinsicKind::NewRef | IntrinsicKind::ReadRef | IntrinsicKind::WriteRef => {
+            lower_intrinsic_ref(cx, builder, env, intrinsic, args)
+        }
+        IntrinsicKind::EmptyMap
+        | IntrinsicKind::MapInsert
+        | IntrinsicKind::MapLookup
+        | IntrinsicKind::MapFindWithDefault => {
+            lower_intrinsic_map(cx, builder, env, intrinsic, args)
+        }
+        IntrinsicKind::ReadInt | IntrinsicKind::ReadDouble => {
+            lower_intrinsic_read(cx, builder, env, intrinsic, args)
+        }
+        IntrinsicKind::Format => lower_intrinsic_format(cx, builder, env, args),
+    }
+}
+
+fn lower_intrinsic_println(
+    cx: &mut LowerCtx,
+    builder: &mut FunctionBuilder,
+    env: &mut CodegenEnv,
+    args: &[Expr],
+) -> NativeResult<LoweredValue> {
+    if args.len() != 1 {
+        return Err(NativeError::unsupported(
+            "CODEGEN181",
+            format!("println の引数数が一致しません: {}", args.len()),
+        ));
+    }
+    let value = lower_expr(cx, builder, env, &args[0])?;
+    let boxed = coerce_value(cx.module, builder, cx.runtime, value, &ValueTy::Unknown)?;
+    let result = call_runtime(builder, cx.module, cx.runtime.println_value, &[boxed.value]);
+    Ok(LoweredValue::new(result, ValueTy::Unknown))
+}
+
+fn lower_intrinsic_trace(
+    cx: &mut LowerCtx,
+    builder: &mut FunctionBuilder,
+    env: &mut CodegenEnv,
+    args: &[Expr],
+) -> NativeResult<LoweredValue> {
+    if args.len() != 2 {
+        return Err(NativeError::unsupported(
+            "CODEGEN182",
+            format!("trace の引数数が一致しません: {}", args.len()),
+        ));
+    }
+    let message = lower_expr(cx, builder, env, &args[0])?;
+    let message = coerce_value(cx.module, builder, cx.runtime, message, &ValueTy::Unknown)?;
+    let value = lower_expr(cx, builder, env, &args[1])?;
+    let value_ty = value.ty.clone();
+    let boxed = coerce_value(cx.module, builder, cx.runtime, value, &ValueTy::Unknown)?;
+    let result = call_runtime(
+        builder,
+        cx.module,
+        cx.runtime.trace_value,
+        &[message.value, boxed.value],
+    );
+    coerce_value(
+        cx.module,
+        builder,
+        cx.runtime,
+        LoweredValue::new(result, ValueTy::Unknown),
+        &value_ty,
+    )
+}
+
+/// `null` はランタイムの `tl_list_is_empty` を、`length` は `tl_list_length` を呼び出す。
+fn lower_intrinsic_list_query(
+    cx: &mut LowerCtx,
+    builder: &mut FunctionBuilder,
+    env: &mut CodegenEnv,
+    intrinsic: intrinsics::Intrinsic,
+    args: &[Expr],
+) -> NativeResult<LoweredValue> {
+    if args.len() != 1 {
+        return Err(NativeError::unsupported(
+            "CODEGEN184",
+            format!("{} の引数数が一致しません: {}", intrinsic.name, args.len()),
+        ));
+    }
+    let list = lower_expr(cx, builder, env, &args[0])?;
+    let list = coerce_value(
+        cx.module,
+        builder,
+        cx.runtime,
+        list,
+        &ValueTy::List(Box::new(ValueTy::Unknown)),
+    )?;
+    if intrinsic.kind == IntrinsicKind::Length {
+        let result = call_runtime(builder, cx.module, cx.runtime.list_length, &[list.value]);
+        return Ok(LoweredValue::new(result, ValueTy::Int));
+    }
+    let result = call_runtime(builder, cx.module, cx.runtime.list_is_empty, &[list.value]);
+    Ok(LoweredValue::new(result, ValueTy::Bool))
+}
+
+/// `replicate` / `take` / `drop` はランタイムの `tl_list_replicate` / `tl_list_take` / `tl_list_drop` を呼び出す。
+///
+/// 要素は箱詰めした `TlValue` のまま並べるため、結果のリストは元の要素型を引き継ぐ。
+fn lower_intrinsic_list_build(
+    cx: &mut LowerCtx,
+    builder: &mut FunctionBuilder,
+    env: &mut CodegenEnv,
+    intrinsic: intrinsics::Intrinsic,
+    args: &[Expr],
+) -> NativeResult<LoweredValue> {
+    if args.len() != 2 {
+        return Err(NativeError::unsupported(
+            "CODEGEN184",
+            format!("{} の引数数が一致しません: {}", intrinsic.name, args.len()),
+        ));
+    }
+    let count = lower_expr(cx, builder, env, &args[0])?;
+    let count = coerce_value(cx.module, builder, cx.runtime, count, &ValueTy::Int)?;
+    let source = lower_expr(cx, builder, env, &args[1])?;
+    let (source, result_ty, symbol) = if intrinsic.kind == IntrinsicKind::Replicate {
+        let item_ty = source.ty.clone();
+        let boxed = coerce_value(cx.module, builder, cx.runtime, source, &ValueTy::Unknown)?;
+        (boxed, item_ty, cx.runtime.list_replicate)
+    } else {
+        let item_ty = match &source.ty {
+            ValueTy::List(item) => item.as_ref().clone(),
+            _ => ValueTy::Unknown,
+        };
+        let list = coerce_value(
+            cx.module,
+            builder,
+            cx.runtime,
+            source,
+            &ValueTy::List(Box::new(ValueTy::Unknown)),
+        )?;
+        let symbol = match intrinsic.kind {
+            IntrinsicKind::Take => cx.runtime.list_take,
+            _ => cx.runtime.list_drop,
+        };
+        (list, item_ty, symbol)
+    };
+    let result = call_runtime(builder, cx.module, symbol, &[count.value, source.value]);
+    Ok(LoweredValue::new(
+        result,
+        ValueTy::List(Box::new(result_ty)),
+    ))
+}
+
+/// `<>` はリストどうしならランタイムの `tl_list_append`、`String` どうしなら `tl_string_append` を呼び出す。
+///
+/// リストの連結は左辺の節点だけを複製し、右辺の節点は結果と共有する。
+fn lower_intrinsic_append(
+    cx: &mut LowerCtx,
+    builder: &mut FunctionBuilder,
+    env: &mut CodegenEnv,
+    args: &[Expr],
+) -> NativeResult<LoweredValue> {
+    if args.len() != 2 {
+        return Err(NativeError::unsupported(
+            "CODEGEN184",
+            format!("<> の引数数が一致しません: {}", args.len()),
+        ));
+    }
+    let lhs = lower_expr(cx, builder, env, &args[0])?;
+    let rhs = lower_expr(cx, builder, env, &args[1])?;
+    let (symbol, result_ty) = match (&lhs.ty, &rhs.ty) {
+        (ValueTy::List(lhs_item), ValueTy::List(rhs_item)) => {
+            let item = if **lhs_item == ValueTy::Unknown {
+                rhs_item.as_ref().clone()
+            } else {
+                lhs_item.as_ref().clone()
+            };
+            (cx.runtime.list_append, ValueTy::List(Box::new(item)))
+        }
+        (ValueTy::String, ValueTy::String) => (cx.runtime.string_append, ValueTy::String),
+        (lhs_ty, rhs_ty) => {
+            return Err(NativeError::unsupported(
+                "CODEGEN218",
+                format!(
+                    "<> はリストどうしか String どうしの連結のみ対応しています: {lhs_ty:?} と {rhs_ty:?}"
+                ),
+            ))
+        }
+    };
+    let operand_ty = match result_ty {
+        ValueTy::String => ValueTy::String,
+        _ => ValueTy::List(Box::new(ValueTy::Unknown)),
+    };
+    let lhs = coerce_value(cx.module, builder, cx.runtime, lhs, &operand_ty)?;
+    let rhs = coerce_value(cx.module, builder, cx.runtime, rhs, &operand_ty)?;
+    let result = call_runtime(builder, cx.module, symbol, &[lhs.value, rhs.value]);
+    Ok(LoweredValue::new(result, result_ty))
+}
+
+/// `foldl' f z xs` を、累積値とリストの残りをブロック引数として受け渡すループへ展開する。
+///
+/// Core IR は `f` の呼び出しを本体に持つ 2 引数のラムダとして渡すため、本体をループ内でそのまま生成する。
+/// 各段で新しい値を確保しないので、要素数によらず一定の領域で畳み込める。
+fn lower_intrinsic_fold_left(
+    cx: &mut LowerCtx,
+    builder: &mut FunctionBuilder,
+    env: &mut CodegenEnv,
+    args: &[Expr],
+) -> NativeResult<LoweredValue> {
+    let (params, body, init, list) = match args {
+        [Expr::Lambda { params, body, .. }, init, list] if params.len() == 2 => {
+            (params, body, init, list)
+        }
+        _ => {
+            return Err(NativeError::unsupported(
+                "CODEGEN184",
+                format!("foldl' の引数の形が一致しません: {}", args.len()),
+            ))
+        }
+    };
+    let init = lower_expr(cx, builder, env, init)?;
+    let acc_ty = init.ty.clone();
+    let list = lower_expr(cx, builder, env, list)?;
+    let item_ty = match &list.ty {
+        ValueTy::List(item) => item.as_ref().clone(),
+        _ => ValueTy::Unknown,
+    };
+    let list = coerce_value(
+        cx.module,
+        builder,
+        cx.runtime,
+        list,
+        &ValueTy::List(Box::new(ValueTy::Unknown)),
+    )?;
+    let acc_clif = clif_type(env.ptr_ty(), &acc_ty)?;
+
+    let header = builder.create_block();
+    let step_block = builder.create_block();
+    let exit = builder.create_block();
+    let acc = builder.append_block_param(header, acc_clif);
+    let rest = builder.append_block_param(header, env.ptr_ty());
+    let result = builder.append_block_param(exit, acc_clif);
+    builder.ins().jump(header, &[init.value, list.value]);
+
+    builder.switch_to_block(header);
+    let is_empty = call_runtime(builder, cx.module, cx.runtime.list_is_empty, &[rest]);
+    builder.ins().brif(is_empty, exit, &[acc], step_block, &[]);
+    builder.seal_block(step_block);
+
+    builder.switch_to_block(step_block);
+    let head = call_runtime(builder, cx.module, cx.runtime.list_head, &[rest]);
+    let item = coerce_value(
+        cx.module,
+        builder,
+        cx.runtime,
+        LoweredValue::new(head, ValueTy::Unknown),
+        &item_ty,
+    )?;
+    let mut scope = env.clone();
+    for (param, value) in params
+        .iter()
+        .zip([LoweredValue::new(acc, acc_ty.clone()), item])
+    {
+        let var = scope.insert(param.name.clone(), value.ty.clone());
+        builder.declare_var(var, clif_type(env.ptr_ty(), &value.ty)?);
+        builder.def_var(var, value.value);
+    }
+    let next = lower_expr(cx, builder, &mut scope, body)?;
+    let next = coerce_value(cx.module, builder, cx.runtime, next, &acc_ty)?;
+    let tail = call_runtime(builder, cx.module, cx.runtime.list_tail, &[rest]);
+    builder.ins().jump(header, &[next.value, tail]);
+    builder.seal_block(header);
+
+    builder.switch_to_block(exit);
+    builder.seal_block(exit);
+    Ok(LoweredValue::new(result, acc_ty))
+}
+
+/// `fromList` と `(!)` はランタイムの `tl_array_from_list` / `tl_array_get` を呼び出す。
+///
+/// 要素は箱詰めした `TlValue` として格納されるため、`(!)` の結果は配列の要素型へ戻す。
+/// 範囲外の添字はランタイムが code=2005 で中断する。
+fn lower_intrinsic_array(
+    cx: &mut LowerCtx,
+    builder: &mut FunctionBuilder,
+    env: &mut CodegenEnv,
+    intrinsic: intrinsics::Intrinsic,
+    args: &[Expr],
+) -> NativeResult<LoweredValue> {
+    let arity = match intrinsic.kind {
+        IntrinsicKind::ArrayFromList => 1,
+        _ => 2,
+    };
+    if args.len() != arity {
+        return Err(NativeError::unsupported(
+            "CODEGEN186",
+            format!("{} の引数数が一致しません: {}", intrinsic.name, args.len()),
+        ));
+    }
+    let source = lower_expr(cx, builder, env, &args[0])?;
+    if intrinsic.kind == IntrinsicKind::ArrayFromList {
+        let item_ty = match &source.ty {
+            ValueTy::List(item) => item.as_ref().clone(),
+            _ => ValueTy::Unknown,
+        };
+        let list = coerce_value(
+            cx.module,
+            builder,
+            cx.runtime,
+            source,
+            &ValueTy::List(Box::new(ValueTy::Unknown)),
+        )?;
+        let array = call_runtime(
+            builder,
+            cx.module,
+            cx.runtime.array_from_list,
+            &[list.value],
+        );
+        return Ok(LoweredValue::new(array, ValueTy::Array(Box::new(item_ty))));
+    }
+    let item_ty = match &source.ty {
+        ValueTy::Array(item) => item.as_ref().clone(),
+        _ => ValueTy::Unknown,
+    };
+    let array = coerce_value(
+        cx.module,
+        builder,
+        cx.runtime,
+        source,
+        &ValueTy::Array(Box::new(ValueTy::Unknown)),
+    )?;
+    let index = lower_expr(cx, builder, env, &args[1])?;
+    let index = coerce_value(cx.module, builder, cx.runtime, index, &ValueTy::Int)?;
+    let item = call_runtime(
+        builder,
+        cx.module,
+        cx.runtime.array_get,
+        &[array.value, index.value],
+    );
+    coerce_value(
+        cx.module,
+        builder,
+        cx.runtime,
+        LoweredValue::new(item, ValueTy::Unknown),
+        &item_ty,
+    )
+}
+
+/// `newRef`/`readRef`/`writeRef` はランタイムの `tl_ref_new` / `tl_ref_read` / `tl_ref_write` を呼び出す。
+///
+/// セルには箱詰めした `TlValue` を格納するため、`readRef` の結果はセルの要素型へ戻す。
+/// `writeRef` の結果は `Unit` になる。
+fn lower_intrinsic_ref(
+    cx: &mut LowerCtx,
+    builder: &mut FunctionBuilder,
+    env: &mut CodegenEnv,
+    intrinsic: intrinsics::Intrinsic,
+    args: &[Expr],
+) -> NativeResult<LoweredValue> {
+    let arity = match intrinsic.kind {
+        IntrinsicKind::WriteRef => 2,
+        _ => 1,
+    };
+    if args.len() != arity {
+        return Err(NativeError::unsupported(
+            "CODEGEN187",
+            format!("{} の引数数が一致しません: {}", intrinsic.name, args.len()),
+        ));
+    }
+    let first = lower_expr(cx, builder, env, &args[0])?;
+    if intrinsic.kind == IntrinsicKind::NewRef {
+        let item_ty = first.ty.clone();
+        let value = coerce_value(cx.module, builder, cx.runtime, first, &ValueTy::Unknown)?;
+        let cell = call_runtime(builder, cx.module, cx.runtime.ref_new, &[value.value]);
+        return Ok(LoweredValue::new(
+            cell,
+            ValueTy::Data {
+                constructor: "Ref".into(),
+                args: vec![item_ty],
+            },
+        ));
+    }
+    let item_ty = match &first.ty {
+        ValueTy::Data { constructor, args } if constructor == "Ref" && args.len() == 1 => {
+            args[0].clone()
+        }
+        _ => ValueTy::Unknown,
+    };
+    let cell = coerce_value(
+        cx.module,
+        builder,
+        cx.runtime,
+        first,
+        &ValueTy::Data {
+            constructor: "Ref".into(),
+            args: vec![ValueTy::Unknown],
+        },
+    )?;
+    if intrinsic.kind == IntrinsicKind::ReadRef {
+        let item = call_runtime(builder, cx.module, cx.runtime.ref_read, &[cell.value]);
+        return coerce_value(
+            cx.module,
+            builder,
+            cx.runtime,
+            LoweredValue::new(item, ValueTy::Unknown),
+            &item_ty,
+        );
+    }
+    let value = lower_expr(cx, builder, env, &args[1])?;
+    let value = coerce_value(cx.module, builder, cx.runtime, value, &ValueTy::Unknown)?;
+    let func_ref = cx
+        .module
+        .declare_func_in_func(cx.runtime.ref_write, builder.func);
+    builder.ins().call(func_ref, &[cell.value, value.value]);
+    let unit = builder.ins().iconst(types::I8, 0);
+    Ok(LoweredValue::new(unit, ValueTy::Unit))
+}
+
+/// `emptyMap`/`insert`/`lookup`/`findWithDefault` はランタイムの `tl_map_*` を呼び出す。
+///
+/// キーと値は箱詰めした `TlValue` として渡す。`lookup` の結果は `Maybe` のデータで、
+/// 構築子のタグは Core IR の `Maybe` の配置から求めてランタイムへ渡す。
+/// `findWithDefault` の結果は既定値と同じ型へ戻す。
+fn lower_intrinsic_map(
+    cx: &mut LowerCtx,
+    builder: &mut FunctionBuilder,
+    env: &mut CodegenEnv,
+    intrinsic: intrinsics::Intrinsic,
+    args: &[Expr],
+) -> NativeResult<LoweredValue> {
+    let map_ty = ValueTy::Data {
+        constructor: "Map".into(),
+        args: vec![ValueTy::Unknown, ValueTy::Unknown],
+    };
+    let (arity, func_id, result_ty) = match intrinsic.kind {
+        IntrinsicKind::EmptyMap => (0, cx.runtime.map_empty, map_ty.clone()),
+        IntrinsicKind::MapInsert => (3, cx.runtime.map_insert, map_ty.clone()),
+        IntrinsicKind::MapLookup => (
+            2,
+            cx.runtime.map_lookup,
+            ValueTy::Data {
+                constructor: MAYBE_TYPE.into(),
+                args: vec![ValueTy::Unknown],
+            },
+        ),
+        IntrinsicKind::MapFindWithDefault => {
+            (3, cx.runtime.map_find_with_default, ValueTy::Unknown)
+        }
+        _ => unreachable!(),
+    };
+    if args.len() != arity {
+        return Err(NativeError::unsupported(
+            "CODEGEN185",
+            format!("{} の引数数が一致しません: {}", intrinsic.name, args.len()),
+        ));
+    }
+    // 表は最後の引数で受け取り、ランタイムへは先頭で渡す。
+    let mut lowered = Vec::with_capacity(arity);
+    let mut default_ty = None;
+    for (index, arg) in args.iter().enumerate() {
+        let value = lower_expr(cx, builder, env, arg)?;
+        default_ty.get_or_insert_with(|| value.ty.clone());
+        let target = if index + 1 == arity {
+            &map_ty
+        } else {
+            &ValueTy::Unknown
+        };
+        lowered.push(coerce_value(cx.module, builder, cx.runtime, value, target)?.value);
+    }
+    lowered.rotate_right(usize::from(arity > 0));
+    if intrinsic.kind == IntrinsicKind::MapFindWithDefault {
+        // ランタイムは (表, キー, 既定値) の順に受け取る。
+        lowered.swap(1, 2);
+    }
+    if intrinsic.kind == IntrinsicKind::MapLookup {
+        lowered.extend(maybe_tags(cx, builder, intrinsic.name)?);
+    }
+    let result = call_runtime(builder, cx.module, func_id, &lowered);
+    let result = LoweredValue::new(result, result_ty);
+    match (intrinsic.kind, default_ty) {
+        (IntrinsicKind::MapFindWithDefault, Some(ty)) => {
+            coerce_value(cx.module, builder, cx.runtime, result, &ty)
+        }
+        _ => Ok(result),
+    }
+}
+
+/// `Maybe` を返すランタイム関数へ渡す `Nothing`・`Just` のタグを、Core IR の `Maybe` の配置から求める。
+fn maybe_tags(
+    cx: &LowerCtx,
+    builder: &mut FunctionBuilder,
+    intrinsic_name: &str,
+) -> NativeResult<[Value; 2]> {
+    let [nothing, just] = [NOTHING, JUST].map(|name| {
+        find_constructor_layout(cx.ir, name)
+            .filter(|layout| layout.parent == MAYBE_TYPE)
+            .map(|layout| layout.tag)
+            .ok_or_else(|| {
+                NativeError::unsupported(
+                    "CODEGEN217",
+                    format!("{intrinsic_name} の結果に使う構築子 {name} が Maybe に見つかりません"),
+                )
+            })
+    });
+    Ok([
+        builder.ins().iconst(types::I32, i64::from(nothing?)),
+        builder.ins().iconst(types::I32, i64::from(just?)),
+    ])
+}
+
+/// `readInt` / `readDouble` はランタイムの `tl_read_int` / `tl_read_double` を呼び出す。
+///
+/// 結果は `lookup` と同じく `Maybe` のデータで、`Just` のフィールドは箱詰めした数値になる。
+fn lower_intrinsic_read(
+    cx: &mut LowerCtx,
+    builder: &mut FunctionBuilder,
+    env: &mut CodegenEnv,
+    intrinsic: intrinsics::Intrinsic,
+    args: &[Expr],
+) -> NativeResult<LoweredValue> {
+    if args.len() != 1 {
+        return Err(NativeError::unsupported(
+            "CODEGEN185",
+            format!("{} の引数数が一致しません: {}", intrinsic.name, args.len()),
+        ));
+    }
+    let (func_id, item_ty) = match intrinsic.kind {
+        IntrinsicKind::ReadInt => (cx.runtime.read_int, ValueTy::Int),
+        _ => (cx.runtime.read_double, ValueTy::Double),
+    };
+    let text = lower_expr(cx, builder, env, &args[0])?;
+    let text = coerce_value(cx.module, builder, cx.runtime, text, &ValueTy::String)?;
+    let [nothing, just] = maybe_tags(cx, builder, intrinsic.name)?;
+    let result = call_runtime(builder, cx.module, func_id, &[text.value, nothing, just]);
     Ok(LoweredValue::new(
-        data_ptr,
+        result,
         ValueTy::Data {
-            constructor: layout.name.clone(),
-            args: lowered_args.into_iter().map(|lv| lv.ty).collect(),
+            constructor: MAYBE_TYPE.into(),
+            args: vec![item_ty],
         },
     ))
 }
 
-#[allow(clippy::too_many_arguments)]
-fn lower_intrinsic_call(
-    module: &mut ObjectModule,
-    ir: &core_ir::Module,
-    runtime: &RuntimeSymbols,
-    func_ids: &HashMap<String, FuncId>,
+/// `format` は引数を箱詰めしてリストに並べ、書式の文字列値とともにランタイムの `tl_format` へ渡す。
+///
+/// 引数の個数と型は Core IR が書式の指定子とそろえてあるため、ここでは順に並べるだけでよい。
+fn lower_intrinsic_format(
+    cx: &mut LowerCtx,
     builder: &mut FunctionBuilder,
     env: &mut CodegenEnv,
-    name: &str,
     args: &[Expr],
 ) -> NativeResult<LoweredValue> {
-    let intrinsic = intrinsics::lookup(name).ok_or_else(|| {
-        NativeError::unsupported(
-            "CODEGEN180",
-            format!("{name} は intrinsic として登録されていません"),
-        )
-    })?;
-    match intrinsic.kind {
-        IntrinsicKind::Println => {
-            lower_intrinsic_println(module, ir, runtime, func_ids, builder, env, args)
-        }
+    let Some((template, values)) = args.split_first() else {
+        return Err(NativeError::unsupported(
+            "CODEGEN185",
+            "format の引数数が一致しません: 0",
+        ));
+    };
+    let template = lower_expr(cx, builder, env, template)?;
+    let template = coerce_value(cx.module, builder, cx.runtime, template, &ValueTy::String)?;
+    let mut boxed = Vec::with_capacity(values.len());
+    for value in values {
+        let lowered = lower_expr(cx, builder, env, value)?;
+        boxed.push(coerce_value(cx.module, builder, cx.runtime, lowered, &ValueTy::Unknown)?.value);
+    }
+    let mut list = call_runtime(builder, cx.module, cx.runtime.list_empty, &[]);
+    for value in boxed.into_iter().rev() {
+        list = call_runtime(builder, cx.module, cx.runtime.list_cons, &[value, list]);
     }
+    let result = call_runtime(
+        builder,
+        cx.module,
+        cx.runtime.format,
+        &[template.value, list],
+    );
+    Ok(LoweredValue::new(result, ValueTy::String))
 }
 
-#[allow(clippy::too_many_arguments)]
-fn lower_intrinsic_println(
-    module: &mut ObjectModule,
-    ir: &core_ir::Module,
-    runtime: &RuntimeSymbols,
-    func_ids: &HashMap<String, FuncId>,
+/// `sqrt` と丸め・変換は Cranelift 命令へ、その他の数学関数はランタイムの libm ラッパーへ下ろす。
+fn lower_intrinsic_math(
+    cx: &mut LowerCtx,
     builder: &mut FunctionBuilder,
     env: &mut CodegenEnv,
+    intrinsic: intrinsics::Intrinsic,
     args: &[Expr],
 ) -> NativeResult<LoweredValue> {
     if args.len() != 1 {
         return Err(NativeError::unsupported(
-            "CODEGEN181",
-            format!("println の引数数が一致しません: {}", args.len()),
+            "CODEGEN183",
+            format!("{} の引数数が一致しません: {}", intrinsic.name, args.len()),
         ));
     }
-    let value = lower_expr(module, ir, runtime, func_ids, builder, env, &args[0])?;
-    let boxed = coerce_value(module, builder, runtime, value, &ValueTy::Unknown)?;
-    let result = call_runtime(builder, module, runtime.println_value, &[boxed.value]);
-    Ok(LoweredValue::new(result, ValueTy::Unknown))
+    let arg = lower_expr(cx, builder, env, &args[0])?;
+    let x = match arg.ty {
+        ValueTy::Int => builder.ins().fcvt_from_sint(types::F64, arg.value),
+        _ => coerce_value(cx.module, builder, cx.runtime, arg, &ValueTy::Double)?.value,
+    };
+    let result = match intrinsic.kind {
+        IntrinsicKind::Sqrt => builder.ins().sqrt(x),
+        IntrinsicKind::Sin => call_runtime(builder, cx.module, cx.runtime.math_sin, &[x]),
+        IntrinsicKind::Cos => call_runtime(builder, cx.module, cx.runtime.math_cos, &[x]),
+        IntrinsicKind::Exp => call_runtime(builder, cx.module, cx.runtime.math_exp, &[x]),
+        IntrinsicKind::Log => call_runtime(builder, cx.module, cx.runtime.math_log, &[x]),
+        IntrinsicKind::FromIntegral => x,
+        IntrinsicKind::Floor
+        | IntrinsicKind::Ceiling
+        | IntrinsicKind::Round
+        | IntrinsicKind::Truncate => {
+            let rounded = match intrinsic.kind {
+                IntrinsicKind::Floor => builder.ins().floor(x),
+                IntrinsicKind::Ceiling => builder.ins().ceil(x),
+                // Haskell の round と同じく偶数丸め。
+                IntrinsicKind::Round => builder.ins().nearest(x),
+                _ => builder.ins().trunc(x),
+            };
+            let int = checked_float_to_int(cx.module, cx.runtime, builder, rounded);
+            return Ok(LoweredValue::new(int, ValueTy::Int));
+        }
+        IntrinsicKind::Println
+        | IntrinsicKind::Trace
+        | IntrinsicKind::Null
+        | IntrinsicKind::Length
+        | IntrinsicKind::Replicate
+        | IntrinsicKind::Take
+        | IntrinsicKind::Drop
+        | IntrinsicKind::Append
+        | IntrinsicKind::FoldLeft
+        | IntrinsicKind::ArrayFromList
+        | IntrinsicKind::ArrayIndex
+        | IntrinsicKind::NewRef
+        | IntrinsicKind::ReadRef
+        | IntrinsicKind::WriteRef
+        | IntrinsicKind::EmptyMap
+        | IntrinsicKind::MapInsert
+        | IntrinsicKind::MapLookup
+        | IntrinsicKind::MapFindWithDefault
+        | IntrinsicKind::ReadInt
+        | IntrinsicKind::ReadDouble
+        | IntrinsicKind::Format => unreachable!(),
+    };
+    Ok(LoweredValue::new(result, ValueTy::Double))
 }
 
 fn prepare_constructor_field(
@@ -1294,7 +2613,11 @@ fn lower_value_to_tl_value(
             })
         }
         ValueTy::Unit => Ok(builder.ins().iconst(ptr_ty, 0)),
-        ValueTy::Data { .. } | ValueTy::Dictionary { .. } | ValueTy::List(_) => {
+        ValueTy::Data { .. }
+        | ValueTy::Dictionary { .. }
+        | ValueTy::List(_)
+        | ValueTy::Array(_)
+        | ValueTy::String => {
             let actual_ty = builder.func.dfg.value_type(lowered.value);
             if actual_ty == ptr_ty {
                 Ok(lowered.value)
@@ -1319,7 +2642,7 @@ fn lower_value_to_tl_value(
                 ))
             }
         }
-        ValueTy::Tuple(_) | ValueTy::Char | ValueTy::String | ValueTy::Function { .. } => {
+        ValueTy::Tuple(_) | ValueTy::Char | ValueTy::Function { .. } => {
             Err(NativeError::unsupported(
                 "CODEGEN143",
                 format!("{} (型 {:?}) は現在未対応です", context, lowered.ty),
@@ -1328,12 +2651,8 @@ fn lower_value_to_tl_value(
     }
 }
 
-#[allow(clippy::too_many_arguments)]
 fn lower_match(
-    module: &mut ObjectModule,
-    ir: &core_ir::Module,
-    runtime: &RuntimeSymbols,
-    func_ids: &HashMap<String, FuncId>,
+    cx: &mut LowerCtx,
     builder: &mut FunctionBuilder,
     env: &mut CodegenEnv,
     scrutinee_expr: &Expr,
@@ -1347,9 +2666,46 @@ fn lower_match(
         ));
     }
 
-    let scrutinee = lower_expr(module, ir, runtime, func_ids, builder, env, scrutinee_expr)?;
-
-    if !matches!(scrutinee.ty, ValueTy::Data { .. } | ValueTy::Unknown) {
+    let scrutinee = lower_expr(cx, builder, env, scrutinee_expr)?;
+
+    // 変数・ワイルドカードだけの単一アームは `let` 相当なので、タグ取得や不一致時の trap を省く。
+    if let [arm] = arms {
+        if arm.guard.is_none() && is_catch_all_pattern(&arm.pattern) {
+            let mut arm_env = env.clone();
+            for binding in &arm.bindings {
+                let bound = coerce_value(
+                    cx.module,
+                    builder,
+                    cx.runtime,
+                    LoweredValue::new(scrutinee.value, scrutinee.ty.clone()),
+                    &binding.ty,
+                )?;
+                let var = arm_env.insert(binding.name.clone(), binding.ty.clone());
+                builder.declare_var(var, clif_type(env.ptr_ty(), &binding.ty)?);
+                builder.def_var(var, bound.value);
+            }
+            let body_value = lower_expr(cx, builder, &mut arm_env, &arm.body)?;
+            if arm.bindings.is_empty() && matches!(scrutinee.ty, ValueTy::Data { .. }) {
+                let free_ref = cx
+                    .module
+                    .declare_func_in_func(cx.runtime.data_free, builder.func);
+                builder.ins().call(free_ref, &[scrutinee.value]);
+            }
+            return coerce_value(cx.module, builder, cx.runtime, body_value, result_ty);
+        }
+    }
+    let newtype = arms.iter().any(|arm| {
+        arm.constructor
+            .as_deref()
+            .and_then(|name| find_constructor_layout(cx.ir, name))
+            .is_some_and(|layout| is_newtype_constructor(cx.ir, layout))
+    });
+
+    // Int/Double/Bool の scrutinee はアンボックスのままリテラルパターンと比較する。
+    let literal =
+        !newtype && matches!(scrutinee.ty, ValueTy::Int | ValueTy::Double | ValueTy::Bool);
+
+    if !newtype && !literal && !matches!(scrutinee.ty, ValueTy::Data { .. } | ValueTy::Unknown) {
         return Err(NativeError::unsupported(
             "CODEGEN161",
             format!(
@@ -1361,12 +2717,17 @@ fn lower_match(
 
     let ptr_ty = env.ptr_ty();
 
-    let tag = {
-        let func_ref = module.declare_func_in_func(runtime.data_tag, builder.func);
+    // newtype はタグを持たないため、コンストラクタ判定は常に成功する。
+    let tag = if newtype || literal {
+        None
+    } else {
+        let func_ref = cx
+            .module
+            .declare_func_in_func(cx.runtime.data_tag, builder.func);
         let call = builder.ins().call(func_ref, &[scrutinee.value]);
-        *builder.inst_results(call).first().ok_or_else(|| {
+        Some(*builder.inst_results(call).first().ok_or_else(|| {
             NativeError::unsupported("CODEGEN162", "tl_data_tag の戻り値が取得できませんでした")
-        })?
+        })?)
     };
 
     let merge_block = builder.create_block();
@@ -1401,11 +2762,20 @@ fn lower_match(
             None
         };
 
-        builder.switch_to_block(current_block);
+        if builder.current_block() != Some(current_block) {
+            builder.switch_to_block(current_block);
+        }
 
-        if let Some(tag_value) = arm.tag {
+        let literal_test = if literal {
+            lower_literal_pattern_test(builder, &scrutinee, &arm.pattern)?
+        } else {
+            None
+        };
+        let fail_block = next_block.unwrap_or(unmatched_block);
+        if let (Some(tag), Some(tag_value)) = (tag, arm.tag) {
             let cmp = builder.ins().icmp_imm(IntCC::Equal, tag, tag_value as i64);
-            let fail_block = next_block.unwrap_or(unmatched_block);
+            builder.ins().brif(cmp, success_block, &[], fail_block, &[]);
+        } else if let Some(cmp) = literal_test {
             builder.ins().brif(cmp, success_block, &[], fail_block, &[]);
         } else {
             builder.ins().jump(success_block, &[]);
@@ -1424,32 +2794,27 @@ fn lower_match(
                 ));
             }
 
-            let value = extract_match_binding_value(
-                module,
-                runtime,
-                builder,
-                ptr_ty,
-                scrutinee.value,
-                binding,
-            )?;
+            let value = if newtype || literal {
+                scrutinee.value
+            } else {
+                extract_match_binding_value(
+                    cx.module,
+                    cx.runtime,
+                    builder,
+                    ptr_ty,
+                    scrutinee.value,
+                    binding,
+                )?
+            };
             let var = arm_env.insert(binding.name.clone(), binding.ty.clone());
             let cl_ty = clif_type(ptr_ty, &binding.ty)?;
             builder.declare_var(var, cl_ty);
             builder.def_var(var, value);
         }
 
-        let mut guard_pass_block = success_block;
         if let Some(guard_expr) = &arm.guard {
             let guard_true = builder.create_block();
-            let guard_val = lower_expr(
-                module,
-                ir,
-                runtime,
-                func_ids,
-                builder,
-                &mut arm_env,
-                guard_expr,
-            )?;
+            let guard_val = lower_expr(cx, builder, &mut arm_env, guard_expr)?;
             if guard_val.ty != ValueTy::Bool {
                 return Err(NativeError::unsupported(
                     "CODEGEN167",
@@ -1463,24 +2828,17 @@ fn lower_match(
                 .brif(guard_cond, guard_true, &[], fail_block, &[]);
             builder.seal_block(guard_true);
             builder.switch_to_block(guard_true);
-            guard_pass_block = guard_true;
         }
 
-        builder.switch_to_block(guard_pass_block);
-
-        let body_value = lower_expr(
-            module,
-            ir,
-            runtime,
-            func_ids,
-            builder,
-            &mut arm_env,
-            &arm.body,
-        )?;
-        let body_value = coerce_value(module, builder, runtime, body_value, result_ty)?;
+        let body_value = lower_expr(cx, builder, &mut arm_env, &arm.body)?;
+        let body_value = coerce_value(cx.module, builder, cx.runtime, body_value, result_ty)?;
 
-        if matches!(scrutinee.ty, ValueTy::Data { .. }) {
-            let free_ref = module.declare_func_in_func(runtime.data_free, builder.func);
+        // scrutinee 全体を束縛したアーム（`whole@(..)` など）は本体が値を参照し得るため解放しない。
+        let binds_scrutinee = arm.bindings.iter().any(|b| b.path.is_empty());
+        if !newtype && !binds_scrutinee && matches!(scrutinee.ty, ValueTy::Data { .. }) {
+            let free_ref = cx
+                .module
+                .declare_func_in_func(cx.runtime.data_free, builder.func);
             builder.ins().call(free_ref, &[scrutinee.value]);
         }
 
@@ -1501,7 +2859,9 @@ fn lower_match(
 
     builder.switch_to_block(unmatched_block);
     builder.seal_block(unmatched_block);
-    let abort_ref = module.declare_func_in_func(runtime.abort, builder.func);
+    let abort_ref = cx
+        .module
+        .declare_func_in_func(cx.runtime.abort, builder.func);
     let code = builder.ins().iconst(types::I32, 2001);
     builder.ins().call(abort_ref, &[code]);
     builder.ins().trap(TrapCode::UnreachableCodeReached);
@@ -1518,29 +2878,30 @@ fn lower_match(
     Ok(LoweredValue::new(result_value, result_ty.clone()))
 }
 
-#[allow(clippy::too_many_arguments)]
 fn lower_list_literal(
-    module: &mut ObjectModule,
-    ir: &core_ir::Module,
-    runtime: &RuntimeSymbols,
-    func_ids: &HashMap<String, FuncId>,
+    cx: &mut LowerCtx,
     builder: &mut FunctionBuilder,
     env: &mut CodegenEnv,
     items: &[Expr],
     ty: &ValueTy,
 ) -> NativeResult<LoweredValue> {
     let ptr_ty = env.ptr_ty();
-    let empty_ref = module.declare_func_in_func(runtime.list_empty, builder.func);
+    let empty_ref = cx
+        .module
+        .declare_func_in_func(cx.runtime.list_empty, builder.func);
     let call = builder.ins().call(empty_ref, &[]);
     let mut acc = *builder.inst_results(call).first().ok_or_else(|| {
         NativeError::unsupported("CODEGEN174", "tl_list_empty の戻り値が取得できませんでした")
     })?;
 
     for (index, item_expr) in items.iter().enumerate().rev() {
-        let lowered = lower_expr(module, ir, runtime, func_ids, builder, env, item_expr)?;
+        let lowered = lower_expr(cx, builder, env, item_expr)?;
         let context = format!("リスト要素 {}", index + 1);
-        let head = lower_value_to_tl_value(module, runtime, builder, ptr_ty, &context, &lowered)?;
-        let cons_ref = module.declare_func_in_func(runtime.list_cons, builder.func);
+        let head =
+            lower_value_to_tl_value(cx.module, cx.runtime, builder, ptr_ty, &context, &lowered)?;
+        let cons_ref = cx
+            .module
+            .declare_func_in_func(cx.runtime.list_cons, builder.func);
         let call = builder.ins().call(cons_ref, &[head, acc]);
         acc = *builder.inst_results(call).first().ok_or_else(|| {
             NativeError::unsupported("CODEGEN175", "tl_list_cons の戻り値が取得できませんでした")
@@ -1550,6 +2911,54 @@ fn lower_list_literal(
     Ok(LoweredValue::new(acc, ty.clone()))
 }
 
+/// 常に一致するパターン（変数・ワイルドカードと、それらへの `as` 束縛）か。
+fn is_catch_all_pattern(pattern: &A::Pattern) -> bool {
+    match pattern {
+        A::Pattern::Wildcard { .. } | A::Pattern::Var { .. } => true,
+        A::Pattern::As { pattern, .. } => is_catch_all_pattern(pattern),
+        _ => false,
+    }
+}
+
+/// リテラルパターンの一致判定を b1 値として生成する。変数・ワイルドカードは常に一致するため `None`。
+fn lower_literal_pattern_test(
+    builder: &mut FunctionBuilder,
+    scrutinee: &LoweredValue,
+    pattern: &A::Pattern,
+) -> NativeResult<Option<Value>> {
+    match (pattern, &scrutinee.ty) {
+        (A::Pattern::Wildcard { .. } | A::Pattern::Var { .. }, _) => Ok(None),
+        (A::Pattern::As { pattern, .. }, _) => {
+            lower_literal_pattern_test(builder, scrutinee, pattern)
+        }
+        (A::Pattern::Int { value, .. }, ValueTy::Int) => Ok(Some(builder.ins().icmp_imm(
+            IntCC::Equal,
+            scrutinee.value,
+            *value,
+        ))),
+        (A::Pattern::Float { value, .. }, ValueTy::Double) => {
+            let expected = builder.ins().f64const(*value);
+            Ok(Some(builder.ins().fcmp(
+                FloatCC::Equal,
+                scrutinee.value,
+                expected,
+            )))
+        }
+        (A::Pattern::Bool { value, .. }, ValueTy::Bool) => Ok(Some(builder.ins().icmp_imm(
+            IntCC::Equal,
+            scrutinee.value,
+            i64::from(*value),
+        ))),
+        (other, ty) => Err(NativeError::unsupported(
+            "CODEGEN176",
+            format!(
+                "パターン {} は型 {:?} の scrutinee に対してネイティブローワリング未対応です",
+                other, ty
+            ),
+        )),
+    }
+}
+
 fn find_constructor_layout<'a>(
     ir: &'a core_ir::Module,
     name: &str,
@@ -1560,6 +2969,12 @@ fn find_constructor_layout<'a>(
         .find(|ctor| ctor.name == name)
 }
 
+fn is_newtype_constructor(ir: &core_ir::Module, layout: &ConstructorLayout) -> bool {
+    ir.data_layouts
+        .get(&layout.parent)
+        .is_some_and(|parent| parent.newtype)
+}
+
 fn extract_match_binding_value(
     module: &mut ObjectModule,
     runtime: &RuntimeSymbols,
@@ -1618,10 +3033,13 @@ fn extract_match_binding_value(
             })?)
         }
         ValueTy::Unit => Ok(builder.ins().iconst(types::I8, 0)),
-        ValueTy::Data { .. } | ValueTy::Dictionary { .. } | ValueTy::List(_) | ValueTy::Unknown => {
-            Ok(current)
-        }
-        ValueTy::Tuple(_) | ValueTy::Function { .. } | ValueTy::Char | ValueTy::String => {
+        ValueTy::Data { .. }
+        | ValueTy::Dictionary { .. }
+        | ValueTy::List(_)
+        | ValueTy::Array(_)
+        | ValueTy::String
+        | ValueTy::Unknown => Ok(current),
+        ValueTy::Tuple(_) | ValueTy::Function { .. } | ValueTy::Char => {
             Err(NativeError::unsupported(
                 "CODEGEN173",
                 format!("Match 束縛型 {:?} は現在未対応です", binding.ty),
@@ -1630,27 +3048,31 @@ fn extract_match_binding_value(
     }
 }
 
-#[allow(clippy::too_many_arguments)]
 fn lower_if(
-    module: &mut ObjectModule,
-    ir: &core_ir::Module,
-    runtime: &RuntimeSymbols,
-    func_ids: &HashMap<String, FuncId>,
+    cx: &mut LowerCtx,
     builder: &mut FunctionBuilder,
     env: &mut CodegenEnv,
     cond: &Expr,
     then_expr: &Expr,
     else_expr: &Expr,
 ) -> NativeResult<LoweredValue> {
-    let condition = lower_expr(module, ir, runtime, func_ids, builder, env, cond)?;
-    if condition.ty != ValueTy::Bool {
-        return Err(NativeError::unsupported(
-            "CODEGEN080",
-            "if 条件式の型は Bool である必要があります",
-        ));
-    }
+    let cond_b1 = lower_if_condition(cx, builder, env, cond)?;
 
-    let cond_b1 = builder.ins().icmp_imm(IntCC::NotEqual, condition.value, 0);
+    // 両分岐が副作用のない小さなスカラー式なら、分岐せず両方を計算して `select` で選ぶ。
+    if is_select_operand(then_expr, env) && is_select_operand(else_expr, env) {
+        let then_value = lower_expr(cx, builder, env, then_expr)?;
+        let else_value = lower_expr(cx, builder, env, else_expr)?;
+        if else_value.ty != then_value.ty {
+            return Err(NativeError::unsupported(
+                "CODEGEN081",
+                "if の両分岐は同じ型を返す必要があります",
+            ));
+        }
+        let selected = builder
+            .ins()
+            .select(cond_b1, then_value.value, else_value.value);
+        return Ok(LoweredValue::new(selected, then_value.ty));
+    }
 
     let then_block = builder.create_block();
     let else_block = builder.create_block();
@@ -1664,15 +3086,7 @@ fn lower_if(
 
     builder.switch_to_block(then_block);
     let mut then_env = env.clone();
-    let then_value = lower_expr(
-        module,
-        ir,
-        runtime,
-        func_ids,
-        builder,
-        &mut then_env,
-        then_expr,
-    )?;
+    let then_value = lower_expr(cx, builder, &mut then_env, then_expr)?;
     let result_ty = then_value.ty.clone();
 
     let merge_ty = clif_type(env.ptr_ty(), &then_value.ty)?;
@@ -1681,15 +3095,7 @@ fn lower_if(
 
     builder.switch_to_block(else_block);
     let mut else_env = env.clone();
-    let else_value = lower_expr(
-        module,
-        ir,
-        runtime,
-        func_ids,
-        builder,
-        &mut else_env,
-        else_expr,
-    )?;
+    let else_value = lower_expr(cx, builder, &mut else_env, else_expr)?;
     if else_value.ty != result_ty {
         return Err(NativeError::unsupported(
             "CODEGEN081",
@@ -1704,6 +3110,160 @@ fn lower_if(
     Ok(LoweredValue::new(merge_param, result_ty))
 }
 
+/// if の条件式を生成し、分岐に使う真偽値を返す。
+fn lower_if_condition(
+    cx: &mut LowerCtx,
+    builder: &mut FunctionBuilder,
+    env: &mut CodegenEnv,
+    cond: &Expr,
+) -> NativeResult<Value> {
+    // 条件式を生成する前に型を確かめ、Bool として扱えない式はその型とともに報告する。
+    if !matches!(cond.ty(), ValueTy::Bool | ValueTy::Unknown) {
+        return Err(NativeError::unsupported(
+            "CODEGEN080",
+            format!(
+                "if 条件式の型は Bool である必要があります: {} の型は {} です",
+                describe_expr(cond),
+                cond.ty()
+            ),
+        ));
+    }
+    let condition = lower_expr(cx, builder, env, cond)?;
+    // 多相関数の戻り値のような箱詰めの値は Bool へ取り出してから分岐する。
+    let condition = coerce_value(cx.module, builder, cx.runtime, condition, &ValueTy::Bool)?;
+    Ok(builder.ins().icmp_imm(IntCC::NotEqual, condition.value, 0))
+}
+
+/// エラーメッセージ向けに式の種類と名前を短く表す。
+fn describe_expr(expr: &Expr) -> String {
+    match expr {
+        Expr::Literal { value, .. } => format!("リテラル {value:?}"),
+        Expr::Var { name, .. } => format!("変数 {name}"),
+        Expr::Apply { func, .. } => match func.as_ref() {
+            Expr::Var { name, .. } => format!("{name} の呼び出し"),
+            _ => "関数適用".into(),
+        },
+        Expr::PrimOp { op, .. } => format!("演算 {op:?}"),
+        Expr::DictionaryPlaceholder {
+            classname,
+            type_repr,
+            ..
+        } => format!("辞書 {classname} {type_repr}"),
+        Expr::Let { .. } => "let 式".into(),
+        Expr::Lambda { .. } => "ラムダ式".into(),
+        Expr::If { .. } => "if 式".into(),
+        Expr::Tuple { .. } => "タプル".into(),
+        Expr::List { .. } => "リスト".into(),
+        Expr::Match { .. } => "case 式".into(),
+    }
+}
+
+/// 無条件に計算しても観測できる違いが生じない `if` の分岐か判定する。
+///
+/// 対象はスカラー型のリテラル・変数と、それらを組み合わせた演算に限る。
+/// 呼び出し、辞書経由の演算、ゼロ除算で trap し得る除算・剰余、
+/// 検査付き算術でオーバーフロー時に中断する加減乗算、分岐を伴う `&&`/`||` は含めない。
+fn is_select_operand(expr: &Expr, env: &CodegenEnv) -> bool {
+    if !matches!(
+        expr.ty(),
+        ValueTy::Int | ValueTy::Double | ValueTy::Bool | ValueTy::Char
+    ) {
+        return false;
+    }
+    match expr {
+        Expr::Literal { .. } => true,
+        Expr::Var {
+            kind: VarKind::Local | VarKind::Param,
+            ..
+        } => true,
+        Expr::PrimOp {
+            op,
+            args,
+            dict_fallback: false,
+            ..
+        } => {
+            let may_stop = match op {
+                PrimOp::DivInt | PrimOp::ModInt | PrimOp::AndBool | PrimOp::OrBool => true,
+                PrimOp::AddInt | PrimOp::SubInt | PrimOp::MulInt => env.checked_arithmetic,
+                _ => false,
+            };
+            !may_stop && args.iter().all(|arg| is_select_operand(arg, env))
+        }
+        _ => false,
+    }
+}
+
+/// オーバーフローフラグ付きの整数演算を生成し、溢れた場合は `tl_abort_with_message` で中断する。
+fn checked_int_op<F>(
+    module: &mut ObjectModule,
+    runtime: &RuntimeSymbols,
+    builder: &mut FunctionBuilder,
+    lhs: LoweredValue,
+    rhs: LoweredValue,
+    f: F,
+) -> NativeResult<LoweredValue>
+where
+    F: FnOnce(&mut FunctionBuilder, Value, Value) -> (Value, Value),
+{
+    if lhs.ty != ValueTy::Int || rhs.ty != ValueTy::Int {
+        return Err(NativeError::unsupported(
+            "CODEGEN082",
+            "整数演算の引数型が一致しません",
+        ));
+    }
+    let (result, overflow) = f(builder, lhs.value, rhs.value);
+    let overflow_block = builder.create_block();
+    let cont_block = builder.create_block();
+    builder
+        .ins()
+        .brif(overflow, overflow_block, &[], cont_block, &[]);
+
+    builder.switch_to_block(overflow_block);
+    builder.seal_block(overflow_block);
+    let abort_ref = module.declare_func_in_func(runtime.abort, builder.func);
+    let code = builder.ins().iconst(types::I32, 2002);
+    builder.ins().call(abort_ref, &[code]);
+    builder.ins().trap(TrapCode::IntegerOverflow);
+
+    builder.switch_to_block(cont_block);
+    builder.seal_block(cont_block);
+    Ok(LoweredValue::new(result, ValueTy::Int))
+}
+
+/// 丸めた Double を Int へ変換する。NaN・無限大・Int の範囲外は `fcvt_to_sint` が
+/// ハードウェア例外になるため、事前に検査して `tl_abort_with_message` (code=2006) で中断する。
+fn checked_float_to_int(
+    module: &mut ObjectModule,
+    runtime: &RuntimeSymbols,
+    builder: &mut FunctionBuilder,
+    rounded: Value,
+) -> Value {
+    // -2^63 以上 2^63 未満なら表現できる。NaN はどちらの比較も偽になる。
+    let min = builder.ins().f64const(i64::MIN as f64);
+    let max = builder.ins().f64const(-(i64::MIN as f64));
+    let above_min = builder
+        .ins()
+        .fcmp(FloatCC::GreaterThanOrEqual, rounded, min);
+    let below_max = builder.ins().fcmp(FloatCC::LessThan, rounded, max);
+    let in_range = builder.ins().band(above_min, below_max);
+    let out_of_range_block = builder.create_block();
+    let cont_block = builder.create_block();
+    builder
+        .ins()
+        .brif(in_range, cont_block, &[], out_of_range_block, &[]);
+
+    builder.switch_to_block(out_of_range_block);
+    builder.seal_block(out_of_range_block);
+    let abort_ref = module.declare_func_in_func(runtime.abort, builder.func);
+    let code = builder.ins().iconst(types::I32, 2006);
+    builder.ins().call(abort_ref, &[code]);
+    builder.ins().trap(TrapCode::IntegerOverflow);
+
+    builder.switch_to_block(cont_block);
+    builder.seal_block(cont_block);
+    builder.ins().fcvt_to_sint(types::I64, rounded)
+}
+
 fn binary_int_op<F>(
     builder: &mut FunctionBuilder,
     lhs: LoweredValue,
@@ -1752,7 +3312,8 @@ fn compare_int(
     rhs: LoweredValue,
     cc: IntCC,
 ) -> NativeResult<LoweredValue> {
-    if lhs.ty != ValueTy::Int || rhs.ty != ValueTy::Int {
+    // Char はコードポイントの整数として持つため、Int と同じ命令で比較できる。
+    if lhs.ty != rhs.ty || !matches!(lhs.ty, ValueTy::Int | ValueTy::Char) {
         return Err(NativeError::unsupported(
             "CODEGEN083",
             "比較演算の引数型が Int ではありません",
@@ -1794,28 +3355,6 @@ fn bool_from_b1(builder: &mut FunctionBuilder, value: Value) -> Value {
     builder.ins().select(value, one, zero)
 }
 
-fn binary_bool_op<F>(
-    builder: &mut FunctionBuilder,
-    lhs: LoweredValue,
-    rhs: LoweredValue,
-    f: F,
-) -> NativeResult<LoweredValue>
-where
-    F: FnOnce(&mut FunctionBuilder, Value, Value) -> Value,
-{
-    if lhs.ty != ValueTy::Bool || rhs.ty != ValueTy::Bool {
-        return Err(NativeError::unsupported(
-            "CODEGEN086",
-            "Bool 演算の引数が Bool ではありません",
-        ));
-    }
-    let lhs_b = bool_to_b1(builder, lhs.value);
-    let rhs_b = bool_to_b1(builder, rhs.value);
-    let out_b = f(builder, lhs_b, rhs_b);
-    let as_i8 = bool_from_b1(builder, out_b);
-    Ok(LoweredValue::new(as_i8, ValueTy::Bool))
-}
-
 fn unary_bool_op<F>(
     builder: &mut FunctionBuilder,
     val: LoweredValue,
@@ -1866,7 +3405,9 @@ fn is_supported_param_type(ty: &ValueTy) -> bool {
             | ValueTy::Double
             | ValueTy::Data { .. }
             | ValueTy::List(_)
+            | ValueTy::Array(_)
             | ValueTy::Dictionary { .. }
+            | ValueTy::String
             | ValueTy::Unknown
     )
 }
@@ -1880,7 +3421,9 @@ fn is_supported_return_type(ty: &ValueTy) -> bool {
             | ValueTy::Unit
             | ValueTy::Data { .. }
             | ValueTy::List(_)
+            | ValueTy::Array(_)
             | ValueTy::Dictionary { .. }
+            | ValueTy::String
             | ValueTy::Unknown
     )
 }
@@ -1900,21 +3443,38 @@ fn make_signature(func: &Function, call_conv: CallConv, ptr_ty: Type) -> NativeR
 
 fn clif_type(ptr_ty: Type, ty: &ValueTy) -> NativeResult<Type> {
     match ty {
-        ValueTy::Int => Ok(types::I64),
+        ValueTy::Int | ValueTy::Char => Ok(types::I64),
         ValueTy::Bool => Ok(types::I8),
         ValueTy::Unit => Ok(types::I8),
         ValueTy::Double => Ok(types::F64),
-        ValueTy::Data { .. } | ValueTy::List(_) | ValueTy::Dictionary { .. } | ValueTy::Unknown => {
-            Ok(ptr_ty)
-        }
-        ValueTy::Char | ValueTy::String | ValueTy::Tuple(_) | ValueTy::Function { .. } => Err(
-            NativeError::unsupported("CODEGEN100", format!("型 {:?} は現在未対応です", ty)),
-        ),
+        ValueTy::Data { .. }
+        | ValueTy::List(_)
+        | ValueTy::Array(_)
+        | ValueTy::Dictionary { .. }
+        | ValueTy::String
+        | ValueTy::Unknown => Ok(ptr_ty),
+        ValueTy::Tuple(_) | ValueTy::Function { .. } => Err(NativeError::unsupported(
+            "CODEGEN100",
+            format!("型 {:?} は現在未対応です", ty),
+        )),
     }
 }
 
+/// 関数名をリンカが受け付けるシンボル名へ変換する。
+///
+/// ASCII 英数字はそのまま残し、`_` は `__`、それ以外の文字は `_{コードポイントの16進}_` に置き換える。
+/// 復元が一意に定まるため、異なる関数名が同じシンボルになることはない。
 fn symbol_name(name: &str) -> String {
-    format!("{SYMBOL_PREFIX}{name}")
+    let mut symbol = String::with_capacity(SYMBOL_PREFIX.len() + name.len());
+    symbol.push_str(SYMBOL_PREFIX);
+    for ch in name.chars() {
+        match ch {
+            'a'..='z' | 'A'..='Z' | '0'..='9' => symbol.push(ch),
+            '_' => symbol.push_str("__"),
+            other => symbol.push_str(&format!("_{:x}_", other as u32)),
+        }
+    }
+    symbol
 }
 
 #[derive(Clone)]
@@ -1925,7 +3485,6 @@ struct VarInfo {
 
 #[derive(Clone)]
 struct DictionaryParamBinding {
-    classname: String,
     type_repr: String,
     var: Variable,
     value_ty: Option<ValueTy>,
@@ -1934,25 +3493,57 @@ struct DictionaryParamBinding {
 #[derive(Clone)]
 struct CodegenEnv {
     vars: HashMap<String, VarInfo>,
-    next_index: u32,
+    /// 変数番号の採番器。`let` などで環境を複製しても関数内で番号が重複しないよう共有する。
+    next_index: Rc<Cell<u32>>,
     ptr_ty: Type,
     dict_symbols: DictionarySymbols,
     dict_cache: HashMap<(String, String), Value>,
     dict_params: HashMap<(String, String), DictionaryParamBinding>,
     dict_params_by_class: HashMap<String, Vec<String>>,
+    checked_arithmetic: bool,
+    string_literals: StringLiterals,
 }
 
+/// 文字列リテラルの内容から、そのバイト列を置いた読み取り専用データへの表。
+/// モジュール全体で共有し、同じ内容のリテラルは 1 つのデータにまとめる。
+type StringLiterals = Rc<RefCell<HashMap<String, DataId>>>;
+
 impl CodegenEnv {
-    fn new(ptr_ty: Type, dict_symbols: DictionarySymbols) -> Self {
+    fn new(ptr_ty: Type, dict_symbols: DictionarySymbols, string_literals: StringLiterals) -> Self {
         Self {
             vars: HashMap::new(),
-            next_index: 0,
+            next_index: Rc::new(Cell::new(0)),
             ptr_ty,
             dict_symbols,
             dict_cache: HashMap::new(),
             dict_params: HashMap::new(),
             dict_params_by_class: HashMap::new(),
+            checked_arithmetic: false,
+            string_literals,
+        }
+    }
+
+    /// 文字列リテラル `text` のバイト列を置いたデータを返す。初出の内容なら定義する。
+    fn string_literal_data(&self, module: &mut ObjectModule, text: &str) -> NativeResult<DataId> {
+        let mut literals = self.string_literals.borrow_mut();
+        if let Some(data) = literals.get(text) {
+            return Ok(*data);
         }
+        let name = format!("tl_str_{}", literals.len());
+        let declare_err = |err| {
+            NativeError::unsupported(
+                "CODEGEN035",
+                format!("文字列リテラル {name} の定義に失敗しました: {err}"),
+            )
+        };
+        let data = module
+            .declare_data(&name, Linkage::Local, false, false)
+            .map_err(declare_err)?;
+        let mut desc = DataDescription::new();
+        desc.define(text.as_bytes().to_vec().into_boxed_slice());
+        module.define_data(data, &desc).map_err(declare_err)?;
+        literals.insert(text.to_string(), data);
+        Ok(data)
     }
 
     fn insert_existing(
@@ -1972,7 +3563,6 @@ impl CodegenEnv {
             });
             let key = (classname.clone(), repr.clone());
             let binding = DictionaryParamBinding {
-                classname: classname.clone(),
                 type_repr: repr.clone(),
                 var,
                 value_ty: dict_value_ty,
@@ -1995,8 +3585,9 @@ impl CodegenEnv {
     }
 
     fn insert(&mut self, name: String, ty: ValueTy) -> Variable {
-        let var = Variable::from_u32(self.next_index);
-        self.next_index += 1;
+        let index = self.next_index.get();
+        self.next_index.set(index + 1);
+        let var = Variable::from_u32(index);
         self.vars.insert(name, VarInfo { var, ty });
         var
     }
@@ -2009,7 +3600,7 @@ impl CodegenEnv {
         self.ptr_ty
     }
 
-    fn lookup_dictionary(&self, classname: &str, type_repr: &str) -> Option<FuncId> {
+    fn lookup_dictionary(&self, classname: &str, type_repr: &str) -> Option<DictionarySymbol> {
         self.dict_symbols
             .get(&(classname.to_string(), type_repr.to_string()))
             .copied()
@@ -2072,21 +3663,44 @@ impl CodegenEnv {
         builder: &mut FunctionBuilder,
         classname: &str,
         type_repr: &str,
-        func_id: FuncId,
+        symbol: DictionarySymbol,
     ) -> NativeResult<Value> {
         let key = (classname.to_string(), type_repr.to_string());
         if let Some(value) = self.dict_cache.get(&key) {
             return Ok(*value);
         }
-        let func_ref = module.declare_func_in_func(func_id, builder.func);
+        // 大域変数に保存済みならそれを使い、まだなら一度だけビルダーを呼んで保存する。
+        let cache_gv = module.declare_data_in_func(symbol.cache, builder.func);
+        let cache_addr = builder.ins().global_value(self.ptr_ty, cache_gv);
+        let cached = builder
+            .ins()
+            .load(self.ptr_ty, MemFlags::trusted(), cache_addr, 0);
+        let build_block = builder.create_block();
+        let done_block = builder.create_block();
+        builder.append_block_param(done_block, self.ptr_ty);
+        builder
+            .ins()
+            .brif(cached, done_block, &[cached], build_block, &[]);
+
+        builder.switch_to_block(build_block);
+        builder.seal_block(build_block);
+        let func_ref = module.declare_func_in_func(symbol.builder, builder.func);
         let call = builder.ins().call(func_ref, &[]);
         let results = builder.inst_results(call);
-        let value = *results.first().ok_or_else(|| {
+        let built = *results.first().ok_or_else(|| {
             NativeError::unsupported(
                 "CODEGEN302",
                 format!("辞書 {classname}<{type_repr}> の生成に失敗しました"),
             )
         })?;
+        builder
+            .ins()
+            .store(MemFlags::trusted(), built, cache_addr, 0);
+        builder.ins().jump(done_block, &[built]);
+
+        builder.switch_to_block(done_block);
+        builder.seal_block(done_block);
+        let value = builder.block_params(done_block)[0];
         self.dict_cache.insert(key, value);
         Ok(value)
     }
@@ -2138,6 +3752,25 @@ fn coerce_value(
             let raw = call_runtime(builder, module, runtime.value_to_bool, &[value.value]);
             Ok(LoweredValue::new(raw, ValueTy::Bool))
         }
+        // リストの要素は常に TlValue として格納されるため、要素型が異なっても表現は同一。
+        (ValueTy::List(_), ValueTy::List(_)) | (ValueTy::Array(_), ValueTy::Array(_)) => {
+            Ok(LoweredValue::new(value.value, target.clone()))
+        }
+        // データも同様に、型引数だけが異なる場合は同じポインタをそのまま使える。
+        (
+            ValueTy::Data {
+                constructor: from, ..
+            },
+            ValueTy::Data {
+                constructor: to, ..
+            },
+        ) if from == to => Ok(LoweredValue::new(value.value, target.clone())),
+        (from, ValueTy::Unknown) if is_pointer_value_ty(from) => {
+            Ok(LoweredValue::new(value.value, ValueTy::Unknown))
+        }
+        (ValueTy::Unknown, to) if is_pointer_value_ty(to) => {
+            Ok(LoweredValue::new(value.value, to.clone()))
+        }
         _ => Err(NativeError::unsupported(
             "CODEGEN214",
             format!("型 {:?} から {:?} への変換は未対応です", value.ty, target),
@@ -2145,6 +3778,20 @@ fn coerce_value(
     }
 }
 
+/// 既にポインタ表現（`ptr_ty`）で受け渡している型か判定する。
+/// これらは `Unknown` との間で箱詰めせずにそのまま受け渡せる。
+/// タプルは `clif_type` が対応した時点でここへ加える。
+fn is_pointer_value_ty(ty: &ValueTy) -> bool {
+    matches!(
+        ty,
+        ValueTy::List(_)
+            | ValueTy::Array(_)
+            | ValueTy::Data { .. }
+            | ValueTy::Dictionary { .. }
+            | ValueTy::String
+    )
+}
+
 fn call_runtime(
     builder: &mut FunctionBuilder,
     module: &mut ObjectModule,
@@ -2155,3 +3802,245 @@ fn call_runtime(
     let call = builder.ins().call(func_ref, args);
     builder.inst_results(call)[0]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// プログラム中の関数 `name` を Cranelift IR まで変換し、参照した外部関数の ID を返す。
+    fn referenced_runtime_calls(
+        src: &str,
+        name: &str,
+        options: &CodegenOptions,
+    ) -> (Vec<u32>, RuntimeSymbols) {
+        let (clif, runtime, _) = lower_test_function(src, name, options);
+        let calls = clif
+            .params
+            .user_named_funcs()
+            .values()
+            .map(|name| name.index)
+            .collect();
+        (calls, runtime)
+    }
+
+    /// プログラム中の関数 `name` を Cranelift IR まで変換する。
+    fn lower_test_function(
+        src: &str,
+        name: &str,
+        options: &CodegenOptions,
+    ) -> (ClifFunction, RuntimeSymbols, DictionarySymbols) {
+        let program = crate::parser::parse_program(src).expect("parse program");
+        let mut ir = crate::compile_core_ir(&program).expect("lower to core ir");
+        crate::codegen::dictionary_codegen::assign_missing_builders(&mut ir.dictionaries)
+            .expect("dictionary builders");
+        let isa = build_isa().expect("host isa");
+        let obj_builder = ObjectBuilder::new(
+            isa,
+            "typelang_test",
+            cranelift_module::default_libcall_names(),
+        )
+        .expect("object builder");
+        let mut module = ObjectModule::new(obj_builder);
+        let call_conv = module.isa().default_call_conv();
+        let runtime = declare_runtime_symbols(&mut module, call_conv).expect("runtime symbols");
+        let func_ids = declare_functions(&ir, &mut module, call_conv).expect("declare functions");
+        let dict_symbols =
+            declare_dictionary_symbols(&ir, &mut module, call_conv).expect("dictionary symbols");
+        let string_literals = StringLiterals::default();
+        let mut cx = LowerCtx {
+            module: &mut module,
+            ir: &ir,
+            runtime: &runtime,
+            func_ids: &func_ids,
+            dict_symbols: &dict_symbols,
+            string_literals: &string_literals,
+        };
+        let clif = build_function(
+            &mut cx,
+            name,
+            &ir.functions[name],
+            call_conv,
+            options,
+            &mut FunctionBuilderContext::new(),
+        )
+        .expect("build function");
+        (clif, runtime, dict_symbols)
+    }
+
+    #[test]
+    fn dictionaries_are_built_once_into_module_globals() {
+        let src = r#"
+step :: Num a => a -> a -> a;
+let step acc x = acc + x;
+
+count :: Int -> Int -> Int;
+let count n acc = if n == 0 then acc else count (n - 1) (step acc n);
+
+main :: Int;
+let main = count 3 0;
+"#;
+        let (clif, _, dict_symbols) = lower_test_function(src, "count", &CodegenOptions::default());
+        let num_int = dict_symbols[&("Num".to_string(), "Int".to_string())];
+        // 辞書は大域変数から読み、空のときだけビルダーを呼んで保存する。
+        let text = clif.display().to_string();
+        assert_eq!(clif.global_values.len(), 1, "{text}");
+        assert!(
+            clif.params
+                .user_named_funcs()
+                .values()
+                .any(|name| name.index == num_int.builder.as_u32()),
+            "{text}"
+        );
+        assert_eq!(text.matches(" = load.").count(), 1, "{text}");
+        assert_eq!(text.matches("store").count(), 1, "{text}");
+    }
+
+    #[test]
+    fn scalar_if_without_effects_lowers_to_select() {
+        let src = r#"
+pick :: Bool -> Int;
+let pick c = if c then 1 else 2;
+
+guarded :: Int -> Int;
+let guarded n = if n == 0 then 0 else pick (n > 1);
+
+main :: Int;
+let main = pick True + guarded 6;
+"#;
+        let options = CodegenOptions::default();
+        let (clif, _, _) = lower_test_function(src, "pick", &options);
+        let text = clif.display().to_string();
+        assert!(text.contains("select"), "{text}");
+        assert!(!text.contains("brif"), "{text}");
+        assert_eq!(clif.layout.blocks().count(), 1, "{text}");
+
+        // 呼び出しを含む分岐は、条件が成り立つときだけ評価するよう分岐のまま残す。
+        let (clif, _, _) = lower_test_function(src, "guarded", &options);
+        let text = clif.display().to_string();
+        assert!(text.contains("brif"), "{text}");
+    }
+
+    #[test]
+    fn single_catch_all_arm_skips_tag_dispatch() {
+        let src = r#"
+data Slot = Empty | Full Int;
+
+keep :: Slot -> Slot;
+let keep s = (case s of t -> t);
+
+unwrap :: Slot -> Int;
+let unwrap s = (case s of Full n -> n; Empty -> 0);
+
+main :: Int;
+let main = unwrap (keep (Full 3));
+"#;
+        let (calls, runtime) = referenced_runtime_calls(src, "keep", &CodegenOptions::default());
+        assert!(!calls.contains(&runtime.data_tag.as_u32()));
+        assert!(!calls.contains(&runtime.abort.as_u32()));
+
+        let (calls, runtime) = referenced_runtime_calls(src, "unwrap", &CodegenOptions::default());
+        assert!(calls.contains(&runtime.data_tag.as_u32()));
+        assert!(calls.contains(&runtime.abort.as_u32()));
+    }
+
+    #[test]
+    fn call_depth_limit_wraps_function_bodies() {
+        let src = r#"
+count :: Int -> Int;
+let count n = if n == 0 then 0 else 1 + count (n - 1);
+
+main :: Int;
+let main = count 3;
+"#;
+        let (calls, runtime) = referenced_runtime_calls(src, "count", &CodegenOptions::default());
+        assert!(!calls.contains(&runtime.stack_enter.as_u32()));
+
+        let options = CodegenOptions {
+            max_call_depth: Some(100),
+            ..CodegenOptions::default()
+        };
+        let (calls, runtime) = referenced_runtime_calls(src, "count", &options);
+        assert!(calls.contains(&runtime.stack_enter.as_u32()));
+        assert!(calls.contains(&runtime.stack_leave.as_u32()));
+    }
+
+    #[test]
+    fn symbol_names_escape_identifiers_injectively() {
+        assert_eq!(symbol_name("main"), "tl_user_main");
+        assert_eq!(symbol_name("print_int"), "tl_user_print__int");
+        assert_eq!(symbol_name("go'"), "tl_user_go_27_");
+        assert_eq!(symbol_name("Foo.bar"), "tl_user_Foo_2e_bar");
+        assert_eq!(symbol_name("λ"), "tl_user__3bb_");
+        assert_ne!(symbol_name("a_27_"), symbol_name("a'"));
+    }
+
+    #[test]
+    fn fnv1a_matches_reference_vectors() {
+        assert_eq!(fnv1a_64(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a_64(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a_64(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn method_signatures_instantiate_to_operand_types() {
+        let (params, result) =
+            instantiate_method_signature("a -> a -> Bool", &ValueTy::Double).unwrap();
+        assert_eq!(params, vec![ValueTy::Double, ValueTy::Double]);
+        assert_eq!(result, ValueTy::Bool);
+        let (params, result) = instantiate_method_signature("Int -> a", &ValueTy::Double).unwrap();
+        assert_eq!(params, vec![ValueTy::Int]);
+        assert_eq!(result, ValueTy::Double);
+        let (params, _) = instantiate_method_signature("Bool -> Bool", &ValueTy::Int).unwrap();
+        assert_eq!(params, vec![ValueTy::Bool]);
+        assert!(instantiate_method_signature("a -> Maybe a", &ValueTy::Int).is_err());
+    }
+
+    #[test]
+    fn dictionary_keyed_on_structured_type_is_found_from_operand_type() {
+        use crate::typesys::{TApp, TCon, Type};
+
+        let con = |name: &str| Type::TCon(TCon { name: name.into() });
+        let app = |func: Type, arg: Type| {
+            Type::TApp(TApp {
+                func: Box::new(func),
+                arg: Box::new(arg),
+            })
+        };
+        let cases = [
+            (
+                app(con("[]"), con("Int")),
+                ValueTy::List(Box::new(ValueTy::Int)),
+            ),
+            (
+                app(app(con("Pair"), con("Int")), con("Integer")),
+                ValueTy::Data {
+                    constructor: "Pair".into(),
+                    args: vec![ValueTy::Int, ValueTy::Int],
+                },
+            ),
+        ];
+        for (ty, operand_ty) in cases {
+            let mut ir = core_ir::Module::new();
+            ir.dictionaries.push(core_ir::DictionaryInit {
+                classname: "Eq".into(),
+                type_repr: type_repr::from_type(&ty),
+                value_ty: operand_ty.clone(),
+                methods: vec![core_ir::DictionaryMethod {
+                    name: "eq".into(),
+                    signature: None,
+                    symbol: "tl_eq".into(),
+                    method_id: 0,
+                }],
+                scheme_repr: String::new(),
+                builder: core_ir::DictionaryBuilder::Unresolved,
+                origin: "test".into(),
+                source_span: Default::default(),
+            });
+            let operand = LoweredValue::new(Value::from_u32(0), operand_ty.clone());
+            let hint = preferred_dictionary_type_repr(&operand, None).expect("operand repr");
+            let found = ensure_dictionary_method_available(&ir, "Eq", &hint, 0)
+                .expect("dictionary keyed on the same type");
+            assert_eq!(found, operand_ty);
+        }
+    }
+}
@@ -28,7 +28,14 @@ pub fn generate(dictionaries: &[DictionaryInit]) -> NativeResult<Option<NamedTem
 
     let mut file = NamedTempFile::new()?;
     write_header(&mut file)?;
+    // リスト・タプルの辞書は要素型が違っても同じビルダーを共有するため、定義は一度だけ出力する。
+    let mut written = HashSet::new();
     for dict in &sorted {
+        if let Some(symbol) = dict.builder.as_str() {
+            if !written.insert(symbol.to_string()) {
+                continue;
+            }
+        }
         write_dictionary(&mut file, dict)?;
     }
     file.flush()?;
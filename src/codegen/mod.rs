@@ -38,6 +38,14 @@ impl NativeError {
         }
     }
 
+    /// `CODEGEN`/`COREIR` などの診断コード。コードを持たないエラーでは `None`。
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            Self::Unsupported { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+
     pub fn command_failure(
         command: impl Into<String>,
         status: Option<ExitStatus>,
@@ -60,6 +68,19 @@ impl From<crate::core_ir::CoreIrError> for NativeError {
     }
 }
 
+impl From<crate::core_ir::CoreIrErrors> for NativeError {
+    /// 1 件ならそのまま、複数件なら `[CODE] message` を 1 行ずつ並べて 1 つのエラーにまとめる。
+    fn from(errs: crate::core_ir::CoreIrErrors) -> Self {
+        match errs.0.as_slice() {
+            [single] => Self::unsupported(single.code, single.message.clone()),
+            _ => Self::unsupported(
+                errs.first().code,
+                format!("{} 件のエラーがあります:\n{}", errs.0.len(), errs),
+            ),
+        }
+    }
+}
+
 impl From<tempfile::PersistError> for NativeError {
     fn from(err: tempfile::PersistError) -> Self {
         NativeError::Io(err.error)
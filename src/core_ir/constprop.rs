@@ -0,0 +1,275 @@
+// パス: src/core_ir/constprop.rs
+// 役割: `let` で束縛したリテラルや変数を参照箇所へ伝播し、リテラル同士の演算を畳み込む
+// 意図: `let x = 5 in x + x` のような式で不要な束縛と読み出しを生成しないようにする
+// 関連ファイル: src/core_ir/inline.rs, src/core_ir/mod.rs, src/lib.rs
+//! Core IR の定数伝播
+//!
+//! - 伝播するのは、スカラー型(`Int`/`Double`/`Bool`/`Char`/`Unit`)のリテラルと、ローカル変数・仮引数への束縛に限る。
+//!   どちらも評価に副作用がないため、伝播した束縛は取り除く。
+//! - `let`・ラムダ・パターンで同名の束縛が現れたら、その有効範囲では伝播を止める。
+//!   変数は、束縛の有効範囲内で参照先の名前が束縛し直されない場合に限って伝播する。
+//! - 畳み込むのは結果が実行環境によらない演算だけとし、オーバーフローする整数演算や除算・シフトは残す。
+
+use std::collections::HashMap;
+
+use super::{Binding, Expr, Literal, Module, PrimOp, ValueTy, VarKind};
+
+/// モジュール内の全関数本体に定数伝播と畳み込みを適用する。
+pub fn propagate_constants(module: &mut Module) {
+    for func in module.functions.values_mut() {
+        propagate(&mut func.body, &Scope::default());
+    }
+}
+
+/// 伝播中の束縛。名前から置き換え先の式を引く。
+#[derive(Clone, Default)]
+struct Scope {
+    substitutions: HashMap<String, Expr>,
+}
+
+impl Scope {
+    /// `name` を新たに束縛する範囲へ入る。外側で同名に対して行っていた伝播を打ち切る。
+    fn shadow(&mut self, name: &str) {
+        self.substitutions.remove(name);
+    }
+}
+
+fn propagate(expr: &mut Expr, scope: &Scope) {
+    match expr {
+        Expr::Var { name, kind, .. } => {
+            if matches!(kind, VarKind::Local | VarKind::Param) {
+                if let Some(value) = scope.substitutions.get(name) {
+                    *expr = value.clone();
+                }
+            }
+        }
+        Expr::Literal { .. } | Expr::DictionaryPlaceholder { .. } => {}
+        Expr::Let { bindings, body, ty } => {
+            let mut inner = scope.clone();
+            let mut kept = Vec::with_capacity(bindings.len());
+            let mut rest = std::mem::take(bindings).into_iter();
+            while let Some(mut binding) = rest.next() {
+                propagate(&mut binding.value, &inner);
+                inner.shadow(&binding.name);
+                let captured = match &binding.value {
+                    Expr::Var { name, .. } => {
+                        rest.as_slice()
+                            .iter()
+                            .any(|later| later.name == *name || rebinds(&later.value, name))
+                            || rebinds(body, name)
+                    }
+                    _ => false,
+                };
+                if is_propagatable(&binding) && !captured {
+                    inner.substitutions.insert(binding.name, binding.value);
+                } else {
+                    kept.push(binding);
+                }
+            }
+            propagate(body, &inner);
+            // 束縛がすべて消えても、本体の型が `let` の型と異なる場合は変換のために `let` を残す。
+            if kept.is_empty() && body.ty() == ty {
+                *expr = take(body);
+            } else {
+                *bindings = kept;
+            }
+        }
+        Expr::Lambda { params, body, .. } => {
+            let mut inner = scope.clone();
+            for param in params.iter() {
+                inner.shadow(&param.name);
+            }
+            propagate(body, &inner);
+        }
+        Expr::Apply { func, args, .. } => {
+            propagate(func, scope);
+            args.iter_mut().for_each(|arg| propagate(arg, scope));
+        }
+        Expr::If {
+            cond,
+            then_branch,
+            else_branch,
+            ty,
+        } => {
+            propagate(cond, scope);
+            propagate(then_branch, scope);
+            propagate(else_branch, scope);
+            if let Expr::Literal {
+                value: Literal::Bool(flag),
+                ..
+            } = cond.as_ref()
+            {
+                let chosen = if *flag { then_branch } else { else_branch };
+                if chosen.ty() == ty {
+                    *expr = take(chosen);
+                }
+            }
+        }
+        Expr::PrimOp {
+            op,
+            args,
+            dict_fallback,
+            ..
+        } => {
+            args.iter_mut().for_each(|arg| propagate(arg, scope));
+            if !*dict_fallback {
+                if let Some(folded) = fold_primop(*op, args) {
+                    *expr = folded;
+                }
+            }
+        }
+        Expr::Tuple { items, .. } | Expr::List { items, .. } => {
+            items.iter_mut().for_each(|item| propagate(item, scope));
+        }
+        Expr::Match {
+            scrutinee, arms, ..
+        } => {
+            propagate(scrutinee, scope);
+            for arm in arms {
+                let mut inner = scope.clone();
+                for binding in &arm.bindings {
+                    inner.shadow(&binding.name);
+                }
+                if let Some(guard) = &mut arm.guard {
+                    propagate(guard, &inner);
+                }
+                propagate(&mut arm.body, &inner);
+            }
+        }
+    }
+}
+
+/// 参照箇所へ複製しても評価結果が変わらない束縛か。
+fn is_propagatable(binding: &Binding) -> bool {
+    // 束縛の型と値の型が異なる場合は箱詰めなどの変換を伴うため、束縛を残す。
+    if binding.value.ty() != &binding.ty {
+        return false;
+    }
+    match &binding.value {
+        Expr::Literal { value, .. } => !matches!(value, Literal::String(_) | Literal::EmptyList),
+        Expr::Var { kind, .. } => matches!(kind, VarKind::Local | VarKind::Param),
+        _ => false,
+    }
+}
+
+/// 式の中で `name` が `let`・ラムダ・パターンによって束縛し直されるか。
+fn rebinds(expr: &Expr, name: &str) -> bool {
+    match expr {
+        Expr::Literal { .. } | Expr::Var { .. } | Expr::DictionaryPlaceholder { .. } => false,
+        Expr::Let { bindings, body, .. } => {
+            bindings
+                .iter()
+                .any(|binding| binding.name == name || rebinds(&binding.value, name))
+                || rebinds(body, name)
+        }
+        Expr::Lambda { params, body, .. } => {
+            params.iter().any(|param| param.name == name) || rebinds(body, name)
+        }
+        Expr::Apply { func, args, .. } => {
+            rebinds(func, name) || args.iter().any(|arg| rebinds(arg, name))
+        }
+        Expr::If {
+            cond,
+            then_branch,
+            else_branch,
+            ..
+        } => rebinds(cond, name) || rebinds(then_branch, name) || rebinds(else_branch, name),
+        Expr::PrimOp { args: items, .. } | Expr::Tuple { items, .. } | Expr::List { items, .. } => {
+            items.iter().any(|item| rebinds(item, name))
+        }
+        Expr::Match {
+            scrutinee, arms, ..
+        } => {
+            rebinds(scrutinee, name)
+                || arms.iter().any(|arm| {
+                    arm.bindings.iter().any(|binding| binding.name == name)
+                        || arm.guard.as_ref().is_some_and(|guard| rebinds(guard, name))
+                        || rebinds(&arm.body, name)
+                })
+        }
+    }
+}
+
+fn take(expr: &mut Expr) -> Expr {
+    std::mem::replace(
+        expr,
+        Expr::Literal {
+            value: Literal::Unit,
+            ty: ValueTy::Unit,
+        },
+    )
+}
+
+/// 被演算子がリテラルの演算を、実行時と同じ結果のリテラルへ置き換える。
+fn fold_primop(op: PrimOp, args: &[Expr]) -> Option<Expr> {
+    use PrimOp::*;
+
+    let literal = |index: usize| match args.get(index) {
+        Some(Expr::Literal { value, .. }) => Some(value),
+        _ => None,
+    };
+    let value = match (op, literal(0), literal(1)) {
+        (NotBool, Some(Literal::Bool(v)), _) => Literal::Bool(!v),
+        // 左辺だけで結果が決まる場合は、右辺がリテラルでなくても畳み込める。
+        (AndBool, Some(Literal::Bool(false)), _) => Literal::Bool(false),
+        (OrBool, Some(Literal::Bool(true)), _) => Literal::Bool(true),
+        (AndBool | OrBool, Some(Literal::Bool(_)), _) => {
+            return args
+                .get(1)
+                .filter(|rhs| rhs.ty() == &ValueTy::Bool)
+                .cloned();
+        }
+        (_, Some(lhs), Some(rhs)) => fold_binary(op, lhs, rhs)?,
+        _ => return None,
+    };
+    let ty = value.ty();
+    Some(Expr::Literal { value, ty })
+}
+
+fn fold_binary(op: PrimOp, lhs: &Literal, rhs: &Literal) -> Option<Literal> {
+    use PrimOp::*;
+
+    if let (Literal::Double(l), Literal::Double(r)) = (lhs, rhs) {
+        let (l, r) = (*l, *r);
+        return Some(match op {
+            AddDouble => Literal::Double(l + r),
+            SubDouble => Literal::Double(l - r),
+            MulDouble => Literal::Double(l * r),
+            DivDouble => Literal::Double(l / r),
+            EqDouble => Literal::Bool(l == r),
+            NeqDouble => Literal::Bool(l != r),
+            LtDouble => Literal::Bool(l < r),
+            LeDouble => Literal::Bool(l <= r),
+            GtDouble => Literal::Bool(l > r),
+            GeDouble => Literal::Bool(l >= r),
+            _ => return None,
+        });
+    }
+    // 文字と真偽値は、生成コードと同じく整数として比較する。
+    let (l, r) = (integral(lhs)?, integral(rhs)?);
+    let arithmetic = matches!(lhs, Literal::Int(_)) && matches!(rhs, Literal::Int(_));
+    Some(match op {
+        EqInt => Literal::Bool(l == r),
+        NeqInt => Literal::Bool(l != r),
+        LtInt => Literal::Bool(l < r),
+        LeInt => Literal::Bool(l <= r),
+        GtInt => Literal::Bool(l > r),
+        GeInt => Literal::Bool(l >= r),
+        AddInt if arithmetic => Literal::Int(l.checked_add(r)?),
+        SubInt if arithmetic => Literal::Int(l.checked_sub(r)?),
+        MulInt if arithmetic => Literal::Int(l.checked_mul(r)?),
+        AndInt if arithmetic => Literal::Int(l & r),
+        OrInt if arithmetic => Literal::Int(l | r),
+        XorInt if arithmetic => Literal::Int(l ^ r),
+        _ => return None,
+    })
+}
+
+fn integral(literal: &Literal) -> Option<i64> {
+    match literal {
+        Literal::Int(v) => Some(*v),
+        Literal::Char(c) => Some(i64::from(u32::from(*c))),
+        Literal::Bool(b) => Some(i64::from(*b)),
+        _ => None,
+    }
+}
@@ -0,0 +1,364 @@
+// パス: src/core_ir/cse.rs
+// 役割: 関数本体で繰り返し現れる同一の純粋な部分式を `let` 束縛へまとめる
+// 意図: `f x + f x` のような重複計算を一度の評価で済ませる
+// 関連ファイル: src/core_ir/inline.rs, src/core_ir/mod.rs, src/lib.rs
+//! Core IR の共通部分式除去
+//!
+//! - 対象は構文的に同一で、純粋と判定できる呼び出し・演算・タプル・リストに限る。
+//! - `println`/`trace` や参照セルの操作、モジュール外の関数、再帰する関数、関数値の適用を含む式は純粋とみなさない。
+//! - 部分式は必ず評価される位置(`if` の条件、`case` の対象など)に現れるものだけをまとめ、
+//!   分岐やラムダの本体はそれぞれ独立した範囲として処理する。
+
+use std::collections::{HashMap, HashSet};
+
+use super::inline::{call_graph, callee_first_order, expr_size, recursive_functions};
+use super::{Binding, Expr, Literal, Module, PrimOp, ValueTy, VarKind};
+use crate::intrinsics;
+
+/// モジュール内の全関数本体に共通部分式除去を適用する。
+pub fn eliminate_common_subexpressions(module: &mut Module) {
+    let graph = call_graph(&module.functions);
+    let recursive = recursive_functions(&graph);
+    let mut pure_functions = HashSet::new();
+    for name in callee_first_order(&graph) {
+        if recursive.contains(&name) {
+            continue;
+        }
+        if is_pure(&module.functions[&name].body, &pure_functions) {
+            pure_functions.insert(name);
+        }
+    }
+    let mut cse = Cse {
+        pure_functions,
+        fresh: 0,
+    };
+    for func in module.functions.values_mut() {
+        cse.region(&mut func.body);
+    }
+}
+
+/// 副作用を持たず、評価結果が引数だけで決まる式か判定する。
+fn is_pure(expr: &Expr, pure_functions: &HashSet<String>) -> bool {
+    match expr {
+        Expr::Literal { .. } | Expr::Var { .. } | Expr::DictionaryPlaceholder { .. } => true,
+        Expr::Lambda { .. } => false,
+        Expr::Apply { func, args, .. } => {
+            is_pure_callee(func, pure_functions)
+                && args.iter().all(|arg| is_pure(arg, pure_functions))
+        }
+        Expr::Let { bindings, body, .. } => {
+            bindings
+                .iter()
+                .all(|binding| is_pure(&binding.value, pure_functions))
+                && is_pure(body, pure_functions)
+        }
+        Expr::If {
+            cond,
+            then_branch,
+            else_branch,
+            ..
+        } => [cond, then_branch, else_branch]
+            .iter()
+            .all(|child| is_pure(child, pure_functions)),
+        Expr::PrimOp { args, .. }
+        | Expr::Tuple { items: args, .. }
+        | Expr::List { items: args, .. } => args.iter().all(|arg| is_pure(arg, pure_functions)),
+        Expr::Match {
+            scrutinee, arms, ..
+        } => {
+            is_pure(scrutinee, pure_functions)
+                && arms.iter().all(|arm| {
+                    arm.guard
+                        .as_ref()
+                        .map_or(true, |guard| is_pure(guard, pure_functions))
+                        && is_pure(&arm.body, pure_functions)
+                })
+        }
+    }
+}
+
+fn is_pure_callee(func: &Expr, pure_functions: &HashSet<String>) -> bool {
+    match func {
+        Expr::Var {
+            name,
+            kind: VarKind::Intrinsic,
+            ..
+        } => intrinsics::lookup(name).is_some_and(|intr| !intr.kind.has_effects()),
+        Expr::Var {
+            kind: VarKind::Primitive,
+            ..
+        } => true,
+        Expr::Var {
+            name,
+            kind: VarKind::Function,
+            ..
+        } => pure_functions.contains(name),
+        _ => false,
+    }
+}
+
+struct Cse {
+    pure_functions: HashSet<String>,
+    fresh: usize,
+}
+
+impl Cse {
+    /// `expr` を根とする範囲で重複をまとめ、必要なら `let` で包む。
+    fn region(&mut self, expr: &mut Expr) {
+        self.sub_regions(expr);
+        let mut bindings = Vec::new();
+        while let Some(target) = self.pick(expr) {
+            let name = format!("$cse{}", self.fresh);
+            self.fresh += 1;
+            let ty = target.ty().clone();
+            let var = Expr::Var {
+                name: name.clone(),
+                ty: ty.clone(),
+                kind: VarKind::Local,
+            };
+            replace(expr, &target, &var, &mut Vec::new());
+            bindings.push(Binding {
+                name,
+                value: target,
+                ty,
+            });
+        }
+        if bindings.is_empty() {
+            return;
+        }
+        let ty = expr.ty().clone();
+        let body = std::mem::replace(
+            expr,
+            Expr::Literal {
+                value: Literal::Unit,
+                ty: ValueTy::Unit,
+            },
+        );
+        *expr = Expr::Let {
+            bindings,
+            body: Box::new(body),
+            ty,
+        };
+    }
+
+    /// 条件付きでしか評価されない子式を、それぞれ独立した範囲として処理する。
+    fn sub_regions(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Literal { .. } | Expr::Var { .. } | Expr::DictionaryPlaceholder { .. } => {}
+            Expr::Let { bindings, body, .. } => {
+                for binding in bindings {
+                    self.sub_regions(&mut binding.value);
+                }
+                self.region(body);
+            }
+            Expr::Lambda { body, .. } => self.region(body),
+            Expr::If {
+                cond,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.sub_regions(cond);
+                self.region(then_branch);
+                self.region(else_branch);
+            }
+            Expr::Match {
+                scrutinee, arms, ..
+            } => {
+                self.sub_regions(scrutinee);
+                for arm in arms {
+                    if let Some(guard) = &mut arm.guard {
+                        self.region(guard);
+                    }
+                    self.region(&mut arm.body);
+                }
+            }
+            Expr::PrimOp {
+                op: PrimOp::AndBool | PrimOp::OrBool,
+                args,
+                ..
+            } => {
+                if let Some((lhs, rest)) = args.split_first_mut() {
+                    self.sub_regions(lhs);
+                    rest.iter_mut().for_each(|arg| self.region(arg));
+                }
+            }
+            Expr::Apply { func, args, .. } => {
+                self.sub_regions(func);
+                args.iter_mut().for_each(|arg| self.sub_regions(arg));
+            }
+            Expr::PrimOp { args, .. }
+            | Expr::Tuple { items: args, .. }
+            | Expr::List { items: args, .. } => {
+                args.iter_mut().for_each(|arg| self.sub_regions(arg));
+            }
+        }
+    }
+
+    /// 2 回以上現れる候補のうち最小(同じ大きさなら先に現れた方)を選ぶ。
+    ///
+    /// 小さい順にまとめるため、後の束縛は先の束縛を参照できる。
+    fn pick(&self, expr: &Expr) -> Option<Expr> {
+        let mut occurrences = Vec::new();
+        self.collect(expr, &mut Vec::new(), &mut occurrences);
+        let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+        for (index, candidate) in occurrences.iter().enumerate() {
+            counts
+                .entry(format!("{candidate:?}"))
+                .or_insert((index, 0))
+                .1 += 1;
+        }
+        counts
+            .into_values()
+            .filter(|&(_, count)| count >= 2)
+            .map(|(index, _)| (expr_size(occurrences[index]), index))
+            .min()
+            .map(|(_, index)| occurrences[index].clone())
+    }
+
+    /// 必ず評価される位置にある候補を出現順に集める。
+    fn collect<'a>(&self, expr: &'a Expr, bound: &mut Vec<String>, out: &mut Vec<&'a Expr>) {
+        if self.is_candidate(expr) && !mentions_any(expr, bound) {
+            out.push(expr);
+        }
+        match expr {
+            Expr::Literal { .. }
+            | Expr::Var { .. }
+            | Expr::DictionaryPlaceholder { .. }
+            | Expr::Lambda { .. } => {}
+            Expr::Let { bindings, .. } => {
+                let depth = bound.len();
+                for binding in bindings {
+                    self.collect(&binding.value, bound, out);
+                    bound.push(binding.name.clone());
+                }
+                bound.truncate(depth);
+            }
+            Expr::If { cond, .. } => self.collect(cond, bound, out),
+            Expr::Match { scrutinee, .. } => self.collect(scrutinee, bound, out),
+            Expr::PrimOp {
+                op: PrimOp::AndBool | PrimOp::OrBool,
+                args,
+                ..
+            } => {
+                if let Some(lhs) = args.first() {
+                    self.collect(lhs, bound, out);
+                }
+            }
+            Expr::Apply { func, args, .. } => {
+                self.collect(func, bound, out);
+                args.iter().for_each(|arg| self.collect(arg, bound, out));
+            }
+            Expr::PrimOp { args, .. }
+            | Expr::Tuple { items: args, .. }
+            | Expr::List { items: args, .. } => {
+                args.iter().for_each(|arg| self.collect(arg, bound, out));
+            }
+        }
+    }
+
+    fn is_candidate(&self, expr: &Expr) -> bool {
+        let shape = match expr {
+            Expr::Apply { .. } | Expr::PrimOp { .. } => true,
+            Expr::Tuple { items, .. } | Expr::List { items, .. } => !items.is_empty(),
+            _ => false,
+        };
+        shape
+            && !matches!(
+                expr.ty(),
+                ValueTy::Unknown | ValueTy::Function { .. } | ValueTy::Dictionary { .. }
+            )
+            && is_pure(expr, &self.pure_functions)
+    }
+}
+
+/// `collect` と同じ位置を辿り、`target` と一致する式を `var` へ置き換える。
+fn replace(expr: &mut Expr, target: &Expr, var: &Expr, bound: &mut Vec<String>) {
+    if expr == target && !mentions_any(expr, bound) {
+        *expr = var.clone();
+        return;
+    }
+    match expr {
+        Expr::Literal { .. }
+        | Expr::Var { .. }
+        | Expr::DictionaryPlaceholder { .. }
+        | Expr::Lambda { .. } => {}
+        Expr::Let { bindings, .. } => {
+            let depth = bound.len();
+            for binding in bindings.iter_mut() {
+                replace(&mut binding.value, target, var, bound);
+                bound.push(binding.name.clone());
+            }
+            bound.truncate(depth);
+        }
+        Expr::If { cond, .. } => replace(cond, target, var, bound),
+        Expr::Match { scrutinee, .. } => replace(scrutinee, target, var, bound),
+        Expr::PrimOp {
+            op: PrimOp::AndBool | PrimOp::OrBool,
+            args,
+            ..
+        } => {
+            if let Some(lhs) = args.first_mut() {
+                replace(lhs, target, var, bound);
+            }
+        }
+        Expr::Apply { func, args, .. } => {
+            replace(func, target, var, bound);
+            args.iter_mut()
+                .for_each(|arg| replace(arg, target, var, bound));
+        }
+        Expr::PrimOp { args, .. }
+        | Expr::Tuple { items: args, .. }
+        | Expr::List { items: args, .. } => {
+            args.iter_mut()
+                .for_each(|arg| replace(arg, target, var, bound));
+        }
+    }
+}
+
+/// 式がローカル変数名 `names` のいずれかを参照し得るか。シャドーイングは考慮せず保守的に判定する。
+fn mentions_any(expr: &Expr, names: &[String]) -> bool {
+    if names.is_empty() {
+        return false;
+    }
+    match expr {
+        Expr::Var { name, kind, .. } => {
+            matches!(kind, VarKind::Local | VarKind::Param) && names.contains(name)
+        }
+        Expr::Literal { .. } | Expr::DictionaryPlaceholder { .. } => false,
+        Expr::Let { bindings, body, .. } => {
+            bindings
+                .iter()
+                .any(|binding| mentions_any(&binding.value, names))
+                || mentions_any(body, names)
+        }
+        Expr::Lambda { body, .. } => mentions_any(body, names),
+        Expr::Apply { func, args, .. } => {
+            mentions_any(func, names) || args.iter().any(|arg| mentions_any(arg, names))
+        }
+        Expr::If {
+            cond,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            mentions_any(cond, names)
+                || mentions_any(then_branch, names)
+                || mentions_any(else_branch, names)
+        }
+        Expr::PrimOp { args, .. }
+        | Expr::Tuple { items: args, .. }
+        | Expr::List { items: args, .. } => args.iter().any(|arg| mentions_any(arg, names)),
+        Expr::Match {
+            scrutinee, arms, ..
+        } => {
+            mentions_any(scrutinee, names)
+                || arms.iter().any(|arm| {
+                    arm.guard
+                        .as_ref()
+                        .is_some_and(|guard| mentions_any(guard, names))
+                        || mentions_any(&arm.body, names)
+                })
+        }
+    }
+}
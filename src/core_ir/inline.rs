@@ -0,0 +1,355 @@
+// パス: src/core_ir/inline.rs
+// 役割: 小さなトップレベル関数の本体を呼び出し箇所へ展開する
+// 意図: ラッパー関数やアクセサへの呼び出しコスト(引数の受け渡しと呼び出し深さの計数)を省く
+// 関連ファイル: src/core_ir/mod.rs, src/lib.rs, src/codegen/cranelift.rs
+//! Core IR のインライン展開
+//!
+//! - 対象は非再帰(相互再帰を含む)で、本体のノード数が [`INLINE_SIZE_LIMIT`] 以下の関数に限る。
+//! - 辞書パラメータや関数型パラメータを持つ関数は、呼び出し側の環境に依存するため展開しない。
+//! - 実引数は `let` で一度だけ、左から順に評価してから本体へ渡す。評価順序と重複評価の有無は展開前と変わらない。
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::{Binding, Expr, Function, Literal, Module, Parameter, ParameterKind, ValueTy, VarKind};
+
+/// 展開対象とする関数本体の最大ノード数。
+pub const INLINE_SIZE_LIMIT: usize = 16;
+
+/// モジュール内の小さな関数呼び出しを展開する。
+///
+/// 呼び出される側から順に処理するため、展開済みの本体がさらに別の呼び出し箇所へ展開されることがある。
+/// 関数定義そのものはモジュールに残す。
+pub fn inline_small_functions(module: &mut Module) {
+    let graph = call_graph(&module.functions);
+    let recursive = recursive_functions(&graph);
+    let mut inliner = Inliner {
+        candidates: HashMap::new(),
+        fresh: 0,
+    };
+    for name in callee_first_order(&graph) {
+        let Some(func) = module.functions.get_mut(&name) else {
+            continue;
+        };
+        inliner.expr(&mut func.body);
+        if !recursive.contains(&name) && is_inlinable(func) {
+            let candidate = Candidate {
+                params: func.params.clone(),
+                result: func.result.clone(),
+                body: func.body.clone(),
+            };
+            inliner.candidates.insert(name, candidate);
+        }
+    }
+}
+
+fn is_inlinable(func: &Function) -> bool {
+    func.params.iter().all(|param| {
+        matches!(param.kind, ParameterKind::Value) && !matches!(param.ty, ValueTy::Function { .. })
+    }) && expr_size(&func.body) <= INLINE_SIZE_LIMIT
+}
+
+/// 展開候補の関数。戻り値型は呼び出し時と同じ型へ結果を揃えるために保持する。
+struct Candidate {
+    params: Vec<Parameter>,
+    result: ValueTy,
+    body: Expr,
+}
+
+struct Inliner {
+    candidates: HashMap<String, Candidate>,
+    fresh: usize,
+}
+
+impl Inliner {
+    fn expr(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Literal { .. } | Expr::Var { .. } | Expr::DictionaryPlaceholder { .. } => {}
+            Expr::Let { bindings, body, .. } => {
+                for binding in bindings {
+                    self.expr(&mut binding.value);
+                }
+                self.expr(body);
+            }
+            Expr::Lambda { body, .. } => self.expr(body),
+            Expr::Apply { func, args, .. } => {
+                self.expr(func);
+                args.iter_mut().for_each(|arg| self.expr(arg));
+                if let Some(inlined) = self.expand(func, args) {
+                    *expr = inlined;
+                }
+            }
+            Expr::If {
+                cond,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.expr(cond);
+                self.expr(then_branch);
+                self.expr(else_branch);
+            }
+            Expr::PrimOp { args, .. }
+            | Expr::Tuple { items: args, .. }
+            | Expr::List { items: args, .. } => {
+                args.iter_mut().for_each(|arg| self.expr(arg));
+            }
+            Expr::Match {
+                scrutinee, arms, ..
+            } => {
+                self.expr(scrutinee);
+                for arm in arms {
+                    if let Some(guard) = &mut arm.guard {
+                        self.expr(guard);
+                    }
+                    self.expr(&mut arm.body);
+                }
+            }
+        }
+    }
+
+    /// 展開できる呼び出しなら、実引数を新しい名前へ束縛する `let` に置き換えた式を返す。
+    fn expand(&mut self, func: &Expr, args: &mut [Expr]) -> Option<Expr> {
+        let Expr::Var {
+            name,
+            kind: VarKind::Function,
+            ..
+        } = func
+        else {
+            return None;
+        };
+        let candidate = self.candidates.get(name)?;
+        if candidate.params.len() != args.len() {
+            return None;
+        }
+        let mut body = candidate.body.clone();
+        let mut bindings = Vec::with_capacity(args.len());
+        for (param, arg) in candidate.params.iter().zip(args.iter_mut()) {
+            let fresh = format!("{}$inline{}", param.name, self.fresh);
+            self.fresh += 1;
+            rename_param(&mut body, &param.name, &fresh);
+            bindings.push(Binding {
+                name: fresh,
+                value: std::mem::replace(
+                    arg,
+                    Expr::Literal {
+                        value: Literal::Unit,
+                        ty: ValueTy::Unit,
+                    },
+                ),
+                ty: param.ty.clone(),
+            });
+        }
+        Some(Expr::Let {
+            bindings,
+            body: Box::new(body),
+            ty: candidate.result.clone(),
+        })
+    }
+}
+
+/// `body` 内で仮引数 `from` を参照する変数を、ローカル変数 `to` の参照へ書き換える。
+///
+/// `let`・ラムダ・パターンで同名の束縛が現れたら、その有効範囲では書き換えない。
+fn rename_param(expr: &mut Expr, from: &str, to: &str) {
+    match expr {
+        Expr::Var { name, kind, .. } => {
+            if name == from && matches!(kind, VarKind::Param | VarKind::Local) {
+                *name = to.to_string();
+                *kind = VarKind::Local;
+            }
+        }
+        Expr::Literal { .. } | Expr::DictionaryPlaceholder { .. } => {}
+        Expr::Let { bindings, body, .. } => {
+            for binding in bindings.iter_mut() {
+                rename_param(&mut binding.value, from, to);
+                if binding.name == from {
+                    return;
+                }
+            }
+            rename_param(body, from, to);
+        }
+        Expr::Lambda { params, body, .. } => {
+            if params.iter().all(|param| param.name != from) {
+                rename_param(body, from, to);
+            }
+        }
+        Expr::Apply { func, args, .. } => {
+            rename_param(func, from, to);
+            args.iter_mut().for_each(|arg| rename_param(arg, from, to));
+        }
+        Expr::If {
+            cond,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            rename_param(cond, from, to);
+            rename_param(then_branch, from, to);
+            rename_param(else_branch, from, to);
+        }
+        Expr::PrimOp { args, .. }
+        | Expr::Tuple { items: args, .. }
+        | Expr::List { items: args, .. } => {
+            args.iter_mut().for_each(|arg| rename_param(arg, from, to));
+        }
+        Expr::Match {
+            scrutinee, arms, ..
+        } => {
+            rename_param(scrutinee, from, to);
+            for arm in arms {
+                if arm.bindings.iter().any(|binding| binding.name == from) {
+                    continue;
+                }
+                if let Some(guard) = &mut arm.guard {
+                    rename_param(guard, from, to);
+                }
+                rename_param(&mut arm.body, from, to);
+            }
+        }
+    }
+}
+
+/// 式のノード数。展開の可否判定に使う。
+pub(super) fn expr_size(expr: &Expr) -> usize {
+    1 + match expr {
+        Expr::Literal { .. } | Expr::Var { .. } | Expr::DictionaryPlaceholder { .. } => 0,
+        Expr::Let { bindings, body, .. } => {
+            bindings
+                .iter()
+                .map(|binding| expr_size(&binding.value))
+                .sum::<usize>()
+                + expr_size(body)
+        }
+        Expr::Lambda { body, .. } => expr_size(body),
+        Expr::Apply { func, args, .. } => {
+            expr_size(func) + args.iter().map(expr_size).sum::<usize>()
+        }
+        Expr::If {
+            cond,
+            then_branch,
+            else_branch,
+            ..
+        } => expr_size(cond) + expr_size(then_branch) + expr_size(else_branch),
+        Expr::PrimOp { args, .. }
+        | Expr::Tuple { items: args, .. }
+        | Expr::List { items: args, .. } => args.iter().map(expr_size).sum(),
+        Expr::Match {
+            scrutinee, arms, ..
+        } => {
+            expr_size(scrutinee)
+                + arms
+                    .iter()
+                    .map(|arm| arm.guard.as_ref().map_or(0, expr_size) + expr_size(&arm.body))
+                    .sum::<usize>()
+        }
+    }
+}
+
+/// 各関数から直接参照されるトップレベル関数の集合。
+pub(super) fn call_graph(functions: &BTreeMap<String, Function>) -> BTreeMap<String, Vec<String>> {
+    functions
+        .iter()
+        .map(|(name, func)| {
+            let mut callees = Vec::new();
+            collect_function_refs(&func.body, &mut callees);
+            callees.retain(|callee| functions.contains_key(callee));
+            callees.sort();
+            callees.dedup();
+            (name.clone(), callees)
+        })
+        .collect()
+}
+
+fn collect_function_refs(expr: &Expr, out: &mut Vec<String>) {
+    match expr {
+        Expr::Var {
+            name,
+            kind: VarKind::Function,
+            ..
+        } => out.push(name.clone()),
+        Expr::Var { .. } | Expr::Literal { .. } | Expr::DictionaryPlaceholder { .. } => {}
+        Expr::Let { bindings, body, .. } => {
+            for binding in bindings {
+                collect_function_refs(&binding.value, out);
+            }
+            collect_function_refs(body, out);
+        }
+        Expr::Lambda { body, .. } => collect_function_refs(body, out),
+        Expr::Apply { func, args, .. } => {
+            collect_function_refs(func, out);
+            args.iter().for_each(|arg| collect_function_refs(arg, out));
+        }
+        Expr::If {
+            cond,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            collect_function_refs(cond, out);
+            collect_function_refs(then_branch, out);
+            collect_function_refs(else_branch, out);
+        }
+        Expr::PrimOp { args, .. }
+        | Expr::Tuple { items: args, .. }
+        | Expr::List { items: args, .. } => {
+            args.iter().for_each(|arg| collect_function_refs(arg, out));
+        }
+        Expr::Match {
+            scrutinee, arms, ..
+        } => {
+            collect_function_refs(scrutinee, out);
+            for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    collect_function_refs(guard, out);
+                }
+                collect_function_refs(&arm.body, out);
+            }
+        }
+    }
+}
+
+/// 自分自身へ呼び出しが戻ってくる(直接・相互再帰の)関数の集合。
+pub(super) fn recursive_functions(graph: &BTreeMap<String, Vec<String>>) -> HashSet<String> {
+    graph
+        .keys()
+        .filter(|&start| {
+            let mut seen = HashSet::new();
+            let mut stack: Vec<&String> = graph[start].iter().collect();
+            while let Some(name) = stack.pop() {
+                if name == start {
+                    return true;
+                }
+                if seen.insert(name) {
+                    stack.extend(graph.get(name).into_iter().flatten());
+                }
+            }
+            false
+        })
+        .cloned()
+        .collect()
+}
+
+/// 呼び出される側が先に来る順序(帰りがけ順)で関数名を並べる。
+pub(super) fn callee_first_order(graph: &BTreeMap<String, Vec<String>>) -> Vec<String> {
+    fn visit<'a>(
+        name: &'a String,
+        graph: &'a BTreeMap<String, Vec<String>>,
+        seen: &mut HashSet<&'a String>,
+        order: &mut Vec<String>,
+    ) {
+        if !seen.insert(name) {
+            return;
+        }
+        for callee in graph.get(name).into_iter().flatten() {
+            visit(callee, graph, seen, order);
+        }
+        order.push(name.clone());
+    }
+
+    let mut seen = HashSet::new();
+    let mut order = Vec::with_capacity(graph.len());
+    for name in graph.keys() {
+        visit(name, graph, &mut seen, &mut order);
+    }
+    order
+}
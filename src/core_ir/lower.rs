@@ -5,48 +5,53 @@
 
 use std::{
     borrow::Cow,
-    collections::{BTreeMap, BTreeSet, HashMap},
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
 };
 
 use crate::ast as A;
 use crate::core_ir::{
-    dict_specs, Binding, ConstructorLayout, CoreIrError, DataTypeLayout, DictionaryBuilder,
-    DictionaryInit, DictionaryMethod, Expr, Function, Literal, MatchArm, MatchBinding, Module,
-    Parameter, ParameterKind, PrimOp, SourceRef, ValueTy, VarKind,
+    dict_specs, newtype, type_repr, Binding, ConstructorLayout, CoreIrError, CoreIrErrors,
+    DataTypeLayout, DictionaryBuilder, DictionaryInit, DictionaryMethod, Expr, Function, Literal,
+    MatchArm, MatchBinding, Module, Parameter, ParameterKind, PrimOp, SourceRef, ValueTy, VarKind,
 };
-use crate::evaluator;
 use crate::infer;
 use crate::intrinsics::{self, IntrinsicKind};
+use crate::primitives::{self, builtin_maybe_decl, MAYBE_TYPE};
 use crate::repl;
 use crate::typesys::{
     Constraint as TyConstraint, QualType, Scheme, TApp, TCon, TFun, TTuple, TVar, Type, TypeEnv,
 };
 
 /// AST プログラムを Core IR へ変換するエントリポイント。
-pub fn lower_program(prog: &A::Program) -> Result<Module, CoreIrError> {
+///
+/// 型シグネチャの登録と各定義の lowering は定義ごとに独立して行い、失敗をまとめて返す。
+/// シグネチャ登録で失敗した場合は、その影響で連鎖するエラーを避けるため lowering へ進まない。
+pub fn lower_program(prog: &A::Program) -> Result<Module, CoreIrErrors> {
     if !prog.class_decls.is_empty() {
         return Err(CoreIrError::new(
             "COREIR001",
             "class 宣言はネイティブコンパイラではまだサポートされていません",
-        ));
+        )
+        .into());
     }
     if !prog.instance_decls.is_empty() {
         return Err(CoreIrError::new(
             "COREIR002",
             "instance 宣言はネイティブコンパイラではまだサポートされていません",
-        ));
+        )
+        .into());
     }
     // 既存パイプラインを利用して型検証を実施し、型環境を取得する。
+    // トップレベルの定義はここでは評価しない（`main` をコンパイル時に実行しない）。
     let mut type_env = infer::initial_env();
     let mut class_env = infer::initial_class_env();
-    let mut value_env = evaluator::initial_env();
-    let load_result =
-        repl::load_program_into_env(prog, &mut type_env, &mut class_env, &mut value_env);
-    value_env.teardown();
-    load_result.map_err(|msg| CoreIrError::new(classify_loader_error(&msg), msg))?;
+    repl::check_program_into_env(prog, &mut type_env, &mut class_env)
+        .map_err(|msg| CoreIrError::new(classify_loader_error(&msg), msg))?;
 
     let mut ctx = LoweringContext::new(type_env.clone_env());
     ctx.register_data_layouts(prog);
+    ctx.qualified_aliases = prog.qualified_aliases().into_iter().collect();
     ctx.register_signatures(prog)?;
     ctx.lower_program(prog)
 }
@@ -58,6 +63,12 @@ struct LoweringContext {
     constructor_map: HashMap<String, ConstructorLayout>,
     dictionaries: Vec<DictionaryInit>,
     dictionary_keys: BTreeSet<(String, String)>,
+    /// 関数本体の演算子が必要とした辞書（リスト同士の `==` など）。シグネチャ由来の辞書に後から合流させる。
+    operand_dictionaries: RefCell<Vec<DictionaryInit>>,
+    /// `module` 宣言由来の修飾名（`Foo.bar`）から定義名（`bar`）への対応。
+    qualified_aliases: HashMap<String, String>,
+    /// `min` / `max` の展開で導入する一時変数の通し番号。
+    fresh: Cell<usize>,
 }
 
 impl LoweringContext {
@@ -69,15 +80,22 @@ impl LoweringContext {
             constructor_map: HashMap::new(),
             dictionaries: Vec::new(),
             dictionary_keys: BTreeSet::new(),
+            operand_dictionaries: RefCell::new(Vec::new()),
+            qualified_aliases: HashMap::new(),
+            fresh: Cell::new(0),
         }
     }
 
     fn register_data_layouts(&mut self, prog: &A::Program) {
-        for data_decl in &prog.data_decls {
+        // 組み込みの `Maybe` は、プログラムが同名の型を宣言していない場合に限って配置する。
+        let builtin_maybe =
+            (!prog.data_decls.iter().any(|decl| decl.name == MAYBE_TYPE)).then(builtin_maybe_decl);
+        for data_decl in prog.data_decls.iter().chain(builtin_maybe.as_ref()) {
             let mut layout = DataTypeLayout {
                 name: data_decl.name.clone(),
                 type_params: data_decl.params.clone(),
                 constructors: Vec::new(),
+                newtype: data_decl.newtype,
             };
             for (idx, ctor) in data_decl.constructors.iter().enumerate() {
                 let ctor_layout = ConstructorLayout {
@@ -94,40 +112,50 @@ impl LoweringContext {
         }
     }
 
-    fn register_signatures(&mut self, prog: &A::Program) -> Result<(), CoreIrError> {
-        for decl in &prog.decls {
-            let scheme = self.type_env.lookup(&decl.name).ok_or_else(|| {
-                CoreIrError::new("COREIR020", format!("{} の型が見つかりません", decl.name))
-            })?;
-            let scheme_for_sig = if let Some(sig_ast) = &decl.signature {
-                scheme_from_sigma(sig_ast)
-            } else {
-                scheme.clone()
-            };
-            let source_span = span_to_source_ref(expr_span(&decl.expr));
-            let dict_reprs =
-                self.record_dictionaries_for(&decl.name, &scheme_for_sig, source_span)?;
-            let mut sig = FunctionSig::from_scheme(&decl.name, &scheme_for_sig)?;
-            sig.patch_dictionary_repr(&dict_reprs);
-
-            let value_param_count = sig
-                .param_specs
-                .iter()
-                .filter(|spec| matches!(spec.kind, ParameterKind::Value))
-                .count();
-            if value_param_count != decl.params.len() {
-                return Err(CoreIrError::new(
-                    "COREIR021",
-                    format!(
-                        "{} の型注釈の引数数 ({}) と定義上の引数数 ({}) が一致しません",
-                        decl.name,
-                        value_param_count,
-                        decl.params.len()
-                    ),
-                ));
-            }
-            self.function_sigs.insert(decl.name.clone(), sig);
+    fn register_signatures(&mut self, prog: &A::Program) -> Result<(), CoreIrErrors> {
+        let errors: Vec<CoreIrError> = prog
+            .decls
+            .iter()
+            .filter_map(|decl| self.register_signature(decl).err())
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(CoreIrErrors(errors))
+        }
+    }
+
+    fn register_signature(&mut self, decl: &A::TopLevel) -> Result<(), CoreIrError> {
+        let scheme = self.type_env.lookup(&decl.name).ok_or_else(|| {
+            CoreIrError::new("COREIR020", format!("{} の型が見つかりません", decl.name))
+        })?;
+        let scheme_for_sig = if let Some(sig_ast) = &decl.signature {
+            scheme_from_sigma(sig_ast)
+        } else {
+            scheme.clone()
+        };
+        let source_span = span_to_source_ref(expr_span(&decl.expr));
+        let dict_reprs = self.record_dictionaries_for(&decl.name, &scheme_for_sig, source_span)?;
+        let mut sig = FunctionSig::from_scheme(&decl.name, &scheme_for_sig)?;
+        sig.patch_dictionary_repr(&dict_reprs);
+
+        let value_param_count = sig
+            .param_specs
+            .iter()
+            .filter(|spec| matches!(spec.kind, ParameterKind::Value))
+            .count();
+        if value_param_count != decl.params.len() {
+            return Err(CoreIrError::new(
+                "COREIR021",
+                format!(
+                    "{} の型注釈の引数数 ({}) と定義上の引数数 ({}) が一致しません",
+                    decl.name,
+                    value_param_count,
+                    decl.params.len()
+                ),
+            ));
         }
+        self.function_sigs.insert(decl.name.clone(), sig);
         Ok(())
     }
 
@@ -141,7 +169,7 @@ impl LoweringContext {
         for constraint in &scheme.qual.constraints {
             let mut init = DictionaryInit {
                 classname: constraint.classname.clone(),
-                type_repr: type_to_string(&constraint.r#type),
+                type_repr: type_repr::from_type(&constraint.r#type),
                 value_ty: convert_type_with_overrides(&constraint.r#type)?,
                 methods: Vec::new(),
                 scheme_repr: scheme_to_string(scheme),
@@ -153,7 +181,7 @@ impl LoweringContext {
             if let Some(resolution) =
                 resolve_builtin_dictionary(&constraint.classname, &constraint.r#type)
             {
-                init.type_repr = resolution.type_repr.to_string();
+                init.type_repr = resolution.type_repr;
                 init.builder = DictionaryBuilder::Resolved(resolution.builder.to_string());
                 init.value_ty = resolution.value_ty.clone();
                 init.methods = resolution
@@ -178,17 +206,32 @@ impl LoweringContext {
         Ok(reprs)
     }
 
-    fn lower_program(self, prog: &A::Program) -> Result<Module, CoreIrError> {
+    fn lower_program(mut self, prog: &A::Program) -> Result<Module, CoreIrErrors> {
         let mut module = Module::new();
+        let mut errors = Vec::new();
         for decl in &prog.decls {
-            let func = self.lower_top_level(decl)?;
-            module.insert_function(func);
+            match self.lower_top_level(decl) {
+                Ok(func) => {
+                    module.insert_function(func);
+                }
+                Err(err) => errors.push(err),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(CoreIrErrors(errors));
         }
         if module.entry.is_none() && module.functions.contains_key("main") {
             module.set_entry("main");
         }
+        for init in self.operand_dictionaries.take() {
+            let key = (init.classname.clone(), init.type_repr.clone());
+            if self.dictionary_keys.insert(key) {
+                self.dictionaries.push(init);
+            }
+        }
         module.data_layouts = self.data_layouts;
         module.dictionaries = self.dictionaries;
+        newtype::erase_newtype_types(&mut module);
         Ok(module)
     }
 
@@ -302,13 +345,53 @@ impl LoweringContext {
             } => self.lower_if(cond, then_branch, else_branch, env),
             App { func, arg, .. } => self.lower_app(func, arg, env),
             BinOp {
-                op, left, right, ..
-            } => self.lower_binop(op, left, right, env),
+                op,
+                left,
+                right,
+                span,
+            } => {
+                // 利用者が定義した演算子と `(!)` のような intrinsic の演算子は、
+                // 通常の 2 引数関数呼び出しとして下ろす。
+                if env.lookup(op).is_some()
+                    || self.function_sigs.contains_key(op.as_str())
+                    || intrinsics::lookup(op).is_some()
+                    || primitives::is_evaluator_only(op)
+                {
+                    let partial = A::Expr::App {
+                        func: Box::new(A::Expr::Var {
+                            name: op.clone(),
+                            span: *span,
+                        }),
+                        arg: left.clone(),
+                        span: *span,
+                    };
+                    return self.lower_app(&partial, right, env);
+                }
+                self.lower_binop(op, left, right, *span, env)
+            }
             Lambda { .. } => Err(CoreIrError::new(
                 "COREIR050",
                 "ラムダ式はトップレベル以外ではまだサポートされていません",
             )),
-            Annot { expr, .. } => self.lower_expr(expr, env),
+            Annot {
+                expr, type_expr, ..
+            } => {
+                let lowered = self.lower_expr(expr, env)?;
+                // `[] :: [Int]` のように注釈で要素型が分かる空リストは型を確定させる。
+                match (lowered, type_expr_to_value_ty(type_expr, &HashMap::new())) {
+                    (
+                        Expr::Literal {
+                            value: Literal::EmptyList,
+                            ..
+                        },
+                        ty @ ValueTy::List(_),
+                    ) => Ok(Expr::Literal {
+                        value: Literal::EmptyList,
+                        ty,
+                    }),
+                    (lowered, _) => Ok(lowered),
+                }
+            }
             Case {
                 scrutinee, arms, ..
             } => self.lower_case(scrutinee, arms, env),
@@ -317,9 +400,9 @@ impl LoweringContext {
 
     fn lower_list(&self, items: &[A::Expr], env: &mut Env) -> Result<Expr, CoreIrError> {
         if items.is_empty() {
-            return Ok(Expr::List {
-                items: vec![],
-                ty: ValueTy::List(Box::new(ValueTy::Unknown)),
+            return Ok(Expr::Literal {
+                value: Literal::EmptyList,
+                ty: Literal::EmptyList.ty(),
             });
         }
         let mut lowered_items = Vec::with_capacity(items.len());
@@ -363,6 +446,15 @@ impl LoweringContext {
                 kind: VarKind::Local,
             });
         }
+        // 修飾名は局所変数に隠されないよう、参照先のトップレベル定義を直接引く。
+        let name = self
+            .qualified_aliases
+            .get(name)
+            .map_or(name, String::as_str);
+        self.lower_global_var(name)
+    }
+
+    fn lower_global_var(&self, name: &str) -> Result<Expr, CoreIrError> {
         if let Some(sig) = self.function_sigs.get(name) {
             let params = sig.param_specs.iter().map(|spec| spec.ty.clone()).collect();
             let ty = ValueTy::Function {
@@ -375,6 +467,40 @@ impl LoweringContext {
                 kind: VarKind::Function,
             });
         }
+        // 生成コードには要素を必要になるまで作らないリストがないため、無限の範囲は専用のコードで拒否する。
+        if name == "enumFrom" {
+            return Err(CoreIrError::new(
+                "COREIR170",
+                "上限のない範囲 [x..] (enumFrom) はネイティブバックエンドでは未対応です。評価器で実行するか、要素数の決まったリストを使ってください",
+            ));
+        }
+        if primitives::is_evaluator_only(name) {
+            return Err(CoreIrError::new(
+                "COREIR165",
+                format!("{name} は評価器でのみ利用でき、ネイティブバックエンドでは未対応です"),
+            ));
+        }
+        if ORD_SELECT_PRIMITIVES.contains(&name) {
+            return Err(CoreIrError::new(
+                "COREIR166",
+                format!("{name} はネイティブバックエンドでは 2 引数の呼び出しのみ対応しています"),
+            ));
+        }
+        if ORD_RANGE_PRIMITIVES.contains(&name) {
+            return Err(CoreIrError::new(
+                "COREIR166",
+                format!("{name} はネイティブバックエンドでは 3 引数の呼び出しのみ対応しています"),
+            ));
+        }
+        if is_fold_left(name) {
+            return Err(CoreIrError::new(
+                "COREIR167",
+                format!("{name} はネイティブバックエンドでは 3 引数の呼び出しのみ対応しています"),
+            ));
+        }
+        if name == "format" {
+            return Err(format_arity_error());
+        }
         if let Some(intr) = intrinsics::lookup(name) {
             let ty = intrinsic_function_ty(intr.kind);
             return Ok(Expr::Var {
@@ -383,19 +509,8 @@ impl LoweringContext {
                 kind: VarKind::Intrinsic,
             });
         }
-        if let Some(scheme) = self.type_env.lookup(name) {
-            let sig = FunctionSig::from_scheme(name, scheme)?;
-            let params = sig.param_specs.iter().map(|spec| spec.ty.clone()).collect();
-            let ty = ValueTy::Function {
-                params,
-                result: Box::new(sig.result.clone()),
-            };
-            return Ok(Expr::Var {
-                name: name.into(),
-                ty,
-                kind: VarKind::Function,
-            });
-        }
+        // データ構築子は型環境にも構築子関数として登録されているため、型環境より先に解決する。
+        // 逆順だと `Full 4` が存在しないトップレベル関数 `Full` の呼び出しになる。
         if let Some(ctor) = self.constructor_map.get(name) {
             let result_ty = if let Some(data_layout) = self.data_layouts.get(&ctor.parent) {
                 ValueTy::Data {
@@ -415,6 +530,19 @@ impl LoweringContext {
                 kind: VarKind::Primitive,
             });
         }
+        if let Some(scheme) = self.type_env.lookup(name) {
+            let sig = FunctionSig::from_scheme(name, scheme)?;
+            let params = sig.param_specs.iter().map(|spec| spec.ty.clone()).collect();
+            let ty = ValueTy::Function {
+                params,
+                result: Box::new(sig.result.clone()),
+            };
+            return Ok(Expr::Var {
+                name: name.into(),
+                ty,
+                kind: VarKind::Function,
+            });
+        }
         Err(CoreIrError::new(
             "COREIR070",
             format!("{} はネイティブコンパイル時に解決できません", name),
@@ -461,10 +589,14 @@ impl LoweringContext {
         env: &mut Env,
     ) -> Result<Expr, CoreIrError> {
         let cond_expr = self.lower_expr(cond, env)?;
-        if !types_compatible(&ValueTy::Bool, cond_expr.ty()) {
+        // 多相な文脈から来た `Unknown` はコード生成時に Bool へ取り出すため受け付ける。
+        if !matches!(cond_expr.ty(), ValueTy::Bool | ValueTy::Unknown) {
             return Err(CoreIrError::new(
                 "COREIR090",
-                "if 条件式は Bool 型である必要があります",
+                format!(
+                    "if 条件式は Bool 型である必要があります: `{cond}` の型は {} です",
+                    cond_expr.ty()
+                ),
             ));
         }
         let then_expr = self.lower_expr(then_branch, env)?;
@@ -485,6 +617,57 @@ impl LoweringContext {
 
     fn lower_app(&self, func: &A::Expr, arg: &A::Expr, env: &mut Env) -> Result<Expr, CoreIrError> {
         let (head, mut arg_nodes) = flatten_app(func, arg);
+        if let (A::Expr::Var { name, .. }, [step, init, list]) = (head, arg_nodes.as_slice()) {
+            if is_fold_left(name)
+                && env.lookup(name).is_none()
+                && !self.function_sigs.contains_key(name.as_str())
+            {
+                return self.lower_fold_left(name, step, init, list, env);
+            }
+            if ORD_RANGE_PRIMITIVES.contains(&name.as_str())
+                && env.lookup(name).is_none()
+                && !self.function_sigs.contains_key(name.as_str())
+            {
+                return self.lower_ord_range(name, step, init, list, expr_span(head), env);
+            }
+        }
+        if let (
+            A::Expr::Var { name, .. },
+            [A::Expr::StringLit {
+                value: template, ..
+            }, rest @ ..],
+        ) = (head, arg_nodes.as_slice())
+        {
+            if name == "format"
+                && env.lookup(name).is_none()
+                && !self.function_sigs.contains_key(name.as_str())
+            {
+                return self.lower_format(template, rest, env);
+            }
+        }
+        if let (A::Expr::Var { name, .. }, [left, right]) = (head, arg_nodes.as_slice()) {
+            // `xor a b` や `div a b` のような名前付き二項プリミティブは演算子と同様に扱う。
+            if self.is_named_binop_primitive(name, env) {
+                return self.lower_binop(name, left, right, expr_span(head), env);
+            }
+            if self.is_ord_select_primitive(name, env) {
+                return self.lower_ord_select(name, left, right, expr_span(head), env);
+            }
+        }
+        if let (A::Expr::Var { name, .. }, [operand]) = (head, arg_nodes.as_slice()) {
+            // 単項マイナス由来の `negate x` は従来どおり `0 - x` として下ろす。
+            if name == "negate"
+                && env.lookup(name).is_none()
+                && !self.function_sigs.contains_key(name.as_str())
+            {
+                let zero = A::Expr::IntLit {
+                    value: 0,
+                    base: A::IntBase::Dec,
+                    span: A::Span::dummy(),
+                };
+                return self.lower_binop("-", &zero, operand, expr_span(head), env);
+            }
+        }
         let callee = self.lower_expr(head, env)?;
         let mut lowered_args = Vec::with_capacity(arg_nodes.len());
         for node in arg_nodes.drain(..) {
@@ -503,6 +686,7 @@ impl LoweringContext {
                         } else {
                             None
                         };
+                    self.ensure_call_instances(name, &lowered_args, expr_span(head))?;
                     if let Some(specs) = param_specs {
                         self.inject_dictionary_args(&specs, lowered_args)?
                     } else {
@@ -516,7 +700,57 @@ impl LoweringContext {
             lowered_args
         };
 
-        let result_ty = infer_apply_type(&callee, &lowered_args)?;
+        let mut result_ty = infer_apply_type(&callee, &lowered_args)?;
+        if let Expr::Var {
+            name,
+            kind: VarKind::Intrinsic,
+            ..
+        } = &callee
+        {
+            // trace は第 2 引数を、findWithDefault は見つからなければ第 1 引数を返すため、
+            // 結果型もその引数に揃える。配列・参照セル・replicate/take/drop/<> の結果は要素型を引数から引き継ぐ。
+            match (
+                intrinsics::lookup(name).map(|intr| intr.kind),
+                lowered_args.as_slice(),
+            ) {
+                (Some(IntrinsicKind::Trace), [_, value])
+                | (Some(IntrinsicKind::MapFindWithDefault), [value, _, _]) => {
+                    result_ty = value.ty().clone();
+                }
+                (Some(IntrinsicKind::Replicate), [_, value]) => {
+                    result_ty = ValueTy::List(Box::new(value.ty().clone()));
+                }
+                (Some(IntrinsicKind::Take | IntrinsicKind::Drop), [_, list]) => {
+                    if let ValueTy::List(_) = list.ty() {
+                        result_ty = list.ty().clone();
+                    }
+                }
+                (Some(IntrinsicKind::Append), [lhs, rhs]) => {
+                    result_ty = append_result_ty(lhs.ty(), rhs.ty());
+                }
+                (Some(IntrinsicKind::ArrayFromList), [list]) => {
+                    if let ValueTy::List(item) = list.ty() {
+                        result_ty = ValueTy::Array(item.clone());
+                    }
+                }
+                (Some(IntrinsicKind::ArrayIndex), [array, _]) => {
+                    if let ValueTy::Array(item) = array.ty() {
+                        result_ty = item.as_ref().clone();
+                    }
+                }
+                (Some(IntrinsicKind::NewRef), [value]) => {
+                    result_ty = ref_value_ty(value.ty().clone());
+                }
+                (Some(IntrinsicKind::ReadRef), [cell]) => {
+                    if let ValueTy::Data { constructor, args } = cell.ty() {
+                        if let ("Ref", [item]) = (constructor.as_str(), args.as_slice()) {
+                            result_ty = item.clone();
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
         Ok(Expr::Apply {
             func: Box::new(callee),
             args: lowered_args,
@@ -524,16 +758,318 @@ impl LoweringContext {
         })
     }
 
+    /// `format "..." x y` を、書式リテラルと指定子ごとの引数を受け取る intrinsic の呼び出しとして下ろす。
+    ///
+    /// 引数の型は型検査が書式から決めているため、ここでは指定子と引数の個数がそろっているかだけを確かめる。
+    fn lower_format(
+        &self,
+        template: &str,
+        args: &[&A::Expr],
+        env: &mut Env,
+    ) -> Result<Expr, CoreIrError> {
+        let pieces = runtime_common::parse_format(template).map_err(|msg| {
+            CoreIrError::new("COREIR171", format!("format の書式が不正です: {msg}"))
+        })?;
+        let specs = runtime_common::format_specs(&pieces);
+        if specs.len() != args.len() {
+            return Err(format_arity_error());
+        }
+        let mut params = vec![ValueTy::String];
+        let mut lowered_args = vec![Expr::Literal {
+            value: Literal::String(template.into()),
+            ty: ValueTy::String,
+        }];
+        for (spec, arg) in specs.iter().zip(args) {
+            params.push(match spec.type_name() {
+                "Int" => ValueTy::Int,
+                "Double" => ValueTy::Double,
+                _ => ValueTy::String,
+            });
+            lowered_args.push(self.lower_expr(arg, env)?);
+        }
+        Ok(Expr::Apply {
+            func: Box::new(Expr::Var {
+                name: "format".into(),
+                ty: ValueTy::Function {
+                    params,
+                    result: Box::new(ValueTy::String),
+                },
+                kind: VarKind::Intrinsic,
+            }),
+            args: lowered_args,
+            ty: ValueTy::String,
+        })
+    }
+
+    fn is_named_binop_primitive(&self, name: &str, env: &Env) -> bool {
+        name.starts_with(|c: char| c.is_ascii_lowercase())
+            && env.lookup(name).is_none()
+            && !self.function_sigs.contains_key(name)
+            && BINOP_SPECS.iter().any(|spec| spec.symbol == name)
+    }
+
+    fn is_ord_select_primitive(&self, name: &str, env: &Env) -> bool {
+        ORD_SELECT_PRIMITIVES.contains(&name)
+            && env.lookup(name).is_none()
+            && !self.function_sigs.contains_key(name)
+    }
+
+    /// `foldl' f z xs` を、`f` の呼び出しを本体に持つ 2 引数のラムダと初期値・リストへの適用として下ろす。
+    ///
+    /// `f` にはトップレベル関数・演算子・2 引数のラムダ式を渡せる。関数値そのものは生成コードで扱えないため、
+    /// コード生成は本体をループへ埋め込み、累積値とリストの残りを受け渡しながら畳み込む。
+    fn lower_fold_left(
+        &self,
+        name: &str,
+        step: &A::Expr,
+        init: &A::Expr,
+        list: &A::Expr,
+        env: &mut Env,
+    ) -> Result<Expr, CoreIrError> {
+        let init = self.lower_expr(init, env)?;
+        let list = self.lower_expr(list, env)?;
+        let acc_ty = init.ty().clone();
+        let item_ty = match list.ty() {
+            ValueTy::List(item) => item.as_ref().clone(),
+            _ => ValueTy::Unknown,
+        };
+        let (acc_name, item_name, body) = match step {
+            A::Expr::Lambda { params, body, .. } if params.len() == 2 => {
+                (params[0].clone(), params[1].clone(), body.as_ref().clone())
+            }
+            _ => {
+                let id = self.fresh.get();
+                self.fresh.set(id + 1);
+                let acc_name = format!("${name}{id}_acc");
+                let item_name = format!("${name}{id}_item");
+                let var = |name: &str| {
+                    Box::new(A::Expr::Var {
+                        name: name.to_string(),
+                        span: A::Span::dummy(),
+                    })
+                };
+                let body = match step {
+                    // `foldl' (+) 0 xs` のような演算子は二項演算として下ろす。
+                    A::Expr::Var { name: op, .. }
+                        if !op.starts_with(|c: char| c.is_alphabetic() || c == '_') =>
+                    {
+                        A::Expr::BinOp {
+                            op: op.clone(),
+                            left: var(&acc_name),
+                            right: var(&item_name),
+                            span: A::Span::dummy(),
+                        }
+                    }
+                    _ => A::Expr::App {
+                        func: Box::new(A::Expr::App {
+                            func: Box::new(step.clone()),
+                            arg: var(&acc_name),
+                            span: A::Span::dummy(),
+                        }),
+                        arg: var(&item_name),
+                        span: A::Span::dummy(),
+                    },
+                };
+                (acc_name, item_name, body)
+            }
+        };
+        let mut scope = env.clone();
+        scope.insert(acc_name.clone(), acc_ty.clone());
+        scope.insert(item_name.clone(), item_ty.clone());
+        let body = self.lower_expr(&body, &mut scope)?;
+        if !types_compatible(&acc_ty, body.ty()) {
+            return Err(CoreIrError::new(
+                "COREIR168",
+                format!(
+                    "{name} の関数の結果型 {} が初期値の型 {acc_ty} と一致しません",
+                    body.ty()
+                ),
+            ));
+        }
+        let step = Expr::Lambda {
+            params: vec![
+                Parameter::new(acc_name, acc_ty.clone()),
+                Parameter::new(item_name, item_ty.clone()),
+            ],
+            body: Box::new(body),
+            ty: ValueTy::Function {
+                params: vec![acc_ty.clone(), item_ty],
+                result: Box::new(acc_ty.clone()),
+            },
+        };
+        Ok(Expr::Apply {
+            func: Box::new(Expr::Var {
+                name: name.into(),
+                ty: intrinsic_function_ty(IntrinsicKind::FoldLeft),
+                kind: VarKind::Intrinsic,
+            }),
+            args: vec![step, init, list],
+            ty: acc_ty,
+        })
+    }
+
+    /// `min a b` / `max a b` を `a <= b` で一方を選ぶ `if` へ展開する。
+    ///
+    /// 引数は一度だけ評価するよう `let` で束縛する。型変数の値は `<=` と同じく `Ord` 辞書で比較する。
+    fn lower_ord_select(
+        &self,
+        name: &str,
+        left: &A::Expr,
+        right: &A::Expr,
+        span: A::Span,
+        env: &mut Env,
+    ) -> Result<Expr, CoreIrError> {
+        let lhs = self.lower_expr(left, env)?;
+        let rhs = self.lower_expr(right, env)?;
+        ensure_instance("Ord", lhs.ty(), span)?;
+        let mapping = map_binop("<=", lhs.ty(), rhs.ty())?;
+        if mapping.dict_fallback {
+            self.record_operand_dictionary(name, mapping.prim_op, lhs.ty());
+        }
+        let id = self.fresh.get();
+        self.fresh.set(id + 1);
+        let bind = |operand: Expr, side: &str| {
+            let name = format!("${name}{id}_{side}");
+            let ty = operand.ty().clone();
+            let var = Expr::Var {
+                name: name.clone(),
+                ty: ty.clone(),
+                kind: VarKind::Local,
+            };
+            let binding = Binding {
+                name,
+                value: operand,
+                ty,
+            };
+            (binding, var)
+        };
+        let ty = if lhs.ty() == rhs.ty() {
+            lhs.ty().clone()
+        } else {
+            ValueTy::Unknown
+        };
+        let (lhs_binding, lhs_var) = bind(lhs, "lhs");
+        let (rhs_binding, rhs_var) = bind(rhs, "rhs");
+        let cond = Expr::PrimOp {
+            op: mapping.prim_op,
+            args: vec![lhs_var.clone(), rhs_var.clone()],
+            ty: mapping.result_ty,
+            dict_fallback: mapping.dict_fallback,
+        };
+        // Haskell と同じく、等しい場合は `min` が第 1 引数を、`max` が第 2 引数を返す。
+        let (then_branch, else_branch) = if name == "min" {
+            (lhs_var, rhs_var)
+        } else {
+            (rhs_var, lhs_var)
+        };
+        Ok(Expr::Let {
+            bindings: vec![lhs_binding, rhs_binding],
+            body: Box::new(Expr::If {
+                cond: Box::new(cond),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+                ty: ty.clone(),
+            }),
+            ty,
+        })
+    }
+
+    /// `clamp lo hi x` / `between lo hi x` を `<` / `<=` の比較を組み合わせた式へ展開する。
+    ///
+    /// `clamp` は `if x < lo then lo else if hi < x then hi else x`、`between` は `lo <= x && x <= hi` になる。
+    /// 引数は `min` / `max` と同じく一度だけ評価するよう `let` で束縛し、型変数の値は `Ord` 辞書で比較する。
+    fn lower_ord_range(
+        &self,
+        name: &str,
+        lo: &A::Expr,
+        hi: &A::Expr,
+        x: &A::Expr,
+        span: A::Span,
+        env: &mut Env,
+    ) -> Result<Expr, CoreIrError> {
+        let id = self.fresh.get();
+        self.fresh.set(id + 1);
+        let mut bindings = Vec::with_capacity(3);
+        let mut vars = Vec::with_capacity(3);
+        for (operand, side) in [(lo, "lo"), (hi, "hi"), (x, "x")] {
+            let value = self.lower_expr(operand, env)?;
+            ensure_instance("Ord", value.ty(), span)?;
+            let var_name = format!("${name}{id}_{side}");
+            let ty = value.ty().clone();
+            vars.push(Expr::Var {
+                name: var_name.clone(),
+                ty: ty.clone(),
+                kind: VarKind::Local,
+            });
+            bindings.push(Binding {
+                name: var_name,
+                value,
+                ty,
+            });
+        }
+        let [lo, hi, x] = <[Expr; 3]>::try_from(vars).expect("引数は 3 つ束縛している");
+        let compare = |op: &str, lhs: &Expr, rhs: &Expr| -> Result<Expr, CoreIrError> {
+            let mapping = map_binop(op, lhs.ty(), rhs.ty())?;
+            if mapping.dict_fallback {
+                self.record_operand_dictionary(name, mapping.prim_op, lhs.ty());
+            }
+            Ok(Expr::PrimOp {
+                op: mapping.prim_op,
+                args: vec![lhs.clone(), rhs.clone()],
+                ty: mapping.result_ty,
+                dict_fallback: mapping.dict_fallback,
+            })
+        };
+        let body = if name == "clamp" {
+            let ty = if lo.ty() == hi.ty() && hi.ty() == x.ty() {
+                x.ty().clone()
+            } else {
+                ValueTy::Unknown
+            };
+            Expr::If {
+                cond: Box::new(compare("<", &x, &lo)?),
+                then_branch: Box::new(lo),
+                else_branch: Box::new(Expr::If {
+                    cond: Box::new(compare("<", &hi, &x)?),
+                    then_branch: Box::new(hi),
+                    else_branch: Box::new(x),
+                    ty: ty.clone(),
+                }),
+                ty,
+            }
+        } else {
+            Expr::PrimOp {
+                op: PrimOp::AndBool,
+                args: vec![compare("<=", &lo, &x)?, compare("<=", &x, &hi)?],
+                ty: ValueTy::Bool,
+                dict_fallback: false,
+            }
+        };
+        Ok(Expr::Let {
+            bindings,
+            ty: body.ty().clone(),
+            body: Box::new(body),
+        })
+    }
+
     fn lower_binop(
         &self,
         op: &str,
         left: &A::Expr,
         right: &A::Expr,
+        span: A::Span,
         env: &mut Env,
     ) -> Result<Expr, CoreIrError> {
         let lhs = self.lower_expr(left, env)?;
         let rhs = self.lower_expr(right, env)?;
+        if let Some(classname) = primitives::operator_class(op) {
+            ensure_instance(classname, lhs.ty(), span)?;
+            ensure_instance(classname, rhs.ty(), span)?;
+        }
         let mapping = map_binop(op, lhs.ty(), rhs.ty())?;
+        if mapping.dict_fallback {
+            self.record_operand_dictionary(op, mapping.prim_op, lhs.ty());
+        }
         Ok(Expr::PrimOp {
             op: mapping.prim_op,
             args: vec![lhs, rhs],
@@ -542,6 +1078,35 @@ impl LoweringContext {
         })
     }
 
+    /// リスト・タプル同士の比較に使う辞書を記録する。型変数由来の辞書は引数で受け取るため対象外。
+    fn record_operand_dictionary(&self, op: &str, prim_op: PrimOp, operand_ty: &ValueTy) {
+        let Some(info) = prim_op.dictionary_method() else {
+            return;
+        };
+        let Some(resolution) = resolve_operand_dictionary(info.classname, operand_ty) else {
+            return;
+        };
+        self.operand_dictionaries.borrow_mut().push(DictionaryInit {
+            classname: info.classname.to_string(),
+            scheme_repr: format!("{} {}", info.classname, resolution.type_repr),
+            type_repr: resolution.type_repr,
+            value_ty: resolution.value_ty,
+            methods: resolution
+                .methods
+                .into_iter()
+                .map(|method| DictionaryMethod {
+                    name: method.name.to_string(),
+                    signature: Some(method.signature),
+                    symbol: method.symbol.to_string(),
+                    method_id: method.method_id,
+                })
+                .collect(),
+            builder: DictionaryBuilder::Resolved(resolution.builder.to_string()),
+            origin: format!("({op})"),
+            source_span: SourceRef::default(),
+        });
+    }
+
     fn lower_case(
         &self,
         scrutinee: &A::Expr,
@@ -623,6 +1188,7 @@ impl LoweringContext {
         pattern: &A::Pattern,
         expected_ty: &ValueTy,
     ) -> Result<Vec<MatchBinding>, CoreIrError> {
+        ensure_linear_pattern(pattern)?;
         let mut out = Vec::new();
         let mut path = Vec::new();
         self.collect_pattern_bindings(pattern, expected_ty, &mut out, &mut path)?;
@@ -687,6 +1253,27 @@ impl LoweringContext {
         Ok(())
     }
 
+    /// 制約付きの関数へ渡す実引数のうち、制約の型変数の位置にある値がインスタンスを持つか確かめる。
+    fn ensure_call_instances(
+        &self,
+        name: &str,
+        args: &[Expr],
+        span: A::Span,
+    ) -> Result<(), CoreIrError> {
+        let Some(scheme) = self.type_env.lookup(name) else {
+            return Ok(());
+        };
+        let (params, _) = flatten_fun_type_types(&scheme.qual.r#type);
+        for constraint in &scheme.qual.constraints {
+            for (param, arg) in params.iter().zip(args) {
+                if *param == constraint.r#type {
+                    ensure_instance(&constraint.classname, arg.ty(), span)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn inject_dictionary_args(
         &self,
         param_specs: &[ParameterSpec],
@@ -855,7 +1442,7 @@ fn convert_type_with_overrides(ty: &Type) -> Result<ValueTy, CoreIrError> {
     }
 }
 
-fn convert_type(ty: &Type) -> Result<ValueTy, CoreIrError> {
+pub(crate) fn convert_type(ty: &Type) -> Result<ValueTy, CoreIrError> {
     match ty {
         Type::TCon(TCon { name }) => match name.as_str() {
             "Int" | "Integer" => Ok(ValueTy::Int),
@@ -885,6 +1472,9 @@ fn convert_type(ty: &Type) -> Result<ValueTy, CoreIrError> {
                     }
                     return Ok(ValueTy::List(Box::new(elem_ty)));
                 }
+                if name == "Array" {
+                    return Ok(ValueTy::Array(Box::new(convert_type(arg)?)));
+                }
             }
             let func_ty = convert_type(func)?;
             let arg_ty = convert_type(arg)?;
@@ -898,6 +1488,7 @@ fn convert_type(ty: &Type) -> Result<ValueTy, CoreIrError> {
                 }
                 ValueTy::Function { .. } | ValueTy::Unknown => Ok(ValueTy::Unknown),
                 ValueTy::List(_)
+                | ValueTy::Array(_)
                 | ValueTy::Tuple(_)
                 | ValueTy::Dictionary { .. }
                 | ValueTy::Int
@@ -964,12 +1555,187 @@ fn flatten_app<'a>(func: &'a A::Expr, arg: &'a A::Expr) -> (&'a A::Expr, Vec<&'a
     (head, args)
 }
 
+/// 1 つのパターン内で同じ名前を 2 度束縛していないか（線形パターンか）を検証する。
+fn ensure_linear_pattern(pattern: &A::Pattern) -> Result<(), CoreIrError> {
+    fn walk<'p>(pattern: &'p A::Pattern, seen: &mut HashSet<&'p str>) -> Result<(), CoreIrError> {
+        let mut bind = |name: &'p str| {
+            if seen.insert(name) {
+                Ok(())
+            } else {
+                Err(CoreIrError::new(
+                    "COREIR164",
+                    format!("パターン変数 {name} が 1 つのパターン内で重複しています"),
+                ))
+            }
+        };
+        match pattern {
+            A::Pattern::Var { name, .. } => bind(name),
+            A::Pattern::As {
+                binder, pattern, ..
+            } => {
+                bind(binder)?;
+                walk(pattern, seen)
+            }
+            A::Pattern::List { items, .. } | A::Pattern::Tuple { items, .. } => {
+                items.iter().try_for_each(|item| walk(item, seen))
+            }
+            A::Pattern::Constructor { args, .. } => args.iter().try_for_each(|arg| walk(arg, seen)),
+            A::Pattern::Wildcard { .. }
+            | A::Pattern::Int { .. }
+            | A::Pattern::Float { .. }
+            | A::Pattern::Char { .. }
+            | A::Pattern::String { .. }
+            | A::Pattern::Bool { .. } => Ok(()),
+        }
+    }
+    walk(pattern, &mut HashSet::new())
+}
+
+/// 組み込みの `Ref a` は、箱詰めした値を 1 つ保持するセルへのポインタとして扱う。
+fn ref_value_ty(item: ValueTy) -> ValueTy {
+    ValueTy::Data {
+        constructor: "Ref".into(),
+        args: vec![item],
+    }
+}
+
+/// 組み込みの `Map k v` は、キー・値を箱詰めして保持するポインタ表現のデータとして扱う。
+fn map_value_ty() -> ValueTy {
+    ValueTy::Data {
+        constructor: "Map".into(),
+        args: vec![ValueTy::Unknown, ValueTy::Unknown],
+    }
+}
+
+/// `format` を書式リテラルと指定子の数の引数をそろえずに使った場合のエラー。
+fn format_arity_error() -> CoreIrError {
+    CoreIrError::new(
+        "COREIR171",
+        "format はネイティブバックエンドでは書式リテラルと指定子の数の引数をそろえた呼び出しのみ対応しています",
+    )
+}
+
+/// `<>` の結果型。両辺は同じ型なので、要素型まで分かっている側を採る。
+fn append_result_ty(lhs: &ValueTy, rhs: &ValueTy) -> ValueTy {
+    match (lhs, rhs) {
+        (ValueTy::List(item), other) if **item == ValueTy::Unknown => other.clone(),
+        (ValueTy::Unknown, other) => other.clone(),
+        _ => lhs.clone(),
+    }
+}
+
 fn intrinsic_function_ty(kind: IntrinsicKind) -> ValueTy {
     match kind {
         IntrinsicKind::Println => ValueTy::Function {
             params: vec![ValueTy::Unknown],
             result: Box::new(ValueTy::Unknown),
         },
+        IntrinsicKind::Trace => ValueTy::Function {
+            params: vec![ValueTy::Unknown, ValueTy::Unknown],
+            result: Box::new(ValueTy::Unknown),
+        },
+        IntrinsicKind::Sqrt
+        | IntrinsicKind::Sin
+        | IntrinsicKind::Cos
+        | IntrinsicKind::Exp
+        | IntrinsicKind::Log => ValueTy::Function {
+            params: vec![ValueTy::Double],
+            result: Box::new(ValueTy::Double),
+        },
+        IntrinsicKind::Floor
+        | IntrinsicKind::Ceiling
+        | IntrinsicKind::Round
+        | IntrinsicKind::Truncate => ValueTy::Function {
+            params: vec![ValueTy::Double],
+            result: Box::new(ValueTy::Int),
+        },
+        IntrinsicKind::FromIntegral => ValueTy::Function {
+            params: vec![ValueTy::Int],
+            result: Box::new(ValueTy::Double),
+        },
+        IntrinsicKind::Null => ValueTy::Function {
+            params: vec![ValueTy::List(Box::new(ValueTy::Unknown))],
+            result: Box::new(ValueTy::Bool),
+        },
+        IntrinsicKind::Length => ValueTy::Function {
+            params: vec![ValueTy::List(Box::new(ValueTy::Unknown))],
+            result: Box::new(ValueTy::Int),
+        },
+        IntrinsicKind::Replicate => ValueTy::Function {
+            params: vec![ValueTy::Int, ValueTy::Unknown],
+            result: Box::new(ValueTy::List(Box::new(ValueTy::Unknown))),
+        },
+        IntrinsicKind::Take | IntrinsicKind::Drop => ValueTy::Function {
+            params: vec![ValueTy::Int, ValueTy::List(Box::new(ValueTy::Unknown))],
+            result: Box::new(ValueTy::List(Box::new(ValueTy::Unknown))),
+        },
+        IntrinsicKind::Append => ValueTy::Function {
+            params: vec![ValueTy::Unknown, ValueTy::Unknown],
+            result: Box::new(ValueTy::Unknown),
+        },
+        IntrinsicKind::FoldLeft => ValueTy::Function {
+            params: vec![
+                ValueTy::Function {
+                    params: vec![ValueTy::Unknown, ValueTy::Unknown],
+                    result: Box::new(ValueTy::Unknown),
+                },
+                ValueTy::Unknown,
+                ValueTy::List(Box::new(ValueTy::Unknown)),
+            ],
+            result: Box::new(ValueTy::Unknown),
+        },
+        IntrinsicKind::ArrayFromList => ValueTy::Function {
+            params: vec![ValueTy::List(Box::new(ValueTy::Unknown))],
+            result: Box::new(ValueTy::Array(Box::new(ValueTy::Unknown))),
+        },
+        IntrinsicKind::ArrayIndex => ValueTy::Function {
+            params: vec![ValueTy::Array(Box::new(ValueTy::Unknown)), ValueTy::Int],
+            result: Box::new(ValueTy::Unknown),
+        },
+        IntrinsicKind::NewRef => ValueTy::Function {
+            params: vec![ValueTy::Unknown],
+            result: Box::new(ref_value_ty(ValueTy::Unknown)),
+        },
+        IntrinsicKind::ReadRef => ValueTy::Function {
+            params: vec![ref_value_ty(ValueTy::Unknown)],
+            result: Box::new(ValueTy::Unknown),
+        },
+        IntrinsicKind::WriteRef => ValueTy::Function {
+            params: vec![ref_value_ty(ValueTy::Unknown), ValueTy::Unknown],
+            result: Box::new(ValueTy::Unit),
+        },
+        IntrinsicKind::EmptyMap => map_value_ty(),
+        IntrinsicKind::MapInsert => ValueTy::Function {
+            params: vec![ValueTy::Unknown, ValueTy::Unknown, map_value_ty()],
+            result: Box::new(map_value_ty()),
+        },
+        IntrinsicKind::MapLookup => ValueTy::Function {
+            params: vec![ValueTy::Unknown, map_value_ty()],
+            result: Box::new(ValueTy::Data {
+                constructor: MAYBE_TYPE.into(),
+                args: vec![ValueTy::Unknown],
+            }),
+        },
+        IntrinsicKind::MapFindWithDefault => ValueTy::Function {
+            params: vec![ValueTy::Unknown, ValueTy::Unknown, map_value_ty()],
+            result: Box::new(ValueTy::Unknown),
+        },
+        // 引数の個数と型は書式で決まるため、呼び出しごとに `lower_format` が型を組み立てる。
+        IntrinsicKind::Format => ValueTy::Function {
+            params: vec![ValueTy::String],
+            result: Box::new(ValueTy::String),
+        },
+        IntrinsicKind::ReadInt | IntrinsicKind::ReadDouble => ValueTy::Function {
+            params: vec![ValueTy::String],
+            result: Box::new(ValueTy::Data {
+                constructor: MAYBE_TYPE.into(),
+                args: vec![if kind == IntrinsicKind::ReadInt {
+                    ValueTy::Int
+                } else {
+                    ValueTy::Double
+                }],
+            }),
+        },
     }
 }
 
@@ -1013,11 +1779,12 @@ fn infer_apply_type(func: &Expr, args: &[Expr]) -> Result<ValueTy, CoreIrError>
     }
 }
 
+/// アームのタグ判定に使うコンストラクタ名。`as` パターンは内側のパターンで判定する。
 fn pattern_constructor(pattern: &A::Pattern) -> Option<&str> {
-    if let A::Pattern::Constructor { name, .. } = pattern {
-        Some(name.as_str())
-    } else {
-        None
+    match pattern {
+        A::Pattern::Constructor { name, .. } => Some(name.as_str()),
+        A::Pattern::As { pattern, .. } => pattern_constructor(pattern),
+        _ => None,
     }
 }
 
@@ -1051,6 +1818,8 @@ enum OperandKind {
     Int,
     Double,
     Bool,
+    /// 文字はコードポイントの整数として比較する。
+    Char,
 }
 
 impl OperandKind {
@@ -1060,6 +1829,7 @@ impl OperandKind {
             (OperandKind::Int, ValueTy::Int)
                 | (OperandKind::Double, ValueTy::Double)
                 | (OperandKind::Bool, ValueTy::Bool)
+                | (OperandKind::Char, ValueTy::Char)
         )
     }
 }
@@ -1106,6 +1876,17 @@ impl BinOpSpec {
     }
 }
 
+/// 2 引数で呼ばれたときに `<=` による選択へ展開するプリミティブ。
+const ORD_SELECT_PRIMITIVES: &[&str] = &["min", "max"];
+
+/// 3 引数で呼ばれたときに比較の組み合わせへ展開するプリミティブ。
+const ORD_RANGE_PRIMITIVES: &[&str] = &["clamp", "between"];
+
+/// `name` が `foldl'` の intrinsic か。
+fn is_fold_left(name: &str) -> bool {
+    intrinsics::lookup(name).is_some_and(|intr| intr.kind == IntrinsicKind::FoldLeft)
+}
+
 const BINOP_SPECS: &[BinOpSpec] = &[
     BinOpSpec {
         symbol: "+",
@@ -1225,6 +2006,12 @@ const BINOP_SPECS: &[BinOpSpec] = &[
                 prim_op: PrimOp::EqInt,
                 result: ValueTy::Bool,
             },
+            BinOpCase {
+                lhs: OperandKind::Char,
+                rhs: OperandKind::Char,
+                prim_op: PrimOp::EqInt,
+                result: ValueTy::Bool,
+            },
             BinOpCase {
                 lhs: OperandKind::Bool,
                 rhs: OperandKind::Bool,
@@ -1253,6 +2040,12 @@ const BINOP_SPECS: &[BinOpSpec] = &[
                 prim_op: PrimOp::NeqInt,
                 result: ValueTy::Bool,
             },
+            BinOpCase {
+                lhs: OperandKind::Char,
+                rhs: OperandKind::Char,
+                prim_op: PrimOp::NeqInt,
+                result: ValueTy::Bool,
+            },
             BinOpCase {
                 lhs: OperandKind::Bool,
                 rhs: OperandKind::Bool,
@@ -1281,6 +2074,12 @@ const BINOP_SPECS: &[BinOpSpec] = &[
                 prim_op: PrimOp::LtInt,
                 result: ValueTy::Bool,
             },
+            BinOpCase {
+                lhs: OperandKind::Char,
+                rhs: OperandKind::Char,
+                prim_op: PrimOp::LtInt,
+                result: ValueTy::Bool,
+            },
             BinOpCase {
                 lhs: OperandKind::Double,
                 rhs: OperandKind::Double,
@@ -1303,6 +2102,12 @@ const BINOP_SPECS: &[BinOpSpec] = &[
                 prim_op: PrimOp::LeInt,
                 result: ValueTy::Bool,
             },
+            BinOpCase {
+                lhs: OperandKind::Char,
+                rhs: OperandKind::Char,
+                prim_op: PrimOp::LeInt,
+                result: ValueTy::Bool,
+            },
             BinOpCase {
                 lhs: OperandKind::Double,
                 rhs: OperandKind::Double,
@@ -1325,6 +2130,12 @@ const BINOP_SPECS: &[BinOpSpec] = &[
                 prim_op: PrimOp::GtInt,
                 result: ValueTy::Bool,
             },
+            BinOpCase {
+                lhs: OperandKind::Char,
+                rhs: OperandKind::Char,
+                prim_op: PrimOp::GtInt,
+                result: ValueTy::Bool,
+            },
             BinOpCase {
                 lhs: OperandKind::Double,
                 rhs: OperandKind::Double,
@@ -1347,6 +2158,12 @@ const BINOP_SPECS: &[BinOpSpec] = &[
                 prim_op: PrimOp::GeInt,
                 result: ValueTy::Bool,
             },
+            BinOpCase {
+                lhs: OperandKind::Char,
+                rhs: OperandKind::Char,
+                prim_op: PrimOp::GeInt,
+                result: ValueTy::Bool,
+            },
             BinOpCase {
                 lhs: OperandKind::Double,
                 rhs: OperandKind::Double,
@@ -1387,6 +2204,61 @@ const BINOP_SPECS: &[BinOpSpec] = &[
             result: ValueTy::Bool,
         }),
     },
+    BinOpSpec {
+        symbol: ".&.",
+        error_code: "COREIR155",
+        cases: &[BinOpCase {
+            lhs: OperandKind::Int,
+            rhs: OperandKind::Int,
+            prim_op: PrimOp::AndInt,
+            result: ValueTy::Int,
+        }],
+        dict_fallback: None,
+    },
+    BinOpSpec {
+        symbol: ".|.",
+        error_code: "COREIR156",
+        cases: &[BinOpCase {
+            lhs: OperandKind::Int,
+            rhs: OperandKind::Int,
+            prim_op: PrimOp::OrInt,
+            result: ValueTy::Int,
+        }],
+        dict_fallback: None,
+    },
+    BinOpSpec {
+        symbol: "xor",
+        error_code: "COREIR157",
+        cases: &[BinOpCase {
+            lhs: OperandKind::Int,
+            rhs: OperandKind::Int,
+            prim_op: PrimOp::XorInt,
+            result: ValueTy::Int,
+        }],
+        dict_fallback: None,
+    },
+    BinOpSpec {
+        symbol: "shiftL",
+        error_code: "COREIR158",
+        cases: &[BinOpCase {
+            lhs: OperandKind::Int,
+            rhs: OperandKind::Int,
+            prim_op: PrimOp::ShlInt,
+            result: ValueTy::Int,
+        }],
+        dict_fallback: None,
+    },
+    BinOpSpec {
+        symbol: "shiftR",
+        error_code: "COREIR159",
+        cases: &[BinOpCase {
+            lhs: OperandKind::Int,
+            rhs: OperandKind::Int,
+            prim_op: PrimOp::ShrInt,
+            result: ValueTy::Int,
+        }],
+        dict_fallback: None,
+    },
 ];
 
 fn map_binop(op: &str, lhs_ty: &ValueTy, rhs_ty: &ValueTy) -> Result<BinOpMapping, CoreIrError> {
@@ -1414,10 +2286,23 @@ fn map_binop(op: &str, lhs_ty: &ValueTy, rhs_ty: &ValueTy) -> Result<BinOpMappin
         }
     }
 
+    // リスト・タプル同士の比較は、要素を構造的に比べる Eq/Ord 辞書へ回す。
+    if lhs_ty == rhs_ty && BuiltinTypeKind::of_value_ty(lhs_ty).is_some_and(|k| k.is_structured()) {
+        if let Some(dict) = &spec.dict_fallback {
+            let class = dict.prim_op.dictionary_method().map(|info| info.classname);
+            if matches!(class, Some("Eq" | "Ord")) && type_repr::from_value_ty(lhs_ty).is_some() {
+                return Ok(BinOpMapping::dictionary(dict.prim_op, dict.result.clone()));
+            }
+        }
+    }
+
     Err(spec.type_mismatch(lhs_ty, rhs_ty))
 }
 
-fn type_expr_to_value_ty(expr: &A::TypeExpr, subst: &HashMap<String, ValueTy>) -> ValueTy {
+pub(super) fn type_expr_to_value_ty(
+    expr: &A::TypeExpr,
+    subst: &HashMap<String, ValueTy>,
+) -> ValueTy {
     match expr {
         A::TypeExpr::TEVar(name) => subst.get(name).cloned().unwrap_or(ValueTy::Unknown),
         A::TypeExpr::TECon(name) => match name.as_str() {
@@ -1468,43 +2353,10 @@ fn type_expr_to_value_ty(expr: &A::TypeExpr, subst: &HashMap<String, ValueTy>) -
     }
 }
 
-fn type_to_string(ty: &Type) -> String {
-    match ty {
-        Type::TVar(tv) => format!("t{}", tv.id),
-        Type::TCon(tc) => tc.name.clone(),
-        Type::TApp(TApp { func, arg }) => match func.as_ref() {
-            Type::TCon(TCon { name }) if name == "[]" => format!("[{}]", type_to_string(arg)),
-            _ => {
-                let func_str = type_to_string(func);
-                let arg_str = match arg.as_ref() {
-                    Type::TFun(_) => format!("({})", type_to_string(arg)),
-                    _ => type_to_string(arg),
-                };
-                format!("{} {}", func_str, arg_str)
-            }
-        },
-        Type::TFun(TFun { arg, ret }) => {
-            let arg_str = match arg.as_ref() {
-                Type::TFun(_) => format!("({})", type_to_string(arg)),
-                _ => type_to_string(arg),
-            };
-            format!("{} -> {}", arg_str, type_to_string(ret))
-        }
-        Type::TTuple(TTuple { items }) => {
-            let inner = items
-                .iter()
-                .map(type_to_string)
-                .collect::<Vec<_>>()
-                .join(", ");
-            format!("({inner})")
-        }
-    }
-}
-
 struct DictionaryResolution {
     builder: &'static str,
     methods: Vec<DictionaryResolutionMethod>,
-    type_repr: &'static str,
+    type_repr: String,
     value_ty: ValueTy,
 }
 
@@ -1557,6 +2409,24 @@ const ORD_DOUBLE_METHOD_SYMBOLS: &[(&str, &str)] = &[
     ("ge", "tl_ord_double_ge"),
 ];
 
+const EQ_LIST_METHOD_SYMBOLS: &[(&str, &str)] = &[("eq", "tl_eq_list"), ("neq", "tl_neq_list")];
+
+const EQ_TUPLE_METHOD_SYMBOLS: &[(&str, &str)] = &[("eq", "tl_eq_tuple"), ("neq", "tl_neq_tuple")];
+
+const ORD_LIST_METHOD_SYMBOLS: &[(&str, &str)] = &[
+    ("lt", "tl_ord_list_lt"),
+    ("le", "tl_ord_list_le"),
+    ("gt", "tl_ord_list_gt"),
+    ("ge", "tl_ord_list_ge"),
+];
+
+const ORD_TUPLE_METHOD_SYMBOLS: &[(&str, &str)] = &[
+    ("lt", "tl_ord_tuple_lt"),
+    ("le", "tl_ord_tuple_le"),
+    ("gt", "tl_ord_tuple_gt"),
+    ("ge", "tl_ord_tuple_ge"),
+];
+
 const BOOL_LOGIC_METHOD_SYMBOLS: &[(&str, &str)] = &[
     ("and", "tl_bool_logic_and"),
     ("or", "tl_bool_logic_or"),
@@ -1568,6 +2438,26 @@ enum BuiltinTypeKind {
     Int,
     Double,
     Bool,
+    List,
+    Tuple,
+}
+
+impl BuiltinTypeKind {
+    /// 要素型によらず 1 つの辞書実装を共有し、`type_repr` は実際の型から決める種別か。
+    fn is_structured(self) -> bool {
+        matches!(self, Self::List | Self::Tuple)
+    }
+
+    fn of_value_ty(ty: &ValueTy) -> Option<Self> {
+        match ty {
+            ValueTy::Int => Some(Self::Int),
+            ValueTy::Double => Some(Self::Double),
+            ValueTy::Bool => Some(Self::Bool),
+            ValueTy::List(_) => Some(Self::List),
+            ValueTy::Tuple(items) if !items.is_empty() => Some(Self::Tuple),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -1653,6 +2543,38 @@ const BUILTIN_DICTIONARY_DESCRIPTORS: &[BuiltinDictionaryDescriptor] = &[
         value_ty: ValueTy::Double,
         method_symbols: ORD_DOUBLE_METHOD_SYMBOLS,
     },
+    BuiltinDictionaryDescriptor {
+        classname: "Eq",
+        target: BuiltinTypeKind::List,
+        type_repr: "[a]",
+        builder: "tl_dict_build_Eq_List",
+        value_ty: ValueTy::Unknown,
+        method_symbols: EQ_LIST_METHOD_SYMBOLS,
+    },
+    BuiltinDictionaryDescriptor {
+        classname: "Eq",
+        target: BuiltinTypeKind::Tuple,
+        type_repr: "(a, b)",
+        builder: "tl_dict_build_Eq_Tuple",
+        value_ty: ValueTy::Unknown,
+        method_symbols: EQ_TUPLE_METHOD_SYMBOLS,
+    },
+    BuiltinDictionaryDescriptor {
+        classname: "Ord",
+        target: BuiltinTypeKind::List,
+        type_repr: "[a]",
+        builder: "tl_dict_build_Ord_List",
+        value_ty: ValueTy::Unknown,
+        method_symbols: ORD_LIST_METHOD_SYMBOLS,
+    },
+    BuiltinDictionaryDescriptor {
+        classname: "Ord",
+        target: BuiltinTypeKind::Tuple,
+        type_repr: "(a, b)",
+        builder: "tl_dict_build_Ord_Tuple",
+        value_ty: ValueTy::Unknown,
+        method_symbols: ORD_TUPLE_METHOD_SYMBOLS,
+    },
     BuiltinDictionaryDescriptor {
         classname: "BoolLogic",
         target: BuiltinTypeKind::Bool,
@@ -1665,15 +2587,43 @@ const BUILTIN_DICTIONARY_DESCRIPTORS: &[BuiltinDictionaryDescriptor] = &[
 
 fn resolve_builtin_dictionary(classname: &str, ty: &Type) -> Option<DictionaryResolution> {
     let descriptor = descriptor_for(classname, ty)?;
-    let methods = build_methods_for_descriptor(descriptor)?;
+    if descriptor.target.is_structured() {
+        let value_ty = convert_type(ty).unwrap_or_else(|_| descriptor.value_ty.clone());
+        return resolve_structured_dictionary(descriptor, type_repr::from_type(ty), value_ty);
+    }
+    let methods = build_methods_for_descriptor(descriptor, descriptor.type_repr)?;
     Some(DictionaryResolution {
         builder: descriptor.builder,
         methods,
-        type_repr: descriptor.type_repr,
+        type_repr: descriptor.type_repr.to_string(),
         value_ty: descriptor.value_ty.clone(),
     })
 }
 
+/// 演算子の被演算子型（リスト・タプル）から、構造比較を行う辞書を引く。
+fn resolve_operand_dictionary(classname: &str, ty: &ValueTy) -> Option<DictionaryResolution> {
+    let kind = BuiltinTypeKind::of_value_ty(ty).filter(|kind| kind.is_structured())?;
+    let descriptor = BUILTIN_DICTIONARY_DESCRIPTORS
+        .iter()
+        .find(|desc| desc.classname == classname && desc.target == kind)?;
+    resolve_structured_dictionary(descriptor, type_repr::from_value_ty(ty)?, ty.clone())
+}
+
+/// リスト・タプルの辞書は要素型によらず同じビルダーを共有し、キーだけを実際の型で区別する。
+fn resolve_structured_dictionary(
+    descriptor: &'static BuiltinDictionaryDescriptor,
+    type_repr: String,
+    value_ty: ValueTy,
+) -> Option<DictionaryResolution> {
+    let methods = build_methods_for_descriptor(descriptor, &type_repr)?;
+    Some(DictionaryResolution {
+        builder: descriptor.builder,
+        methods,
+        type_repr,
+        value_ty,
+    })
+}
+
 fn descriptor_for(classname: &str, ty: &Type) -> Option<&'static BuiltinDictionaryDescriptor> {
     let fallback = match classname {
         "Num" | "Integral" | "Eq" | "Ord" => BuiltinTypeKind::Int,
@@ -1689,6 +2639,7 @@ fn descriptor_for(classname: &str, ty: &Type) -> Option<&'static BuiltinDictiona
 
 fn build_methods_for_descriptor(
     descriptor: &BuiltinDictionaryDescriptor,
+    type_repr: &str,
 ) -> Option<Vec<DictionaryResolutionMethod>> {
     let specs = dict_specs::methods_for_class(descriptor.classname)?;
     let mut methods = Vec::with_capacity(specs.len());
@@ -1698,7 +2649,7 @@ fn build_methods_for_descriptor(
             .iter()
             .find(|(name, _)| *name == spec.name)
             .map(|(_, sym)| *sym)?;
-        let signature = spec.pattern.instantiate(descriptor.type_repr).into_owned();
+        let signature = spec.pattern.instantiate(type_repr).into_owned();
         methods.push(DictionaryResolutionMethod {
             name: spec.name,
             signature,
@@ -1717,36 +2668,21 @@ fn detect_builtin_type(ty: &Type) -> Option<BuiltinTypeKind> {
             "Bool" => Some(BuiltinTypeKind::Bool),
             _ => None,
         },
+        // `[Char]` は文字列としてボックス化されるため、リストの辞書は使えない。
+        Type::TApp(TApp { func, arg }) if matches!(func.as_ref(), Type::TCon(TCon { name }) if name == "[]") => {
+            match arg.as_ref() {
+                Type::TCon(TCon { name }) if name == "Char" => None,
+                _ => Some(BuiltinTypeKind::List),
+            }
+        }
+        Type::TTuple(TTuple { items }) if !items.is_empty() => Some(BuiltinTypeKind::Tuple),
         Type::TVar(_) => None,
         _ => None,
     }
 }
 
 fn scheme_to_string(scheme: &Scheme) -> String {
-    let vars = if scheme.vars.is_empty() {
-        String::new()
-    } else {
-        let names = scheme
-            .vars
-            .iter()
-            .map(|tv| format!("t{}", tv.id))
-            .collect::<Vec<_>>()
-            .join(", ");
-        format!("forall {names}. ")
-    };
-    let constraints = if scheme.qual.constraints.is_empty() {
-        String::new()
-    } else {
-        let cs = scheme
-            .qual
-            .constraints
-            .iter()
-            .map(|c| format!("{} {}", c.classname, type_to_string(&c.r#type)))
-            .collect::<Vec<_>>()
-            .join(", ");
-        format!("{cs} => ")
-    };
-    format!("{vars}{constraints}{}", type_to_string(&scheme.qual.r#type))
+    crate::typesys::pretty_scheme(scheme)
 }
 
 fn collect_type_vars(ty: &Type, out: &mut Vec<TVar>) {
@@ -1769,19 +2705,42 @@ fn collect_type_vars(ty: &Type, out: &mut Vec<TVar>) {
     }
 }
 
+fn collect_texpr_var_names(te: &A::TypeExpr, out: &mut Vec<String>) {
+    match te {
+        A::TypeExpr::TEVar(name) => {
+            if !name.starts_with(char::is_uppercase) && !out.contains(name) {
+                out.push(name.clone());
+            }
+        }
+        A::TypeExpr::TECon(_) => {}
+        A::TypeExpr::TEApp(f, a) | A::TypeExpr::TEFun(f, a) => {
+            collect_texpr_var_names(f, out);
+            collect_texpr_var_names(a, out);
+        }
+        A::TypeExpr::TEList(inner) => collect_texpr_var_names(inner, out),
+        A::TypeExpr::TETuple(items) => {
+            for item in items {
+                collect_texpr_var_names(item, out);
+            }
+        }
+    }
+}
+
 fn scheme_from_sigma(sigma: &A::SigmaType) -> Scheme {
     use std::collections::HashMap as VarMap;
 
     let mut next_id = -1000;
     let mut prebound: VarMap<String, TVar> = VarMap::new();
     for constraint in &sigma.constraints {
-        prebound
-            .entry(constraint.typevar.clone())
-            .or_insert_with(|| {
+        let mut names = Vec::new();
+        collect_texpr_var_names(&constraint.r#type, &mut names);
+        for name in names {
+            prebound.entry(name).or_insert_with(|| {
                 let tv = TVar { id: next_id };
                 next_id -= 1;
                 tv
             });
+        }
     }
     let ty = if prebound.is_empty() {
         infer::type_from_texpr(&sigma.r#type)
@@ -1798,12 +2757,7 @@ fn scheme_from_sigma(sigma: &A::SigmaType) -> Scheme {
         .iter()
         .map(|c| TyConstraint {
             classname: c.classname.clone(),
-            r#type: Type::TVar(
-                prebound
-                    .get(&c.typevar)
-                    .cloned()
-                    .unwrap_or(TVar { id: next_id }),
-            ),
+            r#type: infer::type_from_texpr_with_vars(&c.r#type, &prebound),
         })
         .collect();
     Scheme {
@@ -1823,6 +2777,35 @@ fn classify_loader_error(message: &str) -> &'static str {
     }
 }
 
+/// 組み込みの辞書を持つクラスの値としてデータ型を使っていれば、インスタンスがないことを呼び出し箇所の位置とともに報告する。
+///
+/// 型検査は具体型に対するインスタンスの有無を確かめないため、放置すると辞書の解決が `Int` へ落ちるか、
+/// コード生成で辞書パラメータが見つからないという分かりにくいエラーになる。
+fn ensure_instance(classname: &str, ty: &ValueTy, span: A::Span) -> Result<(), CoreIrError> {
+    let ValueTy::Data { constructor, .. } = ty else {
+        return Ok(());
+    };
+    if !BUILTIN_DICTIONARY_DESCRIPTORS
+        .iter()
+        .any(|desc| desc.classname == classname)
+    {
+        return Ok(());
+    }
+    let repr = type_repr::from_value_ty(ty).unwrap_or_else(|| constructor.clone());
+    let repr = if repr.contains(' ') {
+        format!("({repr})")
+    } else {
+        repr
+    };
+    Err(CoreIrError::new(
+        "COREIR169",
+        format!(
+            "`{classname} {repr}` のインスタンスがありません @line={},col={}",
+            span.line, span.col
+        ),
+    ))
+}
+
 fn span_to_source_ref(span: A::Span) -> SourceRef {
     SourceRef::new(span.line, span.col)
 }
@@ -1859,7 +2842,8 @@ fn types_compatible(expected: &ValueTy, actual: &ValueTy) -> bool {
                         .zip(act.iter())
                         .all(|(e, a)| types_compatible(e, a))
             }
-            (ValueTy::List(exp), ValueTy::List(act)) => types_compatible(exp, act),
+            (ValueTy::List(exp), ValueTy::List(act))
+            | (ValueTy::Array(exp), ValueTy::Array(act)) => types_compatible(exp, act),
             (
                 ValueTy::Function {
                     params: p1,
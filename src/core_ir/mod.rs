@@ -4,10 +4,20 @@
 // 関連ファイル: src/core_ir/lower.rs, src/codegen/cranelift.rs
 #![allow(clippy::module_name_repetitions)]
 
+pub mod constprop;
+
+pub mod cse;
+
 pub mod dict_specs;
 
+pub mod inline;
+
 pub mod lower;
 
+mod newtype;
+
+pub mod type_repr;
+
 use std::collections::BTreeMap;
 use std::fmt;
 
@@ -56,6 +66,8 @@ pub struct DataTypeLayout {
     pub name: String,
     pub type_params: Vec<String>,
     pub constructors: Vec<ConstructorLayout>,
+    /// newtype の場合は包んだ値をそのまま表現として使う。
+    pub newtype: bool,
 }
 
 /// データコンストラクタごとのタグ・フィールド情報。
@@ -293,6 +305,11 @@ pub enum PrimOp {
     AndBool,
     OrBool,
     NotBool,
+    AndInt,
+    OrInt,
+    XorInt,
+    ShlInt,
+    ShrInt,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -323,6 +340,7 @@ impl PrimOp {
             AndBool => ("BoolLogic", "and"),
             OrBool => ("BoolLogic", "or"),
             NotBool => ("BoolLogic", "not"),
+            AndInt | OrInt | XorInt | ShlInt | ShrInt => return None,
         };
         let spec = lookup_method_spec(classname, method)?;
         Some(PrimOpDictionaryInfo {
@@ -355,6 +373,8 @@ pub enum ValueTy {
     Unit,
     Tuple(Vec<ValueTy>),
     List(Box<ValueTy>),
+    /// 要素を連続領域に並べた配列。要素はリストと同じく箱詰めして格納する。
+    Array(Box<ValueTy>),
     Function {
         params: Vec<ValueTy>,
         result: Box<ValueTy>,
@@ -376,7 +396,7 @@ impl ValueTy {
         match self {
             Self::Int | Self::Double | Self::Bool | Self::Char | Self::String | Self::Unit => true,
             Self::Tuple(items) => items.iter().all(Self::is_concrete),
-            Self::List(item) => item.is_concrete(),
+            Self::List(item) | Self::Array(item) => item.is_concrete(),
             Self::Function { params, result } => {
                 params.iter().all(Self::is_concrete) && result.is_concrete()
             }
@@ -405,6 +425,7 @@ impl fmt::Display for ValueTy {
                 write!(f, "({inner})")
             }
             Self::List(item) => write!(f, "[{}]", item),
+            Self::Array(item) => write!(f, "Array<{item}>"),
             Self::Function { params, result } => {
                 let inputs = params
                     .iter()
@@ -497,3 +518,37 @@ impl fmt::Display for CoreIrError {
 }
 
 impl std::error::Error for CoreIrError {}
+
+/// 複数のトップレベル定義から集めた Core IR 生成エラー（発生順、1 件以上）。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreIrErrors(pub Vec<CoreIrError>);
+
+impl CoreIrErrors {
+    /// 最初に検出したエラー。
+    pub fn first(&self) -> &CoreIrError {
+        self.0
+            .first()
+            .expect("CoreIrErrors は少なくとも 1 件のエラーを保持する")
+    }
+}
+
+impl From<CoreIrError> for CoreIrErrors {
+    fn from(err: CoreIrError) -> Self {
+        Self(vec![err])
+    }
+}
+
+impl fmt::Display for CoreIrErrors {
+    /// 1 行に 1 件ずつ `[CODE] message` の形式で列挙する。
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, err) in self.0.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{err}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for CoreIrErrors {}
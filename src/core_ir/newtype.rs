@@ -0,0 +1,130 @@
+// パス: src/core_ir/newtype.rs
+// 役割: newtype の型情報を Core IR 上で包んだ型へ置き換える
+// 意図: newtype をネイティブ表現で包んだ値そのものとして扱い、箱詰めを不要にする
+// 関連ファイル: src/core_ir/lower.rs, src/core_ir/mod.rs, src/codegen/cranelift.rs
+use std::collections::HashMap;
+
+use super::lower::type_expr_to_value_ty;
+use super::{Expr, Module, ValueTy};
+use crate::ast as A;
+
+/// モジュール内の `ValueTy::Data { constructor: <newtype> }` をフィールド型へ展開する。
+///
+/// コンストラクタ適用やパターンの除去はバックエンド側で行い、ここでは型のみを書き換える。
+pub(super) fn erase_newtype_types(module: &mut Module) {
+    let newtypes: HashMap<String, (Vec<String>, A::TypeExpr)> = module
+        .data_layouts
+        .values()
+        .filter(|layout| layout.newtype)
+        .filter_map(|layout| {
+            let field = layout.constructors.first()?.field_types.first()?.clone();
+            Some((layout.name.clone(), (layout.type_params.clone(), field)))
+        })
+        .collect();
+    if newtypes.is_empty() {
+        return;
+    }
+    let eraser = Eraser { newtypes };
+    for func in module.functions.values_mut() {
+        for param in &mut func.params {
+            eraser.ty(&mut param.ty);
+        }
+        eraser.ty(&mut func.result);
+        eraser.expr(&mut func.body);
+    }
+}
+
+struct Eraser {
+    newtypes: HashMap<String, (Vec<String>, A::TypeExpr)>,
+}
+
+impl Eraser {
+    fn ty(&self, ty: &mut ValueTy) {
+        match ty {
+            ValueTy::Tuple(items) => items.iter_mut().for_each(|item| self.ty(item)),
+            ValueTy::List(item) | ValueTy::Array(item) => self.ty(item),
+            ValueTy::Function { params, result } => {
+                params.iter_mut().for_each(|param| self.ty(param));
+                self.ty(result);
+            }
+            ValueTy::Data { constructor, args } => {
+                args.iter_mut().for_each(|arg| self.ty(arg));
+                if let Some((params, field)) = self.newtypes.get(constructor.as_str()) {
+                    let subst: HashMap<String, ValueTy> =
+                        params.iter().cloned().zip(args.iter().cloned()).collect();
+                    let mut erased = type_expr_to_value_ty(field, &subst);
+                    self.ty(&mut erased);
+                    *ty = erased;
+                }
+            }
+            ValueTy::Int
+            | ValueTy::Double
+            | ValueTy::Bool
+            | ValueTy::Char
+            | ValueTy::String
+            | ValueTy::Unit
+            | ValueTy::Dictionary { .. }
+            | ValueTy::Unknown => {}
+        }
+    }
+
+    fn expr(&self, expr: &mut Expr) {
+        match expr {
+            Expr::Literal { ty, .. }
+            | Expr::Var { ty, .. }
+            | Expr::DictionaryPlaceholder { ty, .. } => self.ty(ty),
+            Expr::Let { bindings, body, ty } => {
+                for binding in bindings {
+                    self.ty(&mut binding.ty);
+                    self.expr(&mut binding.value);
+                }
+                self.expr(body);
+                self.ty(ty);
+            }
+            Expr::Lambda { params, body, ty } => {
+                params.iter_mut().for_each(|param| self.ty(&mut param.ty));
+                self.expr(body);
+                self.ty(ty);
+            }
+            Expr::Apply { func, args, ty } => {
+                self.expr(func);
+                args.iter_mut().for_each(|arg| self.expr(arg));
+                self.ty(ty);
+            }
+            Expr::If {
+                cond,
+                then_branch,
+                else_branch,
+                ty,
+            } => {
+                self.expr(cond);
+                self.expr(then_branch);
+                self.expr(else_branch);
+                self.ty(ty);
+            }
+            Expr::PrimOp { args, ty, .. }
+            | Expr::Tuple { items: args, ty }
+            | Expr::List { items: args, ty } => {
+                args.iter_mut().for_each(|arg| self.expr(arg));
+                self.ty(ty);
+            }
+            Expr::Match {
+                scrutinee,
+                arms,
+                ty,
+            } => {
+                self.expr(scrutinee);
+                for arm in arms {
+                    for binding in &mut arm.bindings {
+                        self.ty(&mut binding.ty);
+                    }
+                    if let Some(guard) = &mut arm.guard {
+                        self.expr(guard);
+                    }
+                    self.expr(&mut arm.body);
+                }
+                self.ty(ty);
+            }
+        }
+    }
+}
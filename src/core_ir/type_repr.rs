@@ -0,0 +1,164 @@
+// パス: src/core_ir/type_repr.rs
+// 役割: 辞書を識別する `type_repr` 文字列の正規形を一か所で定める
+// 意図: 辞書を作る側（Core IR 生成）と引く側（コード生成）が同じ表記を使い、食い違いで辞書を見失わないようにする
+// 関連ファイル: src/core_ir/lower.rs, src/codegen/cranelift.rs, src/core_ir/mod.rs
+//! 辞書キー `type_repr` の正規形
+//!
+//! - 型コンストラクタは名前そのまま。ただし `Integer` は `Int`、`String` は `[Char]` に揃える。
+//! - リストは `[T]`、タプルは `(A, B)`。
+//! - 型適用は `Con A B` とし、引数自身が型適用か関数なら括弧で包む（`Maybe (Maybe Int)`）。
+//! - 関数型は `A -> B`、型変数は `t{id}`。
+//!
+//! 大文字小文字を含め、文字列として完全一致したときだけ同じ辞書とみなす。
+
+use crate::core_ir::ValueTy;
+use crate::typesys::{TApp, TCon, TFun, TTuple, Type};
+
+/// 型推論結果の型を正規形で表す。
+pub fn from_type(ty: &Type) -> String {
+    match ty {
+        Type::TVar(tv) => format!("t{}", tv.id),
+        Type::TCon(TCon { name }) => canonical_con(name).to_string(),
+        Type::TApp(TApp { func, arg }) => match func.as_ref() {
+            Type::TCon(TCon { name }) if name == "[]" => format!("[{}]", from_type(arg)),
+            _ => {
+                let arg_str = match arg.as_ref() {
+                    Type::TFun(_) => format!("({})", from_type(arg)),
+                    Type::TApp(TApp { func, .. }) if !is_list_con(func) => {
+                        format!("({})", from_type(arg))
+                    }
+                    _ => from_type(arg),
+                };
+                format!("{} {}", from_type(func), arg_str)
+            }
+        },
+        Type::TFun(TFun { arg, ret }) => {
+            let arg_str = match arg.as_ref() {
+                Type::TFun(_) => format!("({})", from_type(arg)),
+                _ => from_type(arg),
+            };
+            format!("{} -> {}", arg_str, from_type(ret))
+        }
+        Type::TTuple(TTuple { items }) => {
+            let inner = items.iter().map(from_type).collect::<Vec<_>>().join(", ");
+            format!("({inner})")
+        }
+    }
+}
+
+/// Core IR の値の型を正規形で表す。関数・辞書・未確定の型を含む場合は `None`。
+pub fn from_value_ty(ty: &ValueTy) -> Option<String> {
+    match ty {
+        ValueTy::Int => Some("Int".to_string()),
+        ValueTy::Double => Some("Double".to_string()),
+        ValueTy::Bool => Some("Bool".to_string()),
+        ValueTy::Char => Some("Char".to_string()),
+        ValueTy::String => Some("[Char]".to_string()),
+        ValueTy::Unit => Some("Unit".to_string()),
+        ValueTy::List(item) => Some(format!("[{}]", from_value_ty(item)?)),
+        ValueTy::Array(item) => {
+            let item_str = from_value_ty(item)?;
+            match item.as_ref() {
+                ValueTy::Data { args, .. } if !args.is_empty() => {
+                    Some(format!("Array ({item_str})"))
+                }
+                ValueTy::Array(_) => Some(format!("Array ({item_str})")),
+                _ => Some(format!("Array {item_str}")),
+            }
+        }
+        ValueTy::Tuple(items) => {
+            let items = items
+                .iter()
+                .map(from_value_ty)
+                .collect::<Option<Vec<_>>>()?;
+            Some(format!("({})", items.join(", ")))
+        }
+        ValueTy::Data { constructor, args } => {
+            let mut repr = canonical_con(constructor).to_string();
+            for arg in args {
+                let arg_str = from_value_ty(arg)?;
+                match arg {
+                    ValueTy::Data { args, .. } if !args.is_empty() => {
+                        repr.push_str(&format!(" ({arg_str})"));
+                    }
+                    _ => {
+                        repr.push(' ');
+                        repr.push_str(&arg_str);
+                    }
+                }
+            }
+            Some(repr)
+        }
+        ValueTy::Function { .. } | ValueTy::Dictionary { .. } | ValueTy::Unknown => None,
+    }
+}
+
+/// 別名を持つ組み込み型を正規の名前へ揃える。
+fn canonical_con(name: &str) -> &str {
+    match name {
+        "Integer" => "Int",
+        "String" => "[Char]",
+        other => other,
+    }
+}
+
+fn is_list_con(ty: &Type) -> bool {
+    matches!(ty, Type::TCon(TCon { name }) if name == "[]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_type, from_value_ty};
+    use crate::core_ir::lower::convert_type;
+    use crate::typesys::{TApp, TCon, TTuple, TVar, Type};
+
+    fn con(name: &str) -> Type {
+        Type::TCon(TCon { name: name.into() })
+    }
+
+    fn app(func: Type, arg: Type) -> Type {
+        Type::TApp(TApp {
+            func: Box::new(func),
+            arg: Box::new(arg),
+        })
+    }
+
+    #[test]
+    fn type_and_value_reprs_agree() {
+        let cases = [
+            (con("Int"), "Int"),
+            (con("Integer"), "Int"),
+            (con("String"), "[Char]"),
+            (app(con("[]"), con("Char")), "[Char]"),
+            (app(con("[]"), con("Int")), "[Int]"),
+            (con("Slot"), "Slot"),
+            (
+                app(app(con("Pair"), con("Int")), con("Bool")),
+                "Pair Int Bool",
+            ),
+            (
+                app(con("Maybe"), app(con("Maybe"), con("Int"))),
+                "Maybe (Maybe Int)",
+            ),
+            (app(con("Maybe"), app(con("[]"), con("Int"))), "Maybe [Int]"),
+            (
+                Type::TTuple(TTuple {
+                    items: vec![con("Int"), app(con("[]"), con("Bool"))],
+                }),
+                "(Int, [Bool])",
+            ),
+        ];
+        for (ty, expected) in cases {
+            assert_eq!(from_type(&ty), expected);
+            let value_ty = convert_type(&ty).expect("convert type");
+            assert_eq!(from_value_ty(&value_ty).as_deref(), Some(expected));
+        }
+    }
+
+    #[test]
+    fn type_variables_have_no_value_repr() {
+        let ty = app(con("[]"), Type::TVar(TVar { id: 3 }));
+        assert_eq!(from_type(&ty), "[t3]");
+        assert_eq!(from_value_ty(&convert_type(&ty).unwrap()), None);
+    }
+}
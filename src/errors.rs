@@ -267,6 +267,17 @@ impl EvalError {
     ) -> Self {
         Self(Box::new(ErrorInfo::at(code, msg, pos, line, col)))
     }
+    /// 失敗した式（最も内側で位置を持つもの）のスパンを返す。位置が無ければ `None`。
+    pub fn span(&self) -> Option<crate::ast::Span> {
+        match (self.0.line, self.0.col) {
+            (Some(line), Some(col)) => Some(crate::ast::Span::new(
+                self.0.pos.unwrap_or_default(),
+                line,
+                col,
+            )),
+            _ => None,
+        }
+    }
 }
 
 /// `Display` 実装を `ErrorInfo` へ委譲する。
@@ -287,6 +298,39 @@ impl Display for ParseError {
 }
 impl StdError for ParseError {}
 
+#[derive(Debug, Clone)]
+/// プログラム全体の構文解析で検出した複数のエラー。常に 1 件以上を保持する。
+pub struct ParseErrors(pub Vec<ParseError>);
+
+impl ParseErrors {
+    /// 最初に検出したエラー。
+    pub fn first(&self) -> &ParseError {
+        self.0
+            .first()
+            .expect("ParseErrors は少なくとも 1 件のエラーを保持する")
+    }
+}
+
+impl From<ParseError> for ParseErrors {
+    fn from(err: ParseError) -> Self {
+        Self(vec![err])
+    }
+}
+
+/// 1 行に 1 件ずつ `ParseError` の表示を並べる。
+impl Display for ParseErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (idx, err) in self.0.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            Display::fmt(err, f)?;
+        }
+        Ok(())
+    }
+}
+impl StdError for ParseErrors {}
+
 /// `TypeError` の表示実装を `ErrorInfo` に委譲する。
 impl Display for TypeError {
     /// `ErrorInfo` をそのまま書式化する。
@@ -7,23 +7,54 @@
 //! - 正格評価戦略で式を還元し、副作用なしの実装に保つ。
 //! - プリミティブ演算は部分適用可能な値として登録し、REPL 操作を簡潔にする。
 //! - べき乗や比較など一部演算子は直感的な型へフォールバックする設計を採用する。
+//! - let グループは関数束縛を先にまとめて登録し、値束縛は依存順に評価するため、相互再帰や後方参照を書ける。
+
+use std::collections::{HashMap, HashSet};
 
 use crate::ast as A;
 use crate::errors::{EvalError, FrameInfo};
-use crate::primitives::PRIMITIVES;
+use crate::lint::free_names;
+use crate::primitives::{JUST, NOTHING, PRIMITIVES};
+use crate::runtime::{
+    concat_op, fmap_value, fold_left_value, logic_operand, make_data_ctor, PrimOp,
+};
 pub use crate::runtime::{Env, Value};
 
+/// 組み込みプリミティブの実装を束縛した値環境を生成する。
+///
+/// 型推論用の環境は `infer::initial_env` が別に生成する。クレート直下では
+/// それぞれ `initial_value_env` / `initial_type_env` として再エクスポートしている。
 pub fn initial_env() -> Env {
     let env = Env::new();
     for def in PRIMITIVES {
         env.insert(def.name, def.op.to_value());
     }
+    env.insert(NOTHING, make_data_ctor(NOTHING, 0));
+    env.insert(JUST, make_data_ctor(JUST, 1));
     env
 }
 
 /// 値を関数として扱い、引数を適用して評価するヘルパ。
-fn apply(f: &Value, x: Value) -> Result<Value, EvalError> {
+fn apply(f: &Value, x: Value, budget: &mut StepBudget) -> Result<Value, EvalError> {
     match f {
+        Value::Prim(PrimOp::Fmap {
+            func: Some(func),
+            concat,
+        }) => {
+            let mapped = fmap_value(x, |item| apply(func, item, budget))?;
+            if *concat {
+                concat_op(mapped)
+            } else {
+                Ok(mapped)
+            }
+        }
+        Value::Prim(PrimOp::FoldLeft {
+            func: Some(func),
+            init: Some(init),
+        }) => fold_left_value((**init).clone(), x, |acc, item| {
+            let partial = apply(func, acc, budget)?;
+            apply(&partial, item, budget)
+        }),
         Value::Prim(op) => op.clone().apply(x),
         Value::Closure { params, body, env } => {
             if params.is_empty() {
@@ -33,7 +64,7 @@ fn apply(f: &Value, x: Value) -> Result<Value, EvalError> {
             let env2 = env.child();
             env2.insert(params[0].clone(), x);
             if params.len() == 1 {
-                eval_expr(body, &env2)
+                eval_in(body, &env2, budget)
             } else {
                 Ok(Value::Closure {
                     params: params[1..].to_vec(),
@@ -52,13 +83,54 @@ fn apply(f: &Value, x: Value) -> Result<Value, EvalError> {
 
 /// 抽象構文木の式を評価して `Value` へ還元するメインルーチン。
 pub fn eval_expr(e: &A::Expr, env: &Env) -> Result<Value, EvalError> {
-    eval_expr_inner(e, env).map_err(|mut err| {
-        attach_frame(&mut err, e);
-        err
-    })
+    eval_expr_with_limit(e, env, None)
+}
+
+/// 評価ステップ数に上限を設けて式を評価する。
+///
+/// 式の還元 1 回を 1 ステップと数え、`limit` を超えた時点で `EVAL100` を返す。
+/// `None` を渡した場合は `eval_expr` と同じく無制限に評価する。
+pub fn eval_expr_with_limit(
+    e: &A::Expr,
+    env: &Env,
+    limit: Option<u64>,
+) -> Result<Value, EvalError> {
+    let mut budget = StepBudget { used: 0, limit };
+    eval_in(e, env, &mut budget)
+}
+
+/// 評価中に消費したステップ数と上限を保持する。
+struct StepBudget {
+    used: u64,
+    limit: Option<u64>,
+}
+
+impl StepBudget {
+    /// ステップを 1 つ消費し、上限を超えたらエラーを返す。
+    fn tick(&mut self) -> Result<(), EvalError> {
+        self.used += 1;
+        match self.limit {
+            Some(limit) if self.used > limit => Err(EvalError::new(
+                "EVAL100",
+                format!("評価ステップ数の上限 ({limit}) を超えました"),
+                None,
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn eval_in(e: &A::Expr, env: &Env, budget: &mut StepBudget) -> Result<Value, EvalError> {
+    budget
+        .tick()
+        .and_then(|_| eval_expr_inner(e, env, budget))
+        .map_err(|mut err| {
+            attach_frame(&mut err, e);
+            err
+        })
 }
 
-fn eval_expr_inner(e: &A::Expr, env: &Env) -> Result<Value, EvalError> {
+fn eval_expr_inner(e: &A::Expr, env: &Env, budget: &mut StepBudget) -> Result<Value, EvalError> {
     use A::Expr::*;
     match e {
         Var { name, .. } => env
@@ -69,26 +141,26 @@ fn eval_expr_inner(e: &A::Expr, env: &Env) -> Result<Value, EvalError> {
         CharLit { value, .. } => Ok(Value::Char(*value)),
         StringLit { value, .. } => Ok(Value::String(value.clone())),
         BoolLit { value, .. } => Ok(Value::Bool(*value)),
-        ListLit { items, .. } => Ok(Value::List(eval_literal_items(items, env)?)),
-        TupleLit { items, .. } => Ok(Value::Tuple(eval_literal_items(items, env)?)),
+        ListLit { items, .. } => Ok(Value::List(eval_literal_items(items, env, budget)?)),
+        TupleLit { items, .. } => Ok(Value::Tuple(eval_literal_items(items, env, budget)?)),
         Lambda { params, body, .. } => Ok(Value::Closure {
             params: params.clone(),
             body: body.clone(),
             env: env.capture(),
         }),
-        LetIn { bindings, body, .. } => eval_let_in(bindings, body, env),
+        LetIn { bindings, body, .. } => eval_let_in(bindings, body, env, budget),
         If {
             cond,
             then_branch,
             else_branch,
             ..
         } => {
-            let c = eval_expr(cond, env)?;
+            let c = eval_in(cond, env, budget)?;
             if let Value::Bool(b) = c {
                 if b {
-                    eval_expr(then_branch, env)
+                    eval_in(then_branch, env, budget)
                 } else {
-                    eval_expr(else_branch, env)
+                    eval_in(else_branch, env, budget)
                 }
             } else {
                 Err(EvalError::new(
@@ -100,35 +172,122 @@ fn eval_expr_inner(e: &A::Expr, env: &Env) -> Result<Value, EvalError> {
         }
         Case {
             scrutinee, arms, ..
-        } => eval_case(scrutinee, arms, env),
+        } => eval_case(scrutinee, arms, env, budget),
         App { func, arg, .. } => {
-            let f = eval_expr(func, env)?;
-            let x = eval_expr(arg, env)?;
-            apply(&f, x)
+            if let App {
+                func: catch,
+                arg: protected,
+                ..
+            } = func.as_ref()
+            {
+                if is_builtin_catch(catch, env) {
+                    return eval_catch(protected, arg, env, budget);
+                }
+                if let Some(decisive) = builtin_short_circuit(catch, env) {
+                    return eval_short_circuit(decisive, protected, arg, env, budget);
+                }
+            }
+            let f = eval_in(func, env, budget)?;
+            let x = eval_in(arg, env, budget)?;
+            apply(&f, x, budget)
         }
         BinOp {
             op, left, right, ..
         } => {
-            let f = eval_expr(
+            if is_catch_value(env.get(op)) {
+                return eval_catch(left, right, env, budget);
+            }
+            if let Some(decisive) = short_circuit_value(env.get(op)) {
+                return eval_short_circuit(decisive, left, right, env, budget);
+            }
+            let f = eval_in(
                 &A::Expr::Var {
                     name: op.clone(),
                     span: A::Span::dummy(),
                 },
                 env,
+                budget,
             )?;
-            let l = eval_expr(left, env)?;
-            let r = eval_expr(right, env)?;
-            let tmp = apply(&f, l)?;
-            apply(&tmp, r)
+            let l = eval_in(left, env, budget)?;
+            let r = eval_in(right, env, budget)?;
+            let tmp = apply(&f, l, budget)?;
+            apply(&tmp, r, budget)
+        }
+        Annot { expr, .. } => eval_in(expr, env, budget),
+    }
+}
+
+/// `catch` を指す変数か。利用者が同名の束縛で覆い隠している場合は偽。
+fn is_builtin_catch(expr: &A::Expr, env: &Env) -> bool {
+    matches!(expr, A::Expr::Var { name, .. } if is_catch_value(env.get(name)))
+}
+
+fn is_catch_value(value: Option<Value>) -> bool {
+    matches!(value, Some(Value::Prim(PrimOp::Catch { protected: None })))
+}
+
+/// `catch protected handler` を評価する。
+///
+/// `protected` の評価が失敗したら、エラーを `[CODE] message` 形式の文字列にして `handler` へ渡す。
+/// 評価ステップ数の上限超過 (`EVAL100`) は回復できないため捕捉しない。
+fn eval_catch(
+    protected: &A::Expr,
+    handler: &A::Expr,
+    env: &Env,
+    budget: &mut StepBudget,
+) -> Result<Value, EvalError> {
+    match eval_in(protected, env, budget) {
+        Err(err) if err.0.code != "EVAL100" => {
+            let message = format!("[{}] {}", err.0.code, err.0.msg);
+            let handler = eval_in(handler, env, budget)?;
+            apply(&handler, Value::String(message), budget)
         }
-        Annot { expr, .. } => eval_expr(expr, env),
+        result => result,
+    }
+}
+
+/// `&&` / `||` を指す変数なら、左辺だけで結果が決まる値を返す。同名の束縛で覆い隠されていれば `None`。
+fn builtin_short_circuit(expr: &A::Expr, env: &Env) -> Option<bool> {
+    match expr {
+        A::Expr::Var { name, .. } => short_circuit_value(env.get(name)),
+        _ => None,
+    }
+}
+
+fn short_circuit_value(value: Option<Value>) -> Option<bool> {
+    match value {
+        Some(Value::Prim(PrimOp::ShortCircuit {
+            decisive,
+            lhs: None,
+        })) => Some(decisive),
+        _ => None,
     }
 }
 
-fn eval_literal_items(items: &[A::Expr], env: &Env) -> Result<Vec<Value>, EvalError> {
+/// `left && right` / `left || right` を評価する。左辺が `decisive` なら右辺は評価しない。
+fn eval_short_circuit(
+    decisive: bool,
+    left: &A::Expr,
+    right: &A::Expr,
+    env: &Env,
+    budget: &mut StepBudget,
+) -> Result<Value, EvalError> {
+    let lhs = logic_operand(&eval_in(left, env, budget)?)?;
+    if lhs == decisive {
+        return Ok(Value::Bool(lhs));
+    }
+    let rhs = eval_in(right, env, budget)?;
+    Ok(Value::Bool(logic_operand(&rhs)?))
+}
+
+fn eval_literal_items(
+    items: &[A::Expr],
+    env: &Env,
+    budget: &mut StepBudget,
+) -> Result<Vec<Value>, EvalError> {
     let mut values = Vec::with_capacity(items.len());
     for item in items {
-        values.push(eval_expr(item, env)?);
+        values.push(eval_in(item, env, budget)?);
     }
     Ok(values)
 }
@@ -137,7 +296,9 @@ fn eval_let_in(
     bindings: &[(String, Vec<String>, A::Expr)],
     body: &A::Expr,
     env: &Env,
+    budget: &mut StepBudget,
 ) -> Result<Value, EvalError> {
+    // 関数束縛は共有の環境を捕捉するクロージャとして先に登録し、グループ内で相互に参照できるようにする。
     let local_env = env.child();
     for (name, params, rhs) in bindings.iter().filter(|(_, params, _)| !params.is_empty()) {
         let closure = Value::Closure {
@@ -147,19 +308,92 @@ fn eval_let_in(
         };
         local_env.insert(name.clone(), closure);
     }
-    for (name, params, rhs) in bindings {
-        if !params.is_empty() {
-            // 関数束縛は初回ループで確定済みなのでスキップする。
-            continue;
-        }
-        let val = eval_expr(rhs, &local_env)?;
+    for index in value_binding_order(bindings) {
+        let (name, _, rhs) = &bindings[index];
+        let val = eval_in(rhs, &local_env, budget)?;
         local_env.insert(name.clone(), val);
     }
-    eval_expr(body, &local_env)
+    eval_in(body, &local_env, budget)
 }
 
-fn eval_case(scrutinee: &A::Expr, arms: &[A::CaseArm], env: &Env) -> Result<Value, EvalError> {
-    let value = eval_expr(scrutinee, env)?;
+/// let グループの値束縛（引数なし）を評価順に並べる。
+///
+/// 値束縛は、参照する同じグループの値束縛（関数束縛の本体を経由するものを含む）より後に評価する。
+/// 依存が循環する場合は元の並び順のまま評価し、未束縛の参照として報告させる。
+fn value_binding_order(bindings: &[(String, Vec<String>, A::Expr)]) -> Vec<usize> {
+    let index_of: HashMap<&str, usize> = bindings
+        .iter()
+        .enumerate()
+        .map(|(index, (name, _, _))| (name.as_str(), index))
+        .collect();
+    let refs: Vec<Vec<usize>> = bindings
+        .iter()
+        .enumerate()
+        .map(|(index, (_, params, rhs))| {
+            let mut deps: Vec<usize> = free_names(params, rhs)
+                .iter()
+                .filter_map(|name| index_of.get(name.as_str()).copied())
+                .filter(|&dep| dep != index)
+                .collect();
+            deps.sort_unstable();
+            deps
+        })
+        .collect();
+    let is_value = |index: usize| bindings[index].1.is_empty();
+
+    // 値束縛から、関数束縛だけを辿って到達できる値束縛を依存とみなす。
+    let value_deps: Vec<Vec<usize>> = (0..bindings.len())
+        .map(|start| {
+            let mut deps = Vec::new();
+            let mut seen = HashSet::from([start]);
+            let mut stack = refs[start].clone();
+            while let Some(next) = stack.pop() {
+                if !seen.insert(next) {
+                    continue;
+                }
+                if is_value(next) {
+                    deps.push(next);
+                } else {
+                    stack.extend(refs[next].iter().copied());
+                }
+            }
+            deps.sort_unstable();
+            deps
+        })
+        .collect();
+
+    let mut visited = vec![false; bindings.len()];
+    let mut order = Vec::new();
+    for index in (0..bindings.len()).filter(|&index| is_value(index)) {
+        visit_value_binding(index, &value_deps, &mut visited, &mut order);
+    }
+    order
+}
+
+/// 依存先を先に並べる深さ優先探索。訪問済みの束縛へ戻る辺（循環）は無視する。
+fn visit_value_binding(
+    index: usize,
+    value_deps: &[Vec<usize>],
+    visited: &mut [bool],
+    order: &mut Vec<usize>,
+) {
+    if visited[index] {
+        return;
+    }
+    visited[index] = true;
+    for &dep in &value_deps[index] {
+        visit_value_binding(dep, value_deps, visited, order);
+    }
+    order.push(index);
+}
+
+fn eval_case(
+    scrutinee: &A::Expr,
+    arms: &[A::CaseArm],
+    env: &Env,
+    budget: &mut StepBudget,
+) -> Result<Value, EvalError> {
+    let value = eval_in(scrutinee, env, budget)?;
     for arm in arms {
         if let Ok(bindings) = match_pattern(&arm.pattern, &value) {
             let env_branch = env.child();
@@ -167,7 +401,7 @@ fn eval_case(scrutinee: &A::Expr, arms: &[A::CaseArm], env: &Env) -> Result<Valu
                 env_branch.insert(name, val);
             }
             if let Some(guard) = &arm.guard {
-                let guard_val = eval_expr(guard, &env_branch)?;
+                let guard_val = eval_in(guard, &env_branch, budget)?;
                 match guard_val {
                     Value::Bool(true) => {}
                     Value::Bool(false) => continue,
@@ -180,7 +414,7 @@ fn eval_case(scrutinee: &A::Expr, arms: &[A::CaseArm], env: &Env) -> Result<Valu
                     }
                 }
             }
-            return eval_expr(&arm.body, &env_branch);
+            return eval_in(&arm.body, &env_branch, budget);
         }
     }
     Err(EvalError::new(
@@ -190,11 +424,15 @@ fn eval_case(scrutinee: &A::Expr, arms: &[A::CaseArm], env: &Env) -> Result<Valu
     ))
 }
 
+/// 失敗した式の位置を最初の 1 回だけ埋め、スタックトレースへフレームを積む。
 fn attach_frame(err: &mut EvalError, expr: &A::Expr) {
     let span = expr.span();
-    let pos = nonzero(span.pos);
-    let line = nonzero(span.line);
-    let col = nonzero(span.col);
+    // 行が 0 のものはダミー。先頭の式は pos=0 でも有効な位置として扱う。
+    let (pos, line, col) = if span.line > 0 {
+        (Some(span.pos), Some(span.line), nonzero(span.col))
+    } else {
+        (None, None, None)
+    };
     let info = err.0.as_mut();
     info.fill_position_if_absent(pos, line, col);
     let summary = format!("{}", expr);
@@ -278,10 +516,20 @@ fn match_pattern_inner(
             Value::Char(v) if v == expected => Ok(()),
             _ => Err(PatternMatchError),
         },
+        // 文字列は `Value::String` のほか、文字のリストとしても現れる（`['h', 'i']` や `<>` の結果など）。
         A::Pattern::String {
             value: expected, ..
         } => match value {
             Value::String(v) if v == expected => Ok(()),
+            Value::List(items)
+                if items.len() == expected.chars().count()
+                    && items
+                        .iter()
+                        .zip(expected.chars())
+                        .all(|(item, c)| matches!(item, Value::Char(v) if *v == c)) =>
+            {
+                Ok(())
+            }
             _ => Err(PatternMatchError),
         },
         A::Pattern::Bool {
@@ -292,6 +540,10 @@ fn match_pattern_inner(
         },
         A::Pattern::List { items, .. } => match value {
             Value::List(values) => match_sequence(items, values, bindings),
+            Value::String(text) => {
+                let chars: Vec<Value> = text.chars().map(Value::Char).collect();
+                match_sequence(items, &chars, bindings)
+            }
             _ => Err(PatternMatchError),
         },
         A::Pattern::Tuple { items, .. } => match value {
@@ -321,6 +573,13 @@ mod tests {
     use super::*;
     use crate::ast::{Expr, IntBase, Span};
 
+    fn unlimited() -> StepBudget {
+        StepBudget {
+            used: 0,
+            limit: None,
+        }
+    }
+
     #[test]
     fn apply_rejects_closure_without_params() {
         let env = Env::new();
@@ -333,7 +592,8 @@ mod tests {
             }),
             env: env.capture(),
         };
-        let err = super::apply(&closure, Value::Int(1)).expect_err("missing params must error");
+        let err = super::apply(&closure, Value::Int(1), &mut unlimited())
+            .expect_err("missing params must error");
         assert_eq!(err.0.code, "EVAL090");
     }
 
@@ -348,7 +608,8 @@ mod tests {
             }),
             env: env.capture(),
         };
-        let result = super::apply(&closure, Value::Int(1)).expect("partial application succeeds");
+        let result = super::apply(&closure, Value::Int(1), &mut unlimited())
+            .expect("partial application succeeds");
         match result {
             Value::Closure { params, .. } => assert_eq!(params, vec!["y".to_string()]),
             other => panic!("expected closure back, got {:?}", other),
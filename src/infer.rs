@@ -12,7 +12,7 @@ use std::collections::{HashMap, HashSet};
 
 use crate::ast as A;
 use crate::errors::TypeError;
-use crate::primitives::{PrimitiveTypeSpec, PRIMITIVES};
+use crate::primitives::{PrimitiveTypeSpec, JUST, NOTHING, PRIMITIVES};
 use crate::typesys::*;
 
 #[derive(Clone, Debug)]
@@ -36,6 +36,9 @@ pub fn initial_class_env() -> ClassEnv {
     ce.add_class("Show", std::iter::empty::<&str>());
     ce.add_class("Num", std::iter::empty::<&str>());
     ce.add_class("Fractional", ["Num"]);
+    ce.add_class("Functor", std::iter::empty::<&str>());
+    ce.add_class("Semigroup", std::iter::empty::<&str>());
+    ce.add_class("Monoid", ["Semigroup"]);
     // 代表的なインスタンスを登録
     for ty in ["Int", "Integer", "Double", "Char", "Bool"] {
         ce.add_instance("Eq", ty);
@@ -46,6 +49,13 @@ pub fn initial_class_env() -> ClassEnv {
         ce.add_instance("Num", ty);
     }
     ce.add_instance("Fractional", "Double");
+    // `Maybe` は組み込み（または同じ形で宣言し直したもの）、`Either` は利用者が `data` で宣言した標準的な形のものを対象とする。
+    for tycon in ["[]", "Maybe", "Either"] {
+        ce.add_instance("Functor", tycon);
+    }
+    // String = [Char] も `[]` のインスタンスに含まれる。
+    ce.add_instance("Semigroup", "[]");
+    ce.add_instance("Monoid", "[]");
     // String = [Char]
     ce.add_instance("Eq", "[Char]");
     ce.add_instance("Ord", "[Char]");
@@ -54,6 +64,9 @@ pub fn initial_class_env() -> ClassEnv {
 }
 
 /// 演算子などの既定スキームを備えた型環境を生成する。
+///
+/// 評価用の値環境は `evaluator::initial_env` が別に生成する。クレート直下では
+/// それぞれ `initial_type_env` / `initial_value_env` として再エクスポートしている。
 pub fn initial_env() -> TypeEnv {
     let mut env = TypeEnv::new();
     let mut supply = TVarSupply::new();
@@ -63,17 +76,127 @@ pub fn initial_env() -> TypeEnv {
             PrimitiveTypeSpec::BinOp { classname } => {
                 env.extend(def.name, binop_scheme(classname, &mut supply));
             }
+            PrimitiveTypeSpec::Negate => env.extend(def.name, negate_scheme(&mut supply)),
             PrimitiveTypeSpec::IntPow => {
                 env.extend(def.name, intpow_scheme(&mut supply));
             }
             PrimitiveTypeSpec::Pred { classname } => {
                 env.extend(def.name, pred_scheme(classname, &mut supply));
             }
+            PrimitiveTypeSpec::OrdSelect => env.extend(def.name, ord_select_scheme(&mut supply)),
+            PrimitiveTypeSpec::Clamp => env.extend(def.name, clamp_scheme(&mut supply)),
+            PrimitiveTypeSpec::Between => env.extend(def.name, between_scheme(&mut supply)),
+            PrimitiveTypeSpec::BoolLogic => env.extend(def.name, bool_logic_scheme()),
             PrimitiveTypeSpec::Show => env.extend(def.name, show_scheme(&mut supply)),
             PrimitiveTypeSpec::IntBin => env.extend(def.name, int_bin_scheme()),
+            PrimitiveTypeSpec::Bits => env.extend(def.name, bits_scheme()),
+            PrimitiveTypeSpec::Floating => env.extend(def.name, floating_scheme()),
+            PrimitiveTypeSpec::Rounding => env.extend(def.name, conversion_scheme("Double", "Int")),
+            PrimitiveTypeSpec::FromIntegral => {
+                env.extend(def.name, conversion_scheme("Int", "Double"))
+            }
+            PrimitiveTypeSpec::ListPred => env.extend(def.name, list_pred_scheme(&mut supply)),
+            PrimitiveTypeSpec::ListLength => env.extend(def.name, list_length_scheme(&mut supply)),
+            PrimitiveTypeSpec::Replicate => env.extend(def.name, replicate_scheme(&mut supply)),
+            PrimitiveTypeSpec::ListSlice => env.extend(def.name, list_slice_scheme(&mut supply)),
+            PrimitiveTypeSpec::EnumFrom => {
+                env.extend(def.name, enum_from_marker_scheme(&mut supply))
+            }
+            PrimitiveTypeSpec::FoldLeft => env.extend(def.name, fold_left_scheme(&mut supply)),
+            PrimitiveTypeSpec::Fmap => env.extend(def.name, fmap_scheme(&mut supply)),
+            PrimitiveTypeSpec::Append => {
+                env.extend(def.name, single_class_scheme("Semigroup", 2, &mut supply))
+            }
+            PrimitiveTypeSpec::Mempty => {
+                env.extend(def.name, single_class_scheme("Monoid", 0, &mut supply))
+            }
+            PrimitiveTypeSpec::StripPrefix => {
+                env.extend(def.name, strip_prefix_scheme(&mut supply))
+            }
+            PrimitiveTypeSpec::Concat => env.extend(
+                def.name,
+                build_single_var_scheme(
+                    &mut supply,
+                    |a| vec![t_list(t_list(a.clone()))],
+                    |a| t_list(a.clone()),
+                    |_| vec![],
+                ),
+            ),
+            PrimitiveTypeSpec::ConcatMap => env.extend(def.name, concat_map_scheme(&mut supply)),
+            PrimitiveTypeSpec::Intercalate => env.extend(
+                def.name,
+                build_single_var_scheme(
+                    &mut supply,
+                    |a| vec![t_list(a.clone()), t_list(t_list(a.clone()))],
+                    |a| t_list(a.clone()),
+                    |_| vec![],
+                ),
+            ),
+            PrimitiveTypeSpec::ReadNumber { result } => env.extend(
+                def.name,
+                Scheme {
+                    vars: vec![],
+                    qual: qualify(
+                        build_fun_type(
+                            vec![t_string()],
+                            t_maybe(Type::TCon(TCon {
+                                name: result.into(),
+                            })),
+                        ),
+                        vec![],
+                    ),
+                },
+            ),
+            PrimitiveTypeSpec::JoinStrings => env.extend(
+                def.name,
+                Scheme {
+                    vars: vec![],
+                    qual: qualify(build_fun_type(vec![t_list(t_string())], t_string()), vec![]),
+                },
+            ),
+            PrimitiveTypeSpec::ArrayFromList => {
+                env.extend(def.name, array_from_list_scheme(&mut supply))
+            }
+            PrimitiveTypeSpec::ArrayIndex => env.extend(def.name, array_index_scheme(&mut supply)),
+            PrimitiveTypeSpec::NewRef => env.extend(def.name, new_ref_scheme(&mut supply)),
+            PrimitiveTypeSpec::ReadRef => env.extend(def.name, read_ref_scheme(&mut supply)),
+            PrimitiveTypeSpec::WriteRef => env.extend(def.name, write_ref_scheme(&mut supply)),
+            PrimitiveTypeSpec::EmptyMap => {
+                env.extend(def.name, map_scheme(&mut supply, |_, _| vec![], |m, _| m))
+            }
+            PrimitiveTypeSpec::MapInsert => env.extend(
+                def.name,
+                map_scheme(&mut supply, |k, v| vec![k, v], |m, _| m),
+            ),
+            PrimitiveTypeSpec::MapLookup => env.extend(
+                def.name,
+                map_scheme(&mut supply, |k, _| vec![k], |_, v| t_maybe(v)),
+            ),
+            PrimitiveTypeSpec::MapFindWithDefault => env.extend(
+                def.name,
+                map_scheme(&mut supply, |k, v| vec![v, k], |_, v| v),
+            ),
+            PrimitiveTypeSpec::Trace => env.extend(def.name, trace_scheme(&mut supply)),
+            PrimitiveTypeSpec::Catch => env.extend(def.name, catch_scheme(&mut supply)),
+            PrimitiveTypeSpec::Format => env.extend(def.name, format_marker_scheme(&mut supply)),
         }
     }
 
+    // 組み込みの `data Maybe a = Nothing | Just a` の構築子。
+    env.extend(
+        NOTHING,
+        build_single_var_scheme(&mut supply, |_| vec![], |a| t_maybe(a.clone()), |_| vec![]),
+    );
+    env.extend(
+        JUST,
+        build_single_var_scheme(
+            &mut supply,
+            |a| vec![a.clone()],
+            |a| t_maybe(a.clone()),
+            |_| vec![],
+        ),
+    );
+
     env
 }
 
@@ -128,6 +251,21 @@ fn binop_scheme(cls: &str, s: &mut TVarSupply) -> Scheme {
     )
 }
 
+/// 単項マイナスが脱糖される `negate :: Num a => a -> a` のスキームを構築する。
+fn negate_scheme(s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(
+        s,
+        |a| vec![a.clone()],
+        |a| a.clone(),
+        |a| {
+            vec![Constraint {
+                classname: "Num".into(),
+                r#type: a.clone(),
+            }]
+        },
+    )
+}
+
 /// 整数指数を扱う `(^)` 用のスキームを構築する。
 fn intpow_scheme(s: &mut TVarSupply) -> Scheme {
     build_single_var_scheme(
@@ -154,6 +292,52 @@ fn int_bin_scheme() -> Scheme {
     }
 }
 
+/// ビット演算 (`.&.` / `.|.` / `xor` / `shiftL` / `shiftR`) 用の `Int -> Int -> Int` を構築する。
+fn bits_scheme() -> Scheme {
+    let int_ty = Type::TCon(TCon { name: "Int".into() });
+    let ty = build_fun_type(vec![int_ty.clone(), int_ty.clone()], int_ty);
+    Scheme {
+        vars: vec![],
+        qual: qualify(ty, vec![]),
+    }
+}
+
+/// `&&` / `||` 用の `Bool -> Bool -> Bool` を構築する。
+fn bool_logic_scheme() -> Scheme {
+    let bool_ty = Type::TCon(TCon {
+        name: "Bool".into(),
+    });
+    let ty = build_fun_type(vec![bool_ty.clone(), bool_ty.clone()], bool_ty);
+    Scheme {
+        vars: vec![],
+        qual: qualify(ty, vec![]),
+    }
+}
+
+/// 数学関数 (`sqrt` / `sin` / `cos` / `exp` / `log`) 用の `Double -> Double` を構築する。
+fn floating_scheme() -> Scheme {
+    let double_ty = Type::TCon(TCon {
+        name: "Double".into(),
+    });
+    let ty = build_fun_type(vec![double_ty.clone()], double_ty);
+    Scheme {
+        vars: vec![],
+        qual: qualify(ty, vec![]),
+    }
+}
+
+/// 丸め (`floor` など) や `fromIntegral` 用の単相な変換関数スキームを構築する。
+fn conversion_scheme(from: &str, to: &str) -> Scheme {
+    let ty = build_fun_type(
+        vec![Type::TCon(TCon { name: from.into() })],
+        Type::TCon(TCon { name: to.into() }),
+    );
+    Scheme {
+        vars: vec![],
+        qual: qualify(ty, vec![]),
+    }
+}
+
 /// `Eq` / `Ord` 制約を持つ比較演算子スキームを構築する。
 fn pred_scheme(cls: &str, s: &mut TVarSupply) -> Scheme {
     let cls_name = cls.to_string();
@@ -174,6 +358,55 @@ fn pred_scheme(cls: &str, s: &mut TVarSupply) -> Scheme {
     )
 }
 
+/// `min` / `max :: Ord a => a -> a -> a` のスキームを構築する。
+fn ord_select_scheme(s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(
+        s,
+        |a| vec![a.clone(), a.clone()],
+        |a| a.clone(),
+        |a| {
+            vec![Constraint {
+                classname: "Ord".into(),
+                r#type: a.clone(),
+            }]
+        },
+    )
+}
+
+/// `clamp :: Ord a => a -> a -> a -> a` のスキームを構築する。
+fn clamp_scheme(s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(
+        s,
+        |a| vec![a.clone(), a.clone(), a.clone()],
+        |a| a.clone(),
+        |a| {
+            vec![Constraint {
+                classname: "Ord".into(),
+                r#type: a.clone(),
+            }]
+        },
+    )
+}
+
+/// `between :: Ord a => a -> a -> a -> Bool` のスキームを構築する。
+fn between_scheme(s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(
+        s,
+        |a| vec![a.clone(), a.clone(), a.clone()],
+        |_| {
+            Type::TCon(TCon {
+                name: "Bool".into(),
+            })
+        },
+        |a| {
+            vec![Constraint {
+                classname: "Ord".into(),
+                r#type: a.clone(),
+            }]
+        },
+    )
+}
+
 /// `show` プリミティブのスキームを構築する。
 fn show_scheme(s: &mut TVarSupply) -> Scheme {
     build_single_var_scheme(
@@ -189,6 +422,307 @@ fn show_scheme(s: &mut TVarSupply) -> Scheme {
     )
 }
 
+/// `null :: [a] -> Bool` のスキームを構築する。
+fn list_pred_scheme(s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(
+        s,
+        |a| vec![t_list(a.clone())],
+        |_| {
+            Type::TCon(TCon {
+                name: "Bool".into(),
+            })
+        },
+        |_| vec![],
+    )
+}
+
+/// `length :: [a] -> Int` のスキームを構築する。
+fn list_length_scheme(s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(
+        s,
+        |a| vec![t_list(a.clone())],
+        |_| Type::TCon(TCon { name: "Int".into() }),
+        |_| vec![],
+    )
+}
+
+/// `replicate :: Int -> a -> [a]` のスキームを構築する。
+fn replicate_scheme(s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(
+        s,
+        |a| vec![Type::TCon(TCon { name: "Int".into() }), a.clone()],
+        |a| t_list(a.clone()),
+        |_| vec![],
+    )
+}
+
+/// `take` / `drop :: Int -> [a] -> [a]` のスキームを構築する。
+fn list_slice_scheme(s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(
+        s,
+        |a| vec![Type::TCon(TCon { name: "Int".into() }), t_list(a.clone())],
+        |a| t_list(a.clone()),
+        |_| vec![],
+    )
+}
+
+/// `classname a => a -> ... -> a` (引数 `arity` 個) のスキームを構築する。`<>` と `mempty` で使う。
+fn single_class_scheme(classname: &str, arity: usize, s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(
+        s,
+        |a| vec![a.clone(); arity],
+        |a| a.clone(),
+        |a| {
+            vec![Constraint {
+                classname: classname.into(),
+                r#type: a.clone(),
+            }]
+        },
+    )
+}
+
+/// `stripPrefix :: Eq a => [a] -> [a] -> [[a]]` のスキームを構築する。
+fn strip_prefix_scheme(s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(
+        s,
+        |a| vec![t_list(a.clone()), t_list(a.clone())],
+        |a| t_maybe(t_list(a.clone())),
+        |a| {
+            vec![Constraint {
+                classname: "Eq".into(),
+                r#type: a.clone(),
+            }]
+        },
+    )
+}
+
+/// `fmap :: Functor f => (a -> b) -> f a -> f b` のスキームを構築する。
+///
+/// `f` は型引数を 1 つ取る型構築子の位置に現れる型変数で、`[]`・`Maybe`・`Either e` などに単一化される。
+fn fmap_scheme(s: &mut TVarSupply) -> Scheme {
+    let (f, a, b) = (s.fresh(), s.fresh(), s.fresh());
+    let functor = Type::TVar(f.clone());
+    let apply_f = |arg: &TVar| {
+        Type::TApp(TApp {
+            func: Box::new(functor.clone()),
+            arg: Box::new(Type::TVar(arg.clone())),
+        })
+    };
+    let ty = build_fun_type(
+        vec![
+            build_fun_type(vec![Type::TVar(a.clone())], Type::TVar(b.clone())),
+            apply_f(&a),
+        ],
+        apply_f(&b),
+    );
+    Scheme {
+        vars: vec![f.clone(), a, b],
+        qual: qualify(
+            ty,
+            vec![Constraint {
+                classname: "Functor".into(),
+                r#type: functor,
+            }],
+        ),
+    }
+}
+
+/// `concatMap :: (a -> [b]) -> [a] -> [b]` のスキームを構築する。
+fn concat_map_scheme(s: &mut TVarSupply) -> Scheme {
+    let (a, b) = (s.fresh(), s.fresh());
+    let (elem, result) = (Type::TVar(a.clone()), Type::TVar(b.clone()));
+    let ty = build_fun_type(
+        vec![
+            build_fun_type(vec![elem.clone()], t_list(result.clone())),
+            t_list(elem),
+        ],
+        t_list(result),
+    );
+    Scheme {
+        vars: vec![a, b],
+        qual: qualify(ty, vec![]),
+    }
+}
+
+/// `foldl' :: (b -> a -> b) -> b -> [a] -> b` のスキームを構築する。
+fn fold_left_scheme(s: &mut TVarSupply) -> Scheme {
+    let (a, b) = (s.fresh(), s.fresh());
+    let (elem, acc) = (Type::TVar(a.clone()), Type::TVar(b.clone()));
+    let ty = build_fun_type(
+        vec![
+            build_fun_type(vec![acc.clone(), elem.clone()], acc.clone()),
+            acc.clone(),
+            t_list(elem),
+        ],
+        acc,
+    );
+    Scheme {
+        vars: vec![a, b],
+        qual: qualify(ty, vec![]),
+    }
+}
+
+/// `fromList :: [a] -> Array a` のスキームを構築する。
+fn array_from_list_scheme(s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(
+        s,
+        |a| vec![t_list(a.clone())],
+        |a| t_array(a.clone()),
+        |_| vec![],
+    )
+}
+
+/// `(!) :: Array a -> Int -> a` のスキームを構築する。
+fn array_index_scheme(s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(
+        s,
+        |a| vec![t_array(a.clone()), Type::TCon(TCon { name: "Int".into() })],
+        |a| a.clone(),
+        |_| vec![],
+    )
+}
+
+/// `newRef :: a -> Ref a` のスキームを構築する。
+fn new_ref_scheme(s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(s, |a| vec![a.clone()], |a| t_ref(a.clone()), |_| vec![])
+}
+
+/// `readRef :: Ref a -> a` のスキームを構築する。
+fn read_ref_scheme(s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(s, |a| vec![t_ref(a.clone())], |a| a.clone(), |_| vec![])
+}
+
+/// `writeRef :: Ref a -> a -> Unit` のスキームを構築する。
+fn write_ref_scheme(s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(
+        s,
+        |a| vec![t_ref(a.clone()), a.clone()],
+        |_| {
+            Type::TCon(TCon {
+                name: "Unit".into(),
+            })
+        },
+        |_| vec![],
+    )
+}
+
+/// `Map k v` を扱う組み込みのスキームを構築する。
+///
+/// `make_args` が返す引数の後ろに表そのものを受け取る引数を加え、キーには `Eq k` 制約を付ける。
+/// 例: `insert :: Eq k => k -> v -> Map k v -> Map k v`、`lookup :: Eq k => k -> Map k v -> [v]`、
+/// `findWithDefault :: Eq k => v -> k -> Map k v -> v`。
+/// 引数を取らない `emptyMap :: Map k v` は制約を付けない。
+fn map_scheme(
+    s: &mut TVarSupply,
+    make_args: impl Fn(Type, Type) -> Vec<Type>,
+    make_ret: impl Fn(Type, Type) -> Type,
+) -> Scheme {
+    let (k, v) = (s.fresh(), s.fresh());
+    let (key, value) = (Type::TVar(k.clone()), Type::TVar(v.clone()));
+    let map = t_map(key.clone(), value.clone());
+    let mut args = make_args(key.clone(), value.clone());
+    let constraints = if args.is_empty() {
+        vec![]
+    } else {
+        args.push(map.clone());
+        vec![Constraint {
+            classname: "Eq".into(),
+            r#type: key,
+        }]
+    };
+    let ret = make_ret(map, value);
+    Scheme {
+        vars: vec![k, v],
+        qual: qualify(build_fun_type(args, ret), constraints),
+    }
+}
+
+/// `trace :: String -> a -> a` のスキームを構築する。
+fn trace_scheme(s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(
+        s,
+        |a| vec![t_string(), a.clone()],
+        |a| a.clone(),
+        |_| vec![],
+    )
+}
+
+fn catch_scheme(s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(
+        s,
+        |a| vec![a.clone(), build_fun_type(vec![t_string()], a.clone())],
+        |a| a.clone(),
+        |_| vec![],
+    )
+}
+
+/// `format` を初期環境へ登録するための目印のスキーム `FormatString a => String -> a`。
+///
+/// 実際の型は書式文字列リテラルへ適用した箇所で `infer_format` が組み立てる。
+/// `FormatString` はクラス環境に存在しないため、このスキームのまま使われることはない。
+fn format_marker_scheme(s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(
+        s,
+        |_| vec![t_string()],
+        |a| a.clone(),
+        |a| {
+            vec![Constraint {
+                classname: FORMAT_MARKER_CLASS.into(),
+                r#type: a.clone(),
+            }]
+        },
+    )
+}
+
+const FORMAT_MARKER_CLASS: &str = "FormatString";
+
+/// 環境の `name` が組み込みの `format`（利用者の定義で隠されていない）か。
+fn is_builtin_format(env: &TypeEnv, name: &str) -> bool {
+    name == "format"
+        && env.lookup(name).is_some_and(|sch| {
+            sch.qual
+                .constraints
+                .iter()
+                .any(|c| c.classname == FORMAT_MARKER_CLASS)
+        })
+}
+
+/// `enumFrom` を初期環境へ登録するための目印のスキーム `InfiniteRange a => Int -> a`。
+///
+/// 評価は正格で要素を必要になるまで作るリストがないため、`[x..]` を単独のリストとしては型付けせず、
+/// 組み込みの `take` の第 2 引数に置いた箇所だけ `infer_take_range` が `[Int]` として扱う。
+fn enum_from_marker_scheme(s: &mut TVarSupply) -> Scheme {
+    build_single_var_scheme(
+        s,
+        |_| vec![Type::TCon(TCon { name: "Int".into() })],
+        |a| a.clone(),
+        |a| {
+            vec![Constraint {
+                classname: ENUM_FROM_MARKER_CLASS.into(),
+                r#type: a.clone(),
+            }]
+        },
+    )
+}
+
+const ENUM_FROM_MARKER_CLASS: &str = "InfiniteRange";
+
+/// 環境の `name` が組み込みの `enumFrom`（利用者の定義で隠されていない）か。
+fn is_builtin_enum_from(env: &TypeEnv, name: &str) -> bool {
+    name == "enumFrom"
+        && env.lookup(name).is_some_and(|sch| {
+            sch.qual
+                .constraints
+                .iter()
+                .any(|c| c.classname == ENUM_FROM_MARKER_CLASS)
+        })
+}
+
+/// 環境の `take` が初期環境のもの（利用者の定義で隠されていない）か。
+fn is_builtin_take(env: &TypeEnv, name: &str) -> bool {
+    name == "take" && env.lookup(name) == initial_env().lookup(name)
+}
+
 /// 式の主型と制約集合を返すトップレベルの推論関数。
 pub fn infer_expr(
     env: &TypeEnv,
@@ -203,6 +737,55 @@ pub fn infer_expr(
     ctx.infer(env, Subst::new(), e)
 }
 
+/// 単一の式を推論し、`defaulting` が真なら既定化まで適用した `QualType` を返す。
+///
+/// REPL の `:t` と同じ推論経路であり、エディタのホバー表示などツールから直接呼び出せる。
+pub fn infer_qual_type(
+    env: &TypeEnv,
+    ce: &ClassEnv,
+    e: &A::Expr,
+    defaulting: bool,
+) -> Result<QualType, TypeError> {
+    let mut st = InferState {
+        supply: TVarSupply::new(),
+    };
+    let (subst, qual) = infer_expr(env, ce, &mut st, e)?;
+    let qual = qual.apply_subst(&subst);
+    let applied = QualType {
+        constraints: reduce_constraints(ce, &qual.constraints),
+        r#type: qual.r#type,
+    };
+    if defaulting {
+        Ok(apply_defaulting_simple(&applied))
+    } else {
+        Ok(applied)
+    }
+}
+
+/// 制約集合を簡約する。重複をまとめ、同じ型に付いた下位クラス制約から導ける
+/// 上位クラス制約を取り除く（例: `Eq a, Ord a` は `Ord a` だけになる）。
+/// 型構築子に付いた制約のうち、インスタンスから満たされると分かるもの（例: `Monoid [a]`）も取り除く。
+pub fn reduce_constraints(ce: &ClassEnv, cs: &[Constraint]) -> Vec<Constraint> {
+    let mut unique: Vec<Constraint> = Vec::new();
+    for c in cs {
+        let satisfied = !matches!(c.r#type, Type::TVar(_)) && ce.entails(std::slice::from_ref(c));
+        if !satisfied && !unique.contains(c) {
+            unique.push(c.clone());
+        }
+    }
+    unique
+        .iter()
+        .filter(|c| {
+            !unique.iter().any(|d| {
+                d.classname != c.classname
+                    && d.r#type == c.r#type
+                    && ce.is_subclass_of(&d.classname, &c.classname)
+            })
+        })
+        .cloned()
+        .collect()
+}
+
 struct InferCtx<'a> {
     _ce: &'a ClassEnv,
     supply: &'a mut TVarSupply,
@@ -275,6 +858,22 @@ impl<'a> InferCtx<'a> {
             return Ok((subst, qualify(a, vec![])));
         }
 
+        if is_builtin_format(env, name) {
+            return Err(TypeError::new(
+                "TYPE066",
+                "format は書式文字列リテラルへ直接適用する必要があります（例: format \"%d\" 1）",
+                None,
+            ));
+        }
+
+        if is_builtin_enum_from(env, name) {
+            return Err(TypeError::new(
+                "TYPE068",
+                "上限のない範囲 [x..] は take n [x..] の形でのみ使えます（評価は正格で、要素を必要になるまで作らないリストがないため）",
+                None,
+            ));
+        }
+
         if let Some(sch) = env.lookup(name) {
             let q = instantiate(sch, self.supply);
             let applied = q.apply_subst(&subst);
@@ -318,14 +917,16 @@ impl<'a> InferCtx<'a> {
     ) -> Result<(Subst, QualType), TypeError> {
         let elem = Type::TVar(self.supply.fresh());
         let mut s_acc = subst;
+        let mut cs = Vec::new();
         for item in items {
             let (s_new, q) = self.infer_with_subst(env, s_acc, item)?;
             let s_unify = unify(elem.apply_subst(&s_new), q.r#type.apply_subst(&s_new))
                 .map_err(|e| TypeError::new(e.code, e.message, None))?;
             s_acc = compose(&s_unify, &s_new);
+            cs.extend(q.constraints);
         }
         let ty = t_list(elem.apply_subst(&s_acc));
-        Ok((s_acc, qualify(ty, vec![])))
+        Ok((s_acc.clone(), qualify(ty, cs).apply_subst(&s_acc)))
     }
 
     fn infer_tuple(
@@ -336,12 +937,15 @@ impl<'a> InferCtx<'a> {
     ) -> Result<(Subst, QualType), TypeError> {
         let mut s_acc = subst;
         let mut tys = Vec::with_capacity(items.len());
+        let mut cs = Vec::new();
         for item in items {
             let (s_new, q) = self.infer_with_subst(env, s_acc, item)?;
             tys.push(q.r#type.apply_subst(&s_new));
+            cs.extend(q.constraints);
             s_acc = s_new;
         }
-        Ok((s_acc, qualify(Type::TTuple(TTuple { items: tys }), vec![])))
+        let qual = qualify(Type::TTuple(TTuple { items: tys }), cs).apply_subst(&s_acc);
+        Ok((s_acc, qual))
     }
 
     fn infer_lambda(
@@ -378,6 +982,7 @@ impl<'a> InferCtx<'a> {
         Ok((s_body, qual))
     }
 
+    /// let 束縛を 1 つずつ推論・一般化する。単相性制限は設けず、制約付き束縛も量化する。
     fn infer_let(
         &mut self,
         env: &TypeEnv,
@@ -416,7 +1021,11 @@ impl<'a> InferCtx<'a> {
                 .map_err(|e| TypeError::new(e.code, e.message, None))?;
             let s_updated = compose(&s_match, &s_rhs);
             s_acc = s_updated.clone();
-            let generalized = generalize(&env2, q_rhs.apply_subst(&s_updated));
+            // 一般化は置換済みの環境に対して行い、外側で確定した型変数を量化しない。
+            // 自身のプレースホルダは推論結果そのものなので比較対象から外す。
+            let mut env_applied = env2.apply_subst(&s_updated);
+            env_applied.remove(name);
+            let generalized = generalize(&env_applied, q_rhs.apply_subst(&s_updated));
             env2.extend(name.clone(), generalized);
         }
 
@@ -494,7 +1103,7 @@ impl<'a> InferCtx<'a> {
                 if !seen.insert(name.clone()) {
                     return Err(TypeError::new(
                         "TYPE061",
-                        format!("パターン変数 {name} が重複しています"),
+                        format!("パターン変数 {name} が 1 つのパターン内で重複しています"),
                         None,
                     ));
                 }
@@ -575,6 +1184,26 @@ impl<'a> InferCtx<'a> {
         func: &A::Expr,
         arg: &A::Expr,
     ) -> Result<(Subst, QualType), TypeError> {
+        if let (A::Expr::Var { name, .. }, A::Expr::StringLit { value, .. }) = (func, arg) {
+            if is_builtin_format(env, name) {
+                return infer_format(subst, value);
+            }
+        }
+        if let (
+            A::Expr::App { func: take, .. },
+            A::Expr::App {
+                func: range,
+                arg: start,
+                ..
+            },
+        ) = (func, arg)
+        {
+            if matches!(take.as_ref(), A::Expr::Var { name, .. } if is_builtin_take(env, name))
+                && matches!(range.as_ref(), A::Expr::Var { name, .. } if is_builtin_enum_from(env, name))
+            {
+                return self.infer_take_range(env, subst, func, start);
+            }
+        }
         let (s_func, q_func) = self.infer_with_subst(env, subst, func)?;
         let (mut s_acc, q_arg) = self.infer_with_subst(env, s_func, arg)?;
         let result_ty = Type::TVar(self.supply.fresh());
@@ -598,6 +1227,39 @@ impl<'a> InferCtx<'a> {
         Ok((s_acc, qual))
     }
 
+    /// `take n [x..]` を、開始値 `x` を `Int` とした `[Int]` へ `take n` を適用したものとして型付けする。
+    fn infer_take_range(
+        &mut self,
+        env: &TypeEnv,
+        subst: Subst,
+        take: &A::Expr,
+        start: &A::Expr,
+    ) -> Result<(Subst, QualType), TypeError> {
+        let int = Type::TCon(TCon { name: "Int".into() });
+        let (s_take, q_take) = self.infer_with_subst(env, subst, take)?;
+        let (mut s_acc, q_start) = self.infer_with_subst(env, s_take, start)?;
+        let s_start = unify(q_start.r#type.apply_subst(&s_acc), int.clone())
+            .map_err(|e| TypeError::new(e.code, e.message, None))?;
+        s_acc = compose(&s_start, &s_acc);
+        let result_ty = Type::TVar(self.supply.fresh());
+        let s_fun = unify(
+            q_take.r#type.apply_subst(&s_acc),
+            Type::TFun(TFun {
+                arg: Box::new(t_list(int)),
+                ret: Box::new(result_ty.clone()),
+            }),
+        )
+        .map_err(|e| TypeError::new(e.code, e.message, None))?;
+        s_acc = compose(&s_fun, &s_acc);
+        let mut cs = q_take.apply_subst(&s_acc).constraints;
+        cs.extend(q_start.apply_subst(&s_acc).constraints);
+        let qual = QualType {
+            constraints: cs,
+            r#type: result_ty.apply_subst(&s_acc),
+        };
+        Ok((s_acc, qual))
+    }
+
     fn infer_binop(
         &mut self,
         env: &TypeEnv,
@@ -661,16 +1323,35 @@ impl<'a> InferCtx<'a> {
     }
 
     fn is_negative_exponent(&mut self, right: &A::Expr) -> bool {
-        matches!(
-            right,
-            A::Expr::BinOp { op, left, right, .. }
-                if op == "-"
+        match right {
+            A::Expr::IntLit { value, .. } => *value < 0,
+            A::Expr::BinOp {
+                op, left, right, ..
+            } => {
+                op == "-"
                     && matches!(**left, A::Expr::IntLit { value: 0, .. })
                     && matches!(**right, A::Expr::IntLit { .. })
-        )
+            }
+            _ => false,
+        }
     }
 }
 
+/// `format "..."` の型を書式の指定子から組み立てる（`format "%d: %s"` は `Int -> String -> String`）。
+fn infer_format(subst: Subst, template: &str) -> Result<(Subst, QualType), TypeError> {
+    let pieces = runtime_common::parse_format(template).map_err(|msg| {
+        TypeError::new("TYPE067", format!("format の書式が不正です: {msg}"), None)
+    })?;
+    let args = runtime_common::format_specs(&pieces)
+        .into_iter()
+        .map(|spec| match spec.type_name() {
+            "String" => t_string(),
+            name => Type::TCon(TCon { name: name.into() }),
+        })
+        .collect();
+    Ok((subst, qualify(build_fun_type(args, t_string()), vec![])))
+}
+
 struct PatternInfer<'ctx, 'a> {
     ctx: &'ctx mut InferCtx<'a>,
     env: &'ctx TypeEnv,
@@ -693,7 +1374,7 @@ impl<'ctx, 'a> PatternInfer<'ctx, 'a> {
                 let ty = expected.apply_subst(&subst);
                 Ok((subst, vec![(name.clone(), ty)], Vec::new()))
             }
-            A::Pattern::Int { .. } => self.literal(subst, expected, Self::named_con("Integer")),
+            A::Pattern::Int { .. } => self.num_literal(subst, expected),
             A::Pattern::Float { .. } => self.literal(subst, expected, Self::named_con("Double")),
             A::Pattern::Char { .. } => self.literal(subst, expected, Self::named_con("Char")),
             A::Pattern::String { .. } => self.literal(subst, expected, t_string()),
@@ -719,6 +1400,20 @@ impl<'ctx, 'a> PatternInfer<'ctx, 'a> {
         Ok((subst, Vec::new(), Vec::new()))
     }
 
+    /// 整数リテラルパターンは式の整数リテラルと同様に `Num a => a` として扱う。
+    fn num_literal(&mut self, subst: Subst, expected: Type) -> Result<PatternOutcome, TypeError> {
+        let a = Type::TVar(self.ctx.supply.fresh());
+        let subst = self.unify_expected(subst, expected, a.clone())?;
+        Ok((
+            subst,
+            Vec::new(),
+            vec![Constraint {
+                classname: "Num".into(),
+                r#type: a,
+            }],
+        ))
+    }
+
     fn list(
         &mut self,
         subst: Subst,
@@ -756,7 +1451,7 @@ impl<'ctx, 'a> PatternInfer<'ctx, 'a> {
         let mut current = self.unify_expected(subst, expected, tuple_ty)?;
         let mut bindings = Vec::new();
         let mut constraints = Vec::new();
-        for (subpat, elem_ty) in items.iter().zip(elem_types.into_iter()) {
+        for (subpat, elem_ty) in items.iter().zip(elem_types) {
             let expected_elem = elem_ty.apply_subst(&current);
             let (next_subst, mut binds, mut cons) = self.infer(current, subpat, expected_elem)?;
             current = next_subst;
@@ -937,10 +1632,7 @@ impl TypeExprLowering {
 }
 
 fn is_type_constructor_like(name: &str) -> bool {
-    name.chars()
-        .next()
-        .map(|c| c.is_ascii_uppercase())
-        .unwrap_or(false)
+    name.chars().next().map(char::is_uppercase).unwrap_or(false)
 }
 
 /// 単一の式に対する推論結果を文字列表現で返す。
@@ -959,16 +1651,6 @@ pub fn infer_type_str_with_defaulting(
     expr: &A::Expr,
     defaulting_on: bool,
 ) -> Result<String, TypeError> {
-    let env = initial_env();
-    let ce = initial_class_env();
-    let mut st = InferState {
-        supply: TVarSupply::new(),
-    };
-    let (_s, q) = infer_expr(&env, &ce, &mut st, expr)?;
-    if defaulting_on {
-        let qd = crate::typesys::apply_defaulting_simple(&q);
-        Ok(pretty_qual(&qd))
-    } else {
-        Ok(pretty_qual(&q))
-    }
+    let q = infer_qual_type(&initial_env(), &initial_class_env(), expr, defaulting_on)?;
+    Ok(pretty_qual(&q))
 }
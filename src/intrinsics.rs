@@ -8,6 +8,55 @@
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum IntrinsicKind {
     Println,
+    Trace,
+    Sqrt,
+    Sin,
+    Cos,
+    Exp,
+    Log,
+    Floor,
+    Ceiling,
+    Round,
+    Truncate,
+    FromIntegral,
+    Null,
+    Length,
+    Replicate,
+    Take,
+    Drop,
+    Append,
+    FoldLeft,
+    ArrayFromList,
+    ArrayIndex,
+    NewRef,
+    ReadRef,
+    WriteRef,
+    EmptyMap,
+    MapInsert,
+    MapLookup,
+    MapFindWithDefault,
+    ReadInt,
+    ReadDouble,
+    Format,
+}
+
+impl IntrinsicKind {
+    /// 関数ではなく値そのものとして参照される intrinsic か。
+    pub fn is_constant(self) -> bool {
+        matches!(self, IntrinsicKind::EmptyMap)
+    }
+
+    /// 入出力や参照セルの生成・読み書きのように、呼び出すたびに結果や副作用が変わり得る intrinsic か。
+    pub fn has_effects(self) -> bool {
+        matches!(
+            self,
+            IntrinsicKind::Println
+                | IntrinsicKind::Trace
+                | IntrinsicKind::NewRef
+                | IntrinsicKind::ReadRef
+                | IntrinsicKind::WriteRef
+        )
+    }
 }
 
 /// intrinsic のメタデータ。
@@ -17,10 +66,132 @@ pub struct Intrinsic {
     pub kind: IntrinsicKind,
 }
 
-const INTRINSICS: &[Intrinsic] = &[Intrinsic {
-    name: "println",
-    kind: IntrinsicKind::Println,
-}];
+const INTRINSICS: &[Intrinsic] = &[
+    Intrinsic {
+        name: "println",
+        kind: IntrinsicKind::Println,
+    },
+    Intrinsic {
+        name: "trace",
+        kind: IntrinsicKind::Trace,
+    },
+    Intrinsic {
+        name: "sqrt",
+        kind: IntrinsicKind::Sqrt,
+    },
+    Intrinsic {
+        name: "sin",
+        kind: IntrinsicKind::Sin,
+    },
+    Intrinsic {
+        name: "cos",
+        kind: IntrinsicKind::Cos,
+    },
+    Intrinsic {
+        name: "exp",
+        kind: IntrinsicKind::Exp,
+    },
+    Intrinsic {
+        name: "log",
+        kind: IntrinsicKind::Log,
+    },
+    Intrinsic {
+        name: "floor",
+        kind: IntrinsicKind::Floor,
+    },
+    Intrinsic {
+        name: "ceiling",
+        kind: IntrinsicKind::Ceiling,
+    },
+    Intrinsic {
+        name: "round",
+        kind: IntrinsicKind::Round,
+    },
+    Intrinsic {
+        name: "truncate",
+        kind: IntrinsicKind::Truncate,
+    },
+    Intrinsic {
+        name: "fromIntegral",
+        kind: IntrinsicKind::FromIntegral,
+    },
+    Intrinsic {
+        name: "null",
+        kind: IntrinsicKind::Null,
+    },
+    Intrinsic {
+        name: "length",
+        kind: IntrinsicKind::Length,
+    },
+    Intrinsic {
+        name: "replicate",
+        kind: IntrinsicKind::Replicate,
+    },
+    Intrinsic {
+        name: "take",
+        kind: IntrinsicKind::Take,
+    },
+    Intrinsic {
+        name: "drop",
+        kind: IntrinsicKind::Drop,
+    },
+    Intrinsic {
+        name: "<>",
+        kind: IntrinsicKind::Append,
+    },
+    Intrinsic {
+        name: "foldl'",
+        kind: IntrinsicKind::FoldLeft,
+    },
+    Intrinsic {
+        name: "fromList",
+        kind: IntrinsicKind::ArrayFromList,
+    },
+    Intrinsic {
+        name: "!",
+        kind: IntrinsicKind::ArrayIndex,
+    },
+    Intrinsic {
+        name: "newRef",
+        kind: IntrinsicKind::NewRef,
+    },
+    Intrinsic {
+        name: "readRef",
+        kind: IntrinsicKind::ReadRef,
+    },
+    Intrinsic {
+        name: "writeRef",
+        kind: IntrinsicKind::WriteRef,
+    },
+    Intrinsic {
+        name: "emptyMap",
+        kind: IntrinsicKind::EmptyMap,
+    },
+    Intrinsic {
+        name: "insert",
+        kind: IntrinsicKind::MapInsert,
+    },
+    Intrinsic {
+        name: "lookup",
+        kind: IntrinsicKind::MapLookup,
+    },
+    Intrinsic {
+        name: "findWithDefault",
+        kind: IntrinsicKind::MapFindWithDefault,
+    },
+    Intrinsic {
+        name: "readInt",
+        kind: IntrinsicKind::ReadInt,
+    },
+    Intrinsic {
+        name: "readDouble",
+        kind: IntrinsicKind::ReadDouble,
+    },
+    Intrinsic {
+        name: "format",
+        kind: IntrinsicKind::Format,
+    },
+];
 
 /// 名前から intrinsic を検索するユーティリティ。
 pub fn lookup(name: &str) -> Option<Intrinsic> {
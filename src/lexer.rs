@@ -10,12 +10,22 @@
 
 use crate::errors::LexerError;
 
+mod incremental;
+mod layout;
+
+pub use incremental::{relex, TextEdit};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// 生成されたトークンとその位置情報を保持するレコード。
+///
+/// `pos..end` は原文上のバイト範囲、`line`/`col` は開始位置の 1 始まりの行・列（列は文字数）。
+/// `value` は原文の切り出しだが、`DOC` だけは `-- |` を除いた本文を保持する。
 pub struct Token {
     pub kind: TokenKind,
     pub value: String,
     pub pos: usize,
+    /// トークン末尾の直後のバイト位置。`EOF` と仮想トークンでは `pos` と等しい。
+    pub end: usize,
     pub line: usize,
     pub col: usize,
 }
@@ -38,6 +48,8 @@ pub enum TokenKind {
     PLUS,
     MINUS,
     DBLSTAR,
+    BITAND, // ビット積 `.&.`
+    BITOR,  // ビット和 `.|.`
     STAR,
     SLASH,
     CARET,
@@ -51,7 +63,13 @@ pub enum TokenKind {
     QMARK,
     AT,
     UNDERSCORE,
+    BACKTICK, // 関数の中置適用 `` `div` `` の区切り
     BAR,
+    DOTDOT,   // 範囲構文 `[x..]` の `..`
+    OPERATOR, // ユーザー定義演算子（`|>` など予約されていない記号列）
+    DOC,      // ドキュメントコメント `-- | ...`（値は本文）
+    VLBRACE,  // レイアウト規則が挿入する仮想の `{`（case アームの開始）
+    VRBRACE,  // レイアウト規則が挿入する仮想の `}`（case アームの終了）
     // リテラル分類
     CHAR,
     STRING,
@@ -72,9 +90,16 @@ pub enum TokenKind {
     CASE,
     OF,
     DATA,
+    NEWTYPE,
     CLASS,
     INSTANCE,
+    TYPE,
     WHERE,
+    INFIXL,
+    INFIXR,
+    INFIX,
+    IMPORT,
+    MODULE,
     TRUE,
     FALSE,
 }
@@ -149,21 +174,69 @@ fn is_octdigit(c: char) -> bool {
 fn is_bindigit(c: char) -> bool {
     matches!(c, '0' | '1')
 }
+/// 演算子を構成できる記号文字かどうかを判定する。
+pub fn is_symbol_char(c: char) -> bool {
+    matches!(
+        c,
+        '!' | '#'
+            | '$'
+            | '%'
+            | '&'
+            | '*'
+            | '+'
+            | '.'
+            | '/'
+            | '<'
+            | '='
+            | '>'
+            | '?'
+            | '@'
+            | '^'
+            | '|'
+            | '-'
+            | '~'
+            | ':'
+    )
+}
 /// 識別子の先頭に使用可能な文字かどうかを判定する。
+///
+/// Unicode の XID_Start を外部クレートなしで近似し、`char::is_alphabetic`（英字・かな・漢字・ギリシャ文字など）と `_` を認める。
 fn is_letter(c: char) -> bool {
-    c.is_ascii_alphabetic() || c == '_'
+    c.is_alphabetic() || c == '_'
 }
 /// 識別子の後続として許容される文字か判定する。
+///
+/// XID_Continue の近似として、先頭に使える文字・数字・結合文字（濁点やアクセント記号）・`'` を認める。
 fn is_ident_rest(c: char) -> bool {
-    c.is_ascii_alphanumeric() || c == '_' || c == '\''
+    c.is_alphanumeric() || c == '_' || c == '\'' || is_combining_mark(c)
+}
+/// 主要な結合文字のブロックに属するか判定する（`char` には一般カテゴリ Mn/Mc の判定が無いため）。
+fn is_combining_mark(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036F}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{200C}'..='\u{200D}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{3099}'..='\u{309A}'
+            | '\u{FE20}'..='\u{FE2F}'
+    )
 }
 
+/// 生文字列（エスケープを解釈せず、改行を含められる文字列）の区切り。
+pub const RAW_STRING_DELIMITER: &str = "\"\"\"";
+
 struct Lexer<'a> {
     src: &'a str,
     cursor: usize,
     len: usize,
     line_map: LineMap,
     tokens: Vec<Token>,
+    /// 直前のドキュメントコメント行の終端。続く `--` 行を同じ DOC トークンへ連結するために使う。
+    doc_end: Option<usize>,
+    /// ファイル先頭の `{-# LAYOUT #-}` で有効になる、字下げによる case アームの区切り。
+    layout: bool,
 }
 
 impl<'a> Lexer<'a> {
@@ -174,10 +247,22 @@ impl<'a> Lexer<'a> {
             len: src.len(),
             line_map: LineMap::new(src),
             tokens: Vec::new(),
+            doc_end: None,
+            layout: false,
         }
     }
 
     fn run(mut self) -> Result<Vec<Token>, LexerError> {
+        while self.advance_token()? {}
+        self.push_simple(TokenKind::EOF, "", self.len);
+        if self.layout {
+            return Ok(layout::resolve_case_layout(self.tokens));
+        }
+        Ok(self.tokens)
+    }
+
+    /// 空白・コメントを読み飛ばして次のトークンを 1 つ読み進める。入力の終端に達したら偽を返す。
+    fn advance_token(&mut self) -> Result<bool, LexerError> {
         while self.cursor < self.len {
             if self.consume_trivia()? {
                 continue;
@@ -186,9 +271,9 @@ impl<'a> Lexer<'a> {
                 break;
             }
             self.lex_token()?;
+            return Ok(true);
         }
-        self.push_simple(TokenKind::EOF, "", self.len);
-        Ok(self.tokens)
+        Ok(false)
     }
 
     fn consume_trivia(&mut self) -> Result<bool, LexerError> {
@@ -234,15 +319,49 @@ impl<'a> Lexer<'a> {
         if !self.starts_with("--") {
             return false;
         }
+        let start = self.cursor;
         self.advance_bytes(2);
+        let body_start = self.cursor;
         while let Some(ch) = self.advance_char() {
             if ch == '\n' {
                 break;
             }
         }
+        let body = self.src[body_start..self.cursor].trim();
+        let line_end = body_start + self.src[body_start..self.cursor].trim_end().len();
+        let starts_line = self.src[..start]
+            .rsplit('\n')
+            .next()
+            .is_some_and(|prefix| prefix.trim().is_empty());
+        if let Some(doc) = body.strip_prefix('|').filter(|_| starts_line) {
+            self.push_simple(TokenKind::DOC, doc.trim(), start);
+            if let Some(last) = self.tokens.last_mut() {
+                last.end = line_end;
+            }
+            self.doc_end = Some(self.cursor);
+        } else if self.continues_doc_comment(start) {
+            // `-- |` の直後に続く `--` 行はドキュメントの続きとして連結する。
+            if let Some(last) = self.tokens.last_mut() {
+                last.value.push('\n');
+                last.value.push_str(body);
+                last.end = line_end;
+            }
+            self.doc_end = Some(self.cursor);
+        }
         true
     }
 
+    fn continues_doc_comment(&self, start: usize) -> bool {
+        let Some(doc_end) = self.doc_end else {
+            return false;
+        };
+        matches!(self.tokens.last(), Some(t) if t.kind == TokenKind::DOC)
+            && doc_end <= start
+            && self.src[doc_end..start]
+                .chars()
+                .all(|c| c == ' ' || c == '\t')
+    }
+
     fn consume_block_comment(&mut self) -> Result<bool, LexerError> {
         if !self.starts_with("{-") {
             return Ok(false);
@@ -254,6 +373,7 @@ impl<'a> Lexer<'a> {
                 depth -= 1;
                 idx += 2;
                 if depth == 0 {
+                    self.detect_layout_pragma(&self.src[self.cursor..idx]);
                     self.cursor = idx;
                     return Ok(true);
                 }
@@ -269,8 +389,20 @@ impl<'a> Lexer<'a> {
             };
             idx += ch.len_utf8();
         }
-        let pos = idx.min(self.len);
-        Err(self.err("LEX001", "ブロックコメントが閉じていません", pos))
+        // 末尾ではなく、閉じられていないコメントの開始位置を指す。
+        Err(self.err("LEX001", "ブロックコメントが閉じていません", self.cursor))
+    }
+
+    /// 最初のトークンより前にある `{-# LAYOUT #-}` を見つけたらレイアウト規則を有効にする。
+    fn detect_layout_pragma(&mut self, comment: &str) {
+        let before_code = self.tokens.iter().all(|t| t.kind == TokenKind::DOC);
+        let pragma = comment
+            .strip_prefix("{-#")
+            .and_then(|rest| rest.strip_suffix("#-}"))
+            .map(str::trim);
+        if before_code && pragma == Some("LAYOUT") {
+            self.layout = true;
+        }
     }
 
     fn lex_token(&mut self) -> Result<(), LexerError> {
@@ -278,7 +410,7 @@ impl<'a> Lexer<'a> {
         let ch = self
             .peek_char()
             .expect("lex_token は EOF では呼び出されない");
-        if self.try_multi_char_symbol(ch) {
+        if self.try_symbol_run() {
             return Ok(());
         }
         if self.try_single_char_symbol(ch) {
@@ -299,52 +431,62 @@ impl<'a> Lexer<'a> {
         Err(self.err("LEX090", format!("字句解析に失敗: {:?}", ch), start))
     }
 
-    fn try_multi_char_symbol(&mut self, first: char) -> bool {
-        let Some(second) = self.peek_second_char() else {
+    /// 記号文字の最長列を 1 トークンとして切り出す。
+    /// 予約済みの記号・組み込み演算子はそれぞれの種別へ、それ以外はユーザー定義演算子 `OPERATOR` とする。
+    fn try_symbol_run(&mut self) -> bool {
+        let start = self.cursor;
+        let mut end = start;
+        while let Some(ch) = self.char_at(end) {
+            if !is_symbol_char(ch) || (end > start && self.src[end..].starts_with("--")) {
+                break;
+            }
+            end += ch.len_utf8();
+        }
+        if end == start {
             return false;
-        };
-        let token = match (first, second) {
-            ('-', '>') => Some((TokenKind::ARROW, "->")),
-            (':', ':') => Some((TokenKind::DCOLON, "::")),
-            ('=', '>') => Some((TokenKind::DARROW, "=>")),
-            ('<', '=') => Some((TokenKind::LE, "<=")),
-            ('>', '=') => Some((TokenKind::GE, ">=")),
-            ('=', '=') => Some((TokenKind::EQ, "==")),
-            ('/', '=') => Some((TokenKind::NE, "/=")),
-            ('*', '*') => Some((TokenKind::DBLSTAR, "**")),
-            _ => None,
-        };
-        if let Some((kind, value)) = token {
-            let start = self.cursor;
-            self.advance_bytes(first.len_utf8());
-            self.advance_bytes(second.len_utf8());
-            self.push_simple(kind, value, start);
-            return true;
         }
-        false
+        let text = &self.src[start..end];
+        let kind = match text {
+            "->" => TokenKind::ARROW,
+            "::" => TokenKind::DCOLON,
+            "=>" => TokenKind::DARROW,
+            "<=" => TokenKind::LE,
+            ">=" => TokenKind::GE,
+            "==" => TokenKind::EQ,
+            "/=" => TokenKind::NE,
+            "**" => TokenKind::DBLSTAR,
+            ".&." => TokenKind::BITAND,
+            ".|." => TokenKind::BITOR,
+            "<" => TokenKind::LT,
+            ">" => TokenKind::GT,
+            "+" => TokenKind::PLUS,
+            "-" => TokenKind::MINUS,
+            "*" => TokenKind::STAR,
+            "/" => TokenKind::SLASH,
+            "^" => TokenKind::CARET,
+            "=" => TokenKind::EQUAL,
+            "|" => TokenKind::BAR,
+            "?" => TokenKind::QMARK,
+            "@" => TokenKind::AT,
+            ".." => TokenKind::DOTDOT,
+            _ => TokenKind::OPERATOR,
+        };
+        self.cursor = end;
+        self.push_slice(kind, start, end);
+        true
     }
 
     fn try_single_char_symbol(&mut self, ch: char) -> bool {
         let token = match ch {
             '\\' => Some((TokenKind::LAMBDA, "\\")),
-            '<' => Some((TokenKind::LT, "<")),
-            '>' => Some((TokenKind::GT, ">")),
-            '+' => Some((TokenKind::PLUS, "+")),
-            '-' => Some((TokenKind::MINUS, "-")),
-            '*' => Some((TokenKind::STAR, "*")),
-            '/' => Some((TokenKind::SLASH, "/")),
-            '^' => Some((TokenKind::CARET, "^")),
             '(' => Some((TokenKind::LPAREN, "(")),
             ')' => Some((TokenKind::RPAREN, ")")),
             '[' => Some((TokenKind::LBRACK, "[")),
             ']' => Some((TokenKind::RBRACK, "]")),
             ',' => Some((TokenKind::COMMA, ",")),
             ';' => Some((TokenKind::SEMI, ";")),
-            '=' => Some((TokenKind::EQUAL, "=")),
-            '|' => Some((TokenKind::BAR, "|")),
-            '?' => Some((TokenKind::QMARK, "?")),
-            '@' => Some((TokenKind::AT, "@")),
             '_' => Some((TokenKind::UNDERSCORE, "_")),
+            '`' => Some((TokenKind::BACKTICK, "`")),
             _ => None,
         };
         if let Some((kind, value)) = token {
@@ -388,6 +530,9 @@ impl<'a> Lexer<'a> {
 
     fn lex_string_literal(&mut self) -> Result<(), LexerError> {
         let start = self.cursor;
+        if self.src[start..].starts_with(RAW_STRING_DELIMITER) {
+            return self.lex_raw_string_literal();
+        }
         self.advance_bytes(1); // 開始ダブルクォート
         let mut escaped = false;
         let mut ok = false;
@@ -416,6 +561,19 @@ impl<'a> Lexer<'a> {
         Ok(())
     }
 
+    /// `"""` で囲んだ生文字列を、閉じる `"""` まで改行を含めてそのまま切り出す。
+    fn lex_raw_string_literal(&mut self) -> Result<(), LexerError> {
+        let start = self.cursor;
+        let body_start = start + RAW_STRING_DELIMITER.len();
+        let Some(offset) = self.src[body_start..].find(RAW_STRING_DELIMITER) else {
+            return Err(self.err("LEX004", "複数行文字列リテラルが閉じていません", start));
+        };
+        let end = body_start + offset + RAW_STRING_DELIMITER.len();
+        self.cursor = end;
+        self.push_slice(TokenKind::STRING, start, end);
+        Ok(())
+    }
+
     fn lex_number(&mut self) -> Result<(), LexerError> {
         let start = self.cursor;
         if self.starts_with("0x") || self.starts_with("0X") {
@@ -539,13 +697,18 @@ impl<'a> Lexer<'a> {
 
     fn lex_identifier_or_keyword(&mut self) -> Result<(), LexerError> {
         let start = self.cursor;
-        self.advance_char();
-        while let Some(ch) = self.peek_char() {
-            if is_ident_rest(ch) {
-                self.advance_char();
-            } else {
+        let mut segment_start = start;
+        self.advance_identifier_segment();
+        // `Foo.bar` / `Data.List.map` のように大文字始まりの区間の直後に空白なしで `.` と
+        // 識別子が続く場合は、修飾名として 1 トークンにまとめる。
+        while self.src[segment_start..].starts_with(char::is_uppercase) {
+            let mut rest = self.src[self.cursor..].chars();
+            if rest.next() != Some('.') || !rest.next().is_some_and(is_letter) {
                 break;
             }
+            self.advance_bytes(1);
+            segment_start = self.cursor;
+            self.advance_identifier_segment();
         }
         let slice = &self.src[start..self.cursor];
         let (kind, value) = match slice {
@@ -557,14 +720,24 @@ impl<'a> Lexer<'a> {
             "case" => (TokenKind::CASE, slice),
             "of" => (TokenKind::OF, slice),
             "data" => (TokenKind::DATA, slice),
+            "newtype" => (TokenKind::NEWTYPE, slice),
             "class" => (TokenKind::CLASS, slice),
             "instance" => (TokenKind::INSTANCE, slice),
+            "type" => (TokenKind::TYPE, slice),
             "where" => (TokenKind::WHERE, slice),
+            "infixl" => (TokenKind::INFIXL, slice),
+            "infixr" => (TokenKind::INFIXR, slice),
+            "infix" => (TokenKind::INFIX, slice),
+            "import" => (TokenKind::IMPORT, slice),
+            "module" => (TokenKind::MODULE, slice),
             "True" => (TokenKind::TRUE, slice),
             "False" => (TokenKind::FALSE, slice),
             _ => {
-                let first = slice.chars().next().expect("識別子は少なくとも1文字");
-                if first.is_ascii_uppercase() {
+                let first = self.src[segment_start..]
+                    .chars()
+                    .next()
+                    .expect("識別子は少なくとも1文字");
+                if first.is_uppercase() {
                     (TokenKind::CONID, slice)
                 } else {
                     (TokenKind::VARID, slice)
@@ -575,12 +748,24 @@ impl<'a> Lexer<'a> {
         Ok(())
     }
 
+    fn advance_identifier_segment(&mut self) {
+        self.advance_char();
+        while let Some(ch) = self.peek_char() {
+            if is_ident_rest(ch) {
+                self.advance_char();
+            } else {
+                break;
+            }
+        }
+    }
+
     fn push_simple(&mut self, kind: TokenKind, value: &str, start: usize) {
         let (line, col) = self.line_map.locate(self.src, start);
         self.tokens.push(Token {
             kind,
             value: value.into(),
             pos: start,
+            end: self.cursor,
             line,
             col,
         });
@@ -592,6 +777,7 @@ impl<'a> Lexer<'a> {
             kind,
             value: self.src[start..end].into(),
             pos: start,
+            end,
             line,
             col,
         });
@@ -649,3 +835,30 @@ impl<'a> Lexer<'a> {
 pub fn lex(src: &str) -> Result<Vec<Token>, LexerError> {
     Lexer::new(src).run()
 }
+
+/// ソースをトークン列へ変換する、構文強調やエディタ連携向けの公開 API。
+///
+/// パーサが読むものと同じトークン列を返す。
+///
+/// - 末尾には必ず `EOF` トークンが 1 つ付き、その `pos`/`end` は入力のバイト長に等しい。
+/// - 空白と通常のコメント（`--` 行コメント・`{- -}` ブロックコメント）は読み捨て、トークンにならない。
+/// - 行頭の `-- |` で始まるドキュメントコメントだけは `DOC` トークンとして残る。
+///   直後に続く `--` 行は同じトークンへ連結され、`end` もその行の末尾まで伸びる。
+/// - `{-# LAYOUT #-}` 指定時に挿入される仮想トークン（`VLBRACE`/`VRBRACE`/`SEMI`）は
+///   幅 0（`pos == end`）なので、原文に対応する範囲だけが必要な場合は読み飛ばせる。
+///
+/// # Examples
+/// ```
+/// use typelang::{tokenize, TokenKind};
+///
+/// let tokens = tokenize("let x = 1 -- コメント").unwrap();
+/// let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind.clone()).collect();
+/// assert_eq!(
+///     kinds,
+///     [TokenKind::LET, TokenKind::VARID, TokenKind::EQUAL, TokenKind::INT, TokenKind::EOF]
+/// );
+/// assert_eq!((tokens[1].pos, tokens[1].end), (4, 5));
+/// ```
+pub fn tokenize(src: &str) -> Result<Vec<Token>, LexerError> {
+    lex(src)
+}
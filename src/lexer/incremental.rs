@@ -0,0 +1,119 @@
+// パス: src/lexer/incremental.rs
+// 役割: 編集前のトークン列を再利用し、編集箇所の周辺だけを字句解析し直す
+// 意図: エディタで 1 文字打つたびにファイル全体を字句解析し直すコストを省く
+// 関連ファイル: src/lexer.rs, tests/lexer_parser.rs
+//! 差分字句解析
+//!
+//! - 編集範囲に触れない手前のトークンはそのまま残し、その直後から新しいソースを読み直す。
+//! - 編集範囲より後ろで、編集前と同じ位置関係・種別・値のトークンが再び現れた時点で読み直しを打ち切り、
+//!   残りは編集前のトークンの位置をずらして使う。
+//! - レイアウト規則が関わる入力や、編集内容とトークン列が食い違う場合は全体を字句解析し直す。
+//!   結果は常に `lex` で全体を読み直した場合と一致させる。
+
+use std::ops::Range;
+
+use super::{lex, Lexer, Token, TokenKind};
+use crate::errors::LexerError;
+
+/// 1 回分の編集。`range` は編集前のソース上のバイト範囲で、そこが `text` に置き換わる。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+/// 編集前のトークン列 `prev` と編集 `edit` から、編集後のソース `new_src` のトークン列を求める。
+///
+/// 編集箇所に隣接するトークンは保守的に読み直すため、識別子の末尾への追記やコメント記号の挿入のように
+/// 前後のトークンと結合・分離する編集も正しく扱える。
+pub fn relex(prev: &[Token], edit: &TextEdit, new_src: &str) -> Result<Vec<Token>, LexerError> {
+    if !can_reuse(prev, edit, new_src) {
+        return lex(new_src);
+    }
+    let delta = edit.text.len() as isize - edit.range.len() as isize;
+    let new_edit_end = edit.range.start + edit.text.len();
+
+    // 編集範囲の手前で終わるトークンを残す。DOC は後続の行コメントを連結し得るため読み直す側へ回す。
+    let mut keep = prev
+        .iter()
+        .take_while(|tok| tok.end < edit.range.start && tok.kind != TokenKind::EOF)
+        .count();
+    while keep > 0 && prev[keep - 1].kind == TokenKind::DOC {
+        keep -= 1;
+    }
+    let tail = &prev[prev.partition_point(|tok| tok.pos < edit.range.end)..];
+
+    let mut lexer = Lexer::new(new_src);
+    lexer.tokens = prev[..keep].to_vec();
+    lexer.cursor = lexer.tokens.last().map_or(0, |tok| tok.end);
+    while lexer.advance_token()? {
+        let Some(fresh) = lexer.tokens.last().filter(|tok| tok.pos >= new_edit_end) else {
+            continue;
+        };
+        if let Some(index) = matching_old_token(tail, fresh, delta) {
+            let (fresh_line, fresh_col) = (fresh.line, fresh.col);
+            let anchor = &tail[index];
+            let mut tokens = lexer.tokens;
+            tokens.extend(
+                tail[index + 1..]
+                    .iter()
+                    .map(|old| shift(old, anchor, delta, fresh_line, fresh_col)),
+            );
+            return Ok(tokens);
+        }
+    }
+    lexer.push_simple(TokenKind::EOF, "", lexer.len);
+    Ok(lexer.tokens)
+}
+
+/// 差分での読み直しが安全に行えるか判定する。
+fn can_reuse(prev: &[Token], edit: &TextEdit, new_src: &str) -> bool {
+    let Some(eof) = prev.last().filter(|tok| tok.kind == TokenKind::EOF) else {
+        return false;
+    };
+    let old_len = eof.pos;
+    edit.range.start <= edit.range.end
+        && edit.range.end <= old_len
+        && old_len - edit.range.len() + edit.text.len() == new_src.len()
+        && new_src.is_char_boundary(edit.range.start)
+        // プラグマはファイル全体の字句規則を変え得るうえ、レイアウト規則の仮想トークンは
+        // 後続の字下げに依存するため、部分的には更新できない。
+        && !new_src.contains("{-#")
+        && !prev
+            .iter()
+            .any(|tok| matches!(tok.kind, TokenKind::VLBRACE | TokenKind::VRBRACE))
+}
+
+/// 読み直したトークン `fresh` と同じ種別・値で、編集前に同じ相対位置にあったトークンを探す。
+fn matching_old_token(tail: &[Token], fresh: &Token, delta: isize) -> Option<usize> {
+    if matches!(fresh.kind, TokenKind::DOC | TokenKind::EOF) {
+        return None;
+    }
+    let old_pos = fresh.pos as isize - delta;
+    let index = tail
+        .binary_search_by_key(&old_pos, |tok| tok.pos as isize)
+        .ok()?;
+    let old = &tail[index];
+    (old.kind == fresh.kind
+        && old.value == fresh.value
+        && old.end - old.pos == fresh.end - fresh.pos)
+        .then_some(index)
+}
+
+/// 同期点 `anchor` より後ろの旧トークンを編集後の位置へずらす。
+///
+/// 行は同期点と同じだけずれる。列は同期点と同じ行にあるものだけがずれ、以降の行は変わらない。
+fn shift(old: &Token, anchor: &Token, delta: isize, line: usize, col: usize) -> Token {
+    let offset = |value: usize, by: isize| (value as isize + by) as usize;
+    Token {
+        pos: offset(old.pos, delta),
+        end: offset(old.end, delta),
+        line: offset(old.line, line as isize - anchor.line as isize),
+        col: if old.line == anchor.line {
+            offset(old.col, col as isize - anchor.col as isize)
+        } else {
+            old.col
+        },
+        ..old.clone()
+    }
+}
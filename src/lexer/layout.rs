@@ -0,0 +1,127 @@
+// パス: src/lexer/layout.rs
+// 役割: `{-# LAYOUT #-}` 指定時に字下げから case アームの区切りを補うレイアウト規則
+// 意図: 明示的な `;` を書かなくても、揃えた字下げだけで case の各アームを区切れるようにする
+// 関連ファイル: src/lexer.rs, src/parser/expr.rs, EBNF.md
+//! case アームのレイアウト規則（オフサイドルール）
+//!
+//! - `of` の直後のトークンの列をブロックの基準列とし、その手前に仮想の `{`（`VLBRACE`）を挿入する。
+//! - 新しい行が基準列ちょうどから始まれば仮想の `;` を、基準列より左から始まれば仮想の `}`（`VRBRACE`）を挿入する。
+//! - 同じ行の中でも、ブロックを囲む括弧の `)`・`]`・`,` や、ブロック内に対応する `if`/`let` が無い
+//!   `then`/`else`/`in` が現れたらブロックを閉じる。
+//! - 入力の終端では開いているブロックをすべて閉じる。
+
+use super::{Token, TokenKind};
+
+/// 開いているレイアウトブロック 1 つ分の状態。
+struct Block {
+    /// アームを揃える列。
+    col: usize,
+    /// ブロックを開いた時点の括弧の深さ。
+    depth: usize,
+    /// ブロック内で `else` を待っている `if` の数。
+    open_ifs: usize,
+    /// ブロック内で `in` を待っている `let` の数。
+    open_lets: usize,
+}
+
+/// トークン列へ case アームのレイアウト用の仮想トークンを挿入する。
+pub(super) fn resolve_case_layout(tokens: Vec<Token>) -> Vec<Token> {
+    let mut out = Vec::with_capacity(tokens.len());
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut depth = 0usize;
+    let mut last_line = 0usize;
+    let mut opens_block = false;
+    for tok in tokens {
+        if tok.kind == TokenKind::EOF {
+            for _ in blocks.drain(..) {
+                out.push(virtual_token(TokenKind::VRBRACE, "}", &tok));
+            }
+            out.push(tok);
+            break;
+        }
+        if opens_block {
+            opens_block = false;
+            // トップレベルの宣言は 1 列目から始まるため、最も外側のブロックも 2 列目以降に置く。
+            let enclosing = blocks.last().map_or(1, |b| b.col);
+            out.push(virtual_token(TokenKind::VLBRACE, "{", &tok));
+            if tok.col > enclosing {
+                blocks.push(Block {
+                    col: tok.col,
+                    depth,
+                    open_ifs: 0,
+                    open_lets: 0,
+                });
+            } else {
+                // 外側のブロックより右へ字下げされていないアームは空のブロックとして閉じ、構文エラーにする。
+                out.push(virtual_token(TokenKind::VRBRACE, "}", &tok));
+            }
+        } else if tok.line > last_line {
+            while let Some(block) = blocks.last() {
+                if block.depth != depth || tok.col > block.col {
+                    break;
+                }
+                if tok.col == block.col {
+                    out.push(virtual_token(TokenKind::SEMI, ";", &tok));
+                    break;
+                }
+                blocks.pop();
+                out.push(virtual_token(TokenKind::VRBRACE, "}", &tok));
+            }
+        }
+        match tok.kind {
+            TokenKind::LPAREN | TokenKind::LBRACK => depth += 1,
+            TokenKind::RPAREN | TokenKind::RBRACK | TokenKind::COMMA => {
+                while blocks.last().is_some_and(|b| b.depth == depth) {
+                    blocks.pop();
+                    out.push(virtual_token(TokenKind::VRBRACE, "}", &tok));
+                }
+                if tok.kind != TokenKind::COMMA {
+                    depth = depth.saturating_sub(1);
+                }
+            }
+            TokenKind::IF | TokenKind::LET => {
+                if let Some(block) = blocks.last_mut().filter(|b| b.depth == depth) {
+                    if tok.kind == TokenKind::IF {
+                        block.open_ifs += 1;
+                    } else {
+                        block.open_lets += 1;
+                    }
+                }
+            }
+            TokenKind::THEN | TokenKind::ELSE | TokenKind::IN => {
+                while let Some(block) = blocks.last_mut().filter(|b| b.depth == depth) {
+                    let pending = match tok.kind {
+                        TokenKind::IN => &mut block.open_lets,
+                        _ => &mut block.open_ifs,
+                    };
+                    if *pending > 0 {
+                        // `then` の時点ではまだ `else` を待つため、対応を消費するのは `else`/`in` だけ。
+                        if tok.kind != TokenKind::THEN {
+                            *pending -= 1;
+                        }
+                        break;
+                    }
+                    blocks.pop();
+                    out.push(virtual_token(TokenKind::VRBRACE, "}", &tok));
+                }
+            }
+            TokenKind::OF => opens_block = true,
+            _ => {}
+        }
+        last_line = tok.line;
+        out.push(tok);
+    }
+    out
+}
+
+/// `next` の位置に置く仮想トークンを作る。
+fn virtual_token(kind: TokenKind, value: &str, next: &Token) -> Token {
+    Token {
+        kind,
+        value: value.into(),
+        pos: next.pos,
+        end: next.pos,
+        line: next.line,
+        col: next.col,
+    }
+}
@@ -23,27 +23,106 @@ pub mod evaluator;
 pub mod infer;
 pub(crate) mod intrinsics;
 pub mod lexer;
+pub mod lint;
 pub mod parser;
+pub mod prelude;
 pub(crate) mod primitives;
 pub mod repl;
 pub mod runtime;
 pub mod typesys;
+pub mod watch;
 
-// 便利な再エクスポート（必要最小限: 利用側からAST/エラー/パーサのみ直接参照可）
+// 便利な再エクスポート（必要最小限: 利用側からAST/エラー/パーサと、構文強調向けのトークン列 API を直接参照可）
 pub use crate::ast::*;
 pub use crate::errors::*;
 pub use crate::parser::*;
-// NOTE: `infer` と `evaluator` は曖昧な `initial_env` を持つため再エクスポートを控える。
+// `infer` と `evaluator` はどちらも `initial_env` を持つため、用途が分かる別名で公開する。
+pub use crate::evaluator::initial_env as initial_value_env;
+pub use crate::evaluator::{Env, Value};
+pub use crate::infer::initial_class_env;
+pub use crate::infer::initial_env as initial_type_env;
+pub use crate::lexer::{tokenize, Token, TokenKind};
+pub use crate::prelude::default_prelude;
+pub use crate::watch::watch_and_compile;
 
-/// AST プログラムを Core IR へ変換する。
-pub fn compile_core_ir(program: &ast::Program) -> Result<core_ir::Module, core_ir::CoreIrError> {
-    core_ir::lower::lower_program(program)
+/// 既存の値環境で式を評価する（型検査は行わない）。
+///
+/// # Examples
+/// ```
+/// use typelang::{eval_expr_in, initial_value_env, parse_expr, Value};
+///
+/// let env = initial_value_env();
+/// let expr = parse_expr("1 + 2 * 3").unwrap();
+/// assert!(matches!(eval_expr_in(&env, &expr), Ok(Value::Int(7))));
+/// ```
+pub fn eval_expr_in(env: &Env, expr: &ast::Expr) -> Result<Value, EvalError> {
+    evaluator::eval_expr(expr, env)
+}
+
+/// プログラムを新しい環境へ型検査付きで読み込み、`main` の値を返す。
+///
+/// 読み込み（型推論・評価）の失敗は `EVAL110`、`main` が無い場合は `EVAL111` になる。
+///
+/// # Examples
+/// ```
+/// use typelang::{eval_program, parse_program, Value};
+///
+/// let program = parse_program("let double x = x * 2; let main = double 21;").unwrap();
+/// assert!(matches!(eval_program(&program), Ok(Value::Int(42))));
+/// ```
+pub fn eval_program(program: &ast::Program) -> Result<Value, EvalError> {
+    let mut type_env = initial_type_env();
+    let mut class_env = initial_class_env();
+    let mut value_env = initial_value_env();
+    repl::load_program_into_env(program, &mut type_env, &mut class_env, &mut value_env)
+        .map_err(|msg| EvalError::new("EVAL110", msg, None))?;
+    value_env
+        .get("main")
+        .ok_or_else(|| EvalError::new("EVAL111", "main が定義されていません", None))
+}
+
+/// AST プログラムを Core IR へ変換する。失敗時は検出したエラーをまとめて返す。
+pub fn compile_core_ir(program: &ast::Program) -> Result<core_ir::Module, core_ir::CoreIrErrors> {
+    compile_core_ir_with_prelude(program, None)
+}
+
+/// `prelude` の宣言を前置してから Core IR へ変換する。
+///
+/// プレリュードの定義はプログラムから使われるものだけを取り込み、同名の定義はプログラム側を優先する。
+///
+/// # Examples
+/// ```
+/// use typelang::{compile_core_ir_with_prelude, default_prelude, parse_program};
+///
+/// let program = parse_program("main :: Int; let main = id 42;").unwrap();
+/// let module = compile_core_ir_with_prelude(&program, Some(&default_prelude())).unwrap();
+/// assert!(!module.functions.contains_key("flip"));
+/// ```
+pub fn compile_core_ir_with_prelude(
+    program: &ast::Program,
+    prelude: Option<&ast::Program>,
+) -> Result<core_ir::Module, core_ir::CoreIrErrors> {
+    let merged;
+    let program = match prelude {
+        Some(prelude) => {
+            merged = prelude::with_prelude(program, prelude);
+            &merged
+        }
+        None => program,
+    };
+    let mut module = core_ir::lower::lower_program(program)?;
+    core_ir::cse::eliminate_common_subexpressions(&mut module);
+    core_ir::inline::inline_small_functions(&mut module);
+    core_ir::constprop::propagate_constants(&mut module);
+    Ok(module)
 }
 
 /// ネイティブビルド時に得られるメタデータ。
 #[derive(Clone, Debug)]
 pub struct NativeBuildArtifacts {
     pub dictionaries: Vec<core_ir::DictionaryInit>,
+    /// リンク前のオブジェクトのバイト列から求めたハッシュ。同じ入力なら同じ値になる。
+    pub object_hash: u64,
 }
 
 /// AST プログラムを解析してネイティブ実行ファイルを生成する。
@@ -52,48 +131,173 @@ pub fn emit_native(
     program: &ast::Program,
     output: &std::path::Path,
 ) -> Result<NativeBuildArtifacts, codegen::NativeError> {
-    emit_native_with_options(
-        program,
-        output,
-        NativeBackend::Cranelift,
-        NativeOptimLevel::Debug,
-    )
+    emit_native_with_options(program, output, &NativeBuildOptions::default())
 }
 
+/// 既定のエントリポイント関数名。
+pub const DEFAULT_ENTRY: &str = "main";
+
 /// 利用可能なネイティブバックエンド。
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum NativeBackend {
+    #[default]
     Cranelift,
     Llvm,
 }
 
 /// コード生成時の最適化レベル指定。
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum NativeOptimLevel {
+    #[default]
     Debug,
     Fast,
     Best,
 }
 
+/// 整数の加減乗算におけるオーバーフローの扱い。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NativeArithmetic {
+    /// 2 の補数で折り返す（既定）。
+    #[default]
+    Wrapping,
+    /// オーバーフローを検出したら実行を中断する。
+    Checked,
+}
+
+/// データ値・リスト節点の確保方式。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NativeAllocation {
+    /// 値ごとに確保・解放する（既定）。
+    #[default]
+    Individual,
+    /// ランタイムの `arena` フィーチャを有効にしてバンプアリーナから確保し、`main` の終了時にまとめて破棄する。
+    Arena,
+}
+
+/// 関数呼び出しの深さの扱い。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NativeCallDepth {
+    /// 深さを数えない（既定）。深すぎる再帰は C スタックを溢れさせてクラッシュする。
+    #[default]
+    Unlimited,
+    /// 各関数の入口と出口で深さを数え、指定した深さを超えたら実行を中断する。
+    Limit(u32),
+}
+
+/// ネイティブビルドの進行状況の出力。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NativeVerbosity {
+    /// 何も出力しない（既定）。
+    #[default]
+    Quiet,
+    /// 各段階と、失敗時の診断コードを標準エラーへ出力する。
+    Verbose,
+}
+
+/// ネイティブビルドの生成オプション。既定値は `emit_native` と同じ設定になる。
+#[derive(Clone, Debug)]
+pub struct NativeBuildOptions<'a> {
+    pub backend: NativeBackend,
+    pub optim_level: NativeOptimLevel,
+    pub arithmetic: NativeArithmetic,
+    pub allocation: NativeAllocation,
+    pub call_depth: NativeCallDepth,
+    pub verbosity: NativeVerbosity,
+    /// エントリポイント関数名。
+    pub entry: String,
+    /// 渡した場合は、その宣言を前置してから Core IR へ変換する。
+    pub prelude: Option<&'a ast::Program>,
+}
+
+impl Default for NativeBuildOptions<'_> {
+    fn default() -> Self {
+        Self {
+            backend: NativeBackend::default(),
+            optim_level: NativeOptimLevel::default(),
+            arithmetic: NativeArithmetic::default(),
+            allocation: NativeAllocation::default(),
+            call_depth: NativeCallDepth::default(),
+            verbosity: NativeVerbosity::default(),
+            entry: DEFAULT_ENTRY.into(),
+            prelude: None,
+        }
+    }
+}
+
+/// 指定したバックエンドと生成オプションでネイティブ実行ファイルを生成する。
 #[allow(clippy::result_large_err)]
 pub fn emit_native_with_options(
     program: &ast::Program,
     output: &std::path::Path,
-    backend: NativeBackend,
-    optim_level: NativeOptimLevel,
+    options: &NativeBuildOptions<'_>,
 ) -> Result<NativeBuildArtifacts, codegen::NativeError> {
-    match backend {
+    let verbose = options.verbosity == NativeVerbosity::Verbose;
+    let result = match options.backend {
         NativeBackend::Cranelift => {
-            let _ = optim_level; // 現状は Cranelift 側に最適化レベルを伝搬しない
-            let mut ir = compile_core_ir(program).map_err(codegen::NativeError::from)?;
-            codegen::dictionary_codegen::assign_missing_builders(&mut ir.dictionaries)?;
-            let dictionaries = ir.dictionaries.clone();
-            codegen::cranelift::emit_native(&ir, output)?;
-            Ok(NativeBuildArtifacts { dictionaries })
+            // 現状は Cranelift 側に最適化レベル (`optim_level`) を伝搬しない
+            let codegen_options = codegen::cranelift::CodegenOptions {
+                checked_arithmetic: options.arithmetic == NativeArithmetic::Checked,
+                arena_allocation: options.allocation == NativeAllocation::Arena,
+                max_call_depth: match options.call_depth {
+                    NativeCallDepth::Unlimited => None,
+                    NativeCallDepth::Limit(limit) => Some(limit),
+                },
+                verbose,
+            };
+            emit_native_cranelift(
+                program,
+                output,
+                &options.entry,
+                options.prelude,
+                &codegen_options,
+            )
         }
         NativeBackend::Llvm => Err(codegen::NativeError::unsupported(
             "CODEGEN900",
             "LLVM backend はまだ実装されていません",
         )),
+    };
+    if verbose {
+        if let Err(err) = &result {
+            eprintln!(
+                "[typelang native] failed with {}: {err}",
+                err.code().unwrap_or("(no code)")
+            );
+        }
+    }
+    result
+}
+
+#[allow(clippy::result_large_err)]
+fn emit_native_cranelift(
+    program: &ast::Program,
+    output: &std::path::Path,
+    entry: &str,
+    prelude: Option<&ast::Program>,
+    options: &codegen::cranelift::CodegenOptions,
+) -> Result<NativeBuildArtifacts, codegen::NativeError> {
+    let mut ir =
+        compile_core_ir_with_prelude(program, prelude).map_err(codegen::NativeError::from)?;
+    if !ir.functions.contains_key(entry) {
+        return Err(codegen::NativeError::unsupported(
+            "CODEGEN001",
+            format!("エントリポイント関数 ({entry}) が見つかりません"),
+        ));
     }
+    ir.set_entry(entry);
+    codegen::cranelift::trace(
+        options,
+        format_args!(
+            "core ir: {} functions, {} dictionaries",
+            ir.functions.len(),
+            ir.dictionaries.len()
+        ),
+    );
+    codegen::dictionary_codegen::assign_missing_builders(&mut ir.dictionaries)?;
+    let dictionaries = ir.dictionaries.clone();
+    let object_hash = codegen::cranelift::emit_native_with(&ir, output, options)?;
+    Ok(NativeBuildArtifacts {
+        dictionaries,
+        object_hash,
+    })
 }
@@ -0,0 +1,348 @@
+// パス: src/lint.rs
+// 役割: 型検査後の AST を走査し、未使用の束縛を警告として収集する
+// 意図: 実行を止めずに綴り間違いや消し忘れなどの不注意を利用者へ知らせる
+// 関連ファイル: src/ast.rs, src/repl/cmd.rs, src/repl/loader.rs
+//! 未使用束縛の警告パス
+//!
+//! - スコープを正しく辿り、シャドーイングされた名前を外側の束縛と取り違えない。
+//! - 自分自身の本体からの再帰参照だけでは「使用済み」とみなさない。
+//! - case のパターン変数が外側の局所束縛を隠す場合も警告する。
+//! - 浮動小数リテラルのパターンは厳密比較になるため注意を促す。
+//! - トップレベル定義の未使用警告は `main` を持つプログラムに限る
+//!   （`main` が無いファイルは REPL から使うライブラリとして扱う）。
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::ast as A;
+
+/// 警告 1 件分の情報。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    pub code: &'static str,
+    pub message: String,
+    pub span: A::Span,
+}
+
+impl fmt::Display for Warning {
+    /// `警告 [CODE] message @line=..,col=..` の形式で文字列化する。
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.span.line > 0 {
+            write!(
+                f,
+                "警告 [{}] {} @line={},col={}",
+                self.code, self.message, self.span.line, self.span.col
+            )
+        } else {
+            write!(f, "警告 [{}] {}", self.code, self.message)
+        }
+    }
+}
+
+/// プログラム全体の未使用トップレベル定義・局所 `let`・引数と、パターンによる隠蔽を検出する。
+pub fn lint_program(prog: &A::Program) -> Vec<Warning> {
+    let mut linter = Linter::default();
+    for decl in &prog.decls {
+        linter.current_top = Some(decl.name.clone());
+        let mark = linter.push_params(&decl.params, decl.expr.span());
+        linter.walk(&decl.expr);
+        linter.pop_to(mark);
+    }
+    linter.current_top = None;
+
+    if prog.decls.iter().any(|decl| decl.name == "main") {
+        for decl in &prog.decls {
+            if decl.name != "main" && !linter.globals.contains(&decl.name) {
+                linter.warnings.push(Warning {
+                    code: "LINT001",
+                    message: format!("トップレベル定義 {} は使われていません", decl.name),
+                    span: decl.expr.span(),
+                });
+            }
+        }
+    }
+
+    let mut warnings = linter.warnings;
+    warnings.sort_by_key(|w| (w.span.line, w.span.col));
+    warnings
+}
+
+/// 単一の式について未使用の局所 `let` と引数を検出する。
+pub fn lint_expr(expr: &A::Expr) -> Vec<Warning> {
+    let mut linter = Linter::default();
+    linter.walk(expr);
+    let mut warnings = linter.warnings;
+    warnings.sort_by_key(|w| (w.span.line, w.span.col));
+    warnings
+}
+
+/// トップレベル定義が本体から参照する、局所束縛で解決されない名前を集める。自己再帰は含めない。
+pub(crate) fn top_level_references(decl: &A::TopLevel) -> HashSet<String> {
+    let mut linter = Linter {
+        current_top: Some(decl.name.clone()),
+        ..Linter::default()
+    };
+    let mark = linter.push_params(&decl.params, decl.expr.span());
+    linter.walk(&decl.expr);
+    linter.pop_to(mark);
+    linter.globals
+}
+
+/// `params` を引数とする本体 `expr` が参照する、局所束縛で解決されない名前を集める。
+///
+/// ラムダ・`case`・`let` の束縛による隠蔽を考慮する（`\x -> x` の `x` は含めない）。
+pub(crate) fn free_names(params: &[String], expr: &A::Expr) -> HashSet<String> {
+    let mut linter = Linter::default();
+    let mark = linter.push_params(params, expr.span());
+    linter.walk(expr);
+    linter.pop_to(mark);
+    linter.globals
+}
+
+/// スコープ上の 1 束縛。`report` が `None` のもの（パターン変数など）は警告対象外。
+struct Binder {
+    name: String,
+    used: bool,
+    report: Option<(&'static str, A::Span)>,
+}
+
+#[derive(Default)]
+struct Linter {
+    scope: Vec<Binder>,
+    /// 現在右辺を走査中の `let` 束縛（スコープ上の添字）。自己参照を使用扱いしない。
+    defining: Vec<usize>,
+    /// 走査中のトップレベル定義名。自己再帰を使用扱いしない。
+    current_top: Option<String>,
+    /// 局所束縛で解決されなかった（= トップレベルを指す）参照。
+    globals: HashSet<String>,
+    warnings: Vec<Warning>,
+}
+
+impl Linter {
+    fn push(&mut self, name: &str, report: Option<(&'static str, A::Span)>) {
+        self.scope.push(Binder {
+            name: name.to_string(),
+            used: false,
+            report,
+        });
+    }
+
+    fn push_params(&mut self, params: &[String], span: A::Span) -> usize {
+        let mark = self.scope.len();
+        for param in params {
+            self.push(param, Some(("LINT003", span)));
+        }
+        mark
+    }
+
+    /// `mark` より上の束縛を取り除き、未使用のものを警告へ変換する。
+    fn pop_to(&mut self, mark: usize) {
+        for binder in self.scope.drain(mark..).rev() {
+            let Some((code, span)) = binder.report else {
+                continue;
+            };
+            if binder.used {
+                continue;
+            }
+            let message = match code {
+                "LINT002" => format!("let 束縛 {} は使われていません", binder.name),
+                _ => format!("引数 {} は使われていません", binder.name),
+            };
+            self.warnings.push(Warning {
+                code,
+                message,
+                span,
+            });
+        }
+    }
+
+    fn resolve(&mut self, name: &str) {
+        match self.scope.iter().rposition(|b| b.name == name) {
+            Some(idx) if self.defining.contains(&idx) => {}
+            Some(idx) => self.scope[idx].used = true,
+            None => {
+                if self.current_top.as_deref() != Some(name) {
+                    self.globals.insert(name.to_string());
+                }
+            }
+        }
+    }
+
+    fn walk(&mut self, expr: &A::Expr) {
+        match expr {
+            A::Expr::Var { name, .. } => self.resolve(name),
+            A::Expr::IntLit { .. }
+            | A::Expr::FloatLit { .. }
+            | A::Expr::CharLit { .. }
+            | A::Expr::StringLit { .. }
+            | A::Expr::BoolLit { .. } => {}
+            A::Expr::ListLit { items, .. } | A::Expr::TupleLit { items, .. } => {
+                for item in items {
+                    self.walk(item);
+                }
+            }
+            A::Expr::Lambda { params, body, span } => {
+                let mark = self.push_params(params, *span);
+                self.walk(body);
+                self.pop_to(mark);
+            }
+            A::Expr::LetIn { bindings, body, .. } => {
+                // let は再帰的なので、全束縛を先にスコープへ入れてから右辺を辿る。
+                let mark = self.scope.len();
+                for (name, _, rhs) in bindings {
+                    self.push(name, Some(("LINT002", rhs.span())));
+                }
+                for (offset, (_, params, rhs)) in bindings.iter().enumerate() {
+                    self.defining.push(mark + offset);
+                    let params_mark = self.push_params(params, rhs.span());
+                    self.walk(rhs);
+                    self.pop_to(params_mark);
+                    self.defining.pop();
+                }
+                self.walk(body);
+                self.pop_to(mark);
+            }
+            A::Expr::If {
+                cond,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.walk(cond);
+                self.walk(then_branch);
+                self.walk(else_branch);
+            }
+            A::Expr::App { func, arg, .. } => {
+                self.walk(func);
+                self.walk(arg);
+            }
+            A::Expr::BinOp {
+                op, left, right, ..
+            } => {
+                self.resolve(op);
+                self.walk(left);
+                self.walk(right);
+            }
+            A::Expr::Annot { expr, .. } => self.walk(expr),
+            A::Expr::Case {
+                scrutinee, arms, ..
+            } => {
+                self.walk(scrutinee);
+                for arm in arms {
+                    let mark = self.scope.len();
+                    self.push_pattern(&arm.pattern);
+                    if let Some(guard) = &arm.guard {
+                        self.walk(guard);
+                    }
+                    self.walk(&arm.body);
+                    self.pop_to(mark);
+                }
+            }
+        }
+    }
+
+    /// パターン変数を積む。外側の局所束縛を隠す場合は警告する。
+    fn push_pattern_binder(&mut self, name: &str, span: A::Span) {
+        if self.scope.iter().any(|b| b.name == name) {
+            self.warnings.push(Warning {
+                code: "LINT004",
+                message: format!("パターン変数 {name} が外側の束縛を隠しています"),
+                span,
+            });
+        }
+        self.push(name, None);
+    }
+
+    fn push_pattern(&mut self, pattern: &A::Pattern) {
+        match pattern {
+            A::Pattern::Var { name, span } => self.push_pattern_binder(name, *span),
+            A::Pattern::As {
+                binder,
+                pattern,
+                span,
+            } => {
+                self.push_pattern_binder(binder, *span);
+                self.push_pattern(pattern);
+            }
+            A::Pattern::List { items, .. } | A::Pattern::Tuple { items, .. } => {
+                for item in items {
+                    self.push_pattern(item);
+                }
+            }
+            A::Pattern::Constructor { args, .. } => {
+                for arg in args {
+                    self.push_pattern(arg);
+                }
+            }
+            A::Pattern::Float { value, span } => self.warnings.push(Warning {
+                code: "LINT005",
+                message: format!("浮動小数パターン {value} は値を厳密に比較します"),
+                span: *span,
+            }),
+            A::Pattern::Wildcard { .. }
+            | A::Pattern::Int { .. }
+            | A::Pattern::Char { .. }
+            | A::Pattern::String { .. }
+            | A::Pattern::Bool { .. } => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lint_expr, lint_program};
+    use crate::parser::{parse_expr, parse_program};
+
+    fn codes_for_expr(src: &str) -> Vec<&'static str> {
+        lint_expr(&parse_expr(src).unwrap())
+            .into_iter()
+            .map(|w| w.code)
+            .collect()
+    }
+
+    #[test]
+    fn unused_let_and_params_are_reported() {
+        assert_eq!(codes_for_expr("let x = 1; y = 2 in x"), vec!["LINT002"]);
+        assert_eq!(codes_for_expr("\\a b -> a"), vec!["LINT003"]);
+        assert!(codes_for_expr("\\a -> \\b -> a + b").is_empty());
+    }
+
+    #[test]
+    fn shadowing_and_recursion_are_resolved_by_scope() {
+        // 内側の x が外側の x を隠すので、外側は未使用。
+        assert_eq!(
+            codes_for_expr("let x = 1 in let x = 2 in x"),
+            vec!["LINT002"]
+        );
+        // case のパターン変数は外側の束縛を隠す（隠された側は未使用になる）。
+        assert_eq!(
+            codes_for_expr("let y = 1 in case 2 of y -> y"),
+            vec!["LINT002", "LINT004"]
+        );
+        assert!(codes_for_expr("\\p -> case p of (a, b) -> a + b").is_empty());
+        // 自己再帰だけでは使用済みとみなさない。
+        assert_eq!(codes_for_expr("let f n = f n in 0"), vec!["LINT002"]);
+        assert!(codes_for_expr("let f n = if n == 0 then 0 else f (n - 1) in f 3").is_empty());
+    }
+
+    #[test]
+    fn float_patterns_warn_about_exact_comparison() {
+        assert_eq!(
+            codes_for_expr("\\d -> case d of -0.5 -> 1; _ -> 0"),
+            vec!["LINT005"]
+        );
+        assert!(codes_for_expr("\\n -> case n of -1 -> 1; _ -> 0").is_empty());
+    }
+
+    #[test]
+    fn unused_top_level_requires_main() {
+        let lib = parse_program("let helper x = x; let other = 1;").unwrap();
+        assert!(lint_program(&lib).is_empty());
+
+        let prog = parse_program("let helper x = x; let other = 1; let main = helper 2;").unwrap();
+        let warnings = lint_program(&prog);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, "LINT001");
+        assert!(warnings[0].message.contains("other"));
+    }
+}
@@ -26,7 +26,7 @@ impl Parser {
             TokenKind::LET => self.parse_let_in(),
             TokenKind::IF => self.parse_if(),
             TokenKind::CASE => self.parse_case(),
-            _ => self.parse_infix_level(0),
+            _ => self.parse_infix(0),
         }
     }
 
@@ -49,7 +49,7 @@ impl Parser {
         let let_tok = self.pop(TokenKind::LET)?;
         let mut bindings = Vec::new();
         loop {
-            let name_tok = self.pop(TokenKind::VARID)?;
+            let name_tok = self.pop_binder_name()?;
             let mut params = Vec::new();
             while self.peek().kind == TokenKind::VARID {
                 params.push(self.pop_any().value);
@@ -92,6 +92,9 @@ impl Parser {
         let case_tok = self.pop(TokenKind::CASE)?;
         let scrutinee = self.parse_expr()?;
         self.pop(TokenKind::OF)?;
+        let layout = self.accept(TokenKind::VLBRACE).is_some();
+        // レイアウト規則の仮想 `;` は、明示的な `;` と同様にアームの区切りとして扱う。
+        while layout && self.accept(TokenKind::SEMI).is_some() {}
         if !Self::is_pattern_start(&self.peek().kind) {
             let t = self.peek().clone();
             return Err(ParseError::at(
@@ -104,19 +107,18 @@ impl Parser {
         }
         let mut arms = Vec::new();
         loop {
-            let pat = self.parse_pattern()?;
-            let guard = if self.accept(TokenKind::BAR).is_some() {
-                Some(self.parse_expr()?)
-            } else {
-                None
-            };
-            self.pop(TokenKind::ARROW)?;
-            let body = self.parse_expr()?;
-            arms.push(CaseArm {
-                pattern: pat,
-                guard,
-                body,
-            });
+            arms.push(self.parse_case_arm()?);
+            if layout {
+                let mut separated = false;
+                while self.accept(TokenKind::SEMI).is_some() {
+                    separated = true;
+                }
+                if !separated || self.peek().kind == TokenKind::VRBRACE {
+                    self.pop(TokenKind::VRBRACE)?;
+                    break;
+                }
+                continue;
+            }
             if self.accept(TokenKind::SEMI).is_some() && Self::is_pattern_start(&self.peek().kind) {
                 continue;
             }
@@ -129,6 +131,23 @@ impl Parser {
         })
     }
 
+    /// `pat [| guard] -> body` 形式のアームを 1 つ解析する。
+    fn parse_case_arm(&mut self) -> Result<CaseArm, ParseError> {
+        let pattern = self.parse_pattern()?;
+        let guard = if self.accept(TokenKind::BAR).is_some() {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+        self.pop(TokenKind::ARROW)?;
+        let body = self.parse_expr()?;
+        Ok(CaseArm {
+            pattern,
+            guard,
+            body,
+        })
+    }
+
     fn parse_pattern(&mut self) -> Result<Pattern, ParseError> {
         if self.peek().kind == TokenKind::VARID && matches!(self.peek_kind(1), Some(TokenKind::AT))
         {
@@ -188,6 +207,37 @@ impl Parser {
                 let name = tok.value;
                 Ok(Pattern::Var { name, span })
             }
+            TokenKind::MINUS => {
+                self.pop_any();
+                // 負の数値リテラルパターン。`-` の直後には数値リテラルだけを許す。
+                let next = self.peek().clone();
+                let span = span_from_token(&tok);
+                match next.kind {
+                    TokenKind::INT
+                    | TokenKind::HEX
+                    | TokenKind::OCT
+                    | TokenKind::BIN
+                    | TokenKind::FLOAT => match self.parse_pattern_atom()? {
+                        Pattern::Int { value, base, .. } => Ok(Pattern::Int {
+                            value: value.wrapping_neg(),
+                            base,
+                            span,
+                        }),
+                        Pattern::Float { value, .. } => Ok(Pattern::Float {
+                            value: -value,
+                            span,
+                        }),
+                        other => Ok(other),
+                    },
+                    _ => Err(ParseError::at(
+                        "PAR302",
+                        "パターン中の '-' の後には数値リテラルが必要です",
+                        Some(next.pos),
+                        Some(next.line),
+                        Some(next.col),
+                    )),
+                }
+            }
             TokenKind::INT | TokenKind::HEX | TokenKind::OCT | TokenKind::BIN => {
                 self.pop_any();
                 let (value, base) = Self::parse_int_value(&tok)?;
@@ -211,13 +261,13 @@ impl Parser {
             TokenKind::CHAR => {
                 self.pop_any();
                 let span = span_from_token(&tok);
-                let ch = decode_char(&tok.value)?;
+                let ch = decode_char(&tok)?;
                 Ok(Pattern::Char { value: ch, span })
             }
             TokenKind::STRING => {
                 self.pop_any();
                 let span = span_from_token(&tok);
-                let s = decode_string(&tok.value)?;
+                let s = decode_string(&tok)?;
                 Ok(Pattern::String { value: s, span })
             }
             TokenKind::TRUE => {
@@ -284,39 +334,48 @@ impl Parser {
         }
     }
 
-    fn parse_infix_level(&mut self, level: usize) -> Result<Expr, ParseError> {
-        if level >= INFIX_LEVELS.len() {
-            return self.parse_app();
-        }
-        let spec = &INFIX_LEVELS[level];
-        let mut left = self.parse_infix_level(level + 1)?;
-        match spec.assoc {
-            Assoc::Left => {
-                while spec.contains(&self.peek().kind) {
-                    let op_token = self.pop_any();
-                    let right = self.parse_infix_level(level + 1)?;
-                    left = Self::mk_binop(left, op_token, right);
-                }
-                Ok(left)
-            }
-            Assoc::Right => {
-                if spec.contains(&self.peek().kind) {
-                    let op_token = self.pop_any();
-                    let right = self.parse_infix_level(level)?;
-                    Ok(Self::mk_binop(left, op_token, right))
-                } else {
-                    Ok(left)
-                }
-            }
-            Assoc::Non => {
-                if spec.contains(&self.peek().kind) {
-                    let op_token = self.pop_any();
-                    let right = self.parse_infix_level(level + 1)?;
-                    Ok(Self::mk_binop(left, op_token, right))
-                } else {
-                    Ok(left)
-                }
+    /// 結合性テーブルに従う優先順位上昇法で中置式を解析する。
+    fn parse_infix(&mut self, min_prec: u8) -> Result<Expr, ParseError> {
+        let mut left = self.parse_app()?;
+        // 直前に結合した非結合演算子の優先順位。同じ優先順位での連鎖は受け付けない。
+        let mut non_assoc_prec: Option<u8> = None;
+        while let Some(op) = self.peek_infix_operator() {
+            let fixity = self.fixities.lookup(&op.token.value);
+            if fixity.precedence < min_prec || non_assoc_prec == Some(fixity.precedence) {
+                break;
             }
+            self.skip_infix_operator(&op);
+            let next_min = match fixity.assoc {
+                Associativity::Right => fixity.precedence,
+                Associativity::Left | Associativity::None => fixity.precedence + 1,
+            };
+            let right = self.parse_infix(next_min)?;
+            left = if op.backtick {
+                Self::mk_backtick_app(left, op.token, right)
+            } else {
+                Self::mk_binop(left, op.token, right)
+            };
+            non_assoc_prec = (fixity.assoc == Associativity::None).then_some(fixity.precedence);
+        }
+        Ok(left)
+    }
+
+    /// `` x `f` y `` を `f x y` の関数適用へ展開する。
+    fn mk_backtick_app(left: Expr, name_token: Token, right: Expr) -> Expr {
+        let span = span_from_token(&name_token);
+        let func = Expr::Var {
+            name: name_token.value,
+            span,
+        };
+        let partial = Expr::App {
+            func: Box::new(func),
+            arg: Box::new(left),
+            span,
+        };
+        Expr::App {
+            func: Box::new(partial),
+            arg: Box::new(right),
+            span,
         }
     }
 
@@ -351,15 +410,25 @@ impl Parser {
             TokenKind::MINUS => {
                 self.pop_any();
                 let rhs = self.parse_atom()?;
-                Ok(Expr::BinOp {
-                    op: "-".into(),
-                    left: Box::new(Expr::IntLit {
-                        value: 0,
-                        base: IntBase::Dec,
-                        span: Span::dummy(),
-                    }),
-                    right: Box::new(rhs),
-                    span: span_from_token(&t),
+                // 数値リテラルは負のリテラルへ畳み込み、それ以外は `negate` の適用にする。
+                Ok(match rhs {
+                    Expr::IntLit { value, base, .. } => Expr::IntLit {
+                        value: value.wrapping_neg(),
+                        base,
+                        span: span_from_token(&t),
+                    },
+                    Expr::FloatLit { value, .. } => Expr::FloatLit {
+                        value: -value,
+                        span: span_from_token(&t),
+                    },
+                    other => Expr::App {
+                        func: Box::new(Expr::Var {
+                            name: "negate".into(),
+                            span: span_from_token(&t),
+                        }),
+                        arg: Box::new(other),
+                        span: span_from_token(&t),
+                    },
                 })
             }
             TokenKind::INT | TokenKind::HEX | TokenKind::OCT | TokenKind::BIN => {
@@ -389,7 +458,7 @@ impl Parser {
             }
             TokenKind::CHAR => {
                 self.pop_any();
-                let ch = decode_char(&t.value)?;
+                let ch = decode_char(&t)?;
                 Ok(Expr::CharLit {
                     value: ch,
                     span: span_from_token(&t),
@@ -397,7 +466,7 @@ impl Parser {
             }
             TokenKind::STRING => {
                 self.pop_any();
-                let s = decode_string(&t.value)?;
+                let s = decode_string(&t)?;
                 Ok(Expr::StringLit {
                     value: s,
                     span: span_from_token(&t),
@@ -445,7 +514,19 @@ impl Parser {
                 })
             }
             TokenKind::LPAREN => {
+                if let Some(op) = self.accept_paren_operator(false) {
+                    return Ok(Expr::Var {
+                        name: op.value,
+                        span: span_from_token(&t),
+                    });
+                }
                 self.pop_any();
+                if self.accept(TokenKind::RPAREN).is_some() {
+                    return Ok(Expr::TupleLit {
+                        items: Vec::new(),
+                        span: span_from_token(&t),
+                    });
+                }
                 let expr = self.parse_expr()?;
                 if self.accept(TokenKind::COMMA).is_some() {
                     let mut items = vec![expr, self.parse_expr()?];
@@ -466,7 +547,20 @@ impl Parser {
                 self.pop_any();
                 let mut items = Vec::new();
                 if self.peek().kind != TokenKind::RBRACK {
-                    items.push(self.parse_expr()?);
+                    let first = self.parse_expr()?;
+                    // `[x..]` は上限のない範囲。`enumFrom x` の適用へ脱糖する。
+                    if self.accept(TokenKind::DOTDOT).is_some() {
+                        self.pop(TokenKind::RBRACK)?;
+                        return Ok(Expr::App {
+                            func: Box::new(Expr::Var {
+                                name: "enumFrom".into(),
+                                span: span_from_token(&t),
+                            }),
+                            arg: Box::new(first),
+                            span: span_from_token(&t),
+                        });
+                    }
+                    items.push(first);
                     while self.accept(TokenKind::COMMA).is_some() {
                         items.push(self.parse_expr()?);
                     }
@@ -477,10 +571,10 @@ impl Parser {
                     span: span_from_token(&t),
                 })
             }
-            _ => Err(ParseError::new(
+            _ => Err(error_at_token(
                 "PAR002",
                 format!("不正なトークン: {:?} {}", t.kind, t.value),
-                Some(t.pos),
+                &t,
             )),
         }
     }
@@ -512,6 +606,7 @@ impl Parser {
             TokenKind::UNDERSCORE
                 | TokenKind::VARID
                 | TokenKind::CONID
+                | TokenKind::MINUS
                 | TokenKind::INT
                 | TokenKind::HEX
                 | TokenKind::OCT
@@ -543,11 +638,7 @@ impl Parser {
                 };
                 Ok((value, base))
             }
-            Err(_) => Err(ParseError::new(
-                "PAR210",
-                "整数リテラルが範囲外",
-                Some(token.pos),
-            )),
+            Err(_) => Err(error_at_token("PAR210", "整数リテラルが範囲外", token)),
         }
     }
 }
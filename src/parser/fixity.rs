@@ -0,0 +1,107 @@
+// パス: src/parser/fixity.rs
+// 役割: 中置演算子の優先順位と結合性を保持する結合性テーブルを提供する
+// 意図: 固定の優先順位表ではなく、`infixl`/`infixr`/`infix` 宣言で更新できる表を式パーサへ渡す
+// 関連ファイル: src/parser/expr.rs, src/parser/program.rs, src/ast.rs
+//! 結合性テーブル
+//!
+//! - 組み込み演算子は Haskell の Prelude に倣った既定値で初期化する。
+//! - 宣言されていない利用者定義演算子と `` `name` `` 形式の中置適用は `infixl 9` として扱う。
+
+use std::collections::HashMap;
+
+use crate::ast::{Associativity, FixityDecl};
+
+/// 演算子 1 つ分の優先順位（0〜9、大きいほど強く結合する）と結合性。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fixity {
+    pub assoc: Associativity,
+    pub precedence: u8,
+}
+
+impl Fixity {
+    const fn new(assoc: Associativity, precedence: u8) -> Self {
+        Self { assoc, precedence }
+    }
+}
+
+/// 宣言が無い演算子に適用する既定の結合性。
+const DEFAULT_FIXITY: Fixity = Fixity::new(Associativity::Left, 9);
+
+const BUILTIN_FIXITIES: &[(&str, Fixity)] = &[
+    ("||", Fixity::new(Associativity::Right, 2)),
+    ("&&", Fixity::new(Associativity::Right, 3)),
+    ("==", Fixity::new(Associativity::None, 4)),
+    ("/=", Fixity::new(Associativity::None, 4)),
+    ("<", Fixity::new(Associativity::None, 4)),
+    ("<=", Fixity::new(Associativity::None, 4)),
+    (">", Fixity::new(Associativity::None, 4)),
+    (">=", Fixity::new(Associativity::None, 4)),
+    (".|.", Fixity::new(Associativity::Left, 5)),
+    ("<>", Fixity::new(Associativity::Right, 6)),
+    ("+", Fixity::new(Associativity::Left, 6)),
+    ("-", Fixity::new(Associativity::Left, 6)),
+    ("*", Fixity::new(Associativity::Left, 7)),
+    ("/", Fixity::new(Associativity::Left, 7)),
+    (".&.", Fixity::new(Associativity::Left, 7)),
+    ("div", Fixity::new(Associativity::Left, 7)),
+    ("mod", Fixity::new(Associativity::Left, 7)),
+    ("quot", Fixity::new(Associativity::Left, 7)),
+    ("rem", Fixity::new(Associativity::Left, 7)),
+    ("^", Fixity::new(Associativity::Right, 8)),
+    ("**", Fixity::new(Associativity::Right, 8)),
+    ("!", Fixity::new(Associativity::Left, 9)),
+];
+
+/// 演算子名から結合性を引く表。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixityTable {
+    entries: HashMap<String, Fixity>,
+}
+
+impl Default for FixityTable {
+    fn default() -> Self {
+        Self {
+            entries: BUILTIN_FIXITIES
+                .iter()
+                .map(|(op, fixity)| ((*op).to_string(), *fixity))
+                .collect(),
+        }
+    }
+}
+
+impl FixityTable {
+    /// 演算子の結合性を返す。未宣言なら `infixl 9`。
+    pub fn lookup(&self, op: &str) -> Fixity {
+        self.entries.get(op).copied().unwrap_or(DEFAULT_FIXITY)
+    }
+
+    /// 結合性宣言を表へ反映する。後から宣言したものが優先される。
+    pub fn declare(&mut self, decl: &FixityDecl) {
+        let fixity = Fixity::new(decl.assoc, decl.precedence);
+        for op in &decl.operators {
+            self.entries.insert(op.clone(), fixity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Span;
+
+    #[test]
+    fn declarations_override_builtin_and_default_fixities() {
+        let mut table = FixityTable::default();
+        assert_eq!(table.lookup("+"), Fixity::new(Associativity::Left, 6));
+        assert_eq!(table.lookup("|>"), DEFAULT_FIXITY);
+
+        table.declare(&FixityDecl {
+            assoc: Associativity::Right,
+            precedence: 0,
+            operators: vec!["|>".into(), "+".into()],
+            span: Span::dummy(),
+        });
+        assert_eq!(table.lookup("|>"), Fixity::new(Associativity::Right, 0));
+        assert_eq!(table.lookup("+"), Fixity::new(Associativity::Right, 0));
+    }
+}
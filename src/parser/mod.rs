@@ -5,73 +5,87 @@
 //! 構文解析モジュール
 //!
 //! - EBNF で定義された文法に従ってプログラム・式・型注釈を解析する。
-//! - 演算子の結合規則・優先順位は `cmp > add > mul > pow > app` の順でハンドコードする。
-//! - 単項マイナスなどの糖衣は `0 - x` など正規化した AST へ変換する。
+//! - 演算子の結合規則・優先順位は結合性テーブルを引く優先順位上昇法で解析し、`infixl`/`infixr`/`infix` 宣言で変更できる。
+//! - 単項マイナスは数値リテラルなら負のリテラルへ畳み込み、それ以外は `negate x` の適用へ変換する。
 
 use crate::ast::{
-    CaseArm, Constraint as AConstraint, DataConstructor, DataDecl, Expr, IntBase, Pattern, Program,
-    SigmaType, Span, TopLevel, TypeExpr,
+    Associativity, CaseArm, Constraint as AConstraint, DataConstructor, DataDecl, Expr, IntBase,
+    Pattern, Program, SigmaType, Span, TopLevel, TypeExpr,
 };
-use crate::errors::ParseError;
-use crate::lexer::{lex, Token, TokenKind};
+use std::collections::HashMap;
+
+use crate::errors::{LexerError, ParseError, ParseErrors};
+use crate::lexer::{lex, Token, TokenKind, RAW_STRING_DELIMITER};
 
 mod expr;
+mod fixity;
 mod program;
+mod synonyms;
 mod types;
 
+pub use fixity::{Fixity, FixityTable};
+
 pub struct Parser {
     ts: Vec<Token>,
     i: usize,
+    fixities: FixityTable,
+    /// ドキュメントコメント。キーは直後に続くトークンの位置。
+    docs: HashMap<usize, String>,
 }
 
-#[derive(Clone, Copy)]
-pub(super) enum Assoc {
-    Left,
-    Right,
-    Non,
-}
-
-pub(super) struct InfixSpec {
-    pub tokens: &'static [TokenKind],
-    pub assoc: Assoc,
-}
+/// `(+)` のように括弧で囲んで関数として参照できる演算子トークン。中置演算子としても扱う。
+const SECTIONABLE_OPERATORS: &[TokenKind] = &[
+    TokenKind::OPERATOR,
+    TokenKind::PLUS,
+    TokenKind::MINUS,
+    TokenKind::STAR,
+    TokenKind::SLASH,
+    TokenKind::CARET,
+    TokenKind::DBLSTAR,
+    TokenKind::BITAND,
+    TokenKind::BITOR,
+    TokenKind::EQ,
+    TokenKind::NE,
+    TokenKind::LT,
+    TokenKind::LE,
+    TokenKind::GT,
+    TokenKind::GE,
+];
 
-impl InfixSpec {
-    pub(super) fn contains(&self, kind: &TokenKind) -> bool {
-        self.tokens.iter().any(|tk| tk == kind)
-    }
+/// 中置位置に現れた演算子。`backtick` は `` `div` `` 形式の関数名であることを表す。
+pub(super) struct InfixOperator {
+    pub token: Token,
+    pub backtick: bool,
 }
 
-pub(super) const INFIX_LEVELS: &[InfixSpec] = &[
-    InfixSpec {
-        tokens: &[
-            TokenKind::EQ,
-            TokenKind::NE,
-            TokenKind::LT,
-            TokenKind::LE,
-            TokenKind::GT,
-            TokenKind::GE,
-        ],
-        assoc: Assoc::Non,
-    },
-    InfixSpec {
-        tokens: &[TokenKind::PLUS, TokenKind::MINUS],
-        assoc: Assoc::Left,
-    },
-    InfixSpec {
-        tokens: &[TokenKind::STAR, TokenKind::SLASH],
-        assoc: Assoc::Left,
-    },
-    InfixSpec {
-        tokens: &[TokenKind::CARET, TokenKind::DBLSTAR],
-        assoc: Assoc::Right,
-    },
-];
-
 impl Parser {
     /// トークン列から新しいパーサインスタンスを構築する。
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { ts: tokens, i: 0 }
+        Self::with_fixities(tokens, FixityTable::default())
+    }
+
+    /// 既存の結合性テーブルを引き継いでパーサを構築する。
+    pub fn with_fixities(tokens: Vec<Token>, fixities: FixityTable) -> Self {
+        let mut ts = Vec::with_capacity(tokens.len());
+        let mut docs: HashMap<usize, String> = HashMap::new();
+        for tok in tokens {
+            if tok.kind == TokenKind::DOC {
+                docs.entry(ts.len())
+                    .and_modify(|doc| {
+                        doc.push('\n');
+                        doc.push_str(&tok.value);
+                    })
+                    .or_insert(tok.value);
+            } else {
+                ts.push(tok);
+            }
+        }
+        Self {
+            ts,
+            i: 0,
+            fixities,
+            docs,
+        }
     }
 
     pub(super) fn peek(&self) -> &Token {
@@ -103,6 +117,57 @@ impl Parser {
         Ok(t)
     }
 
+    /// `(op)` 形式の演算子名を読み取る。`definable` のときはユーザー定義演算子だけを受け付ける。
+    pub(super) fn accept_paren_operator(&mut self, definable: bool) -> Option<Token> {
+        if self.peek().kind != TokenKind::LPAREN || self.peek_kind(2) != Some(TokenKind::RPAREN) {
+            return None;
+        }
+        let op_kind = self.peek_kind(1)?;
+        let allowed = if definable {
+            op_kind == TokenKind::OPERATOR
+        } else {
+            SECTIONABLE_OPERATORS.contains(&op_kind)
+        };
+        if !allowed {
+            return None;
+        }
+        self.pop_any();
+        let op = self.pop_any();
+        self.pop_any();
+        Some(op)
+    }
+
+    /// 中置演算子（記号演算子または `` `name` ``）を先読みする。位置は進めない。
+    pub(super) fn peek_infix_operator(&self) -> Option<InfixOperator> {
+        let tok = self.peek();
+        if SECTIONABLE_OPERATORS.contains(&tok.kind) {
+            return Some(InfixOperator {
+                token: tok.clone(),
+                backtick: false,
+            });
+        }
+        let is_backtick_name = tok.kind == TokenKind::BACKTICK
+            && matches!(self.peek_kind(1), Some(TokenKind::VARID | TokenKind::CONID))
+            && self.peek_kind(2) == Some(TokenKind::BACKTICK);
+        is_backtick_name.then(|| InfixOperator {
+            token: self.ts[self.i + 1].clone(),
+            backtick: true,
+        })
+    }
+
+    /// `peek_infix_operator` で得た演算子を読み飛ばす。
+    pub(super) fn skip_infix_operator(&mut self, op: &InfixOperator) {
+        self.i += if op.backtick { 3 } else { 1 };
+    }
+
+    /// 束縛名（変数名または `(op)`）を読み取る。
+    pub(super) fn pop_binder_name(&mut self) -> Result<Token, ParseError> {
+        match self.accept_paren_operator(true) {
+            Some(op) => Ok(op),
+            None => self.pop(TokenKind::VARID),
+        }
+    }
+
     pub(super) fn accept(&mut self, kind: TokenKind) -> Option<Token> {
         if self.peek().kind == kind {
             let t = self.pop_any();
@@ -117,27 +182,46 @@ pub(super) fn span_from_token(token: &Token) -> Span {
     Span::new(token.pos, token.line, token.col)
 }
 
-pub(super) fn decode_string(quoted: &str) -> Result<String, ParseError> {
+/// トークンの位置を指すエラーを作る。
+pub(super) fn error_at_token(
+    code: &'static str,
+    msg: impl Into<String>,
+    token: &Token,
+) -> ParseError {
+    ParseError::at(
+        code,
+        msg,
+        Some(token.pos),
+        Some(token.line),
+        Some(token.col),
+    )
+}
+
+/// 字句解析エラーを、字句解析器が報告した位置を保ったまま構文解析エラーへ包む。
+fn lex_error(err: LexerError) -> ParseError {
+    let (pos, line, col) = (err.0.pos, err.0.line, err.0.col);
+    ParseError::at("PAR100", format!("lex error: {}", err), pos, line, col)
+}
+
+/// 文字列リテラルのトークンを復号する。`"""` で囲んだ生文字列は中身をそのまま返す。
+/// エラーはトークンの位置を指す。
+pub(super) fn decode_string(token: &Token) -> Result<String, ParseError> {
+    let quoted = token.value.as_str();
+    if let Some(raw) = quoted
+        .strip_prefix(RAW_STRING_DELIMITER)
+        .and_then(|rest| rest.strip_suffix(RAW_STRING_DELIMITER))
+    {
+        return Ok(raw.to_string());
+    }
     if !quoted.starts_with('"') || !quoted.ends_with('"') {
-        return Err(ParseError::new("PAR201", "文字列リテラルが不正", None));
+        return Err(error_at_token("PAR201", "文字列リテラルが不正", token));
     }
     let s = &quoted[1..quoted.len() - 1];
     let mut out = String::new();
     let mut chars = s.chars();
     while let Some(ch) = chars.next() {
         if ch == '\\' {
-            let Some(e) = chars.next() else {
-                return Err(ParseError::new("PAR202", "末尾のバックスラッシュ", None));
-            };
-            match e {
-                'n' => out.push('\n'),
-                'r' => out.push('\r'),
-                't' => out.push('\t'),
-                '\\' => out.push('\\'),
-                '\'' => out.push('\''),
-                '"' => out.push('"'),
-                _ => out.push(e),
-            }
+            out.push(decode_escape(&mut chars, token)?);
         } else {
             out.push(ch);
         }
@@ -145,38 +229,118 @@ pub(super) fn decode_string(quoted: &str) -> Result<String, ParseError> {
     Ok(out)
 }
 
-pub(super) fn decode_char(quoted: &str) -> Result<char, ParseError> {
+/// 文字リテラルのトークンを復号する。エラーはトークンの位置を指す。
+pub(super) fn decode_char(token: &Token) -> Result<char, ParseError> {
+    let quoted = token.value.as_str();
     if !quoted.starts_with('\'') || !quoted.ends_with('\'') {
-        return Err(ParseError::new("PAR204", "文字リテラルが不正", None));
+        return Err(error_at_token("PAR204", "文字リテラルが不正", token));
     }
     let s = &quoted[1..quoted.len() - 1];
-    let ch = if s.starts_with('\\') {
-        match s.chars().nth(1) {
-            Some('n') => '\n',
-            Some('r') => '\r',
-            Some('t') => '\t',
-            Some('\\') => '\\',
-            Some('\'') => '\'',
-            Some('"') => '"',
-            Some(x) => x,
-            None => return Err(ParseError::new("PAR203", "空のエスケープ", None)),
+    let ch = if let Some(rest) = s.strip_prefix('\\') {
+        if rest.is_empty() {
+            return Err(error_at_token("PAR203", "空のエスケープ", token));
         }
+        decode_escape(&mut rest.chars(), token)?
     } else {
         s.chars()
             .next()
-            .ok_or_else(|| ParseError::new("PAR205", "空の文字", None))?
+            .ok_or_else(|| error_at_token("PAR205", "空の文字", token))?
     };
     Ok(ch)
 }
 
-pub fn parse_program(src: &str) -> Result<Program, ParseError> {
-    let ts = lex(src).map_err(|e| ParseError::new("PAR100", format!("lex error: {}", e), None))?;
-    Parser::new(ts).parse_program()
+/// バックスラッシュの直後から 1 つのエスケープを読み、表す文字を返す。
+///
+/// 対応するのは `\n` `\r` `\t` `\0` `\\` `\'` `\"`、2 桁の 16 進数 `\xNN`、
+/// 1〜6 桁の 16 進数 `\u{...}`。それ以外は黙って通さずエラーにする。
+fn decode_escape(chars: &mut std::str::Chars<'_>, token: &Token) -> Result<char, ParseError> {
+    let Some(e) = chars.next() else {
+        return Err(error_at_token("PAR202", "末尾のバックスラッシュ", token));
+    };
+    match e {
+        'n' => Ok('\n'),
+        'r' => Ok('\r'),
+        't' => Ok('\t'),
+        '0' => Ok('\0'),
+        '\\' => Ok('\\'),
+        '\'' => Ok('\''),
+        '"' => Ok('"'),
+        'x' => {
+            let digits: String = chars.by_ref().take(2).collect();
+            match u8::from_str_radix(&digits, 16) {
+                Ok(byte) if digits.len() == 2 => Ok(char::from(byte)),
+                _ => Err(error_at_token(
+                    "PAR207",
+                    format!("\\x には 16 進数 2 桁が必要です: \\x{digits}"),
+                    token,
+                )),
+            }
+        }
+        'u' => {
+            let rest = chars.as_str();
+            let digits = rest
+                .strip_prefix('{')
+                .and_then(|body| body.split_once('}'))
+                .map(|(digits, _)| digits)
+                .filter(|digits| {
+                    (1..=6).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_hexdigit())
+                })
+                .ok_or_else(|| {
+                    error_at_token(
+                        "PAR207",
+                        "\\u には {} で囲んだ 16 進数 1〜6 桁が必要です",
+                        token,
+                    )
+                })?;
+            // `{` + 桁 + `}` を読み進める。
+            for _ in 0..digits.len() + 2 {
+                chars.next();
+            }
+            let code = u32::from_str_radix(digits, 16).expect("validated hex digits");
+            char::from_u32(code).ok_or_else(|| {
+                error_at_token(
+                    "PAR208",
+                    format!("Unicode のコードポイントとして不正です: U+{code:X}"),
+                    token,
+                )
+            })
+        }
+        other => Err(error_at_token(
+            "PAR206",
+            format!("未知のエスケープシーケンス: \\{other}"),
+            token,
+        )),
+    }
+}
+
+/// プログラムを解析する。失敗した宣言は読み飛ばして解析を続け、検出したエラーをすべて返す。
+pub fn parse_program(src: &str) -> Result<Program, ParseErrors> {
+    parse_program_with_fixities(src, &FixityTable::default())
+}
+
+/// 既知の結合性宣言を前提にプログラムを解析する（REPL で以前の宣言を引き継ぐ用途）。
+pub fn parse_program_with_fixities(
+    src: &str,
+    fixities: &FixityTable,
+) -> Result<Program, ParseErrors> {
+    let ts = lex(src).map_err(lex_error)?;
+    Parser::with_fixities(ts, fixities.clone()).parse_program()
+}
+
+/// 型シノニムを展開せずにプログラムを解析する。書かれたままの形を保ちたい整形処理で使う。
+pub(crate) fn parse_program_unexpanded(src: &str) -> Result<Program, ParseErrors> {
+    let ts = lex(src).map_err(lex_error)?;
+    Parser::new(ts).parse_program_syntax()
 }
 
 pub fn parse_expr(src: &str) -> Result<Expr, ParseError> {
-    let ts = lex(src).map_err(|e| ParseError::new("PAR100", format!("lex error: {}", e), None))?;
-    let mut p = Parser::new(ts);
+    parse_expr_with_fixities(src, &FixityTable::default())
+}
+
+/// 既知の結合性宣言を前提に式を解析する。
+pub fn parse_expr_with_fixities(src: &str, fixities: &FixityTable) -> Result<Expr, ParseError> {
+    let ts = lex(src).map_err(lex_error)?;
+    let mut p = Parser::with_fixities(ts, fixities.clone());
     let e = p.parse_expr()?;
     if p.peek().kind != TokenKind::EOF {
         let t = p.peek().clone();
@@ -193,7 +357,27 @@ pub fn parse_expr(src: &str) -> Result<Expr, ParseError> {
 
 #[cfg(test)]
 mod tests {
-    use super::{decode_char, decode_string};
+    use super::{parse_expr, parse_program};
+    use crate::lexer::{Token, TokenKind};
+
+    fn literal(kind: TokenKind, value: &str) -> Token {
+        Token {
+            kind,
+            value: value.into(),
+            pos: 7,
+            end: 7 + value.len(),
+            line: 2,
+            col: 3,
+        }
+    }
+
+    fn decode_string(value: &str) -> Result<String, crate::errors::ParseError> {
+        super::decode_string(&literal(TokenKind::STRING, value))
+    }
+
+    fn decode_char(value: &str) -> Result<char, crate::errors::ParseError> {
+        super::decode_char(&literal(TokenKind::CHAR, value))
+    }
 
     #[test]
     /// 文字列リテラルの基本的なエスケープをテストする。
@@ -203,6 +387,34 @@ mod tests {
         assert_eq!(decode_string("\"\\\\\"").unwrap(), "\\");
     }
 
+    #[test]
+    /// `\xNN`・`\u{...}`・`\0` を文字へ復号し、不正な形はエラーにする。
+    fn decode_hex_unicode_and_nul_escapes() {
+        assert_eq!(decode_string("\"\\x41\\x7a\"").unwrap(), "Az");
+        assert_eq!(decode_string("\"\\u{1F600}!\"").unwrap(), "\u{1F600}!");
+        assert_eq!(decode_string("\"a\\0b\"").unwrap(), "a\0b");
+        assert_eq!(decode_char("'\\x41'").unwrap(), 'A');
+        assert_eq!(decode_char("'\\u{e9}'").unwrap(), '\u{e9}');
+        assert_eq!(decode_char("'\\0'").unwrap(), '\0');
+
+        let cases = [
+            ("\"\\q\"", "PAR206"),
+            ("\"\\x4\"", "PAR207"),
+            ("\"\\xZZ\"", "PAR207"),
+            ("\"\\u41\"", "PAR207"),
+            ("\"\\u{}\"", "PAR207"),
+            ("\"\\u{1234567}\"", "PAR207"),
+            ("\"\\u{110000}\"", "PAR208"),
+            ("\"\\u{D800}\"", "PAR208"),
+        ];
+        for (src, code) in cases {
+            let err = decode_string(src).unwrap_err();
+            assert_eq!(err.0.code, code, "{src}");
+            assert_eq!((err.0.line, err.0.col), (Some(2), Some(3)), "{src}");
+        }
+        assert_eq!(decode_char("'\\q'").unwrap_err().0.code, "PAR206");
+    }
+
     #[test]
     /// 文字リテラルのエスケープをテストする。
     fn decode_char_escapes_and_plain() {
@@ -211,4 +423,37 @@ mod tests {
         assert_eq!(decode_char("'\\\''").unwrap(), '\'');
         assert_eq!(decode_char("'\"'").unwrap(), '"');
     }
+
+    #[test]
+    /// 字句・リテラル復号・構文のどのエラーも行と列を持つ。
+    fn parse_errors_always_carry_positions() {
+        let trailing = decode_string("\"\\\"").unwrap_err();
+        assert_eq!(trailing.0.code, "PAR202");
+        assert_eq!(
+            (trailing.0.pos, trailing.0.line, trailing.0.col),
+            (Some(7), Some(2), Some(3))
+        );
+        let empty = decode_char("''").unwrap_err();
+        assert_eq!(
+            (empty.0.code, empty.0.line, empty.0.col),
+            ("PAR205", Some(2), Some(3))
+        );
+
+        let lexed = parse_program("let main = 1;\nlet s = \"open;").unwrap_err();
+        let lexed = lexed.first();
+        assert_eq!(
+            (lexed.0.code, lexed.0.line, lexed.0.col),
+            ("PAR100", Some(2), Some(9))
+        );
+        let unexpected = parse_expr("1 + )").unwrap_err();
+        assert_eq!(
+            (unexpected.0.code, unexpected.0.line, unexpected.0.col),
+            ("PAR002", Some(1), Some(5))
+        );
+        let overflow = parse_expr("99999999999999999999").unwrap_err();
+        assert_eq!(
+            (overflow.0.code, overflow.0.line, overflow.0.col),
+            ("PAR210", Some(1), Some(1))
+        );
+    }
 }
@@ -3,70 +3,308 @@
 // 意図: プログラム全体の解析ロジックを `Parser` から分離し可読性を高める
 // 関連ファイル: src/parser/expr.rs, src/parser/types.rs, src/parser/mod.rs
 
+use super::synonyms;
 use super::*;
-use crate::ast::{ClassDecl, InstanceDecl};
+use crate::ast::{ClassDecl, FixityDecl, ImportDecl, InstanceDecl, ModuleDecl, TypeSynonymDecl};
 
 impl Parser {
-    pub(super) fn parse_program(&mut self) -> Result<Program, ParseError> {
-        let mut decls = Vec::new();
-        let mut data_decls = Vec::new();
-        let mut class_decls = Vec::new();
-        let mut instance_decls = Vec::new();
+    pub(super) fn parse_program(&mut self) -> Result<Program, ParseErrors> {
+        let mut program = self.parse_program_syntax()?;
+        synonyms::expand_program(&mut program)?;
+        Ok(program)
+    }
+
+    /// 型シノニムを展開せず、書かれたとおりの構文木を返す（整形ツール向け）。
+    ///
+    /// 宣言の解析に失敗しても次の宣言の先頭まで読み飛ばして続行し、検出したエラーをすべて返す。
+    pub(super) fn parse_program_syntax(&mut self) -> Result<Program, ParseErrors> {
+        let mut program = Program {
+            modules: Vec::new(),
+            imports: Vec::new(),
+            class_decls: Vec::new(),
+            instance_decls: Vec::new(),
+            data_decls: Vec::new(),
+            type_synonyms: Vec::new(),
+            fixities: Vec::new(),
+            decls: Vec::new(),
+        };
+        let mut errors = Vec::new();
+        // 結合性宣言は宣言位置より前の式にも効くため、本解析の前に先読みして表へ登録する。
+        self.collect_fixity_decls();
         while self.peek().kind != TokenKind::EOF {
             if self.peek().kind == TokenKind::SEMI {
                 self.pop_any();
                 continue;
             }
-            if self.peek().kind == TokenKind::CLASS {
+            let start = self.i;
+            if let Err(err) = self.parse_top_level_item(&mut program) {
+                errors.push(err);
+                self.recover_to_next_declaration(start);
+            }
+        }
+        if errors.is_empty() {
+            Ok(program)
+        } else {
+            Err(ParseErrors(errors))
+        }
+    }
+
+    /// 解析に失敗した宣言を読み飛ばす。直後の `;` を消費するか、次の宣言を始めるキーワードの手前で止まる。
+    fn recover_to_next_declaration(&mut self, start: usize) {
+        // 同じ位置で失敗し続けないよう、宣言の先頭より最低 1 トークンは進める。
+        if self.i <= start {
+            self.i = start + 1;
+        }
+        while self.peek().kind != TokenKind::EOF {
+            match self.peek().kind {
+                TokenKind::SEMI => {
+                    self.pop_any();
+                    return;
+                }
+                TokenKind::LET
+                | TokenKind::DATA
+                | TokenKind::NEWTYPE
+                | TokenKind::TYPE
+                | TokenKind::CLASS
+                | TokenKind::INSTANCE
+                | TokenKind::MODULE
+                | TokenKind::IMPORT
+                | TokenKind::INFIXL
+                | TokenKind::INFIXR
+                | TokenKind::INFIX => return,
+                _ => {
+                    self.pop_any();
+                }
+            }
+        }
+    }
+
+    /// トップレベルの宣言を 1 つ解析し、`program` の該当する一覧へ追加する。
+    fn parse_top_level_item(&mut self, program: &mut Program) -> Result<(), ParseError> {
+        match self.peek().kind {
+            TokenKind::MODULE => {
+                let module = self.parse_module_decl()?;
+                if !program.modules.is_empty() {
+                    return Err(ParseError::at(
+                        "PAR561",
+                        format!("module 宣言は 1 ファイルに 1 つだけです: {}", module.name),
+                        Some(module.span.pos),
+                        Some(module.span.line),
+                        Some(module.span.col),
+                    ));
+                }
+                self.expect_semicolon_optional()?;
+                program.modules.push(module);
+                return Ok(());
+            }
+            TokenKind::IMPORT => {
+                let import = self.parse_import_decl()?;
+                self.expect_semicolon_optional()?;
+                program.imports.push(import);
+                return Ok(());
+            }
+            TokenKind::INFIXL | TokenKind::INFIXR | TokenKind::INFIX => {
+                let fixity = self.parse_fixity_decl()?;
+                self.expect_semicolon_optional()?;
+                program.fixities.push(fixity);
+                return Ok(());
+            }
+            TokenKind::CLASS => {
                 let class_decl = self.parse_class_decl()?;
                 self.expect_semicolon_optional()?;
-                class_decls.push(class_decl);
-                continue;
+                program.class_decls.push(class_decl);
+                return Ok(());
             }
-            if self.peek().kind == TokenKind::INSTANCE {
+            TokenKind::INSTANCE => {
                 let instance_decl = self.parse_instance_decl()?;
                 self.expect_semicolon_optional()?;
-                instance_decls.push(instance_decl);
-                continue;
+                program.instance_decls.push(instance_decl);
+                return Ok(());
+            }
+            TokenKind::TYPE => {
+                let synonym = self.parse_type_synonym_decl()?;
+                self.expect_semicolon_optional()?;
+                program.type_synonyms.push(synonym);
+                return Ok(());
             }
-            if self.peek().kind == TokenKind::DATA {
+            TokenKind::DATA | TokenKind::NEWTYPE => {
                 let data = self.parse_data_decl()?;
                 self.expect_semicolon_optional()?;
-                data_decls.push(data);
-                continue;
+                program.data_decls.push(data);
+                return Ok(());
             }
-            let mut sig: Option<SigmaType> = None;
-            let save = self.i;
-            if self.peek().kind == TokenKind::VARID {
-                let _name_tok = self.pop_any();
-                if self.accept(TokenKind::DCOLON).is_some() {
-                    sig = Some(self.parse_sigma_type()?);
-                    self.expect_semicolon_optional()?;
-                } else {
-                    self.i = save;
-                }
+            _ => {}
+        }
+        let mut sig: Option<SigmaType> = None;
+        let save = self.i;
+        let mut doc = self.docs.remove(&save);
+        if matches!(self.peek().kind, TokenKind::VARID | TokenKind::LPAREN) {
+            if self.pop_binder_name().is_ok() && self.accept(TokenKind::DCOLON).is_some() {
+                sig = Some(self.parse_sigma_type()?);
+                self.expect_semicolon_optional()?;
+            } else {
+                self.i = save;
             }
-            self.pop(TokenKind::LET)?;
-            let name_tok = self.pop(TokenKind::VARID)?;
-            let mut params: Vec<String> = Vec::new();
-            while self.peek().kind == TokenKind::VARID {
-                params.push(self.pop_any().value);
-            }
-            self.pop(TokenKind::EQUAL)?;
-            let expr = self.parse_expr()?;
-            self.expect_semicolon_optional()?;
-            decls.push(TopLevel {
-                name: name_tok.value,
-                params,
-                expr,
-                signature: sig,
+        }
+        if let Some(extra) = self.docs.remove(&self.i) {
+            doc = Some(match doc {
+                Some(prev) => format!("{prev}\n{extra}"),
+                None => extra,
             });
         }
-        Ok(Program {
-            class_decls,
-            instance_decls,
-            data_decls,
-            decls,
+        self.pop(TokenKind::LET)?;
+        let name_tok = self.pop_binder_name()?;
+        let mut params: Vec<String> = Vec::new();
+        while self.peek().kind == TokenKind::VARID {
+            params.push(self.pop_any().value);
+        }
+        self.pop(TokenKind::EQUAL)?;
+        let expr = self.parse_expr()?;
+        self.expect_semicolon_optional()?;
+        program.decls.push(TopLevel {
+            name: name_tok.value,
+            params,
+            expr,
+            signature: sig,
+            doc,
+        });
+        Ok(())
+    }
+
+    /// `module Foo (bar, (<+>)) where` 形式のモジュール宣言を解析する。公開リストと `where` は省略可。
+    fn parse_module_decl(&mut self) -> Result<ModuleDecl, ParseError> {
+        let kw = self.pop(TokenKind::MODULE)?;
+        let name_tok = self.peek().clone();
+        if name_tok.kind != TokenKind::CONID {
+            return Err(ParseError::at(
+                "PAR560",
+                format!(
+                    "module の後には大文字で始まるモジュール名が必要です: {}",
+                    name_tok.value
+                ),
+                Some(name_tok.pos),
+                Some(name_tok.line),
+                Some(name_tok.col),
+            ));
+        }
+        self.pop_any();
+        let mut exports = None;
+        // 直後の `(op) :: ...` は演算子のシグネチャなので公開リストとみなさない。
+        let operator_signature = self.peek_kind(2) == Some(TokenKind::RPAREN)
+            && self.peek_kind(3) == Some(TokenKind::DCOLON);
+        if self.peek().kind == TokenKind::LPAREN && !operator_signature {
+            self.pop(TokenKind::LPAREN)?;
+            let mut names = Vec::new();
+            while self.peek().kind != TokenKind::RPAREN {
+                names.push(self.pop_binder_name()?.value);
+                if self.accept(TokenKind::COMMA).is_none() {
+                    break;
+                }
+            }
+            self.pop(TokenKind::RPAREN)?;
+            exports = Some(names);
+        }
+        self.accept(TokenKind::WHERE);
+        Ok(ModuleDecl {
+            name: name_tok.value,
+            exports,
+            span: span_from_token(&kw),
+        })
+    }
+
+    /// `import "path"` 形式の取り込み宣言を解析する。
+    fn parse_import_decl(&mut self) -> Result<ImportDecl, ParseError> {
+        let kw = self.pop(TokenKind::IMPORT)?;
+        let tok = self.peek().clone();
+        if tok.kind != TokenKind::STRING {
+            return Err(ParseError::at(
+                "PAR550",
+                format!("import にはファイルパスの文字列が必要です: {}", tok.value),
+                Some(tok.pos),
+                Some(tok.line),
+                Some(tok.col),
+            ));
+        }
+        self.pop_any();
+        Ok(ImportDecl {
+            path: decode_string(&tok)?,
+            span: span_from_token(&kw),
+        })
+    }
+
+    fn is_fixity_keyword(kind: &TokenKind) -> bool {
+        matches!(
+            kind,
+            TokenKind::INFIXL | TokenKind::INFIXR | TokenKind::INFIX
+        )
+    }
+
+    /// 結合性宣言だけを先読みして表へ登録する。不正な宣言は本解析でエラーとして報告するため、ここでは読み飛ばす。
+    fn collect_fixity_decls(&mut self) {
+        let start = self.i;
+        for idx in start..self.ts.len() {
+            if Self::is_fixity_keyword(&self.ts[idx].kind) {
+                self.i = idx;
+                if let Ok(decl) = self.parse_fixity_decl() {
+                    self.fixities.declare(&decl);
+                }
+            }
+        }
+        self.i = start;
+    }
+
+    /// `infixl 6 <+>, <->` や ``infixl 7 `div` `` 形式の結合性宣言を解析する。
+    fn parse_fixity_decl(&mut self) -> Result<FixityDecl, ParseError> {
+        let kw = self.pop_any();
+        let assoc = match kw.kind {
+            TokenKind::INFIXL => Associativity::Left,
+            TokenKind::INFIXR => Associativity::Right,
+            _ => Associativity::None,
+        };
+        let prec_tok = self.peek().clone();
+        let precedence = match prec_tok.kind {
+            TokenKind::INT => prec_tok.value.parse::<u8>().ok().filter(|p| *p <= 9),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            ParseError::at(
+                "PAR540",
+                format!(
+                    "優先順位は 0〜9 の整数で指定してください: {}",
+                    prec_tok.value
+                ),
+                Some(prec_tok.pos),
+                Some(prec_tok.line),
+                Some(prec_tok.col),
+            )
+        })?;
+        self.pop_any();
+        let mut operators = Vec::new();
+        loop {
+            let Some(op) = self.peek_infix_operator() else {
+                let tok = self.peek().clone();
+                return Err(ParseError::at(
+                    "PAR541",
+                    format!(
+                        "結合性宣言には演算子が必要です: {:?} {}",
+                        tok.kind, tok.value
+                    ),
+                    Some(tok.pos),
+                    Some(tok.line),
+                    Some(tok.col),
+                ));
+            };
+            self.skip_infix_operator(&op);
+            operators.push(op.token.value);
+            if self.accept(TokenKind::COMMA).is_none() {
+                break;
+            }
+        }
+        Ok(FixityDecl {
+            assoc,
+            precedence,
+            operators,
+            span: span_from_token(&kw),
         })
     }
 
@@ -76,7 +314,8 @@ impl Parser {
     }
 
     pub(super) fn parse_data_decl(&mut self) -> Result<DataDecl, ParseError> {
-        let data_tok = self.pop(TokenKind::DATA)?;
+        let newtype = self.peek().kind == TokenKind::NEWTYPE;
+        let data_tok = self.pop_any();
         let name_tok = self.pop(TokenKind::CONID)?;
         let mut params = Vec::new();
         while self.peek().kind == TokenKind::VARID {
@@ -91,14 +330,44 @@ impl Parser {
             }
             break;
         }
+        if newtype && (ctors.len() != 1 || ctors[0].args.len() != 1) {
+            return Err(ParseError::at(
+                "PAR530",
+                format!(
+                    "newtype {} はフィールドを 1 つだけ持つコンストラクタを 1 つだけ宣言できます",
+                    name_tok.value
+                ),
+                Some(data_tok.pos),
+                Some(data_tok.line),
+                Some(data_tok.col),
+            ));
+        }
         Ok(DataDecl {
             name: name_tok.value,
             params,
             constructors: ctors,
+            newtype,
             span: span_from_token(&data_tok),
         })
     }
 
+    fn parse_type_synonym_decl(&mut self) -> Result<TypeSynonymDecl, ParseError> {
+        let type_tok = self.pop(TokenKind::TYPE)?;
+        let name_tok = self.pop(TokenKind::CONID)?;
+        let mut params = Vec::new();
+        while self.peek().kind == TokenKind::VARID {
+            params.push(self.pop_any().value);
+        }
+        self.pop(TokenKind::EQUAL)?;
+        let ty = self.parse_type()?;
+        Ok(TypeSynonymDecl {
+            name: name_tok.value,
+            params,
+            r#type: ty,
+            span: span_from_token(&type_tok),
+        })
+    }
+
     fn parse_data_constructor(&mut self) -> Result<DataConstructor, ParseError> {
         let ctor_tok = self.pop(TokenKind::CONID)?;
         let span = span_from_token(&ctor_tok);
@@ -228,7 +497,7 @@ impl Parser {
         let tyvar_tok = self.pop(TokenKind::VARID)?;
         Ok(AConstraint {
             classname: class_tok.value,
-            typevar: tyvar_tok.value,
+            r#type: TypeExpr::TEVar(tyvar_tok.value),
         })
     }
 }
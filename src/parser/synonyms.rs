@@ -0,0 +1,260 @@
+// パス: src/parser/synonyms.rs
+// 役割: `type` 宣言で定義された型シノニムを AST 上で展開する
+// 意図: 推論・評価・ネイティブ変換より前に別名を解消し、後段からは透過的に扱えるようにする
+// 関連ファイル: src/parser/program.rs, src/parser/types.rs, src/ast.rs
+use std::collections::HashMap;
+
+use super::*;
+use crate::ast::TypeSynonymDecl;
+
+type SynonymTable<'a> = HashMap<&'a str, &'a TypeSynonymDecl>;
+
+/// プログラム内の型注釈・シグネチャ・コンストラクタ引数に現れる型シノニムを展開する。
+///
+/// # Errors
+/// 重複定義 (PAR520)、循環参照 (PAR521)、引数不足の適用 (PAR522) を検出した場合に返す。
+pub(super) fn expand_program(program: &mut Program) -> Result<(), ParseError> {
+    let Program {
+        type_synonyms,
+        data_decls,
+        decls,
+        ..
+    } = program;
+    if type_synonyms.is_empty() {
+        return Ok(());
+    }
+    let mut table = SynonymTable::new();
+    for syn in type_synonyms.iter() {
+        if table.insert(syn.name.as_str(), syn).is_some() {
+            return Err(error_at(
+                "PAR520",
+                format!("型シノニム {} が重複して定義されています", syn.name),
+                &syn.span,
+            ));
+        }
+    }
+    // 未使用のシノニムでも循環していれば報告する。
+    for syn in type_synonyms.iter() {
+        let mut stack = vec![syn.name.clone()];
+        expand_type(&syn.r#type, &table, &mut stack, &syn.span)?;
+    }
+
+    for decl in data_decls.iter_mut() {
+        for ctor in &mut decl.constructors {
+            for arg in &mut ctor.args {
+                *arg = expand_type(arg, &table, &mut Vec::new(), &ctor.span)?;
+            }
+        }
+    }
+    for decl in decls.iter_mut() {
+        let span = decl.expr.span();
+        if let Some(sig) = &mut decl.signature {
+            for c in &mut sig.constraints {
+                c.r#type = expand_type(&c.r#type, &table, &mut Vec::new(), &span)?;
+            }
+            sig.r#type = expand_type(&sig.r#type, &table, &mut Vec::new(), &span)?;
+        }
+        decl.expr = expand_expr(&decl.expr, &table)?;
+    }
+    Ok(())
+}
+
+fn expand_expr(e: &Expr, table: &SynonymTable<'_>) -> Result<Expr, ParseError> {
+    let boxed = |inner: &Expr| -> Result<Box<Expr>, ParseError> {
+        Ok(Box::new(expand_expr(inner, table)?))
+    };
+    let many = |items: &[Expr]| -> Result<Vec<Expr>, ParseError> {
+        items.iter().map(|it| expand_expr(it, table)).collect()
+    };
+    Ok(match e {
+        Expr::Annot {
+            expr,
+            type_expr,
+            span,
+        } => Expr::Annot {
+            expr: boxed(expr)?,
+            type_expr: expand_type(type_expr, table, &mut Vec::new(), span)?,
+            span: *span,
+        },
+        Expr::ListLit { items, span } => Expr::ListLit {
+            items: many(items)?,
+            span: *span,
+        },
+        Expr::TupleLit { items, span } => Expr::TupleLit {
+            items: many(items)?,
+            span: *span,
+        },
+        Expr::Lambda { params, body, span } => Expr::Lambda {
+            params: params.clone(),
+            body: boxed(body)?,
+            span: *span,
+        },
+        Expr::LetIn {
+            bindings,
+            body,
+            span,
+        } => Expr::LetIn {
+            bindings: bindings
+                .iter()
+                .map(|(n, ps, ex)| Ok((n.clone(), ps.clone(), expand_expr(ex, table)?)))
+                .collect::<Result<_, ParseError>>()?,
+            body: boxed(body)?,
+            span: *span,
+        },
+        Expr::If {
+            cond,
+            then_branch,
+            else_branch,
+            span,
+        } => Expr::If {
+            cond: boxed(cond)?,
+            then_branch: boxed(then_branch)?,
+            else_branch: boxed(else_branch)?,
+            span: *span,
+        },
+        Expr::App { func, arg, span } => Expr::App {
+            func: boxed(func)?,
+            arg: boxed(arg)?,
+            span: *span,
+        },
+        Expr::BinOp {
+            op,
+            left,
+            right,
+            span,
+        } => Expr::BinOp {
+            op: op.clone(),
+            left: boxed(left)?,
+            right: boxed(right)?,
+            span: *span,
+        },
+        Expr::Case {
+            scrutinee,
+            arms,
+            span,
+        } => Expr::Case {
+            scrutinee: boxed(scrutinee)?,
+            arms: arms
+                .iter()
+                .map(|arm| {
+                    Ok(CaseArm {
+                        pattern: arm.pattern.clone(),
+                        guard: arm
+                            .guard
+                            .as_ref()
+                            .map(|g| expand_expr(g, table))
+                            .transpose()?,
+                        body: expand_expr(&arm.body, table)?,
+                    })
+                })
+                .collect::<Result<_, ParseError>>()?,
+            span: *span,
+        },
+        other => other.clone(),
+    })
+}
+
+fn expand_type(
+    te: &TypeExpr,
+    table: &SynonymTable<'_>,
+    stack: &mut Vec<String>,
+    span: &Span,
+) -> Result<TypeExpr, ParseError> {
+    let mut args = Vec::new();
+    let mut head = te;
+    while let TypeExpr::TEApp(f, a) = head {
+        args.push(a.as_ref());
+        head = f;
+    }
+    args.reverse();
+
+    if let TypeExpr::TECon(name) = head {
+        if let Some(syn) = table.get(name.as_str()) {
+            if stack.iter().any(|n| n == name) {
+                return Err(error_at(
+                    "PAR521",
+                    format!(
+                        "型シノニムが循環しています: {} -> {}",
+                        stack.join(" -> "),
+                        name
+                    ),
+                    &syn.span,
+                ));
+            }
+            if args.len() < syn.params.len() {
+                return Err(error_at(
+                    "PAR522",
+                    format!(
+                        "型シノニム {} には {} 個の引数が必要です (実際: {})",
+                        name,
+                        syn.params.len(),
+                        args.len()
+                    ),
+                    span,
+                ));
+            }
+            let mut subst = HashMap::new();
+            for (param, arg) in syn.params.iter().zip(&args) {
+                subst.insert(param.as_str(), expand_type(arg, table, stack, span)?);
+            }
+            stack.push(name.clone());
+            let body = expand_type(&substitute(&syn.r#type, &subst), table, stack, span);
+            stack.pop();
+            let mut result = body?;
+            for extra in &args[syn.params.len()..] {
+                result = TypeExpr::TEApp(
+                    Box::new(result),
+                    Box::new(expand_type(extra, table, stack, span)?),
+                );
+            }
+            return Ok(result);
+        }
+    }
+
+    Ok(match te {
+        TypeExpr::TEVar(_) | TypeExpr::TECon(_) => te.clone(),
+        TypeExpr::TEApp(f, a) => TypeExpr::TEApp(
+            Box::new(expand_type(f, table, stack, span)?),
+            Box::new(expand_type(a, table, stack, span)?),
+        ),
+        TypeExpr::TEFun(a, b) => TypeExpr::TEFun(
+            Box::new(expand_type(a, table, stack, span)?),
+            Box::new(expand_type(b, table, stack, span)?),
+        ),
+        TypeExpr::TEList(inner) => {
+            TypeExpr::TEList(Box::new(expand_type(inner, table, stack, span)?))
+        }
+        TypeExpr::TETuple(items) => TypeExpr::TETuple(
+            items
+                .iter()
+                .map(|it| expand_type(it, table, stack, span))
+                .collect::<Result<_, _>>()?,
+        ),
+    })
+}
+
+fn substitute(te: &TypeExpr, subst: &HashMap<&str, TypeExpr>) -> TypeExpr {
+    match te {
+        TypeExpr::TEVar(name) => subst
+            .get(name.as_str())
+            .cloned()
+            .unwrap_or_else(|| te.clone()),
+        TypeExpr::TECon(_) => te.clone(),
+        TypeExpr::TEApp(f, a) => TypeExpr::TEApp(
+            Box::new(substitute(f, subst)),
+            Box::new(substitute(a, subst)),
+        ),
+        TypeExpr::TEFun(a, b) => TypeExpr::TEFun(
+            Box::new(substitute(a, subst)),
+            Box::new(substitute(b, subst)),
+        ),
+        TypeExpr::TEList(inner) => TypeExpr::TEList(Box::new(substitute(inner, subst))),
+        TypeExpr::TETuple(items) => {
+            TypeExpr::TETuple(items.iter().map(|it| substitute(it, subst)).collect())
+        }
+    }
+}
+
+fn error_at(code: &'static str, msg: String, span: &Span) -> ParseError {
+    ParseError::at(code, msg, Some(span.pos), Some(span.line), Some(span.col))
+}
@@ -46,6 +46,9 @@ impl Parser {
             }
             TokenKind::LPAREN => {
                 self.pop_any();
+                if self.accept(TokenKind::RPAREN).is_some() {
+                    return Ok(TypeExpr::TETuple(Vec::new()));
+                }
                 let inner = self.parse_type()?;
                 if self.accept(TokenKind::COMMA).is_some() {
                     let mut items = vec![inner, self.parse_type()?];
@@ -93,44 +96,47 @@ impl Parser {
 
     #[cfg_attr(coverage, coverage(off))]
     fn try_parse_context(&mut self) -> Result<Option<Vec<AConstraint>>, ParseError> {
+        // 制約部は `=>` が現れるまで確定しないため、失敗したら位置を巻き戻す。
+        let save = self.i;
+        match self.parse_context_candidate() {
+            Ok(Some(cs)) if self.peek().kind == TokenKind::DARROW => Ok(Some(cs)),
+            _ => {
+                self.i = save;
+                Ok(None)
+            }
+        }
+    }
+
+    fn parse_context_candidate(&mut self) -> Result<Option<Vec<AConstraint>>, ParseError> {
         if self.peek().kind == TokenKind::LPAREN {
-            if matches!(self.peek_kind(1), Some(TokenKind::CONID))
-                && matches!(self.peek_kind(2), Some(TokenKind::VARID))
-            {
-                let save = self.i;
-                self.pop_any();
-                let mut constraints = Vec::new();
+            if !matches!(self.peek_kind(1), Some(TokenKind::CONID)) {
+                return Ok(None);
+            }
+            self.pop_any();
+            let mut constraints = vec![self.parse_constraint()?];
+            while self.accept(TokenKind::COMMA).is_some() {
                 constraints.push(self.parse_constraint()?);
-                while self.accept(TokenKind::COMMA).is_some() {
-                    constraints.push(self.parse_constraint()?);
-                }
-                self.pop(TokenKind::RPAREN)?;
-                if self.peek().kind == TokenKind::DARROW {
-                    return Ok(Some(constraints));
-                }
-                self.i = save;
             }
-            return Ok(None);
+            self.pop(TokenKind::RPAREN)?;
+            return Ok(Some(constraints));
         }
         if self.peek().kind == TokenKind::CONID
-            && matches!(self.peek_kind(1), Some(TokenKind::VARID))
+            && self
+                .peek_kind(1)
+                .is_some_and(|kind| self.is_type_atom_start(&kind))
         {
-            let save = self.i;
-            let constraint = self.parse_constraint()?;
-            if self.peek().kind == TokenKind::DARROW {
-                return Ok(Some(vec![constraint]));
-            }
-            self.i = save;
+            return Ok(Some(vec![self.parse_constraint()?]));
         }
         Ok(None)
     }
 
+    /// `Class a` / `Class (T a)` / `Class [a]` 形式の制約を読む。
     fn parse_constraint(&mut self) -> Result<AConstraint, ParseError> {
         let class_tok = self.pop(TokenKind::CONID)?;
-        let tyvar_tok = self.pop(TokenKind::VARID)?;
+        let ty = self.parse_type_atom()?;
         Ok(AConstraint {
             classname: class_tok.value,
-            typevar: tyvar_tok.value,
+            r#type: ty,
         })
     }
 }
@@ -0,0 +1,90 @@
+// パス: src/prelude.rs
+// 役割: 標準定義 (プレリュード) を用意し、利用者のプログラムへ前置する
+// 意図: `id` や `const` のような汎用関数をファイルごとに定義し直さずに使えるようにする
+// 関連ファイル: src/lib.rs, src/lint.rs, tests/core_ir_tests.rs
+//! プレリュードの結合
+//!
+//! - プレリュードの宣言はプログラムの宣言より前に置く。
+//! - プログラムが同名の定義を持つ場合はプログラム側を優先し、プレリュード側は取り込まない。
+//! - プレリュードの定義はプログラムから (プレリュード内の参照も辿って) 使われるものだけを取り込む。
+//!   ネイティブ生成が未対応の定義を含んでいても、使わない限りビルドを妨げない。
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use crate::ast::Program;
+use crate::lint::top_level_references;
+use crate::parser::parse_program;
+
+/// 既定のプレリュードのソース。
+const DEFAULT_PRELUDE_SOURCE: &str = r#"
+-- | 引数をそのまま返す。
+id :: a -> a;
+let id x = x;
+
+-- | 2 番目の引数を捨て、1 番目の引数を返す。
+const :: a -> b -> a;
+let const x y = x;
+
+-- | 2 引数関数の引数の順序を入れ替える。
+flip :: (a -> b -> c) -> b -> a -> c;
+let flip f x y = f y x;
+"#;
+
+/// 既定のプレリュードを返す。ソースの解析は最初の呼び出しで一度だけ行う。
+pub fn default_prelude() -> Program {
+    static PRELUDE: OnceLock<Program> = OnceLock::new();
+    PRELUDE
+        .get_or_init(|| parse_program(DEFAULT_PRELUDE_SOURCE).expect("default prelude must parse"))
+        .clone()
+}
+
+/// `prelude` の宣言を `program` の前に置いた新しいプログラムを返す。
+///
+/// トップレベル定義はプログラムから到達できるものだけを、元の順序のまま取り込む。
+pub fn with_prelude(program: &Program, prelude: &Program) -> Program {
+    let defined: HashSet<&str> = program.decls.iter().map(|d| d.name.as_str()).collect();
+    let candidates: Vec<_> = prelude
+        .decls
+        .iter()
+        .filter(|decl| !defined.contains(decl.name.as_str()))
+        .collect();
+
+    let mut pending: Vec<String> = program
+        .decls
+        .iter()
+        .flat_map(top_level_references)
+        .collect();
+    let mut used = HashSet::new();
+    while let Some(name) = pending.pop() {
+        if used.contains(&name) {
+            continue;
+        }
+        if let Some(decl) = candidates.iter().find(|decl| decl.name == name) {
+            pending.extend(top_level_references(decl));
+            used.insert(name);
+        }
+    }
+
+    let mut merged = prelude.clone();
+    merged.decls = candidates
+        .into_iter()
+        .filter(|decl| used.contains(&decl.name))
+        .cloned()
+        .collect();
+    merged.modules.extend(program.modules.iter().cloned());
+    merged.imports = program.imports.clone();
+    merged
+        .class_decls
+        .extend(program.class_decls.iter().cloned());
+    merged
+        .instance_decls
+        .extend(program.instance_decls.iter().cloned());
+    merged.data_decls.extend(program.data_decls.iter().cloned());
+    merged
+        .type_synonyms
+        .extend(program.type_synonyms.iter().cloned());
+    merged.fixities.extend(program.fixities.iter().cloned());
+    merged.decls.extend(program.decls.iter().cloned());
+    merged
+}
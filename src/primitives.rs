@@ -8,19 +8,119 @@
 //! - 各モジュールは `type_spec` / `op` を利用して必要な初期化を行う。
 //! - 実装ロジックは個別モジュール側に残しつつ、一覧のみ共有する。
 
+use crate::ast::{DataConstructor, DataDecl, Span, TypeExpr};
 use crate::runtime::{
-    add_op, div_int_op, div_op, eq_op, ge_op, gt_op, le_op, lt_op, mod_int_op, mul_op, ne_op, powf,
-    powi, println_op, py_show, quot_int_op, rem_int_op, sub_op, PrimOp,
+    add_op, append_op, array_from_list_op, array_index_op, band_op, between_op, bor_op, bxor_op,
+    ceiling_op, clamp_op, concat_op, cos_op, div_int_op, div_op, drop_op, empty_list, empty_map,
+    eq_op, exp_op, floor_op, from_integral_op, ge_op, gt_op, intercalate_op, le_op, length_op,
+    log_op, lt_op, map_find_with_default_op, map_insert_op, map_lookup_op, max_op, min_op,
+    mod_int_op, mul_op, ne_op, negate_op, new_ref_op, null_op, powf, powi, println_op, py_show,
+    quot_int_op, read_double_op, read_int_op, read_ref_op, rem_int_op, replicate_op, round_op,
+    shl_op, shr_op, sin_op, sqrt_op, strip_prefix_op, sub_op, take_op, trace_op, truncate_op,
+    unlines_op, unwords_op, write_ref_op, PrimOp,
 };
 
 /// 型推論側で利用するスキーム分類。
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PrimitiveTypeSpec {
-    BinOp { classname: &'static str },
+    BinOp {
+        classname: &'static str,
+    },
     IntPow,
-    Pred { classname: &'static str },
+    Negate,
+    Pred {
+        classname: &'static str,
+    },
+    /// `min` / `max` 用の `Ord a => a -> a -> a`。
+    OrdSelect,
+    /// `clamp` 用の `Ord a => a -> a -> a -> a`。
+    Clamp,
+    /// `between` 用の `Ord a => a -> a -> a -> Bool`。
+    Between,
+    /// `&&` / `||` 用の `Bool -> Bool -> Bool`。
+    BoolLogic,
     Show,
     IntBin,
+    Bits,
+    Floating,
+    Rounding,
+    FromIntegral,
+    ListPred,
+    /// `length` 用の `[a] -> Int`。
+    ListLength,
+    /// `replicate` 用の `Int -> a -> [a]`。
+    Replicate,
+    /// `take` / `drop` 用の `Int -> [a] -> [a]`。
+    ListSlice,
+    /// `enumFrom`（`[x..]` の脱糖先）用の目印。型は `take n [x..]` の形で使った箇所でのみ決まる。
+    EnumFrom,
+    /// `foldl'` 用の `(b -> a -> b) -> b -> [a] -> b`。
+    FoldLeft,
+    /// `fmap` 用の `Functor f => (a -> b) -> f a -> f b`。
+    Fmap,
+    /// `<>` 用の `Semigroup a => a -> a -> a`。
+    Append,
+    /// `mempty` 用の `Monoid a => a`。
+    Mempty,
+    /// `stripPrefix` 用の `Eq a => [a] -> [a] -> Maybe [a]`。
+    StripPrefix,
+    /// `concat` 用の `[[a]] -> [a]`。
+    Concat,
+    /// `concatMap` 用の `(a -> [b]) -> [a] -> [b]`。
+    ConcatMap,
+    /// `intercalate` 用の `[a] -> [[a]] -> [a]`。
+    Intercalate,
+    /// `unwords` / `unlines` 用の `[String] -> String`。
+    JoinStrings,
+    /// `format` 用。型は書式文字列リテラルの指定子から決まるため、推論器が適用ごとに組み立てる。
+    Format,
+    /// `readInt` / `readDouble` 用の `String -> Maybe result`。読めなければ `Nothing`。
+    ReadNumber {
+        result: &'static str,
+    },
+    ArrayFromList,
+    ArrayIndex,
+    NewRef,
+    ReadRef,
+    WriteRef,
+    EmptyMap,
+    MapInsert,
+    MapLookup,
+    MapFindWithDefault,
+    Trace,
+    Catch,
+}
+
+/// 組み込みのデータ型 `data Maybe a = Nothing | Just a` の型名。
+///
+/// `lookup` / `stripPrefix` / `readInt` / `readDouble` は結果の有無をこの型で返す。
+/// プログラムが同じ名前の `data Maybe` を宣言した場合は、その宣言が組み込みの定義を置き換える。
+pub const MAYBE_TYPE: &str = "Maybe";
+/// 値のない `Maybe` の構築子。
+pub const NOTHING: &str = "Nothing";
+/// 値を 1 つ包む `Maybe` の構築子。
+pub const JUST: &str = "Just";
+
+/// 組み込みの `Maybe` を `data` 宣言として返す。Core IR のデータ配置を作るのに使う。
+pub fn builtin_maybe_decl() -> DataDecl {
+    DataDecl {
+        name: MAYBE_TYPE.into(),
+        params: vec!["a".into()],
+        constructors: vec![
+            DataConstructor {
+                name: NOTHING.into(),
+                args: Vec::new(),
+                span: Span::dummy(),
+            },
+            DataConstructor {
+                name: JUST.into(),
+                args: vec![TypeExpr::TEVar("a".into())],
+                span: Span::dummy(),
+            },
+        ],
+        newtype: false,
+        span: Span::dummy(),
+    }
 }
 
 /// プリミティブ定義。
@@ -48,6 +148,11 @@ pub const PRIMITIVES: &[PrimitiveDef] = &[
         type_spec: PrimitiveTypeSpec::BinOp { classname: "Num" },
         op: PrimOp::binary(mul_op),
     },
+    PrimitiveDef {
+        name: "negate",
+        type_spec: PrimitiveTypeSpec::Negate,
+        op: PrimOp::unary(negate_op),
+    },
     PrimitiveDef {
         name: "/",
         type_spec: PrimitiveTypeSpec::BinOp {
@@ -117,6 +222,269 @@ pub const PRIMITIVES: &[PrimitiveDef] = &[
         type_spec: PrimitiveTypeSpec::Pred { classname: "Ord" },
         op: PrimOp::binary(ge_op),
     },
+    PrimitiveDef {
+        name: "min",
+        type_spec: PrimitiveTypeSpec::OrdSelect,
+        op: PrimOp::binary(min_op),
+    },
+    PrimitiveDef {
+        name: "max",
+        type_spec: PrimitiveTypeSpec::OrdSelect,
+        op: PrimOp::binary(max_op),
+    },
+    PrimitiveDef {
+        name: "clamp",
+        type_spec: PrimitiveTypeSpec::Clamp,
+        op: PrimOp::ternary(clamp_op),
+    },
+    PrimitiveDef {
+        name: "between",
+        type_spec: PrimitiveTypeSpec::Between,
+        op: PrimOp::ternary(between_op),
+    },
+    PrimitiveDef {
+        name: "&&",
+        type_spec: PrimitiveTypeSpec::BoolLogic,
+        op: PrimOp::ShortCircuit {
+            decisive: false,
+            lhs: None,
+        },
+    },
+    PrimitiveDef {
+        name: "||",
+        type_spec: PrimitiveTypeSpec::BoolLogic,
+        op: PrimOp::ShortCircuit {
+            decisive: true,
+            lhs: None,
+        },
+    },
+    PrimitiveDef {
+        name: ".&.",
+        type_spec: PrimitiveTypeSpec::Bits,
+        op: PrimOp::binary(band_op),
+    },
+    PrimitiveDef {
+        name: ".|.",
+        type_spec: PrimitiveTypeSpec::Bits,
+        op: PrimOp::binary(bor_op),
+    },
+    PrimitiveDef {
+        name: "xor",
+        type_spec: PrimitiveTypeSpec::Bits,
+        op: PrimOp::binary(bxor_op),
+    },
+    PrimitiveDef {
+        name: "shiftL",
+        type_spec: PrimitiveTypeSpec::Bits,
+        op: PrimOp::binary(shl_op),
+    },
+    PrimitiveDef {
+        name: "shiftR",
+        type_spec: PrimitiveTypeSpec::Bits,
+        op: PrimOp::binary(shr_op),
+    },
+    PrimitiveDef {
+        name: "sqrt",
+        type_spec: PrimitiveTypeSpec::Floating,
+        op: PrimOp::unary(sqrt_op),
+    },
+    PrimitiveDef {
+        name: "sin",
+        type_spec: PrimitiveTypeSpec::Floating,
+        op: PrimOp::unary(sin_op),
+    },
+    PrimitiveDef {
+        name: "cos",
+        type_spec: PrimitiveTypeSpec::Floating,
+        op: PrimOp::unary(cos_op),
+    },
+    PrimitiveDef {
+        name: "exp",
+        type_spec: PrimitiveTypeSpec::Floating,
+        op: PrimOp::unary(exp_op),
+    },
+    PrimitiveDef {
+        name: "log",
+        type_spec: PrimitiveTypeSpec::Floating,
+        op: PrimOp::unary(log_op),
+    },
+    PrimitiveDef {
+        name: "floor",
+        type_spec: PrimitiveTypeSpec::Rounding,
+        op: PrimOp::unary(floor_op),
+    },
+    PrimitiveDef {
+        name: "ceiling",
+        type_spec: PrimitiveTypeSpec::Rounding,
+        op: PrimOp::unary(ceiling_op),
+    },
+    PrimitiveDef {
+        name: "round",
+        type_spec: PrimitiveTypeSpec::Rounding,
+        op: PrimOp::unary(round_op),
+    },
+    PrimitiveDef {
+        name: "truncate",
+        type_spec: PrimitiveTypeSpec::Rounding,
+        op: PrimOp::unary(truncate_op),
+    },
+    PrimitiveDef {
+        name: "fromIntegral",
+        type_spec: PrimitiveTypeSpec::FromIntegral,
+        op: PrimOp::unary(from_integral_op),
+    },
+    PrimitiveDef {
+        name: "null",
+        type_spec: PrimitiveTypeSpec::ListPred,
+        op: PrimOp::unary(null_op),
+    },
+    PrimitiveDef {
+        name: "length",
+        type_spec: PrimitiveTypeSpec::ListLength,
+        op: PrimOp::unary(length_op),
+    },
+    PrimitiveDef {
+        name: "replicate",
+        type_spec: PrimitiveTypeSpec::Replicate,
+        op: PrimOp::binary(replicate_op),
+    },
+    PrimitiveDef {
+        name: "take",
+        type_spec: PrimitiveTypeSpec::ListSlice,
+        op: PrimOp::binary(take_op),
+    },
+    PrimitiveDef {
+        name: "drop",
+        type_spec: PrimitiveTypeSpec::ListSlice,
+        op: PrimOp::binary(drop_op),
+    },
+    PrimitiveDef {
+        name: "enumFrom",
+        type_spec: PrimitiveTypeSpec::EnumFrom,
+        op: PrimOp::EnumFrom { start: None },
+    },
+    PrimitiveDef {
+        name: "readInt",
+        type_spec: PrimitiveTypeSpec::ReadNumber { result: "Int" },
+        op: PrimOp::unary(read_int_op),
+    },
+    PrimitiveDef {
+        name: "readDouble",
+        type_spec: PrimitiveTypeSpec::ReadNumber { result: "Double" },
+        op: PrimOp::unary(read_double_op),
+    },
+    PrimitiveDef {
+        name: "format",
+        type_spec: PrimitiveTypeSpec::Format,
+        op: PrimOp::Format {
+            pieces: None,
+            collected: Vec::new(),
+        },
+    },
+    PrimitiveDef {
+        name: "foldl'",
+        type_spec: PrimitiveTypeSpec::FoldLeft,
+        op: PrimOp::FoldLeft {
+            func: None,
+            init: None,
+        },
+    },
+    PrimitiveDef {
+        name: "fmap",
+        type_spec: PrimitiveTypeSpec::Fmap,
+        op: PrimOp::Fmap {
+            func: None,
+            concat: false,
+        },
+    },
+    PrimitiveDef {
+        name: "<>",
+        type_spec: PrimitiveTypeSpec::Append,
+        op: PrimOp::binary(append_op),
+    },
+    PrimitiveDef {
+        name: "mempty",
+        type_spec: PrimitiveTypeSpec::Mempty,
+        op: PrimOp::Const(empty_list),
+    },
+    PrimitiveDef {
+        name: "stripPrefix",
+        type_spec: PrimitiveTypeSpec::StripPrefix,
+        op: PrimOp::binary(strip_prefix_op),
+    },
+    PrimitiveDef {
+        name: "concat",
+        type_spec: PrimitiveTypeSpec::Concat,
+        op: PrimOp::unary(concat_op),
+    },
+    PrimitiveDef {
+        name: "concatMap",
+        type_spec: PrimitiveTypeSpec::ConcatMap,
+        op: PrimOp::Fmap {
+            func: None,
+            concat: true,
+        },
+    },
+    PrimitiveDef {
+        name: "intercalate",
+        type_spec: PrimitiveTypeSpec::Intercalate,
+        op: PrimOp::binary(intercalate_op),
+    },
+    PrimitiveDef {
+        name: "unwords",
+        type_spec: PrimitiveTypeSpec::JoinStrings,
+        op: PrimOp::unary(unwords_op),
+    },
+    PrimitiveDef {
+        name: "unlines",
+        type_spec: PrimitiveTypeSpec::JoinStrings,
+        op: PrimOp::unary(unlines_op),
+    },
+    PrimitiveDef {
+        name: "fromList",
+        type_spec: PrimitiveTypeSpec::ArrayFromList,
+        op: PrimOp::unary(array_from_list_op),
+    },
+    PrimitiveDef {
+        name: "!",
+        type_spec: PrimitiveTypeSpec::ArrayIndex,
+        op: PrimOp::binary(array_index_op),
+    },
+    PrimitiveDef {
+        name: "newRef",
+        type_spec: PrimitiveTypeSpec::NewRef,
+        op: PrimOp::unary(new_ref_op),
+    },
+    PrimitiveDef {
+        name: "readRef",
+        type_spec: PrimitiveTypeSpec::ReadRef,
+        op: PrimOp::unary(read_ref_op),
+    },
+    PrimitiveDef {
+        name: "writeRef",
+        type_spec: PrimitiveTypeSpec::WriteRef,
+        op: PrimOp::binary(write_ref_op),
+    },
+    PrimitiveDef {
+        name: "emptyMap",
+        type_spec: PrimitiveTypeSpec::EmptyMap,
+        op: PrimOp::Const(empty_map),
+    },
+    PrimitiveDef {
+        name: "insert",
+        type_spec: PrimitiveTypeSpec::MapInsert,
+        op: PrimOp::ternary(map_insert_op),
+    },
+    PrimitiveDef {
+        name: "lookup",
+        type_spec: PrimitiveTypeSpec::MapLookup,
+        op: PrimOp::binary(map_lookup_op),
+    },
+    PrimitiveDef {
+        name: "findWithDefault",
+        type_spec: PrimitiveTypeSpec::MapFindWithDefault,
+        op: PrimOp::ternary(map_find_with_default_op),
+    },
     PrimitiveDef {
         name: "show",
         type_spec: PrimitiveTypeSpec::Show,
@@ -127,4 +495,49 @@ pub const PRIMITIVES: &[PrimitiveDef] = &[
         type_spec: PrimitiveTypeSpec::Show,
         op: PrimOp::unary(println_op),
     },
+    PrimitiveDef {
+        name: "trace",
+        type_spec: PrimitiveTypeSpec::Trace,
+        op: PrimOp::binary(trace_op),
+    },
+    PrimitiveDef {
+        name: "catch",
+        type_spec: PrimitiveTypeSpec::Catch,
+        op: PrimOp::Catch { protected: None },
+    },
 ];
+
+/// 演算子 `name` の被演算子に課される型クラス（`+` なら `Num`、`<` なら `Ord`）。
+pub fn operator_class(name: &str) -> Option<&'static str> {
+    PRIMITIVES
+        .iter()
+        .find(|def| def.name == name)
+        .and_then(|def| match def.type_spec {
+            PrimitiveTypeSpec::BinOp { classname } | PrimitiveTypeSpec::Pred { classname } => {
+                Some(classname)
+            }
+            PrimitiveTypeSpec::OrdSelect => Some("Ord"),
+            _ => None,
+        })
+}
+
+/// 評価器だけが実装し、ネイティブバックエンドでは利用できないプリミティブか。
+///
+/// `catch` は評価エラーを捕捉するため、生成コードには対応する仕組みがない。
+/// `fmap` は関数値を受け取るが、生成コードはまだ関数値を扱えない。
+/// `mempty` は Core IR で型が決まらず、空リストと空文字列のどちらを作るか選べない。
+/// `stripPrefix` / `concat` などは、ランタイムにリストの切り出し・平坦化がまだない。
+pub fn is_evaluator_only(name: &str) -> bool {
+    PRIMITIVES.iter().any(|def| {
+        def.name == name
+            && (matches!(def.op, PrimOp::Catch { .. } | PrimOp::Fmap { .. })
+                || matches!(
+                    def.type_spec,
+                    PrimitiveTypeSpec::Mempty
+                        | PrimitiveTypeSpec::StripPrefix
+                        | PrimitiveTypeSpec::Concat
+                        | PrimitiveTypeSpec::Intercalate
+                        | PrimitiveTypeSpec::JoinStrings
+                ))
+    })
+}
@@ -6,17 +6,24 @@
 //! 利用者の入力をコマンドや式として解釈し、型推論と評価パイプラインへ橋渡しする。
 
 use crate::ast as A;
+use crate::ast::pretty::format_source;
 use crate::evaluator::{initial_env as value_env_init, Value};
 use crate::infer::{initial_class_env, initial_env as type_env_init};
-use crate::parser::{parse_expr, parse_program};
+use crate::lint::{lint_program, Warning};
+use crate::parser::{
+    parse_expr, parse_expr_with_fixities, parse_program_with_fixities, FixityTable,
+};
+use crate::runtime::with_checked_arithmetic;
 use crate::typesys::pretty_qual;
 
+use std::collections::HashMap;
 use std::io::{self, Write};
+use std::time::Instant;
 
 use super::line_editor::{LineEditor, ReadResult};
-use super::loader::load_program_into_env;
+use super::loader::{load_program_into_env, resolve_imports};
 use super::pipeline::{run_repl_pipeline, EvaluationMode};
-use super::printer::{render_help, write_value};
+use super::printer::{render_help, write_typed_value, write_value};
 
 /// TypeLang の対話セッションを開始し、ユーザー入力を処理し続ける。
 ///
@@ -32,7 +39,14 @@ pub fn run_repl() {
     let fs = FsIo;
     let mut stdout = io::stdout();
     let mut stderr = io::stderr();
-    if let Err(err) = run_repl_with(&mut editor, &fs, &mut stdout, &mut stderr) {
+    let session_file = session_path();
+    if let Err(err) = run_repl_with(
+        &mut editor,
+        &fs,
+        session_file.as_deref(),
+        &mut stdout,
+        &mut stderr,
+    ) {
         let _ = writeln!(stderr, "REPL 実行中にエラーが発生しました: {}", err);
     }
 }
@@ -86,6 +100,7 @@ impl ReplLineSource for LineEditor {
 fn run_repl_with<S, I, W, E>(
     editor: &mut S,
     file_io: &I,
+    session_file: Option<&str>,
     out: &mut W,
     err: &mut E,
 ) -> io::Result<()>
@@ -100,19 +115,23 @@ where
         "TypeLang REPL (Rust) :: :t EXPR で型 :: :help でヘルプ"
     )?;
     let mut session = ReplSession::with_defaults();
+    if let Some(path) = session_file {
+        dispatch_messages(session.restore_session(path, file_io), out, err)?;
+    }
+    let restored = session.autosave;
     let mut buffer = String::new();
 
     'repl: loop {
         buffer.clear();
-        let mut prompt = "> ";
+        let mut prompt = session.prompt.clone();
         let mut first_line = true;
         let input = loop {
-            match editor.read_line(prompt) {
+            match editor.read_line(&prompt) {
                 Ok(ReadResult::Line(line)) => {
                     buffer.push_str(&line);
                     buffer.push('\n');
                     if needs_more_input(&buffer) {
-                        prompt = ".. ";
+                        prompt = session.continuation_prompt();
                         first_line = false;
                         continue;
                     }
@@ -158,6 +177,19 @@ where
     if let Err(e) = editor.save_history() {
         writeln!(err, "ヒストリーの保存に失敗しました: {}", e)?;
     }
+    if let Some(path) = session_file {
+        // 復元したセッションで autosave を切った場合は、次回から空の状態で始まるよう消しておく。
+        let saved = if session.autosave {
+            session.save_session(path, file_io)
+        } else if restored {
+            file_io.remove_file(path)
+        } else {
+            Ok(())
+        };
+        if let Err(e) = saved {
+            writeln!(err, "{}", e)?;
+        }
+    }
 
     Ok(())
 }
@@ -173,62 +205,90 @@ fn dispatch_messages<W: Write, E: Write>(
             ReplMsg::Out(s) => writeln!(out, "{}", s)?,
             ReplMsg::Err(s) => writeln!(err, "{}", s)?,
             ReplMsg::Value(v) => write_value(out, &v)?,
+            ReplMsg::TypedValue(v, ty) => write_typed_value(out, &v, &ty)?,
         }
     }
     Ok(())
 }
 
-// 括弧や文字列リテラルの開放状態をざっくり検知して多行入力を判断する。
 /// ソース文字列が追加の入力行を要求するかどうかを判定する。
+///
+/// 括弧の対応と文字列・文字リテラルの開閉は字句解析の結果から判断するため、
+/// `foldl'` のような `'` を含む識別子や、生文字列の中の `"` で誤判定しない。
 fn needs_more_input(src: &str) -> bool {
+    use crate::lexer::{lex, TokenKind as K};
+
     // コマンド行は常に単行扱いなので継続入力を抑止する。
     let s = src.trim_start();
     if s.starts_with(':') {
         return false;
     }
-    let mut paren = 0i32;
-    let mut bracket = 0i32;
-    let mut in_str = false;
-    let mut in_chr = false;
-    let mut esc = false;
-    for ch in src.chars() {
-        if in_str {
-            if esc {
-                esc = false;
-                continue;
-            }
-            match ch {
-                '\\' => esc = true,
-                '"' => in_str = false,
-                _ => {}
-            }
-            continue;
-        }
-        if in_chr {
-            if esc {
-                esc = false;
-                continue;
-            }
-            match ch {
-                '\\' => esc = true,
-                '\'' => in_chr = false,
-                _ => {}
-            }
-            continue;
-        }
-        match ch {
-            '(' => paren += 1,
-            ')' => paren -= 1,
-            '[' => bracket += 1,
-            ']' => bracket -= 1,
-            '"' => in_str = true,
-            '\'' => in_chr = true,
-            _ => {}
+    let tokens = match lex(src) {
+        Ok(tokens) => tokens,
+        Err(err) => {
+            return match err.0.code {
+                // 閉じていないブロックコメントと生文字列は、後の行で閉じられる。
+                "LEX001" | "LEX004" => true,
+                // 通常の文字列・文字リテラルは改行をまたげないため、最終行で開いたままの場合だけ続きを待つ。
+                "LEX002" | "LEX003" => err.0.pos.is_some_and(|pos| !src[pos..].contains('\n')),
+                _ => false,
+            };
         }
+    };
+    let depth = |open: K, close: K| {
+        tokens.iter().fold(0i64, |depth, token| match &token.kind {
+            kind if *kind == open => depth + 1,
+            kind if *kind == close => depth - 1,
+            _ => depth,
+        })
+    };
+    depth(K::LPAREN, K::RPAREN) > 0
+        || depth(K::LBRACK, K::RBRACK) > 0
+        || has_dangling_keyword(&tokens)
+}
+
+/// `if` の `else` 待ちや `case` の `of` 待ち、行末の `then`・`in`・`->` など、
+/// 式の途中で行が終わっているかをトークン単位で判定する。
+fn has_dangling_keyword(tokens: &[crate::lexer::Token]) -> bool {
+    use crate::lexer::TokenKind as K;
+
+    let kinds: Vec<&K> = tokens
+        .iter()
+        .map(|t| &t.kind)
+        .filter(|k| !matches!(k, K::EOF | K::DOC))
+        .collect();
+    let Some(last) = kinds.last() else {
+        return false;
+    };
+    if matches!(
+        last,
+        K::IF
+            | K::THEN
+            | K::ELSE
+            | K::CASE
+            | K::OF
+            | K::LET
+            | K::IN
+            | K::ARROW
+            | K::EQUAL
+            | K::LAMBDA
+    ) {
+        return true;
     }
-    paren > 0 || bracket > 0 || in_str || in_chr
+    let count = |kind: K| kinds.iter().filter(|k| ***k == kind).count();
+    // REPL では先頭の `let` は `in` を伴わない定義として扱う。
+    let lets_needing_in = count(K::LET) - usize::from(*kinds[0] == K::LET);
+    let last_lambda = kinds.iter().rposition(|k| **k == K::LAMBDA);
+    let last_arrow = kinds.iter().rposition(|k| **k == K::ARROW);
+    count(K::IF) > count(K::ELSE)
+        || count(K::CASE) > count(K::OF)
+        || count(K::IN) < lets_needing_in
+        || last_lambda > last_arrow
 }
 
+/// 既定の入力プロンプト。
+const DEFAULT_PROMPT: &str = "> ";
+
 #[derive(Clone)]
 /// REPL の型・クラス・値環境をまとめて保持するセッション管理構造体。
 pub(crate) struct ReplSession {
@@ -236,7 +296,25 @@ pub(crate) struct ReplSession {
     pub class_env: crate::typesys::ClassEnv,
     pub value_env: crate::evaluator::Env,
     pub last_loaded_paths: Vec<String>,
+    /// ロードしたファイルごとの、定義名と整形済みの型。`:reload` で差分を出すのに使う。
+    pub loaded_signatures: HashMap<String, Vec<(String, String)>>,
     pub defaulting_on: bool,
+    pub step_limit: Option<u64>,
+    pub checked_arithmetic: bool,
+    /// 評価結果に推論した型を `value :: Type` の形で添えて表示するか。
+    pub show_types: bool,
+    /// これまでに宣言された演算子の結合性。以降の入力の解析に使う。
+    pub fixities: FixityTable,
+    /// `-- |` コメントから取り込んだ定義ごとのドキュメント。
+    pub docs: HashMap<String, String>,
+    /// 入力待ちのプロンプト。
+    pub prompt: String,
+    /// 継続行のプロンプト。`None` なら `prompt` から導出する。
+    pub continuation_prompt: Option<String>,
+    /// `:let` で取り込めた定義のソース。入力順に保持し、`:edit` で編集対象にする。
+    pub source: Vec<String>,
+    /// 終了時に `source` をセッションファイルへ保存し、次回起動時に復元するか。
+    pub autosave: bool,
 }
 
 impl ReplSession {
@@ -256,26 +334,81 @@ impl ReplSession {
             class_env,
             value_env,
             last_loaded_paths: Vec::new(),
+            loaded_signatures: HashMap::new(),
             defaulting_on: false,
+            step_limit: None,
+            checked_arithmetic: false,
+            show_types: false,
+            fixities: FixityTable::default(),
+            docs: HashMap::new(),
+            prompt: DEFAULT_PROMPT.to_string(),
+            continuation_prompt: None,
+            source: Vec::new(),
+            autosave: false,
+        }
+    }
+
+    /// 継続行のプロンプト。未設定なら `> ` → `.. ` のように本体を `.` で置き換えて導出する。
+    pub(crate) fn continuation_prompt(&self) -> String {
+        if let Some(prompt) = &self.continuation_prompt {
+            return prompt.clone();
         }
+        let body = self.prompt.trim_end();
+        let padding = &self.prompt[body.len()..];
+        format!(
+            "{}{}",
+            ".".repeat(body.chars().count().max(2)),
+            if padding.is_empty() { " " } else { padding }
+        )
     }
 
     /// 解釈済みコマンドを実行し、出力メッセージを返す。
     pub(crate) fn execute<I: ReplIo>(&mut self, cmd: ReplCommand, io: &I) -> Vec<ReplMsg> {
         use ReplCommand::*;
         match cmd {
-            TypeOf(src) => self.exec_type_of(&src),
+            TypeOf(src) => self.exec_type_of(&src, EvaluationMode::OnInferenceFailure),
+            Check(src) => self.exec_type_of(&src, EvaluationMode::Never),
             Let(src) => self.exec_let(&src),
-            Load(path) => self.exec_load(&path, io),
+            Edit => self.exec_edit(io),
+            Load(paths) => self.exec_load(&paths, io),
             Reload => self.exec_reload(io),
             Browse(prefix) => self.exec_browse(prefix),
             SetDefault(on) => self.exec_set_default(on),
+            SetStepLimit(limit) => self.exec_set_step_limit(limit),
+            SetCheckedArithmetic(on) => self.exec_set_checked_arithmetic(on),
+            SetShowTypes(on) => {
+                self.show_types = on;
+                vec![ReplMsg::Out(format!(
+                    "set showtypes = {}",
+                    if on { "on" } else { "off" }
+                ))]
+            }
+            SetAutosave(on) => {
+                self.autosave = on;
+                vec![ReplMsg::Out(format!(
+                    "set autosave = {}",
+                    if on { "on" } else { "off" }
+                ))]
+            }
+            SetPrompt(prompt) => {
+                let msg = format!("set prompt = {:?}", prompt);
+                self.prompt = prompt;
+                vec![ReplMsg::Out(msg)]
+            }
+            SetContinuationPrompt(prompt) => {
+                let msg = format!("set prompt-cont = {:?}", prompt);
+                self.continuation_prompt = Some(prompt);
+                vec![ReplMsg::Out(msg)]
+            }
             Unset(name) => self.exec_unset(&name),
+            Doc(name) => self.exec_doc(&name),
+            Format(path) => self.exec_format(&path, io),
             Dictionaries => vec![ReplMsg::Out(
                 "ネイティブ辞書情報は CLI の `typelang build --print-dictionaries` で確認できます"
                     .into(),
             )],
             Eval(src) => self.exec_eval(&src),
+            Time(src) => self.exec_time(&src),
             Help | Quit => Vec::new(),
             Invalid(s) => vec![ReplMsg::Err(format!(
                 "エラー: コマンド形式が不正です: {}",
@@ -284,16 +417,20 @@ impl ReplSession {
         }
     }
 
-    fn exec_type_of(&mut self, src: &str) -> Vec<ReplMsg> {
-        match parse_expr(src) {
-            Ok(expr) => match run_repl_pipeline(
-                &self.type_env,
-                &self.class_env,
-                &expr,
-                self.defaulting_on,
-                &self.value_env,
-                EvaluationMode::OnInferenceFailure,
-            ) {
+    /// `:t` と `:check` の共通処理。`mode` が `Never` なら推論失敗時も評価しない。
+    fn exec_type_of(&mut self, src: &str, mode: EvaluationMode) -> Vec<ReplMsg> {
+        match parse_expr_with_fixities(src, &self.fixities) {
+            Ok(expr) => match with_checked_arithmetic(self.checked_arithmetic, || {
+                run_repl_pipeline(
+                    &self.type_env,
+                    &self.class_env,
+                    &expr,
+                    self.defaulting_on,
+                    &self.value_env,
+                    mode,
+                    self.step_limit,
+                )
+            }) {
                 Ok(result) => vec![ReplMsg::Out(format!("-- {}", pretty_qual(&result.qual)))],
                 Err(msg) => vec![ReplMsg::Err(msg)],
             },
@@ -304,12 +441,17 @@ impl ReplSession {
     fn exec_let(&mut self, src: &str) -> Vec<ReplMsg> {
         match self.parse_program_text(src) {
             Ok(prog) => match self.apply_program(&prog) {
-                Ok(loaded) => {
-                    if loaded.is_empty() {
-                        Vec::new()
-                    } else {
-                        vec![ReplMsg::Out(format!("Defined {}", loaded.join(", ")))]
+                Ok(applied) => {
+                    let mut msgs = Vec::new();
+                    if !applied.loaded.is_empty() {
+                        msgs.push(ReplMsg::Out(format!(
+                            "Defined {}",
+                            applied.loaded.join(", ")
+                        )));
                     }
+                    append_warnings(&applied.warnings, &mut msgs);
+                    self.source.push(src.to_string());
+                    msgs
                 }
                 Err(msg) => vec![ReplMsg::Err(msg)],
             },
@@ -317,20 +459,100 @@ impl ReplSession {
         }
     }
 
-    fn exec_load<I: ReplIo>(&mut self, path: &str, io: &I) -> Vec<ReplMsg> {
-        match self.read_and_apply_path(path, io) {
-            Ok(loaded) => {
+    /// これまでの `:let` のソースを一時ファイルに書き出して `$EDITOR` で開き、
+    /// 保存された内容を読み込み直す。読み込めた場合は編集後の内容をセッションのソースとする。
+    fn exec_edit<I: ReplIo>(&mut self, io: &I) -> Vec<ReplMsg> {
+        let path = edit_file_path();
+        let edited = io
+            .write_file(&path, &self.source_text())
+            .and_then(|()| io.open_in_editor(&path))
+            .and_then(|()| io.read_to_string(&path));
+        let src = match edited {
+            Ok(src) => src,
+            Err(err) => return vec![ReplMsg::Err(err)],
+        };
+        let applied = self
+            .parse_program_text(&src)
+            .and_then(|prog| self.apply_program(&prog));
+        match applied {
+            Ok(applied) => {
+                let mut msgs = vec![ReplMsg::Out(format!(
+                    "Reloaded {} def(s) from :edit",
+                    applied.loaded.len()
+                ))];
+                self.append_signature_summaries(&applied.loaded, &mut msgs);
+                append_warnings(&applied.warnings, &mut msgs);
+                self.source = vec![src.trim_end().to_string()];
+                msgs
+            }
+            Err(err) => vec![ReplMsg::Err(err)],
+        }
+    }
+
+    /// 前回保存したセッションファイルを読み込む。ファイルが無ければ何もしない。
+    /// 読み込めた場合は保存時の設定を引き継いで `autosave` を有効にする。
+    pub(crate) fn restore_session<I: ReplIo>(&mut self, path: &str, io: &I) -> Vec<ReplMsg> {
+        let Ok(src) = io.read_to_string(path) else {
+            return Vec::new();
+        };
+        let applied = self
+            .parse_program_text(&src)
+            .and_then(|prog| self.apply_program(&prog));
+        match applied {
+            Ok(applied) => {
+                self.autosave = true;
+                self.source = vec![src.trim_end().to_string()];
                 let mut msgs = vec![ReplMsg::Out(format!(
-                    "Loaded {} def(s) from {}",
-                    loaded.len(),
+                    "Restored {} def(s) from {}",
+                    applied.loaded.len(),
                     path
                 ))];
-                self.append_signature_summaries(&loaded, &mut msgs);
-                self.record_load_path(path);
+                append_warnings(&applied.warnings, &mut msgs);
                 msgs
             }
-            Err(err) => vec![ReplMsg::Err(err)],
+            Err(err) => vec![ReplMsg::Err(format!(
+                "エラー: セッションを復元できません ({}): {}",
+                path, err
+            ))],
+        }
+    }
+
+    /// `source` をセッションファイルへ書き出す。
+    pub(crate) fn save_session<I: ReplIo>(&self, path: &str, io: &I) -> Result<(), String> {
+        io.write_file(path, &self.source_text())
+    }
+
+    /// `source` を 1 つのソースファイルとしてまとめた文字列。
+    fn source_text(&self) -> String {
+        let mut contents = self.source.join("\n");
+        if !contents.is_empty() {
+            contents.push('\n');
         }
+        contents
+    }
+
+    fn exec_load<I: ReplIo>(&mut self, paths: &[String], io: &I) -> Vec<ReplMsg> {
+        let mut msgs = Vec::new();
+        for path in paths {
+            match self.read_and_apply_path(path, io) {
+                Ok(applied) => {
+                    msgs.push(ReplMsg::Out(format!(
+                        "Loaded {} def(s) from {}{}",
+                        applied.loaded.len(),
+                        path,
+                        imports_note(&applied.imported)
+                    )));
+                    self.append_signature_summaries(&applied.loaded, &mut msgs);
+                    append_warnings(&applied.warnings, &mut msgs);
+                    self.record_load_path(path, &applied.loaded);
+                }
+                Err(err) => {
+                    msgs.push(ReplMsg::Err(err));
+                    break;
+                }
+            }
+        }
+        msgs
     }
 
     fn exec_reload<I: ReplIo>(&mut self, io: &I) -> Vec<ReplMsg> {
@@ -341,11 +563,23 @@ impl ReplSession {
         let mut msgs = Vec::new();
         for path in self.last_loaded_paths.clone() {
             match self.read_and_apply_path(&path, io) {
-                Ok(loaded) => msgs.push(ReplMsg::Out(format!(
-                    "Reloaded {} def(s) from {}",
-                    loaded.len(),
-                    path
-                ))),
+                Ok(applied) => {
+                    msgs.push(ReplMsg::Out(format!(
+                        "Reloaded {} def(s) from {}{}",
+                        applied.loaded.len(),
+                        path,
+                        imports_note(&applied.imported)
+                    )));
+                    let before = self
+                        .loaded_signatures
+                        .get(&path)
+                        .cloned()
+                        .unwrap_or_default();
+                    self.record_load_path(&path, &applied.loaded);
+                    let after = &self.loaded_signatures[&path];
+                    append_signature_diff(&before, after, &mut msgs);
+                    append_warnings(&applied.warnings, &mut msgs);
+                }
                 Err(err) => msgs.push(ReplMsg::Err(err)),
             }
         }
@@ -384,6 +618,20 @@ impl ReplSession {
         ))]
     }
 
+    fn exec_set_step_limit(&mut self, limit: Option<u64>) -> Vec<ReplMsg> {
+        self.step_limit = limit;
+        let shown = limit.map_or_else(|| "off".to_string(), |n| n.to_string());
+        vec![ReplMsg::Out(format!("set steplimit = {}", shown))]
+    }
+
+    fn exec_set_checked_arithmetic(&mut self, on: bool) -> Vec<ReplMsg> {
+        self.checked_arithmetic = on;
+        vec![ReplMsg::Out(format!(
+            "set checked = {}",
+            if on { "on" } else { "off" }
+        ))]
+    }
+
     fn exec_unset(&mut self, name: &str) -> Vec<ReplMsg> {
         let mut removed = false;
         if self.type_env.env.remove(name).is_some() {
@@ -399,23 +647,57 @@ impl ReplSession {
         }
     }
 
+    fn exec_doc(&self, name: &str) -> Vec<ReplMsg> {
+        let Some(sch) = self.type_env.lookup(name) else {
+            return vec![ReplMsg::Err(format!("エラー: 未定義です: {}", name))];
+        };
+        let mut msgs = vec![ReplMsg::Out(format!(
+            "{} :: {}",
+            name,
+            pretty_qual(&sch.qual)
+        ))];
+        match self.docs.get(name) {
+            Some(doc) => msgs.extend(doc.lines().map(|line| ReplMsg::Out(format!("  {line}")))),
+            None => msgs.push(ReplMsg::Out("  (ドキュメントなし)".into())),
+        }
+        msgs
+    }
+
+    /// ファイルを読み込んで整形結果を表示する。ファイル自体は書き換えない。
+    fn exec_format<I: ReplIo>(&self, path: &str, io: &I) -> Vec<ReplMsg> {
+        let formatted = io
+            .read_to_string(path)
+            .and_then(|src| format_source(&src).map_err(|e| format!("{}", e)));
+        match formatted {
+            Ok(text) => text.lines().map(|line| ReplMsg::Out(line.into())).collect(),
+            Err(err) => vec![ReplMsg::Err(err)],
+        }
+    }
+
     fn exec_eval(&mut self, src: &str) -> Vec<ReplMsg> {
-        match parse_expr(src) {
-            Ok(expr) => match run_repl_pipeline(
-                &self.type_env,
-                &self.class_env,
-                &expr,
-                self.defaulting_on,
-                &self.value_env,
-                EvaluationMode::Always,
-            ) {
+        match parse_expr_with_fixities(src, &self.fixities) {
+            Ok(expr) => match with_checked_arithmetic(self.checked_arithmetic, || {
+                run_repl_pipeline(
+                    &self.type_env,
+                    &self.class_env,
+                    &expr,
+                    self.defaulting_on,
+                    &self.value_env,
+                    EvaluationMode::Always,
+                    self.step_limit,
+                )
+            }) {
                 Ok(result) => {
                     let value = result
                         .value
                         .expect("pipeline with Always mode must return a value");
+                    let shown_type = self.show_types.then(|| pretty_qual(&result.scheme.qual));
                     self.type_env.extend("it", result.scheme);
                     self.value_env.insert("it", value.clone());
-                    vec![ReplMsg::Value(value)]
+                    match shown_type {
+                        Some(ty) => vec![ReplMsg::TypedValue(value, ty)],
+                        None => vec![ReplMsg::Value(value)],
+                    }
                 }
                 Err(msg) => vec![ReplMsg::Err(msg)],
             },
@@ -423,31 +705,80 @@ impl ReplSession {
         }
     }
 
+    fn exec_time(&mut self, src: &str) -> Vec<ReplMsg> {
+        let start = Instant::now();
+        let mut msgs = self.exec_eval(src);
+        let elapsed = start.elapsed();
+        if matches!(
+            msgs.first(),
+            Some(ReplMsg::Value(_) | ReplMsg::TypedValue(..))
+        ) {
+            msgs.push(ReplMsg::Out(format!(
+                "(経過時間: {:.3} ms)",
+                elapsed.as_secs_f64() * 1000.0
+            )));
+        }
+        msgs
+    }
+
+    /// ファイルと、そこから `import` されるファイル群を依存順に取り込む。
     fn read_and_apply_path<I: ReplIo>(
         &mut self,
         path: &str,
         io: &I,
-    ) -> Result<Vec<String>, String> {
-        let src = io.read_to_string(path)?;
-        self.apply_program_from_source(&src)
-    }
-
-    fn apply_program_from_source(&mut self, src: &str) -> Result<Vec<String>, String> {
-        let prog = self.parse_program_text(src)?;
-        self.apply_program(&prog)
+    ) -> Result<AppliedProgram, String> {
+        let modules = resolve_imports(path, |p| {
+            let src = io.read_to_string(p)?;
+            self.parse_program_text(&src)
+        })?;
+        let mut applied = AppliedProgram {
+            loaded: Vec::new(),
+            warnings: Vec::new(),
+            imported: Vec::new(),
+        };
+        for module in modules {
+            let part = self.apply_program(&module.program)?;
+            applied.loaded.extend(part.loaded);
+            applied.warnings.extend(part.warnings);
+            if module.path != path {
+                applied.imported.push(module.path);
+            }
+        }
+        Ok(applied)
     }
 
     fn parse_program_text(&self, src: &str) -> Result<A::Program, String> {
-        parse_program(src).map_err(|e| format!("{}", e))
+        parse_program_with_fixities(src, &self.fixities).map_err(|e| format!("{}", e))
     }
 
-    fn apply_program(&mut self, prog: &A::Program) -> Result<Vec<String>, String> {
-        load_program_into_env(
-            prog,
-            &mut self.type_env,
-            &mut self.class_env,
-            &mut self.value_env,
-        )
+    fn apply_program(&mut self, prog: &A::Program) -> Result<AppliedProgram, String> {
+        let loaded = with_checked_arithmetic(self.checked_arithmetic, || {
+            load_program_into_env(
+                prog,
+                &mut self.type_env,
+                &mut self.class_env,
+                &mut self.value_env,
+            )
+        })?;
+        for fixity in &prog.fixities {
+            self.fixities.declare(fixity);
+        }
+        for decl in &prog.decls {
+            match &decl.doc {
+                Some(doc) => self.docs.insert(decl.name.clone(), doc.clone()),
+                None => self.docs.remove(&decl.name),
+            };
+        }
+        for (qualified, name) in prog.qualified_aliases() {
+            if let Some(doc) = self.docs.get(&name).cloned() {
+                self.docs.insert(qualified, doc);
+            }
+        }
+        Ok(AppliedProgram {
+            loaded,
+            warnings: lint_program(prog),
+            imported: Vec::new(),
+        })
     }
 
     fn append_signature_summaries(&self, names: &[String], msgs: &mut Vec<ReplMsg>) {
@@ -462,24 +793,127 @@ impl ReplSession {
         }
     }
 
-    fn record_load_path(&mut self, path: &str) {
+    /// ロードしたパスと、その時点での各定義の型を記録する。
+    fn record_load_path(&mut self, path: &str, loaded: &[String]) {
         if !self.last_loaded_paths.iter().any(|p| p == path) {
             self.last_loaded_paths.push(path.to_string());
         }
+        let signatures = loaded
+            .iter()
+            .filter_map(|name| {
+                let sch = self.type_env.lookup(name)?;
+                Some((name.clone(), pretty_qual(&sch.qual)))
+            })
+            .collect();
+        self.loaded_signatures.insert(path.to_string(), signatures);
     }
 }
 
+/// 定義の取り込み結果と、その際に検出した警告。
+struct AppliedProgram {
+    loaded: Vec<String>,
+    warnings: Vec<Warning>,
+    /// `import` 経由で併せて読み込んだファイル。
+    imported: Vec<String>,
+}
+
+/// `:edit` が使う一時ファイルのパス。同時に動く REPL 同士で衝突しないようプロセス ID を含める。
+fn edit_file_path() -> String {
+    std::env::temp_dir()
+        .join(format!("typelang-edit-{}.tl", std::process::id()))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// `:set autosave on` で保存するセッションファイルのパス。
+/// `TYPELANG_SESSION_FILE` を優先し、無ければホームディレクトリの `.typelang_session.tl` を使う。
+fn session_path() -> Option<String> {
+    if let Some(path) = std::env::var_os("TYPELANG_SESSION_FILE") {
+        return Some(path.to_string_lossy().into_owned());
+    }
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| {
+            std::path::Path::new(&home)
+                .join(".typelang_session.tl")
+                .to_string_lossy()
+                .into_owned()
+        })
+}
+
+/// `import` で併せて読み込んだファイルがあれば `(import: a.tl, b.tl)` 形式の注記を返す。
+fn imports_note(imported: &[String]) -> String {
+    if imported.is_empty() {
+        String::new()
+    } else {
+        format!(" (import: {})", imported.join(", "))
+    }
+}
+
+/// `:reload` の前後で、追加・削除された定義と型が変わった定義を出力メッセージへ追加する。
+fn append_signature_diff(
+    before: &[(String, String)],
+    after: &[(String, String)],
+    msgs: &mut Vec<ReplMsg>,
+) {
+    let old: HashMap<&str, &str> = before
+        .iter()
+        .map(|(n, t)| (n.as_str(), t.as_str()))
+        .collect();
+    let new: HashMap<&str, &str> = after
+        .iter()
+        .map(|(n, t)| (n.as_str(), t.as_str()))
+        .collect();
+    for (name, ty) in after {
+        match old.get(name.as_str()) {
+            None => msgs.push(ReplMsg::Out(format!("  added: {} :: {}", name, ty))),
+            Some(prev) if *prev != ty => msgs.push(ReplMsg::Out(format!(
+                "  type changed: {} :: {} (was {})",
+                name, ty, prev
+            ))),
+            Some(_) => {}
+        }
+    }
+    for (name, _) in before {
+        if !new.contains_key(name.as_str()) {
+            msgs.push(ReplMsg::Out(format!("  removed: {}", name)));
+        }
+    }
+}
+
+/// 警告を致命的でない通知として出力メッセージへ追加する。
+fn append_warnings(warnings: &[Warning], msgs: &mut Vec<ReplMsg>) {
+    msgs.extend(warnings.iter().map(|w| ReplMsg::Out(w.to_string())));
+}
+
 /// 対話セッションがユーザーへ返す応答メッセージのカテゴリ。
 pub(crate) enum ReplMsg {
     Out(String),
     Err(String),
     Value(Value),
+    /// `:set showtypes on` のときの評価結果。値と整形済みの型を併せて持つ。
+    TypedValue(Value, String),
 }
 
 /// REPL に必要な最小限のファイル読み込み抽象。
 pub(crate) trait ReplIo {
     /// 指定されたパスのソースコードを文字列として取得する。
     fn read_to_string(&self, path: &str) -> Result<String, String>;
+
+    /// `contents` でファイルを書き換える。`:edit` が編集用の一時ファイルを用意するのに使う。
+    fn write_file(&self, path: &str, _contents: &str) -> Result<(), String> {
+        Err(format!("エラー: ファイルへ書き込めません: {}", path))
+    }
+
+    /// 外部エディタでファイルを開き、終了するまで待つ。
+    fn open_in_editor(&self, _path: &str) -> Result<(), String> {
+        Err("エラー: エディタを起動できません".into())
+    }
+
+    /// ファイルを削除する。存在しない場合は何もしない。
+    fn remove_file(&self, path: &str) -> Result<(), String> {
+        Err(format!("エラー: ファイルを削除できません: {}", path))
+    }
 }
 
 /// 実際のファイルシステムにアクセスする標準実装。
@@ -489,6 +923,43 @@ impl ReplIo for FsIo {
     fn read_to_string(&self, path: &str) -> Result<String, String> {
         std::fs::read_to_string(path).map_err(|e| format!("エラー: ファイルを開けません: {}", e))
     }
+
+    fn write_file(&self, path: &str, contents: &str) -> Result<(), String> {
+        std::fs::write(path, contents)
+            .map_err(|e| format!("エラー: ファイルへ書き込めません: {}", e))
+    }
+
+    fn remove_file(&self, path: &str) -> Result<(), String> {
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                Err(format!("エラー: ファイルを削除できません: {}", e))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// `$EDITOR` を起動する。`code --wait` のように引数を含む指定も空白で区切って渡す。
+    #[cfg_attr(coverage, coverage(off))]
+    fn open_in_editor(&self, path: &str) -> Result<(), String> {
+        let editor = std::env::var("EDITOR").unwrap_or_default();
+        let mut words = editor.split_whitespace();
+        let Some(program) = words.next() else {
+            return Err("エラー: 環境変数 EDITOR が設定されていません".into());
+        };
+        let status = std::process::Command::new(program)
+            .args(words)
+            .arg(path)
+            .status()
+            .map_err(|e| format!("エラー: エディタ {} を起動できません: {}", program, e))?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "エラー: エディタ {} が異常終了しました ({})",
+                program, status
+            ))
+        }
+    }
 }
 
 /// 解釈済みの REPL コマンドを適用し、状態と出力メッセージを更新する。
@@ -510,22 +981,44 @@ pub(crate) enum ReplCommand {
     Quit,
     /// `:t` / `:type` で式の推論結果を照会する。
     TypeOf(String),
+    /// `:check` で式を評価せずに型検査だけ行う。
+    Check(String),
     /// `:let` のペイロードを正規化済みソースとして保持する。
     Let(String),
-    /// `:load` によるファイル読込コマンド。
-    Load(String),
+    /// `:edit` でセッションのソースを `$EDITOR` で編集し、読み込み直す。
+    Edit,
+    /// `:load` によるファイル読込コマンド。複数のパスは指定順に読み込む。
+    Load(Vec<String>),
     /// `:reload` で直近ロードしたファイル群を再評価する。
     Reload,
     /// `:browse` の接頭辞フィルタを含むコマンド。
     Browse(Option<String>),
     /// `:set default on|off` による defaulting 設定。
     SetDefault(bool),
+    /// `:set steplimit N|off` による評価ステップ上限の設定。
+    SetStepLimit(Option<u64>),
+    /// `:set checked on|off` による整数オーバーフロー検査の切替。
+    SetCheckedArithmetic(bool),
+    /// `:set showtypes on|off` による評価結果への型の併記の切替。
+    SetShowTypes(bool),
+    /// `:set autosave on|off` による終了時のセッション保存の切替。
+    SetAutosave(bool),
+    /// `:set prompt "..."` による入力プロンプトの変更。
+    SetPrompt(String),
+    /// `:set prompt-cont "..."` による継続行プロンプトの変更。
+    SetContinuationPrompt(String),
     /// `:unset name` で定義を破棄する。
     Unset(String),
+    /// `:doc name` で定義のドキュメントコメントを表示する。
+    Doc(String),
+    /// `:format path` でファイルを整形した結果を表示する。
+    Format(String),
     /// 辞書情報のヒントを表示する。
     Dictionaries,
     /// 既知のコマンドに該当しない入力を通常式として扱う。
     Eval(String),
+    /// `:time` で式を評価し、経過時間を併せて表示する。
+    Time(String),
     /// シンタックスが認識できなかったコマンド入力。
     Invalid(String),
 }
@@ -549,15 +1042,28 @@ pub(crate) fn parse_repl_command(input: &str) -> ReplCommand {
     if let Some(rest) = s.strip_prefix(":type ") {
         return ReplCommand::TypeOf(rest.trim().to_string());
     }
+    if let Some(rest) = s.strip_prefix(":check ") {
+        return ReplCommand::Check(rest.trim().to_string());
+    }
     if let Some(rest) = s.strip_prefix(":let ") {
         return ReplCommand::Let(normalize_let_payload(rest.trim()));
     }
+    if let Some(rest) = s.strip_prefix(":time ") {
+        return ReplCommand::Time(rest.trim().to_string());
+    }
     if let Some(rest) = s.strip_prefix(":load ") {
-        return ReplCommand::Load(rest.trim().to_string());
+        let paths: Vec<String> = rest.split_whitespace().map(str::to_string).collect();
+        if paths.is_empty() {
+            return ReplCommand::Invalid(s.to_string());
+        }
+        return ReplCommand::Load(paths);
     }
     if s == ":reload" {
         return ReplCommand::Reload;
     }
+    if s == ":edit" {
+        return ReplCommand::Edit;
+    }
     if let Some(rest) = s.strip_prefix(":browse") {
         let pfx = rest.trim();
         return if pfx.is_empty() {
@@ -566,6 +1072,12 @@ pub(crate) fn parse_repl_command(input: &str) -> ReplCommand {
             ReplCommand::Browse(Some(pfx.to_string()))
         };
     }
+    if let Some(rest) = s.strip_prefix(":set prompt-cont ") {
+        return ReplCommand::SetContinuationPrompt(parse_prompt_arg(rest));
+    }
+    if let Some(rest) = s.strip_prefix(":set prompt ") {
+        return ReplCommand::SetPrompt(parse_prompt_arg(rest));
+    }
     if let Some(rest) = s.strip_prefix(":set ") {
         let parts: Vec<&str> = rest.split_whitespace().collect();
         if parts.len() == 2 && parts[0] == "default" {
@@ -575,8 +1087,52 @@ pub(crate) fn parse_repl_command(input: &str) -> ReplCommand {
                 _ => ReplCommand::Invalid(s.to_string()),
             };
         }
+        if parts.len() == 2 && parts[0] == "checked" {
+            return match parts[1] {
+                "on" => ReplCommand::SetCheckedArithmetic(true),
+                "off" => ReplCommand::SetCheckedArithmetic(false),
+                _ => ReplCommand::Invalid(s.to_string()),
+            };
+        }
+        if parts.len() == 2 && parts[0] == "showtypes" {
+            return match parts[1] {
+                "on" => ReplCommand::SetShowTypes(true),
+                "off" => ReplCommand::SetShowTypes(false),
+                _ => ReplCommand::Invalid(s.to_string()),
+            };
+        }
+        if parts.len() == 2 && parts[0] == "autosave" {
+            return match parts[1] {
+                "on" => ReplCommand::SetAutosave(true),
+                "off" => ReplCommand::SetAutosave(false),
+                _ => ReplCommand::Invalid(s.to_string()),
+            };
+        }
+        if parts.len() == 2 && parts[0] == "steplimit" {
+            if parts[1] == "off" {
+                return ReplCommand::SetStepLimit(None);
+            }
+            return match parts[1].parse::<u64>() {
+                Ok(n) if n > 0 => ReplCommand::SetStepLimit(Some(n)),
+                _ => ReplCommand::Invalid(s.to_string()),
+            };
+        }
         return ReplCommand::Invalid(s.to_string());
     }
+    if let Some(rest) = s.strip_prefix(":doc ") {
+        let name = rest.trim();
+        if name.is_empty() {
+            return ReplCommand::Invalid(s.to_string());
+        }
+        return ReplCommand::Doc(name.to_string());
+    }
+    if let Some(rest) = s.strip_prefix(":format ") {
+        let path = rest.trim();
+        if path.is_empty() {
+            return ReplCommand::Invalid(s.to_string());
+        }
+        return ReplCommand::Format(path.to_string());
+    }
     if let Some(rest) = s.strip_prefix(":unset ") {
         let name = rest.trim();
         if name.is_empty() {
@@ -584,6 +1140,12 @@ pub(crate) fn parse_repl_command(input: &str) -> ReplCommand {
         }
         return ReplCommand::Unset(name.to_string());
     }
+    if ["infixl ", "infixr ", "infix "]
+        .iter()
+        .any(|kw| s.starts_with(kw))
+    {
+        return ReplCommand::Let(s.to_string());
+    }
     if s.starts_with("let ") {
         if parse_expr(s).is_ok() {
             return ReplCommand::Eval(s.to_string());
@@ -593,6 +1155,15 @@ pub(crate) fn parse_repl_command(input: &str) -> ReplCommand {
     ReplCommand::Eval(s.to_string())
 }
 
+/// プロンプト指定を取り出す。`"tl> "` のように引用符で囲むと前後の空白も保持する。
+fn parse_prompt_arg(raw: &str) -> String {
+    let arg = raw.trim();
+    arg.strip_prefix('"')
+        .and_then(|inner| inner.strip_suffix('"'))
+        .unwrap_or(arg)
+        .to_string()
+}
+
 /// `:let` に与えられた定義群を均一な `let` 形式へ整形する。
 /// 1 行または `;` 区切りの複数行定義を、REPL で解釈しやすいテキストに揃える。
 pub(crate) fn normalize_let_payload(payload: &str) -> String {
@@ -631,9 +1202,10 @@ pub(crate) fn normalize_let_payload(payload: &str) -> String {
 mod tests {
     use super::*;
     use super::{handle_command, needs_more_input, normalize_let_payload, parse_repl_command};
-    use crate::repl::printer::write_value;
+    use crate::repl::printer::{write_typed_value, write_value};
     use crate::typesys::TypeEnv;
     use crate::{evaluator, infer};
+    use std::cell::RefCell;
     use std::collections::{HashMap, VecDeque};
     use std::io;
 
@@ -659,6 +1231,41 @@ mod tests {
         }
     }
 
+    /// `:edit` 用の入出力。エディタの起動は、書き出された内容を `edit` で置き換える操作として模擬する。
+    struct EditorIo {
+        files: RefCell<HashMap<String, String>>,
+        edit: fn(&str) -> String,
+    }
+
+    impl ReplIo for EditorIo {
+        fn read_to_string(&self, path: &str) -> Result<String, String> {
+            self.files
+                .borrow()
+                .get(path)
+                .cloned()
+                .ok_or_else(|| "not found".into())
+        }
+
+        fn write_file(&self, path: &str, contents: &str) -> Result<(), String> {
+            self.files
+                .borrow_mut()
+                .insert(path.to_string(), contents.to_string());
+            Ok(())
+        }
+
+        fn open_in_editor(&self, path: &str) -> Result<(), String> {
+            let mut files = self.files.borrow_mut();
+            let edited = (self.edit)(&files[path]);
+            files.insert(path.to_string(), edited);
+            Ok(())
+        }
+
+        fn remove_file(&self, path: &str) -> Result<(), String> {
+            self.files.borrow_mut().remove(path);
+            Ok(())
+        }
+    }
+
     struct NoopIo;
 
     impl ReplIo for NoopIo {
@@ -707,11 +1314,21 @@ mod tests {
                         "expected value to contain `{fragment}`, got `{rendered}`"
                     );
                 }
+                (ReplMsg::TypedValue(value, ty), Expected::Value(fragment)) => {
+                    let mut buf = Vec::new();
+                    write_typed_value(&mut buf, &value, &ty).expect("value serialization");
+                    let rendered = String::from_utf8(buf).expect("utf8");
+                    assert!(
+                        rendered.contains(fragment),
+                        "expected value to contain `{fragment}`, got `{rendered}`"
+                    );
+                }
                 (other, expect) => {
                     let actual = match other {
                         ReplMsg::Out(_) => "Out",
                         ReplMsg::Err(_) => "Err",
                         ReplMsg::Value(_) => "Value",
+                        ReplMsg::TypedValue(..) => "TypedValue",
                     };
                     panic!("mismatched variants: actual {actual}, expected {expect:?}");
                 }
@@ -727,6 +1344,30 @@ mod tests {
             ("'a'", false),
             (":t (", false),
             ("let x = 1", false),
+            ("if True\nthen 1", true),
+            ("if True\nthen 1\nelse 2", false),
+            ("if a then if b then 1 else 2", true),
+            ("case xs of", true),
+            ("case xs", true),
+            ("case n of 0 -> 1; _ -> 2", false),
+            ("let y = 2 in", true),
+            ("let f x = let y = x", true),
+            ("let f x = let y = x in y", false),
+            ("\\x", true),
+            ("\\x ->", true),
+            ("\\x -> x", false),
+            ("map (\\x -> x) xs", false),
+            ("let g =", true),
+            ("\"if\" ++ \"case\"", false),
+            ("1 + 2 -- then", false),
+            ("foldl' (+) 0 [1]", false),
+            ("let f' = 1 in f'", false),
+            ("foldl' (+) 0 [1,", true),
+            ("'\\''", false),
+            ("\"\"\"say \"hi there\"\"\"", false),
+            ("\"\"\"say \"hi", true),
+            ("\"\"\"line\nnext", true),
+            ("\"abc\ndef\"", false),
         ];
         for (src, expected) in cases {
             assert_eq!(needs_more_input(src), expected, "case `{src}`");
@@ -756,15 +1397,39 @@ mod tests {
             (":dicts", ReplCommand::Dictionaries),
             (":type 1 + 2", ReplCommand::TypeOf("1 + 2".into())),
             (":t x", ReplCommand::TypeOf("x".into())),
+            (":check f 1", ReplCommand::Check("f 1".into())),
             (":let f x = x", ReplCommand::Let("let f x = x".into())),
-            (":load file.tl", ReplCommand::Load("file.tl".into())),
+            (":load file.tl", ReplCommand::Load(vec!["file.tl".into()])),
+            (
+                ":load a.tl  b.tl",
+                ReplCommand::Load(vec!["a.tl".into(), "b.tl".into()]),
+            ),
             (":browse fo", ReplCommand::Browse(Some("fo".into()))),
             (":browse", ReplCommand::Browse(None)),
             (":set default on", ReplCommand::SetDefault(true)),
             (":set default off", ReplCommand::SetDefault(false)),
+            (":set steplimit 1000", ReplCommand::SetStepLimit(Some(1000))),
+            (":set steplimit off", ReplCommand::SetStepLimit(None)),
+            (":set checked on", ReplCommand::SetCheckedArithmetic(true)),
+            (":set showtypes on", ReplCommand::SetShowTypes(true)),
+            (":set autosave off", ReplCommand::SetAutosave(false)),
+            (
+                ":set prompt \"tl> \"",
+                ReplCommand::SetPrompt("tl> ".into()),
+            ),
+            (":set prompt λ", ReplCommand::SetPrompt("λ".into())),
+            (
+                ":set prompt-cont \"|  \"",
+                ReplCommand::SetContinuationPrompt("|  ".into()),
+            ),
             (":unset foo", ReplCommand::Unset("foo".into())),
+            (":doc foo", ReplCommand::Doc("foo".into())),
+            (":format a.tl", ReplCommand::Format("a.tl".into())),
             (":reload", ReplCommand::Reload),
+            (":edit", ReplCommand::Edit),
+            (":time 1 + 2", ReplCommand::Time("1 + 2".into())),
             ("let x = x", ReplCommand::Let("let x = x".into())),
+            ("infixr 5 <+>", ReplCommand::Let("infixr 5 <+>".into())),
             ("1 + 2", ReplCommand::Eval("1 + 2".into())),
         ];
         for (input, expected) in cases {
@@ -774,7 +1439,13 @@ mod tests {
 
     #[test]
     fn parse_repl_command_invalid_inputs() {
-        for input in [":set default maybe", ":set default", ":set other on"] {
+        for input in [
+            ":set default maybe",
+            ":set default",
+            ":set other on",
+            ":set steplimit 0",
+            ":set steplimit many",
+        ] {
             match parse_repl_command(input) {
                 ReplCommand::Invalid(s) => assert_eq!(s, input),
                 other => panic!("expected invalid for `{input}`, got {other:?}"),
@@ -803,6 +1474,47 @@ mod tests {
 
         let unset_err = handle_command(&mut state, ReplCommand::Unset("foo".into()), &NoopIo);
         assert_msgs(unset_err, &[Expected::Err("未定義")]);
+
+        let lint = handle_command(
+            &mut state,
+            ReplCommand::Let("let konst x y = x".into()),
+            &NoopIo,
+        );
+        assert_msgs(
+            lint,
+            &[
+                Expected::Out("Defined konst"),
+                Expected::Out("[LINT003] 引数 y は使われていません"),
+            ],
+        );
+
+        let op = handle_command(
+            &mut state,
+            ReplCommand::Let("let (|>) x f = f x".into()),
+            &NoopIo,
+        );
+        assert_msgs(op, &[Expected::Out("Defined |>")]);
+        let piped = handle_command(
+            &mut state,
+            ReplCommand::Eval("4 |> konst 9".into()),
+            &NoopIo,
+        );
+        assert_msgs(piped, &[Expected::Value("9")]);
+
+        let fixity = handle_command(&mut state, ReplCommand::Let("infixr 6 <->".into()), &NoopIo);
+        assert_msgs(fixity, &[]);
+        let minus = handle_command(
+            &mut state,
+            ReplCommand::Let("let (<->) a b = a - b".into()),
+            &NoopIo,
+        );
+        assert_msgs(minus, &[Expected::Out("Defined <->")]);
+        let right = handle_command(
+            &mut state,
+            ReplCommand::Eval("10 <-> 4 <-> 3".into()),
+            &NoopIo,
+        );
+        assert_msgs(right, &[Expected::Value("9")]);
     }
 
     #[test]
@@ -820,6 +1532,62 @@ mod tests {
 
         let parse_err = handle_command(&mut state, ReplCommand::TypeOf("(1 +".into()), &NoopIo);
         assert_msgs(parse_err, &[Expected::Err("[PAR")]);
+
+        // 自己適用は評価へ逃げず、オカーズチェックの型エラーをそのまま表示する。
+        let occurs_err = handle_command(
+            &mut state,
+            ReplCommand::TypeOf("\\x -> x x".into()),
+            &NoopIo,
+        );
+        assert_msgs(occurs_err, &[Expected::Err("無限型を構築できません")]);
+
+        // :check は評価しないので、発散する式でも型だけを返す。
+        let diverge = "let loop n = loop (n + 1) in loop 0 == 1";
+        let checked = handle_command(&mut state, ReplCommand::Check(diverge.into()), &NoopIo);
+        assert_msgs(checked, &[Expected::Out("-- Bool")]);
+        let check_err = handle_command(
+            &mut state,
+            ReplCommand::Check("if True then 'a' else False".into()),
+            &NoopIo,
+        );
+        assert_msgs(check_err, &[Expected::Err("[TYPE")]);
+
+        let timed = handle_command(&mut state, ReplCommand::Time("it * 10".into()), &NoopIo);
+        assert_msgs(timed, &[Expected::Value("20"), Expected::Out("経過時間")]);
+
+        let timed_err = handle_command(&mut state, ReplCommand::Time("(1 +".into()), &NoopIo);
+        assert_msgs(timed_err, &[Expected::Err("[PAR")]);
+
+        let limit = handle_command(&mut state, ReplCommand::SetStepLimit(Some(50)), &NoopIo);
+        assert_msgs(limit, &[Expected::Out("set steplimit = 50")]);
+        let within = handle_command(&mut state, ReplCommand::Eval("1 + 2".into()), &NoopIo);
+        assert_msgs(within, &[Expected::Value("3")]);
+        let runaway = handle_command(
+            &mut state,
+            ReplCommand::Eval("let loop n = loop (n + 1) in loop 0".into()),
+            &NoopIo,
+        );
+        assert_msgs(runaway, &[Expected::Err("EVAL100")]);
+        let unlimited = handle_command(&mut state, ReplCommand::SetStepLimit(None), &NoopIo);
+        assert_msgs(unlimited, &[Expected::Out("set steplimit = off")]);
+
+        let overflow_src = "9223372036854775807 + 1";
+        let wrapped = handle_command(&mut state, ReplCommand::Eval(overflow_src.into()), &NoopIo);
+        assert_msgs(wrapped, &[Expected::Value("-9223372036854775808")]);
+        let checked = handle_command(&mut state, ReplCommand::SetCheckedArithmetic(true), &NoopIo);
+        assert_msgs(checked, &[Expected::Out("set checked = on")]);
+        let overflow = handle_command(&mut state, ReplCommand::Eval(overflow_src.into()), &NoopIo);
+        assert_msgs(overflow, &[Expected::Err("EVAL060")]);
+
+        let show = handle_command(&mut state, ReplCommand::SetShowTypes(true), &NoopIo);
+        assert_msgs(show, &[Expected::Out("set showtypes = on")]);
+        let typed = handle_command(&mut state, ReplCommand::Eval("\"ab\"".into()), &NoopIo);
+        assert_msgs(typed, &[Expected::Value("\"ab\" :: [Char]")]);
+        let timed_typed = handle_command(&mut state, ReplCommand::Time("True".into()), &NoopIo);
+        assert_msgs(
+            timed_typed,
+            &[Expected::Value("True :: Bool"), Expected::Out("経過時間")],
+        );
     }
 
     #[test]
@@ -827,13 +1595,17 @@ mod tests {
         let io = MapIo::new().ok("mem://ok", "let x = 1;");
         let mut state = mk_state();
 
-        let load = handle_command(&mut state, ReplCommand::Load("mem://ok".into()), &io);
+        let load = handle_command(&mut state, ReplCommand::Load(vec!["mem://ok".into()]), &io);
         assert_msgs(load, &[Expected::Out("Loaded"), Expected::Out("x ::")]);
 
         let reload = handle_command(&mut state, ReplCommand::Reload, &io);
         assert_msgs(reload, &[Expected::Out("Reloaded")]);
 
-        let missing = handle_command(&mut state, ReplCommand::Load("mem://missing".into()), &io);
+        let missing = handle_command(
+            &mut state,
+            ReplCommand::Load(vec!["mem://missing".into()]),
+            &io,
+        );
         assert_msgs(missing, &[Expected::Err("not found")]);
 
         let mut fresh = mk_state();
@@ -841,10 +1613,262 @@ mod tests {
         assert_msgs(reload_err, &[Expected::Err("直近の :load")]);
     }
 
+    #[test]
+    fn earlier_definitions_survive_later_loads_and_reloads() {
+        let io = MapIo::new().ok(
+            "mem://count",
+            "let count n = if n == 0 then 0 else count (n - 1) + 1;",
+        );
+        let mut state = mk_state();
+        handle_command(
+            &mut state,
+            ReplCommand::Load(vec!["mem://count".into()]),
+            &io,
+        );
+        let keep = handle_command(
+            &mut state,
+            ReplCommand::Let("let keep = count 2;".into()),
+            &io,
+        );
+        assert_msgs(keep, &[Expected::Out("Defined keep")]);
+        for _ in 0..3 {
+            handle_command(&mut state, ReplCommand::Reload, &io);
+        }
+        let value = handle_command(&mut state, ReplCommand::Eval("keep + count 3".into()), &io);
+        assert_msgs(value, &[Expected::Value("5")]);
+    }
+
+    #[test]
+    fn reload_reports_changed_definitions() {
+        let io = EditorIo {
+            files: RefCell::new(HashMap::from([(
+                "mem://diff".to_string(),
+                "let f x = x + 1;\nlet g = 1;\nlet h = True;".to_string(),
+            )])),
+            edit: |src| src.to_string(),
+        };
+        let mut state = ReplSession::with_defaults();
+        handle_command(
+            &mut state,
+            ReplCommand::Load(vec!["mem://diff".into()]),
+            &io,
+        );
+
+        let unchanged = handle_command(&mut state, ReplCommand::Reload, &io);
+        assert_msgs(unchanged, &[Expected::Out("Reloaded 3 def(s)")]);
+
+        io.files.borrow_mut().insert(
+            "mem://diff".into(),
+            "let f x = x == 1;\nlet h = True;\nlet k = 'c';".into(),
+        );
+        let reload = handle_command(&mut state, ReplCommand::Reload, &io);
+        assert_msgs(
+            reload,
+            &[
+                Expected::Out("Reloaded 3 def(s)"),
+                Expected::Out("type changed: f :: Eq a, Num a => a -> Bool (was Num a => a -> a)"),
+                Expected::Out("added: k :: Char"),
+                Expected::Out("removed: g"),
+            ],
+        );
+    }
+
+    #[test]
+    fn edit_reloads_session_source_from_editor() {
+        let mut state = mk_state();
+        let defined = handle_command(
+            &mut state,
+            ReplCommand::Let("let double x = x + x".into()),
+            &NoopIo,
+        );
+        assert_msgs(defined, &[Expected::Out("Defined double")]);
+
+        let io = EditorIo {
+            files: RefCell::new(HashMap::new()),
+            edit: |src| {
+                assert_eq!(src, "let double x = x + x\n");
+                src.replace("x + x", "x * 2")
+            },
+        };
+        let edited = handle_command(&mut state, ReplCommand::Edit, &io);
+        assert_msgs(
+            edited,
+            &[
+                Expected::Out("Reloaded 1 def(s)"),
+                Expected::Out("double ::"),
+            ],
+        );
+        assert_eq!(state.source, vec!["let double x = x * 2".to_string()]);
+        let value = handle_command(&mut state, ReplCommand::Eval("double 21".into()), &io);
+        assert_msgs(value, &[Expected::Value("42")]);
+
+        let broken = EditorIo {
+            files: RefCell::new(HashMap::new()),
+            edit: |_| "let = ".into(),
+        };
+        let failed = handle_command(&mut state, ReplCommand::Edit, &broken);
+        assert_msgs(failed, &[Expected::Err("PAR")]);
+        assert_eq!(state.source, vec!["let double x = x * 2".to_string()]);
+
+        let unsupported = handle_command(&mut state, ReplCommand::Edit, &NoopIo);
+        assert_msgs(unsupported, &[Expected::Err("書き込めません")]);
+    }
+
+    #[test]
+    fn load_multiple_files_resolves_imports() {
+        let io = MapIo::new()
+            .ok("proj/util.tl", "let double x = x * 2;")
+            .ok(
+                "proj/main.tl",
+                "import \"util.tl\";\nlet quad x = double (double x);",
+            )
+            .ok(
+                "proj/lib/seven.tl",
+                "import \"../util.tl\";\nlet seven = 7;",
+            )
+            .ok("cyc/a.tl", "import \"b.tl\";\nlet a = 1;")
+            .ok("cyc/b.tl", "import \"./a.tl\";\nlet b = 2;");
+        let mut state = mk_state();
+
+        let load = handle_command(
+            &mut state,
+            ReplCommand::Load(vec!["proj/main.tl".into(), "proj/lib/seven.tl".into()]),
+            &io,
+        );
+        assert_msgs(
+            load,
+            &[
+                Expected::Out("Loaded 2 def(s) from proj/main.tl (import: proj/util.tl)"),
+                Expected::Out("double ::"),
+                Expected::Out("quad ::"),
+                Expected::Out("Loaded 2 def(s) from proj/lib/seven.tl (import: proj/util.tl)"),
+                Expected::Out("double ::"),
+                Expected::Out("seven ::"),
+            ],
+        );
+        let quad = handle_command(&mut state, ReplCommand::Eval("quad seven".into()), &io);
+        assert_msgs(quad, &[Expected::Value("28")]);
+
+        let cycle = handle_command(&mut state, ReplCommand::Load(vec!["cyc/a.tl".into()]), &io);
+        assert_msgs(
+            cycle,
+            &[Expected::Err(
+                "import が循環しています: cyc/a.tl -> cyc/b.tl -> cyc/a.tl",
+            )],
+        );
+        assert!(state.type_env.lookup("a").is_none());
+    }
+
+    #[test]
+    fn modules_keep_clashing_names_apart() {
+        let io = MapIo::new()
+            .ok("a.tl", "module Alpha\nlet size = 1;\nlet twice x = x + x;")
+            .ok(
+                "b.tl",
+                "module Beta (size)\nlet size = 20;\nlet secret = 0;",
+            )
+            .ok("bad.tl", "module Bad (missing)\nlet x = 1;");
+        let mut state = mk_state();
+        let load = handle_command(
+            &mut state,
+            ReplCommand::Load(vec!["a.tl".into(), "b.tl".into()]),
+            &io,
+        );
+        assert!(load.iter().all(|m| !matches!(m, ReplMsg::Err(_))));
+
+        let sum = handle_command(
+            &mut state,
+            ReplCommand::Eval("Alpha.twice Alpha.size + Beta.size".into()),
+            &io,
+        );
+        assert_msgs(sum, &[Expected::Value("22")]);
+        let flat = handle_command(&mut state, ReplCommand::Eval("size".into()), &io);
+        assert_msgs(flat, &[Expected::Value("20")]);
+        assert!(state.type_env.lookup("Beta.secret").is_none());
+
+        let browse = handle_command(&mut state, ReplCommand::Browse(Some("Beta.".into())), &io);
+        assert_msgs(browse, &[Expected::Out("Beta.size :: Int")]);
+
+        let bad = handle_command(&mut state, ReplCommand::Load(vec!["bad.tl".into()]), &io);
+        assert_msgs(bad, &[Expected::Err("missing は定義されていません")]);
+    }
+
+    #[test]
+    fn doc_command_shows_doc_comments() {
+        let io = MapIo::new().ok(
+            "doc.tl",
+            "module Geo\n-- | 正方形の面積。\n--   辺の長さを受け取る。\nlet area x = x * x;\nlet plain = 0;",
+        );
+        let mut state = mk_state();
+        let load = handle_command(&mut state, ReplCommand::Load(vec!["doc.tl".into()]), &io);
+        assert!(load.iter().all(|m| !matches!(m, ReplMsg::Err(_))));
+
+        let doc = handle_command(&mut state, ReplCommand::Doc("area".into()), &io);
+        assert_msgs(
+            doc,
+            &[
+                Expected::Out("area ::"),
+                Expected::Out("正方形の面積。"),
+                Expected::Out("辺の長さを受け取る。"),
+            ],
+        );
+        let qualified = handle_command(&mut state, ReplCommand::Doc("Geo.area".into()), &io);
+        assert_msgs(
+            qualified,
+            &[
+                Expected::Out("Geo.area ::"),
+                Expected::Out("正方形の面積。"),
+                Expected::Out("辺の長さを受け取る。"),
+            ],
+        );
+        let plain = handle_command(&mut state, ReplCommand::Doc("plain".into()), &io);
+        assert_msgs(
+            plain,
+            &[Expected::Out("plain ::"), Expected::Out("ドキュメントなし")],
+        );
+        let missing = handle_command(&mut state, ReplCommand::Doc("nope".into()), &io);
+        assert_msgs(missing, &[Expected::Err("未定義")]);
+    }
+
+    #[test]
+    fn format_command_prints_canonical_source() {
+        let io = MapIo::new()
+            .ok(
+                "messy.tl",
+                "-- | 2 倍にする。\ndouble::Int->Int;let double x=x*2;\nlet main=double(1+2) ;",
+            )
+            .ok("broken.tl", "let main = ;");
+        let mut state = mk_state();
+        let formatted = handle_command(&mut state, ReplCommand::Format("messy.tl".into()), &io);
+        let lines: Vec<String> = formatted
+            .into_iter()
+            .map(|msg| match msg {
+                ReplMsg::Out(line) => line,
+                _ => panic!("unexpected message"),
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "-- | 2 倍にする。",
+                "double :: Int -> Int;",
+                "let double x = x * 2;",
+                "",
+                "let main = double (1 + 2);",
+            ]
+        );
+        // 整形は表示だけで、定義は読み込まない。
+        assert!(state.type_env.lookup("double").is_none());
+
+        let broken = handle_command(&mut state, ReplCommand::Format("broken.tl".into()), &io);
+        assert_msgs(broken, &[Expected::Err("PAR")]);
+    }
+
     #[derive(Default)]
     struct ScriptedLineSource {
         events: VecDeque<ScriptEvent>,
         history: Vec<String>,
+        prompts: Vec<String>,
         saved: bool,
     }
 
@@ -858,13 +1882,15 @@ mod tests {
             Self {
                 events: events.into_iter().collect(),
                 history: Vec::new(),
+                prompts: Vec::new(),
                 saved: false,
             }
         }
     }
 
     impl ReplLineSource for ScriptedLineSource {
-        fn read_line(&mut self, _prompt: &str) -> io::Result<ReadResult> {
+        fn read_line(&mut self, prompt: &str) -> io::Result<ReadResult> {
+            self.prompts.push(prompt.to_string());
             match self.events.pop_front().unwrap_or(ScriptEvent::Eof) {
                 ScriptEvent::Line(line) => Ok(ReadResult::Line(line.to_string())),
                 ScriptEvent::Eof => Ok(ReadResult::Eof),
@@ -894,7 +1920,7 @@ mod tests {
         let mut out = Vec::new();
         let mut err = Vec::new();
 
-        run_repl_with(&mut script, &io, &mut out, &mut err).unwrap();
+        run_repl_with(&mut script, &io, None, &mut out, &mut err).unwrap();
 
         let stdout = String::from_utf8(out).expect("utf8");
         assert!(stdout.contains("TypeLang REPL"));
@@ -904,4 +1930,67 @@ mod tests {
         assert!(script.history.len() >= 2);
         assert!(err.is_empty());
     }
+
+    #[test]
+    fn run_repl_with_script_uses_configured_prompts() {
+        let events = vec![
+            ScriptEvent::Line(":set prompt \"tl> \""),
+            ScriptEvent::Line("(1 +"),
+            ScriptEvent::Line("2)"),
+            ScriptEvent::Line(":set prompt-cont \"|  \""),
+            ScriptEvent::Line("[1,"),
+            ScriptEvent::Line("2]"),
+            ScriptEvent::Eof,
+        ];
+        let mut script = ScriptedLineSource::new(events);
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+
+        run_repl_with(&mut script, &NoopIo, None, &mut out, &mut err).unwrap();
+
+        assert_eq!(
+            script.prompts,
+            vec!["> ", "tl> ", "... ", "tl> ", "tl> ", "|  ", "tl> "]
+        );
+        let stdout = String::from_utf8(out).expect("utf8");
+        assert!(stdout.contains("set prompt = \"tl> \""));
+        assert!(stdout.contains('3'));
+        assert!(err.is_empty());
+    }
+
+    #[test]
+    fn run_repl_with_autosave_restores_definitions_on_next_launch() {
+        let io = EditorIo {
+            files: RefCell::new(HashMap::new()),
+            edit: |src| src.to_string(),
+        };
+        let session = Some("session.tl");
+
+        // autosave を有効にしていなければ何も保存しない。
+        let mut script = ScriptedLineSource::new(vec![ScriptEvent::Line("let one = 1")]);
+        run_repl_with(&mut script, &io, session, &mut Vec::new(), &mut Vec::new()).unwrap();
+        assert!(io.files.borrow().is_empty());
+
+        let mut script = ScriptedLineSource::new(vec![
+            ScriptEvent::Line(":set autosave on"),
+            ScriptEvent::Line("let square x = x * x"),
+            ScriptEvent::Line(":quit"),
+        ]);
+        run_repl_with(&mut script, &io, session, &mut Vec::new(), &mut Vec::new()).unwrap();
+        assert_eq!(io.files.borrow()["session.tl"], "let square x = x * x\n");
+
+        let mut script = ScriptedLineSource::new(vec![
+            ScriptEvent::Line("square 7"),
+            ScriptEvent::Line(":set autosave off"),
+        ]);
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        run_repl_with(&mut script, &io, session, &mut out, &mut err).unwrap();
+        let stdout = String::from_utf8(out).expect("utf8");
+        assert!(stdout.contains("Restored 1 def(s) from session.tl"));
+        assert!(stdout.contains("49"));
+        assert!(err.is_empty());
+        // 復元後に autosave を切ると、保存済みのセッションは消える。
+        assert!(io.files.borrow().is_empty());
+    }
 }
@@ -5,14 +5,16 @@
 //! TypeLang のプログラム定義を REPL 環境へ読み込むための補助モジュール。
 //! 型推論・既定化・評価の順に処理し、安全に環境へ取り込む。
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::panic;
+use std::path::{Component, Path, PathBuf};
 
 use crate::ast as A;
 use crate::infer::{type_from_texpr, type_from_texpr_with_vars};
+use crate::primitives::{JUST, MAYBE_TYPE, NOTHING};
 use crate::runtime::make_data_ctor;
 use crate::typesys::{
-    generalize, qualify, unify, Scheme, TApp, TCon, TFun, TVar, TVarSupply, Type,
+    generalize, qualify, unify, Scheme, TApp, TCon, TFun, TTuple, TVar, TVarSupply, Type,
 };
 
 use super::pipeline::{eval_expr_for_pipeline, fallback_scheme_from_value, infer_qual_type};
@@ -30,10 +32,41 @@ pub fn load_program_into_env(
     type_env: &mut crate::typesys::TypeEnv,
     class_env: &mut crate::typesys::ClassEnv,
     value_env: &mut crate::evaluator::Env,
+) -> Result<Vec<String>, String> {
+    load_program(prog, type_env, class_env, value_env, true)
+}
+
+/// プログラムの型だけを型・クラス環境へ取り込み、トップレベルの定義は評価しない。
+///
+/// ネイティブビルドはこちらを使い、`trace` の出力や深い再帰をコンパイル時に起こさない。
+/// 評価結果が無いため、推論に失敗した定義は代表型で復旧せずエラーにする。
+///
+/// # Errors
+/// 型推論に失敗した場合は型エラーを文字列化して返す。
+pub fn check_program_into_env(
+    prog: &A::Program,
+    type_env: &mut crate::typesys::TypeEnv,
+    class_env: &mut crate::typesys::ClassEnv,
+) -> Result<Vec<String>, String> {
+    // データ構築子の登録先として使うだけの使い捨て環境。
+    let mut value_env = crate::evaluator::initial_env();
+    let result = load_program(prog, type_env, class_env, &mut value_env, false);
+    value_env.teardown();
+    result
+}
+
+fn load_program(
+    prog: &A::Program,
+    type_env: &mut crate::typesys::TypeEnv,
+    class_env: &mut crate::typesys::ClassEnv,
+    value_env: &mut crate::evaluator::Env,
+    evaluate: bool,
 ) -> Result<Vec<String>, String> {
     let mut type_env_tmp = type_env.clone_env();
     let mut class_env_tmp = class_env.clone();
-    let mut value_env_tmp = crate::evaluator::Env::from_map(value_env.snapshot());
+    // 既存の定義が捕捉したフレームは壊さずに残し、読み込みのたびにフレームが
+    // 連なっていかないよう、現在の束縛を 1 つのフレームへ畳み込んでから追加する。
+    let mut value_env_tmp = value_env.flatten();
 
     for class_decl in &prog.class_decls {
         register_class_decl(class_decl, &mut class_env_tmp).map_err(|e| format!("[TYPE] {e}"))?;
@@ -52,6 +85,7 @@ pub fn load_program_into_env(
             &mut type_env_tmp,
             &mut class_env_tmp,
             &mut value_env_tmp,
+            evaluate,
         )
     }));
     let loaded = match eval_result {
@@ -68,19 +102,143 @@ pub fn load_program_into_env(
             panic::resume_unwind(payload);
         }
     };
-    value_env.teardown();
     *type_env = type_env_tmp;
     *class_env = class_env_tmp;
     *value_env = value_env_tmp;
     Ok(loaded)
 }
 
+/// 読み込み済みの 1 ファイル分のプログラム。
+pub struct LoadedModule {
+    pub path: String,
+    pub program: A::Program,
+}
+
+/// `import` を辿り、読み込むべきファイルを依存順（取り込まれる側が先）に並べる。
+///
+/// `load` はパスを受け取りプログラムを返す関数。`import` のパスは取り込み元ファイルの
+/// ディレクトリを基準に解決する。同じファイルは一度だけ読み込み、循環 import はエラーにする。
+///
+/// # Errors
+/// `load` の失敗、または循環 import を検出した場合にメッセージを返す。
+pub fn resolve_imports<F>(entry: &str, mut load: F) -> Result<Vec<LoadedModule>, String>
+where
+    F: FnMut(&str) -> Result<A::Program, String>,
+{
+    let mut stack = Vec::new();
+    let mut visited = HashSet::new();
+    let mut ordered = Vec::new();
+    visit_imports(
+        entry.to_string(),
+        &mut load,
+        &mut stack,
+        &mut visited,
+        &mut ordered,
+    )?;
+    Ok(ordered)
+}
+
+/// ファイルを `import` ごと読み込み、`main` を評価した値を返す（REPL を介さない実行経路）。
+///
+/// 定義は依存順に新しい環境へ取り込まれ、トップレベル定義は読み込み時に正格に評価される。
+/// そのため `main` 本体の `println` などの副作用は、値を返す前に出力順どおり実行される。
+/// ネイティブ生成の `emit_native` に対応するインタプリタ側の入口。
+///
+/// # Errors
+/// 読み込み・パース・型推論・評価に失敗した場合や、`main` が定義されていない場合に
+/// メッセージを返す。
+#[cfg_attr(coverage, coverage(off))]
+pub fn run_file(path: &str) -> Result<crate::evaluator::Value, String> {
+    let modules = resolve_imports(path, |p| {
+        let src = std::fs::read_to_string(p)
+            .map_err(|e| format!("ファイルの読み込みに失敗しました ({p}): {e}"))?;
+        crate::parser::parse_program(&src).map_err(|e| format!("{e}"))
+    })?;
+    let mut type_env = crate::infer::initial_env();
+    let mut class_env = crate::infer::initial_class_env();
+    let mut value_env = crate::evaluator::initial_env();
+    for module in &modules {
+        load_program_into_env(
+            &module.program,
+            &mut type_env,
+            &mut class_env,
+            &mut value_env,
+        )?;
+    }
+    value_env
+        .get("main")
+        .ok_or_else(|| format!("{path} に main が定義されていません"))
+}
+
+fn visit_imports<F>(
+    path: String,
+    load: &mut F,
+    stack: &mut Vec<PathBuf>,
+    visited: &mut HashSet<PathBuf>,
+    ordered: &mut Vec<LoadedModule>,
+) -> Result<(), String>
+where
+    F: FnMut(&str) -> Result<A::Program, String>,
+{
+    let key = normalize_path(Path::new(&path));
+    if let Some(start) = stack.iter().position(|p| *p == key) {
+        let cycle: Vec<String> = stack[start..]
+            .iter()
+            .chain(std::iter::once(&key))
+            .map(|p| p.display().to_string())
+            .collect();
+        return Err(format!("import が循環しています: {}", cycle.join(" -> ")));
+    }
+    if !visited.insert(key.clone()) {
+        return Ok(());
+    }
+    let program = load(&path)?;
+    let base = Path::new(&path).parent().unwrap_or(Path::new(""));
+    stack.push(key);
+    for import in &program.imports {
+        let target = normalize_path(&base.join(&import.path));
+        visit_imports(target.display().to_string(), load, stack, visited, ordered)?;
+    }
+    stack.pop();
+    ordered.push(LoadedModule { path, program });
+    Ok(())
+}
+
+/// `.` と `..` を字句的に畳み込み、同じファイルを指すパスを同一視できるようにする。
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if matches!(out.components().next_back(), Some(Component::Normal(_))) {
+                    out.pop();
+                } else {
+                    out.push("..");
+                }
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
 fn process_top_level_decls(
     prog: &A::Program,
     type_env_tmp: &mut crate::typesys::TypeEnv,
     class_env_tmp: &mut crate::typesys::ClassEnv,
     value_env_tmp: &mut crate::evaluator::Env,
+    evaluate: bool,
 ) -> Result<Vec<String>, String> {
+    let aliases = prog.qualified_aliases();
+    if let Some((qualified, name)) = aliases
+        .iter()
+        .find(|(_, name)| !prog.decls.iter().any(|decl| decl.name == *name))
+    {
+        return Err(format!(
+            "module が公開する {name} は定義されていません ({qualified})"
+        ));
+    }
     let mut loaded: Vec<String> = Vec::new();
     for decl in &prog.decls {
         let orig = if decl.params.is_empty() {
@@ -102,25 +260,87 @@ fn process_top_level_decls(
                         .map_err(|e| format!("[{}] {}", e.code, e.message))?;
                 }
                 let sch = generalize(type_env_tmp, q_rhs);
-                let val =
-                    eval_expr_for_pipeline(&body, value_env_tmp).map_err(|e| e.to_string())?;
+                if evaluate {
+                    let val = eval_expr_for_pipeline(&body, value_env_tmp, None)
+                        .map_err(|e| e.to_string())?;
+                    value_env_tmp.insert(decl.name.clone(), val);
+                }
+                type_env_tmp.extend(decl.name.clone(), sch);
+                loaded.push(decl.name.clone());
+            }
+            Err(err) if !evaluate => {
+                let sch = scheme_without_evaluation(decl, &orig, type_env_tmp, class_env_tmp)
+                    .ok_or_else(|| err.to_string())??;
                 type_env_tmp.extend(decl.name.clone(), sch);
-                value_env_tmp.insert(decl.name.clone(), val);
                 loaded.push(decl.name.clone());
             }
             Err(_) => {
-                let val =
-                    eval_expr_for_pipeline(&body, value_env_tmp).map_err(|e| e.to_string())?;
+                let val = eval_expr_for_pipeline(&body, value_env_tmp, None)
+                    .map_err(|e| e.to_string())?;
                 let sch = fallback_scheme_from_value(type_env_tmp, &val);
                 type_env_tmp.extend(decl.name.clone(), sch);
                 value_env_tmp.insert(decl.name.clone(), val);
                 loaded.push(decl.name.clone());
             }
         }
+        register_qualified_aliases(&aliases, &decl.name, type_env_tmp, value_env_tmp);
     }
     Ok(loaded)
 }
 
+/// 評価せずに読み込む定義の推論が失敗したときの復旧。
+///
+/// 型注釈があれば、自己再帰をローカルの `let` と同じく単相の型変数として束縛して推論し直し、
+/// 注釈と矛盾しないことを確かめてから注釈の型を使う。注釈の無い関数は評価して読み込む場合と同じく
+/// 代表型 `()` で登録する。どちらにも当たらなければ `None` を返す。
+fn scheme_without_evaluation(
+    decl: &A::TopLevel,
+    orig: &A::Expr,
+    type_env: &crate::typesys::TypeEnv,
+    class_env: &crate::typesys::ClassEnv,
+) -> Option<Result<Scheme, String>> {
+    let Some(sig) = &decl.signature else {
+        return (!decl.params.is_empty()).then(|| {
+            Ok(generalize(
+                type_env,
+                qualify(Type::TTuple(TTuple { items: vec![] }), vec![]),
+            ))
+        });
+    };
+    let ty_anno = type_from_texpr(&sig.r#type);
+    let recursive = normalize_expr(&A::Expr::LetIn {
+        bindings: vec![(decl.name.clone(), vec![], orig.clone())],
+        body: Box::new(A::Expr::Var {
+            name: decl.name.clone(),
+            span: A::Span::dummy(),
+        }),
+        span: A::Span::dummy(),
+    });
+    if let Ok(q_rhs) = infer_qual_type(type_env, class_env, &recursive, false) {
+        if let Err(e) = unify(q_rhs.r#type, ty_anno.clone()) {
+            return Some(Err(format!("[{}] {}", e.code, e.message)));
+        }
+    }
+    Some(Ok(generalize(type_env, qualify(ty_anno, vec![]))))
+}
+
+/// `module` 宣言で公開された定義 `name` を `Foo.name` の修飾名でも参照できるよう登録する。
+fn register_qualified_aliases(
+    aliases: &[(String, String)],
+    name: &str,
+    type_env: &mut crate::typesys::TypeEnv,
+    value_env: &crate::evaluator::Env,
+) {
+    for (qualified, _) in aliases.iter().filter(|(_, target)| target == name) {
+        if let Some(scheme) = type_env.lookup(name).cloned() {
+            type_env.extend(qualified.clone(), scheme);
+        }
+        if let Some(value) = value_env.get(name) {
+            value_env.insert(qualified.clone(), value);
+        }
+    }
+}
+
 #[cfg_attr(coverage, coverage(off))]
 fn register_data_decl(
     decl: &A::DataDecl,
@@ -155,12 +375,15 @@ fn register_data_decl(
         .filter_map(|name| params.get(name).cloned())
         .collect();
 
+    // 組み込みの `Maybe` を宣言し直す場合は、組み込みの構築子を置き換える。
+    let replaces_builtin =
+        |name: &str| decl.name == MAYBE_TYPE && (name == NOTHING || name == JUST);
     let mut seen_ctor: HashMap<String, ()> = HashMap::new();
     for ctor in &decl.constructors {
         if seen_ctor.insert(ctor.name.clone(), ()).is_some() {
             return Err(format!("コンストラクタ {} が重複しています", ctor.name));
         }
-        if type_env.lookup(&ctor.name).is_some() {
+        if type_env.lookup(&ctor.name).is_some() && !replaces_builtin(&ctor.name) {
             return Err(format!("{} は既に定義済みです", ctor.name));
         }
 
@@ -189,6 +412,7 @@ fn register_data_decl(
                 make_data_ctor(&ctor.name, ctor.args.len()),
             )
             .is_some()
+            && !replaces_builtin(&ctor.name)
         {
             return Err(format!("{} は値として既に定義済みです", ctor.name));
         }
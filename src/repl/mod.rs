@@ -19,4 +19,7 @@ mod util;
 
 // 既存パス互換のために公開 API を再公開
 pub use cmd::{run_repl, run_repl_with_native};
-pub use loader::load_program_into_env;
+pub use loader::{
+    check_program_into_env, load_program_into_env, resolve_imports, run_file, LoadedModule,
+};
+pub use printer::print_value;
@@ -5,22 +5,22 @@
 
 use crate::ast as A;
 use crate::evaluator::Value;
-use crate::infer::{infer_expr, InferState};
+pub(crate) use crate::infer::infer_qual_type;
 use crate::typesys::{
-    apply_defaulting_simple, generalize, qualify, t_string, ClassEnv, QualType, Scheme,
-    Substitutable, TCon, TTuple, Type, TypeEnv,
-};
-use crate::{
-    errors::{EvalError, TypeError},
-    evaluator,
+    generalize, qualify, t_string, ClassEnv, QualType, Scheme, TCon, TTuple, Type, TypeEnv,
 };
+use crate::{errors::EvalError, evaluator};
 
 use super::util::normalize_expr;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) enum EvaluationMode {
+    /// 推論の成否にかかわらず評価する。
     Always,
+    /// 推論に失敗したときだけ評価し、値から代表型を復元する。
     OnInferenceFailure,
+    /// 型検査のみ行い、評価は一切しない（推論失敗はそのままエラー）。
+    Never,
 }
 
 pub(crate) struct PipelineResult {
@@ -29,24 +29,6 @@ pub(crate) struct PipelineResult {
     pub value: Option<Value>,
 }
 
-/// 式を推論し、必要に応じて defaulting を適用した `QualType` を返す。
-pub(crate) fn infer_qual_type(
-    type_env: &TypeEnv,
-    class_env: &ClassEnv,
-    expr: &A::Expr,
-    defaulting_on: bool,
-) -> Result<QualType, TypeError> {
-    let mut st = InferState {
-        supply: Default::default(),
-    };
-    let (subst, qual) = infer_expr(type_env, class_env, &mut st, expr)?;
-    let mut applied = qual.apply_subst(&subst);
-    if defaulting_on {
-        applied = apply_defaulting_simple(&applied);
-    }
-    Ok(applied)
-}
-
 /// 評価結果から復旧用の型スキームを構築する。
 pub(crate) fn fallback_scheme_from_value(type_env: &TypeEnv, value: &Value) -> Scheme {
     let fallback_type = fallback_type_from_value(value);
@@ -76,18 +58,22 @@ fn fallback_type_from_value(value: &Value) -> Type {
         }),
         Value::String(_) => t_string(),
         Value::List(_) | Value::Tuple(_) => Type::TTuple(TTuple { items: vec![] }),
-        Value::Data { .. } | Value::Closure { .. } | Value::Prim(_) => {
-            Type::TTuple(TTuple { items: vec![] })
-        }
+        Value::Data { .. }
+        | Value::Closure { .. }
+        | Value::Prim(_)
+        | Value::Map(_)
+        | Value::Array(_)
+        | Value::Ref(_) => Type::TTuple(TTuple { items: vec![] }),
     }
 }
 
-/// ユーティリティ: 式を評価し、結果値を返す。
+/// ユーティリティ: 式を評価し、結果値を返す。`step_limit` で評価ステップ数を制限できる。
 pub(crate) fn eval_expr_for_pipeline(
     expr: &A::Expr,
     env: &evaluator::Env,
+    step_limit: Option<u64>,
 ) -> Result<Value, EvalError> {
-    evaluator::eval_expr(expr, env)
+    evaluator::eval_expr_with_limit(expr, env, step_limit)
 }
 
 /// 型推論と評価を一度ずつ実行し、REPL コマンド共通の結果を返す。
@@ -98,13 +84,17 @@ pub(crate) fn run_repl_pipeline(
     defaulting_on: bool,
     value_env: &evaluator::Env,
     mode: EvaluationMode,
+    step_limit: Option<u64>,
 ) -> Result<PipelineResult, String> {
     let normalized = normalize_expr(expr);
     match infer_qual_type(type_env, class_env, &normalized, defaulting_on) {
         Ok(qual) => {
             let scheme = generalize(type_env, qual.clone());
             let value = if matches!(mode, EvaluationMode::Always) {
-                Some(eval_expr_for_pipeline(&normalized, value_env).map_err(|e| e.to_string())?)
+                Some(
+                    eval_expr_for_pipeline(&normalized, value_env, step_limit)
+                        .map_err(|e| e.to_string())?,
+                )
             } else {
                 None
             };
@@ -114,9 +104,14 @@ pub(crate) fn run_repl_pipeline(
                 value,
             })
         }
+        Err(e) if mode == EvaluationMode::Never => Err(e.to_string()),
+        // 無限型（オカーズチェック失敗）は評価結果の代表型で覆い隠さず、型を尋ねた利用者へそのまま示す。
+        Err(e) if mode == EvaluationMode::OnInferenceFailure && e.0.code == "TYPE002" => {
+            Err(e.to_string())
+        }
         Err(_) => {
-            let value =
-                eval_expr_for_pipeline(&normalized, value_env).map_err(|e| e.to_string())?;
+            let value = eval_expr_for_pipeline(&normalized, value_env, step_limit)
+                .map_err(|e| e.to_string())?;
             let qual = fallback_qual_from_value(&value);
             let scheme = fallback_scheme_from_value(type_env, &value);
             Ok(PipelineResult {
@@ -6,6 +6,7 @@
 //! 表示形式を一箇所にまとめ、対話時の出力を統一する。
 
 use crate::evaluator::Value;
+use runtime_common::format_double;
 use std::io::{self, Write};
 
 const HELP_TEXT: &str = concat!(
@@ -13,12 +14,22 @@ const HELP_TEXT: &str = concat!(
     "  :help              ヘルプ（本メッセージ）\n",
     "  :t EXPR            型を表示\n",
     "  :type EXPR         :t と同じ\n",
+    "  :check EXPR        評価せずに型検査だけ行う\n",
+    "  :time EXPR         評価して経過時間を表示\n",
     "  :let DEF[; ...]    その場で定義（複数は ; 区切り）\n",
-    "  :load PATH         ファイルからロード\n",
+    "  :load PATH...      ファイルを順にロード（import も解決）\n",
     "  :reload            直近ロードしたファイルを再読み込み\n",
+    "  :edit              :let の定義を $EDITOR で編集して再読み込み\n",
     "  :browse [PFX]      定義一覧（接頭辞フィルタ）\n",
+    "  :doc NAME          定義のドキュメント（-- | コメント）を表示\n",
+    "  :format PATH       ファイルを整形して表示（ファイルは書き換えない）\n",
     "  :unset NAME        定義を削除\n",
     "  :set default on|off 型表示の defaulting を切替\n",
+    "  :set steplimit N|off 評価ステップ数の上限を設定\n",
+    "  :set checked on|off 整数演算のオーバーフロー検査を切替\n",
+    "  :set showtypes on|off 評価結果に型を併記（value :: Type）\n",
+    "  :set autosave on|off 終了時に定義を保存し次回起動時に復元\n",
+    "  :set prompt \"S\"   プロンプトを変更（継続行は :set prompt-cont \"S\"）\n",
     "  :dictionaries      辞書情報の確認（CLI 推奨）\n",
     "  :quit              終了\n",
     "\n",
@@ -36,8 +47,7 @@ pub(crate) fn print_help() {
     let _ = render_help(&mut out);
 }
 /// 評価結果を REPL 向けのフォーマットで出力する。
-#[allow(dead_code)]
-pub(crate) fn print_value(v: &Value) {
+pub fn print_value(v: &Value) {
     let mut out = io::stdout();
     let _ = write_value(&mut out, v);
 }
@@ -49,19 +59,29 @@ pub(crate) fn render_help<W: Write>(out: &mut W) -> io::Result<()> {
 
 /// 値出力を任意のライターへ書き出す。
 pub(crate) fn write_value<W: Write>(out: &mut W, v: &Value) -> io::Result<()> {
+    writeln!(out, "{}", render_value(v))
+}
+
+/// 値に型を `value :: Type` の形で添えて書き出す。`:set showtypes on` で使う。
+pub(crate) fn write_typed_value<W: Write>(out: &mut W, v: &Value, ty: &str) -> io::Result<()> {
+    writeln!(out, "{} :: {}", render_value(v), ty)
+}
+
+fn render_value(v: &Value) -> String {
     match v {
-        Value::String(s) => writeln!(out, "\"{}\"", s),
-        Value::Int(i) => writeln!(out, "{}", i),
-        Value::Double(d) => writeln!(out, "{}", d),
-        Value::Bool(b) => writeln!(out, "{}", if *b { "True" } else { "False" }),
-        Value::Char(c) => writeln!(out, "'{}'", c),
-        other => writeln!(out, "{:?}", other),
+        Value::String(s) => format!("\"{}\"", s),
+        Value::Int(i) => i.to_string(),
+        Value::Double(d) => format_double(*d),
+        Value::Bool(b) => (if *b { "True" } else { "False" }).to_string(),
+        Value::Char(c) => format!("'{}'", c),
+        Value::Tuple(items) if items.is_empty() => "()".to_string(),
+        other => format!("{:?}", other),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{render_help, write_value};
+    use super::{render_help, write_typed_value, write_value};
     use crate::evaluator::Value;
 
     fn write_to_string(v: &Value) -> String {
@@ -91,6 +111,9 @@ mod tests {
         let v = Value::Double(1.5);
         assert_eq!(write_to_string(&v), "1.5\n");
 
+        let v = Value::Double(2.0);
+        assert_eq!(write_to_string(&v), "2.0\n");
+
         let v = Value::Bool(true);
         assert_eq!(write_to_string(&v), "True\n");
 
@@ -103,7 +126,18 @@ mod tests {
         let v = Value::List(vec![Value::Int(1), Value::Int(2)]);
         assert_eq!(write_to_string(&v), format!("{:?}\n", &v));
 
+        let v = Value::Tuple(Vec::new());
+        assert_eq!(write_to_string(&v), "()\n");
+
         let v = Value::Tuple(vec![Value::Int(1), Value::Bool(false)]);
         assert_eq!(write_to_string(&v), format!("{:?}\n", &v));
     }
+
+    #[test]
+    /// 型付きの出力では値の表記に ` :: Type` が続くか検証する。
+    fn write_typed_value_appends_type() {
+        let mut buf = Vec::new();
+        write_typed_value(&mut buf, &Value::String("s".into()), "[Char]").unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), "\"s\" :: [Char]\n");
+    }
 }
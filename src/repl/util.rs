@@ -9,21 +9,18 @@ use crate::ast as A;
 /// ユーザー入力の式を正規化し、負のべき乗などを推論しやすい形に直す。
 pub(crate) fn normalize_expr(e: &A::Expr) -> A::Expr {
     use A::Expr::*;
-    /// `0 - n` 形式の負整数リテラルを検出する補助関数。
+    /// 負整数リテラル（`-n` または `0 - n` 形式）を検出する補助関数。
     fn neg_int(e: &A::Expr) -> Option<i64> {
-        if let A::Expr::BinOp {
-            op, left, right, ..
-        } = e
-        {
-            if op == "-" {
-                if let A::Expr::IntLit { value: 0, .. } = **left {
-                    if let A::Expr::IntLit { value: n, .. } = **right {
-                        return Some(-n);
-                    }
-                }
-            }
+        match e {
+            A::Expr::IntLit { value, .. } if *value < 0 => Some(*value),
+            A::Expr::BinOp {
+                op, left, right, ..
+            } if op == "-" => match (&**left, &**right) {
+                (A::Expr::IntLit { value: 0, .. }, A::Expr::IntLit { value: n, .. }) => Some(-n),
+                _ => None,
+            },
+            _ => None,
         }
-        None
     }
     match e {
         BinOp {
@@ -2,16 +2,23 @@
 // 役割: 評価時に用いる値表現とプリミティブ生成ヘルパーを提供する
 // 意図: 評価器・プリミティブ定義から共有される基盤ロジックを分離する
 // 関連ファイル: src/evaluator.rs, src/primitives.rs
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
+use runtime_common::{
+    format_double, format_specs, parse_format, render_format, FormatArg, FormatPiece,
+};
+
 use crate::ast::Expr;
 use crate::errors::EvalError;
+use crate::primitives::{JUST, NOTHING};
 
 thread_local! {
     static PRINTLN_CAPTURE: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+    static CHECKED_ARITHMETIC: Cell<bool> = const { Cell::new(false) };
 }
 
 #[derive(Debug, Clone)]
@@ -135,6 +142,12 @@ impl Env {
         }
     }
 
+    /// 親フレームまでの束縛を 1 つのフレームへ畳み込んだ新しい環境を返す。
+    /// 元のフレームは変更しないため、それを捕捉したクロージャは引き続き動く。
+    pub fn flatten(&self) -> Self {
+        Self::from_map(self.snapshot())
+    }
+
     /// 環境をフラットな `HashMap` としてコピーする。
     pub fn snapshot(&self) -> HashMap<String, Value> {
         let mut acc = HashMap::new();
@@ -142,10 +155,14 @@ impl Env {
         acc
     }
 
-    /// 環境を破棄する前に全束縛を明示的に解放し、`Rc` 循環を防ぐ。
+    /// 環境を破棄する前に親フレームまで含めた全束縛を明示的に解放し、`Rc` 循環を防ぐ。
     /// REPL 等で環境を継続利用する場合は呼び出さないこと。
     pub fn teardown(&mut self) {
-        self.inner.bindings.borrow_mut().clear();
+        let mut frame = Some(Rc::clone(&self.inner));
+        while let Some(current) = frame {
+            current.bindings.borrow_mut().clear();
+            frame = current.parent.clone();
+        }
     }
 
     /// 束縛を追加または更新する。
@@ -223,6 +240,153 @@ pub enum Value {
         env: CapturedEnv,
     },
     Prim(PrimOp),
+    /// `emptyMap`/`insert` で構築する連想配列。`insert` は表を複製してから更新する。
+    Map(Rc<HashMap<Value, Value>>),
+    /// `fromList` で構築する配列。添字アクセスは O(1)。
+    Array(Rc<Vec<Value>>),
+    /// `newRef` で作る書き換え可能な参照セル。複製しても同じセルを指す。
+    Ref(Rc<RefCell<Value>>),
+}
+
+/// 連想配列のキーとしての等価性（`key_eq`）。
+///
+/// `==` と同じく文字列と文字のリストを同じ値とみなすが、NaN は同じビット列の NaN と
+/// 等しいとして反射律を保つ。関数値は `Eq` 制約によりキーにならないため常に等しくない。
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        key_eq(self, other)
+    }
+}
+
+impl Eq for Value {}
+
+/// `key_eq` と整合する構造的ハッシュ。
+///
+/// `Int` と整数値の `Double`、文字列と文字のリストは等しいとみなすため、それぞれ同じハッシュへ揃える。
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Value::Int(i) => {
+                0u8.hash(state);
+                i.hash(state);
+            }
+            Value::Double(d)
+                if d.fract() == 0.0 && *d >= i64::MIN as f64 && *d < i64::MAX as f64 =>
+            {
+                0u8.hash(state);
+                (*d as i64).hash(state);
+            }
+            Value::Double(d) => {
+                1u8.hash(state);
+                d.to_bits().hash(state);
+            }
+            Value::Bool(b) => {
+                2u8.hash(state);
+                b.hash(state);
+            }
+            Value::Char(c) => hash_char(*c, state),
+            Value::String(text) => {
+                5u8.hash(state);
+                text.chars().count().hash(state);
+                for c in text.chars() {
+                    hash_char(c, state);
+                }
+            }
+            Value::List(items) => {
+                5u8.hash(state);
+                hash_items(items, state);
+            }
+            Value::Tuple(items) => {
+                6u8.hash(state);
+                hash_items(items, state);
+            }
+            Value::Data {
+                constructor,
+                fields,
+            } => {
+                7u8.hash(state);
+                constructor.hash(state);
+                hash_items(fields, state);
+            }
+            Value::Map(entries) => {
+                8u8.hash(state);
+                entries.len().hash(state);
+            }
+            Value::Array(items) => {
+                9u8.hash(state);
+                hash_items(items, state);
+            }
+            Value::Ref(cell) => {
+                10u8.hash(state);
+                Rc::as_ptr(cell).hash(state);
+            }
+            Value::Closure { .. } | Value::Prim(_) => 11u8.hash(state),
+        }
+    }
+}
+
+fn hash_char<H: Hasher>(c: char, state: &mut H) {
+    3u8.hash(state);
+    c.hash(state);
+}
+
+/// 長さと各要素を順にハッシュする。文字列のハッシュと同じ並びになるよう `Vec` の実装には頼らない。
+fn hash_items<H: Hasher>(items: &[Value], state: &mut H) {
+    items.len().hash(state);
+    for item in items {
+        item.hash(state);
+    }
+}
+
+/// 連想配列のキー比較。`structural_eq` と同じ規則で比べるが、失敗を返さず、
+/// 同じビット列の Double（NaN を含む）を等しいとみなす点が異なる。
+fn key_eq(a: &Value, b: &Value) -> bool {
+    fn all_key_eq(xs: &[Value], ys: &[Value]) -> bool {
+        xs.len() == ys.len() && xs.iter().zip(ys).all(|(x, y)| key_eq(x, y))
+    }
+
+    match (a, b) {
+        (Value::Int(x), Value::Int(y)) => x == y,
+        (Value::Double(x), Value::Double(y)) => x == y || x.to_bits() == y.to_bits(),
+        (Value::Int(x), Value::Double(y)) | (Value::Double(y), Value::Int(x)) => *x as f64 == *y,
+        (Value::Bool(x), Value::Bool(y)) => x == y,
+        (Value::Char(x), Value::Char(y)) => x == y,
+        (Value::String(x), Value::String(y)) => x == y,
+        (Value::String(text), Value::List(items)) | (Value::List(items), Value::String(text)) => {
+            chars_eq(text, items)
+        }
+        (Value::List(xs), Value::List(ys)) | (Value::Tuple(xs), Value::Tuple(ys)) => {
+            all_key_eq(xs, ys)
+        }
+        (Value::Array(xs), Value::Array(ys)) => all_key_eq(xs, ys),
+        (
+            Value::Data {
+                constructor: c1,
+                fields: f1,
+            },
+            Value::Data {
+                constructor: c2,
+                fields: f2,
+            },
+        ) => c1 == c2 && all_key_eq(f1, f2),
+        (Value::Map(xs), Value::Map(ys)) => {
+            xs.len() == ys.len()
+                && xs
+                    .iter()
+                    .all(|(k, v)| ys.get(k).is_some_and(|other| key_eq(v, other)))
+        }
+        (Value::Ref(x), Value::Ref(y)) => Rc::ptr_eq(x, y),
+        _ => false,
+    }
+}
+
+/// 文字列と、文字だけからなるリストの内容が一致するかを判定する。
+fn chars_eq(text: &str, items: &[Value]) -> bool {
+    let mut chars = text.chars();
+    items
+        .iter()
+        .all(|item| matches!(item, Value::Char(c) if chars.next() == Some(*c)))
+        && chars.next().is_none()
 }
 
 #[derive(Clone, Debug)]
@@ -237,6 +401,48 @@ pub enum PrimOp {
         arity: usize,
         collected: Vec<Value>,
     },
+    /// 引数を取らない定数。環境へ登録する時点で値そのものになる。
+    Const(fn() -> Value),
+    /// 3 引数のプリミティブ。引数が揃うまで `collected` に溜める。
+    Prim3 {
+        f: fn(Value, Value, Value) -> Result<Value, EvalError>,
+        collected: Vec<Value>,
+    },
+    /// `catch`。評価器が `catch e h` の形の呼び出しを見つけると、`e` の評価エラーを捕捉して `h` を呼ぶ。
+    /// 値として受け渡された後に適用された場合は第 1 引数が評価済みのため、そのまま返す。
+    Catch {
+        protected: Option<Box<Value>>,
+    },
+    /// `&&` / `||`。`decisive` は左辺だけで結果が決まる値(`&&` は偽、`||` は真)。
+    /// 評価器は `a && b` の形の呼び出しを見つけると、`a` が `decisive` なら `b` を評価しない。
+    /// 値として受け渡された後に適用された場合は両辺とも評価済みのため、そのまま組み合わせる。
+    ShortCircuit {
+        decisive: bool,
+        lhs: Option<bool>,
+    },
+    /// `fmap` / `concatMap`。関数の呼び出しには評価器が要るため、写像する関数を `func` に溜め、
+    /// 2 引数目は評価器が `fmap_value` で写像する。`concat` が真なら写した結果を `concat_op` で連結する。
+    Fmap {
+        func: Option<Box<Value>>,
+        concat: bool,
+    },
+    /// `foldl'`。関数の呼び出しには評価器が要るため、畳み込む関数と初期値を溜め、
+    /// 3 引数目は評価器が `fold_left_value` で先頭から順に畳み込む。
+    FoldLeft {
+        func: Option<Box<Value>>,
+        init: Option<Box<Value>>,
+    },
+    /// `format`。最初の引数で書式文字列を解釈し、指定子の数だけ引数を `collected` に溜めてから整形する。
+    Format {
+        pieces: Option<Vec<FormatPiece>>,
+        collected: Vec<Value>,
+    },
+    /// `enumFrom` / `[x..]`。開始値を受け取ると `start` から 1 ずつ増える上限のない整数列になる。
+    /// 要素は作らずに保持し、`take` が必要な個数だけ取り出す。リストではないため、型検査は
+    /// `take n [x..]` 以外の場所に置くことを許さない。
+    EnumFrom {
+        start: Option<i64>,
+    },
 }
 
 impl PrimOp {
@@ -248,8 +454,18 @@ impl PrimOp {
         PrimOp::Prim2 { f, captured: None }
     }
 
+    pub const fn ternary(f: fn(Value, Value, Value) -> Result<Value, EvalError>) -> Self {
+        PrimOp::Prim3 {
+            f,
+            collected: Vec::new(),
+        }
+    }
+
     pub fn into_value(self) -> Value {
-        Value::Prim(self)
+        match self {
+            PrimOp::Const(f) => f(),
+            op => Value::Prim(op),
+        }
     }
 
     pub fn to_value(&self) -> Value {
@@ -286,14 +502,224 @@ impl PrimOp {
                     }))
                 }
             }
+            PrimOp::Const(_) => Err(EvalError::new(
+                "EVAL020",
+                "関数適用対象が関数ではありません",
+                None,
+            )),
+            PrimOp::Prim3 { f, mut collected } => {
+                collected.push(arg);
+                if collected.len() < 3 {
+                    return Ok(Value::Prim(PrimOp::Prim3 { f, collected }));
+                }
+                let mut args = collected.into_iter();
+                match (args.next(), args.next(), args.next()) {
+                    (Some(a), Some(b), Some(c)) => f(a, b, c),
+                    _ => unreachable!("Prim3 は 3 引数が揃ってから呼び出す"),
+                }
+            }
+            PrimOp::Catch { protected: None } => Ok(Value::Prim(PrimOp::Catch {
+                protected: Some(Box::new(arg)),
+            })),
+            PrimOp::Catch {
+                protected: Some(value),
+            } => Ok(*value),
+            PrimOp::ShortCircuit {
+                decisive,
+                lhs: None,
+            } => Ok(Value::Prim(PrimOp::ShortCircuit {
+                decisive,
+                lhs: Some(logic_operand(&arg)?),
+            })),
+            PrimOp::ShortCircuit {
+                decisive,
+                lhs: Some(lhs),
+            } => Ok(Value::Bool(if lhs == decisive {
+                lhs
+            } else {
+                logic_operand(&arg)?
+            })),
+            PrimOp::Fmap { func: None, concat } => Ok(Value::Prim(PrimOp::Fmap {
+                func: Some(Box::new(arg)),
+                concat,
+            })),
+            PrimOp::Fmap { func: Some(_), .. } => Err(EvalError::new(
+                "EVAL020",
+                "fmap は評価器の外では適用できません",
+                None,
+            )),
+            PrimOp::FoldLeft { func: None, .. } => Ok(Value::Prim(PrimOp::FoldLeft {
+                func: Some(Box::new(arg)),
+                init: None,
+            })),
+            PrimOp::FoldLeft {
+                func: Some(func),
+                init: None,
+            } => Ok(Value::Prim(PrimOp::FoldLeft {
+                func: Some(func),
+                init: Some(Box::new(arg)),
+            })),
+            PrimOp::FoldLeft { init: Some(_), .. } => Err(EvalError::new(
+                "EVAL020",
+                "foldl' は評価器の外では適用できません",
+                None,
+            )),
+            PrimOp::Format {
+                pieces: None,
+                collected,
+            } => {
+                let pieces = format_pieces(&arg)?;
+                format_or_collect(pieces, collected)
+            }
+            PrimOp::Format {
+                pieces: Some(pieces),
+                mut collected,
+            } => {
+                collected.push(arg);
+                format_or_collect(pieces, collected)
+            }
+            PrimOp::EnumFrom { start: None } => match arg {
+                Value::Int(start) => Ok(Value::Prim(PrimOp::EnumFrom { start: Some(start) })),
+                _ => Err(EvalError::new(
+                    "EVAL050",
+                    "enumFrom の引数は Int である必要があります",
+                    None,
+                )),
+            },
+            PrimOp::EnumFrom { start: Some(_) } => Err(EvalError::new(
+                "EVAL020",
+                "関数適用対象が関数ではありません",
+                None,
+            )),
         }
     }
 }
 
+/// `format` の第 1 引数を書式として解釈する。
+fn format_pieces(template: &Value) -> Result<Vec<FormatPiece>, EvalError> {
+    let text = text_of(template).ok_or_else(|| {
+        EvalError::new(
+            "EVAL050",
+            "format の第 1 引数は文字列である必要があります",
+            None,
+        )
+    })?;
+    parse_format(&text).map_err(|msg| EvalError::new("EVAL050", msg, None))
+}
+
+/// 指定子の数だけ引数が揃っていれば整形し、足りなければ部分適用のまま返す。
+fn format_or_collect(pieces: Vec<FormatPiece>, collected: Vec<Value>) -> Result<Value, EvalError> {
+    if collected.len() < format_specs(&pieces).len() {
+        return Ok(Value::Prim(PrimOp::Format {
+            pieces: Some(pieces),
+            collected,
+        }));
+    }
+    let texts: Vec<Option<String>> = collected.iter().map(text_of).collect();
+    let args: Vec<FormatArg<'_>> = collected
+        .iter()
+        .zip(&texts)
+        .map(|(value, text)| match (value, text) {
+            (Value::Int(n), _) => Ok(FormatArg::Int(*n)),
+            (Value::Double(d), _) => Ok(FormatArg::Double(*d)),
+            (_, Some(text)) => Ok(FormatArg::Str(text)),
+            _ => Err(EvalError::new(
+                "EVAL050",
+                "format の引数は Int・Double・String のいずれかである必要があります",
+                None,
+            )),
+        })
+        .collect::<Result<_, _>>()?;
+    render_format(&pieces, &args)
+        .map(Value::String)
+        .map_err(|msg| EvalError::new("EVAL050", msg, None))
+}
+
+/// `foldl'` の実装。リスト(文字列を含む)の要素を先頭から順に `step` で累積値へ畳み込む。
+///
+/// 累積値は各段で評価済みの値として受け渡すため、要素数によらず追加の領域を使わない。
+pub(crate) fn fold_left_value(
+    init: Value,
+    list: Value,
+    mut step: impl FnMut(Value, Value) -> Result<Value, EvalError>,
+) -> Result<Value, EvalError> {
+    match list {
+        Value::List(items) => items.into_iter().try_fold(init, &mut step),
+        Value::String(text) => text
+            .chars()
+            .try_fold(init, |acc, c| step(acc, Value::Char(c))),
+        _ => Err(EvalError::new(
+            "EVAL050",
+            "foldl' の第 3 引数はリストである必要があります",
+            None,
+        )),
+    }
+}
+
+/// `fmap` の実装。`Functor` のインスタンスごとに値を分解し、`apply` で写した値から組み立て直す。
+///
+/// - リスト(文字列を含む)は各要素を写す。
+/// - `Just x` / `Right x` は中身だけを写し、`Nothing` / `Left e` はそのまま返す。
+pub(crate) fn fmap_value(
+    container: Value,
+    mut apply: impl FnMut(Value) -> Result<Value, EvalError>,
+) -> Result<Value, EvalError> {
+    match container {
+        Value::List(items) => Ok(Value::List(
+            items.into_iter().map(apply).collect::<Result<_, _>>()?,
+        )),
+        Value::String(text) => Ok(Value::List(
+            text.chars()
+                .map(|c| apply(Value::Char(c)))
+                .collect::<Result<_, _>>()?,
+        )),
+        Value::Data {
+            constructor,
+            mut fields,
+        } => match (constructor.as_str(), fields.len()) {
+            ("Just" | "Right", 1) => {
+                let mapped = apply(fields.remove(0))?;
+                Ok(Value::Data {
+                    constructor,
+                    fields: vec![mapped],
+                })
+            }
+            ("Nothing", 0) | ("Left", 1) => Ok(Value::Data {
+                constructor,
+                fields,
+            }),
+            _ => Err(EvalError::new(
+                "EVAL050",
+                format!("fmap: {constructor} は Functor のインスタンスではありません"),
+                None,
+            )),
+        },
+        _ => Err(EvalError::new(
+            "EVAL050",
+            "fmap の第 2 引数はリスト・Maybe・Either のいずれかである必要があります",
+            None,
+        )),
+    }
+}
+
+/// `&&` / `||` の被演算子を取り出す。
+pub(crate) fn logic_operand(value: &Value) -> Result<bool, EvalError> {
+    match value {
+        Value::Bool(b) => Ok(*b),
+        _ => Err(EvalError::new(
+            "EVAL050",
+            "&& / || の被演算子は Bool である必要があります",
+            None,
+        )),
+    }
+}
+
+/// `show` の実装。入れ子でも曖昧にならないよう、文字列と文字は Haskell と同じく
+/// 引用符で囲み、特殊文字をエスケープする（`show "a\nb"` の結果は改行を含まず `"a\\nb"` となる）。
 pub(crate) fn py_show(v: Value) -> Result<Value, EvalError> {
     Ok(Value::String(match v {
         Value::Int(i) => i.to_string(),
-        Value::Double(d) => format!("{}", d),
+        Value::Double(d) => format_double(d),
         Value::Bool(b) => {
             if b {
                 "True".into()
@@ -301,8 +727,8 @@ pub(crate) fn py_show(v: Value) -> Result<Value, EvalError> {
                 "False".into()
             }
         }
-        Value::Char(c) => c.to_string(),
-        Value::String(s) => s,
+        Value::Char(c) => format!("'{}'", escape_for_show(&c.to_string(), '\'')),
+        Value::String(s) => format!("\"{}\"", escape_for_show(&s, '"')),
         Value::Data {
             constructor,
             fields,
@@ -324,6 +750,36 @@ pub(crate) fn py_show(v: Value) -> Result<Value, EvalError> {
     }))
 }
 
+/// `show` 用に文字列をエスケープする。`quote` は囲みに使う引用符で、それだけを `\` で退避する。
+fn escape_for_show(text: &str, quote: char) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c == quote => {
+                out.push('\\');
+                out.push(c);
+            }
+            c if c.is_control() => out.push_str(&format!("\\{}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// `println` が書き出す表示形式。トップレベルの文字列と文字は引用符なしでそのまま出し、
+/// それ以外は `show` と同じ表記にする。
+fn display_text(v: Value) -> Result<Value, EvalError> {
+    match v {
+        Value::String(s) => Ok(Value::String(s)),
+        Value::Char(c) => Ok(Value::String(c.to_string())),
+        other => py_show(other),
+    }
+}
+
 fn emit_line(text: &str) {
     let intercepted = PRINTLN_CAPTURE.with(|slot| {
         let mut guard = slot.borrow_mut();
@@ -339,8 +795,42 @@ fn emit_line(text: &str) {
     }
 }
 
+/// `trace` の出力を標準エラーへ書き出す。`println` と同じキャプチャ領域を共有する。
+fn emit_trace(text: &str) {
+    let intercepted = PRINTLN_CAPTURE.with(|slot| {
+        let mut guard = slot.borrow_mut();
+        if let Some(buffer) = guard.as_mut() {
+            buffer.push(text.to_string());
+            true
+        } else {
+            false
+        }
+    });
+    if !intercepted {
+        eprintln!("{}", text);
+    }
+}
+
+/// `trace msg x`: メッセージを標準エラーへ出力し、第 2 引数をそのまま返す。
+pub(crate) fn trace_op(message: Value, value: Value) -> Result<Value, EvalError> {
+    match message {
+        Value::String(text) => {
+            emit_trace(&text);
+            Ok(value)
+        }
+        other => Err(EvalError::new(
+            "EVAL052",
+            format!(
+                "trace: メッセージは String である必要があります: {:?}",
+                other
+            ),
+            None,
+        )),
+    }
+}
+
 pub(crate) fn println_op(value: Value) -> Result<Value, EvalError> {
-    let rendered = py_show(value)?;
+    let rendered = display_text(value)?;
     match rendered {
         Value::String(text) => {
             emit_line(&text);
@@ -354,28 +844,111 @@ pub(crate) fn println_op(value: Value) -> Result<Value, EvalError> {
     }
 }
 
-#[cfg(test)]
-fn capture_println<F, R>(action: F) -> (R, Vec<String>)
+/// クロージャ実行中の `println` / `trace` 出力を標準出力・標準エラーへ流さずに収集する。
+///
+/// 戻り値はクロージャの結果と、出力順に並んだ行の一覧。入れ子で呼び出した場合は
+/// 内側の呼び出しが自身の区間の出力のみを受け取り、終了後に外側の収集へ戻る。
+/// キャプチャは現在のスレッドに限られる。
+///
+/// # Examples
+/// ```
+/// use typelang::evaluator::{eval_expr, initial_env};
+/// use typelang::parser::parse_expr;
+/// use typelang::runtime::with_captured_output;
+///
+/// let expr = parse_expr("println 42").unwrap();
+/// let env = initial_env();
+/// let (result, lines) = with_captured_output(|| eval_expr(&expr, &env));
+/// assert!(result.is_ok());
+/// assert_eq!(lines, vec!["42".to_string()]);
+/// ```
+pub fn with_captured_output<F, R>(action: F) -> (R, Vec<String>)
 where
     F: FnOnce() -> R,
 {
-    PRINTLN_CAPTURE.with(|slot| {
-        let mut guard = slot.borrow_mut();
-        assert!(
-            guard.is_none(),
-            "capture_println: nested capture not supported"
-        );
-        *guard = Some(Vec::new());
-    });
+    /// パニック時も外側のキャプチャ状態を復元するためのガード。
+    struct Restore(Option<Option<Vec<String>>>);
+    impl Restore {
+        fn finish(&mut self) -> Vec<String> {
+            self.0
+                .take()
+                .and_then(|previous| PRINTLN_CAPTURE.with(|slot| slot.replace(previous)))
+                .unwrap_or_default()
+        }
+    }
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            self.finish();
+        }
+    }
+
+    let previous = PRINTLN_CAPTURE.with(|slot| slot.replace(Some(Vec::new())));
+    let mut guard = Restore(Some(previous));
     let result = action();
-    let lines = PRINTLN_CAPTURE.with(|slot| {
-        slot.borrow_mut()
-            .take()
-            .expect("capture_println: buffer must exist")
-    });
+    let lines = guard.finish();
     (result, lines)
 }
 
+/// クロージャ実行中の整数 `+` / `-` / `*` のオーバーフロー検査を切り替える。
+///
+/// `enabled` が真ならオーバーフロー時に `EVAL060` を返し、偽なら 2 の補数で折り返す
+/// (ネイティブバックエンドの既定と同じ挙動)。設定は現在のスレッドに限られ、
+/// 終了後は呼び出し前の状態へ戻る。
+pub fn with_checked_arithmetic<F, R>(enabled: bool, action: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    /// パニック時も呼び出し前の設定へ戻すためのガード。
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CHECKED_ARITHMETIC.with(|flag| flag.set(self.0));
+        }
+    }
+
+    let _restore = Restore(CHECKED_ARITHMETIC.with(|flag| flag.replace(enabled)));
+    action()
+}
+
+/// `+` / `-` / `*` の本体。Int 同士は Int、Double 同士は Double で計算する。
+///
+/// 型検査は両辺を同じ型へ単一化するため、片側だけが Int になるのは整数リテラル
+/// （`Num a => a`）が Double として使われた場合に限られる。評価器は型を持たないので、
+/// そのリテラルをここで Double へ変換してから計算する（`1 + 2.5` や
+/// `let f x = x + 0.5 in f 2`）。数値以外の組み合わせは EVAL050 とする。
+fn num_arith(
+    a: Value,
+    b: Value,
+    op_name: &str,
+    checked: fn(i64, i64) -> Option<i64>,
+    wrapping: fn(i64, i64) -> i64,
+    double: fn(f64, f64) -> f64,
+) -> Result<Value, EvalError> {
+    match (a, b) {
+        (Value::Int(lhs), Value::Int(rhs)) => {
+            if CHECKED_ARITHMETIC.with(Cell::get) {
+                checked(lhs, rhs).map(Value::Int).ok_or_else(|| {
+                    EvalError::new(
+                        "EVAL060",
+                        format!("({op_name}) の結果が Int の範囲を超えました"),
+                        None,
+                    )
+                })
+            } else {
+                Ok(Value::Int(wrapping(lhs, rhs)))
+            }
+        }
+        (Value::Double(lhs), Value::Double(rhs)) => Ok(Value::Double(double(lhs, rhs))),
+        (Value::Int(lhs), Value::Double(rhs)) => Ok(Value::Double(double(lhs as f64, rhs))),
+        (Value::Double(lhs), Value::Int(rhs)) => Ok(Value::Double(double(lhs, rhs as f64))),
+        _ => Err(EvalError::new(
+            "EVAL050",
+            format!("({op_name}) の引数は数値である必要があります"),
+            None,
+        )),
+    }
+}
+
 fn numeric_binop<T, Conv, Wrap, Op>(
     a: Value,
     b: Value,
@@ -394,16 +967,519 @@ where
     Ok(wrap(op(lhs, rhs)))
 }
 
+pub(crate) fn negate_op(v: Value) -> Result<Value, EvalError> {
+    match v {
+        Value::Int(i) if CHECKED_ARITHMETIC.with(Cell::get) => {
+            i.checked_neg().map(Value::Int).ok_or_else(|| {
+                EvalError::new("EVAL060", "negate の結果が Int の範囲を超えました", None)
+            })
+        }
+        Value::Int(i) => Ok(Value::Int(i.wrapping_neg())),
+        Value::Double(d) => Ok(Value::Double(-d)),
+        _ => Err(EvalError::new(
+            "EVAL050",
+            "negate の引数は Int または Double である必要があります",
+            None,
+        )),
+    }
+}
+
 pub(crate) fn add_op(a: Value, b: Value) -> Result<Value, EvalError> {
-    numeric_binop(a, b, to_int, Value::Int, |x, y| x + y)
+    num_arith(a, b, "+", i64::checked_add, i64::wrapping_add, |x, y| x + y)
 }
 
 pub(crate) fn sub_op(a: Value, b: Value) -> Result<Value, EvalError> {
-    numeric_binop(a, b, to_int, Value::Int, |x, y| x - y)
+    num_arith(a, b, "-", i64::checked_sub, i64::wrapping_sub, |x, y| x - y)
 }
 
 pub(crate) fn mul_op(a: Value, b: Value) -> Result<Value, EvalError> {
-    numeric_binop(a, b, to_int, Value::Int, |x, y| x * y)
+    num_arith(a, b, "*", i64::checked_mul, i64::wrapping_mul, |x, y| x * y)
+}
+
+fn int_bits(a: Value, b: Value, op: fn(i64, i64) -> i64) -> Result<Value, EvalError> {
+    match (a, b) {
+        (Value::Int(x), Value::Int(y)) => Ok(Value::Int(op(x, y))),
+        _ => Err(EvalError::new(
+            "EVAL050",
+            "ビット演算の引数は Int である必要があります",
+            None,
+        )),
+    }
+}
+
+pub(crate) fn band_op(a: Value, b: Value) -> Result<Value, EvalError> {
+    int_bits(a, b, |x, y| x & y)
+}
+
+pub(crate) fn bor_op(a: Value, b: Value) -> Result<Value, EvalError> {
+    int_bits(a, b, |x, y| x | y)
+}
+
+pub(crate) fn bxor_op(a: Value, b: Value) -> Result<Value, EvalError> {
+    int_bits(a, b, |x, y| x ^ y)
+}
+
+/// シフト量は下位 6 ビットのみを用いる（Cranelift の `ishl` / `sshr` と同じ）。
+pub(crate) fn shl_op(a: Value, b: Value) -> Result<Value, EvalError> {
+    int_bits(a, b, |x, y| x.wrapping_shl(y as u32))
+}
+
+pub(crate) fn shr_op(a: Value, b: Value) -> Result<Value, EvalError> {
+    int_bits(a, b, |x, y| x.wrapping_shr(y as u32))
+}
+
+fn double_unary(v: Value, op: fn(f64) -> f64) -> Result<Value, EvalError> {
+    Ok(Value::Double(op(to_double(&v)?)))
+}
+
+pub(crate) fn sqrt_op(v: Value) -> Result<Value, EvalError> {
+    double_unary(v, f64::sqrt)
+}
+
+pub(crate) fn sin_op(v: Value) -> Result<Value, EvalError> {
+    double_unary(v, f64::sin)
+}
+
+pub(crate) fn cos_op(v: Value) -> Result<Value, EvalError> {
+    double_unary(v, f64::cos)
+}
+
+pub(crate) fn exp_op(v: Value) -> Result<Value, EvalError> {
+    double_unary(v, f64::exp)
+}
+
+pub(crate) fn log_op(v: Value) -> Result<Value, EvalError> {
+    double_unary(v, f64::ln)
+}
+
+/// 丸めた結果が Int に収まらない（NaN・無限大を含む）場合は EVAL060 を返す。
+fn round_to_int(v: Value, op_name: &str, round: fn(f64) -> f64) -> Result<Value, EvalError> {
+    let rounded = round(to_double(&v)?);
+    if rounded.is_finite() && rounded >= i64::MIN as f64 && rounded < i64::MAX as f64 {
+        Ok(Value::Int(rounded as i64))
+    } else {
+        Err(EvalError::new(
+            "EVAL060",
+            format!("{op_name} の結果が Int の範囲を超えました"),
+            None,
+        ))
+    }
+}
+
+pub(crate) fn floor_op(v: Value) -> Result<Value, EvalError> {
+    round_to_int(v, "floor", f64::floor)
+}
+
+pub(crate) fn ceiling_op(v: Value) -> Result<Value, EvalError> {
+    round_to_int(v, "ceiling", f64::ceil)
+}
+
+/// Haskell と同じく 0.5 ちょうどは偶数側へ丸める。
+pub(crate) fn round_op(v: Value) -> Result<Value, EvalError> {
+    round_to_int(v, "round", runtime_common::round_half_even)
+}
+
+pub(crate) fn truncate_op(v: Value) -> Result<Value, EvalError> {
+    round_to_int(v, "truncate", f64::trunc)
+}
+
+pub(crate) fn from_integral_op(v: Value) -> Result<Value, EvalError> {
+    match v {
+        Value::Int(i) => Ok(Value::Double(i as f64)),
+        _ => Err(EvalError::new(
+            "EVAL050",
+            "fromIntegral の引数は Int である必要があります",
+            None,
+        )),
+    }
+}
+
+/// `(<>)`: リスト（文字列を含む）を連結する。
+///
+/// 評価器は型を持たないため `mempty` は空リストで表す。どちらかが文字列なら、
+/// もう一方が空リストや文字のリストでも結果を文字列にそろえる。
+pub(crate) fn append_op(lhs: Value, rhs: Value) -> Result<Value, EvalError> {
+    match (lhs, rhs) {
+        (Value::List(mut items), Value::List(rest)) => {
+            items.extend(rest);
+            Ok(Value::List(items))
+        }
+        (lhs, rhs) => match (text_of(&lhs), text_of(&rhs)) {
+            (Some(mut text), Some(rest)) => {
+                text.push_str(&rest);
+                Ok(Value::String(text))
+            }
+            _ => Err(EvalError::new(
+                "EVAL050",
+                "<> の被演算子はリストである必要があります",
+                None,
+            )),
+        },
+    }
+}
+
+/// `concat`: リストのリストを順に連結する。
+pub(crate) fn concat_op(lists: Value) -> Result<Value, EvalError> {
+    let lists = match lists {
+        Value::List(lists) => lists,
+        _ => {
+            return Err(EvalError::new(
+                "EVAL050",
+                "concat の引数はリストのリストである必要があります",
+                None,
+            ))
+        }
+    };
+    lists.into_iter().try_fold(empty_list(), append_op)
+}
+
+/// `intercalate`: `separator` を間に挟んでリストのリストを連結する。
+pub(crate) fn intercalate_op(separator: Value, lists: Value) -> Result<Value, EvalError> {
+    let lists = match lists {
+        Value::List(lists) => lists,
+        _ => {
+            return Err(EvalError::new(
+                "EVAL050",
+                "intercalate の第 2 引数はリストのリストである必要があります",
+                None,
+            ))
+        }
+    };
+    let mut joined = Vec::with_capacity(lists.len() * 2);
+    for (index, list) in lists.into_iter().enumerate() {
+        if index > 0 {
+            joined.push(separator.clone());
+        }
+        joined.push(list);
+    }
+    concat_op(Value::List(joined))
+}
+
+/// `unwords`: 文字列を空白 1 つで区切って連結する。
+pub(crate) fn unwords_op(words: Value) -> Result<Value, EvalError> {
+    as_text(intercalate_op(Value::String(" ".into()), words)?)
+}
+
+/// `unlines`: 各文字列の末尾に改行を付けて連結する。
+pub(crate) fn unlines_op(lines: Value) -> Result<Value, EvalError> {
+    let Value::List(lines) = lines else {
+        return Err(EvalError::new(
+            "EVAL050",
+            "unlines の引数は文字列のリストである必要があります",
+            None,
+        ));
+    };
+    let terminated = lines
+        .into_iter()
+        .map(|line| append_op(line, Value::String("\n".into())))
+        .collect::<Result<_, _>>()?;
+    as_text(concat_op(Value::List(terminated))?)
+}
+
+/// 空リストを空文字列へそろえる。`unwords []` などの結果を文字列として表示するため。
+fn as_text(value: Value) -> Result<Value, EvalError> {
+    append_op(Value::String(String::new()), value)
+}
+
+/// `stripPrefix`: `list` が `prefix` で始まれば残りを `Just` で、そうでなければ `Nothing` を返す。
+///
+/// 文字列の残りは文字列のまま返す。
+pub(crate) fn strip_prefix_op(prefix: Value, list: Value) -> Result<Value, EvalError> {
+    fn items(value: &Value) -> Option<Vec<Value>> {
+        match value {
+            Value::List(items) => Some(items.clone()),
+            Value::String(text) => Some(text.chars().map(Value::Char).collect()),
+            _ => None,
+        }
+    }
+    let (Some(prefix_items), Some(list_items)) = (items(&prefix), items(&list)) else {
+        return Err(EvalError::new(
+            "EVAL050",
+            "stripPrefix の引数はリストである必要があります",
+            None,
+        ));
+    };
+    if prefix_items.len() > list_items.len() {
+        return Ok(maybe_value(None));
+    }
+    for (expected, actual) in prefix_items.iter().zip(&list_items) {
+        if !eqv(expected, actual)? {
+            return Ok(maybe_value(None));
+        }
+    }
+    let rest = match &list {
+        Value::String(text) => Value::String(text.chars().skip(prefix_items.len()).collect()),
+        _ => Value::List(list_items[prefix_items.len()..].to_vec()),
+    };
+    Ok(maybe_value(Some(rest)))
+}
+
+/// 組み込みの `Maybe` の値を作る（`Some` は `Just`、`None` は `Nothing`）。
+pub(crate) fn maybe_value(value: Option<Value>) -> Value {
+    match value {
+        Some(value) => Value::Data {
+            constructor: JUST.into(),
+            fields: vec![value],
+        },
+        None => Value::Data {
+            constructor: NOTHING.into(),
+            fields: Vec::new(),
+        },
+    }
+}
+
+/// 文字列、または文字だけからなるリストの内容を取り出す。
+fn text_of(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::List(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::Char(c) => Some(*c),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// `readInt` / `readDouble` の共通処理。読み取りはネイティブランタイムと同じルーチンを使い、
+/// 読めれば `Just`、空文字列や不正な形式なら `Nothing` を返す。
+fn read_number<T>(
+    name: &str,
+    input: Value,
+    read: fn(&str) -> Option<T>,
+    wrap: fn(T) -> Value,
+) -> Result<Value, EvalError> {
+    let text = text_of(&input).ok_or_else(|| {
+        EvalError::new(
+            "EVAL050",
+            format!("{name} の引数は文字列である必要があります"),
+            None,
+        )
+    })?;
+    Ok(maybe_value(read(&text).map(wrap)))
+}
+
+/// `readInt`: 文字列を `Int` として読む（`readInt " 42 "` は `Just 42`、`readInt "4x"` は `Nothing`）。
+pub(crate) fn read_int_op(input: Value) -> Result<Value, EvalError> {
+    read_number("readInt", input, runtime_common::read_int, Value::Int)
+}
+
+/// `readDouble`: 文字列を `Double` として読む（`readDouble "1.5e3"` は `Just 1500.0`）。
+pub(crate) fn read_double_op(input: Value) -> Result<Value, EvalError> {
+    read_number(
+        "readDouble",
+        input,
+        runtime_common::read_double,
+        Value::Double,
+    )
+}
+
+/// `mempty`: 空リスト。
+pub(crate) fn empty_list() -> Value {
+    Value::List(Vec::new())
+}
+
+/// `null`: リスト（文字列を含む）が空かどうかを返す。
+pub(crate) fn null_op(v: Value) -> Result<Value, EvalError> {
+    match v {
+        Value::List(items) => Ok(Value::Bool(items.is_empty())),
+        Value::String(text) => Ok(Value::Bool(text.is_empty())),
+        _ => Err(EvalError::new(
+            "EVAL050",
+            "null の引数はリストである必要があります",
+            None,
+        )),
+    }
+}
+
+/// `length`: リスト（文字列を含む）の要素数を返す。
+pub(crate) fn length_op(v: Value) -> Result<Value, EvalError> {
+    let len = match v {
+        Value::List(items) => items.len(),
+        Value::String(text) => text.chars().count(),
+        _ => {
+            return Err(EvalError::new(
+                "EVAL050",
+                "length の引数はリストである必要があります",
+                None,
+            ))
+        }
+    };
+    Ok(Value::Int(len as i64))
+}
+
+/// `replicate` / `take` / `drop` の個数引数。負の個数は 0 とみなす。
+fn list_count(name: &str, count: &Value) -> Result<usize, EvalError> {
+    match count {
+        Value::Int(n) => Ok(usize::try_from(*n).unwrap_or(0)),
+        _ => Err(EvalError::new(
+            "EVAL050",
+            format!("{name} の第 1 引数は Int である必要があります"),
+            None,
+        )),
+    }
+}
+
+/// `replicate`: 値を指定した個数だけ並べたリストを作る（`replicate 3 'x'` は `"xxx"`）。
+pub(crate) fn replicate_op(count: Value, item: Value) -> Result<Value, EvalError> {
+    let count = list_count("replicate", &count)?;
+    Ok(Value::List(vec![item; count]))
+}
+
+/// `take` / `drop` の共通処理。`split` 個目の位置で分け、`keep_prefix` なら前半を返す。
+/// 個数がリストより長ければ全体（`drop` なら空リスト）になる。
+fn split_list(
+    name: &str,
+    count: Value,
+    list: Value,
+    keep_prefix: bool,
+) -> Result<Value, EvalError> {
+    let split = list_count(name, &count)?;
+    match list {
+        Value::List(mut items) => {
+            let split = split.min(items.len());
+            let rest = items.split_off(split);
+            Ok(Value::List(if keep_prefix { items } else { rest }))
+        }
+        Value::String(text) => {
+            let split = text
+                .char_indices()
+                .nth(split)
+                .map_or(text.len(), |(idx, _)| idx);
+            let (prefix, rest) = text.split_at(split);
+            Ok(Value::String(
+                if keep_prefix { prefix } else { rest }.to_string(),
+            ))
+        }
+        // 上限のない範囲は `take` が必要な個数だけ要素を作る。Haskell の `Int` と同じく `maxBound` で止まる。
+        Value::Prim(PrimOp::EnumFrom { start: Some(start) }) if keep_prefix => Ok(Value::List(
+            (start..=i64::MAX).take(split).map(Value::Int).collect(),
+        )),
+        _ => Err(EvalError::new(
+            "EVAL050",
+            format!("{name} の第 2 引数はリストである必要があります"),
+            None,
+        )),
+    }
+}
+
+/// `take`: 先頭から指定した個数の要素を取り出す。
+pub(crate) fn take_op(count: Value, list: Value) -> Result<Value, EvalError> {
+    split_list("take", count, list, true)
+}
+
+/// `drop`: 先頭から指定した個数の要素を取り除く。
+pub(crate) fn drop_op(count: Value, list: Value) -> Result<Value, EvalError> {
+    split_list("drop", count, list, false)
+}
+
+/// `fromList`: リストの要素をそのまま並べた配列を作る。
+pub(crate) fn array_from_list_op(v: Value) -> Result<Value, EvalError> {
+    match v {
+        Value::List(items) => Ok(Value::Array(Rc::new(items))),
+        Value::String(text) => Ok(Value::Array(Rc::new(
+            text.chars().map(Value::Char).collect(),
+        ))),
+        _ => Err(EvalError::new(
+            "EVAL050",
+            "fromList の引数はリストである必要があります",
+            None,
+        )),
+    }
+}
+
+/// `(!)`: 配列の `index` 番目の要素を返す。範囲外なら `EVAL062`。
+pub(crate) fn array_index_op(array: Value, index: Value) -> Result<Value, EvalError> {
+    let Value::Array(items) = array else {
+        return Err(EvalError::new(
+            "EVAL050",
+            "(!) の左辺は Array である必要があります",
+            None,
+        ));
+    };
+    let index = to_int(&index)?;
+    usize::try_from(index)
+        .ok()
+        .and_then(|offset| items.get(offset))
+        .cloned()
+        .ok_or_else(|| {
+            EvalError::new(
+                "EVAL062",
+                format!(
+                    "(!): 添字 {index} は配列の範囲外です (長さ {})",
+                    items.len()
+                ),
+                None,
+            )
+        })
+}
+
+/// `newRef`: 初期値を保持する参照セルを作る。
+pub(crate) fn new_ref_op(v: Value) -> Result<Value, EvalError> {
+    Ok(Value::Ref(Rc::new(RefCell::new(v))))
+}
+
+fn expect_ref(value: Value, op_name: &str) -> Result<Rc<RefCell<Value>>, EvalError> {
+    match value {
+        Value::Ref(cell) => Ok(cell),
+        _ => Err(EvalError::new(
+            "EVAL050",
+            format!("{op_name} の引数は Ref である必要があります"),
+            None,
+        )),
+    }
+}
+
+/// `readRef`: セルの現在の値を返す。
+pub(crate) fn read_ref_op(cell: Value) -> Result<Value, EvalError> {
+    Ok(expect_ref(cell, "readRef")?.borrow().clone())
+}
+
+/// `writeRef`: セルの値を置き換え、Unit（`()`）を返す。
+pub(crate) fn write_ref_op(cell: Value, value: Value) -> Result<Value, EvalError> {
+    *expect_ref(cell, "writeRef")?.borrow_mut() = value;
+    Ok(Value::Tuple(Vec::new()))
+}
+
+/// `emptyMap`: 空の連想配列。
+pub(crate) fn empty_map() -> Value {
+    Value::Map(Rc::new(HashMap::new()))
+}
+
+fn expect_map(value: Value, op_name: &str) -> Result<Rc<HashMap<Value, Value>>, EvalError> {
+    match value {
+        Value::Map(entries) => Ok(entries),
+        _ => Err(EvalError::new(
+            "EVAL050",
+            format!("{op_name} の引数は Map である必要があります"),
+            None,
+        )),
+    }
+}
+
+/// `insert`: キーに値を対応付けた新しい表を返す。元の表は変更しない。
+pub(crate) fn map_insert_op(key: Value, value: Value, map: Value) -> Result<Value, EvalError> {
+    let mut entries = expect_map(map, "insert")?;
+    Rc::make_mut(&mut entries).insert(key, value);
+    Ok(Value::Map(entries))
+}
+
+/// `findWithDefault`: 見つかった値、見つからなければ既定値を返す。
+pub(crate) fn map_find_with_default_op(
+    default: Value,
+    key: Value,
+    map: Value,
+) -> Result<Value, EvalError> {
+    let entries = expect_map(map, "findWithDefault")?;
+    Ok(entries.get(&key).cloned().unwrap_or(default))
+}
+
+/// `lookup`: 見つかった値を 1 要素、見つからなければ空のリストで返す。
+pub(crate) fn map_lookup_op(key: Value, map: Value) -> Result<Value, EvalError> {
+    let entries = expect_map(map, "lookup")?;
+    Ok(maybe_value(entries.get(&key).cloned()))
 }
 
 pub(crate) fn div_op(a: Value, b: Value) -> Result<Value, EvalError> {
@@ -482,6 +1558,12 @@ fn structural_compare(a: &Value, b: &Value) -> Result<std::cmp::Ordering, Compar
         (Value::Bool(x), Value::Bool(y)) => Ok(x.cmp(y)),
         (Value::Char(x), Value::Char(y)) => Ok(x.cmp(y)),
         (Value::String(x), Value::String(y)) => Ok(x.cmp(y)),
+        (Value::String(text), Value::List(_)) => {
+            structural_compare(&Value::List(char_values(text)), b)
+        }
+        (Value::List(_), Value::String(text)) => {
+            structural_compare(a, &Value::List(char_values(text)))
+        }
         (Value::List(xs), Value::List(ys)) => {
             for (vx, vy) in xs.iter().zip(ys.iter()) {
                 let ord = structural_compare(vx, vy)?;
@@ -528,19 +1610,67 @@ fn structural_compare(a: &Value, b: &Value) -> Result<std::cmp::Ordering, Compar
     }
 }
 
-fn eqv(a: &Value, b: &Value) -> Result<bool, EvalError> {
-    match structural_compare(a, b) {
-        Ok(Ordering::Equal) => Ok(true),
-        Ok(_) => Ok(false),
-        Err(CompareFailure::Mismatch) => Err(EvalError::new(
-            "EVAL050",
-            "==: 未対応の型の組み合わせ",
-            None,
-        )),
-        Err(CompareFailure::NaN) => Ok(false),
+/// 文字列を文字のリストの要素へ展開する。文字列と文字のリストを同じ値として比べるのに使う。
+fn char_values(text: &str) -> Vec<Value> {
+    text.chars().map(Value::Char).collect()
+}
+
+/// `==` 専用の構造的な等価判定。
+///
+/// 順序を求めずに、長さや構成子が異なれば要素を見ずに、要素が異なれば残りを見ずに偽を返す。
+/// 文字列と文字のリストは内容で比べる。NaN は自身を含むどの値とも等しくない。
+/// 型の組み合わせが比較できない場合の扱いは `structural_compare` と同じく `Mismatch` になる。
+fn structural_eq(a: &Value, b: &Value) -> Result<bool, CompareFailure> {
+    fn all_eq(xs: &[Value], ys: &[Value]) -> Result<bool, CompareFailure> {
+        if xs.len() != ys.len() {
+            return Ok(false);
+        }
+        for (x, y) in xs.iter().zip(ys) {
+            if !structural_eq(x, y)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    match (a, b) {
+        (Value::Int(x), Value::Int(y)) => Ok(x == y),
+        (Value::Double(x), Value::Double(y)) => Ok(x == y),
+        (Value::Int(x), Value::Double(y)) => Ok(*x as f64 == *y),
+        (Value::Double(x), Value::Int(y)) => Ok(*x == *y as f64),
+        (Value::Bool(x), Value::Bool(y)) => Ok(x == y),
+        (Value::Char(x), Value::Char(y)) => Ok(x == y),
+        (Value::String(x), Value::String(y)) => Ok(x == y),
+        (Value::String(text), Value::List(items)) | (Value::List(items), Value::String(text)) => {
+            all_eq(&char_values(text), items)
+        }
+        (Value::List(xs), Value::List(ys)) | (Value::Tuple(xs), Value::Tuple(ys)) => all_eq(xs, ys),
+        (
+            Value::Data {
+                constructor: c1,
+                fields: f1,
+            },
+            Value::Data {
+                constructor: c2,
+                fields: f2,
+            },
+        ) => {
+            if c1 != c2 {
+                return Ok(false);
+            }
+            if f1.len() != f2.len() {
+                return Err(CompareFailure::Mismatch);
+            }
+            all_eq(f1, f2)
+        }
+        _ => Err(CompareFailure::Mismatch),
     }
 }
 
+fn eqv(a: &Value, b: &Value) -> Result<bool, EvalError> {
+    structural_eq(a, b).map_err(|_| EvalError::new("EVAL050", "==: 未対応の型の組み合わせ", None))
+}
+
 fn compare(a: &Value, b: &Value) -> Result<std::cmp::Ordering, EvalError> {
     match structural_compare(a, b) {
         Ok(ord) => Ok(ord),
@@ -583,6 +1713,42 @@ pub(crate) fn ge_op(a: Value, b: Value) -> Result<Value, EvalError> {
     }))
 }
 
+/// `min`: 等しい場合は Haskell と同じく第 1 引数を返す。
+pub(crate) fn min_op(a: Value, b: Value) -> Result<Value, EvalError> {
+    Ok(if compare(&a, &b)? == Ordering::Greater {
+        b
+    } else {
+        a
+    })
+}
+
+/// `max`: 等しい場合は Haskell と同じく第 2 引数を返す。
+pub(crate) fn max_op(a: Value, b: Value) -> Result<Value, EvalError> {
+    Ok(if compare(&a, &b)? == Ordering::Greater {
+        a
+    } else {
+        b
+    })
+}
+
+/// `clamp lo hi x`: `x < lo` なら `lo`、`hi < x` なら `hi`、それ以外は `x` を返す。
+pub(crate) fn clamp_op(lo: Value, hi: Value, x: Value) -> Result<Value, EvalError> {
+    Ok(if compare(&x, &lo)? == Ordering::Less {
+        lo
+    } else if compare(&hi, &x)? == Ordering::Less {
+        hi
+    } else {
+        x
+    })
+}
+
+/// `between lo hi x`: `lo <= x && x <= hi`。
+pub(crate) fn between_op(lo: Value, hi: Value, x: Value) -> Result<Value, EvalError> {
+    Ok(Value::Bool(
+        compare(&lo, &x)? != Ordering::Greater && compare(&x, &hi)? != Ordering::Greater,
+    ))
+}
+
 fn to_int(v: &Value) -> Result<i64, EvalError> {
     match v {
         Value::Int(i) => Ok(*i),
@@ -655,6 +1821,20 @@ mod tests {
         assert!(matches!(snap.get("b"), Some(Value::Int(2))));
     }
 
+    #[test]
+    fn env_flatten_collapses_frames_without_touching_them() {
+        let root = Env::new();
+        root.insert("a", Value::Int(1));
+        let child = root.child();
+        child.insert("b", Value::Int(2));
+        let flat = child.flatten();
+        assert!(flat.inner.parent.is_none());
+        assert!(matches!(flat.get("a"), Some(Value::Int(1))));
+        assert!(matches!(flat.get("b"), Some(Value::Int(2))));
+        flat.insert("a", Value::Int(3));
+        assert!(matches!(child.get("a"), Some(Value::Int(1))));
+    }
+
     #[test]
     fn primop_variants_apply_and_wrap_values() {
         let show = PrimOp::unary(py_show);
@@ -735,6 +1915,17 @@ mod tests {
 
         let bad = add_op(Value::Bool(true), Value::Int(1));
         assert_eq!(err_code(bad), Some("EVAL050"));
+        assert!(matches!(
+            mul_op(Value::Double(1.5), Value::Double(2.0)).unwrap(),
+            Value::Double(d) if (d - 3.0).abs() < 1e-12
+        ));
+        for (mixed, expected) in [
+            (add_op(Value::Int(1), Value::Double(2.5)), 3.5),
+            (sub_op(Value::Double(2.5), Value::Int(1)), 1.5),
+            (mul_op(Value::Int(2), Value::Double(0.5)), 1.0),
+        ] {
+            assert!(matches!(mixed, Ok(Value::Double(d)) if d == expected));
+        }
 
         let div_zero = div_int_op(Value::Int(1), Value::Int(0));
         assert_eq!(err_code(div_zero), Some("EVAL061"));
@@ -813,7 +2004,8 @@ mod tests {
 
     #[test]
     fn println_op_formats_and_records_lines() {
-        let (result, lines) = capture_println(|| println_op(Value::Int(7)).expect("println ok"));
+        let (result, lines) =
+            with_captured_output(|| println_op(Value::Int(7)).expect("println ok"));
         match result {
             Value::String(text) => assert_eq!(text, "7"),
             other => panic!("expected println to return String, got {:?}", other),
@@ -821,6 +2013,84 @@ mod tests {
         assert_eq!(lines, vec!["7".to_string()]);
     }
 
+    #[test]
+    fn trace_op_returns_value_and_records_message() {
+        let (result, lines) = with_captured_output(|| {
+            trace_op(Value::String("here".into()), Value::Int(3)).expect("trace ok")
+        });
+        assert!(matches!(result, Value::Int(3)));
+        assert_eq!(lines, vec!["here".to_string()]);
+        let err = trace_op(Value::Int(1), Value::Int(2)).expect_err("non-string message");
+        assert_eq!(err.0.code, "EVAL052");
+    }
+
+    #[test]
+    fn with_captured_output_nests_and_restores() {
+        let ((), outer) = with_captured_output(|| {
+            println_op(Value::Int(1)).expect("println ok");
+            let ((), inner) = with_captured_output(|| {
+                println_op(Value::Int(2)).expect("println ok");
+            });
+            assert_eq!(inner, vec!["2".to_string()]);
+            println_op(Value::Int(3)).expect("println ok");
+        });
+        assert_eq!(outer, vec!["1".to_string(), "3".to_string()]);
+        PRINTLN_CAPTURE.with(|slot| assert!(slot.borrow().is_none()));
+    }
+
+    #[test]
+    fn checked_arithmetic_reports_overflow_only_when_enabled() {
+        let wrapped = add_op(Value::Int(i64::MAX), Value::Int(1)).expect("wrapping add");
+        assert!(matches!(wrapped, Value::Int(i) if i == i64::MIN));
+        with_checked_arithmetic(true, || {
+            assert_eq!(
+                err_code(add_op(Value::Int(i64::MAX), Value::Int(1))),
+                Some("EVAL060")
+            );
+            assert_eq!(
+                err_code(sub_op(Value::Int(i64::MIN), Value::Int(1))),
+                Some("EVAL060")
+            );
+            assert_eq!(
+                err_code(mul_op(Value::Int(i64::MAX), Value::Int(2))),
+                Some("EVAL060")
+            );
+            assert!(matches!(
+                mul_op(Value::Int(6), Value::Int(7)),
+                Ok(Value::Int(42))
+            ));
+        });
+        assert!(CHECKED_ARITHMETIC.with(|flag| !flag.get()));
+    }
+
+    #[test]
+    fn bitwise_helpers_cover_ints_and_reject_doubles() {
+        assert!(matches!(
+            band_op(Value::Int(12), Value::Int(10)),
+            Ok(Value::Int(8))
+        ));
+        assert!(matches!(
+            bor_op(Value::Int(12), Value::Int(10)),
+            Ok(Value::Int(14))
+        ));
+        assert!(matches!(
+            bxor_op(Value::Int(12), Value::Int(10)),
+            Ok(Value::Int(6))
+        ));
+        assert!(matches!(
+            shl_op(Value::Int(1), Value::Int(4)),
+            Ok(Value::Int(16))
+        ));
+        assert!(matches!(
+            shr_op(Value::Int(-16), Value::Int(2)),
+            Ok(Value::Int(-4))
+        ));
+        assert_eq!(
+            err_code(band_op(Value::Double(1.0), Value::Int(1))),
+            Some("EVAL050")
+        );
+    }
+
     #[test]
     fn powi_negative_exponent_falls_back_to_f64() {
         let result =
@@ -885,6 +2155,40 @@ mod tests {
         assert!(matches!(result, Value::Bool(true)));
     }
 
+    #[test]
+    fn equality_stops_at_first_difference_in_nested_values() {
+        // 先頭のフィールドが異なれば、後ろにある比較できない値(関数)や巨大なリストは見ない。
+        let nested = |head: i64| {
+            (0..1_000).fold(Value::Int(0), |inner, _| Value::Data {
+                constructor: "Node".into(),
+                fields: vec![
+                    Value::Int(head),
+                    inner,
+                    Value::Prim(PrimOp::unary(py_show)),
+                    Value::List((0..10_000).map(Value::Int).collect()),
+                ],
+            })
+        };
+        let result = eq_op(nested(1), nested(2)).unwrap();
+        assert!(matches!(result, Value::Bool(false)));
+        let result = ne_op(nested(1), nested(2)).unwrap();
+        assert!(matches!(result, Value::Bool(true)));
+
+        // 長さの異なるリストは要素を比較せずに等しくないと判定する。
+        let funcs = Value::List(vec![Value::Prim(PrimOp::unary(py_show))]);
+        let result = eq_op(funcs, Value::List(Vec::new())).unwrap();
+        assert!(matches!(result, Value::Bool(false)));
+
+        let nan = Value::Tuple(vec![Value::Int(1), Value::Double(f64::NAN)]);
+        let result = eq_op(nan.clone(), nan).unwrap();
+        assert!(matches!(result, Value::Bool(false)));
+        let result = eq_op(Value::Int(2), Value::Double(2.0)).unwrap();
+        assert!(matches!(result, Value::Bool(true)));
+
+        let funcs = Value::List(vec![Value::Prim(PrimOp::unary(py_show))]);
+        assert_eq!(err_code(eq_op(funcs.clone(), funcs)), Some("EVAL050"));
+    }
+
     #[test]
     fn py_show_formats_scalar_variants() {
         let rendered = py_show(Value::Double(1.25)).expect("double formatted");
@@ -896,10 +2200,28 @@ mod tests {
         assert!(matches!(false_branch, Value::String(s) if s == "False"));
 
         let ch = py_show(Value::Char('λ')).expect("char formatting");
-        assert!(matches!(ch, Value::String(s) if s == "λ"));
-
-        let message = py_show(Value::String("ok".into())).expect("string passthrough");
-        assert!(matches!(message, Value::String(s) if s == "ok"));
+        assert!(matches!(ch, Value::String(s) if s == "'λ'"));
+        let quote = py_show(Value::Char('\'')).expect("quote char");
+        assert!(matches!(quote, Value::String(s) if s == r"'\''"));
+
+        let message = py_show(Value::String("ok".into())).expect("string quoted");
+        assert!(matches!(message, Value::String(s) if s == "\"ok\""));
+        let escaped = py_show(Value::String("a\nb\"c\\".into())).expect("string escaped");
+        assert!(matches!(escaped, Value::String(s) if s == r#""a\nb\"c\\""#));
+
+        let nested = py_show(Value::Data {
+            constructor: "Name".into(),
+            fields: vec![Value::String("x y".into())],
+        })
+        .expect("nested string");
+        assert!(matches!(nested, Value::String(s) if s == "Name \"x y\""));
+
+        // println はトップレベルの文字列・文字を引用符なしで出力する。
+        let (_, lines) = with_captured_output(|| {
+            println_op(Value::String("plain".into())).expect("println string");
+            println_op(Value::Char('c')).expect("println char");
+        });
+        assert_eq!(lines, vec!["plain".to_string(), "c".to_string()]);
     }
 
     #[test]
@@ -60,6 +60,45 @@ pub fn t_list(elem: Type) -> Type {
     })
 }
 
+/// 組み込みの配列型 `Array a` を構築するヘルパー関数。
+pub fn t_array(elem: Type) -> Type {
+    Type::TApp(TApp {
+        func: Box::new(Type::TCon(TCon {
+            name: "Array".into(),
+        })),
+        arg: Box::new(elem),
+    })
+}
+
+/// 組み込みの参照セル型 `Ref a` を構築するヘルパー関数。
+pub fn t_ref(elem: Type) -> Type {
+    Type::TApp(TApp {
+        func: Box::new(Type::TCon(TCon { name: "Ref".into() })),
+        arg: Box::new(elem),
+    })
+}
+
+/// 組み込みの連想配列型 `Map k v` を構築するヘルパー関数。
+pub fn t_map(key: Type, value: Type) -> Type {
+    Type::TApp(TApp {
+        func: Box::new(Type::TApp(TApp {
+            func: Box::new(Type::TCon(TCon { name: "Map".into() })),
+            arg: Box::new(key),
+        })),
+        arg: Box::new(value),
+    })
+}
+
+/// 組み込みの `Maybe a` を構築するヘルパー関数。
+pub fn t_maybe(elem: Type) -> Type {
+    Type::TApp(TApp {
+        func: Box::new(Type::TCon(TCon {
+            name: "Maybe".into(),
+        })),
+        arg: Box::new(elem),
+    })
+}
+
 /// `String` 型（`[Char]`）を構築するヘルパー関数。
 pub fn t_string() -> Type {
     t_list(Type::TCon(TCon {
@@ -178,6 +217,18 @@ impl Substitutable for Scheme {
     }
 }
 
+impl Substitutable for TypeEnv {
+    fn apply_subst(&self, subst: &Subst) -> Self {
+        Self {
+            env: self
+                .env
+                .iter()
+                .map(|(name, sch)| (name.clone(), sch.apply_subst(subst)))
+                .collect(),
+        }
+    }
+}
+
 /// 2つの置換を合成する。
 pub fn compose(a: &Subst, b: &Subst) -> Subst {
     // a ∘ b（先に b を適用し、その結果に a を重ねる）
@@ -347,7 +398,7 @@ pub fn unify(t1: Type, t2: Type) -> Result<Subst, UnifyError> {
                 return Err(UnifyError::new("TYPE001", "タプル要素数が異なります"));
             }
             let mut s = Subst::new();
-            for (a, b) in ta.items.into_iter().zip(tb.items.into_iter()) {
+            for (a, b) in ta.items.into_iter().zip(tb.items) {
                 let s_step = unify(a.apply_subst(&s), b.apply_subst(&s))?;
                 s = compose(&s_step, &s);
             }
@@ -368,7 +419,14 @@ pub fn bind(tv: TVar, t: Type) -> Result<Subst, UnifyError> {
         }
     }
     if ftv(&t).contains(&tv.id) {
-        return Err(UnifyError::new("TYPE002", "オカーズチェック失敗"));
+        // 束縛先の型と同じ名前空間で整形し、どの変数がどの型を含むのかを示す。
+        let mut names = HashMap::new();
+        let var = pp_type(&Type::TVar(tv), &mut names);
+        let ty = pp_type(&t, &mut names);
+        return Err(UnifyError::new(
+            "TYPE002",
+            format!("無限型を構築できません (オカーズチェック失敗): {var} = {ty}"),
+        ));
     }
     let mut s = Subst::new();
     s.insert(tv.id, t);
@@ -395,6 +453,14 @@ impl ClassEnv {
     pub fn add_instance(&mut self, classname: impl Into<String>, tycon: impl Into<String>) {
         self.instances.insert((classname.into(), tycon.into()));
     }
+    /// `sub` が `sup` 自身か、上位クラスを辿って `sup` に到達するかを判定する。
+    pub fn is_subclass_of(&self, sub: &str, sup: &str) -> bool {
+        sub == sup
+            || self
+                .classes
+                .get(sub)
+                .is_some_and(|supers| supers.iter().any(|s| self.is_subclass_of(s, sup)))
+    }
     /// 複数の制約が満たされるかを判定する。
     pub fn entails(&self, cons: &[Constraint]) -> bool {
         cons.iter().all(|c| self.entails_one(c))
@@ -417,17 +483,13 @@ impl ClassEnv {
                 }
                 false
             }
-            Type::TTuple(tt) => {
-                if c.classname == "Eq" || c.classname == "Ord" {
-                    tt.items.iter().all(|t| {
-                        self.entails_one(&Constraint {
-                            classname: c.classname.clone(),
-                            r#type: t.clone(),
-                        })
+            Type::TTuple(tt) if c.classname == "Eq" || c.classname == "Ord" => {
+                tt.items.iter().all(|t| {
+                    self.entails_one(&Constraint {
+                        classname: c.classname.clone(),
+                        r#type: t.clone(),
                     })
-                } else {
-                    false
-                }
+                })
             }
             _ => false,
         }
@@ -449,13 +511,69 @@ impl ClassEnv {
     }
 }
 
+/// `index` 番目の表示用型変数名を返す（`a`〜`z` の後は `a1`, `b1`, … と続ける）。
+fn tvar_display_name(index: usize) -> String {
+    let letter = (b'a' + (index % 26) as u8) as char;
+    match index / 26 {
+        0 => letter.to_string(),
+        round => format!("{letter}{round}"),
+    }
+}
+
+/// 型の中で型変数が初めて現れた順に ID を集める（既出の ID は追加しない）。
+fn collect_tvar_order(t: &Type, order: &mut Vec<i64>) {
+    match t {
+        Type::TVar(TVar { id }) => {
+            if !order.contains(id) {
+                order.push(*id);
+            }
+        }
+        Type::TCon(_) => {}
+        Type::TApp(TApp { func, arg }) => {
+            collect_tvar_order(func, order);
+            collect_tvar_order(arg, order);
+        }
+        Type::TFun(TFun { arg, ret }) => {
+            collect_tvar_order(arg, order);
+            collect_tvar_order(ret, order);
+        }
+        Type::TTuple(TTuple { items }) => {
+            for item in items {
+                collect_tvar_order(item, order);
+            }
+        }
+    }
+}
+
+/// 本体の型で現れた順、続いて制約にしか現れない順に並べた型変数 ID。
+fn qual_tvar_order(q: &QualType) -> Vec<i64> {
+    let mut order = Vec::new();
+    collect_tvar_order(&q.r#type, &mut order);
+    for c in &q.constraints {
+        collect_tvar_order(&c.r#type, &mut order);
+    }
+    order
+}
+
+/// 制約つき型の型変数へ `a`, `b`, `c`, … を割り当てた対応表を返す。
+///
+/// 本体の型で現れた順に名前を決め、制約にしか現れない変数はその後に続ける。
+/// 内部表現の数値 ID は変えず、表示にだけ用いる。
+pub fn canonical_tvar_names(q: &QualType) -> HashMap<i64, String> {
+    qual_tvar_order(q)
+        .into_iter()
+        .enumerate()
+        .map(|(index, id)| (id, tvar_display_name(index)))
+        .collect()
+}
+
 /// 型をドキュメント向けに整形する補助関数（型変数に a, b, c… を割り当てる）。
 fn pp_type(t: &Type, names: &mut HashMap<i64, String>) -> String {
     match t {
         Type::TVar(TVar { id }) => {
             if !names.contains_key(id) {
-                let ch = (b'a' + (names.len() as u8)) as char;
-                names.insert(*id, ch.to_string());
+                let name = tvar_display_name(names.len());
+                names.insert(*id, name);
             }
             names
                 .get(id)
@@ -537,12 +655,12 @@ fn pp_constraints(cs: &[Constraint], names: &mut HashMap<i64, String>) -> String
     if cs.is_empty() {
         return String::new();
     }
-    let mut cs2 = cs.to_vec();
-    cs2.sort_by_key(|c| format!("{}|{}", c.classname, key_of_type(&c.r#type)));
-    let mut parts = Vec::new();
-    for c in cs2 {
-        parts.push(format!("{} {}", c.classname, pp_type(&c.r#type, names)));
-    }
+    // 型変数名は呼び出し側で割当済みなので、表示文字列で並べれば出力が安定する。
+    let mut parts: Vec<String> = cs
+        .iter()
+        .map(|c| format!("{} {}", c.classname, pp_type(&c.r#type, names)))
+        .collect();
+    parts.sort();
     format!("{} => ", parts.join(", "))
 }
 
@@ -568,7 +686,7 @@ fn constraints_relevant_to_type(cs: &[Constraint], t: &Type) -> Vec<Constraint>
 /// assert_eq!(pretty_qual(&q), "Int");
 /// ```
 pub fn pretty_qual(q: &QualType) -> String {
-    let mut names: HashMap<i64, String> = HashMap::new();
+    let mut names = canonical_tvar_names(q);
     let cs = normalize_constraints(&q.constraints);
     let cs = constraints_with_typevars(&cs);
     let cs = constraints_relevant_to_type(&cs, &q.r#type);
@@ -578,6 +696,42 @@ pub fn pretty_qual(q: &QualType) -> String {
     s
 }
 
+/// 型スキームを `forall a b. C a => a -> b` の形式で整形する（制約は省略せずに表示する）。
+///
+/// # Examples
+/// ```
+/// use typelang::typesys::*;
+/// let a = Type::TVar(TVar { id: 1000 });
+/// let id_ty = Type::TFun(TFun { arg: Box::new(a.clone()), ret: Box::new(a) });
+/// let sc = Scheme { vars: vec![TVar { id: 1000 }], qual: qualify(id_ty, vec![]) };
+/// assert_eq!(pretty_scheme(&sc), "forall a. a -> a");
+/// ```
+pub fn pretty_scheme(sc: &Scheme) -> String {
+    let order = qual_tvar_order(&sc.qual);
+    let mut names = canonical_tvar_names(&sc.qual);
+    let mut s = String::new();
+    if !sc.vars.is_empty() {
+        let mut vars = sc.vars.clone();
+        vars.sort_by_key(|tv| {
+            order
+                .iter()
+                .position(|id| *id == tv.id)
+                .unwrap_or(usize::MAX)
+        });
+        let vars: Vec<String> = vars
+            .into_iter()
+            .map(|tv| pp_type(&Type::TVar(tv), &mut names))
+            .collect();
+        s.push_str(&format!("forall {}. ", vars.join(" ")));
+    }
+    s.push_str(&pp_constraints(
+        &normalize_constraints(&sc.qual.constraints),
+        &mut names,
+    ));
+    s.push_str(&pp_type(&sc.qual.r#type, &mut names));
+    s
+}
+
 /// 曖昧な数値型変数を簡易に既定化（`Fractional -> Double`, `Num -> Integer`）。
 /// 表示用のため、推論アルゴリズム自体の健全性には影響しません。
 ///
@@ -0,0 +1,89 @@
+// パス: src/watch.rs
+// 役割: ソースファイルの更新を監視し、変更のたびにネイティブビルドをやり直す
+// 意図: 外部依存を増やさず、更新時刻のポーリングだけで編集→ビルドの往復を自動化する
+// 関連ファイル: src/lib.rs, src/bin/typelang.rs, src/codegen/cranelift.rs
+//! ファイル監視によるビルドの自動再実行
+//!
+//! - inotify などは使わず、一定間隔で更新時刻 (mtime) を確認する。
+//! - ビルドの失敗は報告するだけで、監視は続ける。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// 更新時刻を確認する間隔。
+pub const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// ファイルの更新時刻を覚えておき、前回の確認から変わったかを判定する。
+#[derive(Clone, Debug)]
+pub struct FileWatcher {
+    path: PathBuf,
+    /// 直近に確認した更新時刻。ファイルが読めなかった場合は `None`。
+    last_modified: Option<SystemTime>,
+    checked: bool,
+}
+
+impl FileWatcher {
+    /// `path` を監視対象とする。最初の `poll_changed` は必ず `true` を返す。
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            last_modified: None,
+            checked: false,
+        }
+    }
+
+    /// 監視対象のパス。
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 前回の確認から更新時刻が変わっていれば `true` を返す。
+    /// ファイルが消えた・再び現れた場合も変更として扱う。
+    pub fn poll_changed(&mut self) -> bool {
+        let modified = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        let changed = !self.checked || modified != self.last_modified;
+        self.checked = true;
+        self.last_modified = modified;
+        changed
+    }
+}
+
+/// `src_path` を監視し、更新されるたびに `emit_native` で `out_path` へビルドし直す。
+///
+/// 起動直後に 1 回ビルドし、以降は [`WATCH_POLL_INTERVAL`] ごとに更新時刻を確認する。
+/// 読み込み・パース・コード生成の失敗は標準エラーへ表示して監視を続けるため、この関数は戻らない。
+/// `import` は辿らないので、複数ファイルのプログラムは CLI の `typelang build --watch` を使う。
+#[cfg_attr(coverage, coverage(off))]
+pub fn watch_and_compile(src_path: &Path, out_path: &Path) -> ! {
+    let mut watcher = FileWatcher::new(src_path);
+    loop {
+        if watcher.poll_changed() {
+            match compile_file(src_path, out_path) {
+                Ok(()) => println!(
+                    "✅ ビルド成功: {} -> {}",
+                    src_path.display(),
+                    out_path.display()
+                ),
+                Err(err) => eprintln!("エラー: {err}"),
+            }
+        }
+        thread::sleep(WATCH_POLL_INTERVAL);
+    }
+}
+
+/// ファイルを読み込んでネイティブ実行ファイルを生成する。失敗は表示用の文字列にまとめる。
+fn compile_file(src_path: &Path, out_path: &Path) -> Result<(), String> {
+    let source = fs::read_to_string(src_path).map_err(|e| {
+        format!(
+            "入力ファイルの読み込みに失敗しました ({}): {e}",
+            src_path.display()
+        )
+    })?;
+    let program = crate::parser::parse_program(&source)
+        .map_err(|e| format!("パースに失敗しました ({}): {e}", src_path.display()))?;
+    crate::emit_native(&program, out_path)
+        .map(|_| ())
+        .map_err(|e| format!("ネイティブコード生成に失敗しました: {e}"))
+}
@@ -0,0 +1,454 @@
+// パス: tests/ast_pretty.rs
+// 役割: AST プリティプリンタが再パース可能なソースを出力するか検証する
+// 意図: 手書きの境界ケースと疑似乱数で生成した AST の双方で parse(to_source(ast)) == ast を確認する
+// 関連ファイル: src/ast/pretty.rs, src/parser/expr.rs, src/parser/fixity.rs
+use std::fs;
+
+use typelang::ast::pretty::{expr_to_source, format_source, to_source};
+use typelang::ast::{
+    CaseArm, Expr, FixityDecl, IntBase, Pattern, Program, Span, TopLevel, TypeExpr,
+};
+use typelang::parser::{parse_expr, parse_program};
+
+/// Debug 表記からスパンを取り除き、位置情報以外の構造だけを比較できるようにする。
+fn shape<T: std::fmt::Debug>(value: &T) -> String {
+    let text = format!("{value:?}");
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find("Span {") {
+        out.push_str(&rest[..start]);
+        let end = rest[start..].find('}').expect("span closes") + start + 1;
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn assert_expr_round_trip(src: &str) -> String {
+    let parsed = parse_expr(src).unwrap_or_else(|e| panic!("parse {src}: {e}"));
+    let printed = expr_to_source(&parsed);
+    let reparsed = parse_expr(&printed).unwrap_or_else(|e| panic!("reparse {printed}: {e}"));
+    assert_eq!(shape(&parsed), shape(&reparsed), "{src} => {printed}");
+    printed
+}
+
+fn assert_program_round_trip(program: &Program) -> String {
+    let printed = to_source(program);
+    let reparsed = parse_program(&printed).unwrap_or_else(|e| panic!("reparse {printed}: {e}"));
+    assert_eq!(shape(program), shape(&reparsed), "printed:\n{printed}");
+    printed
+}
+
+#[test]
+/// 優先順位と結合性に必要な括弧だけが補われる。
+fn infix_parenthesization_follows_fixities() {
+    let cases = [
+        ("(1 + 2) * 3", "(1 + 2) * 3"),
+        ("1 + 2 * 3", "1 + 2 * 3"),
+        ("1 - (2 - 3)", "1 - (2 - 3)"),
+        ("(1 - 2) - 3", "1 - 2 - 3"),
+        ("2 ^ 3 ^ 2", "2 ^ 3 ^ 2"),
+        ("(2 ^ 3) ^ 2", "(2 ^ 3) ^ 2"),
+        ("(1 == 2) == True", "(1 == 2) == True"),
+        ("10 `div` (3 - 1)", "div 10 (3 - 1)"),
+        ("f (g x) [1, 2] (a, b)", "f (g x) [1, 2] (a, b)"),
+    ];
+    for (src, expected) in cases {
+        assert_eq!(assert_expr_round_trip(src), expected, "{src}");
+    }
+}
+
+#[test]
+/// 単項マイナスと負のリテラルが元の AST へ戻る。
+fn negation_and_negative_literals_round_trip() {
+    let cases = [
+        ("-x + 1", "-x + 1"),
+        ("f (-x)", "f (-x)"),
+        ("f (-1)", "f (-1)"),
+        ("1 - -2.5", "1 - -2.5"),
+        ("-(-x)", "-(-x)"),
+        ("negate 1", "negate 1"),
+        ("-(1 + x)", "-(1 + x)"),
+        ("-0x1f", "-0x1f"),
+    ];
+    for (src, expected) in cases {
+        assert_eq!(assert_expr_round_trip(src), expected, "{src}");
+    }
+}
+
+#[test]
+/// ラムダ・let・if・case・型注釈が後続のトークンを取り込まないよう括弧で閉じる。
+fn open_expressions_are_closed_where_needed() {
+    for src in [
+        "(\\x -> x) 1",
+        "(\\x -> x :: Int) 1",
+        "((\\x -> x) :: Int -> Int) 1",
+        "case x of Just y -> (case y of 0 -> 1; _ -> 2); Nothing -> 3",
+        "case x of n | (n :: Int) > 0 -> n; _ -> 0",
+        "let f = (case x of A -> 1); g = 2 in f + g",
+        "if (\\x -> x) True then 'a' else '\\n'",
+        "1 + (if b then 2 else 3)",
+        "case p of (a, _) -> a; [x, y] -> x; Pair (Left a) b -> b; v@(Just _) -> v; -1 -> 0",
+        "\"quote \\\" and \\\\ and \\t\"",
+        "\"nul \\0 and bell \\u{7}\"",
+        "(+) 1 2",
+    ] {
+        assert_expr_round_trip(src);
+    }
+}
+
+#[test]
+/// 宣言をすべて含むプログラムと examples 配下のファイルがラウンドトリップする。
+fn programs_round_trip_through_source() {
+    let src = r#"
+module Shapes (area, (<+>)) where
+import "lib.tl";
+infixr 5 <+>;
+type Point = (Int, Int);
+data Shape a = Circle a | Rect (a, a) | Empty;
+newtype Wrap = Wrap Int;
+class Eq a => Metric a;
+instance Metric Int;
+instance Show [];
+
+(<+>) :: Int -> Int -> Int;
+let (<+>) a b = a + b;
+
+-- | 面積を求める。
+-- 2 行目の説明。
+area :: Num a => Shape a -> a;
+let area s = case s of Circle r -> r * r; Rect (w, h) -> w * h; Empty -> 0;
+
+let main = 1 <+> 2 <+> 3;
+"#;
+    let program = parse_program(src).expect("parse program");
+    let printed = assert_program_round_trip(&program);
+    assert!(printed.contains("-- | 面積を求める。\n-- 2 行目の説明。\n"));
+    assert!(printed.contains("let main = 1 <+> 2 <+> 3;"));
+
+    let mut entries: Vec<_> = fs::read_dir("examples")
+        .expect("examples dir")
+        .map(|entry| entry.expect("dir entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "tl"))
+        .collect();
+    entries.sort();
+    for path in entries {
+        let source = fs::read_to_string(&path).expect("read example");
+        let program =
+            parse_program(&source).unwrap_or_else(|e| panic!("parse {}: {e}", path.display()));
+        assert_program_round_trip(&program);
+    }
+}
+
+#[test]
+/// 整形結果をもう一度整形しても変わらず、シノニムは展開されずに残る。
+fn format_source_is_idempotent() {
+    let messy = r#"
+infixl 6 <+>;
+type Name=String;
+-- | あいさつ文を作る。
+--   2 行目。
+greet::Name->String;let greet n="hi "++n;
+data Opt a=None|Some a;
+let (<+>) a b=a+b ; let main =
+  let x=1;y=2 in case Some(x<+>y) of Some v|v>2->v;_->0;
+"#;
+    let once = format_source(messy).expect("format");
+    assert_eq!(
+        once,
+        "infixl 6 <+>;\ntype Name = String;\ndata Opt a = None | Some a;\n\n\
+         -- | あいさつ文を作る。\n-- 2 行目。\ngreet :: Name -> String;\nlet greet n = \"hi \" ++ n;\n\n\
+         let (<+>) a b = a + b;\n\n\
+         let main = (let x = 1; y = 2 in case Some (x <+> y) of Some v | v > 2 -> v; _ -> 0);\n"
+    );
+    assert_eq!(format_source(&once).expect("format twice"), once);
+
+    for entry in fs::read_dir("examples").expect("examples dir") {
+        let path = entry.expect("dir entry").path();
+        if path.extension().is_some_and(|ext| ext == "tl") {
+            let source = fs::read_to_string(&path).expect("read example");
+            let once = format_source(&source).expect("format example");
+            assert_eq!(
+                format_source(&once).expect("format twice"),
+                once,
+                "{}",
+                path.display()
+            );
+        }
+    }
+    assert!(format_source("let main = ;").is_err());
+}
+
+/// テストを再現可能にするための xorshift 疑似乱数。
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+}
+
+const VARS: &[&str] = &["x", "y", "f", "go'", "_", "?hole", "Just", "negate"];
+const OPS: &[&str] = &[
+    "+", "-", "*", "/", "^", "**", "==", "<", ".&.", ".|.", "<+>", "|>",
+];
+const CHARS: &[char] = &['a', ' ', '\'', '"', '\\', '\n', '\t', 'λ'];
+
+fn sp() -> Span {
+    Span::dummy()
+}
+
+fn gen_int(rng: &mut Rng) -> (i64, IntBase) {
+    let value = rng.below(300) as i64 - 100;
+    let base = [IntBase::Dec, IntBase::Hex, IntBase::Oct, IntBase::Bin][rng.below(4)];
+    (value, base)
+}
+
+fn gen_float(rng: &mut Rng) -> f64 {
+    [0.5, 1.0, 2.5e-7, 1.0e20, 123.456, -3.75, -0.0][rng.below(7)]
+}
+
+fn gen_string(rng: &mut Rng) -> String {
+    (0..rng.below(4))
+        .map(|_| CHARS[rng.below(CHARS.len())])
+        .collect()
+}
+
+fn gen_type(rng: &mut Rng, depth: usize) -> TypeExpr {
+    let choice = if depth == 0 {
+        rng.below(2)
+    } else {
+        rng.below(6)
+    };
+    match choice {
+        0 => TypeExpr::TEVar(rng.pick(&["a", "b"]).into()),
+        1 => TypeExpr::TECon(rng.pick(&["Int", "Bool", "Maybe"]).into()),
+        2 => TypeExpr::TEApp(
+            Box::new(TypeExpr::TECon("Maybe".into())),
+            Box::new(gen_type(rng, depth - 1)),
+        ),
+        3 => TypeExpr::TEFun(
+            Box::new(gen_type(rng, depth - 1)),
+            Box::new(gen_type(rng, depth - 1)),
+        ),
+        4 => TypeExpr::TEList(Box::new(gen_type(rng, depth - 1))),
+        _ => {
+            let len = [0, 2, 3][rng.below(3)];
+            TypeExpr::TETuple((0..len).map(|_| gen_type(rng, depth - 1)).collect())
+        }
+    }
+}
+
+fn gen_pattern(rng: &mut Rng, depth: usize) -> Pattern {
+    let choice = if depth == 0 {
+        rng.below(7)
+    } else {
+        rng.below(11)
+    };
+    match choice {
+        0 => Pattern::Wildcard { span: sp() },
+        1 => Pattern::Var {
+            name: rng.pick(&["p", "q"]).into(),
+            span: sp(),
+        },
+        2 => {
+            let (value, base) = gen_int(rng);
+            Pattern::Int {
+                value,
+                base,
+                span: sp(),
+            }
+        }
+        3 => Pattern::Float {
+            value: gen_float(rng),
+            span: sp(),
+        },
+        4 => Pattern::Char {
+            value: CHARS[rng.below(CHARS.len())],
+            span: sp(),
+        },
+        5 => Pattern::String {
+            value: gen_string(rng),
+            span: sp(),
+        },
+        6 => Pattern::Bool {
+            value: rng.below(2) == 0,
+            span: sp(),
+        },
+        7 => Pattern::List {
+            items: (0..rng.below(3))
+                .map(|_| gen_pattern(rng, depth - 1))
+                .collect(),
+            span: sp(),
+        },
+        8 => {
+            let len = [0, 2, 3][rng.below(3)];
+            Pattern::Tuple {
+                items: (0..len).map(|_| gen_pattern(rng, depth - 1)).collect(),
+                span: sp(),
+            }
+        }
+        9 => Pattern::As {
+            binder: "whole".into(),
+            pattern: Box::new(gen_pattern(rng, depth - 1)),
+            span: sp(),
+        },
+        _ => Pattern::Constructor {
+            name: rng.pick(&["Nothing", "Just", "Pair"]).into(),
+            args: (0..rng.below(3))
+                .map(|_| gen_pattern(rng, depth - 1))
+                .collect(),
+            span: sp(),
+        },
+    }
+}
+
+fn gen_expr(rng: &mut Rng, depth: usize) -> Expr {
+    let choice = if depth == 0 {
+        rng.below(6)
+    } else {
+        rng.below(16)
+    };
+    let sub = |rng: &mut Rng| Box::new(gen_expr(rng, depth - 1));
+    match choice {
+        0 => Expr::Var {
+            name: rng.pick(VARS).into(),
+            span: sp(),
+        },
+        1 => {
+            let (value, base) = gen_int(rng);
+            Expr::IntLit {
+                value,
+                base,
+                span: sp(),
+            }
+        }
+        2 => Expr::FloatLit {
+            value: gen_float(rng),
+            span: sp(),
+        },
+        3 => Expr::CharLit {
+            value: CHARS[rng.below(CHARS.len())],
+            span: sp(),
+        },
+        4 => Expr::StringLit {
+            value: gen_string(rng),
+            span: sp(),
+        },
+        5 => Expr::Var {
+            name: rng.pick(OPS).into(),
+            span: sp(),
+        },
+        6 => Expr::ListLit {
+            items: (0..rng.below(3)).map(|_| *sub(rng)).collect(),
+            span: sp(),
+        },
+        7 => {
+            let len = [0, 2, 3][rng.below(3)];
+            Expr::TupleLit {
+                items: (0..len).map(|_| *sub(rng)).collect(),
+                span: sp(),
+            }
+        }
+        8 => Expr::Lambda {
+            params: (0..rng.below(2) + 1).map(|i| format!("v{i}")).collect(),
+            body: sub(rng),
+            span: sp(),
+        },
+        9 => Expr::LetIn {
+            bindings: (0..rng.below(2) + 1)
+                .map(|i| (format!("b{i}"), vec![], *sub(rng)))
+                .collect(),
+            body: sub(rng),
+            span: sp(),
+        },
+        10 => Expr::If {
+            cond: sub(rng),
+            then_branch: sub(rng),
+            else_branch: sub(rng),
+            span: sp(),
+        },
+        11 | 12 => Expr::App {
+            func: sub(rng),
+            arg: sub(rng),
+            span: sp(),
+        },
+        13 => Expr::BinOp {
+            op: rng.pick(OPS).into(),
+            left: sub(rng),
+            right: sub(rng),
+            span: sp(),
+        },
+        14 => Expr::Annot {
+            expr: sub(rng),
+            type_expr: gen_type(rng, 2),
+            span: sp(),
+        },
+        _ => Expr::Case {
+            scrutinee: sub(rng),
+            arms: (0..rng.below(3) + 1)
+                .map(|_| CaseArm {
+                    pattern: gen_pattern(rng, 2),
+                    guard: (rng.below(3) == 0).then(|| *sub(rng)),
+                    body: *sub(rng),
+                })
+                .collect(),
+            span: sp(),
+        },
+    }
+}
+
+/// 構文木 → ソース → 構文木の往復が恒等になるかを、乱数生成した多数の式で確かめる。
+#[test]
+fn random_programs_round_trip() {
+    let fixities = vec![FixityDecl {
+        assoc: typelang::ast::Associativity::Right,
+        precedence: 5,
+        operators: vec!["<+>".into()],
+        span: sp(),
+    }];
+    let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+    for _ in 0..500 {
+        let expr = gen_expr(&mut rng, 4);
+        let program = Program {
+            modules: vec![],
+            imports: vec![],
+            class_decls: vec![],
+            instance_decls: vec![],
+            data_decls: vec![],
+            type_synonyms: vec![],
+            fixities: fixities.clone(),
+            decls: vec![
+                TopLevel {
+                    name: "main".into(),
+                    params: vec![],
+                    expr,
+                    signature: None,
+                    doc: None,
+                },
+                TopLevel {
+                    name: "next".into(),
+                    params: vec!["n".into()],
+                    expr: Expr::Var {
+                        name: "n".into(),
+                        span: sp(),
+                    },
+                    signature: None,
+                    doc: None,
+                },
+            ],
+        };
+        assert_program_round_trip(&program);
+    }
+}
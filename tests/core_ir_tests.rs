@@ -5,7 +5,8 @@
 
 use typelang::ast as A;
 use typelang::compile_core_ir;
-use typelang::core_ir::{Expr, MatchArm, ParameterKind, PrimOp, ValueTy};
+use typelang::core_ir::lower::lower_program;
+use typelang::core_ir::{Expr, Literal, MatchArm, ParameterKind, PrimOp, ValueTy, VarKind};
 use typelang::parser;
 
 #[test]
@@ -16,7 +17,8 @@ main :: Int;
 let main = 1 + 2;
 "#;
     let prog = parser::parse_program(src).expect("parse program");
-    let module = compile_core_ir(&prog).expect("lower to core ir");
+    // 定数の畳み込み前の形を見るため、最適化を挟まない lowering だけを行う。
+    let module = lower_program(&prog).expect("lower to core ir");
     assert_eq!(module.entry(), Some("main"));
     let main_fn = module.functions.get("main").expect("main function lowered");
     assert!(main_fn.params.is_empty());
@@ -186,3 +188,441 @@ fn assert_pattern_constructor(arm: &MatchArm, expected_ctor: &str) {
         other => panic!("expected constructor pattern, got {:?}", other),
     }
 }
+
+#[test]
+/// 同じ変数を 2 度束縛する非線形パターンは lowering で拒否される。
+fn lower_rejects_duplicate_pattern_binders() {
+    let src = r#"
+data Pair = Pair Int Int;
+pick :: Pair -> Int;
+let pick p = (case p of Pair a a -> a);
+main :: Int;
+let main = 0;
+"#;
+    let prog = parser::parse_program(src).expect("parse program");
+    let err = compile_core_ir(&prog).expect_err("duplicate binder must be rejected");
+    assert!(
+        err.to_string().contains("パターン変数 a"),
+        "unexpected error: {err}"
+    );
+}
+
+#[test]
+/// 独立した複数の定義で起きた lowering エラーを最初の 1 件で止めずにまとめて報告する。
+fn lower_reports_errors_from_every_definition() {
+    let src = r#"
+data Pair = Pair Int Int;
+pick :: Pair -> Int;
+let pick p = (case p of Pair a a -> a);
+other :: Pair -> Int;
+let other p = (case p of Pair b b -> b);
+main :: Int;
+let main = 0;
+"#;
+    let prog = parser::parse_program(src).expect("parse program");
+    let errs = compile_core_ir(&prog).expect_err("both definitions must be rejected");
+    assert_eq!(errs.0.len(), 2, "unexpected errors: {errs}");
+    let rendered = errs.to_string();
+    assert!(rendered.contains("パターン変数 a"), "{rendered}");
+    assert!(rendered.contains("パターン変数 b"), "{rendered}");
+
+    let native = typelang::codegen::NativeError::from(errs);
+    assert!(native.to_string().contains("2 件のエラー"), "{native}");
+}
+
+#[test]
+/// 評価器専用のプリミティブ(`catch`・`fmap`・`mempty` など)はネイティブ向けの lowering で専用のコードとともに拒否する。
+fn lower_rejects_evaluator_only_catch() {
+    for (name, body) in [
+        ("catch", "catch (fromList [1] ! 3) (\\msg -> 0)"),
+        ("fmap", "case fmap (\\x -> x + 1) [1] of [n] -> n; _ -> 0"),
+        ("mempty", "if null mempty then 1 else 0"),
+        (
+            "stripPrefix",
+            "case stripPrefix [1] [1, 2] of Just _ -> 1; Nothing -> 0",
+        ),
+    ] {
+        let src = format!("main :: Int;\nlet main = {body};\n");
+        let prog = parser::parse_program(&src).expect("parse program");
+        let rendered = compile_core_ir(&prog)
+            .expect_err("evaluator-only primitives must be rejected")
+            .to_string();
+        assert!(rendered.contains("COREIR165"), "{rendered}");
+        assert!(rendered.contains(name), "{rendered}");
+    }
+}
+
+#[test]
+/// 上限のない範囲 `[x..]` は評価器専用の汎用コードではなく、専用のコードで拒否する。
+fn lower_rejects_infinite_range() {
+    let src = "main :: Int;\nlet main = length (take 5 [1..]);\n";
+    let prog = parser::parse_program(src).expect("parse program");
+    let rendered = compile_core_ir(&prog)
+        .expect_err("infinite ranges must be rejected")
+        .to_string();
+    assert!(rendered.contains("COREIR170"), "{rendered}");
+    assert!(rendered.contains("[x..]"), "{rendered}");
+}
+
+#[test]
+/// インスタンスのないデータ型を制約付きの関数や演算子へ渡すと、呼び出し箇所の位置とともに報告する。
+fn lower_reports_missing_instance_at_call_site() {
+    let src = r#"
+data Foo = Foo Int;
+double :: Num a => a -> a;
+let double x = x + x;
+useFoo :: Foo -> Foo;
+let useFoo v = double v;
+main :: Int;
+let main = 1;
+"#;
+    let prog = parser::parse_program(src).expect("parse program");
+    let rendered = compile_core_ir(&prog)
+        .expect_err("missing Num instance must be rejected")
+        .to_string();
+    assert!(rendered.contains("COREIR169"), "{rendered}");
+    assert!(
+        rendered.contains("`Num Foo` のインスタンスがありません @line=6,col=16"),
+        "{rendered}"
+    );
+
+    let src = "data Foo = Foo Int;
+less :: Foo -> Foo -> Bool;
+let less a b = a < b;
+";
+    let prog = parser::parse_program(src).expect("parse program");
+    let rendered = compile_core_ir(&prog)
+        .expect_err("missing Ord instance must be rejected")
+        .to_string();
+    assert!(
+        rendered.contains("`Ord Foo` のインスタンスがありません"),
+        "{rendered}"
+    );
+}
+
+#[test]
+/// `let` で束縛したリテラルは参照箇所へ伝播して束縛ごと消え、リテラル同士の演算は畳み込まれる。
+fn constant_propagation_removes_literal_bindings_and_folds() {
+    let src = r#"
+main :: Int;
+let main = let x = 5 in x + x;
+
+shadowed :: Int -> Int;
+let shadowed n = let y = n in let n = 1 in y + n;
+"#;
+    let prog = parser::parse_program(src).expect("parse program");
+    let module = compile_core_ir(&prog).expect("lower program");
+    assert_eq!(
+        module.functions["main"].body,
+        Expr::Literal {
+            value: Literal::Int(10),
+            ty: ValueTy::Int,
+        }
+    );
+
+    // `y` の伝播先では `n` が覆い隠されるため、`y` は `n` へ置き換えずに残す。
+    let Expr::Let { bindings, body, .. } = &module.functions["shadowed"].body else {
+        panic!("shadowed binding must be kept");
+    };
+    assert_eq!(bindings.len(), 1);
+    assert_eq!(bindings[0].name, "y");
+    let Expr::PrimOp { op, args, .. } = body.as_ref() else {
+        panic!("inner let should fold into the addition");
+    };
+    assert_eq!(*op, PrimOp::AddInt);
+    assert!(matches!(&args[0], Expr::Var { name, .. } if name == "y"));
+    assert!(matches!(
+        &args[1],
+        Expr::Literal {
+            value: Literal::Int(1),
+            ..
+        }
+    ));
+}
+
+#[test]
+/// `min` / `max` は引数を一度だけ束縛し、`<=` で一方を選ぶ `if` へ展開される。部分適用は拒否する。
+fn lower_expands_min_max_into_comparison() {
+    let src = r#"
+main :: Int;
+let main = max 3 7;
+"#;
+    let prog = parser::parse_program(src).expect("parse program");
+    let module = lower_program(&prog).expect("lower min/max");
+    let Expr::Let { bindings, body, .. } = &module.functions["main"].body else {
+        panic!("max should bind its operands");
+    };
+    assert_eq!(bindings.len(), 2);
+    let Expr::If {
+        cond, then_branch, ..
+    } = body.as_ref()
+    else {
+        panic!("max should select with if");
+    };
+    assert!(matches!(
+        cond.as_ref(),
+        Expr::PrimOp {
+            op: PrimOp::LeInt,
+            ..
+        }
+    ));
+    assert!(matches!(then_branch.as_ref(), Expr::Var { name, .. } if *name == bindings[1].name));
+
+    let partial = parser::parse_program("main :: Int;\nlet main = let f = max 3 in f 1;")
+        .expect("parse program");
+    let rendered = compile_core_ir(&partial)
+        .expect_err("partial application must be rejected")
+        .to_string();
+    assert!(rendered.contains("COREIR166"), "{rendered}");
+}
+
+#[test]
+/// 空リストは `Literal::EmptyList` になり、注釈があれば要素型が確定する。
+fn lower_empty_list_literal_uses_annotation_for_element_type() {
+    let src = r#"
+none :: [Int];
+let none = [];
+typed :: [Int];
+let typed = ([] :: [Int]);
+"#;
+    let prog = parser::parse_program(src).expect("parse program");
+    let module = compile_core_ir(&prog).expect("lower to core ir");
+    match &module.functions["none"].body {
+        Expr::Literal {
+            value: Literal::EmptyList,
+            ty,
+        } => assert_eq!(ty, &ValueTy::List(Box::new(ValueTy::Unknown))),
+        other => panic!("expected empty list literal, got {other:?}"),
+    }
+    match &module.functions["typed"].body {
+        Expr::Literal {
+            value: Literal::EmptyList,
+            ty,
+        } => assert_eq!(ty, &ValueTy::List(Box::new(ValueTy::Int))),
+        other => panic!("expected empty list literal, got {other:?}"),
+    }
+}
+
+#[test]
+/// 小さな非再帰関数は呼び出し箇所へ展開され、実引数は `let` で一度だけ束縛される。
+fn small_functions_are_inlined_at_call_sites() {
+    let src = r#"
+double :: Int -> Int;
+let double x = x + x;
+run :: Int -> Int;
+let run n = double (n + 1);
+"#;
+    let prog = parser::parse_program(src).expect("parse program");
+    let module = compile_core_ir(&prog).expect("lower to core ir");
+    assert!(module.functions.contains_key("double"), "定義自体は残す");
+    let Expr::Let { bindings, body, ty } = &module.functions["run"].body else {
+        panic!(
+            "expected inlined let, got {:?}",
+            module.functions["run"].body
+        );
+    };
+    assert_eq!(*ty, ValueTy::Int);
+    assert_eq!(bindings.len(), 1);
+    assert_ne!(bindings[0].name, "x", "仮引数は新しい名前へ付け替える");
+    assert!(matches!(
+        bindings[0].value,
+        Expr::PrimOp {
+            op: PrimOp::AddInt,
+            ..
+        }
+    ));
+    match body.as_ref() {
+        Expr::PrimOp {
+            op: PrimOp::AddInt,
+            args,
+            ..
+        } => {
+            for arg in args {
+                assert!(
+                    matches!(arg, Expr::Var { name, .. } if *name == bindings[0].name),
+                    "本体は束縛した実引数を参照する: {arg:?}"
+                );
+            }
+        }
+        other => panic!("expected inlined body, got {other:?}"),
+    }
+}
+
+#[test]
+/// 直接・相互再帰する関数は展開しない。
+fn recursive_functions_are_not_inlined() {
+    let src = r#"
+fact :: Int -> Int;
+let fact n = if n == 0 then 1 else n * fact (n - 1);
+isEven :: Int -> Bool;
+let isEven n = if n == 0 then True else isOdd (n - 1);
+isOdd :: Int -> Bool;
+let isOdd n = if n == 0 then False else isEven (n - 1);
+main :: Int;
+let main = if isEven 4 then fact 5 else 0;
+"#;
+    let prog = parser::parse_program(src).expect("parse program");
+    let module = compile_core_ir(&prog).expect("lower to core ir");
+    let Expr::If {
+        cond, then_branch, ..
+    } = &module.functions["main"].body
+    else {
+        panic!("unexpected main body: {:?}", module.functions["main"].body);
+    };
+    for (call, callee) in [(cond.as_ref(), "isEven"), (then_branch.as_ref(), "fact")] {
+        match call {
+            Expr::Apply { func, .. } => {
+                assert!(matches!(func.as_ref(), Expr::Var { name, .. } if name == callee));
+            }
+            other => panic!("{callee} の呼び出しが残っていない: {other:?}"),
+        }
+    }
+}
+
+#[test]
+/// 同じ純粋な呼び出しが 2 回現れると、1 つの `let` 束縛へまとめられる。
+fn duplicated_pure_calls_share_one_let_binding() {
+    let src = r#"
+square :: Int -> Int;
+let square x = x * x;
+run :: Int -> Int;
+let run n = square n + square n;
+"#;
+    let prog = parser::parse_program(src).expect("parse program");
+    let module = compile_core_ir(&prog).expect("lower to core ir");
+    let Expr::Let { bindings, body, .. } = &module.functions["run"].body else {
+        panic!("expected let, got {:?}", module.functions["run"].body);
+    };
+    assert_eq!(bindings.len(), 1, "{bindings:?}");
+    let shared = &bindings[0].name;
+    match body.as_ref() {
+        Expr::PrimOp {
+            op: PrimOp::AddInt,
+            args,
+            ..
+        } => {
+            for arg in args {
+                assert!(
+                    matches!(arg, Expr::Var { name, .. } if name == shared),
+                    "both operands must reuse the binding: {arg:?}"
+                );
+            }
+        }
+        other => panic!("expected addition of the shared value, got {other:?}"),
+    }
+}
+
+#[test]
+/// 副作用を持つ `trace` や、分岐の片側にしか現れない式はまとめない。
+fn impure_or_conditional_expressions_are_not_shared() {
+    let src = r#"
+square :: Int -> Int;
+let square x = x * x;
+traced :: Int;
+let traced = trace "hit" 1 + trace "hit" 1;
+branchy :: Bool -> Int;
+let branchy b = if b then square 2 else square 2;
+"#;
+    let prog = parser::parse_program(src).expect("parse program");
+    let module = compile_core_ir(&prog).expect("lower to core ir");
+    match &module.functions["traced"].body {
+        Expr::PrimOp { args, .. } => {
+            assert!(args.iter().all(|arg| matches!(arg, Expr::Apply { .. })));
+        }
+        other => panic!("trace calls must stay separate, got {other:?}"),
+    }
+    assert!(
+        matches!(module.functions["branchy"].body, Expr::If { .. }),
+        "{:?}",
+        module.functions["branchy"].body
+    );
+}
+
+#[test]
+/// プレリュードからは使われる定義だけを取り込み、同名の定義はプログラム側を優先する。
+fn prelude_contributes_only_referenced_definitions() {
+    let src = r#"
+const :: Int -> Int -> Int;
+let const x y = x + y;
+
+main :: Int;
+let main = const (id 40) 2;
+"#;
+    let prog = parser::parse_program(src).expect("parse program");
+    let prelude = typelang::default_prelude();
+    let merged = typelang::prelude::with_prelude(&prog, &prelude);
+    let names: Vec<&str> = merged.decls.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, ["id", "const", "main"]);
+
+    let module =
+        typelang::compile_core_ir_with_prelude(&prog, Some(&prelude)).expect("lower with prelude");
+    assert!(!module.functions.contains_key("flip"));
+    let value = typelang::eval_program(&merged).expect("evaluate merged program");
+    assert!(matches!(value, typelang::Value::Int(42)), "{value:?}");
+
+    let errs = compile_core_ir(&prog).expect_err("id is undefined without the prelude");
+    assert!(errs.to_string().contains("id"), "{errs}");
+}
+
+#[test]
+/// lowering 前の型検査はトップレベルの定義を評価しない。実行時に失敗する `main` も型が合えば lowering できる。
+fn lower_type_checks_without_evaluating_definitions() {
+    let src = r#"
+main :: Int;
+let main = (1 :: Int) `div` 0;
+"#;
+    let prog = parser::parse_program(src).expect("parse program");
+    let module = lower_program(&prog).expect("division by zero is a run-time error");
+    assert_eq!(module.functions["main"].result, ValueTy::Int);
+}
+
+#[test]
+/// 型注釈付きの再帰関数は注釈の型で登録し、本体が注釈と矛盾すればエラーにする。
+fn lower_checks_recursive_definitions_against_signatures() {
+    let src = r#"
+count :: Int -> Int;
+let count n = if n == 0 then 0 else count (n - 1) + 1;
+main :: Int;
+let main = count 3;
+"#;
+    let prog = parser::parse_program(src).expect("parse program");
+    let module = lower_program(&prog).expect("annotated recursion type-checks");
+    assert_eq!(module.functions["count"].result, ValueTy::Int);
+
+    let src = r#"
+bad :: Int -> Bool;
+let bad n = if n == 0 then 'z' else bad (n - 1);
+main :: Bool;
+let main = bad 3;
+"#;
+    let prog = parser::parse_program(src).expect("parse program");
+    let errs = lower_program(&prog).expect_err("body disagrees with the signature");
+    assert!(errs.to_string().contains("TYPE"), "{errs}");
+}
+
+#[test]
+/// 構築子の適用は型環境の構築子関数ではなく、データレイアウトの構築子として lowering する。
+fn lower_constructor_application_resolves_to_data_constructor() {
+    let src = r#"
+data Slot = Empty | Full Int;
+fill :: Int -> Slot;
+let fill n = Full n;
+main :: Int;
+let main = 0;
+"#;
+    let prog = parser::parse_program(src).expect("parse program");
+    let module = lower_program(&prog).expect("lower program");
+    let Expr::Apply { func, .. } = &module.functions["fill"].body else {
+        panic!(
+            "fill should apply a constructor: {:?}",
+            module.functions["fill"].body
+        );
+    };
+    assert!(
+        matches!(
+            func.as_ref(),
+            Expr::Var { name, kind: VarKind::Primitive, .. } if name == "Full"
+        ),
+        "{func:?}"
+    );
+}
@@ -111,6 +111,396 @@ fn evaluator_smoke_suite() {
             expect: Expect::Double(0.5),
             note: "powf の負指数",
         },
+        EvalCase {
+            expr: "trace \"debug\" (20 + 22)",
+            expect: Expect::Int(42),
+            note: "trace は第 2 引数をそのまま返す",
+        },
+        EvalCase {
+            expr: "(12 .&. 10, 12 .|. 10, xor 12 10) == (8, 14, 6)",
+            expect: Expect::Bool(true),
+            note: "ビット積・和・排他的論理和",
+        },
+        EvalCase {
+            expr: "shiftR (shiftL 1 10) 4",
+            expect: Expect::Int(64),
+            note: "シフト演算",
+        },
+        EvalCase {
+            expr: "1 .|. 6 .&. 3",
+            expect: Expect::Int(3),
+            note: ".&. は .|. より強く結合する",
+        },
+        EvalCase {
+            expr: "sqrt 2.0",
+            expect: Expect::Double(std::f64::consts::SQRT_2),
+            note: "平方根",
+        },
+        EvalCase {
+            expr: "log (exp (cos (sin 0.0)))",
+            expect: Expect::Double(1.0),
+            note: "超越関数",
+        },
+        EvalCase {
+            expr: "(floor 2.7, ceiling 2.1, round 2.5, truncate (-2.7)) == (2, 3, 2, -2)",
+            expect: Expect::Bool(true),
+            note: "Double から Int への丸め",
+        },
+        EvalCase {
+            expr: "fromIntegral (round 3.5) / 8.0",
+            expect: Expect::Double(0.5),
+            note: "fromIntegral で Double へ変換",
+        },
+        EvalCase {
+            expr: "null []",
+            expect: Expect::Bool(true),
+            note: "空リストは null",
+        },
+        EvalCase {
+            expr: "null [1]",
+            expect: Expect::Bool(false),
+            note: "要素を持つリストは null ではない",
+        },
+        EvalCase {
+            expr: "length [1, 2, 3]",
+            expect: Expect::Int(3),
+            note: "length は要素数を返す",
+        },
+        EvalCase {
+            expr: "length \"あい\"",
+            expect: Expect::Int(2),
+            note: "文字列の length は文字数",
+        },
+        EvalCase {
+            expr: "take 3 (replicate 5 'x') == ['x', 'x', 'x']",
+            expect: Expect::Bool(true),
+            note: "replicate で作ったリストの先頭を take で取り出す",
+        },
+        EvalCase {
+            expr: "length (take 9 [1, 2]) * 10 + length (drop (-1) [1, 2]) + length (replicate (-3) 0)",
+            expect: Expect::Int(22),
+            note: "個数はリストの長さと 0 の間に収まる",
+        },
+        EvalCase {
+            expr: "drop 1 \"あいう\"",
+            expect: Expect::String("いう"),
+            note: "文字列は文字単位で drop する",
+        },
+        EvalCase {
+            expr: "take 5 [1..] == [1, 2, 3, 4, 5]",
+            expect: Expect::Bool(true),
+            note: "上限のない範囲は take で必要な個数だけ取り出す",
+        },
+        EvalCase {
+            expr: "take 3 [9223372036854775806..] == [9223372036854775806, 9223372036854775807]",
+            expect: Expect::Bool(true),
+            note: "上限のない範囲も Int の最大値で止まる",
+        },
+        EvalCase {
+            expr: "length (drop 1 [1..])",
+            expect: Expect::Error("EVAL050"),
+            note: "上限のない範囲は take 以外では取り出せない（型検査でも拒否される）",
+        },
+        EvalCase {
+            expr: "foldl' (+) 0 (replicate 1000000 1)",
+            expect: Expect::Int(1000000),
+            note: "foldl' は長いリストも再帰せずに畳み込む",
+        },
+        EvalCase {
+            expr: "foldl' (-) 10 [1, 2, 3] * 100 + foldl' (+) 7 []",
+            expect: Expect::Int(407),
+            note: "foldl' は左から畳み込み、空リストでは初期値を返す",
+        },
+        EvalCase {
+            expr: "case readInt \" 42 \" of Just n -> n; Nothing -> 0",
+            expect: Expect::Int(42),
+            note: "readInt は前後の空白を無視して読む",
+        },
+        EvalCase {
+            expr: "readInt \"4x\" == Nothing && readInt \"\" == Nothing",
+            expect: Expect::Bool(true),
+            note: "読めない文字列や空文字列は Nothing",
+        },
+        EvalCase {
+            expr: "case readDouble \"-1.5e1\" of Just d -> d; Nothing -> 0.0",
+            expect: Expect::Double(-15.0),
+            note: "readDouble は指数表記も読む",
+        },
+        EvalCase {
+            expr: "format \"%d + %d\" 1 2",
+            expect: Expect::String("1 + 2"),
+            note: "format は指定子の位置へ引数を埋め込む",
+        },
+        EvalCase {
+            expr: "format \"%s=%f, %.2f%%\" \"x\" 0.5 (1.0 / 3.0)",
+            expect: Expect::String("x=0.5, 0.33%"),
+            note: "%f は show と同じ表示、%.Nf は桁数を固定する",
+        },
+        EvalCase {
+            expr: "format \"done\"",
+            expect: Expect::String("done"),
+            note: "指定子がなければそのまま文字列になる",
+        },
+        EvalCase {
+            expr: "let xs = fromList [10, 20, 30] in xs ! 0 + xs ! 2",
+            expect: Expect::Int(40),
+            note: "fromList で作った配列を (!) で添字アクセス",
+        },
+        EvalCase {
+            expr: "fromList \"abc\" ! 1 == 'b'",
+            expect: Expect::Bool(true),
+            note: "文字列からも配列を作れる",
+        },
+        EvalCase {
+            expr: "fromList [1, 2] ! 2",
+            expect: Expect::Error("EVAL062"),
+            note: "範囲外の添字はエラー",
+        },
+        EvalCase {
+            expr: "catch (fromList [1, 2] ! 5) (\\msg -> 0 - 1)",
+            expect: Expect::Int(-1),
+            note: "catch は評価エラーの代わりにハンドラの結果を返す",
+        },
+        EvalCase {
+            expr: "catch (fromList [1, 2] ! 1) (\\msg -> 0 - 1)",
+            expect: Expect::Int(2),
+            note: "エラーがなければ catch は第 1 引数の値をそのまま返す",
+        },
+        EvalCase {
+            expr: "catch (show (fromList [1] ! 3)) (\\msg -> msg)",
+            expect: Expect::String("[EVAL062] (!): 添字 3 は配列の範囲外です (長さ 1)"),
+            note: "ハンドラにはエラーコード付きのメッセージが渡る",
+        },
+        EvalCase {
+            expr: "let catch x h = 5 in catch (fromList [1] ! 3) (\\msg -> 0)",
+            expect: Expect::Error("EVAL062"),
+            note: "同名の束縛で覆い隠された catch はエラーを捕捉しない",
+        },
+        EvalCase {
+            expr: "False && fromList [1] ! 3 == 0",
+            expect: Expect::Bool(false),
+            note: "&& は左辺が False なら右辺を評価しない",
+        },
+        EvalCase {
+            expr: "True || fromList [1] ! 3 == 0",
+            expect: Expect::Bool(true),
+            note: "|| は左辺が True なら右辺を評価しない",
+        },
+        EvalCase {
+            expr: "True && fromList [1] ! 3 == 0",
+            expect: Expect::Error("EVAL062"),
+            note: "左辺で決まらなければ右辺を評価する",
+        },
+        EvalCase {
+            expr: "(\\op -> op True False) (&&) || 1 < 2 && 2 < 3",
+            expect: Expect::Bool(true),
+            note: "&& は || より強く結合し、関数値としても使える",
+        },
+        EvalCase {
+            expr: "[1] <> [2] <> mempty == [1, 2]",
+            expect: Expect::Bool(true),
+            note: "<> はリストを連結し、mempty は単位元",
+        },
+        EvalCase {
+            expr: "\"ab\" <> mempty <> \"c\"",
+            expect: Expect::String("abc"),
+            note: "文字列も <> で連結できる",
+        },
+        EvalCase {
+            expr: "show (['a'] <> \"b\")",
+            expect: Expect::String("\"ab\""),
+            note: "文字のリストと文字列の連結は文字列になる",
+        },
+        EvalCase {
+            expr: "case \"hi\" of \"hi\" -> True; _ -> False",
+            expect: Expect::Bool(true),
+            note: "文字列パターンは内容が等しければ一致する",
+        },
+        EvalCase {
+            expr: "case \"hip\" of \"hi\" -> True; _ -> False",
+            expect: Expect::Bool(false),
+            note: "文字列パターンは前方一致では一致しない",
+        },
+        EvalCase {
+            expr: "case ['h', 'i'] of \"hi\" -> True; _ -> False",
+            expect: Expect::Bool(true),
+            note: "文字のリストも文字列パターンと一致する",
+        },
+        EvalCase {
+            expr: "case \"ab\" of [a, b] -> b == 'b'; _ -> False",
+            expect: Expect::Bool(true),
+            note: "文字列をリストパターンで分解できる",
+        },
+        EvalCase {
+            expr: "case stripPrefix \"foo\" \"foobar\" of Just rest -> rest; Nothing -> \"none\"",
+            expect: Expect::String("bar"),
+            note: "stripPrefix は接頭辞を除いた残りを返す",
+        },
+        EvalCase {
+            expr: "stripPrefix [1, 2] [1, 3] == Nothing && stripPrefix \"a\" \"ab\" == Just ['b']",
+            expect: Expect::Bool(true),
+            note: "接頭辞でなければ Nothing、残りは文字のリストとも比較できる",
+        },
+        EvalCase {
+            expr: "intercalate \", \" [\"a\", \"b\", \"c\"]",
+            expect: Expect::String("a, b, c"),
+            note: "intercalate は区切りを挟んで連結する",
+        },
+        EvalCase {
+            expr: "concat [[1], [2, 3], []] == [1, 2, 3]",
+            expect: Expect::Bool(true),
+            note: "concat はリストのリストを平らにする",
+        },
+        EvalCase {
+            expr: "concatMap show [1, 2, 3]",
+            expect: Expect::String("123"),
+            note: "concatMap は写した結果を連結する",
+        },
+        EvalCase {
+            expr: "unwords [\"a\", \"b\"] <> \"|\" <> unlines [\"c\", \"d\"] <> unwords []",
+            expect: Expect::String("a b|c\nd\n"),
+            note: "unwords は空白で、unlines は改行で区切る",
+        },
+        EvalCase {
+            expr: "max 3 7",
+            expect: Expect::Int(7),
+            note: "max は大きい方を返す",
+        },
+        EvalCase {
+            expr: "show (min 'a' 'z')",
+            expect: Expect::String("'a'"),
+            note: "min は Ord を満たす文字にも使える",
+        },
+        EvalCase {
+            expr: "min [2, 1] [2] == [2]",
+            expect: Expect::Bool(true),
+            note: "min はリストを構造的に比較する",
+        },
+        EvalCase {
+            expr: "max (1, 'b') (1, 'a') == (1, 'b')",
+            expect: Expect::Bool(true),
+            note: "max はタプルを構造的に比較する",
+        },
+        EvalCase {
+            expr: "clamp 0 10 15 * 100 + clamp 0 10 (0 - 3) * 10 + clamp 0 10 7",
+            expect: Expect::Int(1007),
+            note: "clamp は範囲外の値を端へ寄せ、範囲内の値はそのまま返す",
+        },
+        EvalCase {
+            expr: "between 1 5 3 && between 1 5 5 && between 1 5 6 == False",
+            expect: Expect::Bool(true),
+            note: "between は両端を含む",
+        },
+        EvalCase {
+            expr: "between \"a\" \"c\" \"b\"",
+            expect: Expect::Bool(true),
+            note: "between は文字列も構造的に比較する",
+        },
+        EvalCase {
+            expr: "let r = newRef 1 in let u = writeRef r (readRef r + 5) in readRef r * 10 + readRef r",
+            expect: Expect::Int(66),
+            note: "writeRef で書き換えた値が以降の readRef に見える",
+        },
+        EvalCase {
+            expr: "case lookup 2 (insert 2 20 (insert 1 10 emptyMap)) of Just v -> v; Nothing -> 0",
+            expect: Expect::Int(20),
+            note: "insert した値を lookup で引ける",
+        },
+        EvalCase {
+            expr: "case lookup 1 (insert 1 11 (insert 1 10 emptyMap)) of Just v -> v; Nothing -> 0",
+            expect: Expect::Int(11),
+            note: "同じキーへの insert は値を置き換える",
+        },
+        EvalCase {
+            expr: "lookup 3 (insert 1 10 emptyMap) == Nothing",
+            expect: Expect::Bool(true),
+            note: "存在しないキーは Nothing",
+        },
+        EvalCase {
+            expr: "let m = insert 1 10 emptyMap in findWithDefault 0 1 (insert 1 5 m) * 100 + findWithDefault 0 1 m + findWithDefault 7 2 m",
+            expect: Expect::Int(517),
+            note: "insert は元の表を変更せず、findWithDefault は既定値へフォールバックする",
+        },
+        EvalCase {
+            expr: "case lookup (1, \"a\") (insert (1, \"a\") True emptyMap) of Just v -> v; Nothing -> False",
+            expect: Expect::Bool(true),
+            note: "タプルのキーは構造的に比較される",
+        },
+        EvalCase {
+            expr: "findWithDefault 0 ['a', 'b'] (insert \"ab\" 1 emptyMap) * 10 + findWithDefault 0 \"cd\" (insert ['c', 'd'] 2 emptyMap)",
+            expect: Expect::Int(12),
+            note: "文字列と文字のリストは同じキーとして引ける",
+        },
+        EvalCase {
+            expr: "let nan = 0.0 / 0.0 in findWithDefault 0 nan (insert nan 1 emptyMap)",
+            expect: Expect::Int(1),
+            note: "NaN のキーも挿入したものと同じキーとして引ける",
+        },
+        EvalCase {
+            expr: "\"ab\" == ['a', 'b'] && ['a'] < \"b\"",
+            expect: Expect::Bool(true),
+            note: "文字列と文字のリストは内容で比較する",
+        },
+        EvalCase {
+            expr: "let isEven n = if n == 0 then True else isOdd (n - 1); isOdd n = if n == 0 then False else isEven (n - 1) in isEven 10",
+            expect: Expect::Bool(true),
+            note: "let グループ内の相互再帰",
+        },
+        EvalCase {
+            expr: "let isOdd n = if n == 0 then False else isEven (n - 1); isEven n = if n == 0 then True else isOdd (n - 1) in isOdd 7",
+            expect: Expect::Bool(true),
+            note: "後に定義した束縛を先の束縛から参照する",
+        },
+        EvalCase {
+            expr: "let total = base + 1; base = 2 in total",
+            expect: Expect::Int(3),
+            note: "値束縛は依存する後続の束縛を先に評価する",
+        },
+        EvalCase {
+            expr: "let result = step 3; step n = n + offset; offset = 10 in result",
+            expect: Expect::Int(13),
+            note: "関数束縛を経由した値束縛への依存",
+        },
+        EvalCase {
+            expr: "let base = (\\total -> total * 2) 3; total = base + 1 in total",
+            expect: Expect::Int(7),
+            note: "ラムダ引数で隠された名前は依存とみなさない",
+        },
+        EvalCase {
+            expr: "let base = (case 4 of total -> total); total = base + 1 in total",
+            expect: Expect::Int(5),
+            note: "case のパターン変数で隠された名前は依存とみなさない",
+        },
+        EvalCase {
+            expr: "let π = 3; 面積 r = π * r * r in 面積 2",
+            expect: Expect::Int(12),
+            note: "ギリシャ文字・漢字の識別子",
+        },
+        EvalCase {
+            expr: "floor (1.0 / 0.0)",
+            expect: Expect::Error("EVAL060"),
+            note: "Int に収まらない丸めはエラー",
+        },
+        EvalCase {
+            expr: "1.5 + 1.25",
+            expect: Expect::Double(2.75),
+            note: "Double 同士の加算は切り捨てない",
+        },
+        EvalCase {
+            expr: "let x = 2.5 in -x * 2.0",
+            expect: Expect::Double(-5.0),
+            note: "単項マイナスは negate に脱糖",
+        },
+        EvalCase {
+            expr: "1 + 2.5",
+            expect: Expect::Double(3.5),
+            note: "Double として使われた整数リテラルは Double で計算する",
+        },
+        EvalCase {
+            expr: "let f x = x + 0.5 in f 2",
+            expect: Expect::Double(2.5),
+            note: "引数として渡した整数リテラルも Double で計算する",
+        },
         EvalCase {
             expr: "div 7 3",
             expect: Expect::Int(2),
@@ -151,6 +541,16 @@ fn evaluator_smoke_suite() {
             expect: Expect::String("42"),
             note: "show が文字列を返す",
         },
+        EvalCase {
+            expr: r#"show "a\nb""#,
+            expect: Expect::String(r#""a\nb""#),
+            note: "show は文字列を引用符で囲み改行をエスケープする",
+        },
+        EvalCase {
+            expr: "show 'x'",
+            expect: Expect::String("'x'"),
+            note: "show は文字を単引用符で囲む",
+        },
         EvalCase {
             expr: "println 42",
             expect: Expect::String("42"),
@@ -191,6 +591,36 @@ fn evaluator_smoke_suite() {
             expect: Expect::Int(1),
             note: "浮動小数リテラルパターン",
         },
+        EvalCase {
+            expr: "case -1 of -1 -> True; _ -> False",
+            expect: Expect::Bool(true),
+            note: "負の整数リテラルパターン",
+        },
+        EvalCase {
+            expr: "case 3 of -3 -> 1; 3 -> 2; _ -> 0",
+            expect: Expect::Int(2),
+            note: "負のパターンは符号まで区別する",
+        },
+        EvalCase {
+            expr: "case -2.5 of 2.5 -> 1; -2.5 -> 2; _ -> 0",
+            expect: Expect::Int(2),
+            note: "負の浮動小数リテラルパターン",
+        },
+        EvalCase {
+            expr: "let (|>) x f = f x in 3 |> (\\n -> n * 2) |> (+) 1",
+            expect: Expect::Int(7),
+            note: "ユーザー定義演算子の定義と中置適用",
+        },
+        EvalCase {
+            expr: "7 `div` 2 + 10 `mod` 4",
+            expect: Expect::Int(5),
+            note: "バッククォートによる組み込み関数の中置適用",
+        },
+        EvalCase {
+            expr: "let f x y = x * 10 + y in 1 `f` 2 `f` 3",
+            expect: Expect::Int(123),
+            note: "利用者定義関数のバッククォート適用は左結合",
+        },
     ];
 
     let failure_cases = [
@@ -308,3 +738,31 @@ fn eval_error_reports_stack_with_location() {
         "stack summary missing expression: {rendered}"
     );
 }
+
+#[test]
+/// ステップ上限を超える評価が EVAL100 で打ち切られることを検証する。
+fn eval_step_limit_stops_runaway_evaluation() {
+    let env = evaluator::initial_env();
+    let finite = support::parse_expr("let f n = if n == 0 then 0 else f (n - 1) in f 3");
+    let value = evaluator::eval_expr_with_limit(&finite, &env, Some(10_000)).expect("within limit");
+    assert_value_int(value, 0, "上限内の評価");
+
+    let runaway = support::parse_expr("let loop n = loop (n + 1) in loop 0");
+    let err = evaluator::eval_expr_with_limit(&runaway, &env, Some(200)).expect_err("上限超過");
+    assert_eq!(err.0.code, "EVAL100");
+}
+#[test]
+/// ゼロ除算エラーが失敗した演算子の位置を `span()` として保持することを検証する。
+fn eval_error_span_points_at_failing_operator() {
+    let err = eval_result("1 + 10 `div` 0").expect_err("ゼロ除算");
+    assert_eq!(err.0.code, "EVAL061");
+    assert_eq!(err.span(), Some(Span::new(8, 1, 9)));
+
+    let nested = eval_result("let f x = 100 `div` x in\n  1 + f 0").expect_err("関数内のゼロ除算");
+    assert_eq!(nested.span(), Some(Span::new(15, 1, 16)));
+
+    // 先頭位置 (pos=0) の式もバイトオフセットを失わない。
+    let head = eval_result("div 1 0").expect_err("先頭のゼロ除算");
+    assert_eq!(head.span(), Some(Span::new(0, 1, 1)));
+    assert!(head.to_string().contains("@line=1,col=1 @pos=0"), "{head}");
+}
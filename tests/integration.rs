@@ -6,6 +6,7 @@
 mod support;
 
 use support::{assert_value_bool, assert_value_double, assert_value_int, ProgramFixture};
+use typelang::ast::TypeExpr;
 use typelang::{evaluator, infer, parser};
 
 const BASICS_TL: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/examples/basics.tl"));
@@ -161,6 +162,65 @@ fn load_data_and_case_evaluate() {
     );
 }
 
+#[test]
+/// `fmap` がリスト・`Maybe`・`Either` の中身だけを写し、構造を保つことを確認する。
+fn fmap_maps_lists_maybe_and_either() {
+    let src = r#"
+data Maybe a = Nothing | Just a;
+data Either a b = Left a | Right b;
+
+let incMaybe = fmap (\x -> x + 1) (Just (2 :: Int));
+let incNothing = fmap (\x -> x + 1) (Nothing :: Maybe Int);
+let scaled = fmap (\x -> x * 10) (Right 4 :: Either [Char] Int);
+let failed = fmap (\x -> x * 10) (Left "e" :: Either [Char] Int);
+let doubled = fmap (\x -> x * 2) [1, 2, 3];
+"#;
+    let fixture = ProgramFixture::load(src);
+    for (symbol, expected) in [
+        ("incMaybe", "Maybe Int"),
+        ("scaled", "Either [Char] Int"),
+        ("doubled", "[Integer]"),
+    ] {
+        let scheme = fixture.type_env.lookup(symbol).unwrap().qual.clone();
+        assert_eq!(
+            typelang::typesys::pretty_qual(&scheme),
+            expected,
+            "{symbol}"
+        );
+    }
+
+    assert_value_int(
+        fixture.eval_value("case incMaybe of Just n -> n; Nothing -> 0"),
+        3,
+        "fmap over Just",
+    );
+    assert_value_bool(
+        fixture.eval_value("case incNothing of Nothing -> True; _ -> False"),
+        true,
+        "fmap keeps Nothing",
+    );
+    assert_value_int(
+        fixture.eval_value("case scaled of Right n -> n; Left _ -> 0"),
+        40,
+        "fmap over Right",
+    );
+    assert_value_bool(
+        fixture.eval_value("case failed of Left e -> e == \"e\"; Right _ -> False"),
+        true,
+        "fmap keeps Left",
+    );
+    assert_value_bool(
+        fixture.eval_value("doubled == [2, 4, 6]"),
+        true,
+        "fmap over list",
+    );
+
+    let err = fixture
+        .eval_result("fmap (\\x -> x) 3")
+        .expect_err("numbers are not functors");
+    assert_eq!(err.0.code, "EVAL050");
+}
+
 #[test]
 /// class / instance 宣言が ClassEnv に反映されることを確認する。
 fn load_user_defined_class_and_instance() {
@@ -172,3 +232,110 @@ fn load_user_defined_class_and_instance() {
         .instances
         .contains(&("Eqish".to_string(), "Int".to_string())));
 }
+
+#[test]
+/// type 宣言が推論前に展開され、循環定義がエラーになることを確認する。
+fn load_type_synonyms_and_detect_cycles() {
+    let src = "type Name = [Char];\ntype Pair a = (a, a);\n\
+               greet :: Name -> Name\nlet greet n = n;\n\
+               swap :: Pair Int -> Pair Int\nlet swap p = case p of (x, y) -> (y, x)";
+    let program = parser::parse_program(src).expect("parse synonyms");
+    assert_eq!(program.type_synonyms.len(), 2);
+    let sig = program.decls[1].signature.as_ref().unwrap();
+    assert_eq!(
+        sig.r#type,
+        TypeExpr::TEFun(
+            Box::new(TypeExpr::TETuple(vec![
+                TypeExpr::TECon("Int".into()),
+                TypeExpr::TECon("Int".into())
+            ])),
+            Box::new(TypeExpr::TETuple(vec![
+                TypeExpr::TECon("Int".into()),
+                TypeExpr::TECon("Int".into())
+            ])),
+        )
+    );
+    let fixture = ProgramFixture::load(src);
+    assert_eq!(fixture.exports, vec!["greet", "swap"]);
+
+    let mismatch = parser::parse_program("type Name = [Char];\nbad :: Name -> Int\nlet bad x = x")
+        .expect("parse mismatch");
+    let mut type_env = infer::initial_env();
+    let mut class_env = infer::initial_class_env();
+    let mut value_env = evaluator::initial_env();
+    let res = typelang::repl::load_program_into_env(
+        &mismatch,
+        &mut type_env,
+        &mut class_env,
+        &mut value_env,
+    );
+    assert!(res.is_err(), "展開後の型注釈で検査される");
+    value_env.teardown();
+
+    let cyclic = parser::parse_program("type A = B;\ntype B = [A];\nlet x = 1").unwrap_err();
+    assert_eq!(cyclic.first().0.code, "PAR521");
+    let partial =
+        parser::parse_program("type P a = (a, a);\nf :: P -> Int\nlet f x = 1").unwrap_err();
+    assert_eq!(partial.first().0.code, "PAR522");
+}
+
+#[test]
+/// run_file が import を解決して main を評価し、main の無いファイルを拒否することを確認する。
+fn run_file_evaluates_main_with_imports() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    std::fs::write(dir.path().join("lib.tl"), "let double x = x * 2;").unwrap();
+    let entry = dir.path().join("main.tl");
+    std::fs::write(&entry, "import \"lib.tl\";\nlet main = double 21;").unwrap();
+    let value = typelang::repl::run_file(&entry.display().to_string()).expect("run main");
+    assert_value_int(value, 42, "main via run_file");
+
+    let lib = dir.path().join("lib.tl").display().to_string();
+    let err = typelang::repl::run_file(&lib).unwrap_err();
+    assert!(err.contains("main が定義されていません"), "{err}");
+}
+
+#[test]
+/// 出力だけを行う main の副作用が順に実行され、結果が () になることを確認する。
+fn run_file_runs_println_side_effects_of_unit_main() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let entry = dir.path().join("main.tl");
+    std::fs::write(
+        &entry,
+        "main :: ();\nlet main = let a = println \"x\"; b = println 2 in ();",
+    )
+    .unwrap();
+    let (value, lines) = typelang::runtime::with_captured_output(|| {
+        typelang::repl::run_file(&entry.display().to_string())
+    });
+    assert!(matches!(value, Ok(evaluator::Value::Tuple(items)) if items.is_empty()));
+    assert_eq!(lines, vec!["x".to_string(), "2".to_string()]);
+}
+
+#[test]
+/// クレート直下の評価 API が値と型付きエラーを返すことを確認する。
+fn crate_root_eval_api_returns_values_and_typed_errors() {
+    let env = typelang::initial_value_env();
+    let expr = parser::parse_expr("let sq x = x * x in sq 9").unwrap();
+    assert_value_int(
+        typelang::eval_expr_in(&env, &expr).expect("eval_expr_in"),
+        81,
+        "eval_expr_in",
+    );
+
+    let program =
+        parser::parse_program("let pick p = case p of (a, _) -> a; let main = pick (1, 2);")
+            .unwrap();
+    assert_value_int(
+        typelang::eval_program(&program).expect("eval_program"),
+        1,
+        "eval_program main",
+    );
+
+    let no_main = parser::parse_program("let helper = 1;").unwrap();
+    let err = typelang::eval_program(&no_main).unwrap_err();
+    assert_eq!(err.0.code, "EVAL111");
+
+    let broken = parser::parse_program("let main = missing + 1;").unwrap();
+    let err = typelang::eval_program(&broken).unwrap_err();
+    assert_eq!(err.0.code, "EVAL110");
+}
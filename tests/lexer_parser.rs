@@ -6,7 +6,7 @@
 mod support;
 
 use support::{lex_ok, parse_expr, parse_program};
-use typelang::ast::TypeExpr;
+use typelang::ast::{Associativity, TypeExpr};
 use typelang::lexer::{self, TokenKind};
 use typelang::parser;
 
@@ -77,6 +77,26 @@ fn lexer_happy_paths() {
             ],
             note: "class/instance/where/as パターンのトークン",
         },
+        Case {
+            src: "let m = x .&. 0xff .|. 1;",
+            kinds: &[TokenKind::BITAND, TokenKind::BITOR],
+            note: "ビット演算子",
+        },
+        Case {
+            src: "let (|>) x f = f x; let y = a|>b-- 末尾コメント",
+            kinds: &[TokenKind::LPAREN, TokenKind::OPERATOR, TokenKind::EQUAL],
+            note: "ユーザー定義演算子は記号列を 1 トークンにまとめる",
+        },
+        Case {
+            src: "let q = 7 `div` 2;",
+            kinds: &[TokenKind::BACKTICK, TokenKind::VARID, TokenKind::INT],
+            note: "バッククォートによる中置適用",
+        },
+        Case {
+            src: "module Calc.Core (triple)\nlet y = Calc.Core.triple 2;",
+            kinds: &[TokenKind::MODULE, TokenKind::CONID, TokenKind::VARID],
+            note: "module 宣言と修飾名",
+        },
     ];
 
     for case in cases {
@@ -102,13 +122,264 @@ fn lexer_error_paths() {
     }
 }
 
+#[test]
+/// 閉じていない文字列は開始位置(バイト・行・列)を持つ `LEX003` になり、構文解析器も同じ位置を報告する。
+fn lexer_unterminated_string_reports_position() {
+    let src = "let a = 1;\nlet s = \"abc\nlet b = 2;";
+    let err = lexer::lex(src).expect_err("unterminated string");
+    assert_eq!(err.0.code, "LEX003");
+    assert_eq!(
+        (err.0.pos, err.0.line, err.0.col),
+        (Some(19), Some(2), Some(9))
+    );
+
+    let parsed = parser::parse_program(src).expect_err("lex error surfaces in parser");
+    let parsed = parsed.first();
+    assert_eq!(parsed.0.code, "PAR100");
+    assert_eq!(
+        (parsed.0.pos, parsed.0.line, parsed.0.col),
+        (Some(19), Some(2), Some(9))
+    );
+}
+
+#[test]
+/// `"""` で囲んだ生文字列は引用符・バックスラッシュ・改行をそのまま含み、後続トークンの行もずれない。
+fn raw_string_literals_keep_quotes_and_newlines() {
+    let src = "let s = \"\"\"say \"hi\"\n  C:\\path\\n\"\"\";\nlet t = 1;";
+    let tokens = lex_ok(src);
+    let raw = tokens
+        .iter()
+        .find(|t| t.kind == TokenKind::STRING)
+        .expect("raw string token");
+    assert_eq!((raw.line, raw.col), (1, 9));
+    let next_let = tokens
+        .iter()
+        .filter(|t| t.kind == TokenKind::LET)
+        .nth(1)
+        .expect("second let");
+    assert_eq!((next_let.line, next_let.col), (3, 1));
+
+    match parse_expr("\"\"\"a \"b\" \\n\nc\"\"\"") {
+        typelang::ast::Expr::StringLit { value, .. } => assert_eq!(value, "a \"b\" \\n\nc"),
+        other => panic!("expected string literal, got {other:?}"),
+    }
+    match parse_expr("\"\"\"\"\"\"") {
+        typelang::ast::Expr::StringLit { value, .. } => assert_eq!(value, ""),
+        other => panic!("expected empty string literal, got {other:?}"),
+    }
+
+    let err = lexer::lex("let s = \"\"\"open\n\"\";").expect_err("unterminated raw string");
+    assert_eq!(err.0.code, "LEX004");
+    assert_eq!((err.0.line, err.0.col), (Some(1), Some(9)));
+}
+
+#[test]
+/// 入れ子のブロックコメントを読み飛ばし、後続トークンの行・列がずれないことを検証する。
+fn lexer_block_comments_keep_positions() {
+    let tokens = lex_ok("{- a {- 入れ子\n -} b -}\n  let {- x -} y = {-\n-}1");
+    let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        [
+            TokenKind::LET,
+            TokenKind::VARID,
+            TokenKind::EQUAL,
+            TokenKind::INT,
+            TokenKind::EOF
+        ]
+    );
+    assert_eq!((tokens[0].line, tokens[0].col), (3, 3));
+    assert_eq!((tokens[1].line, tokens[1].col), (3, 15));
+    assert_eq!((tokens[3].line, tokens[3].col), (4, 3));
+
+    // 閉じていないコメントは開始位置を指すエラーになる（内側だけ閉じても不可）。
+    let err =
+        lexer::lex("let x = 1;\n  {- outer {- inner -}\nlet y = 2;").expect_err("unterminated");
+    assert_eq!(err.0.code, "LEX001");
+    assert_eq!((err.0.line, err.0.col), (Some(2), Some(3)));
+}
+
+#[test]
+/// 公開 API の `tokenize` がバイト範囲付きのトークンを返し、コメントは DOC だけを残すことを検証する。
+fn tokenize_reports_byte_spans_and_keeps_doc_comments() {
+    let src = "-- | 二乗\n--   する\nsq x = x * x -- 末尾\n{- 無視 -}\"é\"";
+    let tokens = typelang::tokenize(src).expect("tokenize");
+    let spans: Vec<(TokenKind, &str)> = tokens
+        .iter()
+        .map(|t| (t.kind.clone(), &src[t.pos..t.end]))
+        .collect();
+    assert_eq!(
+        spans,
+        [
+            (TokenKind::DOC, "-- | 二乗\n--   する"),
+            (TokenKind::VARID, "sq"),
+            (TokenKind::VARID, "x"),
+            (TokenKind::EQUAL, "="),
+            (TokenKind::VARID, "x"),
+            (TokenKind::STAR, "*"),
+            (TokenKind::VARID, "x"),
+            (TokenKind::STRING, "\"é\""),
+            (TokenKind::EOF, ""),
+        ]
+    );
+    assert_eq!(tokens[0].value, "二乗\nする");
+    let eof = tokens.last().expect("eof");
+    assert_eq!((eof.pos, eof.end), (src.len(), src.len()));
+}
+
+#[test]
+/// `relex` による差分字句解析の結果が、編集後のソース全体を読み直した結果と一致することを検証する。
+fn relex_matches_full_lex_after_edits() {
+    let src = "-- | 和\nadd x y = x + y;\n{- 注釈 -}\nmain = add 1 2 -- 末尾\n  + length \"é\";\n";
+    let prev = lex_ok(src);
+    let edits = [
+        (src.find("add x").unwrap() + 3, 0, "er"), // 識別子の末尾に追記して 1 トークンに結合
+        (src.find(" + y").unwrap() + 2, 0, "+"),   // 演算子を伸ばす
+        (src.find(" + y").unwrap() + 1, 1, "--"),  // 行コメントに変えて後続を飲み込む
+        (src.find("{- 注釈").unwrap(), 2, "--"),   // ブロックコメントを行コメントに変える
+        (src.find("1 2").unwrap(), 3, "10\n  20"), // 改行を含む置換で後続の行がずれる
+        (src.find("é").unwrap(), "é".len(), "ü"),  // 文字列内の多バイト文字
+        (src.find("和").unwrap(), 0, "二つの"),    // DOC の本文を伸ばす
+        (src.len(), 0, "extra = 3;"),              // 末尾への追記
+    ];
+    for (start, removed, text) in edits {
+        let mut new_src = src.to_string();
+        new_src.replace_range(start..start + removed, text);
+        let edit = lexer::TextEdit {
+            range: start..start + removed,
+            text: text.into(),
+        };
+        let relexed = lexer::relex(&prev, &edit, &new_src).expect("relex");
+        assert_eq!(relexed, lex_ok(&new_src), "edit {edit:?}");
+    }
+
+    // 読み直しで字句エラーになる編集はエラーを返す。
+    let edit = lexer::TextEdit {
+        range: 0..0,
+        text: "\"".into(),
+    };
+    let new_src = format!("\"{src}");
+    let err = lexer::relex(&prev, &edit, &new_src).expect_err("unterminated string");
+    let full = lexer::lex(&new_src).expect_err("unterminated string");
+    assert_eq!((err.0.code, err.0.pos), (full.0.code, full.0.pos));
+}
+
+#[test]
+/// `{-# LAYOUT #-}` 指定時だけ、字下げで case アームが区切られることを検証する。
+fn layout_pragma_splits_case_arms_by_indentation() {
+    let body = "let classify n = case n of
+  0 -> \"zero\"
+  m | m > 100 -> case m of
+        1000 -> \"thousand\"
+        _ -> \"big\"
+  _ -> if n == 1 then \"one\" else \"some\"
+let pair = (case 1 of 1 -> 'a', case 2 of 2 -> 'b')
+let short x = let y = case x of 0 -> 1 in y + 1;
+";
+    let explicit = parse_program(
+        "let classify n = case n of 0 -> \"zero\"; m | m > 100 -> (case m of 1000 -> \"thousand\"; _ -> \"big\"); _ -> if n == 1 then \"one\" else \"some\";
+let pair = ((case 1 of 1 -> 'a'), (case 2 of 2 -> 'b'));
+let short x = let y = (case x of 0 -> 1) in y + 1;",
+    );
+    let layout = parse_program(&format!("{{-# LAYOUT #-}}\n{body}"));
+    assert_eq!(
+        strip_spans(&format!("{:?}", layout.decls)),
+        strip_spans(&format!("{:?}", explicit.decls))
+    );
+
+    // プラグマが無ければ従来どおり明示的な区切りが必要。
+    assert!(parser::parse_program(body).is_err());
+    // プラグマは最初のトークンより前にあるときだけ有効。
+    assert!(parser::parse_program(&format!("let a = 1;\n{{-# LAYOUT #-}}\n{body}")).is_err());
+
+    let kinds: Vec<TokenKind> = lex_ok("{-# LAYOUT #-}\ncase x of\n  A -> 1\n  B -> 2\n")
+        .into_iter()
+        .map(|t| t.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            TokenKind::CASE,
+            TokenKind::VARID,
+            TokenKind::OF,
+            TokenKind::VLBRACE,
+            TokenKind::CONID,
+            TokenKind::ARROW,
+            TokenKind::INT,
+            TokenKind::SEMI,
+            TokenKind::CONID,
+            TokenKind::ARROW,
+            TokenKind::INT,
+            TokenKind::VRBRACE,
+            TokenKind::EOF,
+        ]
+    );
+
+    // 外側のブロックより字下げされていないアームはエラーになる。
+    let err = parser::parse_program("{-# LAYOUT #-}\nlet f x = case x of\n0 -> 1\n")
+        .expect_err("arms must be indented");
+    assert!(err.to_string().contains("PAR300"), "{err}");
+}
+
+/// Debug 表記からスパンを取り除き、構造だけを比較できるようにする。
+fn strip_spans(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("Span {") {
+        out.push_str(&rest[..start]);
+        let end = rest[start..].find('}').expect("span closes") + start + 1;
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
 #[test]
 /// Unicode や非 ASCII 境界の扱いを検証する。
 fn lexer_unicode_handling() {
-    assert!(lexer::lex("let x = 1 -ー 2").is_err());
+    // 演算子は ASCII のみ。全角の記号は識別子にも演算子にもならない。
+    assert!(lexer::lex("let x = 1 − 2").is_err());
+    assert!(lexer::lex("let x = 1 ＋ 2").is_err());
     assert!(lexer::lex(r#"let f = \\x -> 'あ'"#).is_ok());
 }
 
+#[test]
+/// ギリシャ文字や漢字の識別子を受け付け、大文字で始まるものはコンストラクタになる。
+fn lexer_accepts_unicode_identifiers() {
+    let tokens =
+        lex_ok("let π = 3.14; let 面積 r = π * r * r; data Shape = Π Int; let café' = ΠΣ.ω;");
+    let idents: Vec<(TokenKind, &str)> = tokens
+        .iter()
+        .filter(|t| matches!(t.kind, TokenKind::VARID | TokenKind::CONID))
+        .map(|t| (t.kind.clone(), t.value.as_str()))
+        .collect();
+    assert_eq!(
+        idents,
+        [
+            (TokenKind::VARID, "π"),
+            (TokenKind::VARID, "面積"),
+            (TokenKind::VARID, "r"),
+            (TokenKind::VARID, "π"),
+            (TokenKind::VARID, "r"),
+            (TokenKind::VARID, "r"),
+            (TokenKind::CONID, "Shape"),
+            (TokenKind::CONID, "Π"),
+            (TokenKind::CONID, "Int"),
+            (TokenKind::VARID, "café'"),
+            (TokenKind::VARID, "ΠΣ.ω"),
+        ]
+    );
+    // 結合文字（濁点）も識別子の一部として扱う。
+    let tokens = lex_ok("let か\u{3099} = 1;");
+    assert_eq!(tokens[1].value, "か\u{3099}");
+    // キーワードは ASCII の綴りのときだけ予約語になる。
+    assert_eq!(lex_ok("ｌｅｔ")[0].kind, TokenKind::VARID);
+
+    let program = parse_program("let π = 3; let 面積 r = π * r * r; let main = 面積 2;");
+    assert_eq!(program.decls[1].name, "面積");
+    assert_eq!(program.decls[1].params, ["r"]);
+}
+
 #[test]
 /// 各種式が期待通りにパースされ文字列化できることを検証する。
 fn parser_expr_round_trips() {
@@ -128,10 +399,52 @@ fn parser_expr_round_trips() {
         },
         ExprCase {
             src: "-1",
-            fragments: &["- 1"],
+            fragments: &[],
+            exact: Some("-1"),
+            note: "負の数値リテラルへの畳み込み",
+        },
+        ExprCase {
+            src: "-x",
+            fragments: &["negate x"],
             exact: None,
             note: "単項マイナスの糖衣展開",
         },
+        ExprCase {
+            src: "case n of -1 -> 0; -0.5 -> 1; _ -> 2",
+            fragments: &["-1 ->", "-0.5 ->"],
+            exact: None,
+            note: "負の数値リテラルパターン",
+        },
+        ExprCase {
+            src: "a <+> b <+> c",
+            fragments: &[],
+            exact: Some("((a <+> b) <+> c)"),
+            note: "ユーザー定義演算子は左結合",
+        },
+        ExprCase {
+            src: "(+) 1 2",
+            fragments: &[],
+            exact: Some("(((+) 1) 2)"),
+            note: "括弧付き演算子は関数として参照できる",
+        },
+        ExprCase {
+            src: "Calc.triple Data.Maybe.Just . x",
+            fragments: &[],
+            exact: Some("((Calc.triple Data.Maybe.Just) . x)"),
+            note: "空白を挟まない Conid.name は修飾名として 1 つの識別子になる",
+        },
+        ExprCase {
+            src: "7 `div` 2",
+            fragments: &[],
+            exact: Some("((div 7) 2)"),
+            note: "バッククォートは通常の関数適用へ展開される",
+        },
+        ExprCase {
+            src: "a + b `mod` c `f` d",
+            fragments: &[],
+            exact: Some("(a + ((mod b) ((f c) d)))"),
+            note: "`mod` は infixl 7、未宣言の名前は infixl 9",
+        },
         ExprCase {
             src: "f 2 ^ 3 * 4 + 5",
             fragments: &["f 2", "^", "*", "+"],
@@ -204,6 +517,34 @@ fn parser_program_cases() {
         .expect("signature should exist");
     assert_eq!(sig.constraints.len(), 1);
     assert_eq!(sig.constraints[0].classname, "Num");
+    assert_eq!(sig.constraints[0].typevar(), Some("a"));
+
+    let structural =
+        parse_program("baz :: (Eq (Maybe a), Show [b]) => a -> b -> Bool\nlet baz x y = True");
+    let sig = structural.decls[0]
+        .signature
+        .as_ref()
+        .expect("signature should exist");
+    assert_eq!(sig.constraints.len(), 2, "構造的制約の解析");
+    assert_eq!(
+        sig.constraints[0].r#type,
+        TypeExpr::TEApp(
+            Box::new(TypeExpr::TECon("Maybe".into())),
+            Box::new(TypeExpr::TEVar("a".into()))
+        )
+    );
+    assert_eq!(
+        sig.constraints[1].r#type,
+        TypeExpr::TEList(Box::new(TypeExpr::TEVar("b".into())))
+    );
+    assert!(sig.constraints[1].typevar().is_none());
+
+    let not_context = parse_program("qux :: Maybe a -> Int\nlet qux x = 0");
+    let sig = not_context.decls[0]
+        .signature
+        .as_ref()
+        .expect("signature should exist");
+    assert!(sig.constraints.is_empty(), "型適用を制約と誤認しない");
 
     let long_string = parse_program(&format!("let s = \"{}\";", "a".repeat(5000)));
     assert_eq!(long_string.decls.len(), 1, "長い文字列の解析");
@@ -273,6 +614,107 @@ let id x = x
     assert_eq!(program.decls[0].name, "id");
 }
 
+#[test]
+/// 結合性宣言が宣言位置より前の式も含めて優先順位・結合性を変えることを検証する。
+fn parser_program_applies_fixity_declarations() {
+    let src = r#"
+let chain = a <+> b <+> c;
+let mixed = a <+> b * c;
+let cmp = a <=> b + c;
+let named = a `app` b `app` c;
+infixr 5 <+>, `app`;
+infix 4 <=>, ===;
+"#;
+    let program = parse_program(src);
+    assert_eq!(program.fixities.len(), 2);
+    assert_eq!(program.fixities[0].assoc, Associativity::Right);
+    assert_eq!(program.fixities[1].operators, vec!["<=>", "==="]);
+
+    let rendered: Vec<String> = program.decls.iter().map(|d| d.expr.to_string()).collect();
+    assert_eq!(rendered[0], "(a <+> (b <+> c))", "infixr で右結合");
+    assert_eq!(rendered[1], "(a <+> (b * c))", "* は <+> より強く結合");
+    assert_eq!(rendered[2], "(a <=> (b + c))", "infix 4 は + より弱く結合");
+    assert_eq!(
+        rendered[3], "((app a) ((app b) c))",
+        "バッククォート名にも宣言が効く"
+    );
+
+    let err = parser::parse_program("infixl 10 <+>;").expect_err("precedence out of range");
+    assert!(err.to_string().contains("[PAR540]"));
+    let err = parser::parse_program("infixl 6 foo;").expect_err("operator required");
+    assert!(err.to_string().contains("[PAR541]"));
+    let err = parser::parse_program("infix 4 <=>; let x = a <=> b <=> c;")
+        .expect_err("non-associative operators cannot be chained");
+    assert!(err.to_string().contains("[PAR"));
+}
+
+#[test]
+/// `-- |` ドキュメントコメントが直後のトップレベル定義に結び付くことを検証する。
+fn parser_program_attaches_doc_comments() {
+    let src = r#"
+-- | 値を 2 倍する。
+-- 負数にも使える。
+double :: Int -> Int;
+let double x = x * 2;
+-- 通常のコメント
+let plain = 1; -- | 行末のコメントはドキュメントにしない
+sig :: Int;
+-- | シグネチャと let の間でもよい
+let sig = 3;
+"#;
+    let program = parse_program(src);
+    let docs: Vec<Option<&str>> = program.decls.iter().map(|d| d.doc.as_deref()).collect();
+    assert_eq!(
+        docs,
+        vec![
+            Some("値を 2 倍する。\n負数にも使える。"),
+            None,
+            Some("シグネチャと let の間でもよい"),
+        ]
+    );
+}
+
+#[test]
+/// module 宣言の公開リストと修飾名の対応を検証する。
+fn parser_program_collects_module_declaration() {
+    let program = parse_program(
+        "module Calc (triple, (<+>)) where\nlet triple x = x * 3;\nlet (<+>) a b = a;\nlet hidden = 0;",
+    );
+    assert_eq!(program.modules.len(), 1);
+    assert_eq!(program.modules[0].name, "Calc");
+    assert_eq!(
+        program.qualified_aliases(),
+        vec![
+            ("Calc.triple".to_string(), "triple".to_string()),
+            ("Calc.<+>".to_string(), "<+>".to_string()),
+        ]
+    );
+
+    let open = parse_program("module Open\nlet a = 1;\nlet b = 2;");
+    assert_eq!(
+        open.qualified_aliases().len(),
+        2,
+        "公開リスト省略時は全定義を公開"
+    );
+
+    let err = parser::parse_program("module calc").expect_err("module name must be a conid");
+    assert!(err.to_string().contains("[PAR560]"));
+    let err = parser::parse_program("module A; module B;").expect_err("single module per file");
+    assert!(err.to_string().contains("[PAR561]"));
+}
+
+#[test]
+/// import 宣言がファイルパスとして収集され、文字列以外はエラーになることを検証する。
+fn parser_program_collects_imports() {
+    let program = parse_program("import \"lib/util.tl\";\nimport \"../common.tl\"\nlet x = 1;");
+    let paths: Vec<&str> = program.imports.iter().map(|i| i.path.as_str()).collect();
+    assert_eq!(paths, vec!["lib/util.tl", "../common.tl"]);
+    assert_eq!(program.decls.len(), 1);
+
+    let err = parser::parse_program("import util;").expect_err("import requires a string path");
+    assert!(err.to_string().contains("[PAR550]"));
+}
+
 #[test]
 /// class / instance 宣言のバリエーションと制約を網羅的に検証する。
 fn parser_program_handles_class_and_instance_variants() {
@@ -325,11 +767,35 @@ fn parser_program_reports_class_instance_errors() {
     assert!(rendered.contains("[PAR512]"));
 }
 
+#[test]
+/// 壊れた宣言を読み飛ばして解析を続け、複数のエラーをまとめて報告することを確認する。
+fn parser_program_recovers_and_reports_every_broken_declaration() {
+    let src = "let ok = 1;\nlet broken = (1 + ;\nlet fine = ok;\nlet also = if True then 2;\nlet last = 3;";
+    let errs = parser::parse_program(src).expect_err("two broken definitions");
+    assert_eq!(errs.0.len(), 2, "{errs}");
+    assert_eq!(errs.0[0].0.line, Some(2));
+    assert_eq!(errs.0[1].0.line, Some(4));
+    assert_eq!(errs.first().0.line, Some(2));
+    let rendered = errs.to_string();
+    assert_eq!(
+        rendered.lines().count(),
+        2,
+        "1 行に 1 件ずつ表示する: {rendered}"
+    );
+
+    // 次の宣言のキーワードでも再同期する。
+    let errs =
+        parser::parse_program("data = Oops\nlet x = ;\ndata Ok = Ok").expect_err("broken decls");
+    assert_eq!(errs.0.len(), 2, "{errs}");
+}
+
 #[test]
 /// 不正な構文が適切に弾かれることを検証する。
 fn parser_error_cases() {
     assert!(parser::parse_expr("[1,2").is_err());
     assert!(parser::parse_expr("if True then 1").is_err());
+    let err = parser::parse_expr("case n of -x -> x").expect_err("'-' requires a literal");
+    assert!(err.to_string().contains("[PAR302]"));
 
     let big = "9".repeat(50);
     let src = format!("let x = {};", big);
@@ -3,7 +3,15 @@
 // 意図: TypeLang ソースを実行ファイルへ変換し、期待した出力が得られることを確認する
 // 関連ファイル: runtime_native/tests/runtime.rs, documents/native.md, src/codegen/cranelift.rs
 
-use std::{fs, path::PathBuf, process::Command};
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read},
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
 
 use serde_json::Value;
 use tempfile::tempdir;
@@ -14,6 +22,7 @@ use typelang::{
         Parameter, ParameterKind, PrimOp, SourceRef, ValueTy, VarKind,
     },
     evaluator, infer, repl,
+    watch::FileWatcher,
 };
 
 #[cfg_attr(
@@ -84,6 +93,77 @@ let main =
     Ok(())
 }
 
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn identical_string_literals_share_one_read_only_copy() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+main :: Int;
+let main =
+  let a = println "shared literal";
+      b = println "other literal";
+      c = println "shared literal"
+  in 0;
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("string_literal_sample");
+    typelang::emit_native(&program, &output_path)?;
+
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "string literal sample failed");
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    assert_eq!(
+        stdout.lines().collect::<Vec<_>>(),
+        vec!["shared literal", "other literal", "shared literal", "0"]
+    );
+
+    // 同じ内容のリテラルは読み取り専用データ 1 つにまとまり、実行時も同じ値を共有する。
+    let binary = fs::read(&output_path)?;
+    let needle = b"shared literal";
+    let copies = binary
+        .windows(needle.len())
+        .filter(|window| window == needle)
+        .count();
+    assert_eq!(copies, 1, "literal bytes should be emitted once");
+
+    Ok(())
+}
+
+#[cfg_attr(miri, ignore = "spawns the CLI process, which Miri isolation forbids")]
+#[test]
+fn cli_build_trace_writes_message_to_stderr_only_at_run_time(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let temp = tempdir()?;
+    let input_path = temp.path().join("trace.tl");
+    let output_path = temp.path().join("trace_bin");
+    fs::write(&input_path, "main :: Int;\nlet main = trace \"msg\" 1;\n")?;
+
+    let build = Command::new(typelang_cli_path())
+        .arg("build")
+        .arg(&input_path)
+        .arg("--output")
+        .arg(&output_path)
+        .output()?;
+    assert!(
+        build.status.success(),
+        "CLI build failed: {}",
+        String::from_utf8_lossy(&build.stderr)
+    );
+    // ビルドは main を評価しないため、trace のメッセージはコンパイル時には出ない。
+    assert!(!String::from_utf8_lossy(&build.stderr).contains("msg"));
+
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "trace sample execution failed");
+    assert_eq!(String::from_utf8_lossy(&result.stderr), "msg\n");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "1");
+
+    Ok(())
+}
+
 #[cfg_attr(
     miri,
     ignore = "uses native backend and temp directories that Miri isolation forbids"
@@ -435,7 +515,7 @@ let main = ratio 7.5 2.5;
         repl::load_program_into_env(&program, &mut type_env, &mut class_env, &mut value_env)?;
         let expr = typelang::parser::parse_expr("main")?;
         match evaluator::eval_expr(&expr, &value_env)? {
-            evaluator::Value::Double(d) => d.to_string(),
+            evaluator::Value::Double(d) => runtime_common::format_double(d),
             other => panic!("unexpected interpreter result: {:?}", other),
         }
     };
@@ -461,6 +541,110 @@ let main = ratio 7.5 2.5;
     Ok(())
 }
 
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn main_taking_string_list_receives_args_and_returns_exit_code(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+main :: [String] -> Int;
+let main args = length args;
+"#;
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("count_args");
+    typelang::emit_native(&program, &output_path)?;
+
+    let result = Command::new(&output_path).args(["a", "bc", "d"]).output()?;
+    assert_eq!(result.status.code(), Some(3));
+    // 戻り値は終了コードとして使い、標準出力には表示しない。
+    assert!(result.stdout.is_empty());
+    let no_args = Command::new(&output_path).output()?;
+    assert_eq!(no_args.status.code(), Some(0));
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn main_with_parameters_is_rejected_with_clear_error() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+main :: Int -> Int;
+let main x = x + 1;
+"#;
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let err = typelang::emit_native(&program, &temp.path().join("main_with_param"))
+        .expect_err("main with parameters must be rejected");
+    match err {
+        NativeError::Unsupported { code, message } => {
+            assert_eq!(code, "CODEGEN008");
+            assert!(
+                message.contains("main must take no arguments"),
+                "unexpected message: {message}"
+            );
+        }
+        other => panic!("expected Unsupported error, got {other:?}"),
+    }
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn cli_verbose_traces_codegen_stages_and_failure_codes() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = tempdir()?;
+    let build = |name: &str, src: &str, verbose: bool| -> std::io::Result<std::process::Output> {
+        let input_path = temp.path().join(format!("{name}.tl"));
+        fs::write(&input_path, src)?;
+        let mut cmd = Command::new(typelang_cli_path());
+        cmd.arg("build")
+            .arg(&input_path)
+            .arg("--output")
+            .arg(temp.path().join(name));
+        if verbose {
+            cmd.arg("--verbose");
+        }
+        cmd.output()
+    };
+
+    let ok_src = r#"
+square :: Num a => a -> a;
+let square x = x * x;
+main :: Int;
+let main = square 4;
+"#;
+    let quiet = build("quiet", ok_src, false)?;
+    assert!(quiet.status.success());
+    assert!(!String::from_utf8_lossy(&quiet.stderr).contains("[typelang native]"));
+
+    let verbose = build("verbose", ok_src, true)?;
+    assert!(verbose.status.success());
+    let stderr = String::from_utf8_lossy(&verbose.stderr);
+    assert!(stderr.contains("entrypoint: main :: Int"), "{stderr}");
+    assert!(
+        stderr.contains("declared 2 functions: main, square"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("dictionary Num<Int> -> tl_dict_build_Num_Int"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("lowering function square"), "{stderr}");
+
+    let failing = build("failing", "main :: String;\nlet main = \"hi\";\n", true)?;
+    assert!(!failing.status.success());
+    let stderr = String::from_utf8_lossy(&failing.stderr);
+    assert!(stderr.contains("failed with CODEGEN003"), "{stderr}");
+    Ok(())
+}
+
 #[cfg_attr(
     miri,
     ignore = "uses native backend and temp directories that Miri isolation forbids"
@@ -505,6 +689,7 @@ let main = square 4;
     assert_eq!(json["optim"], "debug");
     assert_eq!(json["input"], input_path.display().to_string());
     assert_eq!(json["output"], output_path.display().to_string());
+    assert_eq!(json["object_hash"].as_str().map(str::len), Some(16));
 
     let dictionaries = json["dictionaries"]
         .as_array()
@@ -571,6 +756,275 @@ let main = foo 0;
     Ok(())
 }
 
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn dictionary_methods_dispatch_over_polymorphic_parameters(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+square :: Num a => a -> a;
+let square x = x * x;
+
+same :: Eq a => a -> a -> Bool;
+let same x y = x == y;
+
+smaller :: Ord a => a -> a -> Bool;
+let smaller x y = x < y;
+
+squareInt :: Int -> Int;
+let squareInt n = square n;
+
+pick :: Int -> Int;
+let pick n = if same (squareInt n) 49 then (if smaller n 3 then 1 else squareInt n) else 0;
+
+main :: Int;
+let main = pick 7;
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("poly_dict_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "poly dictionary sample failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "49");
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn functions_named_like_runtime_symbols_do_not_collide() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+print_int :: Int -> Int;
+let print_int n = n + 1;
+
+value_from_int :: Int -> Int;
+let value_from_int n = print_int n * 2;
+
+main :: Int;
+let main = value_from_int 20;
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("runtime_named_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "runtime-named sample failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "42");
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn repeated_builds_produce_identical_object_hash() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+double :: Num a => a -> a;
+let double x = x + x;
+
+main :: Int;
+let main = double 21;
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let first = typelang::emit_native(&program, &temp.path().join("first"))?;
+    let second = typelang::emit_native(&program, &temp.path().join("second"))?;
+    assert_eq!(first.object_hash, second.object_hash);
+
+    let other = typelang::parser::parse_program("main :: Int;\nlet main = 1;\n")?;
+    let third = typelang::emit_native(&other, &temp.path().join("third"))?;
+    assert_ne!(first.object_hash, third.object_hash);
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn list_comparisons_use_structural_dictionaries() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+same :: [Int] -> [Int] -> Bool;
+let same xs ys = xs == ys;
+
+before :: [Int] -> [Int] -> Bool;
+let before xs ys = xs < ys;
+
+main :: Int;
+let main = (if same [1, 2, 3] [1, 2, 3] then 100 else 0)
+  + (if same [1, 2] [1, 3] then 10 else 0)
+  + (if before [1, 2] [1, 2, 0] then 1 else 0);
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let ir = typelang::compile_core_ir(&program)?;
+    assert!(ir
+        .dictionaries
+        .iter()
+        .any(|dict| dict.classname == "Eq" && dict.type_repr == "[Int]"));
+    let temp = tempdir()?;
+    let output_path = temp.path().join("list_compare_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "list comparison sample failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "101");
+    Ok(())
+}
+
+#[cfg_attr(miri, ignore = "spawns the CLI process, which Miri isolation forbids")]
+#[test]
+fn cli_run_executes_main_side_effects_without_unit_output() -> Result<(), Box<dyn std::error::Error>>
+{
+    let src = r#"
+main :: ();
+let main = let a = println "first"; b = println "second" in ();
+"#;
+
+    let temp = tempdir()?;
+    let input_path = temp.path().join("unit_main.tl");
+    fs::write(&input_path, src)?;
+
+    let output = Command::new(typelang_cli_path())
+        .arg("--run")
+        .arg(&input_path)
+        .output()?;
+    assert!(
+        output.status.success(),
+        "CLI run failed: status={:?}, stderr={}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "first\nsecond\n");
+
+    Ok(())
+}
+
+#[cfg_attr(miri, ignore = "spawns the CLI process, which Miri isolation forbids")]
+#[test]
+fn cli_fmt_prints_formatted_source_without_rewriting() -> Result<(), Box<dyn std::error::Error>> {
+    let src = "type Pair = (Int, Int);\nswap :: Pair -> Pair;\nlet swap p = case p of (a, b) -> (b, a);\nlet main=fst(swap (1,2));\n";
+
+    let temp = tempdir()?;
+    let input_path = temp.path().join("messy.tl");
+    fs::write(&input_path, src)?;
+
+    let output = Command::new(typelang_cli_path())
+        .arg("--fmt")
+        .arg(&input_path)
+        .output()?;
+    assert!(
+        output.status.success(),
+        "CLI fmt failed: status={:?}, stderr={}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "type Pair = (Int, Int);\n\nswap :: Pair -> Pair;\nlet swap p = (case p of (a, b) -> (b, a));\n\nlet main = fst (swap (1, 2));\n"
+    );
+    assert_eq!(fs::read_to_string(&input_path)?, src);
+
+    Ok(())
+}
+
+#[cfg_attr(miri, ignore = "touches the file system, which Miri isolation forbids")]
+#[test]
+fn file_watcher_reports_modifications_and_removal() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = tempdir()?;
+    let path = temp.path().join("watched.tl");
+    fs::write(&path, "let main = 1;")?;
+
+    let mut watcher = FileWatcher::new(&path);
+    assert!(
+        watcher.poll_changed(),
+        "the first poll always triggers a build"
+    );
+    assert!(!watcher.poll_changed());
+
+    // 更新時刻の分解能が粗いファイルシステムでも差が出るよう、少し待ってから書き換える。
+    thread::sleep(Duration::from_millis(1100));
+    fs::write(&path, "let main = 2;")?;
+    assert!(watcher.poll_changed());
+    assert!(!watcher.poll_changed());
+
+    fs::remove_file(&path)?;
+    assert!(watcher.poll_changed());
+    assert!(!watcher.poll_changed());
+    Ok(())
+}
+
+#[cfg_attr(miri, ignore = "spawns the CLI process, which Miri isolation forbids")]
+#[test]
+fn cli_build_watch_rebuilds_after_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = tempdir()?;
+    let input_path = temp.path().join("watched.tl");
+    let output_path = temp.path().join("watched_bin");
+    fs::write(&input_path, "main :: Int;\nlet main = 1 + 2;\n")?;
+
+    let mut child = Command::new(typelang_cli_path())
+        .arg("build")
+        .arg(&input_path)
+        .arg("--output")
+        .arg(&output_path)
+        .arg("--watch")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let (tx, rx) = mpsc::channel();
+    for stream in [
+        Box::new(child.stdout.take().unwrap()) as Box<dyn Read + Send>,
+        Box::new(child.stderr.take().unwrap()),
+    ] {
+        let tx = tx.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                let _ = tx.send(line);
+            }
+        });
+    }
+    let wait_for = |fragment: &str| -> Result<(), String> {
+        loop {
+            match rx.recv_timeout(Duration::from_secs(60)) {
+                Ok(line) if line.contains(fragment) => return Ok(()),
+                Ok(_) => {}
+                Err(e) => return Err(format!("`{fragment}` が出力されませんでした: {e}")),
+            }
+        }
+    };
+
+    let result = (|| -> Result<(), Box<dyn std::error::Error>> {
+        wait_for("ビルド成功")?;
+        assert!(output_path.exists());
+
+        thread::sleep(Duration::from_millis(1100));
+        fs::write(&input_path, "let main = ;\n")?;
+        wait_for("エラー")?;
+        assert!(
+            child.try_wait()?.is_none(),
+            "watch mode must survive build errors"
+        );
+
+        thread::sleep(Duration::from_millis(1100));
+        fs::write(&input_path, "main :: Int;\nlet main = 4;\n")?;
+        wait_for("ビルド成功")?;
+        let run = Command::new(&output_path).output()?;
+        assert_eq!(String::from_utf8_lossy(&run.stdout).trim(), "4");
+        Ok(())
+    })();
+    child.kill()?;
+    child.wait()?;
+    result
+}
+
 fn typelang_cli_path() -> PathBuf {
     const CANDIDATES: [&str; 3] = [
         "CARGO_BIN_EXE_typelang",
@@ -607,29 +1061,99 @@ fn mk_dict_param(name: &str, classname: &str, type_repr: &str, value_ty: ValueTy
     )
 }
 
-fn mk_value_param(name: &str, ty: ValueTy) -> Parameter {
-    Parameter::with_kind(name.to_string(), ty, ParameterKind::Value, None, None)
-}
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_unboxes_polymorphic_if_condition() -> Result<(), Box<dyn std::error::Error>> {
+    // `pickFirst` は辞書を受け取るため展開されず、戻り値は箱詰めの `Unknown` のまま条件に渡る。
+    let src = r#"
+pickFirst :: Eq a => a -> a -> a;
+let pickFirst x y = if x == y then x else y;
 
-fn mk_dict_method(name: &str, signature: &str, symbol: &str, method_id: u64) -> DictionaryMethod {
-    DictionaryMethod {
-        name: name.to_string(),
-        signature: Some(signature.to_string()),
-        symbol: symbol.to_string(),
-        method_id,
-    }
-}
+main :: Int;
+let main = if pickFirst True False then 1 else 2;
+"#;
 
-fn mk_dict_placeholder(classname: &str, type_repr: &str) -> Expr {
-    let class_name = classname.to_string();
-    Expr::DictionaryPlaceholder {
-        classname: class_name.clone(),
-        type_repr: type_repr.into(),
-        ty: ValueTy::Dictionary {
-            classname: class_name,
-        },
-    }
-}
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("unknown_condition_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "unknown condition sample failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "2");
+
+    let interpreted = typelang::eval_program(&program)?;
+    assert!(matches!(interpreted, evaluator::Value::Int(2)));
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn dictionary_typed_if_condition_reports_expression_and_type(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let main_fn = Function {
+        name: "main".into(),
+        params: Vec::new(),
+        result: ValueTy::Int,
+        body: Expr::If {
+            cond: Box::new(mk_dict_placeholder("Num", "Int")),
+            then_branch: Box::new(Expr::Literal {
+                value: Literal::Int(1),
+                ty: ValueTy::Int,
+            }),
+            else_branch: Box::new(Expr::Literal {
+                value: Literal::Int(2),
+                ty: ValueTy::Int,
+            }),
+            ty: ValueTy::Int,
+        },
+        location: SourceRef::default(),
+    };
+    let module = mk_module_with_entry("main", vec![main_fn], Vec::new());
+
+    let temp = tempdir()?;
+    let output = temp.path().join("dict_condition_module");
+    let err = typelang::codegen::cranelift::emit_native(&module, &output)
+        .expect_err("dictionary-typed condition must be rejected");
+    match err {
+        NativeError::Unsupported { code, message } => {
+            assert_eq!(code, "CODEGEN080");
+            assert!(message.contains("辞書 Num Int"), "{message}");
+            assert!(message.contains("Dict<Num>"), "{message}");
+        }
+        other => panic!("expected Unsupported error, got {other:?}"),
+    }
+    Ok(())
+}
+
+fn mk_value_param(name: &str, ty: ValueTy) -> Parameter {
+    Parameter::with_kind(name.to_string(), ty, ParameterKind::Value, None, None)
+}
+
+fn mk_dict_method(name: &str, signature: &str, symbol: &str, method_id: u64) -> DictionaryMethod {
+    DictionaryMethod {
+        name: name.to_string(),
+        signature: Some(signature.to_string()),
+        symbol: symbol.to_string(),
+        method_id,
+    }
+}
+
+fn mk_dict_placeholder(classname: &str, type_repr: &str) -> Expr {
+    let class_name = classname.to_string();
+    Expr::DictionaryPlaceholder {
+        classname: class_name.clone(),
+        type_repr: type_repr.into(),
+        ty: ValueTy::Dictionary {
+            classname: class_name,
+        },
+    }
+}
 
 fn mk_module_with_entry(
     entry: &str,
@@ -644,3 +1168,1058 @@ fn mk_module_with_entry(
     module.dictionaries.extend(dictionaries);
     module
 }
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_newtype_wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+newtype Meters = Meters Int;
+
+unwrap :: Meters -> Int;
+let unwrap m = (case m of Meters n -> n);
+
+double :: Meters -> Meters;
+let double m = (case m of Meters n -> Meters (n * 2));
+
+main :: Int;
+let main = unwrap (double (Meters 21));
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let ir = typelang::compile_core_ir(&program)?;
+    assert!(ir.data_layouts["Meters"].newtype);
+    assert_eq!(ir.functions["double"].params[0].ty, ValueTy::Int);
+    assert_eq!(ir.functions["double"].result, ValueTy::Int);
+
+    let temp = tempdir()?;
+    let output_path = temp.path().join("newtype_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "newtype sample execution failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "42");
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_as_pattern() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+data Slot = Empty | Full Int;
+
+valueOr :: Slot -> Int;
+let valueOr s = (case s of Empty -> 0; Full n -> n);
+
+describe :: Slot -> Int;
+let describe s = (case s of Empty -> 0; whole@(Full x) -> x * 10 + valueOr whole);
+
+main :: Int;
+let main = describe (Full 4);
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let ir = typelang::compile_core_ir(&program)?;
+    let typelang::core_ir::Expr::Match { arms, .. } = &ir.functions["describe"].body else {
+        panic!("describe should lower to a match");
+    };
+    assert_eq!(arms[1].constructor.as_deref(), Some("Full"));
+    assert!(arms[1].tag.is_some());
+    let paths: Vec<_> = arms[1]
+        .bindings
+        .iter()
+        .map(|b| (b.name.as_str(), b.path.clone()))
+        .collect();
+    assert_eq!(paths, vec![("whole", vec![]), ("x", vec![0])]);
+
+    let temp = tempdir()?;
+    let output_path = temp.path().join("as_pattern_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(
+        result.status.success(),
+        "as-pattern sample execution failed"
+    );
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "44");
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_passing_pointer_values_through_polymorphic_function(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+data Slot = Empty | Full Int;
+
+ident :: a -> a;
+let ident x = x;
+
+wrapped :: [Int];
+let wrapped = ident [1, 2, 3];
+
+valueOr :: Slot -> Int;
+let valueOr s = (case s of Empty -> 0; Full n -> n);
+
+main :: Int;
+let main = valueOr (ident (Full 5));
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("pointer_unknown_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(
+        result.status.success(),
+        "pointer round-trip sample execution failed"
+    );
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "5");
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_empty_list_literals() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+none :: [Int];
+let none = [];
+
+typed :: [Int];
+let typed = ([] :: [Int]);
+
+main :: Int;
+let main = 3;
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("empty_list_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(
+        result.status.success(),
+        "empty list sample execution failed"
+    );
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "3");
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_negative_and_float_patterns() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+sign :: Int -> Int;
+let sign n = (case n of -1 -> 10; 0 -> 20; k | k > 0 -> 30; _ -> 40);
+
+half :: Double -> Int;
+let half d = (case d of -0.5 -> 1; 0.5 -> 2; _ -> 3);
+
+main :: Int;
+let main = sign (-1) + sign 0 + sign 7 + sign (-9) + half (-0.5) * 1000 + half 0.5 * 2000 + half 1.0 * 5000;
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("literal_pattern_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(
+        result.status.success(),
+        "literal pattern sample execution failed"
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&result.stdout).trim(),
+        (10 + 20 + 30 + 40 + 1000 + 4000 + 15000).to_string()
+    );
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_user_defined_operator() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+(<+>) :: Int -> Int -> Int;
+let (<+>) a b = a * 10 + b;
+
+main :: Int;
+let main = 1 <+> 2 <+> 3;
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("operator_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "operator sample execution failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "123");
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_qualified_module_names() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+module Calc (triple);
+
+triple :: Int -> Int;
+let triple x = x * 3;
+
+main :: Int;
+let main = let triple = 0 in Calc.triple 14 + triple;
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("module_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "module sample execution failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "42");
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_checked_arithmetic_aborts_on_overflow(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+bump :: Int -> Int;
+let bump x = x + 1;
+
+main :: Int;
+let main = bump 9223372036854775807;
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+
+    let wrapping_path = temp.path().join("wrapping_sample");
+    typelang::emit_native(&program, &wrapping_path)?;
+    let wrapped = Command::new(&wrapping_path).output()?;
+    assert!(wrapped.status.success(), "wrapping sample execution failed");
+    assert_eq!(
+        String::from_utf8_lossy(&wrapped.stdout).trim(),
+        i64::MIN.to_string()
+    );
+
+    let checked_path = temp.path().join("checked_sample");
+    typelang::emit_native_with_options(
+        &program,
+        &checked_path,
+        &typelang::NativeBuildOptions {
+            arithmetic: typelang::NativeArithmetic::Checked,
+            ..Default::default()
+        },
+    )?;
+    let checked = Command::new(&checked_path).output()?;
+    assert!(!checked.status.success(), "overflow must abort");
+    assert!(String::from_utf8_lossy(&checked.stderr).contains("code=2002"));
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_default_prelude() -> Result<(), Box<dyn std::error::Error>> {
+    // `flip` は関数を引数に取るためネイティブ生成できないが、使わなければ取り込まれない。
+    let src = r#"
+pick :: Int -> Int -> Int;
+let pick x y = const (id x) y;
+
+main :: Int;
+let main = pick 40 7 + 2;
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let prelude = typelang::default_prelude();
+    let temp = tempdir()?;
+    let output_path = temp.path().join("prelude_sample");
+    typelang::emit_native_with_options(
+        &program,
+        &output_path,
+        &typelang::NativeBuildOptions {
+            prelude: Some(&prelude),
+            ..Default::default()
+        },
+    )?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "prelude sample execution failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "42");
+
+    let merged = typelang::prelude::with_prelude(&program, &prelude);
+    let interpreted = typelang::eval_program(&merged)?;
+    assert!(matches!(interpreted, evaluator::Value::Int(42)));
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_arena_allocation() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+data Slot = Empty | Full Int;
+
+valueOr :: Slot -> Int;
+let valueOr s = (case s of Empty -> 0; Full n -> n);
+
+total :: Int -> Int;
+let total n = if n == 0 then 0 else valueOr (Full n) + total (n - 1);
+
+main :: Int;
+let main = total 100;
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("arena_sample");
+    typelang::emit_native_with_options(
+        &program,
+        &output_path,
+        &typelang::NativeBuildOptions {
+            allocation: typelang::NativeAllocation::Arena,
+            ..Default::default()
+        },
+    )?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "arena sample execution failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "5050");
+
+    // アリーナ版は既定のランタイムを上書きしないよう専用の出力先へビルドされる。
+    let target_dir = std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target"));
+    assert!(target_dir
+        .join("runtime-arena/release/libruntime_native.a")
+        .exists());
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn call_depth_limit_aborts_deep_recursion() -> Result<(), Box<dyn std::error::Error>> {
+    let build = |depth: i64, path: &std::path::Path| -> Result<(), Box<dyn std::error::Error>> {
+        let src = format!(
+            "count :: Int -> Int;\nlet count n = if n == 0 then 0 else 1 + count (n - 1);\n\nmain :: Int;\nlet main = count {depth};\n"
+        );
+        let program = typelang::parser::parse_program(&src)?;
+        typelang::emit_native_with_options(
+            &program,
+            path,
+            &typelang::NativeBuildOptions {
+                call_depth: typelang::NativeCallDepth::Limit(10),
+                ..Default::default()
+            },
+        )?;
+        Ok(())
+    };
+    let temp = tempdir()?;
+
+    let shallow_path = temp.path().join("shallow_sample");
+    build(5, &shallow_path)?;
+    let shallow = Command::new(&shallow_path).output()?;
+    assert!(shallow.status.success(), "shallow recursion must succeed");
+    assert_eq!(String::from_utf8_lossy(&shallow.stdout).trim(), "5");
+
+    let deep_path = temp.path().join("deep_sample");
+    build(40, &deep_path)?;
+    let deep = Command::new(&deep_path).output()?;
+    assert!(!deep.status.success(), "deep recursion must abort");
+    let stderr = String::from_utf8_lossy(&deep.stderr);
+    assert!(stderr.contains("stack overflow"), "stderr: {stderr}");
+    assert!(stderr.contains("code=2004"), "stderr: {stderr}");
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_bitwise_operators() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+mask :: Int -> Int;
+let mask x = (x .&. 240) .|. shiftR x 8;
+
+main :: Int;
+let main = xor (mask 4660) (shiftL 1 2);
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("bits_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "bits sample execution failed");
+    let expected = ((4660 & 240) | (4660 >> 8)) ^ (1 << 2);
+    assert_eq!(
+        String::from_utf8_lossy(&result.stdout).trim(),
+        expected.to_string()
+    );
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_math_intrinsics() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+main :: Double;
+let main = sqrt 2.0 + log (exp 1.0) + sin 0.0 * cos 0.0;
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("math_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "math sample execution failed");
+    let expected = 2f64.sqrt() + 1f64.exp().ln() + 0f64.sin() * 0f64.cos();
+    assert_eq!(
+        String::from_utf8_lossy(&result.stdout).trim(),
+        runtime_common::format_double(expected)
+    );
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_array_indexing_matches_interpreter() -> Result<(), Box<dyn std::error::Error>>
+{
+    let src = r#"
+total :: Array Int -> Int -> Int -> Int;
+let total xs i n = if i == n then 0 else xs ! i + total xs (i + 1) n;
+
+main :: Int;
+let main = let xs = fromList [3, 5, 7, 11] in total xs 0 4 * 10 + xs ! 3;
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("array_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "array sample execution failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "271");
+
+    let interpreted = typelang::eval_program(&program)?;
+    assert!(matches!(interpreted, evaluator::Value::Int(271)));
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_map_builtins_matches_interpreter() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+find :: Int -> Map Int Int -> Int;
+let find k m = findWithDefault 0 k m;
+
+main :: Int;
+let main =
+  let table = insert 2 20 (insert 1 10 emptyMap) in
+  let updated = insert 1 11 table in
+  let found = (case lookup 2 updated of Just v -> v; Nothing -> 0) in
+  let missing = (case lookup 3 updated of Just v -> v; Nothing -> 7000) in
+  find 1 updated * 100 + find 1 table + found + find 3 updated + missing
+    + findWithDefault 0 [1, 2] (insert [1, 2] 5000 emptyMap);
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("map_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "map sample execution failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "13130");
+
+    let interpreted = typelang::eval_program(&program)?;
+    assert!(matches!(interpreted, evaluator::Value::Int(13130)));
+
+    // プログラムが宣言し直した Maybe では、構築子のタグも宣言の順に従う。
+    let redeclared = typelang::parser::parse_program(
+        r#"
+data Maybe a = Just a | Nothing;
+
+main :: Int;
+let main =
+  let table = insert 1 10 emptyMap in
+  (case lookup 1 table of Just v -> v; Nothing -> 0)
+    + (case lookup 2 table of Just v -> v; Nothing -> 100);
+"#,
+    )?;
+    let redeclared_path = temp.path().join("map_maybe_sample");
+    typelang::emit_native(&redeclared, &redeclared_path)?;
+    let result = Command::new(&redeclared_path).output()?;
+    assert!(result.status.success(), "redeclared Maybe sample failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "110");
+    assert!(matches!(
+        typelang::eval_program(&redeclared)?,
+        evaluator::Value::Int(110)
+    ));
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_replicate_take_drop_matches_interpreter(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+main :: Int;
+let main =
+  length (take 3 (replicate 5 7)) * 1000
+    + length (drop 2 [1, 2, 3, 4]) * 100
+    + length (take (-1) [1]) + length (drop 9 [1, 2]) + length (replicate (-2) 0)
+    + fromList (drop 1 (take 3 [7, 8, 9, 10])) ! 1 * 10 + fromList (replicate 2 4) ! 0;
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("list_build_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(
+        result.status.success(),
+        "list build sample execution failed"
+    );
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "3294");
+
+    let interpreted = typelang::eval_program(&program)?;
+    assert!(matches!(interpreted, evaluator::Value::Int(3294)));
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_list_and_string_append_matches_interpreter(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+main :: Int;
+let main =
+  let xs = [1, 2] <> [] <> [3];
+      greeting = println ("ab" <> "" <> "cd")
+  in length xs * 100 + fromList (xs <> take 1 xs) ! 3 * 10 + length (drop 1 xs <> []);
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("append_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "append sample execution failed");
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        vec!["abcd", "312"],
+        "unexpected append output: {stdout}"
+    );
+
+    let interpreted = typelang::eval_program(&program)?;
+    assert!(matches!(interpreted, evaluator::Value::Int(312)));
+
+    // リストと String の表現はネイティブでは異なるため、混ぜた連結は専用のコードで拒否する。
+    let mixed = typelang::parser::parse_program(
+        "main :: Int;\nlet main = let s = println (\"b\" <> []) in 0;\n",
+    )?;
+    let err = typelang::emit_native(&mixed, &temp.path().join("mixed_append"))
+        .expect_err("mixed append must be rejected");
+    match err {
+        NativeError::Unsupported { code, .. } => assert_eq!(code, "CODEGEN218"),
+        other => panic!("expected Unsupported error, got {other:?}"),
+    }
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_read_number_matches_interpreter() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+main :: Int;
+let main =
+  (case readInt " 42 " of Just n -> n; Nothing -> 0)
+    + (case readInt "4x" of Just n -> n; Nothing -> 100)
+    + (case readDouble "0.5" of Just d -> round (d * 2000.0); Nothing -> 0);
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("read_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "read sample execution failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "1142");
+
+    let interpreted = typelang::eval_program(&program)?;
+    assert!(matches!(interpreted, evaluator::Value::Int(1142)));
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_format_matches_interpreter() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+label :: Int -> String;
+let label n = format "%s #%d" "item" n;
+
+main :: Int;
+let main =
+  let first = println (format "%d + %.2f = %f (100%%)" 1 2.5 3.5);
+      second = println (label 7)
+  in 0;
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("format_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "format sample execution failed");
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        vec!["1 + 2.50 = 3.5 (100%)", "item #7", "0"],
+        "unexpected format output: {stdout}"
+    );
+
+    // 書式の指定子より少ない引数で使う（部分適用する）と専用のコードで拒否する。
+    let partial = typelang::parser::parse_program(
+        "main :: Int;\nlet main = let f = format \"%d-%d\" 1 in 0;\n",
+    )?;
+    let err = typelang::emit_native(&partial, &temp.path().join("partial_format"))
+        .expect_err("partial format must be rejected");
+    match err {
+        NativeError::Unsupported { code, .. } => assert_eq!(code, "COREIR171"),
+        other => panic!("expected Unsupported error, got {other:?}"),
+    }
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_strict_left_fold_matches_interpreter(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+add :: Int -> Int -> Int;
+let add a b = a + b;
+
+sumSquares :: [Int] -> Int;
+let sumSquares xs = foldl' (\acc x -> acc + x * x) 0 xs;
+
+main :: Int;
+let main = foldl' (+) 0 (replicate 1000000 1) + foldl' add 0 [1, 2, 3] * 10
+  + sumSquares [1, 2] * 1000 + foldl' (-) 7 ([] :: [Int]);
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("fold_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "fold sample execution failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "1005067");
+
+    let interpreted = typelang::eval_program(&program)?;
+    assert!(matches!(interpreted, evaluator::Value::Int(1005067)));
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn self_tail_calls_run_as_loops_without_growing_the_stack() -> Result<(), Box<dyn std::error::Error>>
+{
+    let src = r#"
+countdown :: Int -> Int -> Int;
+let countdown n acc =
+  if n == 0 then acc
+  else let next = n - 1 in if next > 5 then countdown next (acc + 1) else countdown next (acc + 2);
+
+main :: [String] -> Int;
+let main args = if countdown 50000000 0 == 50000006 then 0 else 1;
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("tail_loop_sample");
+    // 呼び出しの深さを 10 に制限しても上限に届かないことを確かめる。
+    typelang::emit_native_with_options(
+        &program,
+        &output_path,
+        &typelang::NativeBuildOptions {
+            call_depth: typelang::NativeCallDepth::Limit(10),
+            ..Default::default()
+        },
+    )?;
+    let result = Command::new(&output_path).output()?;
+    assert!(
+        result.status.success(),
+        "tail loop sample execution failed: {}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+    Ok(())
+}
+
+#[cfg_attr(miri, ignore = "spawns the CLI process, which Miri isolation forbids")]
+#[test]
+fn cli_build_runs_constant_tail_loop_without_evaluating_it_at_compile_time(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+loop :: Int -> Int -> Int;
+let loop n acc = if n == 0 then acc else loop (n - 1) (acc + 1);
+
+main :: Int;
+let main = loop 1000000 0;
+"#;
+
+    let temp = tempdir()?;
+    let input_path = temp.path().join("constant_loop.tl");
+    let output_path = temp.path().join("constant_loop_bin");
+    fs::write(&input_path, src)?;
+
+    let build = Command::new(typelang_cli_path())
+        .arg("build")
+        .arg(&input_path)
+        .arg("--output")
+        .arg(&output_path)
+        .output()?;
+    assert!(
+        build.status.success(),
+        "CLI build failed: {}",
+        String::from_utf8_lossy(&build.stderr)
+    );
+
+    let result = Command::new(&output_path).output()?;
+    assert!(
+        result.status.success(),
+        "constant loop execution failed: {}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "1000000");
+
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_calling_polymorphic_function_in_loop_matches_interpreter(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+step :: Num a => a -> a -> a;
+let step acc x = acc + x * x;
+
+count :: Int -> Int -> Int;
+let count n acc = if n == 0 then acc else count (n - 1) (step acc n);
+
+main :: Int;
+let main = count 100 0;
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("dict_loop_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "dict loop sample execution failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "338350");
+
+    let interpreted = typelang::eval_program(&program)?;
+    assert!(matches!(interpreted, evaluator::Value::Int(338350)));
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_ref_counter_matches_interpreter() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+loop :: Ref Int -> Int -> Int;
+let loop r n = if n == 0 then readRef r else let u = writeRef r (readRef r + n) in loop r (n - 1);
+
+main :: Int;
+let main = let r = newRef 0 in loop r 10 + readRef r;
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("ref_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "ref sample execution failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "110");
+
+    let interpreted = typelang::eval_program(&program)?;
+    assert!(matches!(interpreted, evaluator::Value::Int(110)));
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_short_circuits_boolean_operators() -> Result<(), Box<dyn std::error::Error>> {
+    // 右辺を評価すると範囲外の添字で中断するため、短絡しなければ実行に失敗する。
+    let src = r#"
+flag :: Bool -> Int;
+let flag b = if b then 1 else 0;
+
+outOfRange :: Int -> Bool;
+let outOfRange n = fromList [n] ! 3 == n;
+
+main :: Int;
+let main = flag (False && outOfRange 1) * 100 + flag (True || outOfRange 2) * 10 + flag (1 < 2 && 2 < 3);
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("short_circuit_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(
+        result.status.success(),
+        "short-circuit sample execution failed: {}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "11");
+
+    let interpreted = typelang::eval_program(&program)?;
+    assert!(matches!(interpreted, evaluator::Value::Int(11)));
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_min_max_matches_interpreter() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+larger :: Ord a => a -> a -> a;
+let larger x y = max x y;
+
+flag :: Bool -> Int;
+let flag b = if b then 1 else 0;
+
+pick :: Int -> Int -> Int;
+let pick x y = larger x y;
+
+main :: Int;
+let main = max 3 7 * 100 + pick 2 9 * 10 + flag (min 'a' 'z' == 'a');
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("min_max_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "min/max sample execution failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "791");
+
+    let interpreted = typelang::eval_program(&program)?;
+    assert!(matches!(interpreted, evaluator::Value::Int(791)));
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_clamp_between_matches_interpreter() -> Result<(), Box<dyn std::error::Error>>
+{
+    let src = r#"
+inRange :: Ord a => a -> a -> a -> Bool;
+let inRange lo hi x = between lo hi x;
+
+flag :: Bool -> Int;
+let flag b = if b then 1 else 0;
+
+main :: Int;
+let main = clamp 0 10 15 * 1000 + clamp 0 10 (0 - 3) * 100
+  + flag (between 1 5 3) * 10 + flag (inRange 1 5 9);
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("clamp_between_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(
+        result.status.success(),
+        "clamp/between sample execution failed"
+    );
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "10010");
+
+    let interpreted = typelang::eval_program(&program)?;
+    assert!(matches!(interpreted, evaluator::Value::Int(10010)));
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_null_predicate_matches_interpreter() -> Result<(), Box<dyn std::error::Error>>
+{
+    let src = r#"
+flag :: Bool -> Int;
+let flag b = if b then 1 else 0;
+
+main :: Int;
+let main = flag (null []) * 10 + flag (null [1]);
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("null_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "null sample execution failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "10");
+
+    let interpreted = typelang::eval_program(&program)?;
+    assert!(matches!(interpreted, evaluator::Value::Int(10)));
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn build_program_with_rounding_conversions() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+main :: Int;
+let main = floor 2.7 * 1000 + ceiling 2.1 * 100 + round 2.5 * 10 + truncate (fromIntegral 7 / 2.0);
+"#;
+
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let output_path = temp.path().join("rounding_sample");
+    typelang::emit_native(&program, &output_path)?;
+    let result = Command::new(&output_path).output()?;
+    assert!(result.status.success(), "rounding sample execution failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "2323");
+
+    // NaN・無限大・Int の範囲外はハードウェア例外にせず、評価器の EVAL060 と同じく中断する。
+    for (name, expr) in [
+        ("nan", "truncate (sqrt (0.0 - 1.0))"),
+        ("inf", "floor (1.0 / 0.0)"),
+        ("huge", "round 1.0e19"),
+    ] {
+        let src = format!("main :: Int;\nlet main = {expr};\n");
+        let program = typelang::parser::parse_program(&src)?;
+        let output_path = temp.path().join(format!("rounding_{name}"));
+        typelang::emit_native(&program, &output_path)?;
+        let result = Command::new(&output_path).output()?;
+        assert_eq!(result.status.code(), Some(1), "{expr} should abort");
+        assert!(
+            String::from_utf8_lossy(&result.stderr).contains("code=2006"),
+            "{expr}: {}",
+            String::from_utf8_lossy(&result.stderr)
+        );
+        let err = typelang::eval_program(&program).expect_err("evaluator rejects it too");
+        assert!(err.to_string().contains("EVAL060"), "{expr}: {err}");
+    }
+    Ok(())
+}
+
+#[cfg_attr(
+    miri,
+    ignore = "uses native backend and temp directories that Miri isolation forbids"
+)]
+#[test]
+fn custom_entry_point_name_is_compiled_as_main() -> Result<(), Box<dyn std::error::Error>> {
+    let src = r#"
+start :: Int;
+let start = 42;
+"#;
+    let program = typelang::parser::parse_program(src)?;
+    let temp = tempdir()?;
+    let build = |entry: &str, path: &std::path::Path| {
+        typelang::emit_native_with_options(
+            &program,
+            path,
+            &typelang::NativeBuildOptions {
+                entry: entry.into(),
+                ..Default::default()
+            },
+        )
+        .map_err(Box::new)
+    };
+
+    let start_path = temp.path().join("start_entry");
+    build("start", &start_path)?;
+    let result = Command::new(&start_path).output()?;
+    assert!(result.status.success(), "start entry execution failed");
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "42");
+
+    match build(typelang::DEFAULT_ENTRY, &temp.path().join("missing_main")).map_err(|e| *e) {
+        Err(NativeError::Unsupported { code, message }) => {
+            assert_eq!(code, "CODEGEN001");
+            assert!(message.contains("(main)"), "unexpected message: {message}");
+        }
+        other => panic!("expected missing entry error, got {other:?}"),
+    }
+    Ok(())
+}
@@ -98,6 +98,21 @@ fn inference_type_strings() {
             expected: "Integer",
             note: "div は整数を返す",
         },
+        Case {
+            src: "\\x -> shiftL (x .&. 255) 2",
+            expected: "Int -> Int",
+            note: "ビット演算は Int に固定",
+        },
+        Case {
+            src: "\\x -> sqrt (x * 2.0)",
+            expected: "Double -> Double",
+            note: "数学関数は Double に固定",
+        },
+        Case {
+            src: "\\x -> fromIntegral (round x)",
+            expected: "Double -> Double",
+            note: "丸めと fromIntegral による明示的な変換",
+        },
         Case {
             src: "case True of True -> False; False -> True",
             expected: "Bool",
@@ -108,11 +123,81 @@ fn inference_type_strings() {
             expected: "Num a => a",
             note: "リストパターンと数値制約",
         },
+        Case {
+            src: "\\n -> case n of -1 -> True; _ -> False",
+            expected: "Num a => a -> Bool",
+            note: "整数リテラルパターンは Num 制約を課す",
+        },
         Case {
             src: "\\x -> println x",
             expected: "Show a => a -> [Char]",
             note: "println は Show 制約を共有",
         },
+        Case {
+            src: "fmap",
+            expected: "Functor c => (a -> b) -> c a -> c b",
+            note: "fmap は型構築子の位置に型変数を持つ",
+        },
+        Case {
+            src: "fmap (\\c -> c == 'a') \"ab\"",
+            expected: "[Bool]",
+            note: "文字列はリストの Functor として写せる",
+        },
+        Case {
+            src: "concatMap",
+            expected: "(a -> [b]) -> [a] -> [b]",
+            note: "concatMap は要素ごとのリストを連結する",
+        },
+        Case {
+            src: "\\xs -> intercalate [0] xs",
+            expected: "Num a => [[a]] -> [a]",
+            note: "intercalate は区切りと要素の型をそろえる",
+        },
+        Case {
+            src: "format \"%s: %d (%.1f%%)\"",
+            expected: "[Char] -> Int -> Double -> [Char]",
+            note: "format の引数の型は書式の指定子から決まる",
+        },
+        Case {
+            src: "format \"%d + %d\" 1 2",
+            expected: "[Char]",
+            note: "数値リテラルは %d で Int に定まる",
+        },
+        Case {
+            src: "\\xs -> foldl' (\\n x -> n + length x) 0 xs",
+            expected: "[[a]] -> Int",
+            note: "foldl' の累積値は関数の結果型と初期値の型をそろえる",
+        },
+        Case {
+            src: "clamp",
+            expected: "Ord a => a -> a -> a -> a",
+            note: "clamp は 3 つの引数を Ord で比較する",
+        },
+        Case {
+            src: "\\x -> between 'a' 'z' x",
+            expected: "Char -> Bool",
+            note: "between は範囲の端と値の型をそろえる",
+        },
+        Case {
+            src: "\\n -> take n [n..]",
+            expected: "Int -> [Int]",
+            note: "take の第 2 引数に置いた [x..] は Int の範囲になる",
+        },
+        Case {
+            src: "\\k -> lookup k (insert 1 'a' emptyMap)",
+            expected: "Eq a, Num a => a -> Maybe Char",
+            note: "lookup は組み込みの Maybe で結果を返す",
+        },
+        Case {
+            src: "\\s -> case readInt s of Just n -> n; Nothing -> 0",
+            expected: "[Char] -> Int",
+            note: "readInt の結果は Just / Nothing で分解できる",
+        },
+        Case {
+            src: "stripPrefix \"a\" \"ab\" == Just \"b\"",
+            expected: "Bool",
+            note: "stripPrefix の結果は Maybe として比較できる",
+        },
     ];
 
     for case in cases {
@@ -130,6 +215,79 @@ fn inference_defaulting_behaviour() {
     assert!(defaulted == "String" || defaulted == "[Char]");
 }
 
+#[test]
+/// 公開 API `infer_qual_type` が制約付きの QualType と既定化結果を返すことを確認する。
+fn infer_qual_type_returns_full_qual_type() {
+    let env = typelang::initial_type_env();
+    let ce = typelang::initial_class_env();
+    let expr = support::parse_expr("\\x -> x + 1");
+
+    let qual = typelang::infer::infer_qual_type(&env, &ce, &expr, false).expect("infer");
+    assert!(!qual.constraints.is_empty());
+    assert!(qual.constraints.iter().all(|c| c.classname == "Num"));
+    assert_eq!(pretty_qual(&qual), "Num a => a -> a");
+
+    let literal = support::parse_expr("1 + 2");
+    let defaulted = typelang::infer::infer_qual_type(&env, &ce, &literal, true).expect("infer");
+    assert_eq!(pretty_qual(&defaulted), "Integer");
+
+    let err = typelang::infer::infer_qual_type(&env, &ce, &support::parse_expr("foo"), true);
+    assert!(err.is_err());
+}
+
+#[test]
+/// 上位クラスから導ける制約と重複した制約が表示前に取り除かれることを確認する。
+fn infer_qual_type_reduces_entailed_constraints() {
+    let env = typelang::initial_type_env();
+    let ce = typelang::initial_class_env();
+    let both = support::parse_expr("\\x y -> if x == y then True else x < y");
+    let qual = typelang::infer::infer_qual_type(&env, &ce, &both, false).expect("infer");
+    assert_eq!(qual.constraints.len(), 1);
+    assert_eq!(pretty_qual(&qual), "Ord a => a -> a -> Bool");
+
+    let monoid = support::parse_expr("\\xs -> xs <> mempty");
+    let qual = typelang::infer::infer_qual_type(&env, &ce, &monoid, false).expect("infer");
+    assert_eq!(pretty_qual(&qual), "Monoid a => a -> a");
+
+    let lists = support::parse_expr("[1] <> [2] <> mempty");
+    let qual = typelang::infer::infer_qual_type(&env, &ce, &lists, false).expect("infer");
+    assert_eq!(pretty_qual(&qual), "Num a => [a]");
+
+    let redundant = vec![
+        Constraint {
+            classname: "Num".into(),
+            r#type: Type::TVar(TVar { id: 0 }),
+        },
+        Constraint {
+            classname: "Fractional".into(),
+            r#type: Type::TVar(TVar { id: 0 }),
+        },
+        Constraint {
+            classname: "Fractional".into(),
+            r#type: Type::TVar(TVar { id: 0 }),
+        },
+        Constraint {
+            classname: "Eq".into(),
+            r#type: Type::TVar(TVar { id: 1 }),
+        },
+    ];
+    let reduced = typelang::infer::reduce_constraints(&ce, &redundant);
+    let classes: Vec<&str> = reduced.iter().map(|c| c.classname.as_str()).collect();
+    assert_eq!(classes, vec!["Fractional", "Eq"]);
+}
+
+#[test]
+/// 自己適用が無限型として報告され、型変数と束縛先の型がメッセージに含まれることを確認する。
+fn inference_reports_infinite_type_with_variable() {
+    let err = infer_pretty_qual("\\x -> x x").expect_err("x x は無限型");
+    assert_eq!(err.0.code, "TYPE002");
+    assert!(
+        err.0.msg.contains("無限型を構築できません") && err.0.msg.contains("a = a -> b"),
+        "{}",
+        err.0.msg
+    );
+}
+
 #[test]
 /// 推論失敗ケースをまとめて検証する。
 fn inference_error_cases() {
@@ -138,6 +296,11 @@ fn inference_error_cases() {
         "if True then (1 :: Int) else ('a' :: Char)",
         "if 'a' then 2 else 3",
         "case 1 of x | x -> x; _ -> 0",
+        "fmap not True",
+        "format \"%d\" True",
+        "format \"%s\" 'a'",
+        "format \"%q\"",
+        "\\s -> format s 1",
     ] {
         assert!(
             infer_pretty_qual(src).is_err(),
@@ -146,6 +309,29 @@ fn inference_error_cases() {
     }
 }
 
+#[test]
+/// 上限のない範囲はリストとして作れないため、`take n [x..]` 以外の場所では型エラーになる。
+fn inference_rejects_infinite_range_outside_take() {
+    for src in [
+        "length [1..]",
+        "\\n -> drop n [1..]",
+        "[1..] == [1]",
+        "let take n xs = xs in take 1 [1..]",
+    ] {
+        let err = infer_pretty_qual(src).expect_err(src);
+        assert_eq!(err.0.code, "TYPE068", "{src}");
+    }
+    assert!(infer_pretty_qual("take 2 ['a'..]").is_err());
+}
+
+#[test]
+/// 1 つのパターン内で同じ変数を 2 度束縛すると型エラーになる。
+fn inference_rejects_non_linear_patterns() {
+    let err = infer_pretty_qual("\\p -> case p of (a, a) -> a").unwrap_err();
+    assert_eq!(err.0.code, "TYPE061");
+    assert!(err.0.msg.contains("パターン変数 a"), "{}", err.0.msg);
+}
+
 #[test]
 /// let 多相が評価でも利用できることを検証する。
 fn infer_let_polymorphism_eval_ok() {
@@ -159,6 +345,29 @@ fn infer_let_polymorphism_eval_ok() {
     }
 }
 
+#[test]
+/// let 束縛の一般化点と、制約がタプル・リストへ伝播することを検証する。
+fn infer_let_generalization_cases() {
+    assert_eq!(
+        infer_pretty_qual("let id = \\x -> x in (id 1, id True)").unwrap(),
+        "Num a => (a, Bool)"
+    );
+    assert_eq!(
+        infer_pretty_qual("let f x = x in [f 1, f 2]").unwrap(),
+        "Num a => [a]"
+    );
+    // 局所 let は制約付きでも一般化する（単相性制限なし）。
+    assert_eq!(
+        infer_pretty_qual("let n = 1 in (n + 1, n / 2)").unwrap(),
+        "Fractional b, Num a, Num b => (a, b)"
+    );
+    // 外側のラムダ引数に依存する型変数は量化されない。
+    assert!(
+        infer_pretty_qual("\\f -> let g = f 1 in if g then (g :: Int) else 0").is_err(),
+        "環境で束縛された型変数を一般化してはならない"
+    );
+}
+
 #[test]
 /// 相互再帰する let 束縛が型推論と評価で利用できることを確認する。
 fn infer_and_eval_mutual_recursion_functions() {
@@ -256,6 +256,7 @@ fn bind_performs_occurs_check() {
     });
     let err = bind(tv, ty).expect_err("List of itself should trigger occurs check");
     assert_eq!(err.code, "TYPE002");
+    assert!(err.message.ends_with(": a = List a"), "{}", err.message);
 }
 
 #[test]
@@ -316,3 +317,44 @@ fn pretty_qual_formats_various_type_shapes() {
     };
     assert_eq!(pretty_qual(&app_ty), "Maybe a");
 }
+
+#[test]
+fn pretty_naming_follows_order_of_appearance() {
+    let tv = |id| Type::TVar(TVar { id });
+    let fun = |arg: Type, ret: Type| {
+        Type::TFun(TFun {
+            arg: Box::new(arg),
+            ret: Box::new(ret),
+        })
+    };
+
+    let id_scheme = Scheme {
+        vars: vec![TVar { id: 1000 }],
+        qual: qualify(fun(tv(1000), tv(1000)), vec![]),
+    };
+    assert_eq!(pretty_qual(&id_scheme.qual), "a -> a");
+    assert_eq!(pretty_scheme(&id_scheme), "forall a. a -> a");
+
+    // 制約に先に現れる変数も、本体の型での出現順に名前が付く。
+    let constrained = Scheme {
+        vars: vec![TVar { id: 7 }, TVar { id: 3 }],
+        qual: qualify(
+            fun(tv(3), fun(tv(7), tv(7))),
+            vec![Constraint {
+                classname: "Num".into(),
+                r#type: tv(7),
+            }],
+        ),
+    };
+    assert_eq!(pretty_qual(&constrained.qual), "Num b => a -> b -> b");
+    assert_eq!(
+        pretty_scheme(&constrained),
+        "forall a b. Num b => a -> b -> b"
+    );
+
+    // 26 個を超えると a1, b1, ... と続く。
+    let wide = (0..28).rev().fold(tv(100), |acc, id| fun(tv(id), acc));
+    let rendered = pretty_qual(&qualify(wide, vec![]));
+    assert!(rendered.starts_with("a -> b -> "), "{rendered}");
+    assert!(rendered.ends_with("z -> a1 -> b1 -> c1"), "{rendered}");
+}